@@ -0,0 +1,409 @@
+//! Finds every piece of GML in a game, along with where it is, for tools which look through a game's code.
+
+use crate::gmx::{text, EVENT_NAMES};
+use gm8exe::{
+    asset::{room::Instance, CodeAction, PascalString},
+    AssetList, GameAssets,
+};
+use std::fmt::{self, Display};
+
+/// Where a piece of code is in the game.
+#[derive(Clone, Debug)]
+pub struct Location {
+    /// The kind of asset the code belongs to, such as `script` or `object`.
+    pub kind: &'static str,
+
+    /// The name of the asset, or its index in brackets if it doesn't have one.
+    pub asset: String,
+
+    /// The event, moment or room instance the code belongs to, such as `Step 0` or `Moment 30`.
+    pub event: Option<String>,
+
+    /// Which action in the event or moment the code is in, counting from 1.
+    pub action: Option<usize>,
+
+    /// Which of the action's arguments the code is, counting from 1, if it's an argument rather than a code action.
+    pub argument: Option<usize>,
+}
+
+impl Location {
+    fn new(kind: &'static str, asset: String) -> Self {
+        Location { kind, asset, event: None, action: None, argument: None }
+    }
+
+    fn in_event(self, event: String) -> Self {
+        Location { event: Some(event), ..self }
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.kind, self.asset)?;
+        if let Some(event) = &self.event {
+            write!(f, ", {}", event)?;
+        }
+        if let Some(action) = self.action {
+            write!(f, ", action {}", action)?;
+        }
+        if let Some(argument) = self.argument {
+            write!(f, ", argument {}", argument)?;
+        }
+        Ok(())
+    }
+}
+
+/// Something which looks at a game's code. Actions and room instances can be looked at too, if needed.
+pub trait Visitor {
+    /// Called with each piece of code, such as a script or an "Execute code" action.
+    fn code(&mut self, source: &[u8], location: &Location);
+
+    /// Called with each action argument which is an expression rather than a string.
+    fn expression(&mut self, source: &[u8], location: &Location);
+
+    /// Called with each action, before its code or arguments.
+    fn action(&mut self, _action: &CodeAction, _location: &Location) {}
+
+    /// Called with each instance placed in a room, before its creation code.
+    fn instance(&mut self, _instance: &Instance, _location: &Location) {}
+}
+
+/// Visits all of a game's code, in the order it appears in the game.
+pub fn visit(assets: &GameAssets, visitor: &mut impl Visitor) {
+    for (i, script) in assets.scripts.iter().enumerate() {
+        if let Some(script) = script {
+            visitor.code(&script.source.0, &Location::new("script", asset_name(&script.name, i)));
+        }
+    }
+    for (i, trigger) in assets.triggers.iter().enumerate() {
+        if let Some(trigger) = trigger {
+            visitor.code(&trigger.condition.0, &Location::new("trigger", asset_name(&trigger.name, i)));
+        }
+    }
+    for (i, timeline) in assets.timelines.iter().enumerate() {
+        if let Some(timeline) = timeline {
+            let location = Location::new("timeline", asset_name(&timeline.name, i));
+            for (moment, actions) in &timeline.moments {
+                visit_actions(actions, location.clone().in_event(format!("Moment {}", moment)), visitor);
+            }
+        }
+    }
+    for (i, object) in assets.objects.iter().enumerate() {
+        if let Some(object) = object {
+            let location = Location::new("object", asset_name(&object.name, i));
+            for (ev_type, list) in object.events.iter().enumerate() {
+                let name = EVENT_NAMES.get(ev_type).copied().unwrap_or("Event");
+                for (number, actions) in list {
+                    // Collision events are numbered by the other object, so name that instead
+                    let event = match (name, list_name(&assets.objects, *number as i32, |o| &o.name)) {
+                        ("Collision", Some(other)) => format!("Collision {}", other),
+                        _ => format!("{} {}", name, number),
+                    };
+                    visit_actions(actions, location.clone().in_event(event), visitor);
+                }
+            }
+        }
+    }
+    for (i, room) in assets.rooms.iter().enumerate() {
+        if let Some(room) = room {
+            let location = Location::new("room", asset_name(&room.name, i));
+            visitor.code(&room.creation_code.0, &location.clone().in_event("Creation Code".into()));
+            for instance in &room.instances {
+                let location = location.clone().in_event(format!("Instance {}", instance.id));
+                visitor.instance(instance, &location);
+                visitor.code(&instance.creation_code.0, &location);
+            }
+        }
+    }
+}
+
+/// Visits the actions in an event or moment, treating their code and arguments the same way the runner does.
+fn visit_actions(actions: &[CodeAction], location: Location, visitor: &mut impl Visitor) {
+    for (i, action) in actions.iter().enumerate() {
+        let location = Location { action: Some(i + 1), ..location.clone() };
+        visitor.action(action, &location);
+        match action.action_kind {
+            // Normal actions, whose arguments are expressions unless they're strings
+            0 => {
+                let params = action.param_strings.iter().zip(action.param_types.iter()).take(action.param_count);
+                for (j, (param, kind)) in params.enumerate() {
+                    if !matches!(kind, 1 | 2) {
+                        visitor.expression(&param.0, &Location { argument: Some(j + 1), ..location.clone() });
+                    }
+                }
+            },
+            // Repeat
+            5 => visitor.expression(&action.param_strings[0].0, &Location { argument: Some(1), ..location }),
+            // Set variable, which is compiled as an assignment
+            6 => {
+                let mut code = action.param_strings[0].0.to_vec();
+                code.extend_from_slice(if action.is_relative { b"+=" } else { b"=" });
+                code.extend_from_slice(&action.param_strings[1].0);
+                visitor.code(&code, &location);
+            },
+            // Execute code
+            7 => visitor.code(&action.param_strings[0].0, &location),
+            _ => (),
+        }
+    }
+}
+
+/// The name of an asset, or its index in brackets if it doesn't have one.
+pub(crate) fn asset_name(name: &PascalString, index: usize) -> String {
+    if name.0.is_empty() { format!("[{}]", index) } else { text(name) }
+}
+
+/// The name of the asset at some index in a list, if there is one.
+pub(crate) fn list_name<T>(list: &AssetList<T>, index: i32, name: impl Fn(&T) -> &PascalString) -> Option<String> {
+    let asset = usize::try_from(index).ok().and_then(|i| list.get(i)).and_then(|a| a.as_deref());
+    asset.map(|a| asset_name(name(a), index as usize))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use gm8exe::{
+        asset::{
+            room::{Room, Tile},
+            Background, Object, Script,
+        },
+        settings::{GameHelpDialog, Settings},
+        GameVersion,
+    };
+
+    /// An "Execute code" action.
+    pub(crate) fn code_action(code: &str) -> CodeAction {
+        action(7, "", [1, 0, 0, 0, 0, 0, 0, 0], &[code])
+    }
+
+    /// An action of some kind, with its arguments.
+    pub(crate) fn action(kind: u32, fn_name: &str, param_types: [u32; 8], params: &[&str]) -> CodeAction {
+        let mut param_strings: [PascalString; 8] = Default::default();
+        for (string, param) in param_strings.iter_mut().zip(params) {
+            *string = (*param).into();
+        }
+        CodeAction {
+            id: 603,
+            applies_to: -1,
+            is_condition: false,
+            invert_condition: false,
+            is_relative: false,
+            lib_id: 1,
+            action_kind: kind,
+            execution_type: if fn_name.is_empty() { 2 } else { 1 },
+            can_be_relative: 0,
+            applies_to_something: true,
+            fn_name: fn_name.into(),
+            fn_code: "".into(),
+            param_count: params.len(),
+            param_types,
+            param_strings,
+        }
+    }
+
+    /// An object with the given actions in some events, as (event type, event number, actions).
+    pub(crate) fn object(name: &str, events: Vec<(usize, u32, Vec<CodeAction>)>) -> Option<Box<Object>> {
+        let mut lists = (0..12).map(|_| Vec::new()).collect::<Vec<_>>();
+        for (ev_type, number, actions) in events {
+            lists[ev_type].push((number, actions));
+        }
+        Some(Box::new(Object {
+            name: name.into(),
+            sprite_index: -1,
+            solid: false,
+            visible: true,
+            depth: 0,
+            persistent: false,
+            parent_index: -1,
+            mask_index: -1,
+            events: lists,
+        }))
+    }
+
+    /// A game with some code in each place code can be:
+    /// two scripts, two objects with a few kinds of action between them, and a room with instances of both.
+    pub(crate) fn game() -> GameAssets {
+        let script = |name: &str, source: &str| Some(Box::new(Script { name: name.into(), source: source.into() }));
+        let mut set_hp = action(6, "", [0; 8], &["hp", "-1"]);
+        set_hp.is_relative = true;
+        let mut run_script = action(0, "action_execute_script", [9, 0, 0, 0, 0, 0, 0, 0], &["1", "argument0 * 2"]);
+        run_script.applies_to = 1;
+        let instance = |object, id, code: &str| Instance {
+            x: 0,
+            y: 0,
+            object,
+            id,
+            creation_code: code.into(),
+            xscale: 1.0,
+            yscale: 1.0,
+            blend: 0xFFFFFF,
+            angle: 0.0,
+        };
+        GameAssets {
+            triggers: Vec::new(),
+            constants: Vec::new(),
+            extensions: Vec::new(),
+            sprites: Vec::new(),
+            sounds: Vec::new(),
+            backgrounds: vec![Some(Box::new(Background { name: "bg_sky".into(), width: 1, height: 1, data: None }))],
+            paths: Vec::new(),
+            scripts: vec![
+                script("scr_move", "x += spd\nscr_hurt(1)"),
+                script("scr_hurt", "with (obj_enemy) hp -= argument0\nglobal.score += 1"),
+            ],
+            fonts: Vec::new(),
+            timelines: Vec::new(),
+            objects: vec![
+                object(
+                    "obj_player",
+                    vec![
+                        (0, 0, vec![code_action("var i; i = bg_sky spd = 4\ninstance_create(x, y, 1)")]),
+                        (4, 1, vec![set_hp, run_script]),
+                    ],
+                ),
+                object("obj_enemy", Vec::new()),
+            ],
+            rooms: vec![Some(Box::new(Room {
+                name: "rm_start".into(),
+                caption: "".into(),
+                width: 640,
+                height: 480,
+                speed: 30,
+                persistent: false,
+                bg_colour: 0.into(),
+                clear_screen: true,
+                clear_region: false,
+                creation_code: "global.score = 0".into(),
+                backgrounds: Vec::new(),
+                views_enabled: false,
+                views: Vec::new(),
+                instances: vec![instance(0, 100001, "spd = 2"), instance(1, 100002, "")],
+                tiles: Vec::<Tile>::new(),
+                uses_810_features: false,
+                uses_811_features: false,
+            }))],
+            included_files: Vec::new(),
+            version: GameVersion::GameMaker8_0,
+            dx_dll: Vec::new(),
+            ico_file_raw: None,
+            help_dialog: GameHelpDialog {
+                bg_colour: 0xFFFFFF.into(),
+                new_window: false,
+                caption: "".into(),
+                left: -1,
+                top: -1,
+                width: 600,
+                height: 400,
+                border: true,
+                resizable: true,
+                window_on_top: false,
+                freeze_game: true,
+                info: "".into(),
+            },
+            last_instance_id: 100002,
+            last_tile_id: 10000000,
+            library_init_strings: Vec::new(),
+            room_order: vec![0],
+            settings: settings(),
+            game_id: 1,
+            guid: [0; 4],
+        }
+    }
+
+    fn settings() -> Settings {
+        Settings {
+            fullscreen: false,
+            scaling: 0,
+            interpolate_pixels: false,
+            clear_colour: 0,
+            allow_resize: false,
+            window_on_top: false,
+            dont_draw_border: false,
+            dont_show_buttons: false,
+            display_cursor: true,
+            freeze_on_lose_focus: false,
+            disable_screensaver: true,
+            force_cpu_render: false,
+            set_resolution: false,
+            colour_depth: 0,
+            resolution: 0,
+            frequency: 0,
+            vsync: false,
+            esc_close_game: true,
+            treat_close_as_esc: true,
+            f1_help_menu: true,
+            f4_fullscreen_toggle: true,
+            f5_save_f6_load: true,
+            f9_screenshot: true,
+            priority: 0,
+            custom_load_image: None,
+            transparent: false,
+            translucency: 255,
+            loading_bar: 1,
+            backdata: None,
+            frontdata: None,
+            scale_progress_bar: false,
+            show_error_messages: true,
+            log_errors: false,
+            always_abort: false,
+            zero_uninitialized_vars: false,
+            error_on_uninitialized_args: true,
+            swap_creation_events: false,
+        }
+    }
+
+    /// Records everything it's given, as the location followed by what was there.
+    struct Recorder(Vec<String>);
+
+    impl Visitor for Recorder {
+        fn code(&mut self, source: &[u8], location: &Location) {
+            self.0.push(format!("{}: code {:?}", location, String::from_utf8_lossy(source)));
+        }
+
+        fn expression(&mut self, source: &[u8], location: &Location) {
+            self.0.push(format!("{}: expression {:?}", location, String::from_utf8_lossy(source)));
+        }
+
+        fn action(&mut self, action: &CodeAction, location: &Location) {
+            self.0.push(format!("{}: action {}", location, action.action_kind));
+        }
+
+        fn instance(&mut self, instance: &Instance, location: &Location) {
+            self.0.push(format!("{}: instance of {}", location, instance.object));
+        }
+    }
+
+    #[test]
+    fn visit_everything() {
+        let mut recorder = Recorder(Vec::new());
+        visit(&game(), &mut recorder);
+        assert_eq!(
+            recorder.0,
+            [
+                "script scr_move: code \"x += spd\\nscr_hurt(1)\"",
+                "script scr_hurt: code \"with (obj_enemy) hp -= argument0\\nglobal.score += 1\"",
+                "object obj_player, Create 0, action 1: action 7",
+                "object obj_player, Create 0, action 1: code \"var i; i = bg_sky spd = 4\\ninstance_create(x, y, 1)\"",
+                "object obj_player, Collision obj_enemy, action 1: action 6",
+                "object obj_player, Collision obj_enemy, action 1: code \"hp+=-1\"",
+                "object obj_player, Collision obj_enemy, action 2: action 0",
+                "object obj_player, Collision obj_enemy, action 2, argument 1: expression \"1\"",
+                "object obj_player, Collision obj_enemy, action 2, argument 2: expression \"argument0 * 2\"",
+                "room rm_start, Creation Code: code \"global.score = 0\"",
+                "room rm_start, Instance 100001: instance of 0",
+                "room rm_start, Instance 100001: code \"spd = 2\"",
+                "room rm_start, Instance 100002: instance of 1",
+                "room rm_start, Instance 100002: code \"\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn names() {
+        let game = game();
+        assert_eq!(asset_name(&"obj_player".into(), 0), "obj_player");
+        assert_eq!(asset_name(&"".into(), 3), "[3]");
+        assert_eq!(list_name(&game.objects, 1, |o| &o.name).as_deref(), Some("obj_enemy"));
+        assert_eq!(list_name(&game.objects, 2, |o| &o.name), None);
+        assert_eq!(list_name(&game.objects, -1, |o| &o.name), None);
+    }
+}
@@ -0,0 +1,428 @@
+//! Extracts individual assets from a game into plain files, without building a whole project.
+//!
+//! Sprites are written as horizontal PNG strips with a JSON file for their origin and bounding box, backgrounds as PNG,
+//! sounds in whatever format they were stored in, scripts and object events as `.gml`, and rooms as JSON.
+
+use crate::gmx::{bgra_to_rgba, sanitize, stems, text, write_png, EVENT_NAMES};
+use gm8exe::{
+    asset::{Background, CodeAction, Object, PascalString, Room, Script, Sound, Sprite},
+    AssetList, GameAssets,
+};
+use regex::Regex;
+use serde_json::{json, Value};
+use std::{
+    fmt::{self, Display},
+    fs,
+    io::{self, BufWriter},
+    path::Path,
+    str::FromStr,
+};
+
+/// The kinds of asset which can be extracted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Sprites,
+    Backgrounds,
+    Sounds,
+    Scripts,
+    Objects,
+    Rooms,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 6] =
+        [Kind::Sprites, Kind::Backgrounds, Kind::Sounds, Kind::Scripts, Kind::Objects, Kind::Rooms];
+
+    /// The name of the kind on the command line, which is also the folder it's extracted to.
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Sprites => "sprites",
+            Kind::Backgrounds => "backgrounds",
+            Kind::Sounds => "sounds",
+            Kind::Scripts => "scripts",
+            Kind::Objects => "objects",
+            Kind::Rooms => "rooms",
+        }
+    }
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Kind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Kind::ALL.iter().copied().find(|k| k.name() == s || k.name().trim_end_matches('s') == s).ok_or_else(|| {
+            let names = Kind::ALL.iter().map(|k| k.name()).collect::<Vec<_>>();
+            format!("unknown asset kind '{}' (valid kinds are {})", s, names.join("/"))
+        })
+    }
+}
+
+/// Selects which assets get extracted.
+pub struct Filter {
+    /// The kinds of asset to extract.
+    pub kinds: Vec<Kind>,
+    /// Exact asset names to extract.
+    /// If this and `pattern` are both empty, every asset of the chosen kinds is extracted.
+    pub names: Vec<String>,
+    /// A regex which asset names are searched with.
+    pub pattern: Option<Regex>,
+}
+
+impl Filter {
+    pub fn matches(&self, name: &PascalString) -> bool {
+        let name = text(name);
+        (self.names.is_empty() && self.pattern.is_none())
+            || self.names.contains(&name)
+            || self.pattern.as_ref().is_some_and(|p| p.is_match(&name))
+    }
+}
+
+/// Extracts every asset selected by the filter into a folder per kind under `dir`, returning how many were extracted.
+pub fn extract(assets: &GameAssets, dir: &Path, filter: &Filter) -> io::Result<usize> {
+    let mut count = 0;
+    for &kind in &filter.kinds {
+        let out = dir.join(kind.name());
+        count += match kind {
+            Kind::Sprites => {
+                extract_list(&assets.sprites, |s| &s.name, &out, filter, |s, stem| extract_sprite(s, &out, stem))
+            },
+            Kind::Backgrounds => extract_list(
+                &assets.backgrounds,
+                |b| &b.name,
+                &out,
+                filter,
+                |b, stem| extract_background(b, &out, stem),
+            ),
+            Kind::Sounds => {
+                extract_list(&assets.sounds, |s| &s.name, &out, filter, |s, stem| extract_sound(s, &out, stem))
+            },
+            Kind::Scripts => {
+                extract_list(&assets.scripts, |s| &s.name, &out, filter, |s, stem| extract_script(s, &out, stem))
+            },
+            Kind::Objects => {
+                extract_list(&assets.objects, |o| &o.name, &out, filter, |o, stem| extract_object(o, &out, stem))
+            },
+            Kind::Rooms => {
+                extract_list(&assets.rooms, |r| &r.name, &out, filter, |r, stem| extract_room(r, assets, &out, stem))
+            },
+        }?;
+    }
+    Ok(count)
+}
+
+fn extract_list<T>(
+    list: &AssetList<T>,
+    name: impl Fn(&T) -> &PascalString,
+    dir: &Path,
+    filter: &Filter,
+    extract_one: impl Fn(&T, &str) -> io::Result<()>,
+) -> io::Result<usize> {
+    let mut count = 0;
+    let stems = stems(list.iter().map(|a| a.as_deref().map(&name)), "asset");
+    for (asset, stem) in list.iter().zip(stems) {
+        if let (Some(asset), Some(stem)) = (asset, stem) {
+            if filter.matches(name(asset)) {
+                fs::create_dir_all(dir)?;
+                extract_one(asset, &stem)?;
+                count += 1;
+            }
+        }
+    }
+    Ok(count)
+}
+
+fn write_json(path: &Path, value: &Value) -> io::Result<()> {
+    serde_json::to_writer_pretty(BufWriter::new(fs::File::create(path)?), value).map_err(io::Error::from)
+}
+
+fn extract_sprite(sprite: &Sprite, dir: &Path, stem: &str) -> io::Result<()> {
+    // Frames are all the same size in practice, but make room for the biggest one just in case
+    let width = sprite.frames.iter().map(|f| f.width).max().unwrap_or(0);
+    let height = sprite.frames.iter().map(|f| f.height).max().unwrap_or(0);
+    let strip_width = width * sprite.frames.len() as u32;
+    let mut strip = vec![0u8; strip_width as usize * height as usize * 4];
+    for (i, frame) in sprite.frames.iter().enumerate().filter(|(_, f)| f.width != 0) {
+        let rgba = bgra_to_rgba(&frame.data);
+        for (y, row) in rgba.chunks_exact(frame.width as usize * 4).enumerate() {
+            let start = (y * strip_width as usize + i * width as usize) * 4;
+            strip[start..start + row.len()].copy_from_slice(row);
+        }
+    }
+    write_png(&dir.join(format!("{}.png", stem)), strip_width, height, png::ColorType::RGBA, &strip)?;
+
+    let masks = sprite
+        .colliders
+        .iter()
+        .map(|c| json!({ "left": c.bbox_left, "right": c.bbox_right, "top": c.bbox_top, "bottom": c.bbox_bottom }))
+        .collect::<Vec<_>>();
+    // The union of every mask, for when only one box is wanted
+    let bbox = sprite.colliders.iter().fold(None, |acc: Option<(u32, u32, u32, u32)>, c| {
+        let (l, r, t, b) = acc.unwrap_or((c.bbox_left, c.bbox_right, c.bbox_top, c.bbox_bottom));
+        Some((l.min(c.bbox_left), r.max(c.bbox_right), t.min(c.bbox_top), b.max(c.bbox_bottom)))
+    });
+    let bbox =
+        bbox.map(|(left, right, top, bottom)| json!({ "left": left, "right": right, "top": top, "bottom": bottom }));
+    let meta = json!({
+        "name": text(&sprite.name),
+        "frames": sprite.frames.len(),
+        "width": width,
+        "height": height,
+        "origin_x": sprite.origin_x,
+        "origin_y": sprite.origin_y,
+        "bbox": bbox,
+        "per_frame_masks": sprite.per_frame_colliders,
+        "masks": masks,
+    });
+    write_json(&dir.join(format!("{}.json", stem)), &meta)
+}
+
+fn extract_background(background: &Background, dir: &Path, stem: &str) -> io::Result<()> {
+    if let Some(data) = &background.data {
+        let path = dir.join(format!("{}.png", stem));
+        write_png(&path, background.width, background.height, png::ColorType::RGBA, &bgra_to_rgba(data))?;
+    }
+    Ok(())
+}
+
+fn extract_sound(sound: &Sound, dir: &Path, stem: &str) -> io::Result<()> {
+    match &sound.data {
+        Some(data) => fs::write(dir.join(format!("{}{}", stem, sanitize(&text(&sound.extension)))), data),
+        None => Ok(()),
+    }
+}
+
+fn extract_script(script: &Script, dir: &Path, stem: &str) -> io::Result<()> {
+    fs::write(dir.join(format!("{}.gml", stem)), &script.source.0)
+}
+
+/// Writes the code of every code action in a list, numbering the files after the first.
+fn write_code(actions: &[CodeAction], dir: &Path, stem: &str) -> io::Result<()> {
+    for (i, action) in actions.iter().filter(|a| a.action_kind == 7).enumerate() {
+        let file = match i {
+            0 => format!("{}.gml", stem),
+            n => format!("{}_{}.gml", stem, n),
+        };
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join(file), &action.param_strings[0].0)?;
+    }
+    Ok(())
+}
+
+fn extract_object(object: &Object, dir: &Path, stem: &str) -> io::Result<()> {
+    for (ev_type, list) in object.events.iter().enumerate() {
+        let name = EVENT_NAMES.get(ev_type).copied().unwrap_or("Event");
+        for (number, actions) in list {
+            write_code(actions, &dir.join(stem), &format!("{}_{}", name, number))?;
+        }
+    }
+    Ok(())
+}
+
+fn extract_room(room: &Room, assets: &GameAssets, dir: &Path, stem: &str) -> io::Result<()> {
+    // Asset references are written as names where possible, since indices don't mean much outside the game
+    fn asset_name<T>(list: &AssetList<T>, index: i32, name: impl Fn(&T) -> &PascalString) -> Value {
+        match usize::try_from(index).ok().and_then(|i| list.get(i)).and_then(|a| a.as_deref()) {
+            Some(asset) => text(name(asset)).into(),
+            None => Value::Null,
+        }
+    }
+
+    let backgrounds = room
+        .backgrounds
+        .iter()
+        .map(|b| {
+            json!({
+                "visible": b.visible_on_start,
+                "foreground": b.is_foreground,
+                "background": asset_name(&assets.backgrounds, b.source_bg, |b| &b.name),
+                "x": b.xoffset,
+                "y": b.yoffset,
+                "tile_horizontal": b.tile_horz,
+                "tile_vertical": b.tile_vert,
+                "hspeed": b.hspeed,
+                "vspeed": b.vspeed,
+                "stretch": b.stretch,
+            })
+        })
+        .collect::<Vec<_>>();
+    let views = room
+        .views
+        .iter()
+        .map(|v| {
+            json!({
+                "visible": v.visible,
+                "view": { "x": v.source_x, "y": v.source_y, "w": v.source_w, "h": v.source_h },
+                "port": { "x": v.port_x, "y": v.port_y, "w": v.port_w, "h": v.port_h },
+                "follow": asset_name(&assets.objects, v.following.target, |o| &o.name),
+                "hborder": v.following.hborder,
+                "vborder": v.following.vborder,
+                "hspeed": v.following.hspeed,
+                "vspeed": v.following.vspeed,
+            })
+        })
+        .collect::<Vec<_>>();
+    let instances = room
+        .instances
+        .iter()
+        .map(|i| {
+            json!({
+                "id": i.id,
+                "object": asset_name(&assets.objects, i.object, |o| &o.name),
+                "x": i.x,
+                "y": i.y,
+                "xscale": i.xscale,
+                "yscale": i.yscale,
+                "blend": i.blend,
+                "angle": i.angle,
+                "creation_code": text(&i.creation_code),
+            })
+        })
+        .collect::<Vec<_>>();
+    let tiles = room
+        .tiles
+        .iter()
+        .map(|t| {
+            json!({
+                "id": t.id,
+                "background": asset_name(&assets.backgrounds, t.source_bg, |b| &b.name),
+                "x": t.x,
+                "y": t.y,
+                "tile_x": t.tile_x,
+                "tile_y": t.tile_y,
+                "width": t.width,
+                "height": t.height,
+                "depth": t.depth,
+                "xscale": t.xscale,
+                "yscale": t.yscale,
+                "blend": t.blend,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let room_json = json!({
+        "name": text(&room.name),
+        "caption": text(&room.caption),
+        "width": room.width,
+        "height": room.height,
+        "speed": room.speed,
+        "persistent": room.persistent,
+        "colour": u32::from(room.bg_colour),
+        "clear_screen": room.clear_screen,
+        "clear_region": room.clear_region,
+        "creation_code": text(&room.creation_code),
+        "backgrounds": backgrounds,
+        "views_enabled": room.views_enabled,
+        "views": views,
+        "instances": instances,
+        "tiles": tiles,
+    });
+    write_json(&dir.join(format!("{}.json", stem)), &room_json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gm8exe::asset::sprite::{CollisionMap, Frame};
+    use std::path::PathBuf;
+
+    /// A fresh, empty folder for a test to extract into.
+    fn scratch(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gm8decompiler-extract-{}-{}", std::process::id(), test));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn filter(names: &[&str], pattern: Option<&str>) -> Filter {
+        Filter {
+            kinds: Kind::ALL.to_vec(),
+            names: names.iter().map(|n| n.to_string()).collect(),
+            pattern: pattern.map(|p| Regex::new(p).unwrap()),
+        }
+    }
+
+    fn script(name: &str, source: &str) -> Option<Box<Script>> {
+        Some(Box::new(Script { name: name.into(), source: source.into() }))
+    }
+
+    #[test]
+    fn kind_names() {
+        assert_eq!("sprites".parse::<Kind>(), Ok(Kind::Sprites));
+        assert_eq!("room".parse::<Kind>(), Ok(Kind::Rooms));
+        assert!("fonts".parse::<Kind>().is_err());
+    }
+
+    #[test]
+    fn filter_matches() {
+        assert!(filter(&[], None).matches(&"anything".into()));
+
+        let names = filter(&["spr_player"], None);
+        assert!(names.matches(&"spr_player".into()));
+        assert!(!names.matches(&"spr_player2".into()));
+
+        let both = filter(&["obj_wall"], Some("^spr_"));
+        assert!(both.matches(&"spr_player".into()));
+        assert!(both.matches(&"obj_wall".into()));
+        assert!(!both.matches(&"obj_player".into()));
+    }
+
+    #[test]
+    fn extract_filtered_scripts() {
+        let dir = scratch("scripts");
+        let scripts = vec![script("scr_move", "x += 1"), None, script("scr_jump", "vspeed = -8"), script("init", "")];
+        let count = extract_list(&scripts, |s| &s.name, &dir, &filter(&[], Some("^scr_")), |s, stem| {
+            extract_script(s, &dir, stem)
+        })
+        .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(fs::read_to_string(dir.join("scr_move.gml")).unwrap(), "x += 1");
+        assert_eq!(fs::read_to_string(dir.join("scr_jump.gml")).unwrap(), "vspeed = -8");
+        assert!(!dir.join("init.gml").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn extract_sprite_strip() {
+        let dir = scratch("sprite");
+        let frame = |b: u8| Frame { width: 2, height: 1, data: vec![b, 0, 0, 255, 0, 0, b, 255].into() };
+        let collider = |left, right| CollisionMap {
+            width: 2,
+            height: 1,
+            bbox_left: left,
+            bbox_right: right,
+            bbox_top: 0,
+            bbox_bottom: 0,
+            data: vec![true, true].into(),
+        };
+        let sprite = Sprite {
+            name: "spr_test".into(),
+            origin_x: 1,
+            origin_y: 0,
+            frames: vec![frame(10), frame(20)],
+            colliders: vec![collider(1, 1), collider(0, 0)],
+            per_frame_colliders: true,
+        };
+        extract_sprite(&sprite, &dir, "spr_test").unwrap();
+
+        let decoder = png::Decoder::new(fs::File::open(dir.join("spr_test.png")).unwrap());
+        let (info, mut reader) = decoder.read_info().unwrap();
+        assert_eq!((info.width, info.height), (4, 1));
+        let mut pixels = vec![0; info.buffer_size()];
+        reader.next_frame(&mut pixels).unwrap();
+        // BGRA frames come out as RGBA, side by side
+        assert_eq!(pixels, [0, 0, 10, 255, 10, 0, 0, 255, 0, 0, 20, 255, 20, 0, 0, 255]);
+
+        let meta: Value = serde_json::from_slice(&fs::read(dir.join("spr_test.json")).unwrap()).unwrap();
+        assert_eq!(meta["frames"], 2);
+        assert_eq!(meta["origin_x"], 1);
+        assert_eq!(meta["bbox"], json!({ "left": 0, "right": 1, "top": 0, "bottom": 0 }));
+        assert_eq!(meta["masks"].as_array().unwrap().len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -3,11 +3,15 @@ use byteorder::{WriteBytesExt, LE};
 use flate2::{write::ZlibEncoder, Compression};
 use gm8exe::{
     asset::{self, included_file::ExportSetting, PascalString, WritePascalString},
+    gamedata::gm70,
     settings::{GameHelpDialog, Settings},
     GameAssets, GameVersion,
 };
 use rayon::prelude::*;
-use std::{io, u32};
+use std::{
+    io::{self, Write},
+    u32,
+};
 
 pub trait WriteBuffer: io::Write {
     fn write_buffer(&mut self, buf: &[u8]) -> io::Result<usize> {
@@ -17,17 +21,87 @@ pub trait WriteBuffer: io::Write {
 }
 impl<W> WriteBuffer for W where W: io::Write {}
 
+/// Output stream for a GMK file.
+/// GM7 project files are encrypted from just after the seed in the header, other versions are written as-is.
+pub struct GmkWriter<W: io::Write> {
+    inner: W,
+    table: Option<[u8; 256]>,
+    pos: u8,
+}
+
+impl<W: io::Write> GmkWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner, table: None, pos: 0 }
+    }
+
+    /// Encrypts everything written from now on, using GM7's substitution table for the given seed.
+    pub fn encrypt(&mut self, seed: u32) {
+        self.table = Some(gm70::seed_tables(seed).0);
+        self.pos = 0;
+    }
+}
+
+impl<W: io::Write> io::Write for GmkWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &self.table {
+            Some(table) => {
+                // the inverse of gm70::decrypt: offset each byte by its position, then substitute it
+                let mut pos = self.pos;
+                let encrypted = buf
+                    .iter()
+                    .map(|b| {
+                        let byte = table[usize::from(b.wrapping_add(pos))];
+                        pos = pos.wrapping_add(1);
+                        byte
+                    })
+                    .collect::<Vec<_>>();
+                self.inner.write_all(&encrypted)?;
+                self.pos = pos;
+                Ok(buf.len())
+            },
+            None => self.inner.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// Any seed will do for encrypting GM7 project files, as it's stored in the header
+const GM7_SEED: u32 = 1;
+
 // Writes GMK file header
-pub fn write_header<W>(writer: &mut W, version: GameVersion, game_id: u32, guid: [u32; 4]) -> io::Result<()>
+pub fn write_header<W>(
+    writer: &mut GmkWriter<W>,
+    version: GameVersion,
+    game_id: u32,
+    guid: [u32; 4],
+) -> io::Result<()>
 where
     W: io::Write,
 {
     writer.write_u32::<LE>(1234321)?;
     writer.write_u32::<LE>(match version {
+        GameVersion::GameMaker5_0 | GameVersion::GameMaker6_0 => 600,
+        GameVersion::GameMaker7_0 => 701,
         GameVersion::GameMaker8_0 => 800,
         GameVersion::GameMaker8_1 => 810,
     })?;
-    writer.write_u32::<LE>(game_id)?;
+    if version == GameVersion::GameMaker7_0 {
+        // No garbage either side of the seed, which is the only part GM7 cares about
+        writer.write_u32::<LE>(0)?;
+        writer.write_u32::<LE>(0)?;
+        writer.write_u32::<LE>(GM7_SEED)?;
+
+        // Only the first byte of the game ID is left unencrypted
+        let id = game_id.to_le_bytes();
+        writer.write_all(&id[..1])?;
+        writer.encrypt(GM7_SEED);
+        writer.write_all(&id[1..])?;
+    } else {
+        writer.write_u32::<LE>(game_id)?;
+    }
     for n in &guid {
         writer.write_u32::<LE>(*n)?;
     }
@@ -43,18 +117,70 @@ where
     writer.write_u64::<LE>(0)
 }
 
+// Write an asset's last-changed timestamp, which GM8 added
+#[inline]
+fn write_asset_timestamp<W>(writer: &mut W, version: GameVersion) -> io::Result<()>
+where
+    W: io::Write,
+{
+    if version.is_legacy() { Ok(()) } else { write_timestamp(writer) }
+}
+
+// Writes a 32-bit BMP file, compressed the way GM6 and GM7 store images
+fn write_zlib_bitmap<W>(writer: &mut W, width: u32, height: u32, bgra: &[u8]) -> io::Result<()>
+where
+    W: io::Write,
+{
+    let pixel_len = width * height * 4;
+    let mut enc = ZlibWriter::new();
+    enc.write_all(b"BM")?;
+    enc.write_u32::<LE>(54 + pixel_len)?; // file size
+    enc.write_u32::<LE>(0)?; // reserved
+    enc.write_u32::<LE>(54)?; // pixel data offset
+    enc.write_u32::<LE>(40)?; // info header size
+    enc.write_u32::<LE>(width)?;
+    enc.write_u32::<LE>(height)?; // positive height - rows are stored bottom-up
+    enc.write_u16::<LE>(1)?; // planes
+    enc.write_u16::<LE>(32)?; // bits per pixel
+    enc.write_u32::<LE>(0)?; // compression - none
+    enc.write_u32::<LE>(pixel_len)?;
+    enc.write_u32::<LE>(0)?; // horizontal resolution
+    enc.write_u32::<LE>(0)?; // vertical resolution
+    enc.write_u32::<LE>(0)?; // palette size
+    enc.write_u32::<LE>(0)?; // important colours
+    if width != 0 {
+        for row in bgra.chunks_exact(width as usize * 4).rev() {
+            enc.write_all(row)?;
+        }
+    }
+    enc.finish(writer)
+}
+
+// Whether an image was made transparent using its bottom-left pixel, as GM6 and GM7 do
+fn bottom_left_transparent(width: u32, height: u32, bgra: &[u8]) -> bool {
+    let alpha = ((height as usize).saturating_sub(1) * width as usize) * 4 + 3;
+    bgra.get(alpha).map(|a| *a == 0).unwrap_or(false)
+}
+
 // Writes a settings block to GMK
+// Before GM8, constants and included file names were part of the settings.
 pub fn write_settings<W>(
     writer: &mut W,
     settings: &Settings,
     ico_file: Option<Vec<u8>>,
+    constants: &[asset::Constant],
+    included_files: &[asset::IncludedFile],
     version: GameVersion,
 ) -> io::Result<()>
 where
     W: io::Write,
 {
-    writer.write_u32::<LE>(800)?;
-    let mut enc = ZlibWriter::new();
+    writer.write_u32::<LE>(match version {
+        GameVersion::GameMaker5_0 | GameVersion::GameMaker6_0 => 600,
+        GameVersion::GameMaker7_0 => 702,
+        GameVersion::GameMaker8_0 | GameVersion::GameMaker8_1 => 800,
+    })?;
+    let mut enc = Vec::new();
     enc.write_u32::<LE>(settings.fullscreen as u32)?;
     enc.write_u32::<LE>(settings.interpolate_pixels as u32)?;
     enc.write_u32::<LE>(settings.dont_draw_border as u32)?;
@@ -69,18 +195,22 @@ where
     enc.write_u32::<LE>(settings.frequency)?;
     enc.write_u32::<LE>(settings.dont_show_buttons as u32)?;
     match version {
-        GameVersion::GameMaker8_0 => enc.write_u32::<LE>(settings.vsync as u32)?,
         GameVersion::GameMaker8_1 => {
             enc.write_u32::<LE>(((settings.force_cpu_render as u32) << 7) | (settings.vsync as u32))?
         },
+        _ => enc.write_u32::<LE>(settings.vsync as u32)?,
     };
-    enc.write_u32::<LE>(settings.disable_screensaver as u32)?;
+    if !version.is_legacy() {
+        enc.write_u32::<LE>(settings.disable_screensaver as u32)?;
+    }
     enc.write_u32::<LE>(settings.f4_fullscreen_toggle as u32)?;
     enc.write_u32::<LE>(settings.f1_help_menu as u32)?;
     enc.write_u32::<LE>(settings.esc_close_game as u32)?;
     enc.write_u32::<LE>(settings.f5_save_f6_load as u32)?;
-    enc.write_u32::<LE>(settings.f9_screenshot as u32)?;
-    enc.write_u32::<LE>(settings.treat_close_as_esc as u32)?;
+    if version != GameVersion::GameMaker6_0 {
+        enc.write_u32::<LE>(settings.f9_screenshot as u32)?;
+        enc.write_u32::<LE>(settings.treat_close_as_esc as u32)?;
+    }
     enc.write_u32::<LE>(settings.priority)?;
     enc.write_u32::<LE>(settings.freeze_on_lose_focus as u32)?;
 
@@ -133,21 +263,29 @@ where
     enc.write_u32::<LE>(settings.translucency)?;
     enc.write_u32::<LE>(settings.scale_progress_bar as u32)?;
 
-    if let Some(ico) = ico_file {
-        enc.write_u32::<LE>(ico.len() as u32)?;
-        enc.write_buffer(&ico)?;
-    } else {
-        enc.write_u32::<LE>(0)?;
+    match ico_file {
+        Some(ico) if version.is_legacy() => {
+            let mut ico_enc = ZlibWriter::new();
+            ico_enc.write_buffer(&ico)?;
+            ico_enc.finish(&mut enc)?;
+        },
+        Some(ico) => {
+            enc.write_u32::<LE>(ico.len() as u32)?;
+            enc.write_buffer(&ico)?;
+        },
+        None => {
+            enc.write_u32::<LE>(0)?;
+        },
     }
 
     enc.write_u32::<LE>(settings.show_error_messages as u32)?;
     enc.write_u32::<LE>(settings.log_errors as u32)?;
     enc.write_u32::<LE>(settings.always_abort as u32)?;
     match version {
-        GameVersion::GameMaker8_0 => enc.write_u32::<LE>(settings.zero_uninitialized_vars as u32)?,
         GameVersion::GameMaker8_1 => enc.write_u32::<LE>(
             ((settings.error_on_uninitialized_args as u32) << 1) | (settings.zero_uninitialized_vars as u32),
         )?,
+        _ => enc.write_u32::<LE>(settings.zero_uninitialized_vars as u32)?,
     };
 
     enc.write_pas_string(&"decompiler clan :police_car: :police_car: :police_car:".into())?; // author
@@ -155,6 +293,27 @@ where
     write_timestamp(&mut enc)?; // timestamp
     enc.write_pas_string(&"".into())?; // information
 
+    if version.is_legacy() {
+        enc.write_u32::<LE>(constants.len() as u32)?;
+        for constant in constants {
+            enc.write_pas_string(&constant.name)?;
+            enc.write_pas_string(&constant.expression)?;
+        }
+    }
+    if version == GameVersion::GameMaker6_0 {
+        writer.write_buffer(&enc)?;
+        return Ok(())
+    }
+    if version == GameVersion::GameMaker7_0 {
+        enc.write_u32::<LE>(included_files.len() as u32)?;
+        for file in included_files {
+            enc.write_pas_string(&file.file_name)?;
+        }
+        enc.write_u32::<LE>(0)?; // export folder - 0 = game folder
+        enc.write_u32::<LE>(included_files.iter().any(|f| f.overwrite_file) as u32)?;
+        enc.write_u32::<LE>(included_files.iter().any(|f| f.remove_at_end) as u32)?;
+    }
+
     // TODO: extract all this stuff from .rsrc in gm8x
     enc.write_u32::<LE>(1)?; // major version
     enc.write_u32::<LE>(0)?; // minor version
@@ -164,66 +323,73 @@ where
     enc.write_pas_string(&"".into())?; // product
     enc.write_pas_string(&"".into())?; // copyright info
     enc.write_pas_string(&"".into())?; // description
-    write_timestamp(&mut enc)?; // timestamp
 
-    enc.finish(writer)?;
+    if version.is_legacy() {
+        writer.write_buffer(&enc)?;
+    } else {
+        write_timestamp(&mut enc)?; // timestamp
+        let mut zlib = ZlibWriter::new();
+        zlib.write_buffer(&enc)?;
+        zlib.finish(writer)?;
+    }
 
     Ok(())
 }
 
 // Helper fn - takes a set of assets from an iterator and passes them to the write function for that asset
+// GM8 compresses each asset separately, while earlier versions write them out directly after a different header.
 pub fn write_asset_list<W, T, F>(
     writer: &mut W,
     list: &[Option<Box<T>>],
     write_fn: F,
+    legacy_header: u32,
     version: GameVersion,
     multithread: bool,
 ) -> io::Result<()>
 where
     T: Send + Sync,
     W: io::Write,
-    F: Fn(&mut ZlibEncoder<Vec<u8>>, &T, GameVersion) -> io::Result<()> + Send + Sync,
+    F: Fn(&mut Vec<u8>, &T, GameVersion) -> io::Result<()> + Send + Sync,
 {
-    writer.write_u32::<LE>(800)?;
+    writer.write_u32::<LE>(if version.is_legacy() { legacy_header } else { 800 })?;
     writer.write_u32::<LE>(list.len() as u32)?;
 
+    let write_one = |asset: &Option<Box<T>>| -> io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        match asset {
+            Some(asset) => {
+                buf.write_u32::<LE>(true as u32)?;
+                write_fn(&mut buf, asset, version)?;
+            },
+            None => {
+                buf.write_u32::<LE>(false as u32)?;
+            },
+        }
+        if version.is_legacy() {
+            Ok(buf)
+        } else {
+            let mut enc = ZlibEncoder::new(Vec::new(), Compression::default());
+            enc.write_all(&buf)?;
+            enc.finish()
+        }
+    };
+    let write_out = |writer: &mut W, buf: Vec<u8>| -> io::Result<()> {
+        if !version.is_legacy() {
+            writer.write_u32::<LE>(buf.len().try_into().unwrap())?;
+        }
+        writer.write_buffer(&buf)?;
+        Ok(())
+    };
+
     if multithread {
         list.par_iter()
-            .map(|asset| {
-                let mut enc = ZlibEncoder::new(Vec::new(), Compression::default());
-                match asset {
-                    Some(asset) => {
-                        enc.write_u32::<LE>(true as u32)?;
-                        write_fn(&mut enc, asset, version)?;
-                    },
-                    None => {
-                        enc.write_u32::<LE>(false as u32)?;
-                    },
-                }
-                enc.finish()
-            })
+            .map(write_one)
             .collect::<Result<Vec<_>, io::Error>>()?
             .into_iter()
-            .try_fold((), |_, enc| {
-                writer.write_u32::<LE>(enc.len().try_into().unwrap())?;
-                writer.write_buffer(&enc)?;
-                Ok(())
-            })
+            .try_fold((), |_, buf| write_out(writer, buf))
     } else {
         for asset in list {
-            let mut enc = ZlibEncoder::new(Vec::new(), Compression::default());
-            match asset {
-                Some(asset) => {
-                    enc.write_u32::<LE>(true as u32)?;
-                    write_fn(&mut enc, asset, version)?;
-                },
-                None => {
-                    enc.write_u32::<LE>(false as u32)?;
-                },
-            }
-            let buf = enc.finish()?;
-            writer.write_u32::<LE>(buf.len() as u32)?;
-            writer.write_buffer(&buf)?;
+            write_out(writer, write_one(asset)?)?;
         }
         Ok(())
     }
@@ -259,17 +425,23 @@ where
 }
 
 // Writes a Sound (uncompressed data)
-pub fn write_sound<W>(writer: &mut W, sound: &asset::Sound, _version: GameVersion) -> io::Result<()>
+pub fn write_sound<W>(writer: &mut W, sound: &asset::Sound, version: GameVersion) -> io::Result<()>
 where
     W: io::Write,
 {
     writer.write_pas_string(&sound.name)?;
-    write_timestamp(writer)?;
-    writer.write_u32::<LE>(800)?;
+    write_asset_timestamp(writer, version)?;
+    writer.write_u32::<LE>(if version.is_legacy() { 600 } else { 800 })?;
     writer.write_u32::<LE>(sound.kind as u32)?;
     writer.write_pas_string(&sound.extension)?;
     writer.write_pas_string(&sound.source)?;
     match &sound.data {
+        Some(data) if version.is_legacy() => {
+            writer.write_u32::<LE>(true as u32)?;
+            let mut enc = ZlibWriter::new();
+            enc.write_buffer(data)?;
+            enc.finish(&mut *writer)?;
+        },
         Some(data) => {
             writer.write_u32::<LE>(true as u32)?;
             writer.write_u32::<LE>(data.len() as u32)?;
@@ -294,12 +466,15 @@ where
 }
 
 // Writes a Sprite (uncompressed data)
-pub fn write_sprite<W>(writer: &mut W, sprite: &asset::Sprite, _version: GameVersion) -> io::Result<()>
+pub fn write_sprite<W>(writer: &mut W, sprite: &asset::Sprite, version: GameVersion) -> io::Result<()>
 where
     W: io::Write,
 {
     let gmk_collision = collision::resolve_map(sprite);
     writer.write_pas_string(&sprite.name)?;
+    if version.is_legacy() {
+        return write_legacy_sprite(writer, sprite, gmk_collision)
+    }
     write_timestamp(writer)?;
     writer.write_u32::<LE>(800)?;
     writer.write_i32::<LE>(sprite.origin_x)?;
@@ -341,14 +516,72 @@ where
     Ok(())
 }
 
+// Writes the body of a Sprite in the format used before GM8, with bitmap frames
+fn write_legacy_sprite<W>(
+    writer: &mut W,
+    sprite: &asset::Sprite,
+    gmk_collision: Option<collision::GmkCollision>,
+) -> io::Result<()>
+where
+    W: io::Write,
+{
+    let (width, height) = sprite.frames.first().map(|f| (f.width, f.height)).unwrap_or((0, 0));
+    writer.write_u32::<LE>(542)?;
+    writer.write_u32::<LE>(width)?;
+    writer.write_u32::<LE>(height)?;
+    match &gmk_collision {
+        Some(map) => {
+            writer.write_u32::<LE>(map.bbox_left)?;
+            writer.write_u32::<LE>(map.bbox_right)?;
+            writer.write_u32::<LE>(map.bbox_bottom)?;
+            writer.write_u32::<LE>(map.bbox_top)?;
+        },
+        None => {
+            if !sprite.frames.is_empty() {
+                println!("WARNING: couldn't resolve collision for sprite {}", sprite.name);
+            }
+            writer.write_u32::<LE>(0)?; // bbox left
+            writer.write_u32::<LE>(width.saturating_sub(1))?; // bbox right
+            writer.write_u32::<LE>(height.saturating_sub(1))?; // bbox bottom
+            writer.write_u32::<LE>(0)?; // bbox top
+        },
+    }
+    let transparent = sprite.frames.first().map(|f| bottom_left_transparent(f.width, f.height, &f.data));
+    writer.write_u32::<LE>(transparent.unwrap_or(false) as u32)?;
+    writer.write_u32::<LE>(false as u32)?; // smooth edges
+    writer.write_u32::<LE>(true as u32)?; // preload texture
+    writer.write_u32::<LE>(2)?; // bounding box type - 2 = manual
+    let precise = gmk_collision.map(|map| matches!(map.shape, collision::Shape::Precise)).unwrap_or(true);
+    writer.write_u32::<LE>(precise as u32)?; // precise collision
+    writer.write_i32::<LE>(sprite.origin_x)?;
+    writer.write_i32::<LE>(sprite.origin_y)?;
+    writer.write_u32::<LE>(sprite.frames.len() as u32)?;
+    for frame in &sprite.frames {
+        writer.write_i32::<LE>(10)?; // -1 would mean there's no image
+        write_zlib_bitmap(writer, frame.width, frame.height, &frame.data)?;
+    }
+    Ok(())
+}
+
 // Writes a Background (uncompressed data)
-pub fn write_background<W>(writer: &mut W, background: &asset::Background, _version: GameVersion) -> io::Result<()>
+pub fn write_background<W>(writer: &mut W, background: &asset::Background, version: GameVersion) -> io::Result<()>
 where
     W: io::Write,
 {
     writer.write_pas_string(&background.name)?;
-    write_timestamp(writer)?;
-    writer.write_u32::<LE>(710)?;
+    if version.is_legacy() {
+        writer.write_u32::<LE>(543)?;
+        writer.write_u32::<LE>(background.width)?;
+        writer.write_u32::<LE>(background.height)?;
+        let transparent =
+            background.data.as_ref().map(|d| bottom_left_transparent(background.width, background.height, d));
+        writer.write_u32::<LE>(transparent.unwrap_or(false) as u32)?;
+        writer.write_u32::<LE>(false as u32)?; // smooth edges
+        writer.write_u32::<LE>(true as u32)?; // preload texture
+    } else {
+        write_timestamp(writer)?;
+        writer.write_u32::<LE>(710)?;
+    }
 
     // Tileset info isn't in exe - not sure if there's a consistent way to reverse it...
     writer.write_u32::<LE>(false as u32)?; // is tileset
@@ -359,6 +592,20 @@ where
     writer.write_u32::<LE>(0)?; // H sep
     writer.write_u32::<LE>(0)?; // V sep
 
+    if version.is_legacy() {
+        match &background.data {
+            Some(data) if background.width * background.height != 0 => {
+                writer.write_u32::<LE>(true as u32)?;
+                writer.write_i32::<LE>(10)?; // -1 would mean there's no image
+                write_zlib_bitmap(writer, background.width, background.height, data)?;
+            },
+            _ => {
+                writer.write_u32::<LE>(false as u32)?;
+            },
+        }
+        return Ok(())
+    }
+
     writer.write_u32::<LE>(800)?;
     writer.write_u32::<LE>(background.width)?;
     writer.write_u32::<LE>(background.height)?;
@@ -374,12 +621,12 @@ where
 }
 
 // Writes a Path (uncompressed data)
-pub fn write_path<W>(writer: &mut W, path: &asset::Path, _version: GameVersion) -> io::Result<()>
+pub fn write_path<W>(writer: &mut W, path: &asset::Path, version: GameVersion) -> io::Result<()>
 where
     W: io::Write,
 {
     writer.write_pas_string(&path.name)?;
-    write_timestamp(writer)?;
+    write_asset_timestamp(writer, version)?;
     writer.write_u32::<LE>(530)?;
     writer.write_u32::<LE>(path.connection as u32)?;
     writer.write_u32::<LE>(path.closed as u32)?;
@@ -397,13 +644,13 @@ where
 }
 
 // Writes a Script (uncompressed data)
-pub fn write_script<W>(writer: &mut W, script: &asset::Script, _version: GameVersion) -> io::Result<()>
+pub fn write_script<W>(writer: &mut W, script: &asset::Script, version: GameVersion) -> io::Result<()>
 where
     W: io::Write,
 {
     writer.write_pas_string(&script.name)?;
-    write_timestamp(writer)?;
-    writer.write_u32::<LE>(800)?;
+    write_asset_timestamp(writer, version)?;
+    writer.write_u32::<LE>(if version.is_legacy() { 400 } else { 800 })?;
     writer.write_pas_string(&script.source)?;
     Ok(())
 }
@@ -414,17 +661,17 @@ where
     W: io::Write,
 {
     writer.write_pas_string(&font.name)?;
-    write_timestamp(writer)?;
-    writer.write_u32::<LE>(800)?;
+    write_asset_timestamp(writer, version)?;
+    writer.write_u32::<LE>(if version.is_legacy() { 540 } else { 800 })?;
     writer.write_pas_string(&font.sys_name)?;
     writer.write_u32::<LE>(font.size)?;
     writer.write_u32::<LE>(font.bold as u32)?;
     writer.write_u32::<LE>(font.italic as u32)?;
     match version {
-        GameVersion::GameMaker8_0 => writer.write_u32::<LE>(font.range_start)?,
         GameVersion::GameMaker8_1 => writer.write_u32::<LE>(
             ((font.aa_level & 0xFF) << 24) | ((font.charset & 0xFF) << 16) | (font.range_start & 0xFFFF),
         )?,
+        _ => writer.write_u32::<LE>(font.range_start)?,
     };
     writer.write_u32::<LE>(font.range_end)?;
     Ok(())
@@ -462,12 +709,12 @@ where
 }
 
 // Writes a Timeline (uncompressed data)
-pub fn write_timeline<W>(writer: &mut W, timeline: &asset::Timeline, _version: GameVersion) -> io::Result<()>
+pub fn write_timeline<W>(writer: &mut W, timeline: &asset::Timeline, version: GameVersion) -> io::Result<()>
 where
     W: io::Write,
 {
     writer.write_pas_string(&timeline.name)?;
-    write_timestamp(writer)?;
+    write_asset_timestamp(writer, version)?;
     writer.write_u32::<LE>(500)?;
     writer.write_u32::<LE>(timeline.moments.len() as u32)?;
     for (moment, actions) in &timeline.moments {
@@ -482,12 +729,12 @@ where
 }
 
 // Writes an Object (uncompressed data)
-pub fn write_object<W>(writer: &mut W, object: &asset::Object, _version: GameVersion) -> io::Result<()>
+pub fn write_object<W>(writer: &mut W, object: &asset::Object, version: GameVersion) -> io::Result<()>
 where
     W: io::Write,
 {
     writer.write_pas_string(&object.name)?;
-    write_timestamp(writer)?;
+    write_asset_timestamp(writer, version)?;
     writer.write_u32::<LE>(430)?;
     writer.write_i32::<LE>(object.sprite_index)?;
     writer.write_u32::<LE>(object.solid as u32)?;
@@ -512,12 +759,12 @@ where
 }
 
 // Writes an Room (uncompressed data)
-pub fn write_room<W>(writer: &mut W, room: &asset::Room, _: GameVersion) -> io::Result<()>
+pub fn write_room<W>(writer: &mut W, room: &asset::Room, version: GameVersion) -> io::Result<()>
 where
     W: io::Write,
 {
     writer.write_pas_string(&room.name)?;
-    write_timestamp(writer)?;
+    write_asset_timestamp(writer, version)?;
     writer.write_u32::<LE>(541)?;
     writer.write_pas_string(&room.caption)?;
     writer.write_u32::<LE>(room.width)?;
@@ -607,7 +854,9 @@ where
         } else {
             writer.write_pas_string(&instance.creation_code)?;
         }
-        writer.write_u32::<LE>(false as u32)?; // locked in editor
+        if !version.is_legacy() {
+            writer.write_u32::<LE>(false as u32)?; // locked in editor
+        }
     }
 
     writer.write_u32::<LE>(room.tiles.len() as u32)?;
@@ -621,7 +870,9 @@ where
         writer.write_u32::<LE>(tile.height)?;
         writer.write_i32::<LE>(tile.depth)?;
         writer.write_i32::<LE>(tile.id)?;
-        writer.write_u32::<LE>(false as u32)?; // locked in editor
+        if !version.is_legacy() {
+            writer.write_u32::<LE>(false as u32)?; // locked in editor
+        }
     }
 
     // All these settings are 0/false by default when creating a new room in the IDE.
@@ -715,13 +966,12 @@ where
 }
 
 // Write game information (help dialog) block to GMK
-pub fn write_game_information<W>(writer: &mut W, info: &GameHelpDialog) -> io::Result<()>
+pub fn write_game_information<W>(writer: &mut W, info: &GameHelpDialog, version: GameVersion) -> io::Result<()>
 where
     W: io::Write,
 {
-    writer.write_u32::<LE>(800)?; // TODO: why is this hardcoded?? come on adam
-    // maybe others are too ?
-    let mut enc = ZlibWriter::new();
+    writer.write_u32::<LE>(if version.is_legacy() { 600 } else { 800 })?;
+    let mut enc = Vec::new();
     enc.write_u32::<LE>(info.bg_colour.into())?;
     enc.write_u32::<LE>(info.new_window as u32)?;
     enc.write_pas_string(&info.caption)?;
@@ -733,9 +983,16 @@ where
     enc.write_u32::<LE>(info.resizable as u32)?;
     enc.write_u32::<LE>(info.window_on_top as u32)?;
     enc.write_u32::<LE>(info.freeze_game as u32)?;
-    write_timestamp(&mut enc)?;
-    enc.write_pas_string(&info.info)?;
-    enc.finish(writer)?;
+    if version.is_legacy() {
+        enc.write_pas_string(&info.info)?;
+        writer.write_buffer(&enc)?;
+    } else {
+        write_timestamp(&mut enc)?;
+        enc.write_pas_string(&info.info)?;
+        let mut zlib = ZlibWriter::new();
+        zlib.write_buffer(&enc)?;
+        zlib.finish(writer)?;
+    }
     Ok(())
 }
 
@@ -854,6 +1111,282 @@ where
     }
     write_rt_asset(writer, &"Game Information".into(), 10, 0)?;
     write_rt_asset(writer, &"Global Game Settings".into(), 11, 0)?;
-    write_rt_asset(writer, &"Extension Packages".into(), 13, 0)?;
+    if assets.version != GameVersion::GameMaker6_0 {
+        write_rt_asset(writer, &"Extension Packages".into(), 13, 0)?;
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use byteorder::ReadBytesExt;
+    use gm8exe::asset::{
+        path::{ConnectionKind, Point},
+        sound::SoundFX,
+        sprite::{CollisionMap, Frame},
+        CodeAction, Constant, Extension, Font, Object, Script, Sound, SoundKind, Sprite,
+    };
+
+    // Writes a whole project file, the same way the decompiler does
+    fn write_gmk(assets: &GameAssets) -> Vec<u8> {
+        let mut out = Vec::new();
+        let gmk = &mut GmkWriter::new(&mut out);
+        let version = assets.version;
+        write_header(gmk, version, assets.game_id, assets.guid).unwrap();
+        write_settings(gmk, &assets.settings, assets.ico_file_raw.clone(), &[], &[], version).unwrap();
+        write_asset_list(gmk, &assets.triggers, write_trigger, 800, version, false).unwrap();
+        write_timestamp(gmk).unwrap();
+        write_constants(gmk, &assets.constants).unwrap();
+        write_asset_list(gmk, &assets.sounds, write_sound, 400, version, false).unwrap();
+        write_asset_list(gmk, &assets.sprites, write_sprite, 400, version, false).unwrap();
+        write_asset_list(gmk, &assets.backgrounds, write_background, 400, version, false).unwrap();
+        write_asset_list(gmk, &assets.paths, write_path, 420, version, false).unwrap();
+        write_asset_list(gmk, &assets.scripts, write_script, 400, version, false).unwrap();
+        write_asset_list(gmk, &assets.fonts, write_font, 540, version, false).unwrap();
+        write_asset_list(gmk, &assets.timelines, write_timeline, 500, version, false).unwrap();
+        write_asset_list(gmk, &assets.objects, write_object, 400, version, false).unwrap();
+        write_asset_list(gmk, &assets.rooms, write_room, 420, version, false).unwrap();
+        write_room_editor_meta(gmk, assets.last_instance_id, assets.last_tile_id).unwrap();
+        write_included_files(gmk, &assets.included_files).unwrap();
+        write_extensions(gmk, &assets.extensions).unwrap();
+        write_game_information(gmk, &assets.help_dialog, version).unwrap();
+        write_library_init_code(gmk, &assets.library_init_strings).unwrap();
+        write_room_order(gmk, &assets.room_order).unwrap();
+        write_resource_tree(gmk, assets).unwrap();
+        out
+    }
+
+    fn settings() -> Settings {
+        Settings {
+            fullscreen: true,
+            scaling: 0,
+            interpolate_pixels: false,
+            clear_colour: 0xABCDEF,
+            allow_resize: true,
+            window_on_top: false,
+            dont_draw_border: true,
+            dont_show_buttons: false,
+            display_cursor: true,
+            freeze_on_lose_focus: false,
+            disable_screensaver: true,
+            force_cpu_render: true,
+            set_resolution: false,
+            colour_depth: 0,
+            resolution: 0,
+            frequency: 0,
+            vsync: true,
+            esc_close_game: false,
+            treat_close_as_esc: true,
+            f1_help_menu: false,
+            f4_fullscreen_toggle: true,
+            f5_save_f6_load: false,
+            f9_screenshot: true,
+            priority: 2,
+            custom_load_image: None,
+            transparent: false,
+            translucency: 255,
+            loading_bar: 1,
+            backdata: None,
+            frontdata: None,
+            scale_progress_bar: true,
+            show_error_messages: false,
+            log_errors: true,
+            always_abort: false,
+            zero_uninitialized_vars: true,
+            error_on_uninitialized_args: false,
+            swap_creation_events: false,
+        }
+    }
+
+    fn assets() -> GameAssets {
+        let mut param_strings: [PascalString; 8] = Default::default();
+        param_strings[0] = "hspeed = 4".into();
+        let action = CodeAction {
+            id: 603,
+            applies_to: -1,
+            is_condition: false,
+            invert_condition: false,
+            is_relative: false,
+            lib_id: 1,
+            action_kind: 7,
+            execution_type: 2,
+            can_be_relative: 0,
+            applies_to_something: true,
+            fn_name: "".into(),
+            fn_code: "".into(),
+            param_count: 1,
+            param_types: [1, 0, 0, 0, 0, 0, 0, 0],
+            param_strings,
+        };
+        let mut events = (0..12).map(|_| Vec::new()).collect::<Vec<_>>();
+        events[0].push((0, vec![action]));
+        GameAssets {
+            triggers: Vec::new(),
+            constants: vec![Constant { name: "LIVES".into(), expression: "3".into() }],
+            extensions: vec![Extension { name: "Not Installed".into(), folder_name: "".into(), files: Vec::new() }],
+            sprites: vec![Some(Box::new(Sprite {
+                name: "spr_ball".into(),
+                origin_x: 1,
+                origin_y: 1,
+                frames: vec![Frame { width: 2, height: 2, data: vec![255; 16].into() }],
+                colliders: vec![CollisionMap {
+                    width: 2,
+                    height: 2,
+                    bbox_left: 0,
+                    bbox_right: 1,
+                    bbox_top: 0,
+                    bbox_bottom: 1,
+                    data: vec![true; 4].into(),
+                }],
+                per_frame_colliders: false,
+            }))],
+            sounds: vec![None, Some(Box::new(Sound {
+                name: "snd_bounce".into(),
+                source: "bounce.wav".into(),
+                extension: ".wav".into(),
+                data: Some(b"RIFF".to_vec().into()),
+                kind: SoundKind::Normal,
+                volume: 0.5,
+                pan: 0.0,
+                preload: true,
+                fx: SoundFX { chorus: false, echo: false, flanger: true, gargle: false, reverb: false },
+            }))],
+            backgrounds: Vec::new(),
+            paths: vec![Some(Box::new(asset::Path {
+                name: "path_zigzag".into(),
+                connection: ConnectionKind::StraightLine,
+                precision: 4,
+                closed: true,
+                points: vec![Point { x: 1.0, y: 2.0, speed: 100.0 }, Point { x: 3.0, y: -4.0, speed: 25.0 }],
+            }))],
+            scripts: vec![Some(Box::new(Script { name: "scr_init".into(), source: "globalvar score;".into() }))],
+            fonts: vec![Some(Box::new(Font {
+                name: "fnt_score".into(),
+                sys_name: "Not An Installed Font".into(),
+                size: 14,
+                bold: true,
+                italic: false,
+                range_start: 32,
+                range_end: 127,
+                charset: 0,
+                aa_level: 0,
+                dmap: Box::new([0; 0x600]),
+                map_width: 0,
+                map_height: 0,
+                pixel_map: Box::new([]),
+            }))],
+            timelines: Vec::new(),
+            objects: vec![Some(Box::new(Object {
+                name: "obj_ball".into(),
+                sprite_index: 0,
+                solid: false,
+                visible: true,
+                depth: 5,
+                persistent: false,
+                parent_index: -1,
+                mask_index: -1,
+                events,
+            }))],
+            rooms: Vec::new(),
+            included_files: Vec::new(),
+            version: GameVersion::GameMaker8_0,
+            dx_dll: Vec::new(),
+            ico_file_raw: None,
+            help_dialog: GameHelpDialog {
+                bg_colour: 0xFFFFFF.into(),
+                new_window: false,
+                caption: "Game Information".into(),
+                left: -1,
+                top: -1,
+                width: 600,
+                height: 400,
+                border: true,
+                resizable: true,
+                window_on_top: false,
+                freeze_game: true,
+                info: "{\\rtf1 Bounce the ball}".into(),
+            },
+            last_instance_id: 100005,
+            last_tile_id: 10000007,
+            library_init_strings: Vec::new(),
+            room_order: Vec::new(),
+            settings: settings(),
+            game_id: 4321,
+            guid: [9, 8, 7, 6],
+        }
+    }
+
+    #[test]
+    fn gmk_round_trip() {
+        let assets = assets();
+        let read = gm8exe::gmk::from_gmk::<fn(&str)>(&write_gmk(&assets), &[], None, true, false).unwrap();
+
+        assert_eq!(read.version, GameVersion::GameMaker8_0);
+        assert_eq!((read.game_id, read.guid), (4321, [9, 8, 7, 6]));
+        assert_eq!((read.last_instance_id, read.last_tile_id), (100005, 10000007));
+        assert!(read.settings.fullscreen && read.settings.dont_draw_border && !read.settings.f1_help_menu);
+        assert_eq!((read.settings.clear_colour, read.settings.priority), (0xABCDEF, 2));
+        assert_eq!(read.help_dialog.info.0, assets.help_dialog.info.0);
+        assert_eq!((&*read.constants[0].name.0, &*read.constants[0].expression.0), (&b"LIVES"[..], &b"3"[..]));
+
+        let sprite = read.sprites[0].as_ref().unwrap();
+        assert_eq!((sprite.origin_x, sprite.origin_y, sprite.frames.len()), (1, 1, 1));
+        assert_eq!(sprite.frames[0].data, assets.sprites[0].as_ref().unwrap().frames[0].data);
+        assert!(sprite.colliders[0].data.iter().all(|&p| p));
+
+        assert!(read.sounds[0].is_none());
+        let sound = read.sounds[1].as_ref().unwrap();
+        assert_eq!((sound.data.as_deref(), sound.volume, sound.fx.flanger), (Some(&b"RIFF"[..]), 0.5, true));
+
+        let path = read.paths[0].as_ref().unwrap();
+        let points = path.points.iter().map(|p| (p.x, p.y, p.speed)).collect::<Vec<_>>();
+        assert_eq!((path.closed, points), (true, vec![(1.0, 2.0, 100.0), (3.0, -4.0, 25.0)]));
+        assert_eq!(read.scripts[0].as_ref().unwrap().source.0, assets.scripts[0].as_ref().unwrap().source.0);
+
+        // the font isn't installed, so it comes back without glyphs
+        let font = read.fonts[0].as_ref().unwrap();
+        assert_eq!((font.size, font.bold, font.range_start, font.range_end), (14, true, 32, 127));
+        assert!(font.pixel_map.is_empty());
+
+        let object = read.objects[0].as_ref().unwrap();
+        assert_eq!((object.sprite_index, object.depth, object.visible), (0, 5, true));
+        let (sub, actions) = &object.events[0][0];
+        assert_eq!((*sub, actions[0].id, &*actions[0].param_strings[0].0), (0, 603, &b"hspeed = 4"[..]));
+
+        // extensions that aren't installed are kept by name only
+        assert_eq!(read.extensions.len(), 1);
+        assert_eq!((&*read.extensions[0].name.0, read.extensions[0].files.len()), (&b"Not Installed"[..], 0));
+    }
+
+    #[test]
+    fn gm7_header() {
+        let mut out = Vec::new();
+        write_header(&mut GmkWriter::new(&mut out), GameVersion::GameMaker7_0, 0x12345678, [1, 2, 3, 4]).unwrap();
+        let mut header = out.as_slice();
+        assert_eq!(header.read_u32::<LE>().unwrap(), 1234321);
+        assert_eq!(header.read_u32::<LE>().unwrap(), 701);
+        assert_eq!(header.read_u32::<LE>().unwrap(), 0); // garbage before the seed
+        assert_eq!(header.read_u32::<LE>().unwrap(), 0); // garbage after the seed
+        let seed = header.read_u32::<LE>().unwrap();
+
+        // The game ID is there once, with everything after its first byte encrypted
+        let (_, reverse_table) = gm70::seed_tables(seed);
+        let mut rest = vec![header[0]];
+        rest.extend(header[1..].iter().enumerate().map(|(i, b)| reverse_table[usize::from(*b)].wrapping_sub(i as u8)));
+        let mut rest = rest.as_slice();
+        assert_eq!(rest.read_u32::<LE>().unwrap(), 0x12345678);
+        for n in 1..=4 {
+            assert_eq!(rest.read_u32::<LE>().unwrap(), n);
+        }
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn gm8_header() {
+        let mut out = Vec::new();
+        write_header(&mut GmkWriter::new(&mut out), GameVersion::GameMaker8_0, 99, [0; 4]).unwrap();
+        assert_eq!(out.len(), 4 * 7);
+        assert_eq!(out[8..12], 99u32.to_le_bytes());
+    }
+}
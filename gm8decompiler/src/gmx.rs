@@ -0,0 +1,1386 @@
+//! A folder-based project format, loosely following the layout of GameMaker: Studio's GMX projects.
+//!
+//! Every asset gets its own XML file, named after the asset, in a folder for its kind. Images are written as PNG,
+//! sounds and included files as they were stored, and scripts and code actions as plain `.gml` files,
+//! so a project can be kept under version control and diffed. The `.project.gmx` file in the root of the folder
+//! holds the game's settings and the order of every asset list, including gaps left by deleted assets.
+//!
+//! GameMaker strings are byte strings, so in XML each byte is written as the character with the same value.
+//! Code files are written byte for byte.
+
+use crate::xml::Element;
+use gm8exe::{
+    asset::{
+        code_action::PARAM_COUNT,
+        extension::{CallingConvention, File as ExtensionFile, FileConst, FileFunction, FileKind, FunctionValueKind},
+        included_file::ExportSetting,
+        path::{ConnectionKind, Point},
+        room::{self, Instance, Tile, View, ViewFollowData},
+        sound::SoundFX,
+        sprite::{CollisionMap, Frame},
+        Background, CodeAction, Constant, Extension, Font, IncludedFile, Object, PascalString, Room, Script, Sound,
+        SoundKind, Sprite, Timeline, Trigger, TriggerKind,
+    },
+    settings::{GameHelpDialog, Settings},
+    GameAssets, GameVersion,
+};
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+};
+
+type GmPath = gm8exe::asset::Path;
+type RoomBackground = room::Background;
+
+/// The file extension of the main project file.
+pub const PROJECT_EXT: &str = ".project.gmx";
+
+pub(crate) const EVENT_NAMES: [&str; 12] = [
+    "Create",
+    "Destroy",
+    "Alarm",
+    "Step",
+    "Collision",
+    "Keyboard",
+    "Mouse",
+    "Other",
+    "Draw",
+    "KeyPress",
+    "KeyRelease",
+    "Trigger",
+];
+
+/// Writes the listed fields of a struct as attributes of the same names.
+macro_rules! with_fields {
+    ($el:expr, $src:expr, $($field:ident),* $(,)?) => {
+        $el$(.with(stringify!($field), &$src.$field))*
+    };
+}
+
+/// Builds a struct from attributes named after its fields, followed by any fields which need handling by hand.
+macro_rules! from_fields {
+    ($el:expr, $ty:ident { $($field:ident),* $(,)? } $($rest:tt)*) => {
+        $ty { $($field: $el.attr(stringify!($field))?,)* $($rest)* }
+    };
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+pub(crate) fn text(s: &PascalString) -> String {
+    s.0.iter().map(|&b| char::from(b)).collect()
+}
+
+fn pas(s: &str) -> PascalString {
+    PascalString(s.chars().map(|c| u8::try_from(u32::from(c)).unwrap_or(b'?')).collect())
+}
+
+fn version_name(version: GameVersion) -> &'static str {
+    match version {
+        GameVersion::GameMaker5_0 => "5.0",
+        GameVersion::GameMaker6_0 => "6.0",
+        GameVersion::GameMaker7_0 => "7.0",
+        GameVersion::GameMaker8_0 => "8.0",
+        GameVersion::GameMaker8_1 => "8.1",
+    }
+}
+
+/// Makes a name safe to use in a file name.
+pub(crate) fn sanitize(name: &str) -> String {
+    name.chars().map(|c| if c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.') { c } else { '_' }).collect()
+}
+
+/// Picks a unique file stem for each asset in a list, based on its name.
+pub(crate) fn stems<'a>(names: impl Iterator<Item = Option<&'a PascalString>>, kind: &str) -> Vec<Option<String>> {
+    let mut used = HashSet::new();
+    names
+        .enumerate()
+        .map(|(i, name)| {
+            name.map(|name| {
+                let mut stem = sanitize(&text(name));
+                if stem.is_empty() || stem.starts_with('.') {
+                    stem = format!("{}_{}", kind, i);
+                }
+                // the suffixed stem could be another asset's name too, so keep going until it's free
+                let base = stem.clone();
+                let mut suffix = i;
+                while !used.insert(stem.to_ascii_lowercase()) {
+                    stem = format!("{}_{}", base, suffix);
+                    suffix += 1;
+                }
+                stem
+            })
+        })
+        .collect()
+}
+
+fn write_xml(path: &Path, element: &Element) -> io::Result<()> {
+    element.write_document(&mut BufWriter::new(fs::File::create(path)?))
+}
+
+fn read_xml(path: &Path) -> io::Result<Element> {
+    let doc = fs::read_to_string(path).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+    Element::parse_document(&doc).map_err(|e| invalid(format!("{}: {}", path.display(), e)))
+}
+
+fn read_file(path: &Path) -> io::Result<Vec<u8>> {
+    fs::read(path).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+pub(crate) fn bgra_to_rgba(data: &[u8]) -> Vec<u8> {
+    data.chunks_exact(4).flat_map(|p| [p[2], p[1], p[0], p[3]]).collect()
+}
+
+/// Writes an 8-bit PNG. There's nothing to write for an empty image, so this returns whether anything was written.
+pub(crate) fn write_png(path: &Path, width: u32, height: u32, colour: png::ColorType, data: &[u8]) -> io::Result<bool> {
+    if width == 0 || height == 0 {
+        return Ok(false)
+    }
+    let mut encoder = png::Encoder::new(BufWriter::new(fs::File::create(path)?), width, height);
+    encoder.set_color(colour);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(data)?;
+    Ok(true)
+}
+
+/// Reads a PNG of any kind as RGBA.
+fn read_png(path: &Path) -> io::Result<(u32, u32, Vec<u8>)> {
+    let mut decoder = png::Decoder::new(io::BufReader::new(fs::File::open(path)?));
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let (info, mut reader) = decoder.read_info()?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    reader.next_frame(&mut buf)?;
+    let pixels = info.width as usize * info.height as usize;
+    let rgba = match reader.output_color_type().0 {
+        png::ColorType::RGBA => buf,
+        png::ColorType::RGB => buf.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        png::ColorType::GrayscaleAlpha => buf.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        png::ColorType::Grayscale => buf.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        png::ColorType::Indexed => return Err(invalid(format!("{}: unexpanded palette", path.display()))),
+    };
+    Ok((info.width, info.height, rgba.into_iter().take(pixels * 4).collect()))
+}
+
+/// Reads a PNG referenced by an attribute as BGRA, checking it has the expected size.
+fn read_bgra(dir: &Path, el: &Element, key: &str, width: u32, height: u32) -> io::Result<Box<[u8]>> {
+    match el.get(key) {
+        Some(file) => {
+            let path = dir.join(file);
+            let (w, h, rgba) = read_png(&path)?;
+            if (w, h) != (width, height) {
+                return Err(invalid(format!(
+                    "{}: expected {}x{} image, got {}x{}",
+                    path.display(),
+                    width,
+                    height,
+                    w,
+                    h
+                )))
+            }
+            Ok(bgra_to_rgba(&rgba).into_boxed_slice())
+        },
+        None => Ok(vec![0; width as usize * height as usize * 4].into_boxed_slice()),
+    }
+}
+
+/// Writes a project folder. The folder is created if it doesn't exist.
+pub fn write_project(dir: &Path, assets: &GameAssets) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let mut project = Element::new("project")
+        .with("version", version_name(assets.version))
+        .with("game_id", assets.game_id)
+        .with(
+            "guid",
+            format!("{:08X}-{:08X}-{:08X}-{:08X}", assets.guid[0], assets.guid[1], assets.guid[2], assets.guid[3]),
+        )
+        .with("last_instance_id", assets.last_instance_id)
+        .with("last_tile_id", assets.last_tile_id);
+
+    let configs = dir.join("Configs");
+    fs::create_dir_all(&configs)?;
+    project.push(write_settings(&configs, &assets.settings)?);
+    fs::write(configs.join("D3DX8.dll"), &assets.dx_dll)?;
+    if let Some(ico) = &assets.ico_file_raw {
+        fs::write(configs.join("icon.ico"), ico)?;
+        project.push(Element::new("icon").with("file", "Configs/icon.ico"));
+    }
+
+    let help = &assets.help_dialog;
+    fs::write(dir.join("help.rtf"), &help.info.0)?;
+    project.push(
+        with_fields!(
+            Element::new("help"),
+            help,
+            new_window,
+            left,
+            top,
+            width,
+            height,
+            border,
+            resizable,
+            window_on_top
+        )
+        .with("freeze_game", help.freeze_game)
+        .with("bg_colour", u32::from(help.bg_colour))
+        .with("caption", text(&help.caption))
+        .with("info", "help.rtf"),
+    );
+
+    let mut constants = Element::new("constants");
+    for constant in &assets.constants {
+        constants.push(
+            Element::new("constant").with("name", text(&constant.name)).with("value", text(&constant.expression)),
+        );
+    }
+    project.push(constants);
+
+    let mut library_init = Element::new("library_init");
+    for string in &assets.library_init_strings {
+        library_init.push(Element::new("string").with_text(text(string)));
+    }
+    project.push(library_init);
+
+    let mut room_order = Element::new("room_order");
+    for room in &assets.room_order {
+        room_order.push(Element::new("room").with("index", room));
+    }
+    project.push(room_order);
+
+    macro_rules! list {
+        ($list:expr, $group:literal, $kind:literal, $write:expr) => {
+            project.push(write_list(dir, $group, $kind, $list.iter().map(|a| a.as_deref()), |a| &a.name, $write)?)
+        };
+    }
+    list!(assets.triggers, "triggers", "trigger", write_trigger);
+    list!(assets.sounds, "sound", "sound", write_sound);
+    list!(assets.sprites, "sprites", "sprite", write_sprite);
+    list!(assets.backgrounds, "background", "background", write_background);
+    list!(assets.paths, "paths", "path", write_path);
+    list!(assets.scripts, "scripts", "script", write_script);
+    list!(assets.fonts, "fonts", "font", write_font);
+    list!(assets.timelines, "timelines", "timeline", write_timeline);
+    list!(assets.objects, "objects", "object", write_object);
+    list!(assets.rooms, "rooms", "room", write_room);
+    project.push(write_list(
+        dir,
+        "extensions",
+        "extension",
+        assets.extensions.iter().map(Some),
+        |e| &e.name,
+        write_extension,
+    )?);
+    project.push(write_list(
+        dir,
+        "datafiles",
+        "datafile",
+        assets.included_files.iter().map(Some),
+        |f| &f.file_name,
+        write_included_file,
+    )?);
+
+    let name = dir.file_name().and_then(|n| n.to_str()).map(|n| n.trim_end_matches(".gmx")).unwrap_or("game");
+    write_xml(&dir.join(format!("{}{}", name, PROJECT_EXT)), &project)
+}
+
+/// Writes the files for each asset in a list into the group's folder, returning the list's entry in the project.
+fn write_list<'a, T: 'a>(
+    dir: &Path,
+    group: &str,
+    kind: &str,
+    list: impl Iterator<Item = Option<&'a T>> + Clone,
+    name: impl Fn(&T) -> &PascalString,
+    write: impl Fn(&T, &Path, &str) -> io::Result<()>,
+) -> io::Result<Element> {
+    let group_dir = dir.join(group);
+    let mut element = Element::new(group);
+    for (asset, stem) in list.clone().zip(stems(list.map(|a| a.map(&name)), kind)) {
+        match (asset, stem) {
+            (Some(asset), Some(stem)) => {
+                fs::create_dir_all(&group_dir)?;
+                write(asset, &group_dir, &stem)?;
+                element.push(Element::new(kind).with("name", text(name(asset))).with_text(stem));
+            },
+            _ => element.push(Element::new("none")),
+        }
+    }
+    Ok(element)
+}
+
+fn write_settings(configs: &Path, settings: &Settings) -> io::Result<Element> {
+    let mut element = with_fields!(
+        Element::new("settings"),
+        settings,
+        fullscreen,
+        scaling,
+        interpolate_pixels,
+        clear_colour,
+        allow_resize,
+        window_on_top,
+        dont_draw_border,
+        dont_show_buttons,
+        display_cursor,
+        freeze_on_lose_focus,
+        disable_screensaver,
+        force_cpu_render,
+        set_resolution,
+        colour_depth,
+        resolution,
+        frequency,
+        vsync,
+        esc_close_game,
+        treat_close_as_esc,
+        f1_help_menu,
+        f4_fullscreen_toggle,
+        f5_save_f6_load,
+        f9_screenshot,
+        priority,
+        transparent,
+        translucency,
+        loading_bar,
+        scale_progress_bar,
+        show_error_messages,
+        log_errors,
+        always_abort,
+        zero_uninitialized_vars,
+        error_on_uninitialized_args,
+        swap_creation_events,
+    );
+    for (key, data) in [
+        ("custom_load_image", &settings.custom_load_image),
+        ("backdata", &settings.backdata),
+        ("frontdata", &settings.frontdata),
+    ] {
+        if let Some(data) = data {
+            fs::write(configs.join(format!("{}.bin", key)), data)?;
+            element = element.with(key, format!("Configs/{}.bin", key));
+        }
+    }
+    Ok(element)
+}
+
+/// Writes a list of actions, putting the code of any code actions into .gml files named after `code_stem`.
+fn write_actions(
+    mut element: Element,
+    actions: &[CodeAction],
+    dir: &Path,
+    code_dir: &str,
+    code_stem: &str,
+) -> io::Result<Element> {
+    let mut code_count = 0;
+    for action in actions {
+        let mut el = with_fields!(
+            Element::new("action"),
+            action,
+            lib_id,
+            id,
+            action_kind,
+            execution_type,
+            can_be_relative,
+            is_condition,
+            applies_to_something,
+            param_count,
+            applies_to,
+            is_relative,
+            invert_condition,
+        );
+        if !action.fn_name.0.is_empty() || !action.fn_code.0.is_empty() {
+            el = el.with("fn_name", text(&action.fn_name)).with("fn_code", text(&action.fn_code));
+        }
+        let arg_count = (0..PARAM_COUNT)
+            .rev()
+            .find(|&i| action.param_types[i] != 0 || !action.param_strings[i].0.is_empty())
+            .map_or(0, |i| i + 1)
+            .max(action.param_count);
+        for i in 0..arg_count {
+            let arg = Element::new("arg").with("type", action.param_types[i]);
+            el.push(if i == 0 && action.action_kind == 7 {
+                let file = match code_count {
+                    0 => format!("{}.gml", code_stem),
+                    n => format!("{}_{}.gml", code_stem, n),
+                };
+                fs::create_dir_all(dir.join(code_dir))?;
+                fs::write(dir.join(code_dir).join(&file), &action.param_strings[i].0)?;
+                code_count += 1;
+                arg.with("file", format!("{}/{}", code_dir, file))
+            } else {
+                arg.with_text(text(&action.param_strings[i]))
+            });
+        }
+        element.push(el);
+    }
+    Ok(element)
+}
+
+fn write_trigger(trigger: &Trigger, dir: &Path, stem: &str) -> io::Result<()> {
+    let element = Element::new("trigger")
+        .with("moment", trigger.moment as u32)
+        .with("constant_name", text(&trigger.constant_name))
+        .with_text(text(&trigger.condition));
+    write_xml(&dir.join(format!("{}.trigger.gmx", stem)), &element)
+}
+
+fn write_sound(sound: &Sound, dir: &Path, stem: &str) -> io::Result<()> {
+    let mut element = with_fields!(Element::new("sound"), sound, volume, pan, preload)
+        .with("kind", sound.kind as u32)
+        .with("source", text(&sound.source))
+        .with("extension", text(&sound.extension));
+    element = with_fields!(element, sound.fx, chorus, echo, flanger, gargle, reverb);
+    if let Some(data) = &sound.data {
+        fs::create_dir_all(dir.join("audio"))?;
+        let file = format!("audio/{}{}", stem, sanitize(&text(&sound.extension)));
+        fs::write(dir.join(&file), data)?;
+        element = element.with("data", file);
+    }
+    write_xml(&dir.join(format!("{}.sound.gmx", stem)), &element)
+}
+
+fn write_sprite(sprite: &Sprite, dir: &Path, stem: &str) -> io::Result<()> {
+    fs::create_dir_all(dir.join("images"))?;
+    let mut element = with_fields!(Element::new("sprite"), sprite, origin_x, origin_y, per_frame_colliders);
+    for (i, frame) in sprite.frames.iter().enumerate() {
+        let file = format!("images/{}_{}.png", stem, i);
+        let mut el = with_fields!(Element::new("frame"), frame, width, height);
+        if write_png(&dir.join(&file), frame.width, frame.height, png::ColorType::RGBA, &bgra_to_rgba(&frame.data))? {
+            el = el.with("image", file);
+        }
+        element.push(el);
+    }
+    for (i, collider) in sprite.colliders.iter().enumerate() {
+        let file = format!("images/{}_mask_{}.png", stem, i);
+        let mut el =
+            with_fields!(Element::new("mask"), collider, width, height, bbox_left, bbox_right, bbox_top, bbox_bottom);
+        let data = collider.data.iter().map(|&b| if b { 255 } else { 0 }).collect::<Vec<u8>>();
+        if write_png(&dir.join(&file), collider.width, collider.height, png::ColorType::Grayscale, &data)? {
+            el = el.with("image", file);
+        }
+        element.push(el);
+    }
+    write_xml(&dir.join(format!("{}.sprite.gmx", stem)), &element)
+}
+
+fn write_background(background: &Background, dir: &Path, stem: &str) -> io::Result<()> {
+    let mut element = with_fields!(Element::new("background"), background, width, height);
+    if let Some(data) = &background.data {
+        fs::create_dir_all(dir.join("images"))?;
+        let file = format!("images/{}.png", stem);
+        if write_png(&dir.join(&file), background.width, background.height, png::ColorType::RGBA, &bgra_to_rgba(data))?
+        {
+            element = element.with("image", file);
+        }
+    }
+    write_xml(&dir.join(format!("{}.background.gmx", stem)), &element)
+}
+
+fn write_path(path: &GmPath, dir: &Path, stem: &str) -> io::Result<()> {
+    let mut element =
+        with_fields!(Element::new("path"), path, precision, closed).with("connection", path.connection as u32);
+    for point in &path.points {
+        element.push(with_fields!(Element::new("point"), point, x, y, speed));
+    }
+    write_xml(&dir.join(format!("{}.path.gmx", stem)), &element)
+}
+
+fn write_script(script: &Script, dir: &Path, stem: &str) -> io::Result<()> {
+    fs::write(dir.join(format!("{}.gml", stem)), &script.source.0)
+}
+
+fn write_font(font: &Font, dir: &Path, stem: &str) -> io::Result<()> {
+    let mut element = with_fields!(
+        Element::new("font"),
+        font,
+        size,
+        bold,
+        italic,
+        range_start,
+        range_end,
+        charset,
+        aa_level,
+        map_width,
+        map_height,
+    )
+    .with("sys_name", text(&font.sys_name));
+    let file = format!("{}.png", stem);
+    if write_png(&dir.join(&file), font.map_width, font.map_height, png::ColorType::Grayscale, &font.pixel_map)? {
+        element = element.with("image", file);
+    }
+    for glyph in font.dmap.chunks_exact(6) {
+        element.push(
+            Element::new("glyph")
+                .with("x", glyph[0])
+                .with("y", glyph[1])
+                .with("width", glyph[2])
+                .with("height", glyph[3])
+                .with("offset", glyph[4])
+                .with("distance", glyph[5]),
+        );
+    }
+    write_xml(&dir.join(format!("{}.font.gmx", stem)), &element)
+}
+
+fn write_timeline(timeline: &Timeline, dir: &Path, stem: &str) -> io::Result<()> {
+    let mut element = Element::new("timeline");
+    for (step, actions) in &timeline.moments {
+        let moment = Element::new("moment").with("step", step);
+        element.push(write_actions(moment, actions, dir, stem, &format!("Step_{}", step))?);
+    }
+    write_xml(&dir.join(format!("{}.timeline.gmx", stem)), &element)
+}
+
+fn write_object(object: &Object, dir: &Path, stem: &str) -> io::Result<()> {
+    let mut element = with_fields!(
+        Element::new("object"),
+        object,
+        sprite_index,
+        solid,
+        visible,
+        depth,
+        persistent,
+        parent_index,
+        mask_index,
+    )
+    .with("event_lists", object.events.len());
+    for (ev_type, list) in object.events.iter().enumerate() {
+        for (number, actions) in list {
+            let name = EVENT_NAMES.get(ev_type).copied().unwrap_or("Event");
+            let event = Element::new("event").with("type", ev_type).with("number", number);
+            element.push(write_actions(event, actions, dir, stem, &format!("{}_{}", name, number))?);
+        }
+    }
+    write_xml(&dir.join(format!("{}.object.gmx", stem)), &element)
+}
+
+fn write_room(room: &Room, dir: &Path, stem: &str) -> io::Result<()> {
+    let mut element = with_fields!(
+        Element::new("room"),
+        room,
+        width,
+        height,
+        speed,
+        persistent,
+        clear_screen,
+        clear_region,
+        views_enabled,
+        uses_810_features,
+        uses_811_features,
+    )
+    .with("caption", text(&room.caption))
+    .with("bg_colour", u32::from(room.bg_colour));
+    element.push(Element::new("code").with_text(text(&room.creation_code)));
+    for bg in &room.backgrounds {
+        element.push(with_fields!(
+            Element::new("background"),
+            bg,
+            visible_on_start,
+            is_foreground,
+            source_bg,
+            xoffset,
+            yoffset,
+            tile_horz,
+            tile_vert,
+            hspeed,
+            vspeed,
+            stretch,
+        ));
+    }
+    for view in &room.views {
+        let el = with_fields!(
+            Element::new("view"),
+            view,
+            visible,
+            source_x,
+            source_y,
+            source_w,
+            source_h,
+            port_x,
+            port_y,
+            port_w,
+            port_h,
+        );
+        element.push(with_fields!(el, view.following, hborder, vborder, hspeed, vspeed, target));
+    }
+    for instance in &room.instances {
+        element.push(
+            with_fields!(Element::new("instance"), instance, x, y, object, id, xscale, yscale, blend, angle)
+                .with_text(text(&instance.creation_code)),
+        );
+    }
+    for tile in &room.tiles {
+        element.push(with_fields!(
+            Element::new("tile"),
+            tile,
+            x,
+            y,
+            source_bg,
+            tile_x,
+            tile_y,
+            width,
+            height,
+            depth,
+            id,
+            xscale,
+            yscale,
+            blend,
+        ));
+    }
+    write_xml(&dir.join(format!("{}.room.gmx", stem)), &element)
+}
+
+fn write_extension(extension: &Extension, dir: &Path, stem: &str) -> io::Result<()> {
+    let mut element = Element::new("extension").with("folder_name", text(&extension.folder_name));
+    for (i, file) in extension.files.iter().enumerate() {
+        let mut el = Element::new("file")
+            .with("name", text(&file.name))
+            .with("kind", file.kind as u32)
+            .with("initializer", text(&file.initializer))
+            .with("finalizer", text(&file.finalizer));
+        if !file.contents.is_empty() {
+            fs::create_dir_all(dir.join(stem))?;
+            let path = format!("{}/{}_{}", stem, i, sanitize(&text(&file.name)));
+            fs::write(dir.join(&path), &file.contents)?;
+            el = el.with("contents", path);
+        }
+        for function in &file.functions {
+            let arg_types = function.arg_types.iter().map(|t| (*t as u32).to_string()).collect::<Vec<_>>();
+            el.push(
+                Element::new("function")
+                    .with("name", text(&function.name))
+                    .with("external_name", text(&function.external_name))
+                    .with("convention", function.convention as u32)
+                    .with("id", function.id)
+                    .with("arg_count", function.arg_count)
+                    .with("arg_types", arg_types.join(","))
+                    .with("return_type", function.return_type as u32),
+            );
+        }
+        for constant in &file.consts {
+            el.push(Element::new("const").with("name", text(&constant.name)).with("value", text(&constant.value)));
+        }
+        element.push(el);
+    }
+    write_xml(&dir.join(format!("{}.extension.gmx", stem)), &element)
+}
+
+fn write_included_file(file: &IncludedFile, dir: &Path, stem: &str) -> io::Result<()> {
+    let mut element = with_fields!(
+        Element::new("datafile"),
+        file,
+        data_exists,
+        source_length,
+        stored_in_gmk,
+        overwrite_file,
+        free_memory,
+        remove_at_end,
+    )
+    .with("source_path", text(&file.source_path));
+    element = match &file.export_settings {
+        ExportSetting::NoExport => element.with("export", 0),
+        ExportSetting::TempFolder => element.with("export", 1),
+        ExportSetting::GameFolder => element.with("export", 2),
+        ExportSetting::CustomFolder(folder) => element.with("export", 3).with("export_folder", text(folder)),
+    };
+    if let Some(data) = &file.embedded_data {
+        fs::create_dir_all(dir.join("files"))?;
+        let path = format!("files/{}", stem);
+        fs::write(dir.join(&path), data)?;
+        element = element.with("data", path);
+    }
+    write_xml(&dir.join(format!("{}.datafile.gmx", stem)), &element)
+}
+
+/// Finds the main project file in a project folder.
+pub fn find_project_file(dir: &Path) -> io::Result<PathBuf> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.ends_with(PROJECT_EXT)) {
+            return Ok(path)
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::NotFound, format!("no {} file in {}", PROJECT_EXT, dir.display())))
+}
+
+/// Reads a project folder written by `write_project` back into a set of assets.
+pub fn read_project(dir: &Path) -> io::Result<GameAssets> {
+    let project = read_xml(&find_project_file(dir)?)?;
+
+    let version = match project.str_attr("version")? {
+        "5.0" => GameVersion::GameMaker5_0,
+        "6.0" => GameVersion::GameMaker6_0,
+        "7.0" => GameVersion::GameMaker7_0,
+        "8.0" => GameVersion::GameMaker8_0,
+        "8.1" => GameVersion::GameMaker8_1,
+        v => return Err(invalid(format!("unknown GameMaker version {}", v))),
+    };
+    let guid_parts = project
+        .str_attr("guid")?
+        .split('-')
+        .map(|p| u32::from_str_radix(p, 16))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| invalid(format!("invalid guid: {}", e)))?;
+    let guid: [u32; 4] = guid_parts.try_into().map_err(|_| invalid("guid should have four parts".into()))?;
+
+    let help = project.child("help")?;
+    let help_dialog = from_fields!(help, GameHelpDialog {
+        new_window,
+        left,
+        top,
+        width,
+        height,
+        border,
+        resizable,
+        window_on_top,
+        freeze_game,
+    }
+        bg_colour: help.attr::<u32>("bg_colour")?.into(),
+        caption: pas(help.str_attr("caption")?),
+        info: PascalString(read_file(&dir.join(help.str_attr("info")?))?.into()),
+    );
+
+    let constants = project
+        .child("constants")?
+        .children("constant")
+        .map(|c| Ok(Constant { name: pas(c.str_attr("name")?), expression: pas(c.str_attr("value")?) }))
+        .collect::<io::Result<_>>()?;
+    let library_init_strings = project.child("library_init")?.children("string").map(|s| pas(&s.text)).collect();
+    let room_order =
+        project.child("room_order")?.children("room").map(|r| r.attr("index")).collect::<io::Result<_>>()?;
+
+    let ico_file_raw = match project.children("icon").next() {
+        Some(icon) => Some(read_file(&dir.join(icon.str_attr("file")?))?),
+        None => None,
+    };
+
+    fn boxed<T>(list: Vec<Option<T>>) -> Vec<Option<Box<T>>> {
+        list.into_iter().map(|a| a.map(Box::new)).collect()
+    }
+    fn unwrapped<T>(list: Vec<Option<T>>) -> Vec<T> {
+        list.into_iter().flatten().collect()
+    }
+
+    Ok(GameAssets {
+        triggers: boxed(read_list(&project, dir, "triggers", "trigger", read_trigger)?),
+        constants,
+        extensions: unwrapped(read_list(&project, dir, "extensions", "extension", read_extension)?),
+        sprites: boxed(read_list(&project, dir, "sprites", "sprite", read_sprite)?),
+        sounds: boxed(read_list(&project, dir, "sound", "sound", read_sound)?),
+        backgrounds: boxed(read_list(&project, dir, "background", "background", read_background)?),
+        paths: boxed(read_list(&project, dir, "paths", "path", read_path)?),
+        scripts: boxed(read_list(&project, dir, "scripts", "script", read_script)?),
+        fonts: boxed(read_list(&project, dir, "fonts", "font", read_font)?),
+        timelines: boxed(read_list(&project, dir, "timelines", "timeline", read_timeline)?),
+        objects: boxed(read_list(&project, dir, "objects", "object", read_object)?),
+        rooms: boxed(read_list(&project, dir, "rooms", "room", read_room)?),
+        included_files: unwrapped(read_list(&project, dir, "datafiles", "datafile", read_included_file)?),
+        version,
+        dx_dll: read_file(&dir.join("Configs").join("D3DX8.dll"))?,
+        ico_file_raw,
+        help_dialog,
+        last_instance_id: project.attr("last_instance_id")?,
+        last_tile_id: project.attr("last_tile_id")?,
+        library_init_strings,
+        room_order,
+        settings: read_settings(project.child("settings")?, dir)?,
+        game_id: project.attr("game_id")?,
+        guid,
+    })
+}
+
+/// Reads each asset listed in one of the project's groups, keeping gaps where there's no asset.
+fn read_list<T>(
+    project: &Element,
+    dir: &Path,
+    group: &str,
+    kind: &str,
+    read: impl Fn(&Path, &str, PascalString) -> io::Result<T>,
+) -> io::Result<Vec<Option<T>>> {
+    let group_dir = dir.join(group);
+    project
+        .child(group)?
+        .children
+        .iter()
+        .map(|entry| match entry.name.as_str() {
+            "none" => Ok(None),
+            name if name == kind => read(&group_dir, &entry.text, pas(entry.str_attr("name")?)).map(Some),
+            name => Err(invalid(format!("unexpected <{}> in <{}>", name, group))),
+        })
+        .collect()
+}
+
+fn read_settings(element: &Element, dir: &Path) -> io::Result<Settings> {
+    let blob = |key: &str| -> io::Result<Option<Box<[u8]>>> {
+        element.get(key).map(|file| read_file(&dir.join(file)).map(Vec::into_boxed_slice)).transpose()
+    };
+    Ok(from_fields!(element, Settings {
+        fullscreen,
+        scaling,
+        interpolate_pixels,
+        clear_colour,
+        allow_resize,
+        window_on_top,
+        dont_draw_border,
+        dont_show_buttons,
+        display_cursor,
+        freeze_on_lose_focus,
+        disable_screensaver,
+        force_cpu_render,
+        set_resolution,
+        colour_depth,
+        resolution,
+        frequency,
+        vsync,
+        esc_close_game,
+        treat_close_as_esc,
+        f1_help_menu,
+        f4_fullscreen_toggle,
+        f5_save_f6_load,
+        f9_screenshot,
+        priority,
+        transparent,
+        translucency,
+        loading_bar,
+        scale_progress_bar,
+        show_error_messages,
+        log_errors,
+        always_abort,
+        zero_uninitialized_vars,
+        error_on_uninitialized_args,
+        swap_creation_events,
+    }
+        custom_load_image: blob("custom_load_image")?,
+        backdata: blob("backdata")?,
+        frontdata: blob("frontdata")?,
+    ))
+}
+
+fn read_actions(element: &Element, dir: &Path) -> io::Result<Vec<CodeAction>> {
+    element
+        .children("action")
+        .map(|el| {
+            let mut param_types = [0u32; PARAM_COUNT];
+            let mut param_strings: [PascalString; PARAM_COUNT] = Default::default();
+            for (i, arg) in el.children("arg").enumerate().take(PARAM_COUNT) {
+                param_types[i] = arg.attr("type")?;
+                param_strings[i] = match arg.get("file") {
+                    Some(file) => PascalString(read_file(&dir.join(file))?.into()),
+                    None => pas(&arg.text),
+                };
+            }
+            Ok(from_fields!(el, CodeAction {
+                lib_id,
+                id,
+                action_kind,
+                execution_type,
+                can_be_relative,
+                is_condition,
+                applies_to_something,
+                param_count,
+                applies_to,
+                is_relative,
+                invert_condition,
+            }
+                fn_name: pas(el.get("fn_name").unwrap_or_default()),
+                fn_code: pas(el.get("fn_code").unwrap_or_default()),
+                param_types,
+                param_strings,
+            ))
+        })
+        .collect()
+}
+
+fn read_trigger(dir: &Path, stem: &str, name: PascalString) -> io::Result<Trigger> {
+    let el = read_xml(&dir.join(format!("{}.trigger.gmx", stem)))?;
+    Ok(Trigger {
+        name,
+        condition: pas(&el.text),
+        moment: TriggerKind::from(el.attr::<u32>("moment")?),
+        constant_name: pas(el.str_attr("constant_name")?),
+    })
+}
+
+fn read_sound(dir: &Path, stem: &str, name: PascalString) -> io::Result<Sound> {
+    let el = read_xml(&dir.join(format!("{}.sound.gmx", stem)))?;
+    Ok(from_fields!(el, Sound { volume, pan, preload }
+        name,
+        source: pas(el.str_attr("source")?),
+        extension: pas(el.str_attr("extension")?),
+        data: el.get("data").map(|file| read_file(&dir.join(file)).map(Vec::into_boxed_slice)).transpose()?,
+        kind: SoundKind::from(el.attr::<u32>("kind")?),
+        fx: from_fields!(el, SoundFX { chorus, echo, flanger, gargle, reverb }),
+    ))
+}
+
+fn read_sprite(dir: &Path, stem: &str, name: PascalString) -> io::Result<Sprite> {
+    let el = read_xml(&dir.join(format!("{}.sprite.gmx", stem)))?;
+    let frames = el
+        .children("frame")
+        .map(|frame| {
+            let (width, height) = (frame.attr("width")?, frame.attr("height")?);
+            Ok(Frame { width, height, data: read_bgra(dir, frame, "image", width, height)? })
+        })
+        .collect::<io::Result<_>>()?;
+    let colliders = el
+        .children("mask")
+        .map(|mask| {
+            let (width, height) = (mask.attr("width")?, mask.attr("height")?);
+            let data = read_bgra(dir, mask, "image", width, height)?.chunks_exact(4).map(|p| p[0] >= 128).collect();
+            Ok(from_fields!(mask, CollisionMap { width, height, bbox_left, bbox_right, bbox_top, bbox_bottom } data))
+        })
+        .collect::<io::Result<_>>()?;
+    Ok(from_fields!(el, Sprite { origin_x, origin_y, per_frame_colliders } name, frames, colliders))
+}
+
+fn read_background(dir: &Path, stem: &str, name: PascalString) -> io::Result<Background> {
+    let el = read_xml(&dir.join(format!("{}.background.gmx", stem)))?;
+    let (width, height) = (el.attr("width")?, el.attr("height")?);
+    let data = match el.get("image") {
+        Some(_) => Some(read_bgra(dir, &el, "image", width, height)?),
+        None => None,
+    };
+    Ok(Background { name, width, height, data })
+}
+
+fn read_path(dir: &Path, stem: &str, name: PascalString) -> io::Result<GmPath> {
+    let el = read_xml(&dir.join(format!("{}.path.gmx", stem)))?;
+    let points = el.children("point").map(|p| Ok(from_fields!(p, Point { x, y, speed }))).collect::<io::Result<_>>()?;
+    Ok(from_fields!(el, GmPath { precision, closed }
+        name,
+        connection: ConnectionKind::from(el.attr::<u32>("connection")?),
+        points,
+    ))
+}
+
+fn read_script(dir: &Path, stem: &str, name: PascalString) -> io::Result<Script> {
+    Ok(Script { name, source: PascalString(read_file(&dir.join(format!("{}.gml", stem)))?.into()) })
+}
+
+fn read_font(dir: &Path, stem: &str, name: PascalString) -> io::Result<Font> {
+    let el = read_xml(&dir.join(format!("{}.font.gmx", stem)))?;
+    let mut dmap = Box::new([0u32; 0x600]);
+    for (glyph, dst) in el.children("glyph").zip(dmap.chunks_exact_mut(6)) {
+        for (key, val) in ["x", "y", "width", "height", "offset", "distance"].iter().zip(dst.iter_mut()) {
+            *val = glyph.attr(key)?;
+        }
+    }
+    let (map_width, map_height) = (el.attr("map_width")?, el.attr("map_height")?);
+    let pixel_map = read_bgra(dir, &el, "image", map_width, map_height)?.chunks_exact(4).map(|p| p[0]).collect();
+    Ok(from_fields!(el, Font { size, bold, italic, range_start, range_end, charset, aa_level }
+        name,
+        sys_name: pas(el.str_attr("sys_name")?),
+        dmap,
+        map_width,
+        map_height,
+        pixel_map,
+    ))
+}
+
+fn read_timeline(dir: &Path, stem: &str, name: PascalString) -> io::Result<Timeline> {
+    let el = read_xml(&dir.join(format!("{}.timeline.gmx", stem)))?;
+    let moments =
+        el.children("moment").map(|m| Ok((m.attr("step")?, read_actions(m, dir)?))).collect::<io::Result<_>>()?;
+    Ok(Timeline { name, moments })
+}
+
+fn read_object(dir: &Path, stem: &str, name: PascalString) -> io::Result<Object> {
+    let el = read_xml(&dir.join(format!("{}.object.gmx", stem)))?;
+    let mut events: Vec<Vec<(u32, Vec<CodeAction>)>> =
+        (0..el.attr::<usize>("event_lists")?).map(|_| Vec::new()).collect();
+    for event in el.children("event") {
+        let ev_type = event.attr::<usize>("type")?;
+        let list =
+            events.get_mut(ev_type).ok_or_else(|| invalid(format!("{}: invalid event type {}", stem, ev_type)))?;
+        list.push((event.attr("number")?, read_actions(event, dir)?));
+    }
+    Ok(from_fields!(el, Object { sprite_index, solid, visible, depth, persistent, parent_index, mask_index }
+        name,
+        events,
+    ))
+}
+
+fn read_room(dir: &Path, stem: &str, name: PascalString) -> io::Result<Room> {
+    let el = read_xml(&dir.join(format!("{}.room.gmx", stem)))?;
+    let backgrounds = el
+        .children("background")
+        .map(|bg| {
+            Ok(from_fields!(
+                bg,
+                RoomBackground {
+                    visible_on_start,
+                    is_foreground,
+                    source_bg,
+                    xoffset,
+                    yoffset,
+                    tile_horz,
+                    tile_vert,
+                    hspeed,
+                    vspeed,
+                    stretch,
+                }
+            ))
+        })
+        .collect::<io::Result<_>>()?;
+    let views = el
+        .children("view")
+        .map(|view| {
+            Ok(from_fields!(view, View {
+                visible,
+                source_x,
+                source_y,
+                source_w,
+                source_h,
+                port_x,
+                port_y,
+                port_w,
+                port_h,
+            }
+                following: from_fields!(view, ViewFollowData { hborder, vborder, hspeed, vspeed, target }),
+            ))
+        })
+        .collect::<io::Result<_>>()?;
+    let instances = el
+        .children("instance")
+        .map(|inst| {
+            Ok(from_fields!(inst, Instance { x, y, object, id, xscale, yscale, blend, angle }
+                creation_code: pas(&inst.text),
+            ))
+        })
+        .collect::<io::Result<_>>()?;
+    let tiles = el
+        .children("tile")
+        .map(|tile| {
+            Ok(from_fields!(
+                tile,
+                Tile { x, y, source_bg, tile_x, tile_y, width, height, depth, id, xscale, yscale, blend }
+            ))
+        })
+        .collect::<io::Result<_>>()?;
+    Ok(from_fields!(el, Room {
+        width,
+        height,
+        speed,
+        persistent,
+        clear_screen,
+        clear_region,
+        views_enabled,
+        uses_810_features,
+        uses_811_features,
+    }
+        name,
+        caption: pas(el.str_attr("caption")?),
+        bg_colour: el.attr::<u32>("bg_colour")?.into(),
+        creation_code: pas(&el.child("code")?.text),
+        backgrounds,
+        views,
+        instances,
+        tiles,
+    ))
+}
+
+fn read_extension(dir: &Path, stem: &str, name: PascalString) -> io::Result<Extension> {
+    let el = read_xml(&dir.join(format!("{}.extension.gmx", stem)))?;
+    let files = el
+        .children("file")
+        .map(|file| {
+            let functions = file
+                .children("function")
+                .map(|f| {
+                    let mut arg_types = [FunctionValueKind::GMReal; 17];
+                    for (s, dst) in f.str_attr("arg_types")?.split(',').zip(arg_types.iter_mut()) {
+                        let kind = s.parse::<u32>().map_err(|_| invalid(format!("{}: invalid argument type", stem)))?;
+                        *dst = FunctionValueKind::from(kind);
+                    }
+                    Ok(FileFunction {
+                        name: pas(f.str_attr("name")?),
+                        external_name: pas(f.str_attr("external_name")?),
+                        convention: CallingConvention::from(f.attr::<u32>("convention")?),
+                        id: f.attr("id")?,
+                        arg_count: f.attr("arg_count")?,
+                        arg_types,
+                        return_type: FunctionValueKind::from(f.attr::<u32>("return_type")?),
+                    })
+                })
+                .collect::<io::Result<_>>()?;
+            let consts = file
+                .children("const")
+                .map(|c| Ok(FileConst { name: pas(c.str_attr("name")?), value: pas(c.str_attr("value")?) }))
+                .collect::<io::Result<_>>()?;
+            Ok(ExtensionFile {
+                name: pas(file.str_attr("name")?),
+                kind: FileKind::from(file.attr::<u32>("kind")?),
+                initializer: pas(file.str_attr("initializer")?),
+                finalizer: pas(file.str_attr("finalizer")?),
+                functions,
+                consts,
+                contents: match file.get("contents") {
+                    Some(path) => read_file(&dir.join(path))?.into(),
+                    None => Box::new([]),
+                },
+            })
+        })
+        .collect::<io::Result<_>>()?;
+    Ok(Extension { name, folder_name: pas(el.str_attr("folder_name")?), files })
+}
+
+fn read_included_file(dir: &Path, stem: &str, name: PascalString) -> io::Result<IncludedFile> {
+    let el = read_xml(&dir.join(format!("{}.datafile.gmx", stem)))?;
+    let export_settings = match el.attr::<u32>("export")? {
+        0 => ExportSetting::NoExport,
+        1 => ExportSetting::TempFolder,
+        2 => ExportSetting::GameFolder,
+        _ => ExportSetting::CustomFolder(pas(el.get("export_folder").unwrap_or_default())),
+    };
+    Ok(from_fields!(el, IncludedFile {
+        data_exists,
+        source_length,
+        stored_in_gmk,
+        overwrite_file,
+        free_memory,
+        remove_at_end,
+    }
+        file_name: name,
+        source_path: pas(el.str_attr("source_path")?),
+        embedded_data: el.get("data").map(|file| read_file(&dir.join(file)).map(Vec::into_boxed_slice)).transpose()?,
+        export_settings,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stems_of(names: &[Option<&str>]) -> Vec<Option<String>> {
+        let names = names.iter().map(|n| n.map(PascalString::from)).collect::<Vec<_>>();
+        stems(names.iter().map(Option::as_ref), "script")
+    }
+
+    fn settings() -> Settings {
+        Settings {
+            fullscreen: false,
+            scaling: 2,
+            interpolate_pixels: true,
+            clear_colour: 0x654321,
+            allow_resize: false,
+            window_on_top: true,
+            dont_draw_border: false,
+            dont_show_buttons: true,
+            display_cursor: false,
+            freeze_on_lose_focus: true,
+            disable_screensaver: false,
+            force_cpu_render: false,
+            set_resolution: false,
+            colour_depth: 0,
+            resolution: 0,
+            frequency: 0,
+            vsync: false,
+            esc_close_game: true,
+            treat_close_as_esc: false,
+            f1_help_menu: true,
+            f4_fullscreen_toggle: false,
+            f5_save_f6_load: true,
+            f9_screenshot: false,
+            priority: 1,
+            custom_load_image: None,
+            transparent: false,
+            translucency: 255,
+            loading_bar: 1,
+            backdata: None,
+            frontdata: None,
+            scale_progress_bar: false,
+            show_error_messages: true,
+            log_errors: true,
+            always_abort: false,
+            zero_uninitialized_vars: false,
+            error_on_uninitialized_args: true,
+            swap_creation_events: false,
+        }
+    }
+
+    fn code_action(code: &str) -> CodeAction {
+        CodeAction {
+            id: 603,
+            applies_to: -1,
+            is_condition: false,
+            invert_condition: false,
+            is_relative: false,
+            lib_id: 1,
+            action_kind: 7,
+            execution_type: 2,
+            can_be_relative: 0,
+            applies_to_something: true,
+            fn_name: "".into(),
+            fn_code: "".into(),
+            param_count: 1,
+            param_types: [1, 0, 0, 0, 0, 0, 0, 0],
+            param_strings: [code.into(), "".into(), "".into(), "".into(), "".into(), "".into(), "".into(), "".into()],
+        }
+    }
+
+    fn assets() -> GameAssets {
+        let script = |name: &str, source: &str| Some(Box::new(Script { name: name.into(), source: source.into() }));
+        GameAssets {
+            triggers: Vec::new(),
+            constants: vec![Constant { name: "GRAVITY".into(), expression: "0.5 * \"<&>\"".into() }],
+            extensions: Vec::new(),
+            sprites: vec![Some(Box::new(Sprite {
+                name: "spr_block".into(),
+                origin_x: 0,
+                origin_y: 1,
+                frames: vec![Frame { width: 1, height: 2, data: vec![1, 2, 3, 255, 4, 5, 6, 0].into() }],
+                colliders: vec![CollisionMap {
+                    width: 1,
+                    height: 2,
+                    bbox_left: 0,
+                    bbox_right: 0,
+                    bbox_top: 0,
+                    bbox_bottom: 0,
+                    data: vec![true, false].into(),
+                }],
+                per_frame_colliders: false,
+            }))],
+            sounds: vec![Some(Box::new(Sound {
+                name: "snd_hit".into(),
+                source: "C:\\hit.wav".into(),
+                extension: ".wav".into(),
+                data: Some(b"RIFF....WAVE".to_vec().into()),
+                kind: SoundKind::Normal,
+                volume: 0.75,
+                pan: 0.25,
+                preload: false,
+                fx: SoundFX { chorus: true, echo: false, flanger: false, gargle: true, reverb: false },
+            }))],
+            backgrounds: vec![
+                None,
+                Some(Box::new(Background {
+                    name: "bg_sky".into(),
+                    width: 1,
+                    height: 1,
+                    data: Some(vec![9, 8, 7, 255].into()),
+                })),
+            ],
+            paths: vec![Some(Box::new(GmPath {
+                name: "path_loop".into(),
+                connection: ConnectionKind::StraightLine,
+                precision: 4,
+                closed: false,
+                points: vec![Point { x: 0.5, y: 1.0, speed: 100.0 }, Point { x: -32.0, y: 16.0, speed: 50.0 }],
+            }))],
+            // the same name twice, which needs two different files
+            scripts: vec![script("scr_a", "return 1"), None, script("scr_a", "return argument0\r\n")],
+            fonts: Vec::new(),
+            timelines: Vec::new(),
+            objects: vec![Some(Box::new(Object {
+                name: "obj_player".into(),
+                sprite_index: 0,
+                solid: true,
+                visible: false,
+                depth: -10,
+                persistent: true,
+                parent_index: -1,
+                mask_index: -1,
+                events: (0..12)
+                    .map(|i| if i == 3 { vec![(0, vec![code_action("x += 1;\n// <step>")])] } else { Vec::new() })
+                    .collect(),
+            }))],
+            rooms: Vec::new(),
+            included_files: Vec::new(),
+            version: GameVersion::GameMaker8_0,
+            dx_dll: b"dll".to_vec(),
+            ico_file_raw: Some(b"icon".to_vec()),
+            help_dialog: GameHelpDialog {
+                bg_colour: 0xFFFFFF.into(),
+                new_window: false,
+                caption: "Help & \"info\"".into(),
+                left: -1,
+                top: -1,
+                width: 600,
+                height: 400,
+                border: true,
+                resizable: false,
+                window_on_top: false,
+                freeze_game: true,
+                info: "{\\rtf1 help}".into(),
+            },
+            last_instance_id: 100002,
+            last_tile_id: 10000003,
+            library_init_strings: vec!["init()".into()],
+            room_order: Vec::new(),
+            settings: settings(),
+            game_id: 1234,
+            guid: [0xDEADBEEF, 1, 2, 0x80000000],
+        }
+    }
+
+    #[test]
+    fn unique_stems() {
+        let stems = stems_of(&[Some("a"), Some("a_2"), Some("a"), None, Some("A"), Some("")]);
+        assert_eq!(
+            stems,
+            vec![
+                Some("a".into()),
+                Some("a_2".into()),
+                Some("a_3".into()),
+                None,
+                Some("A_4".into()),
+                Some("script_5".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn project_round_trip() {
+        let dir = std::env::temp_dir().join(format!("gm8decompiler-gmx-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let assets = assets();
+        write_project(&dir, &assets).unwrap();
+        let read = read_project(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(read.version, GameVersion::GameMaker8_0);
+        assert_eq!((read.game_id, read.guid), (assets.game_id, assets.guid));
+        assert_eq!((read.last_instance_id, read.last_tile_id), (100002, 10000003));
+        assert_eq!(read.dx_dll, assets.dx_dll);
+        assert_eq!(read.ico_file_raw, assets.ico_file_raw);
+        assert_eq!(read.library_init_strings[0].0, assets.library_init_strings[0].0);
+        assert_eq!(read.constants[0].expression.0, assets.constants[0].expression.0);
+        assert_eq!(read.help_dialog.caption.0, assets.help_dialog.caption.0);
+        assert_eq!(read.help_dialog.info.0, assets.help_dialog.info.0);
+        assert_eq!((read.settings.scaling, read.settings.clear_colour, read.settings.priority), (2, 0x654321, 1));
+        assert!(read.settings.interpolate_pixels && read.settings.log_errors && !read.settings.vsync);
+
+        let sprite = read.sprites[0].as_ref().unwrap();
+        assert_eq!((sprite.origin_x, sprite.origin_y), (0, 1));
+        assert_eq!(sprite.frames[0].data, assets.sprites[0].as_ref().unwrap().frames[0].data);
+        assert_eq!(&*sprite.colliders[0].data, &[true, false]);
+
+        let sound = read.sounds[0].as_ref().unwrap();
+        assert_eq!((sound.source.0.as_ref(), sound.data.as_deref()), (&b"C:\\hit.wav"[..], Some(&b"RIFF....WAVE"[..])));
+        assert_eq!((sound.volume, sound.pan, sound.preload), (0.75, 0.25, false));
+        assert!(sound.fx.chorus && sound.fx.gargle && !sound.fx.echo);
+
+        assert!(read.backgrounds[0].is_none());
+        assert_eq!(read.backgrounds[1].as_ref().unwrap().data.as_deref(), Some(&[9, 8, 7, 255][..]));
+
+        let path = read.paths[0].as_ref().unwrap();
+        let points = path.points.iter().map(|p| (p.x, p.y, p.speed)).collect::<Vec<_>>();
+        assert_eq!(points, vec![(0.5, 1.0, 100.0), (-32.0, 16.0, 50.0)]);
+
+        let scripts =
+            read.scripts.iter().map(|s| s.as_ref().map(|s| (text(&s.name), text(&s.source)))).collect::<Vec<_>>();
+        assert_eq!(
+            scripts,
+            vec![
+                Some(("scr_a".into(), "return 1".into())),
+                None,
+                Some(("scr_a".into(), "return argument0\r\n".into())),
+            ]
+        );
+
+        let object = read.objects[0].as_ref().unwrap();
+        assert_eq!((object.sprite_index, object.depth, object.solid, object.visible), (0, -10, true, false));
+        assert_eq!(object.events.len(), 12);
+        let (sub, actions) = &object.events[3][0];
+        assert_eq!((*sub, actions.len(), actions[0].id), (0, 1, 603));
+        assert_eq!(text(&actions[0].param_strings[0]), "x += 1;\n// <step>");
+    }
+}
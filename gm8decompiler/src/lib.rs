@@ -1,5 +1,11 @@
+pub mod code;
 pub mod collision;
 pub mod deobfuscate;
+pub mod extract;
 pub mod gmk;
+pub mod gmx;
+pub mod lint;
 pub mod mappings;
+pub mod xref;
+mod xml;
 mod zlib;
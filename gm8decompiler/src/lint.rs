@@ -0,0 +1,444 @@
+//! Looks for likely bugs in a game's GML code without running it.
+//!
+//! Every script, object event, timeline moment, trigger condition and piece of room and instance creation code is
+//! parsed and checked for things GameMaker 8 either accepts or only complains about at runtime, but which are almost
+//! always mistakes: calls to functions which don't exist or with the wrong number of arguments, `=` used as a
+//! comparison, code which can never run, and local variables read before anything's been assigned to them.
+
+use crate::{
+    code::{self, Location, Visitor},
+    gmx::text,
+    mappings,
+};
+use gm8exe::{asset::CodeAction, GameAssets};
+use gml_parser::{
+    ast::{self, Expr, ExprKind, FunctionExpr, AST},
+    lexer::Lexer,
+    span::Span,
+    token::{Operator, Token},
+};
+use serde_json::{json, Value};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Something which works, but probably not the way it was meant to.
+    Warning,
+
+    /// Something which will stop the game compiling, or fail whenever it runs.
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// The kinds of problem the linter looks for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Check {
+    /// Code which doesn't parse.
+    ParseError,
+
+    /// A call to something which isn't a kernel function, script or extension function.
+    UnknownFunction,
+
+    /// A call to a kernel function with the wrong number of arguments.
+    ArgumentCount,
+
+    /// `=` in an expression, where it means `==`.
+    AssignmentComparison,
+
+    /// Statements after an `exit`, `return`, `break` or `continue` in the same block.
+    UnreachableCode,
+
+    /// A local variable which is read before anything's been assigned to it.
+    UninitialisedVariable,
+}
+
+impl Check {
+    pub const ALL: [Check; 6] = [
+        Check::ParseError,
+        Check::UnknownFunction,
+        Check::ArgumentCount,
+        Check::AssignmentComparison,
+        Check::UnreachableCode,
+        Check::UninitialisedVariable,
+    ];
+
+    /// The name of the check on the command line and in JSON output.
+    pub fn name(self) -> &'static str {
+        match self {
+            Check::ParseError => "parse-error",
+            Check::UnknownFunction => "unknown-function",
+            Check::ArgumentCount => "argument-count",
+            Check::AssignmentComparison => "assignment-comparison",
+            Check::UnreachableCode => "unreachable-code",
+            Check::UninitialisedVariable => "uninitialised-variable",
+        }
+    }
+
+    pub fn severity(self) -> Severity {
+        match self {
+            Check::ParseError | Check::UnknownFunction | Check::ArgumentCount => Severity::Error,
+            Check::AssignmentComparison | Check::UnreachableCode | Check::UninitialisedVariable => Severity::Warning,
+        }
+    }
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Check {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Check::ALL.iter().copied().find(|c| c.name() == s).ok_or_else(|| {
+            let names = Check::ALL.iter().map(|c| c.name()).collect::<Vec<_>>();
+            format!("unknown check '{}' (valid checks are {})", s, names.join("/"))
+        })
+    }
+}
+
+/// A problem found in some code.
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub check: Check,
+    pub location: Location,
+
+    /// The line and column the problem starts at in the code, both counting from 1.
+    pub line: usize,
+    pub column: usize,
+
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn severity(&self) -> Severity {
+        self.check.severity()
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "severity": self.severity().to_string(),
+            "check": self.check.name(),
+            "message": self.message,
+            "kind": self.location.kind,
+            "asset": self.location.asset,
+            "event": self.location.event,
+            "action": self.location.action,
+            "argument": self.location.argument,
+            "line": self.line,
+            "column": self.column,
+        })
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}, line {}, column {}: {}[{}]: {}",
+            self.location,
+            self.line,
+            self.column,
+            self.severity(),
+            self.check,
+            self.message
+        )
+    }
+}
+
+/// Lints all of a game's code, returning every problem found in the order the code appears in the game.
+pub fn lint(assets: &GameAssets) -> Vec<Diagnostic> {
+    let mut linter = Linter {
+        kernel_functions: mappings::make_kernel_functions_map(),
+        scripts: assets.scripts.iter().flatten().map(|s| s.name.0.as_ref()).collect(),
+        extension_functions: assets
+            .extensions
+            .iter()
+            .flat_map(|e| e.files.iter().flat_map(|f| f.functions.iter()))
+            .map(|f| f.name.0.as_ref())
+            .collect(),
+        diagnostics: Vec::new(),
+        location: None,
+        unassigned: HashSet::new(),
+    };
+
+    code::visit(assets, &mut linter);
+    linter.diagnostics
+}
+
+struct Linter<'a> {
+    /// Every kernel function, with how many arguments it takes if that's fixed.
+    kernel_functions: HashMap<&'static [u8], Option<usize>>,
+    scripts: HashSet<&'a [u8]>,
+    extension_functions: HashSet<&'a [u8]>,
+
+    diagnostics: Vec<Diagnostic>,
+
+    /// Where the code being linted is.
+    location: Option<Location>,
+
+    /// Local variables which have been declared with `var` in the code being linted but not assigned yet.
+    unassigned: HashSet<Vec<u8>>,
+}
+
+impl<'a> Visitor for Linter<'a> {
+    fn code(&mut self, source: &[u8], location: &Location) {
+        self.location = Some(location.clone());
+        self.unassigned.clear();
+        match AST::new(source) {
+            Ok(ast) => self.block(source, &ast),
+            Err(e) => self.parse_error(e),
+        }
+    }
+
+    fn expression(&mut self, source: &[u8], location: &Location) {
+        self.location = Some(location.clone());
+        self.unassigned.clear();
+        match AST::expression(source) {
+            Ok(expr) => self.read(source, &expr),
+            Err(e) => self.parse_error(e),
+        }
+    }
+
+    fn action(&mut self, action: &CodeAction, location: &Location) {
+        // Normal actions which call a kernel function directly
+        if action.action_kind == 0
+            && action.execution_type == 1
+            && !self.kernel_functions.contains_key(action.fn_name.0.as_ref())
+        {
+            self.location = Some(location.clone());
+            let message = format!("action calls unknown function `{}`", text(&action.fn_name));
+            self.report(Check::UnknownFunction, Span::default(), message);
+        }
+    }
+}
+
+impl<'a> Linter<'a> {
+    fn report(&mut self, check: Check, span: Span, message: String) {
+        let location = self.location.clone().expect("reported a problem outside of any code");
+        self.diagnostics.push(Diagnostic { check, location, line: span.line, column: span.column, message });
+    }
+
+    fn parse_error(&mut self, error: ast::Error) {
+        self.report(Check::ParseError, error.span, error.message);
+    }
+
+    fn block(&mut self, source: &[u8], statements: &[Expr]) {
+        // The keyword which made the rest of the block unreachable, until a case label makes it reachable again
+        let mut ended_by = None;
+        let mut reported = false;
+        for statement in statements {
+            if matches!(statement.kind, ExprKind::Case(_) | ExprKind::Default) {
+                ended_by = None;
+            } else if let Some(keyword) = ended_by.filter(|_| !reported) {
+                // Only the first unreachable statement is reported
+                let message = format!("this code can never run, since it comes after `{}`", keyword);
+                self.report(Check::UnreachableCode, statement.span, message);
+                reported = true;
+            }
+            if ended_by.is_none() {
+                reported = false;
+                ended_by = match statement.kind {
+                    ExprKind::Exit => Some("exit"),
+                    ExprKind::Return(_) => Some("return"),
+                    ExprKind::Break => Some("break"),
+                    ExprKind::Continue => Some("continue"),
+                    _ => None,
+                };
+            }
+            self.statement(source, statement);
+        }
+    }
+
+    fn statement(&mut self, source: &[u8], statement: &Expr) {
+        match &statement.kind {
+            // Binary operators can only be assignments at this level
+            ExprKind::Binary(binary) => {
+                self.read(source, &binary.right);
+                if binary.op == Operator::Assign {
+                    self.write(source, &binary.left);
+                } else {
+                    self.read(source, &binary.left);
+                }
+            },
+            ExprKind::Function(call) => self.call(source, statement.span, call),
+            ExprKind::Group(statements) => self.block(source, statements),
+            ExprKind::If(if_expr) => {
+                self.read(source, &if_expr.cond);
+                self.statement(source, &if_expr.body);
+                if let Some(else_body) = &if_expr.else_body {
+                    self.statement(source, else_body);
+                }
+            },
+            ExprKind::For(for_expr) => {
+                self.statement(source, &for_expr.start);
+                self.read(source, &for_expr.cond);
+                self.statement(source, &for_expr.body);
+                self.statement(source, &for_expr.step);
+            },
+            ExprKind::DoUntil(do_until) => {
+                self.statement(source, &do_until.body);
+                self.read(source, &do_until.cond);
+            },
+            ExprKind::Repeat(repeat) => {
+                self.read(source, &repeat.count);
+                self.statement(source, &repeat.body);
+            },
+            ExprKind::Switch(switch) => {
+                self.read(source, &switch.input);
+                self.statement(source, &switch.body);
+            },
+            ExprKind::With(with) => {
+                self.read(source, &with.target);
+                self.statement(source, &with.body);
+            },
+            ExprKind::While(while_expr) => {
+                self.read(source, &while_expr.cond);
+                self.statement(source, &while_expr.body);
+            },
+            ExprKind::Var(var) => self.unassigned.extend(var.vars.iter().map(|v| v.to_vec())),
+            ExprKind::Case(value) | ExprKind::Return(value) => self.read(source, value),
+            _ => (),
+        }
+    }
+
+    /// Checks the left-hand side of an assignment.
+    fn write(&mut self, source: &[u8], target: &Expr) {
+        match &target.kind {
+            ExprKind::LiteralIdentifier(name) => {
+                self.unassigned.remove(*name);
+            },
+            // Assigning to an element of a local array makes it an array, so that counts as assigning it
+            ExprKind::Binary(binary) if binary.op == Operator::Index => {
+                self.read(source, &binary.right);
+                self.write(source, &binary.left);
+            },
+            _ => self.read(source, target),
+        }
+    }
+
+    /// Checks an expression whose value gets read.
+    fn read(&mut self, source: &[u8], expr: &Expr) {
+        match &expr.kind {
+            // Only report each variable once
+            ExprKind::LiteralIdentifier(name) if self.unassigned.remove(*name) => {
+                let message = format!(
+                    "local variable `{}` is read before anything's assigned to it",
+                    String::from_utf8_lossy(name)
+                );
+                self.report(Check::UninitialisedVariable, expr.span, message);
+            },
+            ExprKind::Unary(unary) => self.read(source, &unary.child),
+            // The right-hand side of a deref is a field name, not a variable
+            ExprKind::Binary(binary) if binary.op == Operator::Deref => self.read(source, &binary.left),
+            ExprKind::Binary(binary) => {
+                // The parser turns `=` into `==` in expressions, so look at the code in between to see which it was
+                let between = source.get(binary.left.span.end..binary.right.span.start).unwrap_or_default();
+                if binary.op == Operator::Equal && Lexer::new(between).any(|t| t == Token::Operator(Operator::Assign)) {
+                    let message = "`=` used as a comparison, which is easy to mistake for an assignment; use `==`";
+                    self.report(Check::AssignmentComparison, expr.span, message.into());
+                }
+                self.read(source, &binary.left);
+                self.read(source, &binary.right);
+            },
+            ExprKind::Function(call) => self.call(source, expr.span, call),
+            // Array indices
+            ExprKind::Group(exprs) => exprs.iter().for_each(|e| self.read(source, e)),
+            _ => (),
+        }
+    }
+
+    fn call(&mut self, source: &[u8], span: Span, call: &FunctionExpr) {
+        let name = String::from_utf8_lossy(call.name);
+        match self.kernel_functions.get(call.name) {
+            Some(Some(count)) if *count != call.params.len() => {
+                let plural = if *count == 1 { "" } else { "s" };
+                let message =
+                    format!("`{}` takes {} argument{}, but was given {}", name, count, plural, call.params.len());
+                self.report(Check::ArgumentCount, span, message);
+            },
+            Some(_) => (),
+            None if self.scripts.contains(call.name) || self.extension_functions.contains(call.name) => (),
+            None => self.report(Check::UnknownFunction, span, format!("unknown function or script `{}`", name)),
+        }
+        call.params.iter().for_each(|param| self.read(source, param));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lints some code as if it were a script, in a game with the given scripts.
+    fn lint_code(source: &str, scripts: &[&'static str]) -> Vec<(Check, usize, usize, String)> {
+        let mut linter = Linter {
+            kernel_functions: mappings::make_kernel_functions_map(),
+            scripts: scripts.iter().map(|s| s.as_bytes()).collect(),
+            extension_functions: HashSet::new(),
+            diagnostics: Vec::new(),
+            location: None,
+            unassigned: HashSet::new(),
+        };
+        let location = Location { kind: "script", asset: "scr_test".into(), event: None, action: None, argument: None };
+        linter.code(source.as_bytes(), &location);
+        linter.diagnostics.into_iter().map(|d| (d.check, d.line, d.column, d.message)).collect()
+    }
+
+    #[test]
+    fn unknown_functions() {
+        let found = lint_code("scr_known()\nshow_message(not_a_function(1))\nscr_unknown()", &["scr_known"]);
+        assert_eq!(
+            found,
+            [
+                (Check::UnknownFunction, 2, 14, "unknown function or script `not_a_function`".into()),
+                (Check::UnknownFunction, 3, 1, "unknown function or script `scr_unknown`".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn argument_counts() {
+        let found = lint_code("a = point_distance(0, 0, 1)\nshow_message()\nb = choose(1, 2, 3, 4, 5, 6, 7)", &[]);
+        assert_eq!(
+            found,
+            [
+                (Check::ArgumentCount, 1, 5, "`point_distance` takes 4 arguments, but was given 3".into()),
+                (Check::ArgumentCount, 2, 1, "`show_message` takes 1 argument, but was given 0".into()),
+            ]
+        );
+        assert!(lint_code("instance_create(x, y, other.object_index)", &[]).is_empty());
+    }
+
+    #[test]
+    fn unassigned_variables() {
+        let found = lint_code("var a, b, c, d;\nb = 1\nc[0] = 2\nx = b + c + a + a\nd += 1", &[]);
+        let places = found.iter().map(|(check, line, column, _)| (*check, *line, *column)).collect::<Vec<_>>();
+        assert_eq!(places, [(Check::UninitialisedVariable, 4, 13), (Check::UninitialisedVariable, 5, 1)]);
+        assert_eq!(found[0].3, "local variable `a` is read before anything's assigned to it");
+    }
+
+    #[test]
+    fn warnings() {
+        let found =
+            lint_code("if (a = 1) exit\nswitch a { case 1: exit b = 2 case 2: break }\nexit\nc = 3\nd = 4", &[]);
+        let checks = found.iter().map(|(check, line, ..)| (*check, *line)).collect::<Vec<_>>();
+        assert_eq!(
+            checks,
+            [(Check::AssignmentComparison, 1), (Check::UnreachableCode, 2), (Check::UnreachableCode, 4)]
+        );
+    }
+}
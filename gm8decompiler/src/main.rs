@@ -4,7 +4,7 @@ use std::{
     path::{Path, PathBuf},
     process,
 };
-use gm8decompiler::{deobfuscate, gmk};
+use gm8decompiler::{deobfuscate, extract, gmk, gmx, lint, xref};
 
 static INFO_STRING: &str = concat!(
     "GM8Decompiler v",
@@ -44,13 +44,32 @@ fn is_cmd(_argv_0: &str) -> bool {
 fn pause(_tip: bool) {}
 
 fn main() {
-    println!("{}", INFO_STRING);
-
     let args: Vec<String> = env::args().collect();
     assert!(!args.is_empty());
     let process_path = args[0].as_str();
     let should_pause = is_cmd(process_path);
 
+    // `lint` and `xref` print their own header, so that they can leave it out of JSON output
+    match args.get(1).map(String::as_str) {
+        Some("lint") => {
+            lint_main(process_path, &args[2..]);
+            return
+        },
+        Some("xref") => {
+            xref_main(process_path, &args[2..]);
+            return
+        },
+        _ => (),
+    }
+
+    println!("{}", INFO_STRING);
+
+    // `extract` is a separate mode with its own options
+    if args.get(1).map(String::as_str) == Some("extract") {
+        extract_main(process_path, &args[2..]);
+        return
+    }
+
     // set up getopts to parse our command line args
     let mut opts = getopts::Options::new();
     opts.optflag("h", "help", "print this help message")
@@ -59,7 +78,9 @@ fn main() {
         .optopt("d", "deobfuscate", "set deobfuscation mode auto/on/off (default=auto)", "")
         .optflag("p", "preserve", "preserve broken events (instead of trying to fix them)")
         .optflag("s", "singlethread", "decompile gamedata synchronously (lower RAM usage)")
-        .optopt("o", "output", "specify output filename", "FILE");
+        .optopt("f", "format", "set output format gmk/gmx/exe (default=gmk)", "")
+        .optopt("o", "output", "specify output filename", "FILE")
+        .optflag("", "experimental-legacy", "read GameMaker 5, 6 and 7 games (experimental)");
 
     // parse command line arguments
     let matches = match opts.parse(&args[1..]) {
@@ -87,6 +108,9 @@ fn main() {
         // TODO: Get a better argument parser in general.
         println!(
             "Usage: {} FILENAME [options]
+       {} extract FILENAME [options]   (see '{} extract --help')
+       {} lint FILENAME [options]      (see '{} lint --help')
+       {} xref FILENAME [NAME...] [options]   (see '{} xref --help')
 
 Options:
     -h, --help                print this help message
@@ -95,8 +119,12 @@ Options:
     -d, --deobfuscate <mode>  set deobfuscation mode auto/on/off (defaults to auto)
     -p, --preserve            preserve broken events (instead of trying to fix them)
     -s, --singlethread        decompile gamedata synchronously (lower RAM usage)
-    -o, --output <file>       specify output filename",
-            process_path
+    -f, --format <format>     set output format gmk/gmx/exe (defaults to gmk, gmx writes a project folder,
+                              exe rebuilds the game using the input as the runner)
+                              FILENAME can also be a project folder, which is converted to a project file
+    -o, --output <file>       specify output filename
+    --experimental-legacy     read GameMaker 5, 6 and 7 games, which isn't well tested yet",
+            process_path, process_path, process_path, process_path, process_path, process_path, process_path
         );
         if should_pause {
             pause(true);
@@ -130,8 +158,18 @@ Options:
             process::exit(1);
         },
     };
+    let format = match matches.opt_str("f").as_deref() {
+        Some("gmx") => Format::Gmx,
+        Some("exe") => Format::Exe,
+        Some("gmk") | None => Format::Gmk,
+        Some(x) => {
+            eprintln!("Invalid output format: {} (valid formats are gmk/gmx/exe)", x);
+            process::exit(1);
+        },
+    };
     let out_path = matches.opt_str("o");
     let preserve = matches.opt_present("p");
+    let legacy = matches.opt_present("experimental-legacy");
     // no_pause extracted before help
 
     // print flags for confirmation
@@ -150,22 +188,31 @@ Options:
     if singlethread {
         println!("Single-threaded mode ON: process will not start new threads (slow)");
     }
+    match format {
+        Format::Gmx => println!("Output format GMX: will write a project folder"),
+        Format::Exe => println!("Output format EXE: will rebuild the game, using the input as the runner"),
+        Format::Gmk => (),
+    }
     if let Some(path) = &out_path {
         println!("Specified output path: {}", path);
     }
     if preserve {
         println!("Preserve mode ON: broken events will be preserved and will not be fixed");
     }
+    if legacy {
+        println!("Legacy mode ON: will also look for GameMaker 5, 6 and 7 games (experimental)");
+    }
 
     // resolve input path
     let input_path = Path::new(input);
-    if !input_path.is_file() {
+    if !input_path.is_file() && !input_path.is_dir() {
         eprintln!("Input file '{}' does not exist.", input);
         process::exit(1);
     }
 
     // allow decompile to handle the rest of main
-    if let Err(e) = decompile(input_path, out_path, !lazy, !singlethread, verbose, deobfuscate, !preserve) {
+    let result = decompile(input_path, out_path, !lazy, !singlethread, legacy, verbose, deobfuscate, !preserve, format);
+    if let Err(e) = result {
         eprintln!("Error parsing gamedata:\n{}", e);
         process::exit(1);
     }
@@ -175,24 +222,332 @@ Options:
     }
 }
 
+/// Help text for the options from `reader_options`.
+const READER_HELP: &str = "    -h, --help                print this help message
+    -l, --lazy                disable various data integrity checks
+    -v, --verbose             enable verbose logging for decompilation
+    -s, --singlethread        decompile gamedata synchronously (lower RAM usage)
+    --experimental-legacy     read GameMaker 5, 6 and 7 games, which isn't well tested yet";
+
+/// Options shared by the modes which read a game with `read_game`.
+fn reader_options() -> getopts::Options {
+    let mut opts = getopts::Options::new();
+    opts.optflag("h", "help", "print this help message")
+        .optflag("l", "lazy", "disable various data integrity checks")
+        .optflag("v", "verbose", "enable verbose logging for decompilation")
+        .optflag("s", "singlethread", "decompile gamedata synchronously (lower RAM usage)")
+        .optflag("", "experimental-legacy", "read GameMaker 5, 6 and 7 games (experimental)");
+    opts
+}
+
+/// Reads and parses a game with the options from `reader_options`.
+/// Logs go to stderr so they don't get mixed up with JSON output.
+fn read_game(path: &Path, matches: &getopts::Matches) -> Result<gm8exe::GameAssets, String> {
+    let file = fs::read(path).map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let logger = if matches.opt_present("v") { Some(|msg: &str| eprintln!("{}", msg)) } else { None };
+    let strict = !matches.opt_present("l");
+    let multithread = !matches.opt_present("s");
+    let legacy = matches.opt_present("experimental-legacy");
+    gm8exe::reader::from_exe(file, logger, strict, multithread, legacy).map_err(|e| format!("Reader error: {}", e))
+}
+
+fn extract_main(process_path: &str, args: &[String]) {
+    let mut opts = reader_options();
+    opts.optopt("k", "kinds", "comma-separated asset kinds to extract", "KINDS")
+        .optmulti("n", "name", "extract the asset with this name", "NAME")
+        .optopt("r", "regex", "extract assets whose names match this regex", "REGEX")
+        .optopt("o", "output", "specify output folder", "DIR");
+
+    let matches = match opts.parse(args) {
+        Ok(matches) => matches,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        },
+    };
+
+    if matches.opt_present("h") || matches.free.len() != 1 {
+        println!(
+            "Usage: {} extract FILENAME [options]
+
+Options:
+{}
+    -k, --kinds <kinds>       comma-separated asset kinds to extract (defaults to all of
+                              sprites,backgrounds,sounds,scripts,objects,rooms)
+    -n, --name <name>         extract the asset with this exact name (can be given more than once)
+    -r, --regex <regex>       extract assets whose names match this regex
+    -o, --output <dir>        specify output folder (defaults to FILENAME_assets)",
+            process_path, READER_HELP
+        );
+        process::exit(if matches.opt_present("h") { 0 } else { 1 });
+    }
+
+    let kinds = match matches.opt_str("k") {
+        Some(list) => match list.split(',').map(|k| k.trim().parse()).collect::<Result<Vec<_>, _>>() {
+            Ok(kinds) => kinds,
+            Err(e) => {
+                eprintln!("Invalid asset kinds: {}", e);
+                process::exit(1);
+            },
+        },
+        None => extract::Kind::ALL.to_vec(),
+    };
+    let pattern = match matches.opt_str("r").map(|r| regex::Regex::new(&r)).transpose() {
+        Ok(pattern) => pattern,
+        Err(e) => {
+            eprintln!("Invalid regex: {}", e);
+            process::exit(1);
+        },
+    };
+    let filter = extract::Filter { kinds, names: matches.opt_strs("n"), pattern };
+
+    let in_path = Path::new(&matches.free[0]);
+    let out_path = match matches.opt_str("o") {
+        Some(p) => PathBuf::from(p),
+        None => {
+            let mut name = in_path.file_stem().unwrap_or_default().to_os_string();
+            name.push("_assets");
+            in_path.with_file_name(name)
+        },
+    };
+
+    let result = read_game(in_path, &matches).and_then(|assets| {
+        println!("Successfully parsed game!");
+        extract::extract(&assets, &out_path, &filter)
+            .map_err(|e| format!("Failed to extract assets to '{}': {}", out_path.display(), e))
+    });
+    match result {
+        Ok(count) => println!("Extracted {} assets to '{}'", count, out_path.display()),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        },
+    }
+}
+
+fn lint_main(process_path: &str, args: &[String]) {
+    let mut opts = reader_options();
+    opts.optflag("j", "json", "print diagnostics as JSON");
+    opts.optopt("a", "allow", "comma-separated checks to skip", "CHECKS");
+
+    let matches = match opts.parse(args) {
+        Ok(matches) => matches,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        },
+    };
+
+    let json = matches.opt_present("j");
+    if !json {
+        println!("{}", INFO_STRING);
+    }
+
+    if matches.opt_present("h") || matches.free.len() != 1 {
+        let checks = lint::Check::ALL.iter().map(|c| c.name()).collect::<Vec<_>>();
+        println!(
+            "Usage: {} lint FILENAME [options]
+
+Checks a game's code for likely bugs, exiting with 1 if any are found.
+
+Options:
+{}
+    -j, --json                print diagnostics as a JSON array
+    -a, --allow <checks>      comma-separated checks to skip, out of
+                              {}",
+            process_path,
+            READER_HELP,
+            checks.join(",")
+        );
+        process::exit(if matches.opt_present("h") { 0 } else { 1 });
+    }
+
+    let allowed = match matches.opt_str("a") {
+        Some(list) => match list.split(',').map(|c| c.trim().parse()).collect::<Result<Vec<lint::Check>, _>>() {
+            Ok(checks) => checks,
+            Err(e) => {
+                eprintln!("Invalid checks: {}", e);
+                process::exit(1);
+            },
+        },
+        None => Vec::new(),
+    };
+
+    let assets = match read_game(Path::new(&matches.free[0]), &matches) {
+        Ok(assets) => assets,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        },
+    };
+
+    let mut diagnostics = lint::lint(&assets);
+    diagnostics.retain(|d| !allowed.contains(&d.check));
+    if json {
+        let list = diagnostics.iter().map(lint::Diagnostic::to_json).collect::<Vec<_>>();
+        println!("{}", serde_json::to_string_pretty(&list).unwrap_or_default());
+    } else {
+        for diagnostic in &diagnostics {
+            println!("{}", diagnostic);
+        }
+        let errors = diagnostics.iter().filter(|d| d.severity() == lint::Severity::Error).count();
+        println!("Found {} error(s) and {} warning(s)", errors, diagnostics.len() - errors);
+    }
+    if !diagnostics.is_empty() {
+        process::exit(1);
+    }
+}
+
+fn xref_main(process_path: &str, args: &[String]) {
+    let mut opts = reader_options();
+    opts.optopt("k", "kinds", "comma-separated reference kinds to list", "KINDS")
+        .optopt("r", "regex", "list references to names which match this regex", "REGEX")
+        .optflag("j", "json", "print references as JSON")
+        .optopt("o", "output", "write references to a JSON file", "FILE");
+
+    let matches = match opts.parse(args) {
+        Ok(matches) => matches,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        },
+    };
+
+    let json = matches.opt_present("j");
+    if !json {
+        println!("{}", INFO_STRING);
+    }
+
+    if matches.opt_present("h") || matches.free.is_empty() {
+        let kinds = xref::Kind::ALL.iter().map(|k| k.name()).collect::<Vec<_>>();
+        println!(
+            "Usage: {} xref FILENAME [NAME...] [options]
+
+Lists everywhere the given names are referred to in a game, or every reference if no names are given.
+Names can have an owner like global.flag, in which case only references with that owner are listed.
+
+Options:
+{}
+    -k, --kinds <kinds>       comma-separated reference kinds to list (defaults to all of
+                              {})
+    -r, --regex <regex>       list references to names which match this regex
+    -j, --json                print references as JSON, grouped by name
+    -o, --output <file>       write references to a JSON file instead of printing them",
+            process_path,
+            READER_HELP,
+            kinds.join(",")
+        );
+        process::exit(if matches.opt_present("h") { 0 } else { 1 });
+    }
+
+    let kinds = match matches.opt_str("k") {
+        Some(list) => match list.split(',').map(|k| k.trim().parse()).collect::<Result<Vec<_>, _>>() {
+            Ok(kinds) => kinds,
+            Err(e) => {
+                eprintln!("Invalid reference kinds: {}", e);
+                process::exit(1);
+            },
+        },
+        None => Vec::new(),
+    };
+    let pattern = match matches.opt_str("r").map(|r| regex::Regex::new(&r)).transpose() {
+        Ok(pattern) => pattern,
+        Err(e) => {
+            eprintln!("Invalid regex: {}", e);
+            process::exit(1);
+        },
+    };
+    let filter = xref::Filter { kinds, names: matches.free[1..].to_vec(), pattern };
+
+    let assets = match read_game(Path::new(&matches.free[0]), &matches) {
+        Ok(assets) => assets,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        },
+    };
+
+    let index = xref::Index::new(&assets);
+    let found = index.find(&filter);
+    let out_path = matches.opt_str("o");
+    if json || out_path.is_some() {
+        let list = found
+            .iter()
+            .map(|(name, refs)| (name.clone(), refs.iter().map(|r| r.to_json()).collect()))
+            .collect::<serde_json::Map<_, _>>();
+        let text = serde_json::to_string_pretty(&list).unwrap_or_default();
+        match out_path {
+            Some(path) => {
+                if let Err(e) = fs::write(&path, text) {
+                    eprintln!("Failed to write '{}': {}", path, e);
+                    process::exit(1);
+                }
+                if !json {
+                    println!("Written references to {} names to '{}'", found.len(), path);
+                }
+            },
+            None => println!("{}", text),
+        }
+    } else {
+        for (name, refs) in &found {
+            println!("{}", name);
+            for reference in refs {
+                println!("    {}", reference);
+            }
+        }
+        println!("Found {} reference(s) to {} name(s)", found.values().map(Vec::len).sum::<usize>(), found.len());
+    }
+}
+
+/// What `decompile` writes its output as.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// A project file for the game's GameMaker version
+    Gmk,
+
+    /// A project folder, see the gmx module
+    Gmx,
+
+    /// A game executable, rebuilt from the assets using the input game as the runner
+    Exe,
+}
+
+#[allow(clippy::too_many_arguments)]
 fn decompile(
     in_path: &Path,
     out_path: Option<String>,
     strict: bool,
     multithread: bool,
+    legacy: bool,
     verbose: bool,
     deobf_mode: deobfuscate::Mode,
     fix_events: bool,
+    format: Format,
 ) -> Result<(), String> {
-    // slurp in file contents
-    let file = fs::read(&in_path).map_err(|e| format!("Failed to read '{}': {}", in_path.display(), e))?;
-
-    // parse (entire) gamedata
     let logger = if verbose { Some(|msg: &str| println!("{}", msg)) } else { None };
-    let mut assets = gm8exe::reader::from_exe(file, logger, strict, multithread) // huge call
-        .map_err(|e| format!("Reader error: {}", e))?;
-
-    println!("Successfully parsed game!");
+    let (mut assets, runner) = if in_path.is_dir() {
+        // a project folder written with '-f gmx', to be turned into a project file
+        if format != Format::Gmk {
+            return Err("A project folder can only be converted to a project file (-f gmk)".into())
+        }
+        let assets = gmx::read_project(in_path)
+            .map_err(|e| format!("Failed to read project folder '{}': {}", in_path.display(), e))?;
+        println!("Successfully read project!");
+        (assets, None)
+    } else {
+        // slurp in file contents
+        let file = fs::read(in_path).map_err(|e| format!("Failed to read '{}': {}", in_path.display(), e))?;
+
+        // the reader decrypts the file in place, so rebuilding needs its own copy of the runner
+        let runner = if format == Format::Exe { Some(file.clone()) } else { None };
+
+        // parse (entire) gamedata
+        let assets = gm8exe::reader::from_exe(file, logger, strict, multithread, legacy) // huge call
+            .map_err(|e| format!("Reader error: {}", e))?;
+
+        println!("Successfully parsed game!");
+        (assets, runner)
+    };
 
     //Do we want to deobfuscate, yes or no?
     let deobfuscate = match deobf_mode {
@@ -241,17 +596,24 @@ fn decompile(
             .for_each(|ev| fix_event(ev));
     }
 
-    // warn user if they specified .gmk for 8.0 or .gm81 for 8.0
+    // nothing here writes GM5's .gmd format, but GameMaker 6 can open GM5 games, so they get written as .gm6 files
+    if format == Format::Gmk && assets.version == GameVersion::GameMaker5_0 {
+        println!("This is a GameMaker 5 game, so it'll be written as a GameMaker 6 project");
+        assets.version = GameVersion::GameMaker6_0;
+    }
+
+    // warn user if they specified the wrong GameMaker extension for the game's version, like .gm81 for 8.0
     let out_expected_ext = match assets.version {
-        GameVersion::GameMaker8_0 => "gmk",
+        GameVersion::GameMaker5_0 | GameVersion::GameMaker6_0 => "gm6",
+        GameVersion::GameMaker7_0 | GameVersion::GameMaker8_0 => "gmk",
         GameVersion::GameMaker8_1 => "gm81",
     };
     let out_path = match out_path {
+        Some(p) if format != Format::Gmk => PathBuf::from(p),
         Some(p) => {
             let path = PathBuf::from(p);
-            match (assets.version, path.extension().and_then(|oss| oss.to_str())) {
-                (GameVersion::GameMaker8_0, Some(extension @ "gm81"))
-                | (GameVersion::GameMaker8_1, Some(extension @ "gmk")) => {
+            match path.extension().and_then(|oss| oss.to_str()) {
+                Some(extension @ ("gm6" | "gmk" | "gm81")) if extension != out_expected_ext => {
                     println!(
                         concat!(
                             "***WARNING*** You've specified an output file '{}'",
@@ -261,6 +623,8 @@ fn decompile(
                         path.display(),
                         extension,
                         match assets.version {
+                            GameVersion::GameMaker5_0 | GameVersion::GameMaker6_0 => "GameMaker 6",
+                            GameVersion::GameMaker7_0 => "GameMaker 7",
                             GameVersion::GameMaker8_0 => "GameMaker 8.0",
                             GameVersion::GameMaker8_1 => "GameMaker 8.1",
                         },
@@ -272,9 +636,14 @@ fn decompile(
             }
             path
         },
+        None if format == Format::Exe => {
+            // not just a new extension, or it'd be the input file
+            let stem = in_path.file_stem().and_then(|oss| oss.to_str()).unwrap_or("game");
+            in_path.with_file_name(format!("{}-rebuilt.exe", stem))
+        },
         None => {
             let mut path = PathBuf::from(in_path);
-            path.set_extension(out_expected_ext);
+            path.set_extension(if format == Format::Gmx { "gmx" } else { out_expected_ext });
             path
         },
     };
@@ -283,8 +652,28 @@ fn decompile(
         deobfuscate::process(&mut assets);
     }
 
-    let mut gmk = fs::File::create(&out_path)
-        .map_err(|e| format!("Failed to create output file '{}': {}", out_path.display(), e))?;
+    if let Some(runner) = runner {
+        println!("Rebuilding game...");
+        let exe = gm8exe::writer::to_exe(&runner, &assets, logger, multithread)
+            .map_err(|e| format!("Failed to rebuild game: {}", e))?;
+        fs::write(&out_path, exe)
+            .map_err(|e| format!("Failed to write output file '{}': {}", out_path.display(), e))?;
+        println!("Successfully written game to '{}'", out_path.display());
+        return Ok(())
+    }
+
+    if format == Format::Gmx {
+        println!("Writing project folder...");
+        gmx::write_project(&out_path, &assets)
+            .map_err(|e| format!("Failed to write project folder '{}': {}", out_path.display(), e))?;
+        println!("Successfully written project to '{}'", out_path.display());
+        return Ok(())
+    }
+
+    let mut gmk = gmk::GmkWriter::new(
+        fs::File::create(&out_path)
+            .map_err(|e| format!("Failed to create output file '{}': {}", out_path.display(), e))?,
+    );
 
     println!("Writing {} header...", out_expected_ext);
     gmk::write_header(&mut gmk, assets.version, assets.game_id, assets.guid)
@@ -292,52 +681,62 @@ fn decompile(
 
     println!("Writing {} settings...", out_expected_ext);
     let ico_file = assets.ico_file_raw.take();
-    gmk::write_settings(&mut gmk, &assets.settings, ico_file, assets.version)
-        .map_err(|e| format!("Failed to write settings block: {}", e))?;
-
-    println!("Writing {} triggers...", assets.triggers.len());
-    gmk::write_asset_list(&mut gmk, &assets.triggers, gmk::write_trigger, assets.version, multithread)
-        .map_err(|e| format!("Failed to write triggers: {}", e))?;
-
-    gmk::write_timestamp(&mut gmk).map_err(|e| format!("Failed to write timestamp: {}", e))?;
-
-    println!("Writing {} constants...", assets.constants.len());
-    gmk::write_constants(&mut gmk, &assets.constants).map_err(|e| format!("Failed to write constants: {}", e))?;
+    gmk::write_settings(
+        &mut gmk,
+        &assets.settings,
+        ico_file,
+        &assets.constants,
+        &assets.included_files,
+        assets.version,
+    )
+    .map_err(|e| format!("Failed to write settings block: {}", e))?;
+
+    // Triggers and constants got their own chunks in GM8
+    if !assets.version.is_legacy() {
+        println!("Writing {} triggers...", assets.triggers.len());
+        gmk::write_asset_list(&mut gmk, &assets.triggers, gmk::write_trigger, 800, assets.version, multithread)
+            .map_err(|e| format!("Failed to write triggers: {}", e))?;
+
+        gmk::write_timestamp(&mut gmk).map_err(|e| format!("Failed to write timestamp: {}", e))?;
+
+        println!("Writing {} constants...", assets.constants.len());
+        gmk::write_constants(&mut gmk, &assets.constants).map_err(|e| format!("Failed to write constants: {}", e))?;
+    }
 
     println!("Writing {} sounds...", assets.sounds.len());
-    gmk::write_asset_list(&mut gmk, &assets.sounds, gmk::write_sound, assets.version, multithread)
+    gmk::write_asset_list(&mut gmk, &assets.sounds, gmk::write_sound, 400, assets.version, multithread)
         .map_err(|e| format!("Failed to write sounds: {}", e))?;
 
     println!("Writing {} sprites...", assets.sprites.len());
-    gmk::write_asset_list(&mut gmk, &assets.sprites, gmk::write_sprite, assets.version, multithread)
+    gmk::write_asset_list(&mut gmk, &assets.sprites, gmk::write_sprite, 400, assets.version, multithread)
         .map_err(|e| format!("Failed to write sprites: {}", e))?;
 
     println!("Writing {} backgrounds...", assets.backgrounds.len());
-    gmk::write_asset_list(&mut gmk, &assets.backgrounds, gmk::write_background, assets.version, multithread)
+    gmk::write_asset_list(&mut gmk, &assets.backgrounds, gmk::write_background, 400, assets.version, multithread)
         .map_err(|e| format!("Failed to write backgrounds: {}", e))?;
 
     println!("Writing {} paths...", assets.paths.len());
-    gmk::write_asset_list(&mut gmk, &assets.paths, gmk::write_path, assets.version, multithread)
+    gmk::write_asset_list(&mut gmk, &assets.paths, gmk::write_path, 420, assets.version, multithread)
         .map_err(|e| format!("Failed to write paths: {}", e))?;
 
     println!("Writing {} scripts...", assets.scripts.len());
-    gmk::write_asset_list(&mut gmk, &assets.scripts, gmk::write_script, assets.version, multithread)
+    gmk::write_asset_list(&mut gmk, &assets.scripts, gmk::write_script, 400, assets.version, multithread)
         .map_err(|e| format!("Failed to write scripts: {}", e))?;
 
     println!("Writing {} fonts...", assets.fonts.len());
-    gmk::write_asset_list(&mut gmk, &assets.fonts, gmk::write_font, assets.version, multithread)
+    gmk::write_asset_list(&mut gmk, &assets.fonts, gmk::write_font, 540, assets.version, multithread)
         .map_err(|e| format!("Failed to write fonts: {}", e))?;
 
     println!("Writing {} timelines...", assets.timelines.len());
-    gmk::write_asset_list(&mut gmk, &assets.timelines, gmk::write_timeline, assets.version, multithread)
+    gmk::write_asset_list(&mut gmk, &assets.timelines, gmk::write_timeline, 500, assets.version, multithread)
         .map_err(|e| format!("Failed to write timelines: {}", e))?;
 
     println!("Writing {} objects...", assets.objects.len());
-    gmk::write_asset_list(&mut gmk, &assets.objects, gmk::write_object, assets.version, multithread)
+    gmk::write_asset_list(&mut gmk, &assets.objects, gmk::write_object, 400, assets.version, multithread)
         .map_err(|e| format!("Failed to write objects: {}", e))?;
 
     println!("Writing {} rooms...", assets.rooms.len());
-    gmk::write_asset_list(&mut gmk, &assets.rooms, gmk::write_room, assets.version, multithread)
+    gmk::write_asset_list(&mut gmk, &assets.rooms, gmk::write_room, 420, assets.version, multithread)
         .map_err(|e| format!("Failed to write rooms: {}", e))?;
 
     println!(
@@ -347,15 +746,20 @@ fn decompile(
     gmk::write_room_editor_meta(&mut gmk, assets.last_instance_id, assets.last_tile_id)
         .map_err(|e| format!("Failed to write room editor metadata: {}", e))?;
 
-    println!("Writing {} included files...", assets.included_files.len());
-    gmk::write_included_files(&mut gmk, &assets.included_files)
-        .map_err(|e| format!("Failed to write included files: {}", e))?;
+    if !assets.version.is_legacy() {
+        println!("Writing {} included files...", assets.included_files.len());
+        gmk::write_included_files(&mut gmk, &assets.included_files)
+            .map_err(|e| format!("Failed to write included files: {}", e))?;
+    }
 
-    println!("Writing {} extensions...", assets.extensions.len());
-    gmk::write_extensions(&mut gmk, &assets.extensions).map_err(|e| format!("Failed to write extensions: {}", e))?;
+    if assets.version != GameVersion::GameMaker6_0 {
+        println!("Writing {} extensions...", assets.extensions.len());
+        gmk::write_extensions(&mut gmk, &assets.extensions)
+            .map_err(|e| format!("Failed to write extensions: {}", e))?;
+    }
 
     println!("Writing game information...");
-    gmk::write_game_information(&mut gmk, &assets.help_dialog)
+    gmk::write_game_information(&mut gmk, &assets.help_dialog, assets.version)
         .map_err(|e| format!("Failed to write game information: {}", e))?;
 
     println!("Writing {} library initialization strings...", assets.library_init_strings.len());
@@ -819,3 +819,7 @@ pub fn make_constants_map() -> HashMap<&'static [u8], f64> {
 pub fn make_kernel_vars_lut() -> HashSet<&'static [u8]> {
     KERNEL_VARS.iter().copied().map(|x| (x.as_bytes())).collect()
 }
+
+pub fn make_kernel_functions_map() -> HashMap<&'static [u8], Option<usize>> {
+    gml_parser::kernel::FUNCTIONS.iter().map(|(s, n)| (s.as_bytes(), *n)).collect()
+}
@@ -0,0 +1,328 @@
+use std::{
+    fmt::Display,
+    io::{self, Write},
+    str::FromStr,
+};
+
+/// A minimal XML element, covering only what the project format needs:
+/// attributes, child elements, and text content in elements without children.
+#[derive(Debug, Default)]
+pub struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    pub text: String,
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl Element {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.into(), ..Default::default() }
+    }
+
+    /// Adds an attribute, builder style.
+    pub fn with(mut self, key: &str, value: impl Display) -> Self {
+        self.attributes.push((key.into(), value.to_string()));
+        self
+    }
+
+    /// Sets the text content, builder style.
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.text = text.into();
+        self
+    }
+
+    pub fn push(&mut self, child: Element) {
+        self.children.push(child);
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.attributes.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// Gets an attribute which must exist.
+    pub fn str_attr(&self, key: &str) -> io::Result<&str> {
+        self.get(key).ok_or_else(|| invalid(format!("<{}> is missing attribute '{}'", self.name, key)))
+    }
+
+    /// Gets and parses an attribute which must exist.
+    pub fn attr<T: FromStr>(&self, key: &str) -> io::Result<T> {
+        let value = self.str_attr(key)?;
+        value.parse().map_err(|_| invalid(format!("<{}> has invalid value for '{}': {}", self.name, key, value)))
+    }
+
+    /// Iterates over the direct children with the given name.
+    pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    /// Finds the first direct child with the given name, which must exist.
+    pub fn child(&self, name: &str) -> io::Result<&Element> {
+        self.children
+            .iter()
+            .find(|c| c.name == name)
+            .ok_or_else(|| invalid(format!("<{}> is missing child <{}>", self.name, name)))
+    }
+
+    /// Writes this element as a full document.
+    pub fn write_document(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n")?;
+        self.write(writer, 0)
+    }
+
+    fn write(&self, writer: &mut impl Write, depth: usize) -> io::Result<()> {
+        let indent = "  ".repeat(depth);
+        write!(writer, "{}<{}", indent, self.name)?;
+        for (key, value) in &self.attributes {
+            write!(writer, " {}=\"{}\"", key, escape(value, true))?;
+        }
+        if !self.children.is_empty() {
+            writer.write_all(b">\n")?;
+            for child in &self.children {
+                child.write(writer, depth + 1)?;
+            }
+            writeln!(writer, "{}</{}>", indent, self.name)
+        } else if !self.text.is_empty() {
+            writeln!(writer, ">{}</{}>", escape(&self.text, false), self.name)
+        } else {
+            writer.write_all(b"/>\n")
+        }
+    }
+
+    /// Parses a document written by `write_document`, or anything else sticking to the same subset of XML.
+    pub fn parse_document(doc: &str) -> io::Result<Element> {
+        let mut parser = Parser { src: doc, pos: 0 };
+        parser.skip_misc()?;
+        let root = parser.element()?;
+        parser.skip_misc()?;
+        if parser.pos != doc.len() {
+            return Err(parser.error("unexpected content after root element"))
+        }
+        Ok(root)
+    }
+}
+
+fn escape(s: &str, attribute: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' if attribute => out.push_str("&quot;"),
+            '\n' | '\t' if !attribute => out.push(c),
+            // escaped so that they survive whitespace normalization
+            '\t' | '\n' | '\r' => out.push_str(&format!("&#x{:X};", c as u32)),
+            // XML 1.0 can't hold any other control characters at all, not even escaped
+            c if (c as u32) < 0x20 => out.push(char::REPLACEMENT_CHARACTER),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.src[self.pos..]
+    }
+
+    fn error(&self, msg: &str) -> io::Error {
+        let line = self.src[..self.pos].matches('\n').count() + 1;
+        invalid(format!("XML error on line {}: {}", line, msg))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.pos = self.src.len() - trimmed.len();
+    }
+
+    fn skip_past(&mut self, end: &str) -> io::Result<()> {
+        match self.rest().find(end) {
+            Some(i) => {
+                self.pos += i + end.len();
+                Ok(())
+            },
+            None => Err(self.error(&format!("expected '{}'", end))),
+        }
+    }
+
+    /// Skips whitespace, comments, processing instructions and doctypes.
+    fn skip_misc(&mut self) -> io::Result<()> {
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.rest().starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.rest().starts_with("<!DOCTYPE") {
+                self.skip_past(">")?;
+            } else {
+                break Ok(())
+            }
+        }
+    }
+
+    fn name(&mut self) -> io::Result<String> {
+        let len =
+            self.rest().find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/')).unwrap_or(self.rest().len());
+        if len == 0 {
+            return Err(self.error("expected a name"))
+        }
+        let name = self.rest()[..len].to_string();
+        self.pos += len;
+        Ok(name)
+    }
+
+    fn expect(&mut self, s: &str) -> io::Result<()> {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", s)))
+        }
+    }
+
+    fn element(&mut self) -> io::Result<Element> {
+        self.expect("<")?;
+        let mut element = Element::new(&self.name()?);
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.pos += 2;
+                return Ok(element)
+            } else if self.rest().starts_with('>') {
+                self.pos += 1;
+                break
+            }
+            let key = self.name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote = if self.rest().starts_with('\'') { "'" } else { "\"" };
+            self.expect(quote)?;
+            let len = self.rest().find(quote).ok_or_else(|| self.error("unterminated attribute"))?;
+            let value = self.unescape(&self.rest()[..len])?;
+            self.pos += len + 1;
+            element.attributes.push((key, value));
+        }
+
+        // Content: text is kept only if there are no child elements
+        let mut text = String::new();
+        loop {
+            let len = self.rest().find('<').ok_or_else(|| self.error("unterminated element"))?;
+            text.push_str(&self.unescape(&self.rest()[..len])?);
+            self.pos += len;
+            if self.rest().starts_with("</") {
+                self.pos += 2;
+                if self.name()? != element.name {
+                    return Err(self.error(&format!("mismatched closing tag for <{}>", element.name)))
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                break
+            } else if self.rest().starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.rest().starts_with("<![CDATA[") {
+                self.pos += 9;
+                let len = self.rest().find("]]>").ok_or_else(|| self.error("unterminated CDATA"))?;
+                text.push_str(&self.rest()[..len]);
+                self.pos += len + 3;
+            } else {
+                element.children.push(self.element()?);
+            }
+        }
+        if element.children.is_empty() {
+            element.text = text;
+        }
+        Ok(element)
+    }
+
+    fn unescape(&self, s: &str) -> io::Result<String> {
+        let mut out = String::with_capacity(s.len());
+        let mut rest = s;
+        while let Some(i) = rest.find('&') {
+            out.push_str(&rest[..i]);
+            rest = &rest[i + 1..];
+            let end = rest.find(';').ok_or_else(|| self.error("unterminated entity"))?;
+            let entity = &rest[..end];
+            rest = &rest[end + 1..];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => match entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                    None => entity.strip_prefix('#').and_then(|n| n.parse().ok()).and_then(char::from_u32),
+                },
+            };
+            out.push(c.ok_or_else(|| self.error(&format!("unknown entity '&{};'", entity)))?);
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(element: &Element) -> Element {
+        let mut doc = Vec::new();
+        element.write_document(&mut doc).unwrap();
+        Element::parse_document(std::str::from_utf8(&doc).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn write_then_parse() {
+        let mut root = Element::new("project").with("name", "a \"quoted\" <name> & more").with("count", 3);
+        root.push(Element::new("empty"));
+        root.push(Element::new("text").with_text("line one\n\tline <two> & 'three'\r\n"));
+        let mut group = Element::new("group");
+        group.push(Element::new("item").with("value", "multi\nline\tvalue\r"));
+        root.push(group);
+
+        let parsed = round_trip(&root);
+        assert_eq!(parsed.name, "project");
+        assert_eq!(parsed.str_attr("name").unwrap(), "a \"quoted\" <name> & more");
+        assert_eq!(parsed.attr::<i32>("count").unwrap(), 3);
+        assert!(parsed.child("empty").unwrap().text.is_empty());
+        assert_eq!(parsed.child("text").unwrap().text, "line one\n\tline <two> & 'three'\r\n");
+        let item = parsed.child("group").unwrap().child("item").unwrap();
+        assert_eq!(item.str_attr("value").unwrap(), "multi\nline\tvalue\r");
+        assert!(parsed.child("missing").is_err());
+    }
+
+    #[test]
+    fn control_characters() {
+        let element = Element::new("e").with("a", "\x01\x1F\x7F").with_text("\x00x\x08");
+        let mut doc = Vec::new();
+        element.write_document(&mut doc).unwrap();
+        let doc = String::from_utf8(doc).unwrap();
+        // none of these may appear in an XML 1.0 document, escaped or not
+        assert!(!doc.contains("&#x1;") && !doc.contains("&#x0;") && !doc.contains('\x01'));
+
+        let parsed = round_trip(&element);
+        assert_eq!(parsed.str_attr("a").unwrap(), "\u{FFFD}\u{FFFD}\x7F");
+        assert_eq!(parsed.text, "\u{FFFD}x\u{FFFD}");
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Element::parse_document("<a><b></a>").is_err());
+        assert!(Element::parse_document("<a>&unknown;</a>").is_err());
+        assert!(Element::parse_document("<a/><b/>").is_err());
+        let parsed =
+            Element::parse_document("<?xml version=\"1.0\"?><!-- c --><a x='1'><![CDATA[<raw>]]></a>").unwrap();
+        assert_eq!((parsed.str_attr("x").unwrap(), parsed.text.as_str()), ("1", "<raw>"));
+    }
+}
@@ -0,0 +1,555 @@
+//! Builds a cross-reference index of a game, for finding everywhere something is used.
+//!
+//! The index records every function call, every read and write of a variable, every asset mentioned by name in code,
+//! every `with` and `instance_create` target, every asset picked in a drag-and-drop action and every object placed in
+//! a room, each along with where it is in the game.
+
+use crate::{
+    code::{self, list_name, Location, Visitor},
+    gmx::text,
+    mappings,
+};
+use gm8exe::{
+    asset::{room::Instance, CodeAction},
+    GameAssets,
+};
+use gml_parser::{
+    ast::{Expr, ExprKind, FunctionExpr, AST},
+    span::Span,
+    token::Operator,
+};
+use regex::Regex;
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// Functions which create or change into an instance of an object, with which argument the object is.
+const CREATE_FUNCTIONS: &[(&[u8], usize)] = &[
+    (b"instance_create", 2),
+    (b"instance_change", 0),
+    (b"action_create_object", 0),
+    (b"action_create_object_motion", 0),
+];
+
+/// The ways something can be referred to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Kind {
+    /// A call to a script, kernel function or extension function.
+    Call,
+
+    /// A variable being read.
+    Read,
+
+    /// A variable being assigned to.
+    Write,
+
+    /// An asset mentioned by name in code.
+    Asset,
+
+    /// The target of a `with` statement, or the object an action applies to.
+    With,
+
+    /// The object given to `instance_create` or `instance_change`.
+    Create,
+
+    /// An asset picked as an argument of a drag-and-drop action.
+    Action,
+
+    /// An object placed in a room.
+    Instance,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 8] =
+        [Kind::Call, Kind::Read, Kind::Write, Kind::Asset, Kind::With, Kind::Create, Kind::Action, Kind::Instance];
+
+    /// The name of the kind on the command line and in JSON output.
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Call => "call",
+            Kind::Read => "read",
+            Kind::Write => "write",
+            Kind::Asset => "asset",
+            Kind::With => "with",
+            Kind::Create => "create",
+            Kind::Action => "action",
+            Kind::Instance => "instance",
+        }
+    }
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Kind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Kind::ALL.iter().copied().find(|k| k.name() == s).ok_or_else(|| {
+            let names = Kind::ALL.iter().map(|k| k.name()).collect::<Vec<_>>();
+            format!("unknown reference kind '{}' (valid kinds are {})", s, names.join("/"))
+        })
+    }
+}
+
+/// One place where something is referred to.
+#[derive(Clone, Debug)]
+pub struct Reference {
+    pub kind: Kind,
+
+    /// What's referred to, such as the name of a script, variable or asset.
+    pub name: String,
+
+    /// The instance a variable belongs to as it's written in the code, such as `global` in `global.flag`.
+    pub owner: Option<String>,
+
+    pub location: Location,
+
+    /// Where the reference is in the code, if it's in code at all.
+    pub span: Option<Span>,
+}
+
+impl Reference {
+    /// The name along with its owner, such as `global.flag`.
+    pub fn full_name(&self) -> String {
+        match &self.owner {
+            Some(owner) => format!("{}.{}", owner, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Describes the reference as JSON, leaving out the name since references are listed under it.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "kind": self.kind.name(),
+            "asset_kind": self.location.kind,
+            "asset": self.location.asset,
+            "event": self.location.event,
+            "action": self.location.action,
+            "argument": self.location.argument,
+            "line": self.span.map(|s| s.line),
+            "column": self.span.map(|s| s.column),
+        })
+    }
+}
+
+impl Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} in {}", self.kind, self.location)?;
+        if let Some(span) = self.span {
+            write!(f, ", {}", span)?;
+        }
+        Ok(())
+    }
+}
+
+/// Selects which references to look at.
+pub struct Filter {
+    /// The kinds of reference to look at. If this is empty, every kind is.
+    pub kinds: Vec<Kind>,
+
+    /// Exact names to look for. Names with an owner, like `global.flag`, only match references with that owner.
+    /// If this and `pattern` are both empty, every reference matches.
+    pub names: Vec<String>,
+
+    /// A regex which full names are searched with.
+    pub pattern: Option<Regex>,
+}
+
+impl Filter {
+    pub fn matches(&self, reference: &Reference) -> bool {
+        let full_name = reference.full_name();
+        (self.kinds.is_empty() || self.kinds.contains(&reference.kind))
+            && ((self.names.is_empty() && self.pattern.is_none())
+                || self.names.iter().any(|n| *n == full_name || *n == reference.name)
+                || self.pattern.as_ref().is_some_and(|p| p.is_match(&full_name)))
+    }
+}
+
+/// Every reference in a game, in the order they appear.
+pub struct Index {
+    pub references: Vec<Reference>,
+}
+
+impl Index {
+    pub fn new(assets: &GameAssets) -> Self {
+        let mut asset_names = HashSet::new();
+        asset_names.extend(assets.sprites.iter().flatten().map(|a| a.name.0.as_ref()));
+        asset_names.extend(assets.sounds.iter().flatten().map(|a| a.name.0.as_ref()));
+        asset_names.extend(assets.backgrounds.iter().flatten().map(|a| a.name.0.as_ref()));
+        asset_names.extend(assets.paths.iter().flatten().map(|a| a.name.0.as_ref()));
+        asset_names.extend(assets.scripts.iter().flatten().map(|a| a.name.0.as_ref()));
+        asset_names.extend(assets.fonts.iter().flatten().map(|a| a.name.0.as_ref()));
+        asset_names.extend(assets.timelines.iter().flatten().map(|a| a.name.0.as_ref()));
+        asset_names.extend(assets.objects.iter().flatten().map(|a| a.name.0.as_ref()));
+        asset_names.extend(assets.rooms.iter().flatten().map(|a| a.name.0.as_ref()));
+
+        let mut constants = mappings::make_constants_map().into_keys().collect::<HashSet<_>>();
+        constants.extend(assets.constants.iter().map(|c| c.name.0.as_ref()));
+
+        let mut indexer = Indexer {
+            assets,
+            asset_names,
+            constants,
+            references: Vec::new(),
+            location: None,
+            locals: HashSet::new(),
+            globals: HashSet::new(),
+        };
+        code::visit(assets, &mut indexer);
+        Index { references: indexer.references }
+    }
+
+    /// Every reference the filter matches, grouped by full name.
+    pub fn find(&self, filter: &Filter) -> BTreeMap<String, Vec<&Reference>> {
+        let mut found = BTreeMap::<_, Vec<_>>::new();
+        for reference in self.references.iter().filter(|r| filter.matches(r)) {
+            found.entry(reference.full_name()).or_default().push(reference);
+        }
+        found
+    }
+}
+
+struct Indexer<'a> {
+    assets: &'a GameAssets,
+
+    /// The names of every asset, for telling them apart from variables.
+    asset_names: HashSet<&'a [u8]>,
+
+    /// Every built-in and user-defined constant, which aren't variables either.
+    constants: HashSet<&'a [u8]>,
+
+    references: Vec<Reference>,
+
+    /// Where the code being indexed is.
+    location: Option<Location>,
+
+    /// Variables declared with `var` in the code being indexed, which can't be referred to from anywhere else.
+    locals: HashSet<Vec<u8>>,
+
+    /// Variables declared with `globalvar` in the code being indexed, which are really `global.` variables.
+    /// This only counts declarations in the same code, since it's not known which code runs first.
+    globals: HashSet<Vec<u8>>,
+}
+
+impl<'a> Visitor for Indexer<'a> {
+    fn code(&mut self, source: &[u8], location: &Location) {
+        self.enter(location);
+        // Code which doesn't parse can't refer to anything
+        if let Ok(ast) = AST::new(source) {
+            ast.iter().for_each(|statement| self.statement(source, statement));
+        }
+    }
+
+    fn expression(&mut self, source: &[u8], location: &Location) {
+        self.enter(location);
+        if let Ok(expr) = AST::expression(source) {
+            self.read(source, &expr);
+        }
+    }
+
+    fn action(&mut self, action: &CodeAction, location: &Location) {
+        self.enter(location);
+        if action.applies_to_something {
+            if let Some(object) = list_name(&self.assets.objects, action.applies_to, |o| &o.name) {
+                self.add(Kind::With, object, None, None);
+            }
+        }
+        // Asset arguments are stored as the asset's index
+        let params = action.param_strings.iter().zip(action.param_types.iter()).take(action.param_count);
+        for (i, (param, kind)) in params.enumerate() {
+            let Ok(index) = text(param).trim().parse::<i32>() else { continue };
+            let a = self.assets;
+            let name = match kind {
+                5 => list_name(&a.sprites, index, |x| &x.name),
+                6 => list_name(&a.sounds, index, |x| &x.name),
+                7 => list_name(&a.backgrounds, index, |x| &x.name),
+                8 => list_name(&a.paths, index, |x| &x.name),
+                9 => list_name(&a.scripts, index, |x| &x.name),
+                10 => list_name(&a.objects, index, |x| &x.name),
+                11 => list_name(&a.rooms, index, |x| &x.name),
+                12 => list_name(&a.fonts, index, |x| &x.name),
+                14 => list_name(&a.timelines, index, |x| &x.name),
+                _ => None,
+            };
+            if let Some(name) = name {
+                let location = Location { argument: Some(i + 1), ..location.clone() };
+                self.references.push(Reference { kind: Kind::Action, name, owner: None, location, span: None });
+            }
+        }
+    }
+
+    fn instance(&mut self, instance: &Instance, location: &Location) {
+        self.enter(location);
+        if let Some(object) = list_name(&self.assets.objects, instance.object, |o| &o.name) {
+            self.add(Kind::Instance, object, None, None);
+        }
+    }
+}
+
+impl<'a> Indexer<'a> {
+    fn enter(&mut self, location: &Location) {
+        self.location = Some(location.clone());
+        self.locals.clear();
+        self.globals.clear();
+    }
+
+    fn add(&mut self, kind: Kind, name: String, owner: Option<String>, span: Option<Span>) {
+        let location = self.location.clone().expect("found a reference outside of any code");
+        self.references.push(Reference { kind, name, owner, location, span });
+    }
+
+    /// Adds a reference to a variable, unless it's a local or a constant. Assets are added as assets instead.
+    fn variable(&mut self, kind: Kind, name: &[u8], owner: Option<String>, span: Span) {
+        let owner = match owner {
+            Some(owner) => Some(owner),
+            None if self.locals.contains(name) || self.constants.contains(name) => return,
+            None if self.globals.contains(name) => Some("global".into()),
+            None if kind == Kind::Read && self.asset_names.contains(name) => {
+                self.add(Kind::Asset, String::from_utf8_lossy(name).into(), None, Some(span));
+                return
+            },
+            None => None,
+        };
+        self.add(kind, String::from_utf8_lossy(name).into(), owner, Some(span));
+    }
+
+    /// The name of whatever an expression refers to, for `with` and `instance_create` targets.
+    /// Object indices are turned into names where possible, since obfuscated games often use them.
+    fn target_name(&self, source: &[u8], target: &Expr) -> String {
+        match target.kind {
+            ExprKind::LiteralIdentifier(name) => String::from_utf8_lossy(name).into(),
+            ExprKind::LiteralReal(index) if index.fract() == 0.0 => {
+                list_name(&self.assets.objects, index as i32, |o| &o.name).unwrap_or_else(|| index.to_string())
+            },
+            _ => String::from_utf8_lossy(source.get(target.span.range()).unwrap_or_default()).into(),
+        }
+    }
+
+    fn statement(&mut self, source: &[u8], statement: &Expr) {
+        match &statement.kind {
+            // Binary operators can only be assignments at this level
+            ExprKind::Binary(binary) => {
+                self.read(source, &binary.right);
+                self.write(source, &binary.left, binary.op != Operator::Assign);
+            },
+            ExprKind::Function(call) => self.call(source, statement.span, call),
+            ExprKind::Group(statements) => statements.iter().for_each(|s| self.statement(source, s)),
+            ExprKind::If(if_expr) => {
+                self.read(source, &if_expr.cond);
+                self.statement(source, &if_expr.body);
+                if let Some(else_body) = &if_expr.else_body {
+                    self.statement(source, else_body);
+                }
+            },
+            ExprKind::For(for_expr) => {
+                self.statement(source, &for_expr.start);
+                self.read(source, &for_expr.cond);
+                self.statement(source, &for_expr.step);
+                self.statement(source, &for_expr.body);
+            },
+            ExprKind::DoUntil(do_until) => {
+                self.statement(source, &do_until.body);
+                self.read(source, &do_until.cond);
+            },
+            ExprKind::Repeat(repeat) => {
+                self.read(source, &repeat.count);
+                self.statement(source, &repeat.body);
+            },
+            ExprKind::Switch(switch) => {
+                self.read(source, &switch.input);
+                self.statement(source, &switch.body);
+            },
+            ExprKind::With(with) => {
+                let target = self.target_name(source, &with.target);
+                self.add(Kind::With, target, None, Some(with.target.span));
+                self.read(source, &with.target);
+                self.statement(source, &with.body);
+            },
+            ExprKind::While(while_expr) => {
+                self.read(source, &while_expr.cond);
+                self.statement(source, &while_expr.body);
+            },
+            ExprKind::Var(var) => self.locals.extend(var.vars.iter().map(|v| v.to_vec())),
+            ExprKind::GlobalVar(var) => self.globals.extend(var.vars.iter().map(|v| v.to_vec())),
+            ExprKind::Case(value) | ExprKind::Return(value) => self.read(source, value),
+            _ => (),
+        }
+    }
+
+    /// Indexes the left-hand side of an assignment. Operators like `+=` read the variable as well as writing it.
+    fn write(&mut self, source: &[u8], target: &Expr, also_read: bool) {
+        let (name, owner, span) = match &target.kind {
+            ExprKind::LiteralIdentifier(name) => (*name, None, target.span),
+            ExprKind::Binary(binary) if binary.op == Operator::Deref => match binary.right.kind {
+                ExprKind::LiteralIdentifier(name) => {
+                    self.read(source, &binary.left);
+                    (name, Some(self.target_name(source, &binary.left)), binary.right.span)
+                },
+                _ => return,
+            },
+            ExprKind::Binary(binary) if binary.op == Operator::Index => {
+                self.read(source, &binary.right);
+                self.write(source, &binary.left, also_read);
+                return
+            },
+            _ => {
+                self.read(source, target);
+                return
+            },
+        };
+        if also_read {
+            self.variable(Kind::Read, name, owner.clone(), span);
+        }
+        self.variable(Kind::Write, name, owner, span);
+    }
+
+    /// Indexes an expression whose value gets read.
+    fn read(&mut self, source: &[u8], expr: &Expr) {
+        match &expr.kind {
+            ExprKind::LiteralIdentifier(name) => self.variable(Kind::Read, name, None, expr.span),
+            ExprKind::Unary(unary) => self.read(source, &unary.child),
+            ExprKind::Binary(binary) if binary.op == Operator::Deref => {
+                self.read(source, &binary.left);
+                if let ExprKind::LiteralIdentifier(name) = binary.right.kind {
+                    let owner = self.target_name(source, &binary.left);
+                    self.variable(Kind::Read, name, Some(owner), binary.right.span);
+                }
+            },
+            ExprKind::Binary(binary) => {
+                self.read(source, &binary.left);
+                self.read(source, &binary.right);
+            },
+            ExprKind::Function(call) => self.call(source, expr.span, call),
+            // Array indices
+            ExprKind::Group(exprs) => exprs.iter().for_each(|e| self.read(source, e)),
+            _ => (),
+        }
+    }
+
+    fn call(&mut self, source: &[u8], span: Span, call: &FunctionExpr) {
+        self.add(Kind::Call, String::from_utf8_lossy(call.name).into(), None, Some(span));
+        let target =
+            CREATE_FUNCTIONS.iter().find(|(name, _)| *name == call.name).and_then(|(_, i)| call.params.get(*i));
+        if let Some(target) = target {
+            let name = self.target_name(source, target);
+            self.add(Kind::Create, name, None, Some(target.span));
+        }
+        call.params.iter().for_each(|param| self.read(source, param));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code::tests::game;
+
+    fn filter(kinds: &[Kind], names: &[&str], pattern: Option<&str>) -> Filter {
+        Filter {
+            kinds: kinds.to_vec(),
+            names: names.iter().map(|n| n.to_string()).collect(),
+            pattern: pattern.map(|p| Regex::new(p).unwrap()),
+        }
+    }
+
+    /// Every reference the filter matches, described without its place in the code, grouped by full name.
+    fn find(filter: &Filter) -> Vec<(String, Vec<String>)> {
+        let index = Index::new(&game());
+        let found = index.find(filter);
+        found
+            .into_iter()
+            .map(|(name, refs)| (name, refs.iter().map(|r| format!("{} in {}", r.kind, r.location)).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn kind_names() {
+        assert_eq!("write".parse::<Kind>(), Ok(Kind::Write));
+        assert!("writes".parse::<Kind>().is_err());
+        assert!(Kind::ALL.iter().all(|k| k.name().parse::<Kind>() == Ok(*k)));
+    }
+
+    #[test]
+    fn scripts() {
+        assert_eq!(
+            find(&filter(&[], &["scr_hurt"], None)),
+            [(
+                "scr_hurt".into(),
+                vec![
+                    "call in script scr_move".into(),
+                    "action in object obj_player, Collision obj_enemy, action 2, argument 1".into(),
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn objects() {
+        assert_eq!(
+            find(&filter(&[], &["obj_enemy"], None)),
+            [(
+                "obj_enemy".into(),
+                vec![
+                    "with in script scr_hurt".into(),
+                    "asset in script scr_hurt".into(),
+                    "create in object obj_player, Create 0, action 1".into(),
+                    "with in object obj_player, Collision obj_enemy, action 2".into(),
+                    "instance in room rm_start, Instance 100002".into(),
+                ]
+            )]
+        );
+        assert_eq!(
+            find(&filter(&[Kind::Instance], &[], None)),
+            [
+                ("obj_enemy".into(), vec!["instance in room rm_start, Instance 100002".into()]),
+                ("obj_player".into(), vec!["instance in room rm_start, Instance 100001".into()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn variables() {
+        // Writes in rooms and instance creation code, with and without an owner
+        assert_eq!(
+            find(&filter(&[Kind::Write], &["global.score", "spd"], None)),
+            [
+                (
+                    "global.score".into(),
+                    vec!["write in script scr_hurt".into(), "write in room rm_start, Creation Code".into()]
+                ),
+                (
+                    "spd".into(),
+                    vec![
+                        "write in object obj_player, Create 0, action 1".into(),
+                        "write in room rm_start, Instance 100001".into(),
+                    ]
+                ),
+            ]
+        );
+
+        // `+=` reads as well as writes, `var` locals aren't recorded and assets are recorded as assets
+        assert_eq!(
+            find(&filter(&[], &["hp"], None)),
+            [(
+                "hp".into(),
+                vec![
+                    "read in script scr_hurt".into(),
+                    "write in script scr_hurt".into(),
+                    "read in object obj_player, Collision obj_enemy, action 1".into(),
+                    "write in object obj_player, Collision obj_enemy, action 1".into(),
+                ]
+            )]
+        );
+        assert!(find(&filter(&[], &["i"], None)).is_empty());
+        assert_eq!(
+            find(&filter(&[Kind::Asset], &[], Some("^bg_"))),
+            [("bg_sky".into(), vec!["asset in object obj_player, Create 0, action 1".into()])]
+        );
+    }
+}
@@ -40,6 +40,30 @@ fn main() -> Result<(), Box<dyn Error>> {
         fs::write(aa_macro_path, &aa_macro)?;
     }
 
+    // list of kernel functions which still call unimplemented_function!(), for the unimplemented function policy
+    // anything else that panics in place of an implementation would be missing from it, so that isn't allowed
+    let kernel = fs::read_to_string("src/gml/kernel.rs")?;
+    if let Some(line) = kernel.lines().position(|line| {
+        let line = line.trim_start();
+        line.starts_with("todo!(") || line.starts_with("unimplemented!(\"")
+    }) {
+        return Err(format!("kernel.rs:{}: use unimplemented_function!() for unimplemented functions", line + 1).into())
+    }
+    let mut unimplemented = kernel
+        .split("unimplemented_function!(")
+        .skip(1)
+        .filter_map(|rest| rest.split_once(')'))
+        .map(|(name, _)| name.trim())
+        .collect::<Vec<_>>();
+    unimplemented.sort_unstable();
+    let mut unimplemented_list = String::with_capacity(8192);
+    unimplemented_list += "pub static UNIMPLEMENTED_FUNCTIONS: &[&str] = &[\n";
+    for name in unimplemented {
+        unimplemented_list += &format!("    {:?},\n", name);
+    }
+    unimplemented_list += "];\n";
+    fs::write(&Path::new(&out).join("_unimplemented.rs"), &unimplemented_list)?;
+
     // opengl bindings
     let mut bindings = File::create(&Path::new(&out).join("gl_bindings.rs"))?;
     Registry::new(Api::Gl, (3, 3), Profile::Core, Fallbacks::All, &OPENGL_EXTENSIONS)
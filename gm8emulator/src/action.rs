@@ -2,9 +2,10 @@ use crate::{
     game::Game,
     gml::{
         self,
+        bytecode::Code,
         compiler::Compiler,
         mappings,
-        runtime::{Instruction, Node},
+        runtime::Node,
         Context, Value,
     },
 };
@@ -81,7 +82,7 @@ pub enum Body {
 #[derive(Serialize, Deserialize)]
 pub enum GmlBody {
     Function(usize),
-    Code(Rc<[Instruction]>),
+    Code(Code),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -162,7 +163,9 @@ impl Tree {
                                         &action.param_types,
                                         action.param_count,
                                     )?,
-                                    body: GmlBody::Code(compiler.compile(&action.fn_code.0).map_err(|e| e.message)?),
+                                    body: GmlBody::Code(
+                                        compiler.compile(&action.fn_code.0).map_err(|e| e.to_string())?,
+                                    ),
                                     is_condition: action.is_condition,
                                 },
                             });
@@ -213,7 +216,7 @@ impl Tree {
                         relative: action.is_relative,
                         invert_condition: action.invert_condition,
                         body: Body::Repeat {
-                            count: compiler.compile_expression(&action.param_strings[0].0).map_err(|e| e.message)?,
+                            count: compiler.compile_expression(&action.param_strings[0].0).map_err(|e| e.to_string())?,
                         },
                     });
                 },
@@ -229,7 +232,7 @@ impl Tree {
                         invert_condition: action.invert_condition,
                         body: Body::Normal {
                             args: Box::new([]),
-                            body: GmlBody::Code(compiler.compile(&code).map_err(|e| e.message)?),
+                            body: GmlBody::Code(compiler.compile(&code).map_err(|e| e.to_string())?),
                             is_condition: false,
                         },
                     });
@@ -243,7 +246,9 @@ impl Tree {
                         invert_condition: action.invert_condition,
                         body: Body::Normal {
                             args: Box::new([]),
-                            body: GmlBody::Code(compiler.compile(&action.param_strings[0].0).map_err(|e| e.message)?),
+                            body: GmlBody::Code(
+                                compiler.compile(&action.param_strings[0].0).map_err(|e| e.to_string())?,
+                            ),
                             is_condition: false,
                         },
                     });
@@ -271,17 +276,17 @@ impl Tree {
                 _ => compiler.compile_expression(&param.0),
             })
             .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| e.message)?
+            .map_err(|e| e.to_string())?
             .into_boxed_slice())
     }
 
-    pub fn new_from_code(code: Rc<[Instruction]>) -> Rc<RefCell<Self>> {
+    pub fn new_from_code(code: Code) -> Rc<RefCell<Self>> {
         let mut tree = Self(Vec::new());
         tree.push_code(code);
         Rc::new(RefCell::new(tree))
     }
 
-    pub fn push_code(&mut self, code: Rc<[Instruction]>) {
+    pub fn push_code(&mut self, code: Code) {
         self.0.push(Action {
             index: self.0.len(),
             target: None,
@@ -290,6 +295,10 @@ impl Tree {
             body: Body::Normal { args: Box::new([]), body: GmlBody::Code(code), is_condition: false },
         });
     }
+
+    pub fn iter(&self) -> std::slice::Iter<Action> {
+        self.0.iter()
+    }
 }
 
 impl Game {
@@ -387,7 +396,7 @@ impl Game {
                                 GmlBody::Code(code) => {
                                     context.arguments = arg_values;
                                     context.argument_count = args.len();
-                                    self.execute(code, &mut context)?;
+                                    self.execute_code(code, &mut context)?;
                                     context.return_value
                                 },
                             };
@@ -409,7 +418,7 @@ impl Game {
                                     GmlBody::Code(code) => {
                                         context.arguments = arg_values;
                                         context.argument_count = args.len();
-                                        self.execute(code, &mut context)?;
+                                        self.execute_code(code, &mut context)?;
                                         context.return_value.clone()
                                     },
                                 };
@@ -31,6 +31,12 @@ impl Path {
     /// Generates a new set of control nodes for this Path and updates its start, end and length
     pub fn update(&mut self) {
         self.control_nodes.clear(); // since you can dynamically add path points...
+        if self.points.is_empty() {
+            // nothing will be pushed, so reset what push_control_node would have set
+            self.length = Real::from(0.0);
+            self.start = Default::default();
+            self.end = Default::default();
+        }
         if self.curve {
             if let (Some(&first_point), Some(&last_point)) = (self.points.first(), self.points.last()) {
                 if !self.closed {
@@ -178,3 +184,42 @@ impl Point {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: f64, y: f64) -> Point {
+        Point { x: x.into(), y: y.into(), speed: Real::from(100.0) }
+    }
+
+    #[test]
+    fn update_after_editing() {
+        let mut path = Path {
+            name: "path".into(),
+            points: vec![point(0.0, 0.0), point(30.0, 40.0)],
+            control_nodes: Vec::new(),
+            length: Real::from(0.0),
+            curve: false,
+            closed: false,
+            precision: 4,
+            start: Point::default(),
+            end: Point::default(),
+        };
+        path.update();
+        assert_eq!(path.length, Real::from(50.0));
+
+        // Like path_insert_point at 1
+        path.points.insert(1, point(0.0, 40.0));
+        path.update();
+        assert_eq!(path.length, Real::from(70.0));
+        assert_eq!(path.end.x, Real::from(30.0));
+
+        // Like path_clear_points, which leaves nothing to take the start, end or length from
+        path.points.clear();
+        path.update();
+        assert_eq!(path.length, Real::from(0.0));
+        assert_eq!(path.end.x, Real::from(0.0));
+        assert!(path.control_nodes.is_empty());
+    }
+}
@@ -1,11 +1,10 @@
 use crate::{
     game::{Background, View},
-    gml::{self, runtime::Instruction},
+    gml::{self, bytecode::Code},
     tile::Tile,
     types::{Colour, ID},
 };
 use serde::{Deserialize, Serialize};
-use std::rc::Rc;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Room {
@@ -17,7 +16,7 @@ pub struct Room {
     pub persistent: bool,
     pub bg_colour: Colour,
     pub clear_screen: bool,
-    pub creation_code: Result<Rc<[Instruction]>, String>,
+    pub creation_code: Result<Code, String>,
 
     pub backgrounds: Vec<Background>,
     pub views_enabled: bool,
@@ -33,7 +32,7 @@ pub struct Instance {
     pub y: i32,
     pub object: i32,
     pub id: ID,
-    pub creation: Result<Rc<[Instruction]>, String>,
+    pub creation: Result<Code, String>,
     pub xscale: f64,
     pub yscale: f64,
     pub blend: u32,
@@ -1,10 +1,9 @@
-use crate::gml::{self, runtime::Instruction};
+use crate::gml::{self, bytecode::Code};
 use serde::{Deserialize, Serialize};
-use std::rc::Rc;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Script {
     pub name: gml::String,
     pub source: gml::String,
-    pub compiled: Rc<[Instruction]>,
+    pub compiled: Code,
 }
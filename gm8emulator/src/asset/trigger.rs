@@ -1,12 +1,11 @@
-use crate::gml::{self, runtime::Instruction};
+use crate::gml::{self, bytecode::Code};
 use gm8exe::asset::trigger::TriggerKind;
 use serde::{Deserialize, Serialize};
-use std::rc::Rc;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Trigger {
     pub name: gml::String,
-    pub condition: Rc<[Instruction]>,
+    pub condition: Code,
     pub moment: TriggerTime,
 }
 
@@ -1,21 +1,29 @@
 pub mod audio;
 pub mod background;
+pub mod branch;
+pub mod console;
+pub mod debugger;
 pub mod draw;
 pub mod events;
 pub mod external;
 pub mod gm_save;
+pub mod hitbox;
 pub mod includedfile;
+pub mod macros;
 pub mod model;
 pub mod movement;
 pub mod particle;
 pub mod pathfinding;
 pub mod platform;
+pub mod profiler;
 pub mod recording;
 pub mod replay;
 pub mod savestate;
+pub mod search;
 pub mod surface;
 pub mod transition;
 pub mod view;
+pub mod watch;
 
 pub use background::Background;
 pub use replay::Replay;
@@ -34,7 +42,7 @@ use crate::{
         Object, Script, Sound, Timeline,
     },
     game::gm_save::GMSave,
-    gml::{self, ds, ev, file, rand::Random, runtime::Instruction, Compiler, Context},
+    gml::{self, bytecode::Code, ds, ev, file, rand::Random, unimplemented, Compiler, Context},
     handleman::{HandleArray, HandleList, HandleManager},
     input::{self, Input},
     instance::{DummyFieldHolder, Instance, InstanceState},
@@ -53,6 +61,7 @@ use gm8exe::asset::{
 use includedfile::IncludedFile;
 use indexmap::IndexMap;
 use ramen::{
+    connection::Connection,
     event::Event,
     window::{Controls, Window},
 };
@@ -214,14 +223,24 @@ pub struct Game {
     pub esc_close_game: bool,
 
     pub play_type: PlayType,
+    pub unimplemented_policy: unimplemented::PolicyTable,
+    pub interpreter: gml::Interpreter,
+    pub debugger: Option<Box<debugger::Debugger>>,
+    pub profiler: Option<Box<profiler::Profiler>>,
+    pub console: Option<Box<console::StdinConsole>>,
+    pub replay_trace: Option<Vec<u64>>, // fingerprint of the state after each replayed frame, if wanted
     pub stored_events: VecDeque<replay::Event>,
     pub frame_limiter: bool, // whether to limit FPS of gameplay by room_speed
     pub frame_limit_at: usize, // on which frame to start limiting FPS
 
     pub audio: audio::AudioManager,
 
+    pub display: platform::VirtualDisplay,
+    pub display_initial: platform::VirtualDisplay, // what display_reset goes back to
+
     // winit windowing
     pub window: Window,
+    pub connection: Connection, // the window's connection, for platform calls which need it
     pub window_border: bool,
     pub window_caption: String,
     pub window_cursor_gml: i32,
@@ -230,6 +249,7 @@ pub struct Game {
     pub window_offset_spoof: (i32, i32),
     pub window_is_logical_dpi: bool,
     pub window_sizeable: bool,
+    pub window_stayontop: bool,
     pub window_visible: bool,
     pub close_requested: bool,
     // Scaling type
@@ -267,7 +287,7 @@ pub enum SceneChange {
 /// A function defined in an extension, which could either be a DLL external or some compiled GML
 pub enum ExtensionFunction {
     Dll(String, ID),
-    Gml(Rc<[Instruction]>),
+    Gml(Code),
 }
 
 /// A room state originally loaded from a room asset.
@@ -334,6 +354,8 @@ impl Game {
         frame_limiter: bool,
         frame_limit_at: usize,
         play_type: PlayType,
+        optimize: bool,
+        line_info: bool,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         // Parse file path
         let mut file_path2 = file_path.clone();
@@ -390,8 +412,12 @@ impl Game {
             ..
         } = assets;
 
+        // GM5, GM6 and GM7 games run on what is essentially the GM8.0 runtime
         let gm_version = match version {
-            gm8exe::GameVersion::GameMaker8_0 => Version::GameMaker8_0,
+            gm8exe::GameVersion::GameMaker5_0
+            | gm8exe::GameVersion::GameMaker6_0
+            | gm8exe::GameVersion::GameMaker7_0
+            | gm8exe::GameVersion::GameMaker8_0 => Version::GameMaker8_0,
             gm8exe::GameVersion::GameMaker8_1 => Version::GameMaker8_1,
         };
 
@@ -502,6 +528,8 @@ impl Game {
 
         // Set up a GML compiler
         let mut compiler = Compiler::new();
+        compiler.set_optimize(optimize);
+        compiler.set_line_info(line_info);
         compiler.reserve_scripts(scripts.iter().flatten().count());
         compiler.reserve_constants(
             backgrounds.iter().flatten().count()
@@ -592,7 +620,7 @@ impl Game {
         let window_border = !settings.dont_draw_border;
         let window_icons = !settings.dont_show_buttons;
 
-        let connection = ramen::connection::Connection::new()?;
+        let connection = Connection::new()?;
         #[cfg(unix)]
         unsafe {
             let display = connection.xdisplay();
@@ -629,6 +657,11 @@ impl Game {
 
         let window = builder.build()?;
 
+        let display = match play_type {
+            PlayType::Normal => platform::VirtualDisplay::host(&connection),
+            PlayType::Record | PlayType::Replay => platform::VirtualDisplay::DEFAULT,
+        };
+
         // Set up audio manager
         let mut audio = audio::AudioManager::new(play_type != PlayType::Record);
 
@@ -1335,12 +1368,21 @@ impl Game {
             error_occurred: false,
             error_last: "".to_string().into(),
             audio,
+            display,
+            display_initial: display,
             window,
+            connection,
             window_border,
             window_icons,
             close_requested: false,
             scaling,
             play_type,
+            unimplemented_policy: unimplemented::PolicyTable::new(),
+            interpreter: gml::Interpreter::Tree,
+            debugger: None,
+            profiler: None,
+            console: None,
+            replay_trace: None,
             stored_events: VecDeque::new(),
 
             // load_room sets this
@@ -1354,6 +1396,7 @@ impl Game {
             window_is_logical_dpi: false,
             window_offset_spoof: (0, 0),
             window_sizeable: settings.allow_resize,
+            window_stayontop: false,
             window_visible: true,
         };
 
@@ -1674,7 +1717,7 @@ impl Game {
                 // Run this instance's room creation code
                 let mut new_context = Context::with_single_instance(*handle);
                 new_context.event_object = instance.object;
-                self.execute(&instance.creation.clone()?, &mut new_context)?;
+                self.execute_code(&instance.creation.clone()?, &mut new_context)?;
 
                 if !self.swap_creation_events {
                     // Run create event for this instance
@@ -1696,7 +1739,7 @@ impl Game {
                 .instance_list
                 .insert_dummy(Instance::new_dummy(self.assets.objects.get_asset(0).map(|x| x.as_ref())));
             let mut new_context = Context::with_single_instance(dummy_instance);
-            self.execute(&room.creation_code?, &mut new_context)?;
+            self.execute_code(&room.creation_code?, &mut new_context)?;
             self.room.instance_list.remove_dummy(dummy_instance);
         }
 
@@ -1816,6 +1859,10 @@ impl Game {
 
     /// Runs a frame loop and draws the screen. Exits immediately, without waiting for any FPS limitation.
     pub fn frame(&mut self) -> gml::Result<()> {
+        self.profile(profiler::Entry::Frame, Self::run_frame)
+    }
+
+    fn run_frame(&mut self) -> gml::Result<()> {
         if self.esc_close_game && self.input.keyboard_lastkey() == input::Button::Escape as u8 {
             self.scene_change = Some(SceneChange::End);
             return Ok(())
@@ -1846,8 +1893,9 @@ impl Game {
         while let Some(handle) = iter.next(&self.room.instance_list) {
             let instance = self.room.instance_list.get(handle);
             let object_index = instance.object_index.get();
+            let timeline_index = instance.timeline_index.get();
             if instance.timeline_running.get() {
-                if let Some(timeline) = self.assets.timelines.get_asset(instance.timeline_index.get()) {
+                if let Some(timeline) = self.assets.timelines.get_asset(timeline_index) {
                     let moments = timeline.moments.clone();
                     let timeline_len = Real::from(*moments.borrow().keys().max().unwrap_or(&0));
 
@@ -1863,12 +1911,12 @@ impl Game {
                                     instance.timeline_position.set(new_position)
                                 }
 
-                                for (_, tree) in moments
+                                for (&moment, tree) in moments
                                     .borrow()
                                     .iter()
                                     .filter(|(&x, _)| Real::from(x) >= old_position && Real::from(x) < new_position)
                                 {
-                                    self.execute_tree(tree.clone(), handle, handle, 0, 0, object_index)?;
+                                    self.run_moment(tree.clone(), timeline_index, moment, handle, object_index)?;
                                 }
                             },
                             x if x < Real::from(0) => {
@@ -1878,13 +1926,13 @@ impl Game {
                                     instance.timeline_position.set(new_position)
                                 }
 
-                                for (_, tree) in moments
+                                for (&moment, tree) in moments
                                     .borrow()
                                     .iter()
                                     .filter(|(&x, _)| Real::from(x) > new_position && Real::from(x) <= old_position)
                                     .rev()
                                 {
-                                    self.execute_tree(tree.clone(), handle, handle, 0, 0, object_index)?;
+                                    self.run_moment(tree.clone(), timeline_index, moment, handle, object_index)?;
                                 }
                             },
                             _ => {},
@@ -2029,6 +2077,12 @@ impl Game {
                     match event {
                         Event::KeyboardDown(key) => self.input.button_press(input::ramen2vk(key), true),
                         Event::KeyboardUp(key) => self.input.button_release(input::ramen2vk(key), true),
+                        Event::Input(ch) => {
+                            // GM8 only sees characters in the game's codepage
+                            for byte in input::encode_char(ch, self.encoding).into_iter().flatten() {
+                                self.input.char_input(byte);
+                            }
+                        },
                         Event::MouseMove((x, y)) => {
                             if let (Ok(x), Ok(y)) = (i32::try_from(x), i32::try_from(y)) {
                                 self.input.mouse_move_to((x, y));
@@ -2103,7 +2157,7 @@ impl Game {
             Some(ExtensionFunction::Gml(gml)) => {
                 let instructions = gml.clone();
                 let mut context = Context::copy_with_args(context, args, arg_count);
-                self.execute(&instructions, &mut context)?;
+                self.execute_code(&instructions, &mut context)?;
                 Ok(context.return_value)
             },
             None => Ok(Default::default()), // unfortunately required
@@ -2119,7 +2173,7 @@ impl Game {
                 .instance_list
                 .insert_dummy(Instance::new_dummy(self.assets.objects.get_asset(0).map(|x| x.as_ref())));
             let instructions = self.compiler.compile(&self.library_init_strings[i])?;
-            self.execute(&instructions, &mut Context::with_single_instance(dummy_instance))?;
+            self.execute_code(&instructions, &mut Context::with_single_instance(dummy_instance))?;
             self.room.instance_list.remove_dummy(dummy_instance);
         }
 
@@ -2171,9 +2225,11 @@ impl Game {
         let mut time_last = time_now;
         loop {
             self.process_window_events();
+            self.poll_console();
 
             self.frame()?;
             handle_scene_change!(self);
+            self.print_warnings();
 
             // Exit if the window was closed by the user, such as by pressing 'X'
             if self.close_requested {
@@ -2205,8 +2261,44 @@ impl Game {
         }
     }
 
+    // Prints any warnings raised while running the game, such as unimplemented functions being called
+    fn print_warnings(&mut self) {
+        for warning in self.unimplemented_policy.take_warnings() {
+            println!("Warning: {}", warning);
+        }
+    }
+
+    // Sets up a recorded frame's inputs, stored events, seed and time, ready for it to run
+    // Console events aren't queued, they get run by replay_console once the frame is done
+    pub fn apply_replay_frame(&mut self, frame: &replay::Frame) {
+        for ev in frame.events.iter().filter(|ev| !matches!(ev, replay::Event::Console { .. })) {
+            self.stored_events.push_back(ev.clone());
+        }
+
+        if let Some(seed) = frame.new_seed {
+            self.rand.set_seed(seed);
+        }
+
+        if let Some(time) = frame.new_time {
+            self.clock = GameClock::SpoofedNanos(time);
+        }
+
+        self.input.mouse_move_to((frame.mouse_x as i32, frame.mouse_y as i32));
+        for ev in frame.inputs.iter() {
+            match ev {
+                replay::Input::KeyPress(v) => self.input.button_press(*v as u8, true),
+                replay::Input::KeyRelease(v) => self.input.button_release(*v as u8, true),
+                replay::Input::MousePress(b) => self.input.mouse_press(*b as i8, true),
+                replay::Input::MouseRelease(b) => self.input.mouse_release(*b as i8, true),
+                replay::Input::MouseWheelUp => self.input.mouse_scroll_up(),
+                replay::Input::MouseWheelDown => self.input.mouse_scroll_down(),
+                replay::Input::Char(ch) => self.input.char_input(*ch),
+            }
+        }
+    }
+
     // Replays some recorded inputs to the game
-    pub fn replay(mut self, replay: Replay, output_bin: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn replay(&mut self, replay: Replay, output_bin: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
         let mut frame_count: usize = 0;
         self.rand.set_seed(replay.start_seed);
         self.clock = GameClock::SpoofedNanos(replay.start_time);
@@ -2217,16 +2309,27 @@ impl Game {
             self.renderer.upload_sprite(Box::new([0, 0, 0, 0]), 1, 1, 0, 0).expect("Failed to upload blank sprite");
         }
 
-        for ev in replay.startup_events.iter() {
+        for ev in replay.startup_events.iter().filter(|ev| !matches!(ev, replay::Event::Console { .. })) {
             self.stored_events.push_back(ev.clone());
         }
         self.init()?;
         handle_scene_change!(self);
+        self.replay_console(&replay.startup_events);
 
+        // Time spent running frames, not counting the frame limiter, for comparing interpreters
+        let mut busy_time = Duration::default();
         let mut time_now = Instant::now();
         loop {
             self.window.poll_events();
             self.input.mouse_step();
+            if frame_count == replay.frame_count() {
+                println!(
+                    "Replayed {} frames in {:.3}s using the {} interpreter",
+                    frame_count,
+                    busy_time.as_secs_f64(),
+                    self.interpreter,
+                );
+            }
             if let Some(frame) = replay.get_frame(frame_count) {
                 if !self.stored_events.is_empty() {
                     return Err(format!(
@@ -2237,32 +2340,10 @@ impl Game {
                     .into())
                 }
 
-                for ev in frame.events.iter() {
-                    self.stored_events.push_back(ev.clone());
-                }
-
-                if let Some(seed) = frame.new_seed {
-                    self.rand.set_seed(seed);
-                }
-
-                if let Some(time) = frame.new_time {
-                    self.clock = GameClock::SpoofedNanos(time);
-                }
-
-                self.input.mouse_move_to((frame.mouse_x as i32, frame.mouse_y as i32));
-                for ev in frame.inputs.iter() {
-                    match ev {
-                        replay::Input::KeyPress(v) => self.input.button_press(*v as u8, true),
-                        replay::Input::KeyRelease(v) => self.input.button_release(*v as u8, true),
-                        replay::Input::MousePress(b) => self.input.mouse_press(*b as i8, true),
-                        replay::Input::MouseRelease(b) => self.input.mouse_release(*b as i8, true),
-                        replay::Input::MouseWheelUp => self.input.mouse_scroll_up(),
-                        replay::Input::MouseWheelDown => self.input.mouse_scroll_down(),
-                    }
-                }
+                self.apply_replay_frame(frame);
             } else if let Some(bin) = &output_bin {
                 let render_state = self.renderer.state();
-                match SaveState::from(&mut self, replay.clone(), render_state)
+                match SaveState::from(self, replay.clone(), render_state)
                     .save_to_file(bin, &mut savestate::Buffer::new())
                 {
                     Ok(()) => break Ok(()),
@@ -2270,6 +2351,7 @@ impl Game {
                 }
             }
 
+            let frame_start = Instant::now();
             self.frame()?;
             match self.scene_change {
                 Some(SceneChange::Room(id)) => self.load_room(id)?,
@@ -2281,6 +2363,15 @@ impl Game {
                 },
                 None => (),
             }
+            if let Some(frame) = replay.get_frame(frame_count) {
+                self.replay_console(&frame.events);
+            }
+            self.print_warnings();
+            busy_time += frame_start.elapsed();
+            if let Some(mut trace) = self.replay_trace.take() {
+                trace.push(search::fingerprint(self));
+                self.replay_trace = Some(trace);
+            }
 
             // exit if X pressed or game_end() invoked
             if self.close_requested {
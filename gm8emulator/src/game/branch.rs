@@ -0,0 +1,187 @@
+//! Branches of a TAS project, so that different routes can be tried out without losing any of them.
+//!
+//! The main branch lives in the project folder itself, the same as projects from before branches existed,
+//! and every other branch gets a folder of its own under "branches". Each one has its own numbered savestates,
+//! plus a head savestate holding wherever the branch was left, which is where switching back to it picks up.
+
+use crate::game::replay::{Frame, Replay};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// What the branch kept in the project folder itself is called
+pub const MAIN: &str = "main";
+
+/// The folder a branch keeps its savestates in, where None is the main branch.
+pub fn folder(project: &Path, branch: Option<&str>) -> PathBuf {
+    match branch {
+        Some(name) => project.join("branches").join(name),
+        None => project.to_path_buf(),
+    }
+}
+
+/// Where a branch's head savestate is kept.
+pub fn head_path(project: &Path, branch: Option<&str>) -> PathBuf {
+    folder(project, branch).join("head.bin")
+}
+
+/// Where a branch's numbered savestates are kept.
+pub fn save_paths(project: &Path, branch: Option<&str>) -> Vec<PathBuf> {
+    let folder = folder(project, branch);
+    (0..16).map(|i| folder.join(format!("save{}.bin", i + 1))).collect()
+}
+
+/// Names of every branch in a project other than the main one, in order.
+pub fn list(project: &Path) -> Vec<String> {
+    let mut names = fs::read_dir(project.join("branches"))
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+/// Checks that a name is usable as a branch's folder name, without it pointing anywhere outside "branches".
+fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name == MAIN {
+        return Err(format!("A branch can't be called \"{}\"", name))
+    }
+    if !name.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_') {
+        return Err(format!("Branch name \"{}\" can only have letters, digits, - and _ in it", name))
+    }
+    Ok(())
+}
+
+/// Checks that a branch named in project.cfg has a usable name and a folder to load it from.
+pub fn check(project: &Path, name: &str) -> Result<(), String> {
+    check_name(name)?;
+    let folder = folder(project, Some(name));
+    if !folder.is_dir() {
+        return Err(format!("{} doesn't exist", folder.display()))
+    }
+    Ok(())
+}
+
+/// Makes the folder for a new branch, as long as its name is usable as a folder name and isn't taken.
+pub fn create(project: &Path, name: &str) -> Result<(), String> {
+    check_name(name)?;
+    let folder = folder(project, Some(name));
+    if folder.exists() {
+        return Err(format!("There's already a branch called \"{}\"", name))
+    }
+    fs::create_dir_all(&folder).map_err(|e| format!("Couldn't create {}: {}", folder.display(), e))
+}
+
+/// Finds every frame where two replays' inputs aren't the same, including frames only one of them has.
+pub fn compare(left: &Replay, right: &Replay) -> Vec<usize> {
+    (0..left.frame_count().max(right.frame_count()))
+        .filter(|&i| match (left.get_frame(i), right.get_frame(i)) {
+            (Some(a), Some(b)) => !same_inputs(a, b),
+            _ => true,
+        })
+        .collect()
+}
+
+/// Checks whether two replays both have the given number of frames at least, with the same inputs on all of them.
+pub fn same_until(left: &Replay, right: &Replay, end: usize) -> bool {
+    (0..end).all(|i| match (left.get_frame(i), right.get_frame(i)) {
+        (Some(a), Some(b)) => same_inputs(a, b),
+        _ => false,
+    })
+}
+
+/// Whether two frames have the same inputs, mouse position and new seed.
+fn same_inputs(a: &Frame, b: &Frame) -> bool {
+    (a.mouse_x, a.mouse_y, &a.inputs, a.new_seed) == (b.mouse_x, b.mouse_y, &b.inputs, b.new_seed)
+}
+
+/// Describes a frame's inputs for comparing them, or says there isn't one.
+pub fn describe(frame: Option<&Frame>) -> String {
+    let frame = match frame {
+        Some(frame) => frame,
+        None => return "(no frame)".into(),
+    };
+    let mut parts = frame.inputs.iter().map(|input| format!("{:?}", input)).collect::<Vec<_>>();
+    parts.push(format!("mouse ({}, {})", frame.mouse_x, frame.mouse_y));
+    if let Some(seed) = frame.new_seed {
+        parts.push(format!("seed {}", seed));
+    }
+    parts.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::replay::Input;
+
+    fn replay(frames: &[(i32, &[Input])]) -> Replay {
+        let mut replay = Replay::new(0, 0);
+        for (mouse_x, inputs) in frames.iter() {
+            let frame = replay.new_frame();
+            frame.mouse_x = *mouse_x;
+            frame.inputs = inputs.to_vec();
+        }
+        replay
+    }
+
+    fn project(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("gm8emulator-branch-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn comparing() {
+        let press = [Input::KeyPress(65)];
+        let release = [Input::KeyRelease(65)];
+        let left = replay(&[(0, &press), (0, &[]), (0, &release)]);
+        assert!(compare(&left, &left).is_empty());
+        assert!(same_until(&left, &left, 3));
+
+        let right = replay(&[(0, &press), (0, &release), (0, &release), (5, &[])]);
+        assert_eq!(compare(&left, &right), [1, 3]);
+        assert_eq!(compare(&right, &left), [1, 3]);
+        assert!(same_until(&left, &right, 1));
+        assert!(!same_until(&left, &right, 2));
+        assert!(!same_until(&left, &left, 4));
+
+        let mut seeded = left.clone();
+        seeded.new_frame().new_seed = Some(1);
+        let mut moved = left.clone();
+        moved.new_frame().mouse_y = 1;
+        let mut plain = left.clone();
+        plain.new_frame();
+        assert_eq!(compare(&seeded, &plain), [3]);
+        assert_eq!(compare(&moved, &plain), [3]);
+        assert!(compare(&plain, &plain).is_empty());
+    }
+
+    #[test]
+    fn creating() {
+        let path = project("create");
+        assert!(list(&path).is_empty());
+        assert!(create(&path, "").is_err());
+        assert!(create(&path, MAIN).is_err());
+        assert!(create(&path, "../escape").is_err());
+        assert!(create(&path, "a b").is_err());
+        assert!(check(&path, "route-2_b").is_err());
+
+        create(&path, "route-2_b").unwrap();
+        create(&path, "alt").unwrap();
+        assert!(create(&path, "alt").is_err());
+        assert!(folder(&path, Some("alt")).is_dir());
+        assert_eq!(list(&path), ["alt", "route-2_b"]);
+        assert!(check(&path, "alt").is_ok());
+        assert!(check(&path, "..").is_err());
+        assert_eq!(save_paths(&path, Some("alt"))[0], path.join("branches").join("alt").join("save1.bin"));
+        assert_eq!(head_path(&path, None), path.join("head.bin"));
+        fs::remove_dir_all(&path).unwrap();
+    }
+}
@@ -0,0 +1,183 @@
+//! A console for running GML against the live game, from a window in the recorder or from stdin.
+//!
+//! Code typed in runs as a chosen instance, or as a dummy instance like room creation code does.
+//! While recording, everything run gets saved in the replay so that it happens again on playback.
+
+use crate::{
+    game::{replay, Game, GetAsset},
+    gml::{
+        runtime::{Instruction, Node},
+        Code, Context, Value,
+    },
+    instance::Instance,
+    types::ID,
+};
+use std::{
+    io::{self, BufRead},
+    sync::mpsc,
+    thread,
+};
+
+const HELP: &str = concat!(
+    "Anything typed is run as GML, and the value of a single expression or a return is shown.\n",
+    "Commands:\n",
+    "  :self ID    run as the instance with the given id\n",
+    "  :self       run as a dummy instance\n",
+    "  :history    show everything run so far\n",
+    "  :help       show this",
+);
+
+/// What's been typed into a console, and what came back.
+#[derive(Default)]
+pub struct Console {
+    /// The instance code runs as, or None for a dummy instance
+    pub instance: Option<ID>,
+
+    /// Everything typed so far, oldest first, without repeats in a row
+    pub history: Vec<String>,
+
+    /// Everything typed and what came back, for showing in a window
+    pub output: Vec<String>,
+}
+
+enum Compiled {
+    Code(Code),
+    Expression(Node),
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs a line typed into the console, which is either GML or a command starting with a colon.
+    /// Gives back what to show for it.
+    pub fn submit(&mut self, game: &mut Game, line: &str) -> String {
+        let line = line.trim();
+        if line.is_empty() {
+            return String::new()
+        }
+        if self.history.last().map(String::as_str) != Some(line) {
+            self.history.push(line.into());
+        }
+        let reply = match line.strip_prefix(':') {
+            Some(command) => self.command(command),
+            None => match game.run_console(line, self.instance) {
+                Ok(Some(value)) => value.to_string(),
+                Ok(None) => "ok".into(),
+                Err(e) => format!("error: {}", e),
+            },
+        };
+        self.output.push(format!("> {}", line));
+        self.output.push(reply.clone());
+        reply
+    }
+
+    fn command(&mut self, command: &str) -> String {
+        let mut words = command.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("self"), None) => {
+                self.instance = None;
+                "running as a dummy instance".into()
+            },
+            (Some("self"), Some(id)) => match id.parse::<ID>() {
+                Ok(id) => {
+                    self.instance = Some(id);
+                    format!("running as instance {}", id)
+                },
+                Err(_) => format!("error: {} isn't an instance id", id),
+            },
+            (Some("history"), None) => self.history.join("\n"),
+            (Some("help"), None) => HELP.into(),
+            _ => format!("error: unknown command :{}, try :help", command),
+        }
+    }
+}
+
+/// A console which reads lines from stdin on another thread, so the game keeps running while nothing's typed.
+pub struct StdinConsole {
+    console: Console,
+    lines: mpsc::Receiver<String>,
+}
+
+impl StdinConsole {
+    pub fn spawn() -> Self {
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in io::stdin().lock().lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break
+                }
+            }
+        });
+        Self { console: Console::new(), lines }
+    }
+}
+
+impl Game {
+    /// Runs some GML as the instance with the given id, or as a dummy instance if there isn't one.
+    /// Gives back its value if it's a single expression or it returned something.
+    /// Anything which changes room only does so when the next frame runs.
+    pub fn run_console(&mut self, source: &str, instance: Option<ID>) -> Result<Option<Value>, String> {
+        let compiled = match self.compiler.compile(source.as_bytes()) {
+            Ok(code) => Compiled::Code(code),
+            // Things like `obj_player.x` aren't statements, but they're what people most want to look at
+            Err(e) => match self.compiler.compile_expression(source.as_bytes()) {
+                Ok(node) => Compiled::Expression(node),
+                Err(_) => return Err(e.to_string()),
+            },
+        };
+
+        let handle = match instance {
+            Some(id) => match self.room.instance_list.get_by_instid(id) {
+                Some(handle) => handle,
+                None => return Err(format!("instance {} doesn't exist", id)),
+            },
+            None => self
+                .room
+                .instance_list
+                .insert_dummy(Instance::new_dummy(self.assets.objects.get_asset(0).map(|x| x.as_ref()))),
+        };
+        let mut context = Context::with_single_instance(handle);
+        let result = match &compiled {
+            Compiled::Expression(node) => self.eval(node, &mut context).map(Some),
+            Compiled::Code(code) => self
+                .execute_code(code, &mut context)
+                .map(|_| if has_value(code) { Some(context.return_value.clone()) } else { None }),
+        };
+        if instance.is_none() {
+            self.room.instance_list.remove_dummy(handle);
+        }
+        result.map_err(|e| e.to_string())
+    }
+
+    /// Runs the GML from any console events recorded for a frame, which happen after the frame itself.
+    /// Errors are ignored, since they happened while recording too.
+    pub fn replay_console(&mut self, events: &[replay::Event]) {
+        for event in events {
+            if let replay::Event::Console { code, instance } = event {
+                self.run_console(code, *instance).ok();
+            }
+        }
+    }
+
+    /// Runs anything typed into the stdin console since the last frame, printing what comes back.
+    pub fn poll_console(&mut self) {
+        if let Some(mut stdin) = self.console.take() {
+            while let Ok(line) = stdin.lines.try_recv() {
+                let reply = stdin.console.submit(self, &line);
+                if !reply.is_empty() {
+                    println!("{}", reply);
+                }
+            }
+            self.console = Some(stdin);
+        }
+    }
+}
+
+/// Whether some code's value is worth showing, which is when it's a single expression or it returns something.
+fn has_value(code: &Code) -> bool {
+    let mut lines = code.iter().filter(|instruction| !matches!(instruction, Instruction::Line { .. }));
+    matches!((lines.next(), lines.next()), (Some(Instruction::EvalExpression { .. }), None))
+        || code.iter().any(|instruction| matches!(instruction, Instruction::SetReturnValue { .. }))
+}
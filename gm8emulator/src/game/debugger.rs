@@ -0,0 +1,823 @@
+//! An interactive GML debugger, which stops at breakpoints and steps through code a line at a time.
+//!
+//! Only code compiled with `Compiler::set_line_info` has lines to stop on, which is done when the emulator is
+//! started with `--debug`. While a debugger is attached, everything runs in the tree interpreter, since that's the
+//! one which sees the line numbers.
+//!
+//! `LineFrontend` drives the debugger with text commands, one per line, and ends every reply with a line
+//! saying either `ok` or `error: <message>`. Whenever the game stops it says `stopped <reason>: <where>`,
+//! followed by the line of source code if it's known. The commands are:
+//!
+//! - `continue`, `step`, `next`, `finish`: carry on until the next breakpoint, the next line,
+//!   the next line not in a deeper call, or the next line after the current call returns
+//! - `detach`: remove the debugger and carry on
+//! - `break <location> [action N] [line N]`: add a breakpoint, where the location is one of
+//!   `script NAME`, `object NAME EVENT NUMBER`, `timeline NAME MOMENT`, `room NAME [instance ID]` or `trigger NAME`
+//! - `delete N`, `breakpoints`: remove a breakpoint, or list them
+//! - `stack`, `locals`, `fields [ID]`, `globals`: show the call stack, the local variables,
+//!   the fields of self or another instance, or the global variables
+
+use crate::{
+    action::{Body, GmlBody, Tree},
+    game::{Assets, Game, GetAsset},
+    gml::{
+        self,
+        runtime::{Instruction, ReturnType},
+        Code, Context,
+    },
+    instance::Field,
+    types::ID,
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Write},
+    net::TcpListener,
+    rc::{Rc, Weak},
+};
+
+/// Names of event types, as used in breakpoint locations.
+pub const EVENT_NAMES: [&str; 12] = [
+    "create",
+    "destroy",
+    "alarm",
+    "step",
+    "collision",
+    "keyboard",
+    "mouse",
+    "other",
+    "draw",
+    "keypress",
+    "keyrelease",
+    "trigger",
+];
+
+/// Where a piece of code came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Location {
+    Script(ID),
+    Event {
+        object: ID,
+        event_type: usize,
+        event_number: usize,
+    },
+    Moment {
+        timeline: ID,
+        moment: i32,
+    },
+    RoomCreation(ID),
+    InstanceCreation {
+        room: ID,
+        instance: ID,
+    },
+    Trigger(ID),
+
+    /// Anything else, such as extension functions or code compiled by execute_string
+    Other,
+}
+
+/// A piece of code which is running.
+pub struct Frame {
+    pub location: Location,
+
+    /// Which action this is, for code in an event or timeline moment
+    pub action: Option<usize>,
+
+    /// The line last run, if any have been yet
+    pub line: Option<usize>,
+}
+
+/// A place to stop. If there's no line, it stops on the first line run at that location.
+#[derive(Clone)]
+pub struct Breakpoint {
+    pub location: Location,
+    pub action: Option<usize>,
+    pub line: Option<usize>,
+}
+
+/// What to do after stopping.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resume {
+    Continue,
+    StepInto,
+    StepOver,
+    StepOut,
+    Detach,
+}
+
+/// Why the game stopped.
+#[derive(Clone, Copy, Debug)]
+pub enum Reason {
+    Step,
+    Breakpoint(usize),
+}
+
+#[derive(Clone, Copy)]
+enum Step {
+    Run,
+    Into,
+    Over(usize),
+    Out(usize),
+}
+
+/// Something which is told when the game stops, and decides how it carries on.
+pub trait Frontend {
+    fn stopped(&mut self, stop: &mut Stop) -> Resume;
+}
+
+pub struct Debugger {
+    pub breakpoints: Vec<Breakpoint>,
+    stack: Vec<Frame>,
+    step: Step,
+
+    /// How many lines have been run since the debugger was attached or restarted
+    ticks: u64,
+
+    /// Stops on or before this tick are skipped, for running back up to where a previous run stopped
+    skip_until: u64,
+
+    locations: CodeIndex,
+
+    frontend: Box<dyn Frontend>,
+}
+
+impl Debugger {
+    pub fn new(frontend: Box<dyn Frontend>) -> Self {
+        Self {
+            breakpoints: Vec::new(),
+            stack: Vec::new(),
+            step: Step::Run,
+            ticks: 0,
+            skip_until: 0,
+            locations: CodeIndex::default(),
+            frontend,
+        }
+    }
+
+    /// Sets how to carry on, as if the frontend had said so when stopped at the current depth.
+    pub fn resume(&mut self, resume: Resume) {
+        let depth = self.stack.len();
+        self.step = match resume {
+            Resume::Continue | Resume::Detach => Step::Run,
+            Resume::StepInto => Step::Into,
+            Resume::StepOver => Step::Over(depth),
+            Resume::StepOut => Step::Out(depth),
+        };
+    }
+
+    /// Starts counting lines from zero, and skips everything up to and including the given tick before carrying on
+    /// as if it had stopped there at the given depth. Running the same code again this way gives the same stops
+    /// as continuing from the earlier stop would have, which is how frontends step without pausing the game.
+    pub fn resume_after(&mut self, tick: u64, depth: usize, resume: Resume) {
+        self.ticks = 0;
+        self.skip_until = tick;
+        self.step = match resume {
+            Resume::Continue | Resume::Detach => Step::Run,
+            Resume::StepInto => Step::Into,
+            Resume::StepOver => Step::Over(depth),
+            Resume::StepOut => Step::Out(depth),
+        };
+    }
+
+    /// Moves on to a new line, and says why to stop there if it should.
+    fn line(&mut self, line: usize) -> Option<Reason> {
+        self.ticks += 1;
+        let depth = self.stack.len();
+        let frame = self.stack.last_mut()?;
+        let first = frame.line.is_none();
+        frame.line = Some(line);
+        if self.ticks <= self.skip_until {
+            return None
+        }
+        let stepped = match self.step {
+            Step::Run => false,
+            Step::Into => true,
+            Step::Over(d) => depth <= d,
+            Step::Out(d) => depth < d,
+        };
+        if stepped {
+            Some(Reason::Step)
+        } else {
+            self.breakpoints.iter().position(|bp| bp.hit(frame, first)).map(Reason::Breakpoint)
+        }
+    }
+}
+
+impl Breakpoint {
+    /// Parses a breakpoint, in the form given to the `break` command.
+    pub fn parse(text: &str, game: &Game) -> Result<Self, String> {
+        let mut words = text.split_whitespace();
+        let mut next = |what: &str| words.next().ok_or_else(|| format!("expected {}", what));
+        let location = match next("script, object, timeline, room or trigger")? {
+            "script" => Location::Script(asset_id(next("a script")?, game, |a| &a.scripts, |s| &s.name)?),
+            "object" => {
+                let object = asset_id(next("an object")?, game, |a| &a.objects, |o| &o.name)?;
+                let event = next("an event type")?;
+                let event_type = match EVENT_NAMES.iter().position(|name| *name == event) {
+                    Some(event_type) => event_type,
+                    None => number(event).filter(|t| *t < EVENT_NAMES.len()).ok_or("invalid event type")?,
+                };
+                let event_number = number(next("an event number")?).ok_or("invalid event number")?;
+                Location::Event { object, event_type, event_number }
+            },
+            "timeline" => {
+                let timeline = asset_id(next("a timeline")?, game, |a| &a.timelines, |t| &t.name)?;
+                let moment = next("a moment")?.parse().map_err(|_| "invalid moment")?;
+                Location::Moment { timeline, moment }
+            },
+            "room" => Location::RoomCreation(asset_id(next("a room")?, game, |a| &a.rooms, |r| &r.name)?),
+            "trigger" => Location::Trigger(asset_id(next("a trigger")?, game, |a| &a.triggers, |t| &t.name)?),
+            other => return Err(format!("unknown location \"{}\"", other)),
+        };
+        let mut breakpoint = Self { location, action: None, line: None };
+        while let Some(word) = words.next() {
+            let value = words.next().ok_or_else(|| format!("expected a number after {}", word))?;
+            match (word, breakpoint.location) {
+                ("action", _) => breakpoint.action = Some(number(value).ok_or("invalid action")?),
+                ("line", _) => breakpoint.line = Some(number(value).ok_or("invalid line")?),
+                ("instance", Location::RoomCreation(room)) => {
+                    let instance = value.parse().map_err(|_| "invalid instance id")?;
+                    breakpoint.location = Location::InstanceCreation { room, instance };
+                },
+                _ => return Err(format!("unexpected \"{}\"", word)),
+            }
+        }
+        Ok(breakpoint)
+    }
+
+    /// Describes the breakpoint in the same form it's parsed from.
+    pub fn describe(&self, game: &Game) -> String {
+        let mut text = describe_location(self.location, game);
+        if let Some(action) = self.action {
+            text += &format!(" action {}", action);
+        }
+        if let Some(line) = self.line {
+            text += &format!(" line {}", line);
+        }
+        text
+    }
+
+    fn hit(&self, frame: &Frame, first: bool) -> bool {
+        self.location == frame.location
+            && (self.action.is_none() || frame.action == self.action)
+            && match self.line {
+                Some(line) => frame.line == Some(line),
+                None => first,
+            }
+    }
+}
+
+/// The game while it's stopped, for frontends to look at.
+pub struct Stop<'a> {
+    pub game: &'a Game,
+    pub context: &'a Context,
+    pub stack: &'a [Frame],
+    pub breakpoints: &'a mut Vec<Breakpoint>,
+    pub reason: Reason,
+
+    /// How many lines had been run when it stopped, for `Debugger::resume_after`
+    pub tick: u64,
+}
+
+impl Stop<'_> {
+    pub fn describe_reason(&self) -> String {
+        match self.reason {
+            Reason::Step => "step".into(),
+            Reason::Breakpoint(i) => format!("breakpoint {}", i),
+        }
+    }
+
+    pub fn describe_frame(&self, frame: &Frame) -> String {
+        let mut text = describe_location(frame.location, self.game);
+        if let Some(action) = frame.action {
+            text += &format!(" action {}", action);
+        }
+        if let Some(line) = frame.line {
+            text += &format!(", line {}", line);
+        }
+        text
+    }
+
+    /// Describes each frame on the stack, starting with the innermost.
+    pub fn backtrace(&self) -> Vec<String> {
+        self.stack.iter().rev().map(|frame| self.describe_frame(frame)).collect()
+    }
+
+    /// The source code of the line it stopped on, if it's known. Only scripts keep their source code.
+    pub fn source_line(&self) -> Option<String> {
+        let frame = self.stack.last()?;
+        match frame.location {
+            Location::Script(id) => {
+                let script = self.game.assets.scripts.get_asset(id)?;
+                let source = script.source.decode(self.game.encoding);
+                source.lines().nth(frame.line?.checked_sub(1)?).map(String::from)
+            },
+            _ => None,
+        }
+    }
+
+    pub fn locals(&self) -> Vec<(String, String)> {
+        fields(&self.context.locals.fields, self.game)
+    }
+
+    pub fn globals(&self) -> Vec<(String, String)> {
+        fields(&self.game.globals.fields, self.game)
+    }
+
+    /// The fields of an instance, or of self if no id is given, along with a few built-in variables.
+    pub fn instance(&self, id: Option<ID>) -> Option<Vec<(String, String)>> {
+        let handle = match id {
+            Some(id) => self.game.room.instance_list.get_by_instid(id)?,
+            None => self.context.this,
+        };
+        let instance = self.game.room.instance_list.get(handle);
+        let object_name = match self.game.assets.objects.get_asset(instance.object_index.get()) {
+            Some(object) => object.name.decode(self.game.encoding).into_owned(),
+            None => "<deleted object>".into(),
+        };
+        let mut output = vec![
+            ("id".into(), instance.id.get().to_string()),
+            ("object_index".into(), format!("{} ({})", instance.object_index.get(), object_name)),
+            ("x".into(), instance.x.get().to_string()),
+            ("y".into(), instance.y.get().to_string()),
+        ];
+        output.extend(fields(&instance.fields.borrow(), self.game));
+        Some(output)
+    }
+
+    /// Copies out everything a frontend might want to show, so it can be looked at after the game carries on.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            tick: self.tick,
+            depth: self.stack.len(),
+            reason: self.describe_reason(),
+            backtrace: self.backtrace(),
+            source_line: self.source_line(),
+            locals: self.locals(),
+            instance: self.instance(None).unwrap_or_default(),
+            globals: self.globals(),
+        }
+    }
+}
+
+/// A copy of what the game looked like when it stopped.
+pub struct Snapshot {
+    pub tick: u64,
+    pub depth: usize,
+    pub reason: String,
+    pub backtrace: Vec<String>,
+    pub source_line: Option<String>,
+    pub locals: Vec<(String, String)>,
+    pub instance: Vec<(String, String)>,
+    pub globals: Vec<(String, String)>,
+}
+
+/// A frontend which takes a snapshot of the first stop and then lets the game carry on. Running the same frame
+/// again with `Debugger::resume_after` steps through it, without the game ever having to wait for the user.
+pub struct SnapshotFrontend(pub Rc<RefCell<Option<Snapshot>>>);
+
+impl Frontend for SnapshotFrontend {
+    fn stopped(&mut self, stop: &mut Stop) -> Resume {
+        let mut snapshot = self.0.borrow_mut();
+        if snapshot.is_none() {
+            *snapshot = Some(stop.snapshot());
+        }
+        Resume::Continue
+    }
+}
+
+/// A frontend which reads commands a line at a time, from a terminal or a socket.
+pub struct LineFrontend<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> LineFrontend<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self { input, output }
+    }
+
+    fn command(&mut self, stop: &mut Stop, command: &str, args: &str) -> io::Result<Result<Option<Resume>, String>> {
+        match command {
+            "continue" | "c" => return Ok(Ok(Some(Resume::Continue))),
+            "step" | "s" => return Ok(Ok(Some(Resume::StepInto))),
+            "next" | "n" => return Ok(Ok(Some(Resume::StepOver))),
+            "finish" | "out" => return Ok(Ok(Some(Resume::StepOut))),
+            "detach" => return Ok(Ok(Some(Resume::Detach))),
+            "break" | "b" => match Breakpoint::parse(args, stop.game) {
+                Ok(breakpoint) => {
+                    writeln!(self.output, "breakpoint {}: {}", stop.breakpoints.len(), breakpoint.describe(stop.game))?;
+                    stop.breakpoints.push(breakpoint);
+                },
+                Err(e) => return Ok(Err(e)),
+            },
+            "delete" | "d" => match args.trim().parse::<usize>() {
+                Ok(i) if i < stop.breakpoints.len() => {
+                    stop.breakpoints.remove(i);
+                },
+                _ => return Ok(Err(format!("no breakpoint {}", args.trim()))),
+            },
+            "breakpoints" => {
+                for (i, breakpoint) in stop.breakpoints.iter().enumerate() {
+                    writeln!(self.output, "{}: {}", i, breakpoint.describe(stop.game))?;
+                }
+            },
+            "stack" | "bt" => {
+                for (i, frame) in stop.backtrace().iter().enumerate() {
+                    writeln!(self.output, "#{} {}", i, frame)?;
+                }
+            },
+            "locals" => self.values(&stop.locals())?,
+            "globals" => self.values(&stop.globals())?,
+            "fields" => {
+                let id = match args.trim() {
+                    "" => None,
+                    id => match id.parse() {
+                        Ok(id) => Some(id),
+                        Err(_) => return Ok(Err(format!("invalid instance id {}", id))),
+                    },
+                };
+                match stop.instance(id) {
+                    Some(values) => self.values(&values)?,
+                    None => return Ok(Err("no such instance".into())),
+                }
+            },
+            "help" => writeln!(
+                self.output,
+                "continue, step, next, finish, detach, break <location>, delete <n>, breakpoints, \
+                stack, locals, fields [id], globals",
+            )?,
+            _ => return Ok(Err(format!("unknown command \"{}\"", command))),
+        }
+        Ok(Ok(None))
+    }
+
+    fn values(&mut self, values: &[(String, String)]) -> io::Result<()> {
+        for (name, value) in values {
+            writeln!(self.output, "{} = {}", name, value)?;
+        }
+        Ok(())
+    }
+
+    fn session(&mut self, stop: &mut Stop) -> io::Result<Resume> {
+        let frame = stop.stack.last().map(|frame| stop.describe_frame(frame)).unwrap_or_default();
+        writeln!(self.output, "stopped {}: {}", stop.describe_reason(), frame)?;
+        if let Some(source) = stop.source_line() {
+            writeln!(self.output, "{}", source)?;
+        }
+        let mut line = String::new();
+        loop {
+            self.output.flush()?;
+            line.clear();
+            if self.input.read_line(&mut line)? == 0 {
+                // Nobody's there any more
+                return Ok(Resume::Detach)
+            }
+            let line = line.trim();
+            if line.is_empty() {
+                continue
+            }
+            let (command, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            match self.command(stop, command, args)? {
+                Ok(resume) => {
+                    writeln!(self.output, "ok")?;
+                    if let Some(resume) = resume {
+                        self.output.flush()?;
+                        return Ok(resume)
+                    }
+                },
+                Err(e) => writeln!(self.output, "error: {}", e)?,
+            }
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> Frontend for LineFrontend<R, W> {
+    fn stopped(&mut self, stop: &mut Stop) -> Resume {
+        match self.session(stop) {
+            Ok(resume) => resume,
+            Err(e) => {
+                eprintln!("Debugger connection failed, detaching: {}", e);
+                Resume::Detach
+            },
+        }
+    }
+}
+
+/// A line-based frontend on stdin and stdout.
+pub fn stdio_frontend() -> Box<dyn Frontend> {
+    Box::new(LineFrontend::new(BufReader::new(io::stdin()), io::stdout()))
+}
+
+/// A line-based frontend on a local TCP port. This waits for something to connect to it.
+pub fn tcp_frontend(port: u16) -> io::Result<Box<dyn Frontend>> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    println!("Waiting for a debugger to connect on port {}...", port);
+    let (stream, _) = listener.accept()?;
+    Ok(Box::new(LineFrontend::new(BufReader::new(stream.try_clone()?), stream)))
+}
+
+impl Game {
+    /// Runs some code under the debugger, keeping track of where it came from.
+    pub(crate) fn debug_code(&mut self, code: &Code, context: &mut Context) -> gml::Result<ReturnType> {
+        if let Some(debugger) = self.debugger.as_mut() {
+            let (location, action) = debugger.locations.locate(code, &self.assets);
+            debugger.stack.push(Frame { location, action, line: None });
+        }
+        let result = self.execute(code, context);
+        if let Some(debugger) = self.debugger.as_mut() {
+            debugger.stack.pop();
+        }
+        result
+    }
+
+    /// Lets the debugger and profiler find code which has just been added to the assets.
+    pub(crate) fn code_added(&mut self) {
+        if let Some(debugger) = self.debugger.as_mut() {
+            debugger.locations.code_added();
+        }
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.code_added();
+        }
+    }
+
+    /// Tells the debugger a new line is being run, and lets its frontend take over if it should stop there.
+    pub(crate) fn debug_line(&mut self, line: usize, context: &Context) {
+        let mut debugger = match self.debugger.take() {
+            Some(debugger) => debugger,
+            None => return,
+        };
+        if let Some(reason) = debugger.line(line) {
+            let mut stop = Stop {
+                game: self,
+                context,
+                stack: &debugger.stack,
+                breakpoints: &mut debugger.breakpoints,
+                reason,
+                tick: debugger.ticks,
+            };
+            match debugger.frontend.stopped(&mut stop) {
+                Resume::Detach => return,
+                resume => debugger.resume(resume),
+            }
+        }
+        self.debugger = Some(debugger);
+    }
+}
+
+/// Describes a location in the same form breakpoints use.
+pub fn describe_location(location: Location, game: &Game) -> String {
+    fn name<T>(assets: &[Option<Box<T>>], id: ID, get_name: fn(&T) -> &gml::String, game: &Game) -> String {
+        match usize::try_from(id).ok().and_then(|id| assets.get(id)).and_then(|x| x.as_ref()) {
+            Some(asset) => get_name(asset).decode(game.encoding).into_owned(),
+            None => id.to_string(),
+        }
+    }
+    let assets = &game.assets;
+    match location {
+        Location::Script(id) => format!("script {}", name(&assets.scripts, id, |s| &s.name, game)),
+        Location::Event { object, event_type, event_number } => format!(
+            "object {} {} {}",
+            name(&assets.objects, object, |o| &o.name, game),
+            EVENT_NAMES.get(event_type).copied().unwrap_or("?"),
+            event_number,
+        ),
+        Location::Moment { timeline, moment } => {
+            format!("timeline {} {}", name(&assets.timelines, timeline, |t| &t.name, game), moment)
+        },
+        Location::RoomCreation(room) => format!("room {}", name(&assets.rooms, room, |r| &r.name, game)),
+        Location::InstanceCreation { room, instance } => {
+            format!("room {} instance {}", name(&assets.rooms, room, |r| &r.name, game), instance)
+        },
+        Location::Trigger(id) => format!("trigger {}", name(&assets.triggers, id, |t| &t.name, game)),
+        Location::Other => "<other code>".into(),
+    }
+}
+
+/// Finds an asset by name or by id.
+fn asset_id<T>(
+    text: &str,
+    game: &Game,
+    get_assets: fn(&Assets) -> &Vec<Option<Box<T>>>,
+    get_name: fn(&T) -> &gml::String,
+) -> Result<ID, String> {
+    let assets = get_assets(&game.assets);
+    let by_name = assets.iter().position(|asset| match asset {
+        Some(asset) => get_name(asset).decode(game.encoding) == text,
+        None => false,
+    });
+    let by_id = || text.parse::<usize>().ok().filter(|id| matches!(assets.get(*id), Some(Some(_))));
+    match by_name.or_else(by_id) {
+        Some(id) => Ok(id as ID),
+        None => Err(format!("no such asset \"{}\"", text)),
+    }
+}
+
+fn number(text: &str) -> Option<usize> {
+    text.parse().ok()
+}
+
+/// Lists some fields by name, sorted.
+fn fields(fields: &HashMap<usize, Field>, game: &Game) -> Vec<(String, String)> {
+    let mut output = Vec::with_capacity(fields.len());
+    for (id, field) in fields {
+        let name = game.compiler.get_field_name(*id).unwrap_or_else(|| "<???>".into());
+        match field {
+            Field::Single(value) => output.push((name, value.to_string())),
+            Field::Array(array) => {
+                let mut indices = array.keys().copied().collect::<Vec<_>>();
+                indices.sort_unstable();
+                for index in indices {
+                    let label = if index < 32000 {
+                        format!("{}[{}]", name, index)
+                    } else {
+                        format!("{}[{}, {}]", name, index / 32000, index % 32000)
+                    };
+                    output.push((label, array[&index].to_string()));
+                }
+            },
+        }
+    }
+    output.sort_by(|(a, _), (b, _)| a.cmp(b));
+    output
+}
+
+/// Finds where code came from, and which action it is if it's in an event or timeline moment.
+///
+/// Code is looked up by the address of its instructions. Every entry holds a weak reference to them, so the address
+/// can't be reused by other code while it's in the index. The assets are only searched again after `code_added`.
+#[derive(Default)]
+pub struct CodeIndex {
+    entries: HashMap<usize, Indexed>,
+    up_to_date: bool,
+
+    /// How many entries there were after code which no longer exists was last forgotten
+    live: usize,
+}
+
+struct Indexed {
+    code: Weak<[Instruction]>,
+    location: Location,
+    action: Option<usize>,
+}
+
+impl CodeIndex {
+    pub fn locate(&mut self, code: &Code, assets: &Assets) -> (Location, Option<usize>) {
+        let address = code.as_ptr() as usize;
+        if !self.up_to_date && !self.entries.contains_key(&address) {
+            self.entries = index(assets);
+            self.up_to_date = true;
+            self.live = self.entries.len();
+        }
+        if self.entries.len() >= self.live * 2 + 64 && !self.entries.contains_key(&address) {
+            // Code compiled at runtime, such as by execute_string, comes and goes, so whatever's gone is forgotten
+            // now and then rather than being kept around forever
+            self.entries.retain(|_, entry| entry.code.strong_count() > 0);
+            self.live = self.entries.len();
+        }
+        let entry = self.entries.entry(address).or_insert_with(|| Indexed {
+            code: code.downgrade(),
+            location: Location::Other,
+            action: None,
+        });
+        (entry.location, entry.action)
+    }
+
+    /// Makes the next unknown code search the assets again, which must be done whenever code is added to them.
+    pub fn code_added(&mut self) {
+        self.up_to_date = false;
+    }
+
+    /// Forgets everything, which must be done whenever the assets are replaced.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.up_to_date = false;
+        self.live = 0;
+    }
+}
+
+/// Finds where all the code in the assets came from, by the address of its instructions.
+fn index(assets: &Assets) -> HashMap<usize, Indexed> {
+    let mut index = HashMap::new();
+    let mut insert = |code: &Code, location: Location, action: Option<usize>| {
+        index.insert(code.as_ptr() as usize, Indexed { code: code.downgrade(), location, action });
+    };
+    let mut add_tree = |tree: &Tree, location: Location| {
+        for action in tree.iter() {
+            if let Body::Normal { body: GmlBody::Code(code), .. } = &action.body {
+                insert(code, location, Some(action.index));
+            }
+        }
+    };
+    for (object_id, object) in assets.objects.iter().enumerate() {
+        if let Some(object) = object {
+            for (event_type, events) in object.events.iter().enumerate() {
+                for (event_number, tree) in events.iter() {
+                    let location =
+                        Location::Event { object: object_id as ID, event_type, event_number: *event_number as usize };
+                    add_tree(&tree.borrow(), location);
+                }
+            }
+        }
+    }
+    for (timeline_id, timeline) in assets.timelines.iter().enumerate() {
+        if let Some(timeline) = timeline {
+            for (moment, tree) in timeline.moments.borrow().iter() {
+                add_tree(&tree.borrow(), Location::Moment { timeline: timeline_id as ID, moment: *moment });
+            }
+        }
+    }
+    for (script_id, script) in assets.scripts.iter().enumerate() {
+        if let Some(script) = script {
+            insert(&script.compiled, Location::Script(script_id as ID), None);
+        }
+    }
+    for (room_id, room) in assets.rooms.iter().enumerate() {
+        if let Some(room) = room {
+            if let Ok(code) = &room.creation_code {
+                insert(code, Location::RoomCreation(room_id as ID), None);
+            }
+            for instance in room.instances.iter() {
+                if let Ok(code) = &instance.creation {
+                    let location = Location::InstanceCreation { room: room_id as ID, instance: instance.id };
+                    insert(code, location, None);
+                }
+            }
+        }
+    }
+    for (trigger_id, trigger) in assets.triggers.iter().enumerate() {
+        if let Some(trigger) = trigger {
+            insert(&trigger.condition, Location::Trigger(trigger_id as ID), None);
+        }
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{asset::Script, gml::Compiler};
+
+    fn assets() -> Assets {
+        Assets {
+            backgrounds: Vec::new(),
+            fonts: Vec::new(),
+            objects: Vec::new(),
+            paths: Vec::new(),
+            rooms: Vec::new(),
+            scripts: Vec::new(),
+            sounds: Vec::new(),
+            sprites: Vec::new(),
+            timelines: Vec::new(),
+            triggers: Vec::new(),
+        }
+    }
+
+    fn script(compiled: Code) -> Option<Box<Script>> {
+        Some(Box::new(Script { name: "scr_test".into(), source: "".into(), compiled }))
+    }
+
+    #[test]
+    fn scripts() {
+        let mut compiler = Compiler::new();
+        let mut assets = assets();
+        assets.scripts.push(script(compiler.compile(b"a = 1").unwrap()));
+        assets.scripts.push(script(compiler.compile(b"b = 2").unwrap()));
+        let mut index = CodeIndex::default();
+        assert_eq!(index.locate(&assets.scripts[1].as_ref().unwrap().compiled, &assets), (Location::Script(1), None));
+        assert_eq!(index.locate(&assets.scripts[0].as_ref().unwrap().compiled, &assets), (Location::Script(0), None));
+    }
+
+    #[test]
+    fn code_added() {
+        let mut compiler = Compiler::new();
+        let mut assets = assets();
+        let mut index = CodeIndex::default();
+        let code = compiler.compile(b"a = 1").unwrap();
+        assert_eq!(index.locate(&code, &assets), (Location::Other, None));
+
+        // Unknown code is remembered, so the assets aren't searched again until they're said to have changed
+        assets.scripts.push(script(code.clone()));
+        assert_eq!(index.locate(&code, &assets), (Location::Other, None));
+        index.code_added();
+        assert_eq!(index.locate(&code, &assets), (Location::Script(0), None));
+    }
+
+    #[test]
+    fn addresses_arent_reused() {
+        let mut compiler = Compiler::new();
+        let mut assets = assets();
+        assets.scripts.push(script(compiler.compile(b"a = 1").unwrap()));
+        let mut index = CodeIndex::default();
+        let old = assets.scripts[0].as_ref().unwrap().compiled.as_ptr();
+        assert_eq!(index.locate(&assets.scripts[0].as_ref().unwrap().compiled, &assets), (Location::Script(0), None));
+
+        // Lots of short-lived code comes and goes, and none of it can be mistaken for the script
+        assets.scripts.clear();
+        for _ in 0..1000 {
+            let code = compiler.compile(b"a = 1").unwrap();
+            assert_ne!(code.as_ptr(), old);
+            assert_eq!(index.locate(&code, &assets), (Location::Other, None));
+        }
+    }
+}
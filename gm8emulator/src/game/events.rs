@@ -1,11 +1,13 @@
 use crate::{
+    action::Tree,
     asset::trigger::TriggerTime,
-    game::{Game, GetAsset},
+    game::{debugger::Location, profiler::Entry, Game, GetAsset},
     gml,
     input::MouseButton,
     instance::Instance,
     types::ID,
 };
+use std::{cell::RefCell, rc::Rc};
 
 impl Game {
     /// Runs an event for all objects which hold the given event.
@@ -77,12 +79,29 @@ impl Game {
                 }
             };
 
-            self.execute_tree(event, instance, other, event_id, event_sub as _, object_id)
+            let location = Location::Event { object: object_id, event_type: event_id, event_number: event_sub as _ };
+            self.profile(Entry::Code(location), |game| {
+                game.execute_tree(event, instance, other, event_id, event_sub as _, object_id)
+            })
         } else {
             Ok(())
         }
     }
 
+    /// Runs a timeline moment for a given instance.
+    pub fn run_moment(
+        &mut self,
+        tree: Rc<RefCell<Tree>>,
+        timeline: ID,
+        moment: i32,
+        instance: usize,
+        as_object: ID,
+    ) -> gml::Result<()> {
+        self.profile(Entry::Code(Location::Moment { timeline, moment }), |game| {
+            game.execute_tree(tree, instance, instance, 0, 0, as_object)
+        })
+    }
+
     /// Runs room end followed by game end events for all instances. Should be called only when the game ends.
     pub fn run_game_end_events(&mut self) -> gml::Result<()> {
         // Reset this so the events will run
@@ -127,7 +146,7 @@ impl Game {
                             context.event_type = 11; // ev_trigger
                             context.event_number = trigger_id as _;
                             context.event_object = self.room.instance_list.get(handle).object_index.get();
-                            self.execute(&trigger.condition, &mut context)?;
+                            self.execute_code(&trigger.condition, &mut context)?;
                             if context.return_value.is_truthy() {
                                 self.run_instance_event(gml::ev::TRIGGER, trigger_id, handle, handle, None)?;
                             }
@@ -0,0 +1,152 @@
+//! Works out where instances' hitboxes are, for drawing over the game in the recorder.
+//!
+//! Precise masks come from the same colliders and pixel checks that `Game::check_collision` uses,
+//! so what gets drawn is exactly what the game collides with.
+
+use crate::{
+    asset::sprite::{Collider, Sprite},
+    game::{Game, GetAsset},
+    instance::Instance,
+    types::ID,
+    util,
+};
+
+/// Everything about one instance that gets drawn in the overlay. Positions are all in room-space.
+pub struct Hitbox {
+    pub object: ID,
+    pub solid: bool,
+
+    /// Left, top, right and bottom, if the instance has a mask at all
+    pub bbox: Option<(i32, i32, i32, i32)>,
+
+    /// Runs of pixels in the precise collision mask, as y, left and right
+    pub mask: Vec<(i32, i32, i32)>,
+
+    pub x: f64,
+    pub y: f64,
+    pub hspeed: f64,
+    pub vspeed: f64,
+
+    /// Points along the instance's path, if it's on one
+    pub path: Vec<(f64, f64)>,
+}
+
+impl Game {
+    /// Works out the hitboxes of all active instances, in drawing order.
+    pub fn hitboxes(&self) -> Vec<Hitbox> {
+        let list = &self.room.instance_list;
+        let mut hitboxes = Vec::new();
+        let mut iter = list.iter_by_drawing();
+        while let Some(handle) = iter.next(list) {
+            let instance = list.get(handle);
+            let sprite = self.get_instance_mask_sprite(handle);
+            // The game's own bbox is only worked out when something needs it, and looking mustn't change anything
+            let bbox = sprite.map(|sprite| {
+                if instance.bbox_is_stale.get() {
+                    instance.compute_bbox(sprite)
+                } else {
+                    (
+                        instance.bbox_left.get(),
+                        instance.bbox_top.get(),
+                        instance.bbox_right.get(),
+                        instance.bbox_bottom.get(),
+                    )
+                }
+            });
+            let mask = match (sprite, bbox) {
+                (Some(sprite), Some(bbox)) => match collider(sprite, instance) {
+                    Some(collider) => mask_runs(instance, bbox, sprite, collider),
+                    None => Vec::new(),
+                },
+                _ => Vec::new(),
+            };
+            hitboxes.push(Hitbox {
+                object: instance.object_index.get(),
+                solid: instance.solid.get(),
+                bbox,
+                mask,
+                x: instance.x.get().into(),
+                y: instance.y.get().into(),
+                hspeed: instance.hspeed.get().into(),
+                vspeed: instance.vspeed.get().into(),
+                path: self.path_points(instance),
+            });
+        }
+        hitboxes
+    }
+
+    /// Works out where an instance's path goes in the room, the same way movement does.
+    fn path_points(&self, instance: &Instance) -> Vec<(f64, f64)> {
+        let path = match self.assets.paths.get_asset(instance.path_index.get()) {
+            Some(path) => path,
+            None => return Vec::new(),
+        };
+        let angle = instance.path_orientation.get().to_radians();
+        let (sin, cos): (f64, f64) = (angle.sin().into(), angle.cos().into());
+        let scale = f64::from(instance.path_scale.get());
+        path.control_nodes
+            .iter()
+            .map(|node| {
+                let mut x = f64::from(node.point.x - path.start.x) * scale;
+                let mut y = f64::from(node.point.y - path.start.y) * scale;
+                util::rotate_around_center(&mut x, &mut y, sin, cos);
+                (x + f64::from(instance.path_xstart.get()), y + f64::from(instance.path_ystart.get()))
+            })
+            .collect()
+    }
+}
+
+/// Picks which of a sprite's colliders an instance is using, like check_collision does.
+fn collider<'a>(sprite: &'a Sprite, instance: &Instance) -> Option<&'a Collider> {
+    if sprite.per_frame_colliders && !sprite.colliders.is_empty() {
+        let frame = instance.image_index.get().floor().to_i32().rem_euclid(sprite.colliders.len() as i32);
+        sprite.colliders.get(frame as usize)
+    } else {
+        sprite.colliders.first()
+    }
+}
+
+/// Finds every pixel inside an instance's bounding box which is part of its precise mask, joining up each row.
+fn mask_runs(
+    instance: &Instance,
+    (left, top, right, bottom): (i32, i32, i32, i32),
+    sprite: &Sprite,
+    collider: &Collider,
+) -> Vec<(i32, i32, i32)> {
+    let x = instance.x.get().round().to_i32();
+    let y = instance.y.get().round().to_i32();
+    let angle = instance.image_angle.get().to_radians();
+    let sin = angle.sin().into_inner();
+    let cos = angle.cos().into_inner();
+    let xscale = instance.image_xscale.get();
+    let yscale = instance.image_yscale.get();
+
+    let mut runs = Vec::new();
+    for row in top..=bottom {
+        let mut start = None;
+        for column in left..=right + 1 {
+            let inside = column <= right
+                && collider.check_collision_point_precise(
+                    column,
+                    row,
+                    x,
+                    y,
+                    sprite.origin_x,
+                    sprite.origin_y,
+                    xscale,
+                    yscale,
+                    sin,
+                    cos,
+                );
+            match (inside, start) {
+                (true, None) => start = Some(column),
+                (false, Some(left)) => {
+                    runs.push((row, left, column - 1));
+                    start = None;
+                },
+                _ => (),
+            }
+        }
+    }
+    runs
+}
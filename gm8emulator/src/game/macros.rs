@@ -0,0 +1,289 @@
+//! Input macros for the recorder: named sequences of button presses which repeat over a range of frames,
+//! and autofire, which taps a button every so many frames.
+//!
+//! Neither of these gets stored in the replay. They only decide what the recorder's buttons will do on the next
+//! frame, or get expanded into a run of frames with `expand`, so what ends up recorded is the same as if every press
+//! had been clicked in by hand.
+
+use crate::{game::replay::Input, gml::mappings};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// A keyboard key or mouse button, numbered the way GML numbers them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Button {
+    Key(u8),
+    Mouse(i8),
+}
+
+/// What to do with a button on one frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Hold,
+    Release,
+
+    /// Press and release within the same frame
+    Tap,
+}
+
+/// A named sequence of inputs, one step per frame, which repeats for as long as it's applied.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Macro {
+    pub name: String,
+
+    /// Steps separated by commas. Each one lists the buttons held on that frame, with a * in front of any which
+    /// should be tapped instead. Buttons used anywhere in the macro but not listed in a step get released.
+    pub steps: String,
+
+    /// The first frame this applies to
+    pub start: usize,
+
+    /// The frame to stop before, or None to keep going forever
+    pub end: Option<usize>,
+
+    pub enabled: bool,
+}
+
+/// Taps a button once every period, on the frame given by the phase.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Autofire {
+    pub button: String,
+    pub period: u32,
+    pub phase: u32,
+    pub enabled: bool,
+}
+
+impl Button {
+    /// Reads a button name the way it'd be written in GML: a vk_ or mb_ constant, or a single letter or digit
+    /// like ord() would be given.
+    pub fn parse(name: &str) -> Option<Self> {
+        let mut chars = name.chars();
+        if let (Some(ch), None) = (chars.next(), chars.next()) {
+            return if ch.is_ascii_alphanumeric() { Some(Self::Key(ch.to_ascii_uppercase() as u8)) } else { None }
+        }
+        let name = name.to_ascii_lowercase();
+        let value = *mappings::CONSTANTS.get(name.as_str())?;
+        if name.starts_with("mb_") && (1.0..=3.0).contains(&value) {
+            Some(Self::Mouse(value as i8))
+        } else if name.starts_with("vk_") && (2.0..=255.0).contains(&value) {
+            // vk_nokey and vk_anykey aren't real keys
+            Some(Self::Key(value as u8))
+        } else {
+            None
+        }
+    }
+}
+
+impl Macro {
+    pub fn new(name: String, start: usize) -> Self {
+        Self { name, steps: String::new(), start, end: None, enabled: false }
+    }
+
+    /// Splits the steps up, giving back what to do with every button the macro uses on each frame.
+    pub fn parse(&self) -> Result<Vec<Vec<(Button, Action)>>, String> {
+        let mut steps = Vec::new();
+        let mut buttons = Vec::new();
+        for step in self.steps.split(',') {
+            let mut actions = Vec::new();
+            for word in step.split_whitespace() {
+                let (name, action) = match word.strip_prefix('*') {
+                    Some(name) => (name, Action::Tap),
+                    None => (word, Action::Hold),
+                };
+                let button = Button::parse(name).ok_or_else(|| format!("unknown button {}", name))?;
+                if !buttons.contains(&button) {
+                    buttons.push(button);
+                }
+                actions.push((button, action));
+            }
+            steps.push(actions);
+        }
+        for actions in steps.iter_mut() {
+            for &button in buttons.iter() {
+                if !actions.iter().any(|(b, _)| *b == button) {
+                    actions.push((button, Action::Release));
+                }
+            }
+        }
+        Ok(steps)
+    }
+
+    /// What this macro does on a frame, which is nothing if it isn't running then.
+    pub fn actions(&self, frame: usize) -> Vec<(Button, Action)> {
+        if !self.enabled || frame < self.start || self.end.map_or(false, |end| frame >= end) {
+            return Vec::new()
+        }
+        match self.parse() {
+            Ok(mut steps) => {
+                let step = (frame - self.start) % steps.len();
+                steps.swap_remove(step)
+            },
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+impl Autofire {
+    pub fn new() -> Self {
+        Self { button: String::new(), period: 2, phase: 0, enabled: false }
+    }
+
+    /// What this does on a frame, if it's on and its button makes sense.
+    /// With a period of one, the button gets tapped every frame rather than being held down.
+    pub fn action(&self, frame: usize) -> Option<(Button, Action)> {
+        if !self.enabled {
+            return None
+        }
+        let button = Button::parse(&self.button)?;
+        let period = self.period.max(1) as usize;
+        let action = if frame % period != self.phase as usize % period {
+            Action::Release
+        } else if period == 1 {
+            Action::Tap
+        } else {
+            Action::Hold
+        };
+        Some((button, action))
+    }
+}
+
+impl Default for Autofire {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Everything the enabled macros and autofires do on a frame.
+/// Autofires come after macros, and later ones win when two of them use the same button.
+pub fn actions(macros: &[Macro], autofires: &[Autofire], frame: usize) -> Vec<(Button, Action)> {
+    let mut actions = Vec::new();
+    let all = macros.iter().flat_map(|m| m.actions(frame)).chain(autofires.iter().filter_map(|a| a.action(frame)));
+    for (button, action) in all {
+        match actions.iter_mut().find(|(b, _)| *b == button) {
+            Some(existing) => existing.1 = action,
+            None => actions.push((button, action)),
+        }
+    }
+    actions
+}
+
+/// Works out the inputs for a range of frames from what the enabled macros and autofires do on each of them,
+/// starting with the given buttons held down. Buttons which none of them use are left alone.
+pub fn expand(macros: &[Macro], autofires: &[Autofire], frames: Range<usize>, held: &[Button]) -> Vec<Vec<Input>> {
+    let mut held = held.to_vec();
+    frames
+        .map(|frame| {
+            let mut inputs = Vec::new();
+            for (button, action) in actions(macros, autofires, frame) {
+                let (press, release) = match button {
+                    Button::Key(key) => (Input::KeyPress(key), Input::KeyRelease(key)),
+                    Button::Mouse(button) => (Input::MousePress(button), Input::MouseRelease(button)),
+                };
+                let was_held = held.contains(&button);
+                // A tap while the button's held has to let go of it first, the same as the recorder's buttons do
+                match (action, was_held) {
+                    (Action::Hold, false) => inputs.push(press),
+                    (Action::Release, true) => inputs.push(release),
+                    (Action::Tap, true) => inputs.extend([release.clone(), press, release]),
+                    (Action::Tap, false) => inputs.extend([press, release]),
+                    (Action::Hold, true) | (Action::Release, false) => (),
+                }
+                match action {
+                    Action::Hold if !was_held => held.push(button),
+                    Action::Release | Action::Tap => held.retain(|b| *b != button),
+                    _ => (),
+                }
+            }
+            inputs
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEFT: u8 = 37;
+
+    fn running(steps: &str, start: usize, end: Option<usize>) -> Macro {
+        Macro { name: "test".into(), steps: steps.into(), start, end, enabled: true }
+    }
+
+    #[test]
+    fn buttons() {
+        assert_eq!(Button::parse("z"), Some(Button::Key(b'Z')));
+        assert_eq!(Button::parse("7"), Some(Button::Key(b'7')));
+        assert_eq!(Button::parse("vk_left"), Some(Button::Key(LEFT)));
+        assert_eq!(Button::parse("VK_LEFT"), Some(Button::Key(LEFT)));
+        assert_eq!(Button::parse("mb_right"), Some(Button::Mouse(2)));
+        assert_eq!(Button::parse("vk_nokey"), None);
+        assert_eq!(Button::parse("c_red"), None);
+        assert_eq!(Button::parse("?"), None);
+    }
+
+    #[test]
+    fn steps() {
+        let (left, z) = (Button::Key(LEFT), Button::Key(b'Z'));
+        assert_eq!(
+            running("vk_left, *Z, ", 0, None).parse(),
+            Ok(vec![
+                vec![(left, Action::Hold), (z, Action::Release)],
+                vec![(z, Action::Tap), (left, Action::Release)],
+                vec![(left, Action::Release), (z, Action::Release)],
+            ])
+        );
+        assert_eq!(running("vk_left,vk_lefty", 0, None).parse(), Err("unknown button vk_lefty".into()));
+    }
+
+    #[test]
+    fn frames() {
+        let mac = running("A, B", 10, Some(13));
+        let step = |held, released| vec![(Button::Key(held), Action::Hold), (Button::Key(released), Action::Release)];
+        assert!(mac.actions(9).is_empty());
+        assert_eq!(mac.actions(10), step(b'A', b'B'));
+        assert_eq!(mac.actions(11), step(b'B', b'A'));
+        assert_eq!(mac.actions(12), step(b'A', b'B'));
+        assert!(mac.actions(13).is_empty());
+        assert!(Macro { enabled: false, ..mac.clone() }.actions(10).is_empty());
+
+        // Autofires win over macros
+        let autofire = Autofire { button: "A".into(), period: 3, phase: 2, enabled: true };
+        assert_eq!(
+            actions(&[mac.clone()], &[autofire.clone()], 10),
+            [(Button::Key(b'A'), Action::Release), (Button::Key(b'B'), Action::Release)]
+        );
+        assert_eq!(
+            actions(&[mac], &[autofire], 11),
+            [(Button::Key(b'B'), Action::Hold), (Button::Key(b'A'), Action::Hold)]
+        );
+    }
+
+    #[test]
+    fn expansion() {
+        let mac = running("vk_left,,*Z", 0, None);
+        assert_eq!(
+            expand(&[mac.clone()], &[], 0..4, &[]),
+            [
+                vec![Input::KeyPress(LEFT)],
+                vec![Input::KeyRelease(LEFT)],
+                vec![Input::KeyPress(b'Z'), Input::KeyRelease(b'Z')],
+                vec![Input::KeyPress(LEFT)],
+            ]
+        );
+
+        // Buttons already held only get released or tapped, and the range can start part way through
+        assert_eq!(
+            expand(&[mac], &[], 2..4, &[Button::Key(LEFT), Button::Key(b'Z')]),
+            [
+                vec![Input::KeyRelease(b'Z'), Input::KeyPress(b'Z'), Input::KeyRelease(b'Z'), Input::KeyRelease(LEFT)],
+                vec![Input::KeyPress(LEFT)],
+            ]
+        );
+
+        let autofire = Autofire { button: "mb_left".into(), period: 2, phase: 1, enabled: true };
+        assert_eq!(
+            expand(&[], &[autofire], 0..3, &[]),
+            [vec![], vec![Input::MousePress(1)], vec![Input::MouseRelease(1)]]
+        );
+    }
+}
@@ -1,4 +1,74 @@
+mod linux;
 mod windows;
 
+#[cfg(target_os = "linux")]
+pub use linux::{
+    disk_free, disk_size, display_colour_depth, display_frequency, display_height, display_width, set_mouse_position,
+    set_stay_on_top,
+};
 #[cfg(windows)]
-pub use windows::{disk_free, disk_size, display_colour_depth, display_frequency, display_height, display_width};
+pub use windows::{
+    disk_free, disk_size, display_colour_depth, display_frequency, display_height, display_width, set_mouse_position,
+    set_stay_on_top,
+};
+
+use ramen::connection::Connection;
+use serde::{Deserialize, Serialize};
+
+/// The display settings reported to the game. While recording or replaying this never reflects the host's
+/// display, so that replays don't depend on which monitor they were made on.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VirtualDisplay {
+    pub width: u32,
+    pub height: u32,
+    pub colour_depth: u32,
+    pub frequency: u32,
+}
+
+impl VirtualDisplay {
+    pub const DEFAULT: Self = Self { width: 1280, height: 720, colour_depth: 32, frequency: 60 };
+
+    /// Reads the host's display settings, using the defaults for anything which can't be read.
+    pub fn host(connection: &Connection) -> Self {
+        #[cfg(target_os = "linux")]
+        {
+            Self {
+                width: display_width(connection).unwrap_or(Self::DEFAULT.width),
+                height: display_height(connection).unwrap_or(Self::DEFAULT.height),
+                colour_depth: display_colour_depth(connection).unwrap_or(Self::DEFAULT.colour_depth),
+                frequency: display_frequency(connection).unwrap_or(Self::DEFAULT.frequency),
+            }
+        }
+        #[cfg(windows)]
+        {
+            // Windows has its own calls for these which don't need a connection
+            let _ = connection;
+            Self {
+                width: display_width().unwrap_or(Self::DEFAULT.width),
+                height: display_height().unwrap_or(Self::DEFAULT.height),
+                colour_depth: display_colour_depth().unwrap_or(Self::DEFAULT.colour_depth),
+                frequency: display_frequency().unwrap_or(Self::DEFAULT.frequency),
+            }
+        }
+        #[cfg(not(any(windows, target_os = "linux")))]
+        {
+            let _ = connection;
+            Self::DEFAULT
+        }
+    }
+
+    /// Whether a set of display settings could be switched to, as checked by display_test_all.
+    pub fn supports(width: i32, height: i32, frequency: i32, colour_depth: i32) -> bool {
+        // -1 leaves a setting unchanged
+        (width > 0 || width == -1)
+            && (height > 0 || height == -1)
+            && (frequency > 0 || frequency == -1)
+            && matches!(colour_depth, 16 | 32 | -1)
+    }
+}
+
+impl Default for VirtualDisplay {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
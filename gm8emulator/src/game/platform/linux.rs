@@ -0,0 +1,178 @@
+#![cfg(target_os = "linux")]
+
+use ramen::{connection::Connection, platform::linux::Display};
+use std::{
+    ffi::CString,
+    mem,
+    os::{
+        raw::{c_char, c_int, c_long, c_short, c_uint, c_ulong, c_void},
+        unix::ffi::OsStrExt,
+    },
+    sync::OnceLock,
+};
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct XClientMessageEvent {
+    kind: c_int,
+    serial: c_ulong,
+    send_event: c_int,
+    display: *mut Display,
+    window: c_ulong,
+    message_type: c_ulong,
+    format: c_int,
+    data: [c_long; 5],
+}
+
+#[repr(C)]
+union XEvent {
+    client_message: XClientMessageEvent,
+    pad: [c_long; 24],
+}
+
+type XRRScreenConfiguration = c_void;
+
+const CLIENT_MESSAGE: c_int = 33;
+const SUBSTRUCTURE_NOTIFY_MASK: c_long = 1 << 19;
+const SUBSTRUCTURE_REDIRECT_MASK: c_long = 1 << 20;
+const NET_WM_STATE_REMOVE: c_long = 0;
+const NET_WM_STATE_ADD: c_long = 1;
+
+#[link(name = "X11")]
+extern "C" {
+    fn XDefaultScreen(display: *mut Display) -> c_int;
+    fn XDefaultRootWindow(display: *mut Display) -> c_ulong;
+    fn XDisplayWidth(display: *mut Display, screen: c_int) -> c_int;
+    fn XDisplayHeight(display: *mut Display, screen: c_int) -> c_int;
+    fn XDefaultDepth(display: *mut Display, screen: c_int) -> c_int;
+    fn XInternAtom(display: *mut Display, atom_name: *const c_char, only_if_exists: c_int) -> c_ulong;
+    fn XSendEvent(display: *mut Display, window: c_ulong, propagate: c_int, mask: c_long, event: *mut XEvent) -> c_int;
+    fn XWarpPointer(
+        display: *mut Display,
+        src_w: c_ulong,
+        dest_w: c_ulong,
+        src_x: c_int,
+        src_y: c_int,
+        src_width: c_uint,
+        src_height: c_uint,
+        dest_x: c_int,
+        dest_y: c_int,
+    ) -> c_int;
+    fn XFlush(display: *mut Display) -> c_int;
+}
+
+/// The parts of libXrandr used for reading the refresh rate. Not everyone has it installed, so it's loaded
+/// when it's first needed rather than linked against, and the refresh rate is left unknown without it.
+struct Xrandr {
+    get_screen_info: unsafe extern "C" fn(*mut Display, c_ulong) -> *mut XRRScreenConfiguration,
+    config_current_rate: unsafe extern "C" fn(*mut XRRScreenConfiguration) -> c_short,
+    free_screen_config_info: unsafe extern "C" fn(*mut XRRScreenConfiguration),
+}
+
+fn xrandr() -> Option<&'static Xrandr> {
+    static XRANDR: OnceLock<Option<Xrandr>> = OnceLock::new();
+    XRANDR
+        .get_or_init(|| unsafe {
+            let lib = libc::dlopen(b"libXrandr.so.2\0".as_ptr().cast(), libc::RTLD_NOW | libc::RTLD_LOCAL);
+            if lib.is_null() {
+                return None
+            }
+            let symbol = |name: &[u8]| Some(libc::dlsym(lib, name.as_ptr().cast())).filter(|f| !f.is_null());
+            Some(Xrandr {
+                get_screen_info: mem::transmute(symbol(b"XRRGetScreenInfo\0")?),
+                config_current_rate: mem::transmute(symbol(b"XRRConfigCurrentRate\0")?),
+                free_screen_config_info: mem::transmute(symbol(b"XRRFreeScreenConfigInfo\0")?),
+            })
+        })
+        .as_ref()
+}
+
+// All of these go through the same X connection as the game window, which ramen opened
+pub fn display_width(connection: &Connection) -> Option<u32> {
+    unsafe {
+        let dpy = connection.xdisplay();
+        u32::try_from(XDisplayWidth(dpy, XDefaultScreen(dpy))).ok()
+    }
+}
+
+pub fn display_height(connection: &Connection) -> Option<u32> {
+    unsafe {
+        let dpy = connection.xdisplay();
+        u32::try_from(XDisplayHeight(dpy, XDefaultScreen(dpy))).ok()
+    }
+}
+
+pub fn display_frequency(connection: &Connection) -> Option<u32> {
+    let xrandr = xrandr()?;
+    unsafe {
+        let dpy = connection.xdisplay();
+        let config = (xrandr.get_screen_info)(dpy, XDefaultRootWindow(dpy));
+        if config.is_null() {
+            return None
+        }
+        let rate = (xrandr.config_current_rate)(config);
+        (xrandr.free_screen_config_info)(config);
+        u32::try_from(rate).ok().filter(|&rate| rate != 0)
+    }
+}
+
+pub fn display_colour_depth(connection: &Connection) -> Option<u32> {
+    unsafe {
+        let dpy = connection.xdisplay();
+        u32::try_from(XDefaultDepth(dpy, XDefaultScreen(dpy))).ok()
+    }
+}
+
+/// Moves the mouse cursor to a position relative to the top-left of the given window.
+pub fn set_mouse_position(connection: &Connection, window: u32, x: i32, y: i32) -> Option<()> {
+    unsafe {
+        let dpy = connection.xdisplay();
+        XWarpPointer(dpy, 0, window.into(), 0, 0, 0, 0, x, y);
+        XFlush(dpy);
+    }
+    Some(())
+}
+
+/// Asks the window manager to keep the given window above all others, or to stop doing so.
+pub fn set_stay_on_top(connection: &Connection, window: u32, stay_on_top: bool) -> Option<()> {
+    unsafe {
+        let dpy = connection.xdisplay();
+        let wm_state = CString::new("_NET_WM_STATE").unwrap();
+        let wm_state_above = CString::new("_NET_WM_STATE_ABOVE").unwrap();
+        let message_type = XInternAtom(dpy, wm_state.as_ptr(), 0);
+        let above = XInternAtom(dpy, wm_state_above.as_ptr(), 0);
+        let mut event: XEvent = mem::zeroed();
+        event.client_message = XClientMessageEvent {
+            kind: CLIENT_MESSAGE,
+            serial: 0,
+            send_event: 1,
+            display: dpy,
+            window: window.into(),
+            message_type,
+            format: 32,
+            data: [if stay_on_top { NET_WM_STATE_ADD } else { NET_WM_STATE_REMOVE }, above as _, 0, 1, 0],
+        };
+        let mask = SUBSTRUCTURE_REDIRECT_MASK | SUBSTRUCTURE_NOTIFY_MASK;
+        let response = XSendEvent(dpy, XDefaultRootWindow(dpy), 0, mask, &mut event);
+        XFlush(dpy);
+        (response != 0).then(|| ())
+    }
+}
+
+fn statvfs_cwd() -> Option<libc::statvfs> {
+    let dir = std::env::current_dir().ok()?;
+    let path = CString::new(dir.as_os_str().as_bytes()).ok()?;
+    unsafe {
+        let mut stat: libc::statvfs = mem::zeroed();
+        (libc::statvfs(path.as_ptr(), &mut stat) == 0).then(|| stat)
+    }
+}
+
+// Linux has no drive letters, so these always look at the filesystem holding the working directory.
+pub fn disk_free(_drive: Option<char>) -> Option<u64> {
+    statvfs_cwd().map(|stat| stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+pub fn disk_size(_drive: Option<char>) -> Option<u64> {
+    statvfs_cwd().map(|stat| stat.f_blocks as u64 * stat.f_frsize as u64)
+}
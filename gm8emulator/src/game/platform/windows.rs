@@ -1,5 +1,6 @@
 #![cfg(windows)]
 
+use ramen::platform::win32::HWND;
 use std::{ffi::OsStr, mem, os::windows::ffi::OsStrExt, ptr};
 
 #[allow(non_snake_case)]
@@ -34,11 +35,25 @@ struct DEVMODEW {
     dmPanningHeight: u32,
 }
 
+#[repr(C)]
+struct POINT {
+    x: i32,
+    y: i32,
+}
+
 const ENUM_CURRENT_SETTINGS: u32 = u32::MAX;
+const HWND_TOPMOST: isize = -1;
+const HWND_NOTOPMOST: isize = -2;
+const SWP_NOSIZE: u32 = 0x0001;
+const SWP_NOMOVE: u32 = 0x0002;
+const SWP_NOACTIVATE: u32 = 0x0010;
 
 #[link(name = "user32")]
 extern "system" {
     fn EnumDisplaySettingsW(lpszDeviceName: *const u16, iModeNum: u32, lpDevMode: *mut DEVMODEW) -> i32;
+    fn ClientToScreen(hWnd: HWND, lpPoint: *mut POINT) -> i32;
+    fn SetCursorPos(X: i32, Y: i32) -> i32;
+    fn SetWindowPos(hWnd: HWND, hWndInsertAfter: HWND, X: i32, Y: i32, cx: i32, cy: i32, uFlags: u32) -> i32;
     fn GetDiskFreeSpaceExW(
         lpDirectoryName: *const u16,
         lpFreeBytesAvailableToCaller: *mut u64,
@@ -71,6 +86,22 @@ pub fn display_colour_depth() -> Option<u32> {
     get_display_settings().map(|dm| dm.dmBitsPerPel)
 }
 
+/// Moves the mouse cursor to a position relative to the top-left of the given window's client area.
+pub fn set_mouse_position(hwnd: HWND, x: i32, y: i32) -> Option<()> {
+    unsafe {
+        let mut point = POINT { x, y };
+        (ClientToScreen(hwnd, &mut point) != 0 && SetCursorPos(point.x, point.y) != 0).then(|| ())
+    }
+}
+
+/// Keeps the given window above all non-topmost windows, or stops doing so.
+pub fn set_stay_on_top(hwnd: HWND, stay_on_top: bool) -> Option<()> {
+    let insert_after = if stay_on_top { HWND_TOPMOST } else { HWND_NOTOPMOST };
+    let flags = SWP_NOSIZE | SWP_NOMOVE | SWP_NOACTIVATE;
+    let response = unsafe { SetWindowPos(hwnd, insert_after as HWND, 0, 0, 0, 0, flags) };
+    (response != 0).then(|| ())
+}
+
 fn trim_drive(drive: Option<char>) -> Vec<u16> {
     match drive {
         Some(letter) => {
@@ -0,0 +1,344 @@
+//! An opt-in profiler, which times scripts, events, timeline moments, kernel functions and draw calls.
+//!
+//! Everything is kept as a call tree, which can be written out as folded stacks for flamegraph tools,
+//! or totalled up by what ran for showing in a table.
+
+use crate::{
+    game::{
+        debugger::{describe_location, CodeIndex, Location},
+        Game,
+    },
+    gml::{self, mappings, runtime::ReturnType, Code, Context},
+    render::DrawStats,
+};
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+/// Something which gets timed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Entry {
+    /// A whole frame, including drawing
+    Frame,
+
+    /// An event, timeline moment, script or any other code
+    Code(Location),
+
+    /// A kernel function
+    Function(usize),
+}
+
+struct Node {
+    entry: Entry,
+    children: HashMap<Entry, usize>,
+    calls: u64,
+
+    /// These include everything called from here
+    time: Duration,
+    draws: DrawStats,
+}
+
+/// Totals for one entry, from everywhere it ran.
+pub struct Row {
+    pub name: String,
+    pub calls: u64,
+
+    /// Time spent in it and everything it called
+    pub total_time: Duration,
+
+    /// Time spent in it but not in anything it called, or in draw calls
+    pub self_time: Duration,
+
+    /// Draw calls made from it but not from anything it called
+    pub draw_calls: u64,
+}
+
+pub struct Profiler {
+    /// The call tree. The first node is the root, which isn't anything itself.
+    nodes: Vec<Node>,
+
+    /// What's running right now, along with when it started and how many draw calls had been made by then
+    stack: Vec<(usize, Instant, DrawStats)>,
+
+    locations: CodeIndex,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self { nodes: vec![Node::new(Entry::Frame)], stack: Vec::new(), locations: CodeIndex::default() }
+    }
+}
+
+impl Node {
+    fn new(entry: Entry) -> Self {
+        Self { entry, children: HashMap::new(), calls: 0, time: Duration::default(), draws: DrawStats::default() }
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Throws away everything measured so far.
+    pub fn reset(&mut self) {
+        self.nodes.truncate(1);
+        self.nodes[0].children.clear();
+        self.stack.clear();
+    }
+
+    /// Forgets where code came from, which must be done whenever the game's assets are replaced.
+    pub fn forget_locations(&mut self) {
+        self.locations.clear();
+    }
+
+    /// Looks for code in the assets again the next time some can't be found, since more has been added.
+    pub fn code_added(&mut self) {
+        self.locations.code_added();
+    }
+
+    fn enter(&mut self, entry: Entry, draws: DrawStats) {
+        let parent = self.stack.last().map_or(0, |(node, ..)| *node);
+        let next = self.nodes.len();
+        let node = *self.nodes[parent].children.entry(entry).or_insert(next);
+        if node == next {
+            self.nodes.push(Node::new(entry));
+        }
+        self.stack.push((node, Instant::now(), draws));
+    }
+
+    fn exit(&mut self, draws: DrawStats) {
+        if let Some((node, start, start_draws)) = self.stack.pop() {
+            let node = &mut self.nodes[node];
+            node.calls += 1;
+            node.time += start.elapsed();
+            node.draws.calls += draws.calls - start_draws.calls;
+            node.draws.time += draws.time - start_draws.time;
+        }
+    }
+
+    /// Works out a node's own time and draw stats, leaving out its children.
+    fn own(&self, node: &Node) -> (Duration, DrawStats) {
+        let mut time = node.time;
+        let mut draws = node.draws;
+        for child in node.children.values().map(|&child| &self.nodes[child]) {
+            time = time.saturating_sub(child.time);
+            draws.calls = draws.calls.saturating_sub(child.draws.calls);
+            draws.time = draws.time.saturating_sub(child.draws.time);
+        }
+        (time.saturating_sub(draws.time), draws)
+    }
+
+    /// Writes out the call tree as folded stacks, which flamegraph tools can read. Times are in microseconds.
+    /// Time spent making draw calls shows up as a "draw calls" frame on top of whatever made them.
+    pub fn write_folded(&self, game: &Game, mut output: impl Write) -> io::Result<()> {
+        let mut path = Vec::new();
+        for child in self.children(&self.nodes[0]) {
+            self.write_folded_node(child, game, &mut path, &mut output)?;
+        }
+        Ok(())
+    }
+
+    fn write_folded_node(
+        &self,
+        index: usize,
+        game: &Game,
+        path: &mut Vec<String>,
+        output: &mut impl Write,
+    ) -> io::Result<()> {
+        let node = &self.nodes[index];
+        // Semicolons separate frames, so they can't be in names
+        path.push(name(node.entry, game).replace(';', ":"));
+        let stack = path.join(";");
+        let (time, draws) = self.own(node);
+        if time.as_micros() > 0 {
+            writeln!(output, "{} {}", stack, time.as_micros())?;
+        }
+        if draws.time.as_micros() > 0 {
+            writeln!(output, "{};draw calls {}", stack, draws.time.as_micros())?;
+        }
+        for child in self.children(node) {
+            self.write_folded_node(child, game, path, output)?;
+        }
+        path.pop();
+        Ok(())
+    }
+
+    /// Totals everything up by what ran, with all draw calls in a row of their own at the end.
+    pub fn table(&self, game: &Game) -> Vec<Row> {
+        let mut totals: HashMap<Entry, Row> = HashMap::new();
+        let mut path = Vec::new();
+        for child in self.children(&self.nodes[0]) {
+            self.total_node(child, game, &mut path, &mut totals);
+        }
+        let mut rows = totals.into_values().collect::<Vec<_>>();
+        rows.sort_by(|a, b| b.total_time.cmp(&a.total_time));
+
+        let draws = self.nodes[0].children.values().fold(DrawStats::default(), |mut draws, &child| {
+            draws.calls += self.nodes[child].draws.calls;
+            draws.time += self.nodes[child].draws.time;
+            draws
+        });
+        rows.push(Row {
+            name: "draw calls".into(),
+            calls: draws.calls,
+            total_time: draws.time,
+            self_time: draws.time,
+            draw_calls: draws.calls,
+        });
+        rows
+    }
+
+    fn total_node(&self, index: usize, game: &Game, path: &mut Vec<Entry>, totals: &mut HashMap<Entry, Row>) {
+        let node = &self.nodes[index];
+        let (time, draws) = self.own(node);
+        let row = totals.entry(node.entry).or_insert_with(|| Row {
+            name: name(node.entry, game),
+            calls: 0,
+            total_time: Duration::default(),
+            self_time: Duration::default(),
+            draw_calls: 0,
+        });
+        row.calls += node.calls;
+        row.self_time += time;
+        row.draw_calls += draws.calls;
+        // Recursive calls are already counted in the outermost call's time
+        if !path.contains(&node.entry) {
+            row.total_time += node.time;
+        }
+        path.push(node.entry);
+        for child in self.children(node) {
+            self.total_node(child, game, path, totals);
+        }
+        path.pop();
+    }
+
+    /// A node's children, in the order they first ran.
+    fn children(&self, node: &Node) -> Vec<usize> {
+        let mut children = node.children.values().copied().collect::<Vec<_>>();
+        children.sort_unstable();
+        children
+    }
+}
+
+fn name(entry: Entry, game: &Game) -> String {
+    match entry {
+        Entry::Frame => "frame".into(),
+        Entry::Code(location) => describe_location(location, game),
+        Entry::Function(id) => match mappings::FUNCTIONS.index(id) {
+            Some((name, _)) => format!("function {}", name),
+            None => format!("function {}", id),
+        },
+    }
+}
+
+impl Game {
+    /// Runs something, timing it as the given entry if the profiler is on.
+    pub(crate) fn profile<T>(&mut self, entry: Entry, f: impl FnOnce(&mut Self) -> T) -> T {
+        match self.profiler.as_mut() {
+            Some(profiler) => {
+                profiler.enter(entry, self.renderer.draw_stats());
+                let result = f(self);
+                if let Some(profiler) = self.profiler.as_mut() {
+                    profiler.exit(self.renderer.draw_stats());
+                }
+                result
+            },
+            None => f(self),
+        }
+    }
+
+    /// Runs some code, timing it by where it came from. Actions are counted as part of their event or moment.
+    pub(crate) fn profile_code(&mut self, code: &Code, context: &mut Context) -> gml::Result<ReturnType> {
+        let location = match self.profiler.as_mut() {
+            Some(profiler) => profiler.locations.locate(code, &self.assets).0,
+            None => Location::Other,
+        };
+        match location {
+            Location::Event { .. } | Location::Moment { .. } => self.run_code(code, context),
+            location => self.profile(Entry::Code(location), |game| game.run_code(code, context)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{asset::Script, game::Assets, gml::Compiler};
+
+    /// Every path from the root of the call tree, with how many times the last entry on it ran.
+    fn paths(profiler: &Profiler) -> Vec<(Vec<Entry>, u64)> {
+        fn walk(profiler: &Profiler, index: usize, path: &mut Vec<Entry>, output: &mut Vec<(Vec<Entry>, u64)>) {
+            let node = &profiler.nodes[index];
+            path.push(node.entry);
+            output.push((path.clone(), node.calls));
+            for child in profiler.children(node) {
+                walk(profiler, child, path, output);
+            }
+            path.pop();
+        }
+        let mut output = Vec::new();
+        for child in profiler.children(&profiler.nodes[0]) {
+            walk(profiler, child, &mut Vec::new(), &mut output);
+        }
+        output
+    }
+
+    /// Starts timing some code the same way `Game::profile_code` does.
+    fn enter_code(profiler: &mut Profiler, code: &Code, assets: &Assets) -> Entry {
+        let entry = Entry::Code(profiler.locations.locate(code, assets).0);
+        profiler.enter(entry, DrawStats::default());
+        entry
+    }
+
+    #[test]
+    fn nested_scripts() {
+        let mut compiler = Compiler::new();
+        let mut assets = Assets {
+            backgrounds: Vec::new(),
+            fonts: Vec::new(),
+            objects: Vec::new(),
+            paths: Vec::new(),
+            rooms: Vec::new(),
+            scripts: Vec::new(),
+            sounds: Vec::new(),
+            sprites: Vec::new(),
+            timelines: Vec::new(),
+            triggers: Vec::new(),
+        };
+        for (name, source) in [("scr_outer", "a = 1"), ("scr_inner", "b = 2")] {
+            let compiled = compiler.compile(source.as_bytes()).unwrap();
+            assets.scripts.push(Some(Box::new(Script { name: name.into(), source: source.into(), compiled })));
+        }
+        let code = |id: usize| assets.scripts[id].as_ref().unwrap().compiled.clone();
+        let function = Entry::Function(0);
+        let mut profiler = Profiler::new();
+        let draws = DrawStats::default();
+
+        // The outer script calls the inner one twice, once directly and once through a kernel function
+        let outer = enter_code(&mut profiler, &code(0), &assets);
+        let inner = enter_code(&mut profiler, &code(1), &assets);
+        profiler.exit(draws);
+        profiler.enter(function, draws);
+        enter_code(&mut profiler, &code(1), &assets);
+        profiler.exit(draws);
+        profiler.exit(draws);
+        profiler.exit(draws);
+        enter_code(&mut profiler, &code(1), &assets);
+        profiler.exit(draws);
+
+        assert_eq!((outer, inner), (Entry::Code(Location::Script(0)), Entry::Code(Location::Script(1))));
+        assert_eq!(
+            paths(&profiler),
+            [
+                (vec![outer], 1),
+                (vec![outer, inner], 1),
+                (vec![outer, function], 1),
+                (vec![outer, function, inner], 1),
+                (vec![inner], 1),
+            ]
+        );
+    }
+}
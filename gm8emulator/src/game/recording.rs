@@ -1,20 +1,37 @@
 use crate::{
     game::{
+        branch,
+        console::Console,
+        debugger::{Breakpoint, Debugger, Resume, Snapshot, SnapshotFrontend},
+        hitbox::Hitbox,
+        macros::{self, Action, Autofire, Button, Macro},
+        profiler::{self, Profiler},
         replay::{self, Replay},
         savestate::{self, SaveState},
-        Game, GameClock, SceneChange,
+        search::{Filter, Search},
+        watch::Watch,
+        Game, GameClock, PlayType, SceneChange,
     },
-    gml::rand::Random,
+    gml::{rand::Random, Value},
     imgui_utils::*,
     input,
     instance::Field,
     render::{atlas::AtlasRef, PrimitiveType, Renderer, RendererState},
-    types::Colour,
+    types::{Colour, ID},
 };
 use imgui::{self, internal::RawWrapper, DrawCmd, DrawList};
 use ramen::{event::Event, input::Key};
 use serde::{Deserialize, Serialize};
-use std::{fs::File, path::PathBuf, time::Instant};
+use std::{
+    cell::RefCell,
+    cmp::Reverse,
+    error::Error,
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
 const CLEAR_COLOUR: Colour = Colour::new(0.0196, 0.1059, 0.06275);
 const BTN_NEUTRAL_COL: Colour = Colour::new(0.15, 0.15, 0.21);
@@ -24,6 +41,20 @@ const BTN_HELD_COL: Colour = Colour::new(0.486, 1.0, 0.506);
 const BTN_HDOUBLE_COL: Colour = Colour::new(0.46, 0.85, 0.48);
 const BTN_HTRIPLE_COL: Colour = Colour::new(0.44, 0.7, 0.455);
 const BTN_CACTUS_COL: Colour = Colour::new(1.0, 0.788, 0.055);
+const HITBOX_SOLID_COL: Colour = Colour::new(1.0, 0.3, 0.3);
+const HITBOX_NONSOLID_COL: Colour = Colour::new(0.3, 1.0, 0.5);
+const HITBOX_ORIGIN_COL: Colour = Colour::new(1.0, 0.788, 0.055);
+const HITBOX_PATH_COL: Colour = Colour::new(0.4, 0.6, 1.0);
+const HITBOX_VELOCITY_COL: Colour = Colour::new(1.0, 1.0, 1.0);
+
+/// Velocity vectors are drawn this many frames long, so that slow movement can still be seen
+const VELOCITY_SCALE: f64 = 4.0;
+
+/// How many differences get shown when comparing two branches
+const BRANCH_DIFF_ROWS: usize = 200;
+
+/// How many of the things left in a RAM search get shown
+const SEARCH_ROWS: usize = 200;
 
 struct UIState<'g> {
     /// The Game struct, constructed outside this file, to be controlled by the TAS UI
@@ -72,6 +103,12 @@ struct UIState<'g> {
     /// Mouse position set by the user to be taken into use next time they advance a frame
     new_mouse_pos: Option<(i32, i32)>,
 
+    /// Characters typed by the user to be sent to the game next time they advance a frame
+    new_text: Vec<u8>,
+
+    /// Contents of the text input box, which get added to new_text when the user presses "Type"
+    text_entry: String,
+
     /// Whether the user is currently in the process of setting a mouse position
     /// If so, mouse inputs should be "eaten" by this process and not sent to imgui windows
     setting_mouse_pos: bool,
@@ -118,6 +155,91 @@ struct UIState<'g> {
 
     /// Cached UI text for select buttons
     select_text: Vec<String>,
+
+    /// GML breakpoints to stop at when advancing a frame
+    breakpoints: Vec<Breakpoint>,
+
+    /// Cached UI text describing each breakpoint
+    breakpoint_text: Vec<String>,
+
+    /// Contents of the text input box for adding a breakpoint
+    breakpoint_entry: String,
+
+    /// Whether to stop on the first line of GML run when advancing a frame
+    break_next_frame: bool,
+
+    /// Where the debugger stopped during the last frame advance, if it did
+    debugger_stop: Rc<RefCell<Option<Snapshot>>>,
+
+    /// The state from before the last frame advance, if the debugger stopped during it
+    /// Stepping loads this and runs the frame again up to the next place to stop
+    debug_savestate: Option<SaveState>,
+
+    /// Profiler results so far, which get lent to the game while advancing if profiling is on
+    profiler: Box<Profiler>,
+
+    /// Whether to profile frame advances
+    profiling: bool,
+
+    /// Cached profiler results, sorted by profile_sort
+    profile_rows: Vec<profiler::Row>,
+
+    /// Which column the profiler results are sorted by
+    profile_sort: ProfileColumn,
+
+    /// GML console, whose runs get saved in the replay after the frame they happened on
+    console: Console,
+
+    /// Contents of the console's input box
+    console_entry: String,
+
+    /// Watch expressions, which are saved in the project config as well
+    watches: Vec<Watch>,
+
+    /// Contents of the text input box for adding a watch expression
+    watch_entry: String,
+
+    /// Whether a watch expression being added should stop advancing when it becomes true
+    watch_pause: bool,
+
+    /// The watch expression which stopped advancing after the last frame, if one did
+    watch_stopped: Option<String>,
+
+    /// Whether to keep advancing a frame every time the UI updates
+    fast_forward: bool,
+
+    /// RAM-watch style search through the game's variables, if one has been started
+    search: Option<Search>,
+
+    /// Contents of the text input box for the value to search for
+    search_value: String,
+
+    /// Cached UI text for the first few things left in the search, along with expressions for pinning them
+    search_rows: Vec<(String, Option<String>)>,
+
+    /// Cached hitboxes of every active instance, for the overlay on the game view
+    hitboxes: Vec<Hitbox>,
+
+    /// Cached UI text for each object which can be hidden from the hitbox overlay
+    hitbox_objects: Vec<(ID, String)>,
+
+    /// Contents of the text input box for the name of a new macro
+    macro_name: String,
+
+    /// The frame to stop before when recording the macros over a range of frames
+    macro_range_end: i32,
+
+    /// Names of every branch in the project other than the main one
+    branches: Vec<String>,
+
+    /// Contents of the text input box for the name of a new branch
+    branch_name: String,
+
+    /// First frame and frame to stop before when splicing in frames from another branch
+    splice_range: (i32, i32),
+
+    /// Cached UI text for the last comparison between two branches: a summary, then one line per differing frame
+    branch_diff: Option<(String, Vec<String>)>,
 }
 
 #[derive(Clone, Copy, PartialEq)]
@@ -244,13 +366,25 @@ enum ContextMenu {
     Seed { pos: Vec2<f32> },
 }
 
+#[derive(Clone, Copy, PartialEq)]
+enum ProfileColumn {
+    Name,
+    Calls,
+    TotalTime,
+    SelfTime,
+    DrawCalls,
+}
+
 #[derive(Deserialize, Serialize)]
 enum InputMode {
     Mouse,
     Direct,
 }
 
+// Written as JSON, with anything missing filled in from DEFAULT_CONFIG, so fields can be added without
+// older projects failing to load
 #[derive(Deserialize, Serialize)]
+#[serde(default)]
 struct ProjectConfig {
     ui_width: u16,
     ui_height: u16,
@@ -260,6 +394,30 @@ struct ProjectConfig {
     full_keyboard: bool,
     input_mode: InputMode,
     quicksave_slot: usize,
+    unimplemented_policy: Vec<String>,
+    watches: Vec<WatchConfig>,
+    hitboxes: HitboxConfig,
+    macros: Vec<Macro>,
+    autofires: Vec<Autofire>,
+    branch: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+struct HitboxConfig {
+    enabled: bool,
+    bbox: bool,
+    mask: bool,
+    origin: bool,
+    path: bool,
+    velocity: bool,
+    hidden_objects: Vec<ID>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct WatchConfig {
+    expression: String,
+    pause: bool,
 }
 
 const DEFAULT_CONFIG: ProjectConfig = ProjectConfig {
@@ -271,8 +429,79 @@ const DEFAULT_CONFIG: ProjectConfig = ProjectConfig {
     full_keyboard: false,
     input_mode: InputMode::Mouse,
     quicksave_slot: 0,
+    unimplemented_policy: Vec::new(),
+    watches: Vec::new(),
+    hitboxes: HitboxConfig {
+        enabled: false,
+        bbox: true,
+        mask: true,
+        origin: true,
+        path: true,
+        velocity: true,
+        hidden_objects: Vec::new(),
+    },
+    macros: Vec::new(),
+    autofires: Vec::new(),
+    branch: None,
 };
 
+// The bincode layout project.cfg had before it was JSON, which gets read so that old projects keep their settings
+#[derive(Deserialize)]
+struct LegacyConfig {
+    ui_width: u16,
+    ui_height: u16,
+    ui_maximised: bool,
+    rerecords: u64,
+    watched_ids: Vec<i32>,
+    full_keyboard: bool,
+    input_mode: InputMode,
+    quicksave_slot: usize,
+}
+
+impl Default for ProjectConfig {
+    fn default() -> Self {
+        DEFAULT_CONFIG
+    }
+}
+
+impl Default for HitboxConfig {
+    fn default() -> Self {
+        DEFAULT_CONFIG.hitboxes
+    }
+}
+
+impl ProjectConfig {
+    fn load(path: &Path) -> Result<Self, String> {
+        Self::parse(&fs::read(path).map_err(|e| e.to_string())?)
+    }
+
+    fn parse(data: &[u8]) -> Result<Self, String> {
+        let json_err = match serde_json::from_slice(data) {
+            Ok(config) => return Ok(config),
+            Err(e) => e,
+        };
+        match bincode::deserialize::<LegacyConfig>(data) {
+            Ok(legacy) => Ok(Self {
+                ui_width: legacy.ui_width,
+                ui_height: legacy.ui_height,
+                ui_maximised: legacy.ui_maximised,
+                rerecords: legacy.rerecords,
+                watched_ids: legacy.watched_ids,
+                full_keyboard: legacy.full_keyboard,
+                input_mode: legacy.input_mode,
+                quicksave_slot: legacy.quicksave_slot,
+                ..DEFAULT_CONFIG
+            }),
+            Err(_) => Err(json_err.to_string()),
+        }
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        let file = File::create(path).map_err(|e| e.to_string())?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self).map_err(|e| e.to_string())
+    }
+}
+
 impl Game {
     pub fn record(&mut self, project_path: PathBuf) {
         // Big setup function for UIState. See UIState::run() below for the event loop
@@ -285,20 +514,34 @@ impl Game {
             p
         };
 
-        let config = if config_path.exists() {
-            match bincode::deserialize_from(File::open(&config_path).expect("Couldn't read project.cfg")) {
+        let mut config = if config_path.exists() {
+            // Carrying on with the defaults would overwrite whatever's in there, including the re-record count
+            match ProjectConfig::load(&config_path) {
                 Ok(config) => config,
-                Err(_) => {
-                    println!("Warning: Couldn't parse project.cfg. Using default configuration.");
-                    DEFAULT_CONFIG
+                Err(e) => {
+                    println!("Error: Couldn't parse project.cfg ({}). Fix or remove it and try again.", e);
+                    return
                 },
             }
         } else {
-            bincode::serialize_into(File::create(&config_path).expect("Couldn't write project.cfg"), &DEFAULT_CONFIG)
-                .expect("Couldn't serialize project.cfg");
+            DEFAULT_CONFIG.save(&config_path).expect("Couldn't write project.cfg");
             DEFAULT_CONFIG
         };
 
+        // Unimplemented function policy given on the command line gets saved as the project's policy
+        if self.unimplemented_policy.rules().is_empty() {
+            for rule in config.unimplemented_policy.iter() {
+                if let Err(e) = self.unimplemented_policy.apply(rule) {
+                    println!("Warning: ignoring unimplemented function policy \"{}\" in project.cfg: {}", rule, e);
+                }
+            }
+        } else {
+            config.unimplemented_policy = self.unimplemented_policy.rules().to_vec();
+            if let Err(e) = config.save(&config_path) {
+                println!("Warning: couldn't save unimplemented function policy to project.cfg: {}", e);
+            }
+        }
+
         let mut replay = Replay::new(if let GameClock::SpoofedNanos(t) = self.clock { t } else { 0 }, self.rand.seed());
 
         let mut context = imgui::Context::create();
@@ -382,16 +625,27 @@ impl Game {
             zbuf_trashed: self.renderer.get_zbuf_trashed(),
         };
 
-        let save_paths = (0..16)
-            .map(|i| {
-                let mut path = project_path.clone();
-                path.push(&format!("save{}.bin", i + 1));
-                path
-            })
-            .collect::<Vec<_>>();
+        // Starting on main rather than a branch that can't be loaded, but not without saying so
+        let mut err_string: Option<String> = None;
+        if let Some(name) = config.branch.take() {
+            match branch::check(&project_path, &name) {
+                Ok(()) => config.branch = Some(name),
+                Err(e) => {
+                    let warning = format!(
+                        "Warning: couldn't load branch \"{}\" ({}), so {} was loaded instead",
+                        name,
+                        e,
+                        branch::MAIN
+                    );
+                    println!("{}", warning);
+                    err_string = Some(warning);
+                },
+            }
+        }
+        let save_paths = branch::save_paths(&project_path, config.branch.as_deref());
+        let branches = branch::list(&project_path);
 
         let mut game_running = true; // false indicates the game closed or crashed, and so advancing is not allowed
-        let mut err_string: Option<String> = None;
 
         let cached_savestate;
         let game_renderer_state;
@@ -493,6 +747,9 @@ impl Game {
         }
 
         let instance_reports = config.watched_ids.iter().map(|id| (*id, InstanceReport::new(&*self, *id))).collect();
+        let watches = config.watches.iter().map(|w| Watch::new(w.expression.clone(), w.pause, self)).collect();
+        let profiling = self.profiler.is_some();
+        let profiler = self.profiler.take().unwrap_or_default();
         let frame_text = format!("Frame: {}", replay.frame_count());
         let seed_text = format!("Seed: {}", self.rand.seed());
         let rerecord_text = format!("Re-record count: {}", config.rerecords);
@@ -512,6 +769,8 @@ impl Game {
             keyboard_state,
             mouse_state,
             new_mouse_pos: None,
+            new_text: Vec::new(),
+            text_entry: String::new(),
             setting_mouse_pos: false,
             ui_renderer_state,
             cached_savestate,
@@ -527,6 +786,34 @@ impl Game {
             save_text: (0..16).map(|i| format!("Save {}", i + 1)).collect::<Vec<_>>(),
             load_text: (0..16).map(|i| format!("Load {}", i + 1)).collect::<Vec<_>>(),
             select_text: (0..16).map(|i| format!("Select###Select{}", i + 1)).collect::<Vec<_>>(),
+            breakpoints: Vec::new(),
+            breakpoint_text: Vec::new(),
+            breakpoint_entry: String::new(),
+            break_next_frame: false,
+            debugger_stop: Rc::new(RefCell::new(None)),
+            debug_savestate: None,
+            profiler,
+            profiling,
+            profile_rows: Vec::new(),
+            profile_sort: ProfileColumn::TotalTime,
+            console: Console::new(),
+            console_entry: String::new(),
+            watches,
+            watch_entry: String::new(),
+            watch_pause: false,
+            watch_stopped: None,
+            fast_forward: false,
+            search: None,
+            search_value: String::new(),
+            search_rows: Vec::new(),
+            hitboxes: Vec::new(),
+            hitbox_objects: Vec::new(),
+            macro_name: String::new(),
+            macro_range_end: 0,
+            branches,
+            branch_name: String::new(),
+            splice_range: (0, 0),
+            branch_diff: None,
         }
         .run(context)
     }
@@ -536,6 +823,8 @@ impl UIState<'_> {
     fn run(mut self, mut context: imgui::Context) {
         // Putting this outside the loop makes sure it never goes out of scope
         let mut callback_data = GameViewData::uninit();
+        self.redo_hitboxes();
+        self.apply_macros();
 
         // Frame loop begins here
         'gui: loop {
@@ -591,6 +880,68 @@ impl UIState<'_> {
                 .position([2.0, 210.0], imgui::Condition::Once)
                 .build(|| self.render_mouse_window(&frame, win_frame_height));
 
+            frame
+                .window("Text Input")
+                .size([300.0, 100.0], imgui::Condition::Once)
+                .position([535.0, 8.0], imgui::Condition::Once)
+                .build(|| self.render_text_window(&frame));
+
+            frame
+                .window("Debugger")
+                .resizable(true)
+                .size([300.0, 300.0], imgui::Condition::Once)
+                .position([535.0, 116.0], imgui::Condition::Once)
+                .build(|| self.render_debugger_window(&frame));
+
+            frame
+                .window("Profiler")
+                .resizable(true)
+                .size([420.0, 300.0], imgui::Condition::Once)
+                .position([843.0, 8.0], imgui::Condition::Once)
+                .build(|| self.render_profiler_window(&frame));
+
+            frame
+                .window("Console")
+                .resizable(true)
+                .size([420.0, 300.0], imgui::Condition::Once)
+                .position([843.0, 316.0], imgui::Condition::Once)
+                .build(|| self.render_console_window(&frame));
+
+            frame
+                .window("Watches")
+                .resizable(true)
+                .size([300.0, 200.0], imgui::Condition::Once)
+                .position([535.0, 424.0], imgui::Condition::Once)
+                .build(|| self.render_watches_window(&frame));
+
+            frame
+                .window("RAM Search")
+                .resizable(true)
+                .size([420.0, 300.0], imgui::Condition::Once)
+                .position([843.0, 624.0], imgui::Condition::Once)
+                .build(|| self.render_search_window(&frame));
+
+            frame
+                .window("Hitboxes")
+                .resizable(true)
+                .size([300.0, 200.0], imgui::Condition::Once)
+                .position([535.0, 632.0], imgui::Condition::Once)
+                .build(|| self.render_hitbox_window(&frame));
+
+            frame
+                .window("Macros")
+                .resizable(true)
+                .size([520.0, 300.0], imgui::Condition::Once)
+                .position([8.0, 720.0], imgui::Condition::Once)
+                .build(|| self.render_macro_window(&frame));
+
+            frame
+                .window("Branches")
+                .resizable(true)
+                .size([420.0, 300.0], imgui::Condition::Once)
+                .position([535.0, 840.0], imgui::Condition::Once)
+                .build(|| self.render_branch_window(&frame));
+
             // Instance-watcher windows
             let previous_len = self.config.watched_ids.len();
             self.instance_images.clear();
@@ -770,6 +1121,7 @@ impl UIState<'_> {
             }
 
             // Show error/info message above everything else, if there is one
+            self.show_warnings();
             if let Some(err) = &self.err_string {
                 if !frame.popup(err) {
                     if self.startup_successful {
@@ -854,6 +1206,10 @@ impl UIState<'_> {
         }
 
         self.save_config();
+        if self.profiling {
+            // Give it back so it can be written out if it was asked for on the command line
+            self.game.profiler = Some(std::mem::take(&mut self.profiler));
+        }
         if let Some(e) = self.err_string {
             println!("Warning: recording.rs exited with an error present: {}", e);
         }
@@ -876,6 +1232,7 @@ impl UIState<'_> {
                         _ => (),
                     }
                 },
+                Event::Input(ch) => io.add_input_character(ch),
                 Event::MouseMove((x, y)) => {
                     io.mouse_pos = [x as f32, y as f32];
                 },
@@ -959,6 +1316,9 @@ impl UIState<'_> {
                 }
 
                 frame.callback(callback, callback_data);
+                if self.config.hitboxes.enabled {
+                    self.draw_hitboxes(frame, Vec2(x + win_border_size, y + win_frame_height), w, h);
+                }
 
                 if self.setting_mouse_pos && frame.is_mouse_clicked(imgui::MouseButton::Left) {
                     self.setting_mouse_pos = false;
@@ -1004,11 +1364,30 @@ impl UIState<'_> {
 
     /// Draws an imgui window with the main controls and some project info in it
     fn render_control_window(&mut self, frame: &imgui::Ui, fps_text: &str) {
-        if (frame.button_with_size("Advance (Space)", [165.0, 20.0]) || frame.key_pressed(input::ramen2vk(Key::Space)))
+        // Don't take keyboard shortcuts while the user is typing into a text box
+        let shortcuts = !frame.io().want_text_input;
+        if (frame.button_with_size("Advance (Space)", [165.0, 20.0])
+            || (shortcuts && frame.key_pressed(input::ramen2vk(Key::Space)))
+            || self.fast_forward)
             && self.game_running
             && self.err_string.is_none()
         {
-            let (w, h) = self.game.renderer.stored_size();
+            *self.debugger_stop.borrow_mut() = None;
+            self.debug_savestate = None;
+            if !self.breakpoints.is_empty() || self.break_next_frame {
+                self.debug_savestate =
+                    Some(SaveState::from(&mut self.game, self.replay.clone(), self.game_renderer_state.clone()));
+                let mut debugger = Debugger::new(Box::new(SnapshotFrontend(self.debugger_stop.clone())));
+                debugger.breakpoints = self.breakpoints.clone();
+                if self.break_next_frame {
+                    debugger.resume(Resume::StepInto);
+                }
+                self.game.debugger = Some(Box::new(debugger));
+            }
+            if self.profiling {
+                self.game.profiler = Some(std::mem::take(&mut self.profiler));
+            }
+
             let frame = self.replay.new_frame();
 
             self.game.input.mouse_step();
@@ -1098,6 +1477,11 @@ impl UIState<'_> {
                 }
             }
 
+            for ch in self.new_text.drain(..) {
+                self.game.input.char_input(ch);
+                frame.inputs.push(replay::Input::Char(ch));
+            }
+
             if let Some((x, y)) = self.new_mouse_pos {
                 frame.mouse_x = x;
                 frame.mouse_y = y;
@@ -1109,36 +1493,24 @@ impl UIState<'_> {
                 self.game.rand.set_seed(rand.seed());
             }
 
-            self.game.renderer.set_state(&self.game_renderer_state);
-            self.game.renderer.resize_framebuffer(w, h, false);
-            self.game.renderer.set_view(
-                0,
-                0,
-                self.game.unscaled_width as _,
-                self.game.unscaled_height as _,
-                0.0,
-                0,
-                0,
-                self.game.unscaled_width as _,
-                self.game.unscaled_height as _,
-            );
-            self.game.renderer.draw_stored(0, 0, w, h);
-            if let Err(e) = match self.game.frame() {
-                Ok(()) => match self.game.scene_change {
-                    Some(SceneChange::Room(id)) => self.game.load_room(id),
-                    Some(SceneChange::Restart) => self.game.restart(),
-                    Some(SceneChange::End) => self.game.restart(),
-                    Some(SceneChange::Load(ref mut path)) => {
-                        let path = std::mem::take(path);
-                        self.game.load_gm_save(path)
-                    },
-                    None => Ok(()),
-                },
-                Err(e) => Err(e.into()),
-            } {
+            if let Err(e) = run_game_frame(
+                self.game,
+                &mut self.game_renderer_state,
+                &self.ui_renderer_state,
+                self.config.ui_width,
+                self.config.ui_height,
+            ) {
                 self.err_string = Some(format!("Game crashed: {}\n\nPlease load a savestate.", e));
                 self.game_running = false;
             }
+            self.game.debugger = None;
+            if self.debugger_stop.borrow().is_none() {
+                self.debug_savestate = None;
+            }
+            if let Some(profiler) = self.game.profiler.take() {
+                self.profiler = profiler;
+                self.redo_profile_rows();
+            }
 
             for ev in self.game.stored_events.iter() {
                 frame.events.push(ev.clone());
@@ -1150,42 +1522,26 @@ impl UIState<'_> {
             for (i, state) in self.mouse_state.iter_mut().enumerate() {
                 state.reset_to(self.game.input.mouse_check_button(i as i8 + 1));
             }
-
-            // Fake frame limiter stuff (don't actually frame-limit in record mode)
-            if let GameClock::SpoofedNanos(t) = &mut self.game.clock {
-                *t += 1_000_000_000 / self.game.room.speed as u128;
-            }
-            if self.game.frame_counter == self.game.room.speed {
-                self.game.fps = self.game.room.speed;
-                self.game.frame_counter = 0;
-            }
-            self.game.frame_counter += 1;
+            self.apply_macros();
 
             self.frame_text = format!("Frame: {}", self.replay.frame_count());
             self.seed_text = format!("Seed: {}", self.game.rand.seed());
 
-            self.game.renderer.resize_framebuffer(self.config.ui_width.into(), self.config.ui_height.into(), true);
-            self.game.renderer.set_view(
-                0,
-                0,
-                self.config.ui_width.into(),
-                self.config.ui_height.into(),
-                0.0,
-                0,
-                0,
-                self.config.ui_width.into(),
-                self.config.ui_height.into(),
-            );
-            self.game.renderer.clear_view(CLEAR_COLOUR, 1.0);
-            self.game_renderer_state = self.game.renderer.state();
-            self.game.renderer.set_state(&self.ui_renderer_state);
             self.context_menu = None;
             self.new_mouse_pos = None;
+            self.new_text.clear();
 
             self.redo_instance_reports();
+            self.update_watches();
+            self.redo_search_rows();
+            self.redo_hitboxes();
+        }
+        if !self.game_running {
+            self.fast_forward = false;
         }
 
-        if (frame.button_with_size("Quick Save (Q)", [165.0, 20.0]) || frame.key_pressed(input::ramen2vk(Key::Q)))
+        if (frame.button_with_size("Quick Save (Q)", [165.0, 20.0])
+            || (shortcuts && frame.key_pressed(input::ramen2vk(Key::Q))))
             && self.game_running
             && self.err_string.is_none()
         {
@@ -1205,7 +1561,9 @@ impl UIState<'_> {
             self.context_menu = None;
         }
 
-        if frame.button_with_size("Load Quicksave (W)", [165.0, 20.0]) || frame.key_pressed(input::ramen2vk(Key::W)) {
+        if frame.button_with_size("Load Quicksave (W)", [165.0, 20.0])
+            || (shortcuts && frame.key_pressed(input::ramen2vk(Key::W)))
+        {
             if self.startup_successful {
                 let state = self.cached_savestate.clone();
                 self.load_state(state);
@@ -1245,7 +1603,7 @@ impl UIState<'_> {
         };
         if frame.button_with_size(keyboard_label, [165.0, 20.0]) {
             self.config.full_keyboard = !self.config.full_keyboard;
-            let _ = File::create(&self.config_path).map(|f| bincode::serialize_into(f, &self.config));
+            let _ = self.config.save(&self.config_path);
         }
 
         let input_label = match self.config.input_mode {
@@ -1259,6 +1617,8 @@ impl UIState<'_> {
             }
         }
 
+        frame.checkbox("Fast-forward", &mut self.fast_forward);
+
         if frame.button_with_size_and_pos(">", Vec2(18.0, 18.0), Vec2(160.0, 138.0)) {
             if let Some(rand) = &mut self.new_rand {
                 rand.cycle();
@@ -1339,6 +1699,12 @@ impl UIState<'_> {
                                 savestate::ReadError::DeserializeErr(err) => {
                                     format!("Error deserializing {}:\n\n{}", filename, err)
                                 },
+                                savestate::ReadError::NewerVersion(version) => format!(
+                                    "{} is savestate version {}, which is newer than this emulator knows about ({})",
+                                    filename,
+                                    version,
+                                    savestate::VERSION,
+                                ),
                             });
                         },
                     }
@@ -1351,7 +1717,7 @@ impl UIState<'_> {
                         Ok(state) => {
                             self.cached_savestate = state;
                             self.config.quicksave_slot = i;
-                            let _ = File::create(&self.config_path).map(|f| bincode::serialize_into(f, &self.config));
+                            let _ = self.config.save(&self.config_path);
                         },
                         Err(e) => self.err_string = Some(format!(
                             "Error: Failed to select quicksave slot {:?}. {:?}",
@@ -1744,11 +2110,1026 @@ impl UIState<'_> {
         }
     }
 
+    /// Renders the text input window, for typing characters (as opposed to pressing keys) into the game
+    fn render_text_window(&mut self, frame: &imgui::Ui) {
+        if frame.is_window_collapsed() {
+            return;
+        }
+
+        frame.input_text("###TextEntry", &mut self.text_entry).build();
+        if frame.button("Type") {
+            // GM8 gets text in the game's codepage, so anything which doesn't exist in that gets left out
+            for ch in self.text_entry.chars() {
+                if let Some(bytes) = input::encode_char(ch, self.game.encoding) {
+                    self.new_text.extend_from_slice(&bytes);
+                }
+            }
+            self.text_entry.clear();
+        }
+        frame.same_line();
+        if frame.button("Backspace") {
+            self.new_text.push(0x08);
+        }
+        frame.same_line();
+        if frame.button("Clear") {
+            self.new_text.clear();
+        }
+
+        if self.new_text.is_empty() {
+            let keyboard_string = self.game.encoding.decode_without_bom_handling(self.game.input.keyboard_string()).0;
+            frame.text(&format!("keyboard_string: {}", keyboard_string));
+        } else {
+            let text = self
+                .new_text
+                .split(|&ch| ch == 0x08)
+                .map(|part| self.game.encoding.decode_without_bom_handling(part).0)
+                .collect::<Vec<_>>()
+                .join("<BS>");
+            frame.coloured_text(&format!("Next frame: {}*", text), Colour::new(1.0, 0.5, 0.5));
+        }
+    }
+
+    /// Renders the GML debugger's breakpoints, and where the game stopped during the last frame advance
+    fn render_debugger_window(&mut self, frame: &imgui::Ui) {
+        if frame.is_window_collapsed() {
+            return;
+        }
+
+        frame.input_text("###BreakpointEntry", &mut self.breakpoint_entry).build();
+        frame.same_line();
+        if frame.button("Add") {
+            match Breakpoint::parse(&self.breakpoint_entry, &self.game) {
+                Ok(breakpoint) => {
+                    self.breakpoint_text.push(breakpoint.describe(&self.game));
+                    self.breakpoints.push(breakpoint);
+                    self.breakpoint_entry.clear();
+                },
+                Err(e) => self.err_string = Some(format!("Couldn't add breakpoint: {}", e)),
+            }
+        }
+        let mut deleted = None;
+        for (i, text) in self.breakpoint_text.iter().enumerate() {
+            if frame.button(&format!("X###DeleteBreakpoint{}", i)) {
+                deleted = Some(i);
+            }
+            frame.same_line();
+            frame.text(text);
+        }
+        if let Some(i) = deleted {
+            self.breakpoints.remove(i);
+            self.breakpoint_text.remove(i);
+        }
+        frame.checkbox("Break when the next frame starts", &mut self.break_next_frame);
+        if !self.game.compiler.line_info() {
+            frame.text("Lines can only be stepped through when the recorder is started with --debug");
+        }
+        frame.separator();
+
+        let mut resume = None;
+        if let Some(stop) = &*self.debugger_stop.borrow() {
+            if let Some(location) = stop.backtrace.first() {
+                frame.text(&format!("Stopped ({}): {}", stop.reason, location));
+            }
+            if let Some(line) = &stop.source_line {
+                frame.coloured_text(line, Colour::new(1.0, 0.788, 0.055));
+            }
+            if frame.button("Step Into") {
+                resume = Some(Resume::StepInto);
+            }
+            frame.same_line();
+            if frame.button("Step Over") {
+                resume = Some(Resume::StepOver);
+            }
+            frame.same_line();
+            if frame.button("Step Out") {
+                resume = Some(Resume::StepOut);
+            }
+            frame.same_line();
+            if frame.button("Continue") {
+                resume = Some(Resume::Continue);
+            }
+            if let Some(node) = frame.tree_node("Call Stack") {
+                stop.backtrace.iter().for_each(|s| frame.text(s));
+                node.pop();
+            }
+            for (label, values) in [("Locals", &stop.locals), ("Self", &stop.instance), ("Globals", &stop.globals)] {
+                if let Some(node) = frame.tree_node(label) {
+                    values.iter().for_each(|(name, value)| frame.text(&format!("{} = {}", name, value)));
+                    node.pop();
+                }
+            }
+        } else {
+            frame.text("Not stopped");
+        }
+        if let Some(resume) = resume {
+            self.debug_rerun(resume);
+        }
+    }
+
+    /// Runs the last frame again from the start, carrying on from where the debugger last stopped.
+    /// This doesn't count as a re-record, since the frame still ends up exactly the same.
+    fn debug_rerun(&mut self, resume: Resume) {
+        let (tick, depth) = match &*self.debugger_stop.borrow() {
+            Some(stop) => (stop.tick, stop.depth),
+            None => return,
+        };
+        let state = match &self.debug_savestate {
+            Some(state) => state.clone(),
+            None => return,
+        };
+        let replay_frame = match self.replay.frame_count().checked_sub(1).and_then(|i| self.replay.get_frame(i)) {
+            Some(replay_frame) => replay_frame.clone(),
+            None => return,
+        };
+
+        let (_, new_renderer_state) = state.load_into(&mut self.game);
+        self.game_renderer_state = new_renderer_state;
+        self.game.input.mouse_step();
+        self.game.play_type = PlayType::Replay;
+        self.game.apply_replay_frame(&replay_frame);
+
+        *self.debugger_stop.borrow_mut() = None;
+        let mut debugger = Debugger::new(Box::new(SnapshotFrontend(self.debugger_stop.clone())));
+        debugger.breakpoints = self.breakpoints.clone();
+        debugger.resume_after(tick, depth, resume);
+        self.game.debugger = Some(Box::new(debugger));
+        let result = run_game_frame(
+            self.game,
+            &mut self.game_renderer_state,
+            &self.ui_renderer_state,
+            self.config.ui_width,
+            self.config.ui_height,
+        );
+        self.game.debugger = None;
+        if result.is_ok() {
+            self.game.replay_console(&replay_frame.events);
+        }
+        self.game.play_type = PlayType::Record;
+        self.game.stored_events.clear();
+
+        if let Err(e) = result {
+            self.err_string = Some(format!("Game crashed: {}\n\nPlease load a savestate.", e));
+            self.game_running = false;
+        }
+        self.seed_text = format!("Seed: {}", self.game.rand.seed());
+        self.redo_instance_reports();
+        self.refresh_watches();
+        self.redo_search_rows();
+        self.redo_hitboxes();
+    }
+
+    /// Renders the profiler's results as a table, which gets sorted by clicking on a column header
+    fn render_profiler_window(&mut self, frame: &imgui::Ui) {
+        if frame.is_window_collapsed() {
+            return;
+        }
+
+        frame.checkbox("Profile frame advances", &mut self.profiling);
+        if frame.button("Reset") {
+            self.profiler.reset();
+            self.redo_profile_rows();
+        }
+        frame.same_line();
+        if frame.button("Export folded stacks") {
+            let mut path = self.project_path.clone();
+            path.push("profile.folded");
+            if let Err(err) =
+                File::create(&path).and_then(|f| self.profiler.write_folded(&self.game, BufWriter::new(f)))
+            {
+                self.err_string = Some(format!("Failed to write profile.folded: {}", err));
+            }
+        }
+
+        fn ms(time: Duration) -> String {
+            format!("{:.3}ms", time.as_secs_f64() * 1000.0)
+        }
+        frame.columns(5, "ProfileTable", true);
+        for (label, column) in [
+            ("Name", ProfileColumn::Name),
+            ("Calls", ProfileColumn::Calls),
+            ("Total", ProfileColumn::TotalTime),
+            ("Self", ProfileColumn::SelfTime),
+            ("Draw calls", ProfileColumn::DrawCalls),
+        ] {
+            if frame.button(label) {
+                self.profile_sort = column;
+                self.sort_profile_rows();
+            }
+            frame.next_column();
+        }
+        frame.separator();
+        for row in self.profile_rows.iter() {
+            frame.text(&row.name);
+            frame.next_column();
+            frame.text(&row.calls.to_string());
+            frame.next_column();
+            frame.text(&ms(row.total_time));
+            frame.next_column();
+            frame.text(&ms(row.self_time));
+            frame.next_column();
+            frame.text(&row.draw_calls.to_string());
+            frame.next_column();
+        }
+        frame.columns(1, "", false);
+    }
+
+    /// Remakes the cached profiler results
+    fn redo_profile_rows(&mut self) {
+        self.profile_rows = self.profiler.table(&self.game);
+        self.sort_profile_rows();
+    }
+
+    fn sort_profile_rows(&mut self) {
+        let rows = &mut self.profile_rows;
+        match self.profile_sort {
+            ProfileColumn::Name => rows.sort_by(|a, b| a.name.cmp(&b.name)),
+            ProfileColumn::Calls => rows.sort_by_key(|row| Reverse(row.calls)),
+            ProfileColumn::TotalTime => rows.sort_by_key(|row| Reverse(row.total_time)),
+            ProfileColumn::SelfTime => rows.sort_by_key(|row| Reverse(row.self_time)),
+            ProfileColumn::DrawCalls => rows.sort_by_key(|row| Reverse(row.draw_calls)),
+        }
+    }
+
+    /// Renders the GML console, along with everything typed into it and what came back
+    fn render_console_window(&mut self, frame: &imgui::Ui) {
+        if frame.is_window_collapsed() {
+            return;
+        }
+
+        let entered = frame.input_text("###ConsoleEntry", &mut self.console_entry).enter_returns_true(true).build();
+        frame.same_line();
+        if (frame.button("Run") || entered) && self.game_running && self.err_string.is_none() {
+            self.run_console();
+        }
+        match self.console.instance {
+            Some(id) => frame.text(&format!("Running as instance {} (:self to change)", id)),
+            None => frame.text("Running as a dummy instance (:self ID to change)"),
+        }
+        frame.text("Anything run here is saved in the replay");
+
+        if let Some(node) = frame.tree_node("History") {
+            let mut chosen = None;
+            for (i, line) in self.console.history.iter().enumerate().rev() {
+                if frame.button(&format!("Use###ConsoleHistory{}", i)) {
+                    chosen = Some(i);
+                }
+                frame.same_line();
+                frame.text(line);
+            }
+            if let Some(i) = chosen {
+                self.console_entry = self.console.history[i].clone();
+            }
+            node.pop();
+        }
+        frame.separator();
+        self.console.output.iter().for_each(|s| frame.text(s));
+    }
+
+    /// Runs whatever's in the console's input box, saving it in the replay after the current frame
+    /// so that it happens again on playback
+    fn run_console(&mut self) {
+        let line = std::mem::take(&mut self.console_entry);
+        self.console.submit(self.game, &line);
+        let line = line.trim();
+        if !line.is_empty() && !line.starts_with(':') {
+            let events = self.replay.last_events_mut();
+            events.push(replay::Event::Console { code: line.into(), instance: self.console.instance });
+            events.extend(self.game.stored_events.drain(..));
+            if self.new_rand.is_none() {
+                self.seed_text = format!("Seed: {}", self.game.rand.seed());
+            }
+            self.redo_instance_reports();
+            self.refresh_watches();
+            self.redo_search_rows();
+            self.redo_hitboxes();
+        }
+    }
+
+    /// Renders the watch expressions and what they came to after the last frame
+    fn render_watches_window(&mut self, frame: &imgui::Ui) {
+        if frame.is_window_collapsed() {
+            return;
+        }
+
+        frame.input_text("###WatchEntry", &mut self.watch_entry).build();
+        frame.same_line();
+        if frame.button("Add") && !self.watch_entry.trim().is_empty() {
+            let source = self.watch_entry.trim().to_string();
+            self.watches.push(Watch::new(source, self.watch_pause, self.game));
+            self.watch_entry.clear();
+            self.save_watches();
+        }
+        frame.checkbox("Stop advancing when it becomes true", &mut self.watch_pause);
+        if let Some(source) = &self.watch_stopped {
+            let text = format!("Stopped advancing because {} became true", source);
+            frame.coloured_text(&text, Colour::new(1.0, 0.788, 0.055));
+        }
+        frame.separator();
+
+        let mut deleted = None;
+        let mut changed = false;
+        for (i, watch) in self.watches.iter_mut().enumerate() {
+            if frame.button(&format!("X###DeleteWatch{}", i)) {
+                deleted = Some(i);
+            }
+            frame.same_line();
+            changed |= frame.checkbox(&format!("###PauseWatch{}", i), &mut watch.pause);
+            if frame.is_item_hovered() {
+                frame.tooltip_text("Stop advancing when this becomes true");
+            }
+            frame.same_line();
+            match &watch.value {
+                Ok(_) => frame.text(&watch.describe()),
+                Err(_) => frame.coloured_text(&watch.describe(), Colour::new(1.0, 0.5, 0.5)),
+            }
+        }
+        if let Some(i) = deleted {
+            self.watches.remove(i);
+        }
+        if deleted.is_some() || changed {
+            self.save_watches();
+        }
+    }
+
+    /// Evaluates all the watch expressions after a frame advance, stopping if any pause conditions became true
+    fn update_watches(&mut self) {
+        self.watch_stopped = None;
+        for watch in self.watches.iter_mut() {
+            if watch.update(self.game) && watch.pause && self.watch_stopped.is_none() {
+                self.fast_forward = false;
+                self.watch_stopped = Some(watch.source.clone());
+            }
+        }
+    }
+
+    /// Evaluates all the watch expressions again without stopping, for when the game changed some other way
+    fn refresh_watches(&mut self) {
+        for watch in self.watches.iter_mut() {
+            watch.update(self.game);
+        }
+    }
+
+    /// Puts the watch expressions into the project config and saves it
+    fn save_watches(&mut self) {
+        self.config.watches = self
+            .watches
+            .iter()
+            .map(|watch| WatchConfig { expression: watch.source.clone(), pause: watch.pause })
+            .collect();
+        self.save_config();
+    }
+
+    /// Renders the RAM search, which narrows down the game's variables by how they change between frame advances
+    fn render_search_window(&mut self, frame: &imgui::Ui) {
+        if frame.is_window_collapsed() {
+            return;
+        }
+
+        if frame.button("New Search") && self.game_running {
+            self.search = Some(Search::new(&self.game));
+            self.redo_search_rows();
+        }
+        let count = match &self.search {
+            Some(search) => search.candidates().len(),
+            None => {
+                frame.text("Start a search, advance, then narrow it down");
+                return
+            },
+        };
+
+        let mut filter = None;
+        if frame.button("Equal to") {
+            let text = self.search_value.trim();
+            filter = Some(Filter::Equal(match text.parse::<f64>() {
+                Ok(real) => Value::from(real),
+                Err(_) => Value::from(text.trim_matches('"')),
+            }));
+        }
+        frame.same_line();
+        frame.input_text("###SearchValue", &mut self.search_value).build();
+        if frame.button("Changed") {
+            filter = Some(Filter::Changed);
+        }
+        frame.same_line();
+        if frame.button("Unchanged") {
+            filter = Some(Filter::Unchanged);
+        }
+        frame.same_line();
+        if frame.button("Increased") {
+            filter = Some(Filter::Increased);
+        }
+        frame.same_line();
+        if frame.button("Decreased") {
+            filter = Some(Filter::Decreased);
+        }
+        if let (Some(filter), Some(search)) = (filter, &mut self.search) {
+            search.filter(&self.game, &filter);
+            self.redo_search_rows();
+        }
+
+        if count > SEARCH_ROWS {
+            frame.text(&format!("{} left, showing the first {}", count, SEARCH_ROWS));
+        } else {
+            frame.text(&format!("{} left", count));
+        }
+        frame.separator();
+        let mut pinned = None;
+        for (i, (text, expression)) in self.search_rows.iter().enumerate() {
+            if let Some(expression) = expression {
+                if frame.button(&format!("Pin###PinSearch{}", i)) {
+                    pinned = Some(expression.clone());
+                }
+                frame.same_line();
+            }
+            frame.text(text);
+        }
+        if let Some(expression) = pinned {
+            self.watches.push(Watch::new(expression, false, self.game));
+            self.save_watches();
+        }
+    }
+
+    /// Remakes the cached UI text for the RAM search, with what everything holds right now
+    fn redo_search_rows(&mut self) {
+        self.search_rows = match &self.search {
+            Some(search) => search
+                .candidates()
+                .iter()
+                .take(SEARCH_ROWS)
+                .map(|(address, old)| {
+                    let now = address.read(&self.game).map_or_else(|| "<gone>".into(), |value| value.to_string());
+                    let text = format!("{} = {} (was {})", address.describe(&self.game), now, old);
+                    (text, address.expression(&self.game))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+    }
+
+    /// Renders the hitbox overlay's settings, including which objects to show it for
+    fn render_hitbox_window(&mut self, frame: &imgui::Ui) {
+        if frame.is_window_collapsed() {
+            return;
+        }
+
+        let config = &mut self.config.hitboxes;
+        let mut changed = frame.checkbox("Show over the game", &mut config.enabled);
+        changed |= frame.checkbox("Bounding boxes", &mut config.bbox);
+        frame.same_line();
+        changed |= frame.checkbox("Precise masks", &mut config.mask);
+        changed |= frame.checkbox("Origins", &mut config.origin);
+        frame.same_line();
+        changed |= frame.checkbox("Paths", &mut config.path);
+        frame.same_line();
+        changed |= frame.checkbox("Velocities", &mut config.velocity);
+        frame.coloured_text("Solid", HITBOX_SOLID_COL);
+        frame.same_line();
+        frame.coloured_text("Not solid", HITBOX_NONSOLID_COL);
+
+        if let Some(node) = frame.tree_node("Objects") {
+            for (object, label) in self.hitbox_objects.iter() {
+                let mut shown = !config.hidden_objects.contains(object);
+                if frame.checkbox(label, &mut shown) {
+                    if shown {
+                        config.hidden_objects.retain(|hidden| hidden != object);
+                    } else {
+                        config.hidden_objects.push(*object);
+                    }
+                    changed = true;
+                }
+            }
+            node.pop();
+        }
+        if changed {
+            self.redo_hitboxes();
+            self.save_config();
+        }
+    }
+
+    /// Draws the hitbox overlay over the game view, whose top-left corner is at the given position
+    fn draw_hitboxes(&self, frame: &imgui::Ui, offset: Vec2<f32>, w: u32, h: u32) {
+        let config = &self.config.hitboxes;
+        let views = if self.game.room.views_enabled {
+            self.game.room.views.iter().filter(|view| view.visible).map(Some).collect::<Vec<_>>()
+        } else {
+            vec![None]
+        };
+        for view in views {
+            let to_screen = |x: f64, y: f64| {
+                let (x, y) = match view {
+                    Some(view) => view.untransform_point_exact(x, y),
+                    None => (x, y),
+                };
+                offset + Vec2(x as f32, y as f32)
+            };
+            match view {
+                Some(view) => frame.push_clip_rect(
+                    offset + Vec2(view.port_x as f32, view.port_y as f32),
+                    offset + Vec2((view.port_x + view.port_w as i32) as f32, (view.port_y + view.port_h as i32) as f32),
+                ),
+                None => frame.push_clip_rect(offset, offset + Vec2(w as f32, h as f32)),
+            }
+
+            for hitbox in self.hitboxes.iter().filter(|hitbox| !config.hidden_objects.contains(&hitbox.object)) {
+                let colour = if hitbox.solid { HITBOX_SOLID_COL } else { HITBOX_NONSOLID_COL };
+                if config.mask {
+                    for &(row, left, right) in hitbox.mask.iter() {
+                        let min = to_screen(left.into(), row.into());
+                        let max = to_screen(f64::from(right) + 1.0, f64::from(row) + 1.0);
+                        frame.rect(min, max, colour, 96);
+                    }
+                }
+                if let (true, Some((left, top, right, bottom))) = (config.bbox, hitbox.bbox) {
+                    // Pixels are inclusive, so the box goes around the outside of the right and bottom ones
+                    let (left, top, right, bottom) =
+                        (f64::from(left), f64::from(top), f64::from(right) + 1.0, f64::from(bottom) + 1.0);
+                    let corners = [
+                        to_screen(left, top),
+                        to_screen(right, top),
+                        to_screen(right, bottom),
+                        to_screen(left, bottom),
+                    ];
+                    for i in 0..4 {
+                        frame.line(corners[i], corners[(i + 1) % 4], colour, 255);
+                    }
+                }
+                if config.path {
+                    for pair in hitbox.path.windows(2) {
+                        frame.line(
+                            to_screen(pair[0].0, pair[0].1),
+                            to_screen(pair[1].0, pair[1].1),
+                            HITBOX_PATH_COL,
+                            200,
+                        );
+                    }
+                }
+                let origin = to_screen(hitbox.x, hitbox.y);
+                if config.velocity && (hitbox.hspeed != 0.0 || hitbox.vspeed != 0.0) {
+                    let end =
+                        to_screen(hitbox.x + hitbox.hspeed * VELOCITY_SCALE, hitbox.y + hitbox.vspeed * VELOCITY_SCALE);
+                    frame.line(origin, end, HITBOX_VELOCITY_COL, 255);
+                }
+                if config.origin {
+                    frame.line(origin - Vec2(3.0, 0.0), origin + Vec2(4.0, 0.0), HITBOX_ORIGIN_COL, 255);
+                    frame.line(origin - Vec2(0.0, 3.0), origin + Vec2(0.0, 4.0), HITBOX_ORIGIN_COL, 255);
+                }
+            }
+            frame.pop_clip_rect();
+        }
+    }
+
+    /// Remakes the cached hitboxes for the overlay if it's on, along with the list of objects it can be filtered by
+    fn redo_hitboxes(&mut self) {
+        if !self.config.hitboxes.enabled {
+            self.hitboxes.clear();
+            self.hitbox_objects.clear();
+            return
+        }
+        use crate::game::GetAsset;
+        self.hitboxes = self.game.hitboxes();
+        let mut objects = self.hitboxes.iter().map(|hitbox| hitbox.object).collect::<Vec<_>>();
+        objects.extend(self.config.hitboxes.hidden_objects.iter().copied());
+        objects.sort_unstable();
+        objects.dedup();
+        self.hitbox_objects = objects
+            .into_iter()
+            .map(|object| {
+                let label = match self.game.assets.objects.get_asset(object) {
+                    Some(obj) => format!("{}###HitboxObject{}", obj.name.decode(self.game.encoding), object),
+                    None => format!("<deleted object>###HitboxObject{}", object),
+                };
+                (object, label)
+            })
+            .collect();
+    }
+
+    /// Renders the list of macros and autofires, each of which can be edited, switched on and off, or deleted
+    fn render_macro_window(&mut self, frame: &imgui::Ui) {
+        if frame.is_window_collapsed() {
+            return;
+        }
+
+        let next_frame = self.replay.frame_count();
+        let mut changed = false;
+        frame.text_wrapped(
+            "Steps are separated by commas, one per frame, and list the buttons held on that frame, \
+            eg. \"vk_left, vk_right\". A * in front taps a button instead, eg. \"*Z,,\".",
+        );
+        frame.input_text("##MacroName", &mut self.macro_name).build();
+        frame.same_line();
+        if frame.button("New macro") && !self.macro_name.trim().is_empty() {
+            self.config.macros.push(Macro::new(self.macro_name.trim().into(), next_frame));
+            self.macro_name.clear();
+            changed = true;
+        }
+
+        let mut deleted = None;
+        for (i, mac) in self.config.macros.iter_mut().enumerate() {
+            changed |= frame.checkbox(&format!("##MacroEnabled{}", i), &mut mac.enabled);
+            frame.same_line();
+            if let Some(node) = frame.tree_node(&format!("{}###Macro{}", mac.name, i)) {
+                changed |= frame.input_text("Steps", &mut mac.steps).build();
+                if let Err(e) = mac.parse() {
+                    frame.coloured_text(&e, Colour::new(1.0, 0.5, 0.5));
+                }
+                let mut start = mac.start as i32;
+                if frame.input_int("First frame", &mut start).build() {
+                    mac.start = start.max(0) as usize;
+                    changed = true;
+                }
+                let mut forever = mac.end.is_none();
+                if frame.checkbox("Forever", &mut forever) {
+                    mac.end = if forever { None } else { Some(mac.start.max(next_frame)) };
+                    changed = true;
+                }
+                if let Some(end) = &mut mac.end {
+                    let mut value = *end as i32;
+                    if frame.input_int("Stop before frame", &mut value).build() {
+                        *end = value.max(0) as usize;
+                        changed = true;
+                    }
+                }
+                if frame.button("Start from current frame") {
+                    mac.start = next_frame;
+                    changed = true;
+                }
+                frame.same_line();
+                if frame.button("Delete") {
+                    deleted = Some(i);
+                }
+                node.pop();
+            }
+        }
+        if let Some(i) = deleted {
+            self.config.macros.remove(i);
+            changed = true;
+        }
+
+        frame.separator();
+        if frame.button("New autofire") {
+            self.config.autofires.push(Autofire::new());
+            changed = true;
+        }
+        let mut deleted = None;
+        for (i, autofire) in self.config.autofires.iter_mut().enumerate() {
+            changed |= frame.checkbox(&format!("##AutofireEnabled{}", i), &mut autofire.enabled);
+            frame.same_line();
+            frame.set_next_item_width(100.0);
+            changed |= frame.input_text(&format!("Button##Autofire{}", i), &mut autofire.button).build();
+            frame.same_line();
+            let mut period = autofire.period as i32;
+            frame.set_next_item_width(80.0);
+            if frame.input_int(&format!("Period##Autofire{}", i), &mut period).build() {
+                autofire.period = period.max(1) as u32;
+                changed = true;
+            }
+            frame.same_line();
+            let mut phase = autofire.phase as i32;
+            frame.set_next_item_width(80.0);
+            if frame.input_int(&format!("Phase##Autofire{}", i), &mut phase).build() {
+                autofire.phase = phase.max(0) as u32;
+                changed = true;
+            }
+            frame.same_line();
+            if frame.button(&format!("Delete##Autofire{}", i)) {
+                deleted = Some(i);
+            }
+            if Button::parse(&autofire.button).is_none() {
+                frame.coloured_text("Unknown button", Colour::new(1.0, 0.5, 0.5));
+            }
+        }
+        if let Some(i) = deleted {
+            self.config.autofires.remove(i);
+            changed = true;
+        }
+
+        frame.separator();
+        frame.input_int("Stop before frame##RecordMacros", &mut self.macro_range_end).build();
+        if frame.button("Record from the current frame up to there") && self.game_running && self.err_string.is_none() {
+            self.record_macros(self.macro_range_end.max(0) as usize);
+        }
+
+        if changed {
+            self.apply_macros();
+            self.save_config();
+        }
+    }
+
+    /// Records what the enabled macros and autofires do on every frame from the current one up to the given frame,
+    /// playing them as it goes
+    fn record_macros(&mut self, end: usize) {
+        let start = self.replay.frame_count();
+        let input = &self.game.input;
+        let mut held = (0..=255).filter(|&key| input.keyboard_check_direct(key)).map(Button::Key).collect::<Vec<_>>();
+        held.extend((1..=3).filter(|&button| input.mouse_check_button(button)).map(Button::Mouse));
+        let (mouse_x, mouse_y) = match start.checked_sub(1).and_then(|i| self.replay.get_frame(i)) {
+            Some(frame) => (frame.mouse_x, frame.mouse_y),
+            None => (0, 0),
+        };
+        let frames = macros::expand(&self.config.macros, &self.config.autofires, start..end.max(start), &held)
+            .into_iter()
+            .map(|inputs| replay::Frame {
+                mouse_x,
+                mouse_y,
+                inputs,
+                events: Vec::new(),
+                new_seed: None,
+                new_time: None,
+            })
+            .collect();
+        self.play_frames(frames, PlayType::Record);
+    }
+
+    /// Sets the buttons for the next frame the way the enabled macros and autofires say to,
+    /// which then gets recorded the same as if they'd been clicked
+    fn apply_macros(&mut self) {
+        let actions = macros::actions(&self.config.macros, &self.config.autofires, self.replay.frame_count());
+        for (button, action) in actions {
+            let state = match button {
+                Button::Key(key) => &mut self.keyboard_state[usize::from(key)],
+                Button::Mouse(button) => &mut self.mouse_state[button as usize - 1],
+            };
+            *state = match (action, state.is_held()) {
+                (Action::Hold, true) => KeyState::Held,
+                (Action::Hold, false) => KeyState::NeutralWillPress,
+                (Action::Release, true) => KeyState::HeldWillRelease,
+                (Action::Release, false) => KeyState::Neutral,
+                (Action::Tap, true) => KeyState::HeldWillTriple,
+                (Action::Tap, false) => KeyState::NeutralWillDouble,
+            };
+        }
+    }
+
+    /// Renders the list of branches, with buttons for switching to, comparing against and splicing from each one
+    fn render_branch_window(&mut self, frame: &imgui::Ui) {
+        if frame.is_window_collapsed() {
+            return;
+        }
+
+        frame.text(&format!("Current branch: {}", self.config.branch.as_deref().unwrap_or(branch::MAIN)));
+        frame.input_text("##BranchName", &mut self.branch_name).build();
+        frame.same_line();
+        if frame.button("Fork here") && self.game_running {
+            self.fork_branch();
+        }
+        frame.input_int("From frame##Splice", &mut self.splice_range.0).build();
+        frame.input_int("Stop before frame##Splice", &mut self.splice_range.1).build();
+        frame.separator();
+
+        let names = std::iter::once(None).chain(self.branches.iter().cloned().map(Some)).collect::<Vec<_>>();
+        for (i, name) in names.into_iter().enumerate() {
+            frame.text(name.as_deref().unwrap_or(branch::MAIN));
+            if name == self.config.branch {
+                continue
+            }
+            frame.same_line();
+            if frame.button(&format!("Switch##Branch{}", i)) && self.startup_successful {
+                self.switch_branch(name.clone());
+            }
+            frame.same_line();
+            if frame.button(&format!("Compare##Branch{}", i)) {
+                self.compare_branch(name.as_deref());
+            }
+            frame.same_line();
+            if frame.button(&format!("Splice##Branch{}", i)) && self.game_running {
+                self.splice_branch(name.as_deref());
+            }
+            if frame.is_item_hovered() {
+                frame.tooltip_text("Replaces the given frames with this branch's, then replays the frames after them");
+            }
+        }
+
+        if let Some((summary, rows)) = &self.branch_diff {
+            frame.separator();
+            frame.text(summary);
+            for row in rows.iter() {
+                frame.text(row);
+            }
+        }
+    }
+
+    /// Makes a new branch from the current frame and switches to it, leaving the current branch's head here too
+    fn fork_branch(&mut self) {
+        let name = self.branch_name.trim().to_string();
+        if let Err(e) = branch::create(&self.project_path, &name) {
+            self.err_string = Some(e);
+            return
+        }
+        let state = SaveState::from(&mut self.game, self.replay.clone(), self.game_renderer_state.clone());
+        let save_paths = branch::save_paths(&self.project_path, Some(&name));
+        let paths = [
+            branch::head_path(&self.project_path, self.config.branch.as_deref()),
+            branch::head_path(&self.project_path, Some(&name)),
+            save_paths[self.config.quicksave_slot].clone(),
+        ];
+        for path in paths.iter() {
+            if let Err(e) = state.save_to_file(path, &mut self.lz4_buffer) {
+                self.err_string = Some(format!("Failed to write {}: {:?}", path.display(), e));
+                return
+            }
+        }
+
+        self.save_paths = save_paths;
+        self.cached_savestate = state;
+        self.config.branch = Some(name);
+        self.branches = branch::list(&self.project_path);
+        self.branch_name.clear();
+        self.branch_diff = None;
+        self.save_config();
+    }
+
+    /// Saves where the current branch is up to, then carries on from wherever another branch was left
+    fn switch_branch(&mut self, name: Option<String>) {
+        let head = branch::head_path(&self.project_path, self.config.branch.as_deref());
+        if self.game_running {
+            let state = SaveState::from(&mut self.game, self.replay.clone(), self.game_renderer_state.clone());
+            if let Err(e) = state.save_to_file(&head, &mut self.lz4_buffer) {
+                self.err_string = Some(format!("Failed to write {}: {:?}", head.display(), e));
+                return
+            }
+        }
+        let head = branch::head_path(&self.project_path, name.as_deref());
+        let state = match SaveState::from_file(&head, &mut self.lz4_buffer) {
+            Ok(state) => state,
+            Err(e) => {
+                self.err_string = Some(format!("Error loading {}: {:?}", head.display(), e));
+                return
+            },
+        };
+
+        self.save_paths = branch::save_paths(&self.project_path, name.as_deref());
+        self.cached_savestate =
+            SaveState::from_file(&self.save_paths[self.config.quicksave_slot], &mut self.lz4_buffer)
+                .unwrap_or_else(|_| state.clone());
+        self.config.branch = name;
+        self.branch_diff = None;
+        self.load_state(state);
+    }
+
+    /// Gets a branch's inputs, which for any branch but the current one are the inputs up to its head.
+    fn branch_replay(&mut self, name: Option<&str>) -> Result<Replay, String> {
+        if name == self.config.branch.as_deref() {
+            return Ok(self.replay.clone())
+        }
+        let head = branch::head_path(&self.project_path, name);
+        SaveState::from_file(&head, &mut self.lz4_buffer)
+            .map(SaveState::into_replay)
+            .map_err(|e| format!("Error loading {}: {:?}", head.display(), e))
+    }
+
+    /// Compares another branch's inputs with the current branch's, frame by frame
+    fn compare_branch(&mut self, name: Option<&str>) {
+        let other = match self.branch_replay(name) {
+            Ok(replay) => replay,
+            Err(e) => {
+                self.err_string = Some(e);
+                return
+            },
+        };
+        let current_name = self.config.branch.as_deref().unwrap_or(branch::MAIN);
+        let other_name = name.unwrap_or(branch::MAIN);
+        let frames = branch::compare(&self.replay, &other);
+        let summary = match frames.first() {
+            Some(first) => format!(
+                "{} and {} differ on {} frames, starting at frame {}",
+                current_name,
+                other_name,
+                frames.len(),
+                first
+            ),
+            None => format!("{} and {} have the same inputs", current_name, other_name),
+        };
+        let rows = frames
+            .into_iter()
+            .take(BRANCH_DIFF_ROWS)
+            .map(|i| {
+                format!(
+                    "{}: {}: {} | {}: {}",
+                    i,
+                    current_name,
+                    branch::describe(self.replay.get_frame(i)),
+                    other_name,
+                    branch::describe(other.get_frame(i)),
+                )
+            })
+            .collect();
+        self.branch_diff = Some((summary, rows));
+    }
+
+    /// Replaces a range of the current branch's frames with another branch's, then plays the rest of the current
+    /// branch's frames back over the top. This goes back to the latest savestate from before the range to do it.
+    fn splice_branch(&mut self, name: Option<&str>) {
+        let source = match self.branch_replay(name) {
+            Ok(replay) => replay,
+            Err(e) => {
+                self.err_string = Some(e);
+                return
+            },
+        };
+        let from = self.splice_range.0.max(0) as usize;
+        let to = (self.splice_range.1.max(0) as usize).min(source.frame_count());
+        let current = self.replay.frame_count();
+        if from >= to {
+            self.err_string = Some(format!("There aren't any frames to splice in from frame {} to frame {}", from, to));
+            return
+        }
+        if from > current {
+            self.err_string =
+                Some(format!("Can't splice at frame {} when this branch only has {} frames", from, current));
+            return
+        }
+
+        // Savestates from other timelines could be anywhere, so only ones this branch went through are any use
+        let mut start: Option<SaveState> = None;
+        if from < current {
+            for path in self.save_paths.iter().filter(|path| path.exists()) {
+                if let Ok(state) = SaveState::from_file(path, &mut self.lz4_buffer) {
+                    let frames = state.replay().frame_count();
+                    if frames <= from
+                        && branch::same_until(state.replay(), &self.replay, frames)
+                        && start.as_ref().map_or(true, |start| start.replay().frame_count() < frames)
+                    {
+                        start = Some(state);
+                    }
+                }
+            }
+            if start.is_none() {
+                self.err_string = Some(format!("There's no savestate on this branch from frame {} or before", from));
+                return
+            }
+        }
+
+        let resume = start.as_ref().map_or(current, |state| state.replay().frame_count());
+        let frames = (resume..from)
+            .filter_map(|i| self.replay.get_frame(i).cloned())
+            .chain((from..to).filter_map(|i| source.get_frame(i).cloned()))
+            .chain((to..current).filter_map(|i| self.replay.get_frame(i).cloned()))
+            .collect();
+        if let Some(state) = start {
+            self.load_state(state);
+        }
+        self.play_frames(frames, PlayType::Replay);
+    }
+
+    /// Plays frames one after another, adding each of them onto the end of the replay. Replayed frames bring their
+    /// own stored events with them, and recorded ones get whatever the game stores while they run.
+    fn play_frames(&mut self, frames: Vec<replay::Frame>, play_type: PlayType) {
+        *self.debugger_stop.borrow_mut() = None;
+        self.debug_savestate = None;
+        for mut replay_frame in frames {
+            self.game.input.mouse_step();
+            self.game.play_type = play_type;
+            self.game.apply_replay_frame(&replay_frame);
+            let result = run_game_frame(
+                self.game,
+                &mut self.game_renderer_state,
+                &self.ui_renderer_state,
+                self.config.ui_width,
+                self.config.ui_height,
+            );
+            if result.is_ok() && play_type == PlayType::Replay {
+                self.game.replay_console(&replay_frame.events);
+            }
+            self.game.play_type = PlayType::Record;
+            if play_type == PlayType::Record {
+                replay_frame.events.extend(self.game.stored_events.drain(..));
+            }
+            self.game.stored_events.clear();
+            *self.replay.new_frame() = replay_frame;
+
+            if let Err(e) = result {
+                self.err_string = Some(format!("Game crashed: {}\n\nPlease load a savestate.", e));
+                self.game_running = false;
+                break
+            }
+        }
+
+        for (i, state) in self.keyboard_state.iter_mut().enumerate() {
+            state.reset_to(self.game.input.keyboard_check_direct(i as u8));
+        }
+        for (i, state) in self.mouse_state.iter_mut().enumerate() {
+            state.reset_to(self.game.input.mouse_check_button(i as i8 + 1));
+        }
+        self.apply_macros();
+        self.frame_text = format!("Frame: {}", self.replay.frame_count());
+        if self.new_rand.is_none() {
+            self.seed_text = format!("Seed: {}", self.game.rand.seed());
+        }
+        self.context_menu = None;
+        self.redo_instance_reports();
+        self.update_watches();
+        self.redo_search_rows();
+        self.redo_hitboxes();
+    }
+
     /// Load a state, reload cached UI stuff, and increase re-record count by 1
     fn load_state(&mut self, state: SaveState) {
         let (new_replay, new_renderer_state) = state.load_into(&mut self.game);
         self.replay = new_replay;
         self.game_renderer_state = new_renderer_state;
+        *self.debugger_stop.borrow_mut() = None;
+        self.debug_savestate = None;
+        self.profiler.forget_locations();
 
         for (i, state) in self.keyboard_state.iter_mut().enumerate() {
             *state = if self.game.input.keyboard_check_direct(i as u8) { KeyState::Held } else { KeyState::Neutral };
@@ -1756,18 +3137,26 @@ impl UIState<'_> {
         for (i, state) in self.mouse_state.iter_mut().enumerate() {
             *state = if self.game.input.mouse_check_button(i as i8 + 1) { KeyState::Held } else { KeyState::Neutral };
         }
+        self.apply_macros();
 
         self.frame_text = format!("Frame: {}", self.replay.frame_count());
         self.seed_text = format!("Seed: {}", self.game.rand.seed());
         self.context_menu = None;
         self.new_rand = None;
         self.new_mouse_pos = None;
+        self.new_text.clear();
         self.game_running = true;
         self.redo_instance_reports();
+        for watch in self.watches.iter_mut() {
+            watch.compile(self.game);
+        }
+        self.redo_search_rows();
+        self.redo_hitboxes();
         self.config.rerecords += 1;
         self.rerecord_text = format!("Re-record count: {}", self.config.rerecords);
-        self.err_string = File::create(&self.config_path)
-            .map(|f| bincode::serialize_into(f, &self.config))
+        self.err_string = self
+            .config
+            .save(&self.config_path)
             .err()
             .map(|e| format!("Config file was not saved to disk because of an error: {}", e));
     }
@@ -1804,6 +3193,7 @@ impl UIState<'_> {
                     *state = if state.is_held() { KeyState::HeldWillDouble } else { KeyState::NeutralWillDouble };
                 }
             },
+            InputMode::Direct if frame.io().want_text_input => (),
             InputMode::Direct => {
                 if frame.key_pressed(vk) {
                     *state = match state {
@@ -1884,15 +3274,83 @@ impl UIState<'_> {
             self.config.watched_ids.iter().map(|id| (*id, InstanceReport::new(&self.game, *id))).collect();
     }
 
+    /// Adds any warnings the game raised since last time, such as unimplemented functions being called, to the popup
+    fn show_warnings(&mut self) {
+        let warnings = self.game.unimplemented_policy.take_warnings();
+        if !warnings.is_empty() {
+            let warnings = warnings.iter().map(|w| format!("Warning: {}", w)).collect::<Vec<_>>().join("\n");
+            self.err_string = Some(match self.err_string.take() {
+                Some(err) => format!("{}\n\n{}", err, warnings),
+                None => warnings,
+            });
+        }
+    }
+
     /// Tries to save the config file, showing the user an error popup if it fails for some reason
     fn save_config(&mut self) {
-        self.err_string = File::create(&self.config_path)
-            .map(|f| bincode::serialize_into(f, &self.config))
+        self.err_string = self
+            .config
+            .save(&self.config_path)
             .err()
             .map(|e| format!("Config file was not saved to disk because of an error: {}", e));
     }
 }
 
+/// Runs one frame of the game, rendering it into the game's view and then putting the renderer back for the UI.
+fn run_game_frame(
+    game: &mut Game,
+    game_renderer_state: &mut RendererState,
+    ui_renderer_state: &RendererState,
+    ui_width: u16,
+    ui_height: u16,
+) -> Result<(), Box<dyn Error>> {
+    let (w, h) = game.renderer.stored_size();
+    game.renderer.set_state(game_renderer_state);
+    game.renderer.resize_framebuffer(w, h, false);
+    game.renderer.set_view(
+        0,
+        0,
+        game.unscaled_width as _,
+        game.unscaled_height as _,
+        0.0,
+        0,
+        0,
+        game.unscaled_width as _,
+        game.unscaled_height as _,
+    );
+    game.renderer.draw_stored(0, 0, w, h);
+    let result = match game.frame() {
+        Ok(()) => match game.scene_change {
+            Some(SceneChange::Room(id)) => game.load_room(id),
+            Some(SceneChange::Restart) => game.restart(),
+            Some(SceneChange::End) => game.restart(),
+            Some(SceneChange::Load(ref mut path)) => {
+                let path = std::mem::take(path);
+                game.load_gm_save(path)
+            },
+            None => Ok(()),
+        },
+        Err(e) => Err(e.into()),
+    };
+
+    // Fake frame limiter stuff (don't actually frame-limit in record mode)
+    if let GameClock::SpoofedNanos(t) = &mut game.clock {
+        *t += 1_000_000_000 / game.room.speed as u128;
+    }
+    if game.frame_counter == game.room.speed {
+        game.fps = game.room.speed;
+        game.frame_counter = 0;
+    }
+    game.frame_counter += 1;
+
+    game.renderer.resize_framebuffer(ui_width.into(), ui_height.into(), true);
+    game.renderer.set_view(0, 0, ui_width.into(), ui_height.into(), 0.0, 0, 0, ui_width.into(), ui_height.into());
+    game.renderer.clear_view(CLEAR_COLOUR, 1.0);
+    *game_renderer_state = game.renderer.state();
+    game.renderer.set_state(ui_renderer_state);
+    result
+}
+
 /// A full "report" on the state of an instance.
 /// Mostly consiste of pre-allocated strings, because that's far more ideal than allocating
 /// loads of strings every frame for things that relatively rarely change.
@@ -2078,3 +3536,68 @@ impl GameViewData {
         Self { renderer: std::ptr::null_mut(), x: 0, y: 0, w: 0, h: 0 }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// project.cfg as the recorder wrote it before it was JSON: 1280x720 and not maximised, 57 re-records,
+    /// watching instances 100001 and 100002, the full keyboard, direct input and the fourth quicksave slot
+    const BINCODE_CONFIG: &[u8] = &[
+        0x00, 0x05, 0xD0, 0x02, 0x00, // ui_width, ui_height, ui_maximised
+        0x39, 0, 0, 0, 0, 0, 0, 0, // rerecords
+        0x02, 0, 0, 0, 0, 0, 0, 0, 0xA1, 0x86, 0x01, 0x00, 0xA2, 0x86, 0x01, 0x00, // watched_ids
+        0x01, 0x01, 0, 0, 0, // full_keyboard, input_mode
+        0x03, 0, 0, 0, 0, 0, 0, 0, // quicksave_slot
+    ];
+
+    #[test]
+    fn bincode_config() {
+        let config = ProjectConfig::parse(BINCODE_CONFIG).unwrap();
+        assert_eq!((config.ui_width, config.ui_height, config.ui_maximised), (1280, 720, false));
+        assert_eq!(config.rerecords, 57);
+        assert_eq!(config.watched_ids, [100001, 100002]);
+        assert!(config.full_keyboard);
+        assert!(matches!(config.input_mode, InputMode::Direct));
+        assert_eq!(config.quicksave_slot, 3);
+        assert!(config.unimplemented_policy.is_empty());
+
+        // Saving it again writes JSON, which reads back the same
+        let config = ProjectConfig::parse(&serde_json::to_vec(&config).unwrap()).unwrap();
+        assert_eq!(config.rerecords, 57);
+        assert_eq!(config.watched_ids, [100001, 100002]);
+        assert!(matches!(config.input_mode, InputMode::Direct));
+    }
+
+    #[test]
+    fn missing_fields() {
+        let config = ProjectConfig::parse(br#"{"rerecords": 12, "input_mode": "Direct"}"#).unwrap();
+        assert_eq!(config.rerecords, 12);
+        assert!(matches!(config.input_mode, InputMode::Direct));
+        assert_eq!((config.ui_width, config.ui_height), (DEFAULT_CONFIG.ui_width, DEFAULT_CONFIG.ui_height));
+        assert!(config.watched_ids.is_empty());
+        assert!(ProjectConfig::parse(b"not a config").is_err());
+    }
+
+    #[test]
+    fn missing_hitbox_fields() {
+        let config = ProjectConfig::parse(br#"{"hitboxes": {"enabled": true, "mask": false}}"#).unwrap();
+        assert!(config.hitboxes.enabled);
+        assert!(!config.hitboxes.mask);
+        assert_eq!(config.hitboxes.bbox, DEFAULT_CONFIG.hitboxes.bbox);
+        assert_eq!(config.hitboxes.velocity, DEFAULT_CONFIG.hitboxes.velocity);
+        assert!(config.hitboxes.hidden_objects.is_empty());
+
+        let config = ProjectConfig::parse(BINCODE_CONFIG).unwrap();
+        assert!(!config.hitboxes.enabled);
+        assert_eq!(config.hitboxes.origin, DEFAULT_CONFIG.hitboxes.origin);
+    }
+
+    #[test]
+    fn missing_branch() {
+        assert!(ProjectConfig::parse(BINCODE_CONFIG).unwrap().branch.is_none());
+        assert!(ProjectConfig::parse(br#"{"rerecords": 3}"#).unwrap().branch.is_none());
+        let config = ProjectConfig::parse(br#"{"branch": "alt"}"#).unwrap();
+        assert_eq!(config.branch.as_deref(), Some("alt"));
+    }
+}
@@ -45,10 +45,14 @@ pub enum Event {
     ShowMenu(Value),     // value returned from show_menu()
     ShowMessage,         // acknowledges that a show_message() does not need to be shown during replay
     ShowQuestion(Value), // value returned from show_question()
+
+    // GML typed into the console while recording, which runs after the frame (or after startup) it's stored in.
+    // Any events it stores itself come straight after it.
+    Console { code: String, instance: Option<i32> },
 }
 
 // An input event which takes place during a frame
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Input {
     KeyPress(u8),
     KeyRelease(u8),
@@ -56,6 +60,7 @@ pub enum Input {
     MouseRelease(i8),
     MouseWheelUp,
     MouseWheelDown,
+    Char(u8), // typed character, already translated by the keyboard layout
 }
 
 #[derive(Debug)]
@@ -159,6 +164,14 @@ impl Replay {
         self.frames.get(index)
     }
 
+    // Gets the stored events of the last frame, or the startup events if there aren't any frames yet
+    pub fn last_events_mut(&mut self) -> &mut Vec<Event> {
+        match self.frames.last_mut() {
+            Some(frame) => &mut frame.events,
+            None => &mut self.startup_events,
+        }
+    }
+
     // Gets the replay's frame count
     pub fn frame_count(&self) -> usize {
         self.frames.len()
@@ -1,8 +1,8 @@
 use crate::{
     game::{
         audio::AudioState, draw, external, includedfile::IncludedFile, model::Model, particle,
-        pathfinding::PotentialStepSettings, surface::Surface, transition::UserTransition, Assets,
-        Game, GameClock, Replay, RoomState, Version,
+        pathfinding::PotentialStepSettings, platform::VirtualDisplay, surface::Surface, transition::UserTransition,
+        Assets, Game, GameClock, Replay, RoomState, Version,
     },
     gml::{self, ds, rand::Random, Compiler},
     handleman::HandleList,
@@ -15,9 +15,9 @@ use crate::{
 use byteorder::{ReadBytesExt, WriteBytesExt, LE};
 use indexmap::IndexMap;
 use lzzzz::lz4;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     collections::{HashMap, HashSet},
     fs::{File, OpenOptions},
     io::{self, Read, Write},
@@ -25,6 +25,37 @@ use std::{
     rc::Rc,
 };
 
+/// Written at the start of savestate files, followed by the version. Savestates from before there were versions
+/// start with their length instead, which can't ever be this.
+const MAGIC: &[u8; 8] = b"GM8SAVE\0";
+
+/// Which layout savestates are written in. This goes up by one whenever anything in SaveState changes, and fields
+/// added since an older version get their defaults when loading savestates from it. Version 0 is the layout from
+/// before there were versions.
+pub const VERSION: u32 = 1;
+
+thread_local! {
+    /// The version of the savestate currently being loaded, for the since_v* functions to check.
+    static LOADING_VERSION: Cell<u32> = Cell::new(VERSION);
+}
+
+/// Deserializes a field which was added in version 1, leaving it as the default in older savestates.
+pub fn since_v1<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    if LOADING_VERSION.with(Cell::get) >= 1 { T::deserialize(deserializer) } else { Ok(T::default()) }
+}
+
+/// Runs a function with the since_v* functions going by the given savestate version.
+fn loading_version<T>(version: u32, f: impl FnOnce() -> T) -> T {
+    let previous = LOADING_VERSION.with(|v| v.replace(version));
+    let result = f();
+    LOADING_VERSION.with(|v| v.set(previous));
+    result
+}
+
 /// Represents a savestate. Very similar to the Game struct, but without things which aren't serialized.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SaveState {
@@ -107,6 +138,10 @@ pub struct SaveState {
     pub gm_version: Version,
     pub clock: GameClock,
 
+    #[serde(deserialize_with = "since_v1")]
+    display: VirtualDisplay,
+    #[serde(deserialize_with = "since_v1")]
+    window_offset_spoof: (i32, i32),
     scaling: Scaling,
     unscaled_width: u32,
     unscaled_height: u32,
@@ -193,6 +228,8 @@ impl SaveState {
             included_files: game.included_files.clone(),
             gm_version: game.gm_version.clone(),
             clock: game.clock.clone(),
+            display: game.display,
+            window_offset_spoof: game.window_offset_spoof,
             scaling: game.scaling,
             unscaled_width: game.unscaled_width,
             unscaled_height: game.unscaled_height,
@@ -221,7 +258,9 @@ impl SaveState {
 
         game.surface_fix = self.surface_fix;
 
-        game.compiler = self.compiler;
+        let mut compiler = self.compiler;
+        compiler.copy_settings(&game.compiler);
+        game.compiler = compiler;
         game.rand = self.rand;
         game.input = self.input;
         game.assets = self.assets;
@@ -283,49 +322,53 @@ impl SaveState {
         game.gm_version = self.gm_version;
         game.clock = self.clock;
         game.audio.set_state(self.audio_state);
+        game.display = self.display;
+        game.window_offset_spoof = self.window_offset_spoof;
         game.scaling = self.scaling;
         game.unscaled_width = self.unscaled_width;
         game.unscaled_height = self.unscaled_height;
         (self.replay, self.renderer_state)
     }
 
+    /// Gets the Replay this SaveState was made with.
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
     /// Consumes this SaveState and returns just its Replay component.
     pub fn into_replay(self) -> Replay {
         self.replay
     }
 
-    /// Loads a SaveState from a file. The format will always match the one used by `save_to_file()`.
+    /// Loads a SaveState from a file. The format will always match the one used by `save_to_file()`,
+    /// from this version or any older one.
     pub fn from_file(path: &PathBuf, buffer: &mut Buffer) -> Result<Self, ReadError> {
-        match File::open(path).map(|f| (f.metadata().map(|m| m.len() as usize + 1).unwrap_or(0), f)) {
-            Ok((init_size, mut file)) => {
-                buffer.lz4_buf.clear();
-                buffer.lz4_buf.reserve(init_size);
-                match file.read_to_end(&mut buffer.lz4_buf) {
-                    Ok(_) => {
-                        match (buffer.lz4_buf.as_slice().read_u64::<LE>().map(|x| x as usize), buffer.lz4_buf.get(8..))
-                        {
-                            (Ok(len), Some(block)) => {
-                                buffer.bin_buf.clear();
-                                buffer.bin_buf.reserve(len);
-                                unsafe { buffer.bin_buf.set_len(len) };
-                                match lz4::decompress(block, buffer.bin_buf.as_mut_slice()) {
-                                    Ok(len) => {
-                                        unsafe { buffer.bin_buf.set_len(len) };
-                                        bincode::deserialize::<'_, SaveState>(buffer.bin_buf.as_slice())
-                                            .map_err(ReadError::DeserializeErr)
-                                    },
-                                    Err(err) => Err(ReadError::DecompressErr(err)),
-                                }
-                            },
-                            (Ok(_), None) => Err(ReadError::IOErr(io::Error::from(io::ErrorKind::UnexpectedEof))),
-                            (Err(err), _) => Err(ReadError::IOErr(err)),
-                        }
-                    },
-                    Err(err) => Err(ReadError::IOErr(err)),
-                }
+        let mut file = File::open(path).map_err(ReadError::IOErr)?;
+        buffer.lz4_buf.clear();
+        buffer.lz4_buf.reserve(file.metadata().map(|m| m.len() as usize + 1).unwrap_or(0));
+        file.read_to_end(&mut buffer.lz4_buf).map_err(ReadError::IOErr)?;
+
+        let mut data = buffer.lz4_buf.as_slice();
+        let version = match data.strip_prefix(MAGIC) {
+            Some(mut rest) => {
+                let version = rest.read_u32::<LE>().map_err(ReadError::IOErr)?;
+                data = rest;
+                version
             },
-            Err(err) => Err(ReadError::IOErr(err)),
+            None => 0,
+        };
+        if version > VERSION {
+            return Err(ReadError::NewerVersion(version))
         }
+
+        let len = data.read_u64::<LE>().map_err(ReadError::IOErr)? as usize;
+        buffer.bin_buf.clear();
+        buffer.bin_buf.reserve(len);
+        unsafe { buffer.bin_buf.set_len(len) };
+        let len = lz4::decompress(data, buffer.bin_buf.as_mut_slice()).map_err(ReadError::DecompressErr)?;
+        unsafe { buffer.bin_buf.set_len(len) };
+        loading_version(version, || bincode::deserialize::<SaveState>(&buffer.bin_buf))
+            .map_err(ReadError::DeserializeErr)
     }
 
     /// Saves a SaveState to a file. The SaveState object is formatted with Serde/bincode and compressed with lz4.
@@ -338,7 +381,9 @@ impl SaveState {
                 match lz4::compress_to_vec(buffer.bin_buf.as_slice(), buffer.lz4_buf.as_mut(), lz4::ACC_LEVEL_DEFAULT) {
                     Ok(_length) => {
                         match OpenOptions::new().create(true).write(true).truncate(true).open(path).and_then(|mut f| {
-                            f.write_u64::<LE>(buffer.bin_buf.len() as u64)
+                            f.write_all(MAGIC)
+                                .and_then(|_| f.write_u32::<LE>(VERSION))
+                                .and_then(|_| f.write_u64::<LE>(buffer.bin_buf.len() as u64))
                                 .and_then(|_| f.write_all(buffer.lz4_buf.as_slice()))
                         }) {
                             Ok(()) => Ok(()),
@@ -369,6 +414,7 @@ pub enum ReadError {
     IOErr(io::Error),
     DecompressErr(lzzzz::Error),
     DeserializeErr(Box<bincode::ErrorKind>),
+    NewerVersion(u32),
 }
 
 #[derive(Debug)]
@@ -377,3 +423,40 @@ pub enum WriteError {
     CompressErr(lzzzz::Error),
     SerializeErr(Box<bincode::ErrorKind>),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Before {
+        a: u32,
+        c: u8,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct After {
+        a: u32,
+        #[serde(deserialize_with = "since_v1")]
+        b: Vec<u8>,
+        c: u8,
+    }
+
+    #[test]
+    fn older_versions() {
+        let before = bincode::serialize(&Before { a: 7, c: 9 }).unwrap();
+        let after: After = loading_version(0, || bincode::deserialize(&before)).unwrap();
+        assert_eq!((after.a, after.b.len(), after.c), (7, 0, 9));
+
+        let current = bincode::serialize(&After { a: 7, b: vec![1, 2], c: 9 }).unwrap();
+        let after: After = bincode::deserialize(&current).unwrap();
+        assert_eq!((after.a, after.b, after.c), (7, vec![1, 2], 9));
+        assert_eq!(LOADING_VERSION.with(Cell::get), VERSION);
+    }
+
+    #[test]
+    fn magic_isnt_a_length() {
+        // Old savestates start with their uncompressed length, which would have to be over 10 petabytes to match
+        assert!(u64::from_le_bytes(*MAGIC) > 10u64.pow(16));
+    }
+}
@@ -0,0 +1,402 @@
+//! RAM-watch style searching through the game's variables.
+//!
+//! A search starts with everything which holds a value: instance variables, globals, and the contents of data
+//! structures. Each time it gets filtered, whatever doesn't match is thrown out, and what's left gets compared
+//! against its new value the next time.
+
+use crate::{
+    game::Game,
+    gml::{ds, Value},
+    instance::{Field, Instance},
+    types::ID,
+};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+/// Built-in instance variables worth searching through. Alarms and fields are handled separately.
+const VARIABLES: [(&str, fn(&Instance) -> Value); 25] = [
+    ("x", |inst| inst.x.get().into()),
+    ("y", |inst| inst.y.get().into()),
+    ("xprevious", |inst| inst.xprevious.get().into()),
+    ("yprevious", |inst| inst.yprevious.get().into()),
+    ("xstart", |inst| inst.xstart.get().into()),
+    ("ystart", |inst| inst.ystart.get().into()),
+    ("hspeed", |inst| inst.hspeed.get().into()),
+    ("vspeed", |inst| inst.vspeed.get().into()),
+    ("speed", |inst| inst.speed.get().into()),
+    ("direction", |inst| inst.direction.get().into()),
+    ("gravity", |inst| inst.gravity.get().into()),
+    ("gravity_direction", |inst| inst.gravity_direction.get().into()),
+    ("friction", |inst| inst.friction.get().into()),
+    ("solid", |inst| inst.solid.get().into()),
+    ("visible", |inst| inst.visible.get().into()),
+    ("depth", |inst| inst.depth.get().into()),
+    ("sprite_index", |inst| inst.sprite_index.get().into()),
+    ("image_index", |inst| inst.image_index.get().into()),
+    ("image_speed", |inst| inst.image_speed.get().into()),
+    ("image_xscale", |inst| inst.image_xscale.get().into()),
+    ("image_yscale", |inst| inst.image_yscale.get().into()),
+    ("image_angle", |inst| inst.image_angle.get().into()),
+    ("path_position", |inst| inst.path_position.get().into()),
+    ("path_speed", |inst| inst.path_speed.get().into()),
+    ("timeline_position", |inst| inst.timeline_position.get().into()),
+];
+
+/// Somewhere a value is kept.
+#[derive(Clone, Debug)]
+pub enum Address {
+    /// One of the built-in variables listed in VARIABLES
+    Variable {
+        instance: ID,
+        variable: usize,
+    },
+    Alarm {
+        instance: ID,
+        alarm: u32,
+    },
+    Field {
+        instance: ID,
+        field: usize,
+        index: Option<u32>,
+    },
+    Global {
+        field: usize,
+        index: Option<u32>,
+    },
+    List {
+        id: i32,
+        index: usize,
+    },
+
+    Map {
+        id: i32,
+        key: Value,
+    },
+    Grid {
+        id: i32,
+        x: usize,
+        y: usize,
+    },
+    Stack {
+        id: i32,
+        index: usize,
+    },
+    Queue {
+        id: i32,
+        index: usize,
+    },
+}
+
+/// How to narrow down a search.
+pub enum Filter {
+    Equal(Value),
+    Changed,
+    Unchanged,
+    Increased,
+    Decreased,
+}
+
+pub struct Search {
+    /// Everything still matching, along with its value when the search was last filtered
+    candidates: Vec<(Address, Value)>,
+}
+
+impl Search {
+    /// Starts a new search with everything in the game.
+    pub fn new(game: &Game) -> Self {
+        Self { candidates: snapshot(game) }
+    }
+
+    /// Throws out everything which doesn't match a filter, or doesn't exist any more.
+    pub fn filter(&mut self, game: &Game, filter: &Filter) {
+        self.filter_with(filter, |address| address.read(game))
+    }
+
+    fn filter_with(&mut self, filter: &Filter, read: impl Fn(&Address) -> Option<Value>) {
+        self.candidates = self
+            .candidates
+            .drain(..)
+            .filter_map(|(address, old)| {
+                let new = read(&address)?;
+                if filter.matches(&old, &new) { Some((address, new)) } else { None }
+            })
+            .collect();
+    }
+
+    /// Everything still matching, along with its value when the search was last filtered.
+    pub fn candidates(&self) -> &[(Address, Value)] {
+        &self.candidates
+    }
+}
+
+impl Filter {
+    fn matches(&self, old: &Value, new: &Value) -> bool {
+        match self {
+            Self::Equal(value) => new.almost_equals(value),
+            Self::Changed => !new.almost_equals(old),
+            Self::Unchanged => new.almost_equals(old),
+            Self::Increased => matches!((old, new), (Value::Real(old), Value::Real(new)) if new > old),
+            Self::Decreased => matches!((old, new), (Value::Real(old), Value::Real(new)) if new < old),
+        }
+    }
+}
+
+impl Address {
+    /// Gets the value kept here, if it still exists.
+    pub fn read(&self, game: &Game) -> Option<Value> {
+        match self {
+            Self::Variable { instance, variable } => Some((VARIABLES[*variable].1)(instance_by_id(game, *instance)?)),
+            Self::Alarm { instance, alarm } => {
+                instance_by_id(game, *instance)?.alarms.borrow().get(alarm).map(|&time| time.into())
+            },
+            Self::Field { instance, field, index } => {
+                read_field(instance_by_id(game, *instance)?.fields.borrow().get(field)?, *index)
+            },
+            Self::Global { field, index } => read_field(game.globals.fields.get(field)?, *index),
+            Self::List { id, index } => game.lists.get(*id)?.get(*index).cloned(),
+            Self::Map { id, key } => {
+                let map = game.maps.get(*id)?;
+                map.keys.iter().position(|k| k.almost_equals(key)).map(|i| map.values[i].clone())
+            },
+            Self::Grid { id, x, y } => game.grids.get(*id)?.get(*x as i32, *y as i32).cloned(),
+            Self::Stack { id, index } => game.stacks.get(*id)?.get(*index).cloned(),
+            Self::Queue { id, index } => game.queues.get(*id)?.get(*index).cloned(),
+        }
+    }
+
+    /// A GML expression for the value kept here, if there is one, for pinning it as a watch.
+    pub fn expression(&self, game: &Game) -> Option<String> {
+        let field_name = |field| game.compiler.get_field_name(field).unwrap_or_else(|| "<???>".into());
+        match self {
+            Self::Variable { instance, variable } => Some(format!("({}).{}", instance, VARIABLES[*variable].0)),
+            Self::Alarm { instance, alarm } => Some(format!("({}).alarm[{}]", instance, alarm)),
+            Self::Field { instance, field, index } => {
+                Some(format!("({}).{}{}", instance, field_name(*field), array_index(*index)))
+            },
+            Self::Global { field, index } => Some(format!("global.{}{}", field_name(*field), array_index(*index))),
+            Self::List { id, index } => Some(format!("ds_list_find_value({}, {})", id, index)),
+            Self::Map { id, key } => Some(format!("ds_map_find_value({}, {})", id, literal(key))),
+            Self::Grid { id, x, y } => Some(format!("ds_grid_get({}, {}, {})", id, x, y)),
+            Self::Stack { .. } | Self::Queue { .. } => None,
+        }
+    }
+
+    /// Describes where this is for showing to the user.
+    pub fn describe(&self, game: &Game) -> String {
+        match self {
+            Self::Stack { id, index } => format!("ds_stack {} [{}]", id, index),
+            Self::Queue { id, index } => format!("ds_queue {} [{}]", id, index),
+            _ => self.expression(game).unwrap_or_default(),
+        }
+    }
+}
+
+/// A hash of everything a search looks through, along with the room and random seed, for checking whether two runs
+/// of a game are in the same state. It doesn't depend on the order things are stored in.
+pub fn fingerprint(game: &Game) -> u64 {
+    let mut entries =
+        snapshot(game).iter().map(|(address, value)| format!("{:?} = {:?}", address, value)).collect::<Vec<_>>();
+    entries.sort_unstable();
+    let mut hasher = DefaultHasher::new();
+    entries.hash(&mut hasher);
+    game.room.id.hash(&mut hasher);
+    game.rand.seed().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Finds everything in the game which holds a value, along with what it holds right now.
+fn snapshot(game: &Game) -> Vec<(Address, Value)> {
+    let mut values = Vec::new();
+
+    let list = &game.room.instance_list;
+    let mut active = list.iter_by_drawing();
+    let mut inactive = list.iter_inactive();
+    while let Some(instance) = active.next(list).or_else(|| inactive.next(list)).map(|x| list.get(x)) {
+        let id = instance.id.get();
+        for (variable, (_, read)) in VARIABLES.iter().enumerate() {
+            values.push((Address::Variable { instance: id, variable }, read(instance)));
+        }
+        for (&alarm, &time) in instance.alarms.borrow().iter() {
+            values.push((Address::Alarm { instance: id, alarm }, time.into()));
+        }
+        for (&field, value) in instance.fields.borrow().iter() {
+            snapshot_field(&mut values, field, value, |field, index| Address::Field { instance: id, field, index });
+        }
+    }
+    for (&field, value) in game.globals.fields.iter() {
+        snapshot_field(&mut values, field, value, |field, index| Address::Global { field, index });
+    }
+
+    for (id, list) in game.lists.iter() {
+        values.extend(list.iter().enumerate().map(|(index, value)| (Address::List { id, index }, value.clone())));
+    }
+    for (id, map) in game.maps.iter() {
+        values.extend(
+            map.keys
+                .iter()
+                .zip(map.values.iter())
+                .map(|(key, value)| (Address::Map { id, key: key.clone() }, value.clone())),
+        );
+    }
+    for (id, grid) in game.grids.iter() {
+        snapshot_grid(&mut values, id, grid);
+    }
+    for (id, stack) in game.stacks.iter() {
+        values.extend(stack.iter().enumerate().map(|(index, value)| (Address::Stack { id, index }, value.clone())));
+    }
+    for (id, queue) in game.queues.iter() {
+        values.extend(queue.iter().enumerate().map(|(index, value)| (Address::Queue { id, index }, value.clone())));
+    }
+    values
+}
+
+fn snapshot_field(
+    values: &mut Vec<(Address, Value)>,
+    field: usize,
+    value: &Field,
+    address: impl Fn(usize, Option<u32>) -> Address,
+) {
+    match value {
+        Field::Single(value) => values.push((address(field, None), value.clone())),
+        Field::Array(array) => {
+            values.extend(array.iter().map(|(&index, value)| (address(field, Some(index)), value.clone())))
+        },
+    }
+}
+
+fn snapshot_grid(values: &mut Vec<(Address, Value)>, id: i32, grid: &ds::Grid) {
+    for x in 0..grid.width() {
+        for y in 0..grid.height() {
+            if let Some(value) = grid.get(x as i32, y as i32) {
+                values.push((Address::Grid { id, x, y }, value.clone()));
+            }
+        }
+    }
+}
+
+fn read_field(field: &Field, index: Option<u32>) -> Option<Value> {
+    match (field, index) {
+        (Field::Single(value), None) => Some(value.clone()),
+        (Field::Array(array), Some(index)) => array.get(&index).cloned(),
+        _ => None,
+    }
+}
+
+fn instance_by_id(game: &Game, id: ID) -> Option<&Instance> {
+    game.room.instance_list.get_by_instid(id).map(|handle| game.room.instance_list.get(handle))
+}
+
+/// Writes a value as a GML literal. GML strings can't escape anything, so quotes, control characters and bytes
+/// outside of ASCII are joined on with chr() instead.
+fn literal(value: &Value) -> String {
+    match value {
+        Value::Real(real) => real.to_string(),
+        Value::Str(string) => {
+            let mut parts = Vec::new();
+            let mut plain = String::new();
+            for &byte in string.as_ref() {
+                if (b' '..=b'~').contains(&byte) && byte != b'"' {
+                    plain.push(char::from(byte));
+                } else {
+                    if !plain.is_empty() {
+                        parts.push(format!("\"{}\"", plain));
+                        plain.clear();
+                    }
+                    parts.push(format!("chr({})", byte));
+                }
+            }
+            if !plain.is_empty() || parts.is_empty() {
+                parts.push(format!("\"{}\"", plain));
+            }
+            parts.join(" + ")
+        },
+    }
+}
+
+/// Writes an array index the way GML does, with two-dimensional arrays taking two indices.
+fn array_index(index: Option<u32>) -> String {
+    match index {
+        None => String::new(),
+        Some(index) if index >= 32000 => format!("[{}, {}]", index / 32000, index % 32000),
+        Some(index) => format!("[{}]", index),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gml::Compiler;
+
+    fn list(index: usize) -> Address {
+        Address::List { id: 0, index }
+    }
+
+    #[test]
+    fn filters() {
+        let (one, two, text) = (Value::from(1.0), Value::from(2.0), Value::from("1"));
+        assert!(Filter::Equal(one.clone()).matches(&two, &one));
+        assert!(!Filter::Equal(one.clone()).matches(&one, &text));
+        assert!(Filter::Changed.matches(&one, &two));
+        assert!(Filter::Changed.matches(&one, &text));
+        assert!(!Filter::Changed.matches(&text, &Value::from("1")));
+        assert!(Filter::Unchanged.matches(&two, &two));
+        assert!(!Filter::Unchanged.matches(&one, &two));
+        assert!(Filter::Increased.matches(&one, &two));
+        assert!(!Filter::Increased.matches(&two, &one));
+        assert!(Filter::Decreased.matches(&two, &one));
+        assert!(!Filter::Decreased.matches(&one, &one));
+
+        // Strings never go up or down
+        assert!(!Filter::Increased.matches(&text, &Value::from("2")));
+        assert!(!Filter::Decreased.matches(&one, &text));
+    }
+
+    #[test]
+    fn filtering() {
+        let mut search = Search { candidates: (0..4).map(|i| (list(i), Value::from(i))).collect() };
+        let values = |values: Vec<Option<f64>>| {
+            move |address: &Address| match address {
+                Address::List { index, .. } => values[*index].map(Value::from),
+                _ => None,
+            }
+        };
+        let indices = |search: &Search| {
+            search
+                .candidates()
+                .iter()
+                .map(|(address, value)| match address {
+                    Address::List { index, .. } => (*index, f64::from(value.clone())),
+                    _ => unreachable!(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Anything which stops existing is dropped, and what's left is compared against its latest value
+        search.filter_with(&Filter::Changed, values(vec![Some(5.0), Some(1.0), Some(0.0), None]));
+        assert_eq!(indices(&search), [(0, 5.0), (2, 0.0)]);
+        search.filter_with(&Filter::Decreased, values(vec![Some(4.0), None, Some(1.0), None]));
+        assert_eq!(indices(&search), [(0, 4.0)]);
+        search.filter_with(&Filter::Unchanged, values(vec![Some(4.0), None, None, None]));
+        assert_eq!(indices(&search), [(0, 4.0)]);
+        search.filter_with(&Filter::Equal(Value::from(3.0)), values(vec![Some(4.0), None, None, None]));
+        assert!(search.candidates().is_empty());
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(literal(&Value::from(1.5)), "1.5");
+        assert_eq!(literal(&Value::from(-3.0)), "-3");
+        assert_eq!(literal(&Value::from("")), "\"\"");
+        assert_eq!(literal(&Value::from("key")), "\"key\"");
+        assert_eq!(literal(&Value::from("say \"hi\"\n")), "\"say \" + chr(34) + \"hi\" + chr(34) + chr(10)");
+        assert_eq!(literal(&Value::from(&b"\x82\xa0'"[..])), "chr(130) + chr(160) + \"'\"");
+
+        let mut compiler = Compiler::new();
+        for key in [Value::from(0.1), Value::from("it's \"quoted\""), Value::from(&b"\x01\xff"[..])] {
+            let source = format!("ds_map_find_value(0, {})", literal(&key));
+            assert!(compiler.compile_expression(source.as_bytes()).is_ok(), "{}", source);
+        }
+    }
+}
@@ -254,7 +254,7 @@ impl Game {
                             .room
                             .instance_list
                             .insert_dummy(Instance::new_dummy(game.assets.objects.get_asset(0).map(|x| x.as_ref())));
-                        game.execute(&instructions, &mut Context {
+                        game.execute_code(&instructions, &mut Context {
                             this: dummy_instance,
                             other: dummy_instance,
                             arguments: [
@@ -78,4 +78,30 @@ impl View {
         );
         (Real::from(x).round().to_i32(), Real::from(y).round().to_i32())
     }
+
+    /// Transforms a point relative to this view in room-space to a point on screen
+    pub fn untransform_point(&self, x: i32, y: i32) -> (i32, i32) {
+        let (x, y) = self.untransform_point_exact(x.into(), y.into());
+        (Real::from(x).round().to_i32(), Real::from(y).round().to_i32())
+    }
+
+    /// Same as untransform_point, but without rounding to whole pixels
+    pub fn untransform_point_exact(&self, mut x: f64, mut y: f64) -> (f64, f64) {
+        let src_x = f64::from(self.source_x);
+        let src_y = f64::from(self.source_y);
+        let src_w = f64::from(self.source_w);
+        let src_h = f64::from(self.source_h);
+        let angle = (-self.angle).to_radians();
+        util::rotate_around(
+            &mut x,
+            &mut y,
+            src_x + (src_w / 2.0),
+            src_y + (src_h / 2.0),
+            angle.sin().into(),
+            angle.cos().into(),
+        );
+        let x = f64::from(self.port_x) + (f64::from(self.port_w) * (x - src_x) / src_w);
+        let y = f64::from(self.port_y) + (f64::from(self.port_h) * (y - src_y) / src_h);
+        (x, y)
+    }
 }
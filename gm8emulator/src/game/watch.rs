@@ -0,0 +1,143 @@
+//! Watch expressions, which are compiled once and then evaluated again whenever the game moves on.
+
+use crate::{
+    game::{Game, GetAsset},
+    gml::{
+        self, mappings,
+        runtime::{ArrayAccessor, InstanceIdentifier, Node},
+        Context, Value,
+    },
+    instance::Instance,
+};
+
+/// An expression to keep an eye on, which can also stop the recorder advancing when it becomes true.
+pub struct Watch {
+    pub source: String,
+
+    /// Whether advancing should stop when this becomes true
+    pub pause: bool,
+
+    node: Result<Node, String>,
+
+    /// How many field names the game's compiler knew when this was compiled
+    fields: usize,
+
+    /// What it came to when it was last evaluated, or why it couldn't be
+    pub value: Result<Value, String>,
+}
+
+impl Watch {
+    pub fn new(source: String, pause: bool, game: &mut Game) -> Self {
+        let mut watch = Self { source, pause, node: Err(String::new()), fields: 0, value: Err(String::new()) };
+        watch.compile(game);
+        watch
+    }
+
+    /// Compiles the expression again and evaluates it. This must be done whenever the game's compiler is replaced,
+    /// such as when loading a savestate, since field names might not mean the same thing any more.
+    pub fn compile(&mut self, game: &mut Game) {
+        self.compile_node(game);
+        self.value = Err(String::new());
+        self.update(game);
+    }
+
+    /// Compiles the expression with a copy of the game's compiler, since compiling can add field names to it and
+    /// it's saved in savestates. Anything which might change the game is refused, so watching can't affect it.
+    fn compile_node(&mut self, game: &Game) {
+        let mut compiler = game.compiler.clone();
+        self.node = match compiler.compile_expression(self.source.as_bytes()) {
+            Ok(node) => match side_effect(&node) {
+                Some(what) => Err(format!("can't watch {}, since it might change the game", what)),
+                None => Ok(node),
+            },
+            Err(e) => Err(e.to_string()),
+        };
+        self.fields = game.compiler.field_count();
+    }
+
+    /// Evaluates the expression again, giving back whether it's just become true.
+    pub fn update(&mut self, game: &mut Game) -> bool {
+        // A field the game didn't know about before might have been given the same number as one in here
+        if self.fields != game.compiler.field_count() {
+            self.compile_node(game);
+        }
+        let was_true = matches!(&self.value, Ok(value) if value.is_truthy());
+        self.value = match &self.node {
+            Ok(node) => game.peek(node).map_err(|e| e.to_string()),
+            Err(e) => Err(e.clone()),
+        };
+        !was_true && matches!(&self.value, Ok(value) if value.is_truthy())
+    }
+
+    /// Describes the expression along with its value.
+    pub fn describe(&self) -> String {
+        match &self.value {
+            Ok(value) => format!("{} = {}", self.source, value),
+            Err(e) => format!("{}: {}", self.source, e),
+        }
+    }
+}
+
+/// Finds the first thing in an expression which might change the game. Only kernel functions which don't change
+/// anything are allowed, and scripts and extension functions never are.
+fn side_effect(node: &Node) -> Option<String> {
+    match node {
+        Node::Literal { .. } | Node::Constant { .. } | Node::RuntimeError { .. } => None,
+        Node::Function { args, function_id } => match mappings::FUNCTIONS.index(*function_id) {
+            Some((_, gml::Function::Pure(_) | gml::Function::Constant(_))) => args.iter().find_map(side_effect),
+            Some((name, _)) => Some(format!("{}()", name)),
+            None => Some("an unknown function".into()),
+        },
+        Node::Script { .. } => Some("a script".into()),
+        Node::ExtensionFunction { .. } => Some("an extension function".into()),
+        Node::Field { accessor } => accessor_side_effect(&accessor.owner, &accessor.array),
+        Node::Variable { accessor } => accessor_side_effect(&accessor.owner, &accessor.array),
+        Node::Binary { left, right, .. } => side_effect(left).or_else(|| side_effect(right)),
+        Node::Unary { child, .. } => side_effect(child),
+    }
+}
+
+fn accessor_side_effect(owner: &InstanceIdentifier, array: &ArrayAccessor) -> Option<String> {
+    let owner = match owner {
+        InstanceIdentifier::Expression(node) => side_effect(node),
+        _ => None,
+    };
+    owner.or_else(|| match array {
+        ArrayAccessor::None => None,
+        ArrayAccessor::Single(index) => side_effect(index),
+        ArrayAccessor::Double(index1, index2) => side_effect(index1).or_else(|| side_effect(index2)),
+    })
+}
+
+impl Game {
+    /// Evaluates an expression as a dummy instance. Watches make sure the expression can't change anything first.
+    pub fn peek(&mut self, node: &Node) -> gml::Result<Value> {
+        let dummy_instance = self
+            .room
+            .instance_list
+            .insert_dummy(Instance::new_dummy(self.assets.objects.get_asset(0).map(|x| x.as_ref())));
+        let result = self.eval(node, &mut Context::with_single_instance(dummy_instance));
+        self.room.instance_list.remove_dummy(dummy_instance);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gml::Compiler;
+
+    fn check(source: &str) -> Option<String> {
+        side_effect(&Compiler::new().compile_expression(source.as_bytes()).unwrap())
+    }
+
+    #[test]
+    fn side_effects() {
+        assert_eq!(check("x + abs(y) > point_distance(0, 0, hspeed, vspeed)"), None);
+        assert_eq!(check("global.lives[2, 1] == -1"), None);
+        assert_eq!(check("instance_create(x, y, 0)"), Some("instance_create()".into()));
+        assert_eq!(check("a[floor(random(3))] > 0"), Some("random()".into()));
+        assert_eq!(check("(abs(x)).y"), None);
+        assert_eq!(check("(instance_create(0, 0, 0)).y"), Some("instance_create()".into()));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod bytecode;
 pub mod compiler;
 pub mod context;
 pub mod datetime;
@@ -6,11 +7,14 @@ pub mod file;
 pub mod kernel;
 pub mod mappings;
 pub mod network;
+pub mod optimizer;
 pub mod rand;
 pub mod runtime;
 pub mod string;
+pub mod unimplemented;
 pub mod value;
 
+pub use bytecode::{Code, Interpreter};
 pub use compiler::Compiler;
 pub use context::Context;
 pub use string::String;
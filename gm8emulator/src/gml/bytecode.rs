@@ -0,0 +1,863 @@
+//! A flat bytecode form of compiled GML, and a stack machine which runs it.
+//!
+//! The compiler still produces instruction trees, which are flattened into bytecode afterwards. `Code` keeps both,
+//! since the tree-walking interpreter in `runtime` is still the default. This one is used with `--interpreter
+//! bytecode`, and `--compare-interpreters` replays a recording with both to time them and check they agree.
+
+use crate::{
+    asset,
+    game::Game,
+    gml::{
+        self,
+        runtime::{
+            self, ArrayAccessor, BinaryOperator, FieldAccessor, InstanceIdentifier, Instruction, Node, ReturnType,
+            Target, UnaryOperator,
+        },
+        Context, InstanceVariable, Value,
+    },
+    instance::Field,
+    instancelist::{ILIterDrawOrder, IdentityIter},
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    fmt::{self, Display},
+    ops::Deref,
+    rc::{Rc, Weak},
+    str::FromStr,
+};
+
+/// Which interpreter runs compiled GML.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpreter {
+    /// Walk the instruction tree. This is the default.
+    Tree,
+
+    /// Run the bytecode, which should be faster but hasn't been checked against as many games yet.
+    Bytecode,
+}
+
+impl Display for Interpreter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Tree => "tree",
+            Self::Bytecode => "bytecode",
+        })
+    }
+}
+
+impl FromStr for Interpreter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tree" => Ok(Self::Tree),
+            "bytecode" => Ok(Self::Bytecode),
+            _ => Err(format!("unknown interpreter \"{}\" (expected tree or bytecode)", s)),
+        }
+    }
+}
+
+/// Compiled GML, as both an instruction tree and the bytecode it was flattened into.
+/// Only the tree is serialized, and the bytecode is flattened again when it's loaded.
+#[derive(Clone)]
+pub struct Code {
+    tree: Rc<[Instruction]>,
+    program: Rc<Program>,
+}
+
+impl Code {
+    pub fn new(tree: Rc<[Instruction]>) -> Self {
+        let program = Rc::new(Program::new(&tree));
+        Self { tree, program }
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    /// A weak reference to the instructions. While it's held, no other code can be allocated at the same address.
+    pub fn downgrade(&self) -> Weak<[Instruction]> {
+        Rc::downgrade(&self.tree)
+    }
+}
+
+impl Deref for Code {
+    type Target = [Instruction];
+
+    fn deref(&self) -> &Self::Target {
+        &self.tree
+    }
+}
+
+impl Serialize for Code {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.tree.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Code {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Self::new(Vec::<Instruction>::deserialize(deserializer)?.into()))
+    }
+}
+
+impl fmt::Debug for Code {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.tree.fmt(f)
+    }
+}
+
+/// A flattened piece of compiled code.
+pub struct Program {
+    ops: Box<[Op]>,
+}
+
+/// One bytecode operation. Expressions push their results onto a stack of values, which statements then pop.
+/// Variable accesses resolve their owner and array index onto a separate stack of places before they're used.
+#[derive(Debug)]
+pub enum Op {
+    /// Pushes a value.
+    Literal(Value),
+
+    /// Pushes the value of a user-defined constant.
+    Constant(usize),
+
+    /// Pops some arguments, calls a kernel function with them and pushes the result.
+    Function { function_id: usize, argc: usize },
+
+    /// Pops some arguments, runs a script with them and pushes its return value.
+    Script { script_id: usize, argc: usize },
+
+    /// Pops some arguments, calls an extension function with them and pushes the result.
+    ExtensionFunction { id: usize, argc: usize },
+
+    /// Pops two values and pushes the result of an operator. If `type_unsafe`, errors push the left side instead.
+    Binary { operator: BinaryOperator, type_unsafe: bool },
+
+    /// Pops a value and pushes the result of an operator.
+    Unary(UnaryOperator),
+
+    /// Pushes a place for an owner which isn't an expression.
+    Target(InstanceIdentifier),
+
+    /// Pushes a place for a field with no owner, which is global if it was declared with globalvar.
+    FieldTarget(usize),
+
+    /// Pops an instance expression and pushes a place for it.
+    ExpressionTarget,
+
+    /// Pops one or two array indices and sets them as the index of the top place.
+    ArrayIndex(usize),
+
+    /// Pops a place and pushes the value of a field there.
+    GetField(usize),
+
+    /// Pops a place and pushes the value of an instance variable there.
+    GetVariable(InstanceVariable),
+
+    /// Pops a value and a place, and assigns the value to a field there.
+    SetField(usize),
+
+    /// Pops a value and a place, and assigns the value to an instance variable there.
+    SetVariable(InstanceVariable),
+
+    /// Pops a value and sets it as the return value.
+    SetReturnValue,
+
+    /// Declares some fields as global.
+    GlobalVar(Vec<usize>),
+
+    /// Raises an error.
+    Error(gml::Error),
+
+    /// Jumps to another op.
+    Jump(usize),
+
+    /// Pops a value and jumps to another op if it isn't true.
+    JumpUnless(usize),
+
+    /// Pops a case and compares it to the switch input under it. If they match, pops the input and jumps.
+    Case(usize),
+
+    /// Pops a value and discards it.
+    Pop,
+
+    /// Stops running. Exiting this way ends any with or repeat loops first.
+    Return(ReturnType),
+
+    /// Ends any with or repeat loops nested deeper than the given depth, for jumping out of them.
+    Unwind(usize),
+
+    /// Pops a count and starts a repeat loop.
+    RepeatStart,
+
+    /// Counts down a repeat loop, or jumps to its end if it's finished.
+    RepeatNext(usize),
+
+    /// Ends a repeat loop.
+    RepeatEnd,
+
+    /// Pops a target and starts a with loop over it, jumping to its end if there's nothing to loop over.
+    WithStart(usize),
+
+    /// Moves a with loop on to its next instance and jumps back to the start of its body, if there is one.
+    WithNext(usize),
+
+    /// Ends a with loop, restoring self and other.
+    WithEnd,
+}
+
+/// A with or repeat loop which is running.
+enum Frame {
+    Repeat(i32),
+    With { this: usize, other: usize, instances: Instances },
+}
+
+/// The instances a with loop has left to go through.
+enum Instances {
+    None,
+    Identity(IdentityIter),
+    Drawing(ILIterDrawOrder),
+}
+
+impl Program {
+    /// Flattens an instruction tree into bytecode.
+    pub fn new(code: &[Instruction]) -> Self {
+        let mut builder = Builder { ops: Vec::new(), loops: Vec::new(), depth: 0 };
+        builder.block(code);
+        Self { ops: builder.ops.into_boxed_slice() }
+    }
+
+    pub fn ops(&self) -> &[Op] {
+        &self.ops
+    }
+}
+
+/// Where break, continue and exit go while flattening some code.
+struct Loop {
+    kind: LoopKind,
+
+    /// Jumps to patch with the end of the loop
+    breaks: Vec<usize>,
+
+    /// Jumps to patch with wherever continue goes
+    continues: Vec<usize>,
+}
+
+enum LoopKind {
+    /// A loop, or the body of a with.
+    Loop,
+
+    /// A switch, which catches break but not continue.
+    Switch,
+
+    /// The step of a for loop. Anything which stops it early, even exit, just moves on to the loop condition.
+    Step { head: usize, depth: usize },
+}
+
+struct Builder {
+    ops: Vec<Op>,
+    loops: Vec<Loop>,
+
+    /// How many with and repeat loops deep the code being flattened is
+    depth: usize,
+}
+
+impl Builder {
+    fn block(&mut self, code: &[Instruction]) {
+        for instruction in code.iter() {
+            self.instruction(instruction);
+        }
+    }
+
+    fn instruction(&mut self, instruction: &Instruction) {
+        match instruction {
+            Instruction::SetField { accessor, value } => {
+                self.field_target(accessor);
+                self.array(&accessor.array);
+                self.node(value);
+                self.ops.push(Op::SetField(accessor.index));
+            },
+            Instruction::SetVariable { accessor, value } => {
+                self.target(&accessor.owner);
+                self.array(&accessor.array);
+                self.node(value);
+                self.ops.push(Op::SetVariable(accessor.var));
+            },
+            Instruction::EvalExpression { node } | Instruction::SetReturnValue { value: node } => {
+                self.node(node);
+                self.ops.push(Op::SetReturnValue);
+            },
+            Instruction::IfElse { cond, if_body, else_body } => {
+                self.node(cond);
+                let to_else = self.push_jump(Op::JumpUnless(0));
+                self.block(if_body);
+                if else_body.is_empty() {
+                    self.patch(to_else, self.ops.len());
+                } else {
+                    let to_end = self.push_jump(Op::Jump(0));
+                    self.patch(to_else, self.ops.len());
+                    self.block(else_body);
+                    self.patch(to_end, self.ops.len());
+                }
+            },
+            Instruction::LoopUntil { cond, body } => {
+                let start = self.ops.len();
+                self.enter(LoopKind::Loop);
+                self.block(body);
+                let lp = self.loops.pop().unwrap();
+                self.node(cond);
+                self.ops.push(Op::JumpUnless(start));
+                // continue goes straight back to the body without checking the condition, as in GM8
+                self.finish(lp, self.ops.len(), start);
+            },
+            Instruction::LoopWhile { cond, body } => {
+                let head = self.ops.len();
+                self.node(cond);
+                let to_end = self.push_jump(Op::JumpUnless(0));
+                self.enter(LoopKind::Loop);
+                self.block(body);
+                self.ops.push(Op::Jump(head));
+                let lp = self.loops.pop().unwrap();
+                self.finish(lp, self.ops.len(), head);
+                self.patch(to_end, self.ops.len());
+            },
+            Instruction::LoopFor { cond, body, step } => {
+                let head = self.ops.len();
+                self.node(cond);
+                let to_end = self.push_jump(Op::JumpUnless(0));
+                self.enter(LoopKind::Loop);
+                self.block(body);
+                let lp = self.loops.pop().unwrap();
+                let step_start = self.ops.len();
+                self.enter(LoopKind::Step { head, depth: self.depth });
+                self.block(step);
+                self.loops.pop();
+                self.ops.push(Op::Jump(head));
+                self.finish(lp, self.ops.len(), step_start);
+                self.patch(to_end, self.ops.len());
+            },
+            Instruction::Return { return_type } => match return_type {
+                ReturnType::Normal => (),
+                ReturnType::Break => self.break_(),
+                ReturnType::Continue => self.continue_(),
+                ReturnType::Exit => self.exit(),
+            },
+            Instruction::Repeat { count, body } => {
+                self.node(count);
+                self.ops.push(Op::RepeatStart);
+                self.depth += 1;
+                let head = self.ops.len();
+                let to_end = self.push_jump(Op::RepeatNext(0));
+                self.enter(LoopKind::Loop);
+                self.block(body);
+                self.ops.push(Op::Jump(head));
+                let lp = self.loops.pop().unwrap();
+                self.finish(lp, self.ops.len(), head);
+                self.patch(to_end, self.ops.len());
+                self.ops.push(Op::RepeatEnd);
+                self.depth -= 1;
+            },
+            Instruction::Switch { input, cases, default, body } => {
+                self.node(input);
+                let mut to_cases = Vec::with_capacity(cases.len());
+                for (case, start) in cases.iter() {
+                    self.node(case);
+                    to_cases.push((self.push_jump(Op::Case(0)), *start));
+                }
+                self.ops.push(Op::Pop);
+                let to_default = self.push_jump(Op::Jump(0));
+
+                // Cases jump into the middle of the body, so keep track of where each instruction starts
+                let mut starts = Vec::with_capacity(body.len() + 1);
+                self.enter(LoopKind::Switch);
+                for instruction in body.iter() {
+                    starts.push(self.ops.len());
+                    self.instruction(instruction);
+                }
+                starts.push(self.ops.len());
+                let lp = self.loops.pop().unwrap();
+                let end = self.ops.len();
+                self.finish(lp, end, end);
+
+                for (jump, start) in to_cases {
+                    self.patch(jump, starts[start]);
+                }
+                self.patch(to_default, default.map_or(end, |start| starts[start]));
+            },
+            Instruction::With { target, body } => {
+                self.node(target);
+                let to_end = self.push_jump(Op::WithStart(0));
+                self.depth += 1;
+                let start = self.ops.len();
+                self.enter(LoopKind::Loop);
+                self.block(body);
+                let lp = self.loops.pop().unwrap();
+                let next = self.ops.len();
+                self.ops.push(Op::WithNext(start));
+                let end = self.ops.len();
+                self.ops.push(Op::WithEnd);
+                self.finish(lp, end, next);
+                self.patch(to_end, end);
+                self.depth -= 1;
+            },
+            Instruction::GlobalVar { fields } => self.ops.push(Op::GlobalVar(fields.clone())),
+            Instruction::RuntimeError { error } => self.ops.push(Op::Error(error.clone())),
+            // Line numbers are only for the debugger, which always uses the tree
+            Instruction::Line { .. } => (),
+        }
+    }
+
+    fn node(&mut self, node: &Node) {
+        match node {
+            Node::Literal { value } => self.ops.push(Op::Literal(value.clone())),
+            Node::Constant { constant_id } => self.ops.push(Op::Constant(*constant_id)),
+            Node::Function { args, function_id } => {
+                self.args(args);
+                self.ops.push(Op::Function { function_id: *function_id, argc: args.len() });
+            },
+            Node::Script { args, script_id } => {
+                self.args(args);
+                self.ops.push(Op::Script { script_id: *script_id, argc: args.len() });
+            },
+            Node::ExtensionFunction { args, id } => {
+                self.args(args);
+                self.ops.push(Op::ExtensionFunction { id: *id, argc: args.len() });
+            },
+            Node::Field { accessor } => {
+                self.field_target(accessor);
+                self.array(&accessor.array);
+                self.ops.push(Op::GetField(accessor.index));
+            },
+            Node::Variable { accessor } => {
+                self.target(&accessor.owner);
+                self.array(&accessor.array);
+                self.ops.push(Op::GetVariable(accessor.var));
+            },
+            Node::Binary { left, right, operator, type_unsafe } => {
+                self.node(left);
+                self.node(right);
+                self.ops.push(Op::Binary { operator: *operator, type_unsafe: *type_unsafe });
+            },
+            Node::Unary { child, operator } => {
+                self.node(child);
+                self.ops.push(Op::Unary(*operator));
+            },
+            Node::RuntimeError { error } => self.ops.push(Op::Error(error.clone())),
+        }
+    }
+
+    fn args(&mut self, args: &[Node]) {
+        // The runtime only has room for 16 arguments, and doesn't evaluate any more than that
+        for arg in args.iter().take(16) {
+            self.node(arg);
+        }
+    }
+
+    fn target(&mut self, owner: &InstanceIdentifier) {
+        match owner {
+            InstanceIdentifier::Expression(node) => {
+                self.node(node);
+                self.ops.push(Op::ExpressionTarget);
+            },
+            owner => self.ops.push(Op::Target(owner.clone())),
+        }
+    }
+
+    fn field_target(&mut self, accessor: &FieldAccessor) {
+        match accessor.owner {
+            InstanceIdentifier::Unknown => self.ops.push(Op::FieldTarget(accessor.index)),
+            ref owner => self.target(owner),
+        }
+    }
+
+    fn array(&mut self, array: &ArrayAccessor) {
+        match array {
+            ArrayAccessor::None => (),
+            ArrayAccessor::Single(index) => {
+                self.node(index);
+                self.ops.push(Op::ArrayIndex(1));
+            },
+            ArrayAccessor::Double(index1, index2) => {
+                self.node(index1);
+                self.node(index2);
+                self.ops.push(Op::ArrayIndex(2));
+            },
+        }
+    }
+
+    fn break_(&mut self) {
+        match self.loops.last_mut() {
+            Some(Loop { kind: LoopKind::Step { head, .. }, .. }) => {
+                let head = *head;
+                self.ops.push(Op::Jump(head));
+            },
+            Some(lp) => {
+                lp.breaks.push(self.ops.len());
+                self.ops.push(Op::Jump(0));
+            },
+            None => self.ops.push(Op::Return(ReturnType::Break)),
+        }
+    }
+
+    fn continue_(&mut self) {
+        let len = self.ops.len();
+        match self.loops.iter_mut().rev().find(|lp| !matches!(lp.kind, LoopKind::Switch)) {
+            Some(Loop { kind: LoopKind::Step { head, .. }, .. }) => {
+                let head = *head;
+                self.ops.push(Op::Jump(head));
+            },
+            Some(lp) => {
+                lp.continues.push(len);
+                self.ops.push(Op::Jump(0));
+            },
+            None => self.ops.push(Op::Return(ReturnType::Continue)),
+        }
+    }
+
+    fn exit(&mut self) {
+        let step = self.loops.iter().rev().find_map(|lp| match lp.kind {
+            LoopKind::Step { head, depth } => Some((head, depth)),
+            _ => None,
+        });
+        match step {
+            Some((head, depth)) => {
+                if self.depth > depth {
+                    self.ops.push(Op::Unwind(depth));
+                }
+                self.ops.push(Op::Jump(head));
+            },
+            None => self.ops.push(Op::Return(ReturnType::Exit)),
+        }
+    }
+
+    fn enter(&mut self, kind: LoopKind) {
+        self.loops.push(Loop { kind, breaks: Vec::new(), continues: Vec::new() });
+    }
+
+    fn finish(&mut self, lp: Loop, break_to: usize, continue_to: usize) {
+        for jump in lp.breaks {
+            self.patch(jump, break_to);
+        }
+        for jump in lp.continues {
+            self.patch(jump, continue_to);
+        }
+    }
+
+    fn push_jump(&mut self, op: Op) -> usize {
+        self.ops.push(op);
+        self.ops.len() - 1
+    }
+
+    fn patch(&mut self, jump: usize, target: usize) {
+        match &mut self.ops[jump] {
+            Op::Jump(to)
+            | Op::JumpUnless(to)
+            | Op::Case(to)
+            | Op::RepeatNext(to)
+            | Op::WithStart(to)
+            | Op::WithNext(to) => *to = target,
+            op => unreachable!("tried to patch {:?}, which isn't a jump", op),
+        }
+    }
+}
+
+/// Pops the arguments to a call into the fixed-size array the runtime uses.
+fn pop_args(stack: &mut Vec<Value>, count: usize) -> [Value; 16] {
+    let mut args: [Value; 16] = Default::default();
+    let start = stack.len() - count.min(16);
+    for (dest, src) in args.iter_mut().zip(stack.drain(start..)) {
+        *dest = src;
+    }
+    args
+}
+
+/// Ends every with or repeat loop deeper than the given depth, restoring self and other.
+fn unwind(frames: &mut Vec<Frame>, depth: usize, context: &mut Context) {
+    while frames.len() > depth {
+        if let Some(Frame::With { this, other, .. }) = frames.pop() {
+            context.this = this;
+            context.other = other;
+        }
+    }
+}
+
+impl Game {
+    /// Runs some compiled code, timing it if the profiler is on.
+    pub fn execute_code(&mut self, code: &Code, context: &mut Context) -> gml::Result<ReturnType> {
+        if self.profiler.is_some() {
+            return self.profile_code(code, context)
+        }
+        self.run_code(code, context)
+    }
+
+    /// Runs some compiled code with whichever interpreter is selected, or under the debugger if one is attached.
+    pub(crate) fn run_code(&mut self, code: &Code, context: &mut Context) -> gml::Result<ReturnType> {
+        if self.debugger.is_some() {
+            return self.debug_code(code, context)
+        }
+        match self.interpreter {
+            Interpreter::Tree => self.execute(code, context),
+            Interpreter::Bytecode => self.execute_program(code.program(), context),
+        }
+    }
+
+    /// Runs a bytecode program. This must behave exactly like `execute` on the tree it came from.
+    pub fn execute_program(&mut self, program: &Program, context: &mut Context) -> gml::Result<ReturnType> {
+        let mut stack: Vec<Value> = Vec::new();
+        let mut places: Vec<(Target, u32)> = Vec::new();
+        let mut frames: Vec<Frame> = Vec::new();
+        let mut pc = 0;
+        while let Some(op) = program.ops.get(pc) {
+            pc += 1;
+            match op {
+                Op::Literal(value) => stack.push(value.clone()),
+                Op::Constant(constant_id) => match self.constants.get(*constant_id) {
+                    Some(value) => stack.push(value.clone()),
+                    None => return Err(gml::Error::NonexistentAsset(asset::Type::Constant, *constant_id as i32)),
+                },
+                Op::Function { function_id, argc } => {
+                    let args = pop_args(&mut stack, *argc);
+                    stack.push(self.invoke(*function_id, context, &args[..*argc])?);
+                },
+                Op::Script { script_id, argc } => {
+                    // Unlike the tree, this checks the script exists after evaluating the arguments rather than
+                    // before, but scripts are only compiled to calls if they exist so that can't be seen
+                    let args = pop_args(&mut stack, *argc);
+                    if let Some(Some(script)) = self.assets.scripts.get(*script_id) {
+                        let code = script.compiled.clone();
+                        let mut new_context = Context::copy_with_args(context, args, *argc);
+                        // This goes through execute_code like the tree does, so the profiler and debugger see it
+                        self.execute_code(&code, &mut new_context)?;
+                        stack.push(new_context.return_value);
+                    } else {
+                        return Err(gml::Error::NonexistentAsset(asset::Type::Script, *script_id as i32))
+                    }
+                },
+                Op::ExtensionFunction { id, argc } => {
+                    let args = pop_args(&mut stack, *argc);
+                    stack.push(self.run_extension_function(*id, context, args, *argc)?);
+                },
+                Op::Binary { operator, type_unsafe } => {
+                    let rhs = stack.pop().unwrap();
+                    let lhs = stack.pop().unwrap();
+                    // the + in += can happen here, and += ignores errors in the + portion
+                    let value = if *type_unsafe {
+                        operator.call(lhs.clone(), rhs).unwrap_or(lhs)
+                    } else {
+                        operator.call(lhs, rhs)?
+                    };
+                    stack.push(value);
+                },
+                Op::Unary(operator) => {
+                    let value = operator.call(stack.pop().unwrap())?;
+                    stack.push(value);
+                },
+                Op::Target(owner) => places.push((self.get_target(context, owner, false)?, 0)),
+                Op::FieldTarget(index) => {
+                    let in_globalvars = self.globalvars.contains(index);
+                    places.push((self.get_target(context, &InstanceIdentifier::Unknown, in_globalvars)?, 0));
+                },
+                Op::ExpressionTarget => {
+                    let value = i32::from(stack.pop().unwrap());
+                    places.push((self.target_of(context, value), 0));
+                },
+                Op::ArrayIndex(dimensions) => {
+                    let index = if *dimensions == 2 {
+                        let index2 = stack.pop().unwrap().round();
+                        let index1 = stack.pop().unwrap().round();
+                        runtime::array_index(index1, Some(index2))?
+                    } else {
+                        runtime::array_index(stack.pop().unwrap().round(), None)?
+                    };
+                    places.last_mut().unwrap().1 = index;
+                },
+                Op::GetField(index) => {
+                    let (target, array_index) = places.pop().unwrap();
+                    stack.push(self.read_field(context, target, *index, array_index)?);
+                },
+                Op::GetVariable(var) => {
+                    let (target, array_index) = places.pop().unwrap();
+                    stack.push(self.read_variable(context, target, var, array_index)?);
+                },
+                Op::SetField(index) => {
+                    let value = stack.pop().unwrap();
+                    let (target, array_index) = places.pop().unwrap();
+                    self.write_field(context, target, *index, array_index, value);
+                },
+                Op::SetVariable(var) => {
+                    let value = stack.pop().unwrap();
+                    let (target, array_index) = places.pop().unwrap();
+                    self.write_variable(context, target, var, array_index, value)?;
+                },
+                Op::SetReturnValue => context.return_value = stack.pop().unwrap(),
+                Op::GlobalVar(fields) => {
+                    self.globalvars.extend(fields);
+                    for &field in fields {
+                        self.globals.fields.entry(field).or_insert(Field::new(0, Default::default()));
+                    }
+                },
+                Op::Error(error) => return Err(error.clone()),
+                Op::Jump(to) => pc = *to,
+                Op::JumpUnless(to) => {
+                    if !stack.pop().unwrap().is_truthy() {
+                        pc = *to;
+                    }
+                },
+                Op::Case(to) => {
+                    let case = stack.pop().unwrap();
+                    if case.almost_equals(stack.last().unwrap()) {
+                        stack.pop();
+                        pc = *to;
+                    }
+                },
+                Op::Pop => {
+                    stack.pop();
+                },
+                Op::Return(return_type) => {
+                    unwind(&mut frames, 0, context);
+                    return Ok(*return_type)
+                },
+                Op::Unwind(depth) => unwind(&mut frames, *depth, context),
+                Op::RepeatStart => {
+                    let count = stack.pop().unwrap().round();
+                    frames.push(Frame::Repeat(count));
+                },
+                Op::RepeatNext(to) => {
+                    if let Some(Frame::Repeat(count)) = frames.last_mut() {
+                        if *count > 0 {
+                            *count -= 1;
+                        } else {
+                            pc = *to;
+                        }
+                    }
+                },
+                Op::RepeatEnd => {
+                    frames.pop();
+                },
+                Op::WithStart(to) => {
+                    let target = i32::from(stack.pop().unwrap());
+                    let (this, other) = (context.this, context.other);
+                    context.other = context.this;
+                    let (first, instances) = match target {
+                        gml::SELF | gml::UNSPECIFIED => (Some(this), Instances::None),
+                        gml::OTHER => (Some(other), Instances::None),
+                        gml::ALL => {
+                            let mut iter = self.room.instance_list.iter_by_drawing();
+                            (iter.next(&self.room.instance_list), Instances::Drawing(iter))
+                        },
+                        i if i < 0 => (None, Instances::None),
+                        i if i < 100_000 => {
+                            let mut iter = self.room.instance_list.iter_by_identity(i);
+                            (iter.next(&self.room.instance_list), Instances::Identity(iter))
+                        },
+                        i => (self.room.instance_list.get_by_instid(i), Instances::None),
+                    };
+                    frames.push(Frame::With { this, other, instances });
+                    match first {
+                        Some(instance) => context.this = instance,
+                        None => pc = *to,
+                    }
+                },
+                Op::WithNext(to) => {
+                    if let Some(Frame::With { instances, .. }) = frames.last_mut() {
+                        let next = match instances {
+                            Instances::None => None,
+                            Instances::Identity(iter) => iter.next(&self.room.instance_list),
+                            Instances::Drawing(iter) => iter.next(&self.room.instance_list),
+                        };
+                        if let Some(instance) = next {
+                            context.this = instance;
+                            pc = *to;
+                        }
+                    }
+                },
+                Op::WithEnd => {
+                    if let Some(Frame::With { this, other, .. }) = frames.pop() {
+                        context.this = this;
+                        context.other = other;
+                    }
+                },
+            }
+        }
+        Ok(ReturnType::Normal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gml::Compiler;
+
+    fn compile(source: &str) -> Program {
+        let mut compiler = Compiler::new();
+        compiler.set_optimize(false);
+        Program::new(&compiler.compile(source.as_bytes()).unwrap())
+    }
+
+    #[test]
+    fn jumps_stay_in_bounds() {
+        let program = compile(
+            "for (i = 0; i < 10; i += 1) { if i == 3 continue; while a { break } }
+            do { with all { if x continue; exit } } until b
+            repeat 3 { switch c { case 1: break; case 2: continue; default: d = 1 } }",
+        );
+        for op in program.ops() {
+            match op {
+                Op::Jump(to)
+                | Op::JumpUnless(to)
+                | Op::Case(to)
+                | Op::RepeatNext(to)
+                | Op::WithStart(to)
+                | Op::WithNext(to) => assert!(*to <= program.ops().len()),
+                _ => (),
+            }
+        }
+    }
+
+    #[test]
+    fn break_and_continue_outside_loops() {
+        let program = compile("switch a { case 1: continue; case 2: break } exit; break");
+        let returns = program
+            .ops()
+            .iter()
+            .filter_map(|op| match op {
+                Op::Return(return_type) => Some(*return_type),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(returns, [ReturnType::Continue, ReturnType::Exit, ReturnType::Break]);
+    }
+
+    #[test]
+    fn exit_in_for_step() {
+        let program = compile("for (i = 0; i < 3; exit) { with all { a = 1 } }");
+        assert!(!program.ops().iter().any(|op| matches!(op, Op::Return(_))));
+    }
+
+    #[test]
+    fn line_markers() {
+        let mut compiler = Compiler::new();
+        compiler.set_line_info(true);
+        let code = compiler.compile(b"var a;\na = 1; b = 2;\n{\n    c = 3\n}").unwrap();
+        let lines = code
+            .iter()
+            .filter_map(|instruction| match instruction {
+                Instruction::Line { line } => Some(*line),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(lines, [2, 4]);
+
+        // They're only for the tree interpreter, so they shouldn't change the bytecode
+        compiler.set_line_info(false);
+        let plain = compiler.compile(b"var a;\na = 1; b = 2;\n{\n    c = 3\n}").unwrap();
+        assert_eq!(code.program().ops().len(), plain.program().ops().len());
+    }
+}
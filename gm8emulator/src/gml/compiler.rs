@@ -1,5 +1,6 @@
 use super::{
-    mappings,
+    bytecode::Code,
+    mappings, optimizer,
     runtime::{
         ArrayAccessor, BinaryOperator, FieldAccessor, InstanceIdentifier, Instruction, Node, ReturnType, UnaryOperator,
         VariableAccessor,
@@ -9,7 +10,7 @@ use super::{
 use crate::{gml, math::Real};
 use gml_parser::{ast, token::Operator};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, rc::Rc, str};
+use std::{collections::HashMap, str};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Compiler {
@@ -27,6 +28,18 @@ pub struct Compiler {
 
     /// Lookup table of unique field names
     fields: Vec<Box<[u8]>>,
+
+    /// Whether to fold constants and remove dead code after compiling
+    #[serde(skip)]
+    optimize: bool,
+
+    /// Whether to mark which line each statement is on, for the debugger
+    #[serde(skip)]
+    line_info: bool,
+
+    /// The last line marked in the code currently being compiled
+    #[serde(skip)]
+    last_line: usize,
 }
 
 impl Compiler {
@@ -38,9 +51,36 @@ impl Compiler {
             script_names: HashMap::new(),
             extension_fn_names: HashMap::new(),
             fields: Vec::new(),
+            optimize: true,
+            line_info: false,
+            last_line: 0,
         }
     }
 
+    /// Set whether compiled code should be optimized. This is on by default, and turning it off
+    /// is only useful for checking that optimized code behaves the same as unoptimized code.
+    pub fn set_optimize(&mut self, optimize: bool) {
+        self.optimize = optimize;
+    }
+
+    /// Set whether compiled code should say which line each statement is on. This is off by default,
+    /// and is only needed for code which will be stepped through in the debugger.
+    pub fn set_line_info(&mut self, line_info: bool) {
+        self.line_info = line_info;
+    }
+
+    /// Whether compiled code says which line each statement is on.
+    pub fn line_info(&self) -> bool {
+        self.line_info
+    }
+
+    /// Copy the settings which aren't saved with the compiler, such as whether to optimize, from another compiler.
+    /// These belong to the emulator session rather than the game, so they're kept when a savestate is loaded.
+    pub fn copy_settings(&mut self, other: &Self) {
+        self.optimize = other.optimize;
+        self.line_info = other.line_info;
+    }
+
     /// Reserve space to register at least the given number of constants.
     pub fn reserve_constants(&mut self, size: usize) {
         self.constants.reserve(size)
@@ -83,49 +123,62 @@ impl Compiler {
         self.user_constant_names.insert(name, index);
     }
 
-    /// Compile a GML string into instructions.
-    pub fn compile(&mut self, source: &[u8]) -> Result<Rc<[Instruction]>, ast::Error> {
+    /// Compile a GML string into instructions, and flatten those into bytecode.
+    pub fn compile(&mut self, source: &[u8]) -> Result<Code, ast::Error> {
         let ast = ast::AST::new(source)?;
 
         let mut instructions = Vec::new();
         let mut locals: Vec<&[u8]> = Vec::new();
+        self.last_line = 0;
         for node in ast.iter() {
             self.compile_ast_line(node, &mut instructions, &mut locals);
         }
-        Ok(instructions.into())
+        if self.optimize {
+            instructions = optimizer::block(instructions);
+        }
+        Ok(Code::new(instructions.into()))
     }
 
     /// Compile an expression into a format which can be evaluated.
     pub fn compile_expression(&mut self, source: &[u8]) -> Result<Node, ast::Error> {
         let expr = ast::AST::expression(source)?;
-        Ok(self.compile_ast_expr(&expr, &[]))
+        let node = self.compile_ast_expr(&expr, &[]);
+        Ok(if self.optimize { optimizer::node(node) } else { node })
     }
 
     /// Compile a single line of code from an AST expression.
     fn compile_ast_line<'a>(&mut self, line: &'a ast::Expr, output: &mut Vec<Instruction>, locals: &mut Vec<&'a [u8]>) {
-        match line {
+        // Groups and var declarations don't do anything themselves, so they aren't worth stopping on
+        if self.line_info
+            && self.last_line != line.span.line
+            && !matches!(line.kind, ast::ExprKind::Group(_) | ast::ExprKind::Var(_))
+        {
+            self.last_line = line.span.line;
+            output.push(Instruction::Line { line: line.span.line });
+        }
+        match &line.kind {
             // Line of code identified by an assignment operator
-            ast::Expr::Binary(binary_expr) => {
+            ast::ExprKind::Binary(binary_expr) => {
                 output.push(self.binary_to_instruction(binary_expr.as_ref(), &locals));
             },
 
             // Break
-            ast::Expr::Break => {
+            ast::ExprKind::Break => {
                 output.push(Instruction::Return { return_type: ReturnType::Break });
             },
 
             // Continue
-            ast::Expr::Continue => {
+            ast::ExprKind::Continue => {
                 output.push(Instruction::Return { return_type: ReturnType::Continue });
             },
 
             // Exit
-            ast::Expr::Exit => {
+            ast::ExprKind::Exit => {
                 output.push(Instruction::Return { return_type: ReturnType::Exit });
             },
 
             // For loop
-            ast::Expr::For(for_expr) => {
+            ast::ExprKind::For(for_expr) => {
                 self.compile_ast_line(&for_expr.start, output, locals);
                 let cond = self.compile_ast_expr(&for_expr.cond, locals);
                 let mut body = Vec::new();
@@ -140,44 +193,35 @@ impl Compiler {
             },
 
             // Function or Script
-            f @ ast::Expr::Function(_) => {
-                output.push(Instruction::EvalExpression { node: self.compile_ast_expr(f, locals) });
+            ast::ExprKind::Function(_) => {
+                output.push(Instruction::EvalExpression { node: self.compile_ast_expr(line, locals) });
             },
 
             // Group of expressions
-            ast::Expr::Group(group) => {
+            ast::ExprKind::Group(group) => {
                 for expr in group {
                     self.compile_ast_line(expr, output, locals);
                 }
             },
 
             // If/else body
-            ast::Expr::If(if_expr) => {
+            ast::ExprKind::If(if_expr) => {
                 let cond = self.compile_ast_expr(&if_expr.cond, locals);
-                if let Node::Literal { value: v } = cond {
-                    // The "if" condition is constant, so we can optimize this away
-                    if v.is_truthy() {
-                        self.compile_ast_line(&if_expr.body, output, locals);
-                    } else if let Some(expr_else_body) = &if_expr.else_body {
-                        self.compile_ast_line(expr_else_body, output, locals);
-                    }
-                } else {
-                    let mut if_body = Vec::new();
-                    self.compile_ast_line(&if_expr.body, &mut if_body, locals);
-                    let mut else_body = Vec::new();
-                    if let Some(expr_else_body) = &if_expr.else_body {
-                        self.compile_ast_line(expr_else_body, &mut else_body, locals);
-                    }
-                    output.push(Instruction::IfElse {
-                        cond,
-                        if_body: if_body.into_boxed_slice(),
-                        else_body: else_body.into_boxed_slice(),
-                    });
+                let mut if_body = Vec::new();
+                self.compile_ast_line(&if_expr.body, &mut if_body, locals);
+                let mut else_body = Vec::new();
+                if let Some(expr_else_body) = &if_expr.else_body {
+                    self.compile_ast_line(expr_else_body, &mut else_body, locals);
                 }
+                output.push(Instruction::IfElse {
+                    cond,
+                    if_body: if_body.into_boxed_slice(),
+                    else_body: else_body.into_boxed_slice(),
+                });
             },
 
             // "repeat" block
-            ast::Expr::Repeat(repeat_expr) => {
+            ast::ExprKind::Repeat(repeat_expr) => {
                 let count = self.compile_ast_expr(&repeat_expr.count, locals);
                 let mut body = Vec::new();
                 self.compile_ast_line(&repeat_expr.body, &mut body, locals);
@@ -185,25 +229,25 @@ impl Compiler {
             },
 
             // Return
-            ast::Expr::Return(expr) => {
+            ast::ExprKind::Return(expr) => {
                 let value = self.compile_ast_expr(&expr, locals);
                 output.push(Instruction::SetReturnValue { value });
                 output.push(Instruction::Return { return_type: ReturnType::Exit });
             },
 
             // "switch" block
-            ast::Expr::Switch(switch_expr) => {
+            ast::ExprKind::Switch(switch_expr) => {
                 let input = self.compile_ast_expr(&switch_expr.input, locals);
-                if let ast::Expr::Group(group) = &switch_expr.body {
+                if let ast::ExprKind::Group(group) = &switch_expr.body.kind {
                     let mut cases = Vec::new();
                     let mut body = Vec::new();
                     let mut default: Option<usize> = None;
                     for expr in group {
-                        if let ast::Expr::Case(case_expr) = expr {
+                        if let ast::ExprKind::Case(case_expr) = &expr.kind {
                             if default.is_none() {
                                 cases.push((self.compile_ast_expr(case_expr, locals), body.len()));
                             }
-                        } else if let ast::Expr::Default = expr {
+                        } else if let ast::ExprKind::Default = expr.kind {
                             if default.is_none() {
                                 default = Some(body.len());
                             }
@@ -225,7 +269,7 @@ impl Compiler {
             },
 
             // "do-until" block
-            ast::Expr::DoUntil(while_expr) => {
+            ast::ExprKind::DoUntil(while_expr) => {
                 let cond = self.compile_ast_expr(&while_expr.cond, locals);
                 let mut body = Vec::new();
                 self.compile_ast_line(&while_expr.body, &mut body, locals);
@@ -233,18 +277,18 @@ impl Compiler {
             },
 
             // "var" declaration
-            ast::Expr::Var(var_expr) => {
+            ast::ExprKind::Var(var_expr) => {
                 locals.extend_from_slice(&var_expr.vars);
             },
 
-            ast::Expr::GlobalVar(globalvar_expr) => {
+            ast::ExprKind::GlobalVar(globalvar_expr) => {
                 // globalvar doesn't work on builtins
                 let fields = globalvar_expr.vars.iter().map(|x| self.get_field_id(x)).collect();
                 output.push(Instruction::GlobalVar { fields });
             },
 
             // "while" block
-            ast::Expr::While(while_expr) => {
+            ast::ExprKind::While(while_expr) => {
                 let cond = self.compile_ast_expr(&while_expr.cond, locals);
                 let mut body = Vec::new();
                 self.compile_ast_line(&while_expr.body, &mut body, locals);
@@ -252,7 +296,7 @@ impl Compiler {
             },
 
             // "with" block
-            ast::Expr::With(with_expr) => {
+            ast::ExprKind::With(with_expr) => {
                 let target = self.compile_ast_expr(&with_expr.target, locals);
                 let mut body = Vec::new();
                 self.compile_ast_line(&with_expr.body, &mut body, locals);
@@ -268,12 +312,12 @@ impl Compiler {
 
     /// Compile an AST expression into a Node.
     fn compile_ast_expr(&mut self, expr: &ast::Expr, locals: &[&[u8]]) -> Node {
-        match expr {
-            ast::Expr::LiteralReal(real) => Node::Literal { value: Value::Real(Real::from(*real)) },
+        match &expr.kind {
+            ast::ExprKind::LiteralReal(real) => Node::Literal { value: Value::Real(Real::from(*real)) },
 
-            ast::Expr::LiteralString(string) => Node::Literal { value: Value::Str((*string).into()) },
+            ast::ExprKind::LiteralString(string) => Node::Literal { value: Value::Str((*string).into()) },
 
-            ast::Expr::LiteralIdentifier(string) => {
+            ast::ExprKind::LiteralIdentifier(string) => {
                 if let Some(entry) = self.constants.get(*string) {
                     Node::Literal { value: entry.clone() }
                 } else if let Some(constant_id) = self.user_constant_names.get(*string) {
@@ -285,29 +329,29 @@ impl Compiler {
                 }
             },
 
-            ast::Expr::Binary(binary_expr) => match &binary_expr.op {
-                Operator::Deref => match &binary_expr.right {
-                    ast::Expr::LiteralIdentifier(var_name) => {
+            ast::ExprKind::Binary(binary_expr) => match &binary_expr.op {
+                Operator::Deref => match &binary_expr.right.kind {
+                    ast::ExprKind::LiteralIdentifier(var_name) => {
                         let owner = self.make_instance_identifier(&binary_expr.left, locals);
                         self.identifier_to_variable(var_name, Some(owner), ArrayAccessor::None, locals)
                     },
                     _ => Node::RuntimeError { error: gml::Error::InvalidDeref(binary_expr.right.to_string()) },
                 },
 
-                Operator::Index => match &binary_expr.right {
-                    ast::Expr::Group(dimensions) => {
+                Operator::Index => match &binary_expr.right.kind {
+                    ast::ExprKind::Group(dimensions) => {
                         let accessor = match self.make_array_accessor(dimensions, locals) {
                             Ok(a) => a,
                             Err(e) => return Node::RuntimeError { error: gml::Error::TooManyArrayDimensions(e) },
                         };
-                        match &binary_expr.left {
-                            ast::Expr::LiteralIdentifier(string) => {
+                        match &binary_expr.left.kind {
+                            ast::ExprKind::LiteralIdentifier(string) => {
                                 self.identifier_to_variable(string, None, accessor, locals)
                             },
-                            ast::Expr::Binary(binary_expr) => {
+                            ast::ExprKind::Binary(binary_expr) => {
                                 if let ast::BinaryExpr {
                                     left,
-                                    right: ast::Expr::LiteralIdentifier(i),
+                                    right: ast::Expr { kind: ast::ExprKind::LiteralIdentifier(i), .. },
                                     op: Operator::Deref,
                                 } = binary_expr.as_ref()
                                 {
@@ -352,27 +396,16 @@ impl Compiler {
                         op => return Node::RuntimeError { error: gml::Error::InvalidBinaryOperator(*op) },
                     };
 
-                    let left = self.compile_ast_expr(&binary_expr.left, locals);
-                    let right = self.compile_ast_expr(&binary_expr.right, locals);
-
-                    match (left, right) {
-                        (Node::Literal { value: lhs @ _ }, Node::Literal { value: rhs @ _ }) => {
-                            match op_function.call(lhs, rhs) {
-                                Ok(value) => Node::Literal { value },
-                                Err(error) => Node::RuntimeError { error },
-                            }
-                        },
-                        (left, right) => Node::Binary {
-                            left: Box::new(left),
-                            right: Box::new(right),
-                            operator: op_function,
-                            type_unsafe: false,
-                        },
+                    Node::Binary {
+                        left: Box::new(self.compile_ast_expr(&binary_expr.left, locals)),
+                        right: Box::new(self.compile_ast_expr(&binary_expr.right, locals)),
+                        operator: op_function,
+                        type_unsafe: false,
                     }
                 },
             },
 
-            ast::Expr::Function(function) => {
+            ast::ExprKind::Function(function) => {
                 let args = function
                     .params
                     .iter()
@@ -395,7 +428,7 @@ impl Compiler {
                 }
             },
 
-            ast::Expr::Unary(unary_expr) => {
+            ast::ExprKind::Unary(unary_expr) => {
                 let new_node = self.compile_ast_expr(&unary_expr.child, locals);
                 let operator = match unary_expr.op {
                     Operator::Add => return new_node,
@@ -404,13 +437,7 @@ impl Compiler {
                     Operator::Complement => UnaryOperator::Complement,
                     _ => return Node::RuntimeError { error: gml::Error::InvalidUnaryOperator(unary_expr.op) },
                 };
-                match new_node {
-                    Node::Literal { value } => match operator.call(value) {
-                        Ok(value) => Node::Literal { value },
-                        Err(error) => Node::RuntimeError { error },
-                    },
-                    node => Node::Unary { child: Box::new(node), operator },
-                }
+                Node::Unary { child: Box::new(new_node), operator }
             },
 
             _ => Node::RuntimeError { error: gml::Error::UnexpectedASTExpr(expr.to_string()) },
@@ -453,16 +480,16 @@ impl Compiler {
         };
 
         let value = self.compile_ast_expr(&binary_expr.right, locals);
-        match &binary_expr.left {
-            ast::Expr::LiteralIdentifier(string) => {
+        match &binary_expr.left.kind {
+            ast::ExprKind::LiteralIdentifier(string) => {
                 if let Some(mod_type) = modification_type {
                     self.make_modify_instruction(string, None, ArrayAccessor::None, mod_type, value, locals)
                 } else {
                     self.make_set_instruction(string, None, ArrayAccessor::None, value, locals)
                 }
             },
-            ast::Expr::Binary(binary_expr) if binary_expr.op == Operator::Deref => {
-                if let ast::Expr::LiteralIdentifier(string) = binary_expr.right {
+            ast::ExprKind::Binary(binary_expr) if binary_expr.op == Operator::Deref => {
+                if let ast::ExprKind::LiteralIdentifier(string) = binary_expr.right.kind {
                     let owner = self.make_instance_identifier(&binary_expr.left, locals);
                     if let Some(mod_type) = modification_type {
                         self.make_modify_instruction(string, Some(owner), ArrayAccessor::None, mod_type, value, locals)
@@ -473,22 +500,22 @@ impl Compiler {
                     Instruction::RuntimeError { error: gml::Error::InvalidDeref(binary_expr.right.to_string()) }
                 }
             },
-            ast::Expr::Binary(binary_expr) if binary_expr.op == Operator::Index => {
-                if let ast::Expr::Group(dimensions) = &binary_expr.right {
+            ast::ExprKind::Binary(binary_expr) if binary_expr.op == Operator::Index => {
+                if let ast::ExprKind::Group(dimensions) = &binary_expr.right.kind {
                     let accessor = match self.make_array_accessor(dimensions, locals) {
                         Ok(a) => a,
                         Err(e) => return Instruction::RuntimeError { error: gml::Error::TooManyArrayDimensions(e) },
                     };
-                    match &binary_expr.left {
-                        ast::Expr::LiteralIdentifier(string) => {
+                    match &binary_expr.left.kind {
+                        ast::ExprKind::LiteralIdentifier(string) => {
                             if let Some(mod_type) = modification_type {
                                 self.make_modify_instruction(string, None, accessor, mod_type, value, locals)
                             } else {
                                 self.make_set_instruction(string, None, accessor, value, locals)
                             }
                         },
-                        ast::Expr::Binary(binary_expr) if binary_expr.op == Operator::Deref => {
-                            if let ast::Expr::LiteralIdentifier(string) = binary_expr.right {
+                        ast::ExprKind::Binary(binary_expr) if binary_expr.op == Operator::Deref => {
+                            if let ast::ExprKind::LiteralIdentifier(string) = binary_expr.right.kind {
                                 let owner = self.make_instance_identifier(&binary_expr.left, locals);
                                 if let Some(mod_type) = modification_type {
                                     self.make_modify_instruction(string, Some(owner), accessor, mod_type, value, locals)
@@ -618,18 +645,7 @@ impl Compiler {
 
     /// Converts an AST node to an InstanceIdentifier.
     fn make_instance_identifier(&mut self, expression: &ast::Expr, locals: &[&[u8]]) -> InstanceIdentifier {
-        let node = self.compile_ast_expr(expression, locals);
-        if let Node::Literal { value: v @ Value::Real(_) } = &node {
-            match v.round() {
-                gml::SELF | gml::UNSPECIFIED => InstanceIdentifier::Own,
-                gml::OTHER => InstanceIdentifier::Other,
-                gml::GLOBAL => InstanceIdentifier::Global,
-                gml::LOCAL => InstanceIdentifier::Local,
-                _ => InstanceIdentifier::Expression(Box::new(node)),
-            }
-        } else {
-            InstanceIdentifier::Expression(Box::new(node))
-        }
+        instance_identifier(self.compile_ast_expr(expression, locals))
     }
 
     /// Converts a list of expressions into an array accessor (or an error message).
@@ -649,4 +665,24 @@ impl Compiler {
     pub fn get_field_name(&self, id: usize) -> Option<String> {
         self.fields.get(id).map(|s| String::from_utf8_lossy(s).into())
     }
+
+    /// How many field names have been given IDs.
+    pub fn field_count(&self) -> usize {
+        self.fields.len()
+    }
+}
+
+/// Converts a compiled expression to an InstanceIdentifier, skipping the expression if it's a magic value.
+pub(super) fn instance_identifier(node: Node) -> InstanceIdentifier {
+    if let Node::Literal { value: v @ Value::Real(_) } = &node {
+        match v.round() {
+            gml::SELF | gml::UNSPECIFIED => InstanceIdentifier::Own,
+            gml::OTHER => InstanceIdentifier::Other,
+            gml::GLOBAL => InstanceIdentifier::Global,
+            gml::LOCAL => InstanceIdentifier::Local,
+            _ => InstanceIdentifier::Expression(Box::new(node)),
+        }
+    } else {
+        InstanceIdentifier::Expression(Box::new(node))
+    }
 }
@@ -1,6 +1,9 @@
-use crate::{gml::Value, math::Real};
+use crate::{
+    gml::{rand::Random, Value},
+    math::Real,
+};
 use serde::{Deserialize, Serialize};
-use std::{cmp::Ordering, collections};
+use std::{cmp::Ordering, collections, io::Read};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -137,12 +140,14 @@ impl Grid {
 
     fn range_x(&self, x1: i32, x2: i32) -> std::ops::Range<usize> {
         let (x1, x2) = if x1 < x2 { (x1, x2) } else { (x2, x1) };
-        (x1.max(0) as usize)..((x2 + 1).clamp(0, self.width() as _) as usize)
+        let start = x1.clamp(0, self.width() as _);
+        (start as usize)..((x2 + 1).clamp(start, self.width() as _) as usize)
     }
 
     fn range_y(&self, y1: i32, y2: i32) -> std::ops::Range<usize> {
         let (y1, y2) = if y1 < y2 { (y1, y2) } else { (y2, y1) };
-        (y1.max(0) as usize)..((y2 + 1).clamp(0, self.height() as _) as usize)
+        let start = y1.clamp(0, self.height() as _);
+        (start as usize)..((y2 + 1).clamp(start, self.height() as _) as usize)
     }
 
     /// Goes through each column
@@ -249,6 +254,48 @@ impl Grid {
         self.grid.iter_mut().flatten()
     }
 
+    /// Combines a region of another grid into this one, with the region's top-left corner placed at (xpos, ypos).
+    pub fn combine_region(
+        &mut self,
+        src: &Grid,
+        (x1, y1, x2, y2): (i32, i32, i32, i32),
+        (xpos, ypos): (i32, i32),
+        mut op: impl FnMut(&mut Value, Value),
+    ) {
+        let (left, top) = (x1.min(x2), y1.min(y2));
+        for ((x, y), val) in src.region_positioned(x1, y1, x2, y2) {
+            if let Some(cell) = self.get_mut(xpos + (x as i32 - left), ypos + (y as i32 - top)) {
+                op(cell, val.clone());
+            }
+        }
+    }
+
+    /// Like combine_region, but with this grid as the source, so the whole region is read before anything is written.
+    pub fn combine_own_region(
+        &mut self,
+        (x1, y1, x2, y2): (i32, i32, i32, i32),
+        (xpos, ypos): (i32, i32),
+        mut op: impl FnMut(&mut Value, Value),
+    ) {
+        let (left, top) = (x1.min(x2), y1.min(y2));
+        let region = self.region_positioned(x1, y1, x2, y2).map(|(pos, val)| (pos, val.clone())).collect::<Vec<_>>();
+        for ((x, y), val) in region {
+            if let Some(cell) = self.get_mut(xpos + (x as i32 - left), ypos + (y as i32 - top)) {
+                op(cell, val);
+            }
+        }
+    }
+
+    /// Shuffles the grid the same way ds_list_shuffle shuffles a list of its cells, taken column by column
+    /// in the order ds_grid_write writes them.
+    pub fn shuffle(&mut self, rand: &mut Random) {
+        let height = self.height();
+        shuffle(self.width() * height, rand, |id1, id2| {
+            let temp = std::mem::take(&mut self.grid[id1 / height][id1 % height]);
+            self.grid[id1 / height][id1 % height] = std::mem::replace(&mut self.grid[id2 / height][id2 % height], temp);
+        });
+    }
+
     pub fn width(&self) -> usize {
         self.grid.len()
     }
@@ -258,6 +305,81 @@ impl Grid {
     }
 }
 
+/// Adds a value to a cell like ds_grid_add, replacing the cell if the types don't match.
+pub fn add_cell(cell: &mut Value, val: Value) {
+    if cell.add_assign(val.clone()).is_err() {
+        *cell = val;
+    }
+}
+
+/// Multiplies a cell by a value like ds_grid_multiply, ignoring anything which isn't a real.
+pub fn multiply_cell(cell: &mut Value, val: Value) {
+    if let (Value::Real(cell), Value::Real(fac)) = (cell, val) {
+        *cell *= fac;
+    }
+}
+
+// Aggregates used by ds_grid_get_* and ds_grid_get_disk_*, which skip over strings.
+
+pub fn sum<'a>(values: impl Iterator<Item = &'a Value>) -> Real {
+    values.filter_map(Value::as_real).sum()
+}
+
+/// Shuffles `len` items like ds_list_shuffle, by making `len - 1` random swaps.
+pub fn shuffle(len: usize, rand: &mut Random, mut swap: impl FnMut(usize, usize)) {
+    for _ in 1..len {
+        let id1 = rand.next_int(len as u32 - 1);
+        let id2 = rand.next_int(len as u32 - 1);
+        swap(id1 as usize, id2 as usize);
+    }
+}
+
+pub fn max<'a>(values: impl Iterator<Item = &'a Value>) -> Real {
+    // weird fold needed due to NaN nonsense
+    values.filter_map(Value::as_real).fold(Real::from(-100000000), |acc, val| if val >= acc { val } else { acc })
+}
+
+pub fn min<'a>(values: impl Iterator<Item = &'a Value>) -> Real {
+    // weird fold needed due to NaN nonsense
+    values.filter_map(Value::as_real).fold(Real::from(100000000), |acc, val| if val <= acc { val } else { acc })
+}
+
+pub fn mean<'a>(values: impl Iterator<Item = &'a Value>) -> Real {
+    let (total, count) =
+        values.filter_map(Value::as_real).fold((Real::from(0.0), 0u32), |(total, count), val| (total + val, count + 1));
+    if count == 0 { Real::from(0.0) } else { total / Real::from(count) }
+}
+
+// Each kind of data structure has its own header: 101 for stacks, 201 for queues, 301 for lists and so on
+pub const STACK_HEADER: u32 = 101;
+pub const QUEUE_HEADER: u32 = 201;
+pub const LIST_HEADER: u32 = 301;
+pub const MAP_HEADER: u32 = 401;
+
+/// Serialises a queue to the hex format used by ds_queue_write, front to back.
+pub fn write_queue(queue: &Queue) -> String {
+    let mut output = hex::encode_upper(QUEUE_HEADER.to_le_bytes());
+    output.push_str(&hex::encode_upper((queue.len() as u32).to_le_bytes()));
+    output.extend(queue.iter().map(|v| hex::encode_upper(v.as_bytes())));
+    output
+}
+
+/// Deserialises a queue written by ds_queue_write, or returns None if the data is invalid.
+pub fn read_queue(mut reader: &[u8]) -> Option<Queue> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf).ok()?;
+    if u32::from_le_bytes(buf) != QUEUE_HEADER {
+        return None
+    }
+    reader.read_exact(&mut buf).ok()?;
+    let size = u32::from_le_bytes(buf) as usize;
+    let mut queue = Queue::with_capacity(size);
+    for _ in 0..size {
+        queue.push_back(Value::from_reader(&mut reader)?);
+    }
+    Some(queue)
+}
+
 pub fn eq(v1: &Value, v2: &Value, precision: Real) -> bool {
     match (v1, v2) {
         (Value::Real(x), Value::Real(y)) => (*x - *y).abs() <= precision,
@@ -280,3 +402,77 @@ pub fn cmp(v1: &Value, v2: &Value, precision: Real) -> Ordering {
         (Value::Str(_), Value::Real(_)) => Ordering::Greater,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid_from(columns: &[&[i32]]) -> Grid {
+        let mut grid = Grid::new(columns.len(), columns[0].len());
+        for (x, column) in columns.iter().enumerate() {
+            for (y, val) in column.iter().enumerate() {
+                *grid.get_mut(x as i32, y as i32).unwrap() = Value::from(*val);
+            }
+        }
+        grid
+    }
+
+    fn reals(grid: &Grid) -> Vec<f64> {
+        grid.all().map(|v| v.as_real().unwrap().into()).collect()
+    }
+
+    #[test]
+    fn queue_format() {
+        let queue: Queue = vec![Value::from(1), Value::from(b"a".to_vec())].into();
+        // GM8 layout: header, count, then each value from front to back
+        let hex = "C900000002000000\
+                   00000000000000000000F03F00000000\
+                   0100000000000000000000000100000061";
+        assert_eq!(write_queue(&queue), hex);
+        let read = read_queue(&hex::decode(hex).unwrap()).unwrap();
+        assert!(read.iter().zip(queue.iter()).all(|(a, b)| eq(a, b, Real::from(0.0))));
+        assert!(read_queue(&hex::decode("6500000000000000").unwrap()).is_none());
+        assert!(read_queue(&hex::decode("CA00000000000000").unwrap()).is_none());
+        assert!(read_queue(&hex::decode("C900000001000000").unwrap()).is_none());
+    }
+
+    #[test]
+    fn grid_regions() {
+        let src = grid_from(&[&[1, 2], &[3, 4]]);
+        let mut grid = grid_from(&[&[1, 1, 1], &[1, 1, 1], &[1, 1, 1]]);
+        grid.combine_region(&src, (1, 1, 0, 0), (1, 1), add_cell);
+        assert_eq!(reals(&grid), [1.0, 1.0, 1.0, 1.0, 2.0, 3.0, 1.0, 4.0, 5.0]);
+        grid.combine_region(&src, (0, 0, 5, 5), (-1, 2), multiply_cell);
+        assert_eq!(reals(&grid), [1.0, 1.0, 3.0, 1.0, 2.0, 3.0, 1.0, 4.0, 5.0]);
+
+        assert_eq!(f64::from(mean(grid.region(0, 0, 0, 2))), 5.0 / 3.0);
+        assert_eq!(f64::from(mean(grid.region(5, 5, 6, 6))), 0.0);
+        assert_eq!(f64::from(max(grid.disk(Real::from(1.0), Real::from(1.0), Real::from(1.0)))), 4.0);
+        assert_eq!(f64::from(sum(grid.disk(Real::from(1.0), Real::from(1.0), Real::from(1.0)))), 11.0);
+
+        // Overlapping a grid with itself reads the whole region first
+        let mut grid = grid_from(&[&[1, 2, 3], &[4, 5, 6]]);
+        grid.combine_own_region((0, 0, 1, 1), (0, 1), |cell, val| *cell = val);
+        assert_eq!(reals(&grid), [1.0, 1.0, 2.0, 4.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn grid_shuffle() {
+        let mut grid = grid_from(&[&[1, 2, 3], &[4, 5, 6]]);
+        let mut rand = Random::with_seed(1);
+        grid.shuffle(&mut rand);
+
+        // Should match ds_list_shuffle on the cells in column order
+        let mut list = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let mut list_rand = Random::with_seed(1);
+        shuffle(list.len(), &mut list_rand, |id1, id2| list.swap(id1, id2));
+        assert_eq!(reals(&grid), list);
+        assert!(rand == list_rand);
+
+        // Nothing to swap, so the RNG isn't touched
+        let mut grid = grid_from(&[&[1]]);
+        let mut rand = Random::with_seed(1);
+        grid.shuffle(&mut rand);
+        assert!(rand == Random::with_seed(1));
+    }
+}
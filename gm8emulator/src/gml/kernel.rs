@@ -5,8 +5,10 @@
 use crate::{
     action, asset,
     game::{
-        draw, external, gm_save::GMSave, model, particle, pathfinding, platform, replay, surface::Surface,
-        transition::UserTransition, view::View, Game, GameClock, GetAsset, PlayType, SceneChange, Version,
+        draw, external, gm_save::GMSave, model, particle, pathfinding,
+        platform::{self, VirtualDisplay},
+        replay, surface::Surface, transition::UserTransition, view::View, Game, GameClock, GetAsset, PlayType,
+        SceneChange, Version,
     },
     gml::{
         self,
@@ -45,6 +47,14 @@ macro_rules! _count_rep {
 
 include!(concat!(env!("OUT_DIR"), "/_apply_args.macro.rs"));
 
+/// Panics for a kernel function which hasn't been implemented yet.
+/// build.rs lists every function which uses this, which is how the unimplemented function policy knows about them.
+macro_rules! unimplemented_function {
+    ($name: ident) => {
+        unimplemented!(concat!("Called unimplemented kernel function ", stringify!($name)))
+    };
+}
+
 /// Helper macro to validate input arguments from a GML function.
 macro_rules! expect_args {
     ($args: expr, [$($x: ident),*]) => {{
@@ -97,90 +107,85 @@ fn rgb_to_hsv(colour: i32) -> (i32, i32, i32) {
 impl Game {
     pub fn display_get_width(&self, args: &[Value]) -> gml::Result<Value> {
         expect_args!(args, [])?;
-        #[cfg(target_os = "windows")]
-        if self.play_type == PlayType::Normal {
-            return platform::display_width().map(Value::from).ok_or_else(|| {
-                gml::Error::FunctionError("display_get_width".into(), "getting display width failed".into())
-            });
-        }
-        Ok(1280.into())
+        Ok(self.display.width.into())
     }
 
     pub fn display_get_height(&self, args: &[Value]) -> gml::Result<Value> {
         expect_args!(args, [])?;
-        #[cfg(target_os = "windows")]
-        if self.play_type == PlayType::Normal {
-            return platform::display_height().map(Value::from).ok_or_else(|| {
-                gml::Error::FunctionError("display_get_height".into(), "getting display height failed".into())
-            });
-        }
-        Ok(720.into())
+        Ok(self.display.height.into())
     }
 
     pub fn display_get_colordepth(&self, args: &[Value]) -> gml::Result<Value> {
         expect_args!(args, [])?;
-        #[cfg(target_os = "windows")]
-        if self.play_type == PlayType::Normal {
-            return platform::display_colour_depth().map(Value::from).ok_or_else(|| {
-                gml::Error::FunctionError("display_get_colordepth".into(), "getting display colour depth failed".into())
-            });
-        }
-        Ok(32.into())
+        Ok(self.display.colour_depth.into())
     }
 
     pub fn display_get_frequency(&self, args: &[Value]) -> gml::Result<Value> {
         expect_args!(args, [])?;
-        #[cfg(target_os = "windows")]
-        if self.play_type == PlayType::Normal {
-            return platform::display_frequency().map(Value::from).ok_or_else(|| {
-                gml::Error::FunctionError("display_get_frequency".into(), "getting display frequency failed".into())
-            });
-        }
-        Ok(60.into())
+        Ok(self.display.frequency.into())
     }
 
-    pub fn display_set_size(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function display_set_size")
+    pub fn display_set_size(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (width, height) = expect_args!(args, [int, int])?;
+        Ok(self.change_display(width, height, -1, -1).into())
     }
 
-    pub fn display_set_colordepth(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function display_set_colordepth")
+    pub fn display_set_colordepth(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let colour_depth = expect_args!(args, [int])?;
+        Ok(self.change_display(-1, -1, -1, colour_depth).into())
     }
 
-    pub fn display_set_frequency(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function display_set_frequency")
+    pub fn display_set_frequency(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let frequency = expect_args!(args, [int])?;
+        Ok(self.change_display(-1, -1, frequency, -1).into())
     }
 
-    pub fn display_set_all(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 4
-        unimplemented!("Called unimplemented kernel function display_set_all")
+    pub fn display_set_all(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (width, height, frequency, colour_depth) = expect_args!(args, [int, int, int, int])?;
+        Ok(self.change_display(width, height, frequency, colour_depth).into())
     }
 
-    pub fn display_test_all(&self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 4
-        unimplemented!("Called unimplemented kernel function display_test_all")
+    pub fn display_test_all(&self, args: &[Value]) -> gml::Result<Value> {
+        let (width, height, frequency, colour_depth) = expect_args!(args, [int, int, int, int])?;
+        Ok(VirtualDisplay::supports(width, height, frequency, colour_depth).into())
     }
 
-    pub fn display_reset(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        unimplemented!("Called unimplemented kernel function display_reset")
+    pub fn display_reset(&mut self, args: &[Value]) -> gml::Result<Value> {
+        expect_args!(args, [])?;
+        self.display = self.display_initial;
+        Ok(Default::default())
     }
 
-    pub fn display_mouse_get_x(&self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function display_mouse_get_x")
+    /// Changes the display settings, leaving any given as -1 unchanged. The host display is never changed.
+    fn change_display(&mut self, width: i32, height: i32, frequency: i32, colour_depth: i32) -> bool {
+        if !VirtualDisplay::supports(width, height, frequency, colour_depth) {
+            return false
+        }
+        let keep_or = |value: i32, current: u32| if value == -1 { current } else { value as u32 };
+        self.display = VirtualDisplay {
+            width: keep_or(width, self.display.width),
+            height: keep_or(height, self.display.height),
+            colour_depth: keep_or(colour_depth, self.display.colour_depth),
+            frequency: keep_or(frequency, self.display.frequency),
+        };
+        true
     }
 
-    pub fn display_mouse_get_y(&self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function display_mouse_get_y")
+    pub fn display_mouse_get_x(&self, args: &[Value]) -> gml::Result<Value> {
+        expect_args!(args, [])?;
+        Ok((self.window_offset_spoof.0 + self.input.mouse_x()).into())
     }
 
-    pub fn display_mouse_set(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function display_mouse_set")
+    pub fn display_mouse_get_y(&self, args: &[Value]) -> gml::Result<Value> {
+        expect_args!(args, [])?;
+        Ok((self.window_offset_spoof.1 + self.input.mouse_y()).into())
+    }
+
+    pub fn display_mouse_set(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (x, y) = expect_args!(args, [int, int])?;
+        let (window_x, window_y) = self.window_offset_spoof;
+        self.set_window_mouse(x - window_x, y - window_y);
+        Ok(Default::default())
     }
 
     pub fn window_set_visible(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -241,14 +246,23 @@ impl Game {
         Ok(self.window_icons.into())
     }
 
-    pub fn window_set_stayontop(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function window_set_stayontop")
+    pub fn window_set_stayontop(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let stay_on_top = expect_args!(args, [bool])?;
+        if stay_on_top != self.window_stayontop {
+            self.window_stayontop = stay_on_top;
+            if self.play_type == PlayType::Normal {
+                #[cfg(target_os = "linux")]
+                platform::set_stay_on_top(&self.connection, self.window.xid(), stay_on_top);
+                #[cfg(target_os = "windows")]
+                platform::set_stay_on_top(self.window.hwnd(), stay_on_top);
+            }
+        }
+        Ok(Default::default())
     }
 
-    pub fn window_get_stayontop(&self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function window_get_stayontop")
+    pub fn window_get_stayontop(&self, args: &[Value]) -> gml::Result<Value> {
+        expect_args!(args, [])?;
+        Ok(self.window_stayontop.into())
     }
 
     pub fn window_set_sizeable(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -350,19 +364,45 @@ impl Game {
         Ok(Default::default())
     }
 
-    pub fn window_set_rectangle(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 4
-        unimplemented!("Called unimplemented kernel function window_set_rectangle")
+    pub fn window_set_rectangle(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (x, y, width, height) = expect_args!(args, [int, int, int, int])?;
+        self.window_offset_spoof = (x, y);
+        if width > 0 && height > 0 {
+            self.window_inner_size = (width as u32, height as u32);
+            if self.play_type != PlayType::Record {
+                self.window.set_size((width as _, height as _));
+            }
+        }
+        Ok(Default::default())
     }
 
-    pub fn window_center(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // TODO: make this do something!
+    pub fn window_center(&mut self, args: &[Value]) -> gml::Result<Value> {
+        expect_args!(args, [])?;
+        self.center_window();
         Ok(Default::default())
     }
 
-    pub fn window_default(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function window_default")
+    pub fn window_default(&mut self, args: &[Value]) -> gml::Result<Value> {
+        expect_args!(args, [])?;
+        let (width, height) = match self.scaling {
+            Scaling::Fixed(scale) => {
+                ((f64::from(self.unscaled_width) * scale) as u32, (f64::from(self.unscaled_height) * scale) as u32)
+            },
+            _ => (self.unscaled_width, self.unscaled_height),
+        };
+        self.window_inner_size = (width, height);
+        if self.play_type != PlayType::Record {
+            self.window.set_size((width as _, height as _));
+        }
+        self.center_window();
+        Ok(Default::default())
+    }
+
+    /// Centres the window on the display the game thinks it's running on.
+    fn center_window(&mut self) {
+        let (width, height) = self.window_inner_size;
+        self.window_offset_spoof =
+            ((self.display.width as i32 - width as i32) / 2, (self.display.height as i32 - height as i32) / 2);
     }
 
     pub fn window_get_x(&self, args: &[Value]) -> gml::Result<Value> {
@@ -385,12 +425,21 @@ impl Game {
         Ok(self.window_inner_size.1.into())
     }
 
-    pub fn window_set_region_size(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 3
-        // unscaled_width and unscaled_height will need to be separated into framebuffer size
-        // and window region size for this to work
-        // probably keep the framebuffer size on the renderer and make a getter?
-        unimplemented!("Called unimplemented kernel function window_set_region_size")
+    pub fn window_set_region_size(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (width, height, adapt_window) = expect_args!(args, [int, int, bool])?;
+        if width > 0 && height > 0 && self.unscaled_width > 0 && self.unscaled_height > 0 {
+            // The region is always scaled uniformly, so use the largest scale which fits in the requested size
+            let scale = (f64::from(width) / f64::from(self.unscaled_width))
+                .min(f64::from(height) / f64::from(self.unscaled_height));
+            self.scaling = Scaling::Fixed(scale);
+            if adapt_window {
+                self.window_inner_size = (width as u32, height as u32);
+                if self.play_type != PlayType::Record {
+                    self.window.set_size((width as _, height as _));
+                }
+            }
+        }
+        Ok(Default::default())
     }
 
     pub fn window_get_region_width(&self, args: &[Value]) -> gml::Result<Value> {
@@ -446,39 +495,76 @@ impl Game {
         Ok(self.input.mouse_y().into())
     }
 
-    pub fn window_mouse_set(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function window_mouse_set")
+    pub fn window_mouse_set(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (x, y) = expect_args!(args, [int, int])?;
+        self.set_window_mouse(x, y);
+        Ok(Default::default())
     }
 
-    pub fn window_view_mouse_get_x(&self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function window_view_mouse_get_x")
+    /// Moves the mouse to a position relative to the window. The host's cursor is only moved during normal play.
+    fn set_window_mouse(&mut self, x: i32, y: i32) {
+        self.input.mouse_move_to((x, y));
+        if self.play_type == PlayType::Normal {
+            #[cfg(target_os = "linux")]
+            platform::set_mouse_position(&self.connection, self.window.xid(), x, y);
+            #[cfg(target_os = "windows")]
+            platform::set_mouse_position(self.window.hwnd(), x, y);
+        }
     }
 
-    pub fn window_view_mouse_get_y(&self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function window_view_mouse_get_y")
+    pub fn window_view_mouse_get_x(&self, args: &[Value]) -> gml::Result<Value> {
+        let view = expect_args!(args, [int])?;
+        match self.room.views.get(view as usize) {
+            Some(view) => Ok(view.transform_point(self.input.mouse_x(), self.input.mouse_y()).0.into()),
+            None => Ok(self.input.mouse_x().into()),
+        }
     }
 
-    pub fn window_view_mouse_set(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function window_view_mouse_set")
+    pub fn window_view_mouse_get_y(&self, args: &[Value]) -> gml::Result<Value> {
+        let view = expect_args!(args, [int])?;
+        match self.room.views.get(view as usize) {
+            Some(view) => Ok(view.transform_point(self.input.mouse_x(), self.input.mouse_y()).1.into()),
+            None => Ok(self.input.mouse_y().into()),
+        }
     }
 
-    pub fn window_views_mouse_get_x(&self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function window_views_mouse_get_x")
+    pub fn window_view_mouse_set(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (view, x, y) = expect_args!(args, [int, int, int])?;
+        let (x, y) = match self.room.views.get(view as usize) {
+            Some(view) => view.untransform_point(x, y),
+            None => (x, y),
+        };
+        self.set_window_mouse(x, y);
+        Ok(Default::default())
     }
 
-    pub fn window_views_mouse_get_y(&self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function window_views_mouse_get_y")
+    pub fn window_views_mouse_get_x(&self, args: &[Value]) -> gml::Result<Value> {
+        expect_args!(args, [])?;
+        Ok(self.get_mouse_in_room().0.into())
     }
 
-    pub fn window_views_mouse_set(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function window_views_mouse_set")
+    pub fn window_views_mouse_get_y(&self, args: &[Value]) -> gml::Result<Value> {
+        expect_args!(args, [])?;
+        Ok(self.get_mouse_in_room().1.into())
+    }
+
+    pub fn window_views_mouse_set(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (x, y) = expect_args!(args, [int, int])?;
+        let (x, y) = if self.room.views_enabled {
+            // Same view selection as mouse_x and mouse_y
+            let (mouse_x, mouse_y) = (self.input.mouse_x(), self.input.mouse_y());
+            match self.room.views.iter().rev().find(|view| view.visible && view.contains_point(mouse_x, mouse_y)) {
+                Some(view) => view.untransform_point(x, y),
+                None => match self.room.views.iter().find(|view| view.visible) {
+                    Some(view) => view.untransform_point(x, y),
+                    None => (x, y),
+                },
+            }
+        } else {
+            (x, y)
+        };
+        self.set_window_mouse(x, y);
+        Ok(Default::default())
     }
 
     pub fn set_synchronization(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -2081,9 +2167,9 @@ impl Game {
         Ok(self.last_tile_id.into())
     }
 
-    pub fn tile_find(&self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function tile_find")
+    pub fn tile_find(&self, args: &[Value]) -> gml::Result<Value> {
+        let (x, y, foreground) = expect_args!(args, [real, real, bool])?;
+        Ok(self.tile_at(x, y, foreground).map(|handle| self.room.tile_list.get(handle).id.get()).unwrap_or(-1).into())
     }
 
     pub fn tile_exists(&self, args: &[Value]) -> gml::Result<Value> {
@@ -2101,9 +2187,43 @@ impl Game {
         }
     }
 
-    pub fn tile_delete_at(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function tile_delete_at")
+    pub fn tile_delete_at(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (x, y, foreground) = expect_args!(args, [real, real, bool])?;
+        if let Some(handle) = self.tile_at(x, y, foreground) {
+            self.room.tile_list.remove(handle);
+        }
+        Ok(Default::default())
+    }
+
+    /// Finds the frontmost or backmost tile covering a point, for tile_find and tile_delete_at.
+    fn tile_at(&self, x: Real, y: Real, foreground: bool) -> Option<usize> {
+        let mut found: Option<(usize, Real)> = None;
+        let mut iter_tile = self.room.tile_list.iter_by_drawing();
+        while let Some(handle) = iter_tile.next(&self.room.tile_list) {
+            let tile = self.room.tile_list.get(handle);
+            if Self::tile_contains_point(self.gm_version, tile, x, y) {
+                let depth = tile.depth.get();
+                // on equal depths, whichever is drawn last counts as being in front
+                let replace = match found {
+                    Some((_, found_depth)) => if foreground { depth <= found_depth } else { depth > found_depth },
+                    None => true,
+                };
+                if replace {
+                    found = Some((handle, depth));
+                }
+            }
+        }
+        found.map(|(handle, _)| handle)
+    }
+
+    /// Checks if a point is on a tile. GM8.0 multiplies the tile's size by zero instead of its scale,
+    /// so nothing is ever found there.
+    fn tile_contains_point(gm_version: Version, tile: &Tile, x: Real, y: Real) -> bool {
+        let use_scaling = gm_version == Version::GameMaker8_1; // 8.1 bugfix
+        x >= tile.x.get()
+            && x < tile.x.get() + if use_scaling { tile.xscale.get() } else { 0.into() } * tile.width.get().into()
+            && y >= tile.y.get()
+            && y < tile.y.get() + if use_scaling { tile.yscale.get() } else { 0.into() } * tile.height.get().into()
     }
 
     pub fn tile_layer_hide(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -2151,16 +2271,10 @@ impl Game {
 
     pub fn tile_layer_find(&self, args: &[Value]) -> gml::Result<Value> {
         let (depth, x, y) = expect_args!(args, [real, real, real])?;
-        let use_scaling = self.gm_version == Version::GameMaker8_1; // 8.1 bugfix
         let mut iter_tile = self.room.tile_list.iter_by_drawing();
         while let Some(handle) = iter_tile.next(&self.room.tile_list) {
             let tile = self.room.tile_list.get(handle);
-            if tile.depth.get() == depth
-                && x >= tile.x.get()
-                && x < tile.x.get() + if use_scaling { tile.xscale.get() } else { 0.into() } * tile.width.get().into()
-                && y >= tile.y.get()
-                && y < tile.y.get() + if use_scaling { tile.yscale.get() } else { 0.into() } * tile.height.get().into()
-            {
+            if tile.depth.get() == depth && Self::tile_contains_point(self.gm_version, tile, x, y) {
                 return Ok(tile.id.get().into())
             }
         }
@@ -2169,14 +2283,10 @@ impl Game {
 
     pub fn tile_layer_delete_at(&mut self, args: &[Value]) -> gml::Result<Value> {
         let (depth, x, y) = expect_args!(args, [real, real, real])?;
-        let use_scaling = self.gm_version == Version::GameMaker8_1; // 8.1 bugfix
-        self.room.tile_list.remove_with(|tile| {
-            tile.depth.get() == depth
-                && x >= tile.x.get()
-                && x < tile.x.get() + if use_scaling { tile.xscale.get() } else { 0.into() } * tile.width.get().into()
-                && y >= tile.y.get()
-                && y < tile.y.get() + if use_scaling { tile.yscale.get() } else { 0.into() } * tile.height.get().into()
-        });
+        let gm_version = self.gm_version;
+        self.room
+            .tile_list
+            .remove_with(|tile| tile.depth.get() == depth && Self::tile_contains_point(gm_version, tile, x, y));
         Ok(Default::default())
     }
 
@@ -3093,37 +3203,37 @@ impl Game {
 
     pub fn action_splash_text(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function action_splash_text")
+        unimplemented_function!(action_splash_text)
     }
 
     pub fn action_splash_image(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function action_splash_image")
+        unimplemented_function!(action_splash_image)
     }
 
     pub fn action_splash_web(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function action_splash_web")
+        unimplemented_function!(action_splash_web)
     }
 
     pub fn action_splash_settings(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 5
-        unimplemented!("Called unimplemented kernel function action_splash_settings")
+        unimplemented_function!(action_splash_settings)
     }
 
     pub fn action_replace_sprite(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function action_replace_sprite")
+        unimplemented_function!(action_replace_sprite)
     }
 
     pub fn action_replace_sound(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function action_replace_sound")
+        unimplemented_function!(action_replace_sound)
     }
 
     pub fn action_replace_background(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function action_replace_background")
+        unimplemented_function!(action_replace_background)
     }
 
     pub fn action_if_empty(&mut self, context: &mut Context, args: &[Value]) -> gml::Result<Value> {
@@ -3229,7 +3339,7 @@ impl Game {
                 ],
                 5,
             );
-            self.execute(&instructions, &mut new_context)?;
+            self.execute_code(&instructions, &mut new_context)?;
             Ok(new_context.return_value)
         } else {
             Err(gml::Error::NonexistentAsset(asset::Type::Script, script_id))
@@ -3307,7 +3417,7 @@ impl Game {
 
     pub fn action_highscore_show(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 5
-        unimplemented!("Called unimplemented kernel function action_highscore_show")
+        unimplemented_function!(action_highscore_show)
     }
 
     pub fn action_set_life(&mut self, context: &mut Context, args: &[Value]) -> gml::Result<Value> {
@@ -3628,7 +3738,7 @@ impl Game {
 
     pub fn action_webpage(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function action_webpage")
+        unimplemented_function!(action_webpage)
     }
 
     pub fn action_draw_sprite(&mut self, context: &mut Context, args: &[Value]) -> gml::Result<Value> {
@@ -4899,7 +5009,7 @@ impl Game {
 
     pub fn mp_linear_path_object(&mut self, _context: &mut Context, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 5
-        unimplemented!("Called unimplemented kernel function mp_linear_path_object")
+        unimplemented_function!(mp_linear_path_object)
     }
 
     pub fn mp_potential_settings(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -4934,7 +5044,7 @@ impl Game {
 
     pub fn mp_potential_path(&mut self, _context: &mut Context, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 6
-        unimplemented!("Called unimplemented kernel function mp_potential_path")
+        unimplemented_function!(mp_potential_path)
     }
 
     pub fn mp_potential_step_object(&mut self, context: &mut Context, args: &[Value]) -> gml::Result<Value> {
@@ -4956,7 +5066,7 @@ impl Game {
 
     pub fn mp_potential_path_object(&mut self, _context: &mut Context, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 6
-        unimplemented!("Called unimplemented kernel function mp_potential_path_object")
+        unimplemented_function!(mp_potential_path_object)
     }
 
     pub fn mp_grid_create(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -5091,12 +5201,12 @@ impl Game {
 
     pub fn mp_grid_add_instances(&mut self, _context: &mut Context, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function mp_grid_add_instances")
+        unimplemented_function!(mp_grid_add_instances)
     }
 
     pub fn mp_grid_path(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 7
-        unimplemented!("Called unimplemented kernel function mp_grid_path")
+        unimplemented_function!(mp_grid_path)
     }
 
     pub fn mp_grid_draw(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -5522,7 +5632,7 @@ impl Game {
 
     pub fn position_change(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 4
-        unimplemented!("Called unimplemented kernel function position_change")
+        unimplemented_function!(position_change)
     }
 
     pub fn instance_deactivate_all(&mut self, context: &mut Context, args: &[Value]) -> gml::Result<Value> {
@@ -6331,7 +6441,7 @@ impl Game {
 
     pub fn file_attributes(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function file_attributes")
+        unimplemented_function!(file_attributes)
     }
 
     pub fn filename_name(args: &[Value]) -> gml::Result<Value> {
@@ -6485,7 +6595,7 @@ impl Game {
 
     pub fn execute_shell(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function execute_shell")
+        unimplemented_function!(execute_shell)
     }
 
     pub fn parameter_count(&self, args: &[Value]) -> gml::Result<Value> {
@@ -6518,57 +6628,57 @@ impl Game {
 
     pub fn registry_write_string(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function registry_write_string")
+        unimplemented_function!(registry_write_string)
     }
 
     pub fn registry_write_real(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function registry_write_real")
+        unimplemented_function!(registry_write_real)
     }
 
     pub fn registry_read_string(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function registry_read_string")
+        unimplemented_function!(registry_read_string)
     }
 
     pub fn registry_read_real(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function registry_read_real")
+        unimplemented_function!(registry_read_real)
     }
 
     pub fn registry_exists(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function registry_exists")
+        unimplemented_function!(registry_exists)
     }
 
     pub fn registry_write_string_ext(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function registry_write_string_ext")
+        unimplemented_function!(registry_write_string_ext)
     }
 
     pub fn registry_write_real_ext(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function registry_write_real_ext")
+        unimplemented_function!(registry_write_real_ext)
     }
 
     pub fn registry_read_string_ext(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function registry_read_string_ext")
+        unimplemented_function!(registry_read_string_ext)
     }
 
     pub fn registry_read_real_ext(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function registry_read_real_ext")
+        unimplemented_function!(registry_read_real_ext)
     }
 
     pub fn registry_exists_ext(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function registry_exists_ext")
+        unimplemented_function!(registry_exists_ext)
     }
 
     pub fn registry_set_root(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function registry_set_root")
+        unimplemented_function!(registry_set_root)
     }
 
     pub fn ini_open(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -6716,7 +6826,7 @@ impl Game {
     }
 
     pub fn disk_free(&self, args: &[Value]) -> gml::Result<Value> {
-        #[cfg(target_os = "windows")]
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
         if self.play_type == PlayType::Normal {
             let path = match args.get(0).clone() {
                 Some(Value::Str(p)) => p.as_ref().get(0).map(|&x| x as char),
@@ -6730,7 +6840,7 @@ impl Game {
     }
 
     pub fn disk_size(&self, args: &[Value]) -> gml::Result<Value> {
-        #[cfg(target_os = "windows")]
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
         if self.play_type == PlayType::Normal {
             let path = match args.get(0).clone() {
                 Some(Value::Str(p)) => p.as_ref().get(0).map(|&x| x as char),
@@ -6744,122 +6854,122 @@ impl Game {
 
     pub fn splash_set_caption(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function splash_set_caption")
+        unimplemented_function!(splash_set_caption)
     }
 
     pub fn splash_set_fullscreen(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function splash_set_fullscreen")
+        unimplemented_function!(splash_set_fullscreen)
     }
 
     pub fn splash_set_border(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function splash_set_border")
+        unimplemented_function!(splash_set_border)
     }
 
     pub fn splash_set_size(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function splash_set_size")
+        unimplemented_function!(splash_set_size)
     }
 
     pub fn splash_set_position(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function splash_set_position")
+        unimplemented_function!(splash_set_position)
     }
 
     pub fn splash_set_adapt(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function splash_set_adapt")
+        unimplemented_function!(splash_set_adapt)
     }
 
     pub fn splash_set_top(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function splash_set_top")
+        unimplemented_function!(splash_set_top)
     }
 
     pub fn splash_set_color(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function splash_set_color")
+        unimplemented_function!(splash_set_color)
     }
 
     pub fn splash_set_main(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function splash_set_main")
+        unimplemented_function!(splash_set_main)
     }
 
     pub fn splash_set_scale(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function splash_set_scale")
+        unimplemented_function!(splash_set_scale)
     }
 
     pub fn splash_set_cursor(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function splash_set_cursor")
+        unimplemented_function!(splash_set_cursor)
     }
 
     pub fn splash_set_interrupt(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function splash_set_interrupt")
+        unimplemented_function!(splash_set_interrupt)
     }
 
     pub fn splash_set_stop_key(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function splash_set_stop_key")
+        unimplemented_function!(splash_set_stop_key)
     }
 
     pub fn splash_set_close_button(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function splash_set_close_button")
+        unimplemented_function!(splash_set_close_button)
     }
 
     pub fn splash_set_stop_mouse(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function splash_set_stop_mouse")
+        unimplemented_function!(splash_set_stop_mouse)
     }
 
     pub fn splash_show_video(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function splash_show_video")
+        unimplemented_function!(splash_show_video)
     }
 
     pub fn splash_show_image(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function splash_show_image")
+        unimplemented_function!(splash_show_image)
     }
 
     pub fn splash_show_text(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function splash_show_text")
+        unimplemented_function!(splash_show_text)
     }
 
     pub fn splash_show_web(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function splash_show_web")
+        unimplemented_function!(splash_show_web)
     }
 
     pub fn show_image(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function show_image")
+        unimplemented_function!(show_image)
     }
 
     pub fn show_video(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function show_video")
+        unimplemented_function!(show_video)
     }
 
     pub fn show_text(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 4
-        unimplemented!("Called unimplemented kernel function show_text")
+        unimplemented_function!(show_text)
     }
 
     pub fn show_message(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function show_message")
+        unimplemented_function!(show_message)
     }
 
     pub fn show_question(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function show_question")
+        unimplemented_function!(show_question)
     }
 
     pub fn show_error(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -6869,211 +6979,211 @@ impl Game {
 
     pub fn show_info(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function show_info")
+        unimplemented_function!(show_info)
     }
 
     pub fn load_info(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function load_info")
+        unimplemented_function!(load_info)
     }
 
     pub fn highscore_show(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function highscore_show")
+        unimplemented_function!(highscore_show)
     }
 
     pub fn highscore_set_background(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function highscore_set_background")
+        unimplemented_function!(highscore_set_background)
     }
 
     pub fn highscore_set_border(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function highscore_set_border")
+        unimplemented_function!(highscore_set_border)
     }
 
     pub fn highscore_set_font(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function highscore_set_font")
+        unimplemented_function!(highscore_set_font)
     }
 
     pub fn highscore_set_strings(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function highscore_set_strings")
+        unimplemented_function!(highscore_set_strings)
     }
 
     pub fn highscore_set_colors(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function highscore_set_colors")
+        unimplemented_function!(highscore_set_colors)
     }
 
     pub fn highscore_show_ext(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 7
-        unimplemented!("Called unimplemented kernel function highscore_show_ext")
+        unimplemented_function!(highscore_show_ext)
     }
 
     pub fn highscore_clear(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function highscore_clear")
+        unimplemented_function!(highscore_clear)
     }
 
     pub fn highscore_add(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function highscore_add")
+        unimplemented_function!(highscore_add)
     }
 
     pub fn highscore_add_current(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function highscore_add_current")
+        unimplemented_function!(highscore_add_current)
     }
 
     pub fn highscore_value(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function highscore_value")
+        unimplemented_function!(highscore_value)
     }
 
     pub fn highscore_name(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function highscore_name")
+        unimplemented_function!(highscore_name)
     }
 
     pub fn draw_highscore(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 4
-        unimplemented!("Called unimplemented kernel function draw_highscore")
+        unimplemented_function!(draw_highscore)
     }
 
     pub fn show_message_ext(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 4
-        unimplemented!("Called unimplemented kernel function show_message_ext")
+        unimplemented_function!(show_message_ext)
     }
 
     pub fn message_background(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        //unimplemented!("Called unimplemented kernel function message_background")
+        //unimplemented_function!(message_background)
         // TODO
         Ok(Default::default())
     }
 
     pub fn message_button(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        //unimplemented!("Called unimplemented kernel function message_button")
+        //unimplemented_function!(message_button)
         // TODO
         Ok(Default::default())
     }
 
     pub fn message_alpha(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        //unimplemented!("Called unimplemented kernel function message_alpha")
+        //unimplemented_function!(message_alpha)
         // TODO
         Ok(Default::default())
     }
 
     pub fn message_text_font(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 4
-        //unimplemented!("Called unimplemented kernel function message_text_font")
+        //unimplemented_function!(message_text_font)
         // TODO
         Ok(Default::default())
     }
 
     pub fn message_button_font(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 4
-        //unimplemented!("Called unimplemented kernel function message_button_font")
+        //unimplemented_function!(message_button_font)
         // TODO
         Ok(Default::default())
     }
 
     pub fn message_input_font(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 4
-        //unimplemented!("Called unimplemented kernel function message_input_font")
+        //unimplemented_function!(message_input_font)
         // TODO
         Ok(Default::default())
     }
 
     pub fn message_text_charset(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        //unimplemented!("Called unimplemented kernel function message_text_charset")
+        //unimplemented_function!(message_text_charset)
         // TODO
         Ok(Default::default())
     }
 
     pub fn message_mouse_color(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        //unimplemented!("Called unimplemented kernel function message_mouse_color")
+        //unimplemented_function!(message_mouse_color)
         // TODO
         Ok(Default::default())
     }
 
     pub fn message_input_color(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        //unimplemented!("Called unimplemented kernel function message_input_color")
+        //unimplemented_function!(message_input_color)
         // TODO
         Ok(Default::default())
     }
 
     pub fn message_position(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        //unimplemented!("Called unimplemented kernel function message_position")
+        //unimplemented_function!(message_position)
         // TODO
         Ok(Default::default())
     }
 
     pub fn message_size(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        //unimplemented!("Called unimplemented kernel function message_size")
+        //unimplemented_function!(message_size)
         // TODO
         Ok(Default::default())
     }
 
     pub fn message_caption(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        //unimplemented!("Called unimplemented kernel function message_caption")
+        //unimplemented_function!(message_caption)
         // TODO
         Ok(Default::default())
     }
 
     pub fn show_menu(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function show_menu")
+        unimplemented_function!(show_menu)
     }
 
     pub fn show_menu_pos(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 4
-        unimplemented!("Called unimplemented kernel function show_menu_pos")
+        unimplemented_function!(show_menu_pos)
     }
 
     pub fn get_integer(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function get_integer")
+        unimplemented_function!(get_integer)
     }
 
     pub fn get_string(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function get_string")
+        unimplemented_function!(get_string)
     }
 
     pub fn get_color(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function get_color")
+        unimplemented_function!(get_color)
     }
 
     pub fn get_open_filename(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function get_open_filename")
+        unimplemented_function!(get_open_filename)
     }
 
     pub fn get_save_filename(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function get_save_filename")
+        unimplemented_function!(get_save_filename)
     }
 
     pub fn get_directory(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function get_directory")
+        unimplemented_function!(get_directory)
     }
 
     pub fn get_directory_alt(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function get_directory_alt")
+        unimplemented_function!(get_directory_alt)
     }
 
     // NB: This function is constant because numlock state is tracked.
@@ -7094,7 +7204,8 @@ impl Game {
         //     self.input.button_press(vk, true);
         // }
         // Ok(Default::default())
-        todo!() // should go on next event poll
+        // should go on next event poll
+        unimplemented_function!(keyboard_key_press)
     }
 
     pub fn keyboard_key_release(&mut self, _args: &[Value]) -> gml::Result<Value> {
@@ -7103,7 +7214,8 @@ impl Game {
         //     self.input.button_release(vk, true);
         // }
         // Ok(Default::default())
-        todo!() // should go on next event poll
+        // should go on next event poll
+        unimplemented_function!(keyboard_key_release)
     }
 
     pub fn keyboard_set_map(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -7197,98 +7309,98 @@ impl Game {
 
     pub fn joystick_exists(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        //unimplemented!("Called unimplemented kernel function joystick_exists")
+        //unimplemented_function!(joystick_exists)
         // TODO
         Ok(gml::FALSE.into())
     }
 
     pub fn joystick_direction(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        //unimplemented!("Called unimplemented kernel function joystick_direction")
+        //unimplemented_function!(joystick_direction)
         // TODO
         Ok(101.into())
     }
 
     pub fn joystick_name(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        //unimplemented!("Called unimplemented kernel function joystick_name")
+        //unimplemented_function!(joystick_name)
         // TODO
         Ok("".into())
     }
 
     pub fn joystick_axes(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        //unimplemented!("Called unimplemented kernel function joystick_axes")
+        //unimplemented_function!(joystick_axes)
         // TODO
         Ok(0.into())
     }
 
     pub fn joystick_buttons(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        //unimplemented!("Called unimplemented kernel function joystick_buttons")
+        //unimplemented_function!(joystick_buttons)
         // TODO
         Ok(0.into())
     }
 
     pub fn joystick_has_pov(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        //unimplemented!("Called unimplemented kernel function joystick_has_pov")
+        //unimplemented_function!(joystick_has_pov)
         // TODO
         Ok(gml::FALSE.into())
     }
 
     pub fn joystick_check_button(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        //unimplemented!("Called unimplemented kernel function joystick_check_button")
+        //unimplemented_function!(joystick_check_button)
         // TODO
         Ok(gml::FALSE.into())
     }
 
     pub fn joystick_xpos(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        //unimplemented!("Called unimplemented kernel function joystick_xpos")
+        //unimplemented_function!(joystick_xpos)
         // TODO
         Ok(0.into())
     }
 
     pub fn joystick_ypos(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        //unimplemented!("Called unimplemented kernel function joystick_ypos")
+        //unimplemented_function!(joystick_ypos)
         // TODO
         Ok(0.into())
     }
 
     pub fn joystick_zpos(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        //unimplemented!("Called unimplemented kernel function joystick_zpos")
+        //unimplemented_function!(joystick_zpos)
         // TODO
         Ok(0.into())
     }
 
     pub fn joystick_rpos(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        //unimplemented!("Called unimplemented kernel function joystick_rpos")
+        //unimplemented_function!(joystick_rpos)
         // TODO
         Ok(0.into())
     }
 
     pub fn joystick_upos(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        //unimplemented!("Called unimplemented kernel function joystick_upos")
+        //unimplemented_function!(joystick_upos)
         // TODO
         Ok(0.into())
     }
 
     pub fn joystick_vpos(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        //unimplemented!("Called unimplemented kernel function joystick_vpos")
+        //unimplemented_function!(joystick_vpos)
         // TODO
         Ok(0.into())
     }
 
     pub fn joystick_pov(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        //unimplemented!("Called unimplemented kernel function joystick_pov")
+        //unimplemented_function!(joystick_pov)
         // TODO
         Ok((-1).into())
     }
@@ -7340,147 +7452,147 @@ impl Game {
 
     pub fn mouse_wait(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function mouse_wait")
+        unimplemented_function!(mouse_wait)
     }
 
     pub fn mplay_init_ipx(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function mplay_init_ipx")
+        unimplemented_function!(mplay_init_ipx)
     }
 
     pub fn mplay_init_tcpip(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function mplay_init_tcpip")
+        unimplemented_function!(mplay_init_tcpip)
     }
 
     pub fn mplay_init_modem(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function mplay_init_modem")
+        unimplemented_function!(mplay_init_modem)
     }
 
     pub fn mplay_init_serial(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 5
-        unimplemented!("Called unimplemented kernel function mplay_init_serial")
+        unimplemented_function!(mplay_init_serial)
     }
 
     pub fn mplay_connect_status(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function mplay_connect_status")
+        unimplemented_function!(mplay_connect_status)
     }
 
     pub fn mplay_end(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function mplay_end")
+        unimplemented_function!(mplay_end)
     }
 
     pub fn mplay_session_mode(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function mplay_session_mode")
+        unimplemented_function!(mplay_session_mode)
     }
 
     pub fn mplay_session_create(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function mplay_session_create")
+        unimplemented_function!(mplay_session_create)
     }
 
     pub fn mplay_session_find(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function mplay_session_find")
+        unimplemented_function!(mplay_session_find)
     }
 
     pub fn mplay_session_name(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function mplay_session_name")
+        unimplemented_function!(mplay_session_name)
     }
 
     pub fn mplay_session_join(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function mplay_session_join")
+        unimplemented_function!(mplay_session_join)
     }
 
     pub fn mplay_session_status(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function mplay_session_status")
+        unimplemented_function!(mplay_session_status)
     }
 
     pub fn mplay_session_end(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function mplay_session_end")
+        unimplemented_function!(mplay_session_end)
     }
 
     pub fn mplay_player_find(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function mplay_player_find")
+        unimplemented_function!(mplay_player_find)
     }
 
     pub fn mplay_player_name(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function mplay_player_name")
+        unimplemented_function!(mplay_player_name)
     }
 
     pub fn mplay_player_id(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function mplay_player_id")
+        unimplemented_function!(mplay_player_id)
     }
 
     pub fn mplay_data_write(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function mplay_data_write")
+        unimplemented_function!(mplay_data_write)
     }
 
     pub fn mplay_data_read(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function mplay_data_read")
+        unimplemented_function!(mplay_data_read)
     }
 
     pub fn mplay_data_mode(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function mplay_data_mode")
+        unimplemented_function!(mplay_data_mode)
     }
 
     pub fn mplay_message_send(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function mplay_message_send")
+        unimplemented_function!(mplay_message_send)
     }
 
     pub fn mplay_message_send_guaranteed(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function mplay_message_send_guaranteed")
+        unimplemented_function!(mplay_message_send_guaranteed)
     }
 
     pub fn mplay_message_receive(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function mplay_message_receive")
+        unimplemented_function!(mplay_message_receive)
     }
 
     pub fn mplay_message_id(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function mplay_message_id")
+        unimplemented_function!(mplay_message_id)
     }
 
     pub fn mplay_message_value(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function mplay_message_value")
+        unimplemented_function!(mplay_message_value)
     }
 
     pub fn mplay_message_player(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function mplay_message_player")
+        unimplemented_function!(mplay_message_player)
     }
 
     pub fn mplay_message_name(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function mplay_message_name")
+        unimplemented_function!(mplay_message_name)
     }
 
     pub fn mplay_message_count(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function mplay_message_count")
+        unimplemented_function!(mplay_message_count)
     }
 
     pub fn mplay_message_clear(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function mplay_message_clear")
+        unimplemented_function!(mplay_message_clear)
     }
 
     pub fn mplay_ipaddress(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -7608,92 +7720,92 @@ impl Game {
 
     pub fn external_define0(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function external_define0")
+        unimplemented_function!(external_define0)
     }
 
     pub fn external_call0(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function external_call0")
+        unimplemented_function!(external_call0)
     }
 
     pub fn external_define1(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 4
-        unimplemented!("Called unimplemented kernel function external_define1")
+        unimplemented_function!(external_define1)
     }
 
     pub fn external_call1(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function external_call1")
+        unimplemented_function!(external_call1)
     }
 
     pub fn external_define2(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 5
-        unimplemented!("Called unimplemented kernel function external_define2")
+        unimplemented_function!(external_define2)
     }
 
     pub fn external_call2(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function external_call2")
+        unimplemented_function!(external_call2)
     }
 
     pub fn external_define3(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 6
-        unimplemented!("Called unimplemented kernel function external_define3")
+        unimplemented_function!(external_define3)
     }
 
     pub fn external_call3(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 4
-        unimplemented!("Called unimplemented kernel function external_call3")
+        unimplemented_function!(external_call3)
     }
 
     pub fn external_define4(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 7
-        unimplemented!("Called unimplemented kernel function external_define4")
+        unimplemented_function!(external_define4)
     }
 
     pub fn external_call4(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 5
-        unimplemented!("Called unimplemented kernel function external_call4")
+        unimplemented_function!(external_call4)
     }
 
     pub fn external_define5(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function external_define5")
+        unimplemented_function!(external_define5)
     }
 
     pub fn external_call5(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 6
-        unimplemented!("Called unimplemented kernel function external_call5")
+        unimplemented_function!(external_call5)
     }
 
     pub fn external_define6(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function external_define6")
+        unimplemented_function!(external_define6)
     }
 
     pub fn external_call6(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 7
-        unimplemented!("Called unimplemented kernel function external_call6")
+        unimplemented_function!(external_call6)
     }
 
     pub fn external_define7(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function external_define7")
+        unimplemented_function!(external_define7)
     }
 
     pub fn external_call7(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 8
-        unimplemented!("Called unimplemented kernel function external_call7")
+        unimplemented_function!(external_call7)
     }
 
     pub fn external_define8(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function external_define8")
+        unimplemented_function!(external_define8)
     }
 
     pub fn external_call8(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 9
-        unimplemented!("Called unimplemented kernel function external_call8")
+        unimplemented_function!(external_call8)
     }
 
     pub fn execute_string(&mut self, context: &mut Context, args: &[Value]) -> gml::Result<Value> {
@@ -7706,10 +7818,10 @@ impl Game {
                     }
                     // Note: GM8 does not update the argument_count here to (args.len() - 1) as it should
                     let mut new_context = Context::copy_with_args(context, new_args, context.argument_count);
-                    self.execute(&instrs, &mut new_context)?;
+                    self.execute_code(&instrs, &mut new_context)?;
                     Ok(new_context.return_value)
                 },
-                Err(e) => Err(gml::Error::FunctionError("execute_string".into(), e.message)),
+                Err(e) => Err(gml::Error::FunctionError("execute_string".into(), e.to_string())),
             }
         } else {
             // eg execute_string(42) - does nothing, returns 0
@@ -7908,17 +8020,17 @@ impl Game {
 
     pub fn clipboard_has_text(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function clipboard_has_text")
+        unimplemented_function!(clipboard_has_text)
     }
 
     pub fn clipboard_set_text(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function clipboard_set_text")
+        unimplemented_function!(clipboard_set_text)
     }
 
     pub fn clipboard_get_text(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function clipboard_get_text")
+        unimplemented_function!(clipboard_get_text)
     }
 
     pub fn date_current_datetime(&self, args: &[Value]) -> gml::Result<Value> {
@@ -7970,12 +8082,12 @@ impl Game {
 
     pub fn date_inc_year(_args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function date_inc_year")
+        unimplemented_function!(date_inc_year)
     }
 
     pub fn date_inc_month(_args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function date_inc_month")
+        unimplemented_function!(date_inc_month)
     }
 
     pub fn date_inc_week(args: &[Value]) -> gml::Result<Value> {
@@ -8069,97 +8181,97 @@ impl Game {
 
     pub fn date_year_span(_args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function date_year_span")
+        unimplemented_function!(date_year_span)
     }
 
     pub fn date_month_span(_args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function date_month_span")
+        unimplemented_function!(date_month_span)
     }
 
     pub fn date_week_span(_args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function date_week_span")
+        unimplemented_function!(date_week_span)
     }
 
     pub fn date_day_span(_args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function date_day_span")
+        unimplemented_function!(date_day_span)
     }
 
     pub fn date_hour_span(_args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function date_hour_span")
+        unimplemented_function!(date_hour_span)
     }
 
     pub fn date_minute_span(_args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function date_minute_span")
+        unimplemented_function!(date_minute_span)
     }
 
     pub fn date_second_span(_args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function date_second_span")
+        unimplemented_function!(date_second_span)
     }
 
     pub fn date_compare_datetime(_args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function date_compare_datetime")
+        unimplemented_function!(date_compare_datetime)
     }
 
     pub fn date_compare_date(_args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function date_compare_date")
+        unimplemented_function!(date_compare_date)
     }
 
     pub fn date_compare_time(_args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function date_compare_time")
+        unimplemented_function!(date_compare_time)
     }
 
     pub fn date_date_of(_args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function date_date_of")
+        unimplemented_function!(date_date_of)
     }
 
     pub fn date_time_of(_args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function date_time_of")
+        unimplemented_function!(date_time_of)
     }
 
     pub fn date_datetime_string(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function date_datetime_string")
+        unimplemented_function!(date_datetime_string)
     }
 
     pub fn date_date_string(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function date_date_string")
+        unimplemented_function!(date_date_string)
     }
 
     pub fn date_time_string(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function date_time_string")
+        unimplemented_function!(date_time_string)
     }
 
     pub fn date_days_in_month(_args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function date_days_in_month")
+        unimplemented_function!(date_days_in_month)
     }
 
     pub fn date_days_in_year(_args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function date_days_in_year")
+        unimplemented_function!(date_days_in_year)
     }
 
     pub fn date_leap_year(_args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function date_leap_year")
+        unimplemented_function!(date_leap_year)
     }
 
     pub fn date_is_today(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function date_is_today")
+        unimplemented_function!(date_is_today)
     }
 
     pub fn sprite_exists(&self, args: &[Value]) -> gml::Result<Value> {
@@ -8625,12 +8737,12 @@ impl Game {
 
     pub fn sprite_add_sprite(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function sprite_add_sprite")
+        unimplemented_function!(sprite_add_sprite)
     }
 
     pub fn sprite_replace_sprite(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function sprite_replace_sprite")
+        unimplemented_function!(sprite_replace_sprite)
     }
 
     pub fn sprite_delete(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -8648,7 +8760,7 @@ impl Game {
 
     pub fn sprite_duplicate(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function sprite_duplicate")
+        unimplemented_function!(sprite_duplicate)
     }
 
     pub fn sprite_assign(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -8699,7 +8811,7 @@ impl Game {
 
     pub fn sprite_merge(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function sprite_merge")
+        unimplemented_function!(sprite_merge)
     }
 
     pub fn sprite_save(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -8722,7 +8834,7 @@ impl Game {
 
     pub fn sprite_save_strip(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function sprite_save_strip")
+        unimplemented_function!(sprite_save_strip)
     }
 
     pub fn sprite_collision_mask(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -8940,7 +9052,7 @@ impl Game {
 
     pub fn background_create_gradient(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 5
-        unimplemented!("Called unimplemented kernel function background_create_gradient")
+        unimplemented_function!(background_create_gradient)
     }
 
     pub fn background_add(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -9003,12 +9115,12 @@ impl Game {
 
     pub fn background_add_background(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function background_add_background")
+        unimplemented_function!(background_add_background)
     }
 
     pub fn background_replace_background(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function background_replace_background")
+        unimplemented_function!(background_replace_background)
     }
 
     pub fn background_delete(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -9254,12 +9366,12 @@ impl Game {
 
     pub fn font_add(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 6
-        unimplemented!("Called unimplemented kernel function font_add")
+        unimplemented_function!(font_add)
     }
 
     pub fn font_replace(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 7
-        unimplemented!("Called unimplemented kernel function font_replace")
+        unimplemented_function!(font_replace)
     }
 
     pub fn font_add_sprite(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -9357,7 +9469,7 @@ impl Game {
                     *dest = src.clone();
                 }
                 let mut new_context = Context::copy_with_args(context, new_args, args.len() - 1);
-                self.execute(&instructions, &mut new_context)?;
+                self.execute_code(&instructions, &mut new_context)?;
                 Ok(new_context.return_value)
             } else {
                 Err(gml::Error::NonexistentAsset(asset::Type::Script, script_id))
@@ -9559,9 +9671,15 @@ impl Game {
         }
     }
 
-    pub fn path_append(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function path_append")
+    pub fn path_append(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (path_id, src_id) = expect_args!(args, [int, int])?;
+        if let Some(src_points) = self.assets.paths.get_asset(src_id).map(|path| path.points.clone()) {
+            if let Some(path) = self.assets.paths.get_asset_mut(path_id) {
+                path.points.extend(src_points);
+                path.update();
+            }
+        }
+        Ok(Default::default())
     }
 
     pub fn path_delete(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -9583,9 +9701,14 @@ impl Game {
         Ok(Default::default())
     }
 
-    pub fn path_insert_point(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 5
-        unimplemented!("Called unimplemented kernel function path_insert_point")
+    pub fn path_insert_point(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (path_id, n, x, y, speed) = expect_args!(args, [int, int, real, real, real])?;
+        if let Some(path) = self.assets.paths.get_asset_mut(path_id) {
+            let n = n.clamp(0, path.points.len() as i32) as usize;
+            path.points.insert(n, asset::path::Point { x, y, speed });
+            path.update();
+        }
+        Ok(Default::default())
     }
 
     pub fn path_change_point(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -9603,14 +9726,26 @@ impl Game {
         Ok(Default::default())
     }
 
-    pub fn path_delete_point(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function path_delete_point")
+    pub fn path_delete_point(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (path_id, n) = expect_args!(args, [int, int])?;
+        if n >= 0 {
+            if let Some(path) = self.assets.paths.get_asset_mut(path_id) {
+                if (n as usize) < path.points.len() {
+                    path.points.remove(n as usize);
+                    path.update();
+                }
+            }
+        }
+        Ok(Default::default())
     }
 
-    pub fn path_clear_points(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function path_clear_points")
+    pub fn path_clear_points(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let path_id = expect_args!(args, [int])?;
+        if let Some(path) = self.assets.paths.get_asset_mut(path_id) {
+            path.points.clear();
+            path.update();
+        }
+        Ok(Default::default())
     }
 
     pub fn path_reverse(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -9750,9 +9885,10 @@ impl Game {
             let instrs = self
                 .compiler
                 .compile(code.as_ref())
-                .map_err(|e| gml::Error::FunctionError("timeline_moment_add".into(), e.message))?;
+                .map_err(|e| gml::Error::FunctionError("timeline_moment_add".into(), e.to_string()))?;
 
             timeline.moments.borrow_mut().entry(moment).or_insert(Default::default()).borrow_mut().push_code(instrs);
+            self.code_added();
         }
         Ok(Default::default())
     }
@@ -9952,9 +10088,35 @@ impl Game {
         Ok(id.into())
     }
 
-    pub fn object_delete(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function object_delete")
+    pub fn object_delete(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let object_id = expect_args!(args, [int])?;
+        if let Some(object) = self.assets.objects.get_asset(object_id) {
+            // Remove object from its parents, but leave its children pointing at it like GM8 does
+            let mut parent_index = object.parent_index;
+            while let Some(parent) = self.assets.objects.get_asset(parent_index) {
+                parent.children.borrow_mut().remove(&object_id);
+                parent_index = parent.parent_index;
+            }
+            // Its instances have no events left to run, so they go with it, without running a destroy event
+            let list = &mut self.room.instance_list;
+            let mut doomed = Vec::new();
+            let mut iter = list.iter_by_drawing();
+            while let Some(handle) = iter.next(list) {
+                doomed.push(handle);
+            }
+            let mut iter = list.iter_inactive();
+            while let Some(handle) = iter.next(list) {
+                doomed.push(handle);
+            }
+            for handle in doomed {
+                if list.get(handle).object_index.get() == object_id {
+                    list.mark_deleted(handle);
+                }
+            }
+            self.assets.objects[object_id as usize] = None;
+            self.refresh_event_holders();
+        }
+        Ok(Default::default())
     }
 
     pub fn object_event_clear(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -9971,7 +10133,7 @@ impl Game {
         if let Some(object) = self.assets.objects.get_asset_mut(object_index) {
             let instrs = match self.compiler.compile(code.as_ref()) {
                 Ok(instrs) => instrs,
-                Err(e) => return Err(gml::Error::FunctionError("object_event_add".into(), e.message)),
+                Err(e) => return Err(gml::Error::FunctionError("object_event_add".into(), e.to_string())),
             };
             let object_event_map = &mut object.events[ev_type as usize];
             match object_event_map.get_mut(&(ev_number as u32)) {
@@ -9983,6 +10145,7 @@ impl Game {
                     self.refresh_event_holders();
                 },
             }
+            self.code_added();
         }
         Ok(Default::default())
     }
@@ -10029,9 +10192,16 @@ impl Game {
         Ok(Default::default())
     }
 
-    pub fn room_set_code(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function room_set_code")
+    pub fn room_set_code(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (room_id, code) = expect_args!(args, [int, bytes])?;
+        if let Some(room) = self.assets.rooms.get_asset_mut(room_id) {
+            match self.compiler.compile(code.as_ref()) {
+                Ok(instrs) => room.creation_code = Ok(instrs),
+                Err(e) => return Err(gml::Error::FunctionError("room_set_code".into(), e.to_string())),
+            }
+            self.code_added();
+        }
+        Ok(Default::default())
     }
 
     pub fn room_set_background_color(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -10129,7 +10299,7 @@ impl Game {
             persistent: false,
             bg_colour: 0xc0c0c0.into(),
             clear_screen: true,
-            creation_code: Ok(std::rc::Rc::new([])),
+            creation_code: Ok(gml::Code::new(std::rc::Rc::new([]))),
             backgrounds: vec![
                 crate::game::background::Background {
                     visible: false,
@@ -10216,7 +10386,7 @@ impl Game {
                 y,
                 object,
                 id: self.last_instance_id,
-                creation: Ok(std::rc::Rc::new([])),
+                creation: Ok(gml::Code::new(std::rc::Rc::new([]))),
                 xscale: 1.0,
                 yscale: 1.0,
                 blend: u32::MAX,
@@ -10238,19 +10408,59 @@ impl Game {
         }
     }
 
-    pub fn room_tile_add(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 9
-        unimplemented!("Called unimplemented kernel function room_tile_add")
+    pub fn room_tile_add(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (room_id, background_index, tile_x, tile_y, width, height, x, y, depth) =
+            expect_args!(args, [int, int, int, int, int, int, real, real, real])?;
+        self.room_tile_add_ext(&[
+            room_id.into(),
+            background_index.into(),
+            tile_x.into(),
+            tile_y.into(),
+            width.into(),
+            height.into(),
+            x.into(),
+            y.into(),
+            depth.into(),
+            1.into(),
+            1.into(),
+            1.into(),
+        ])
     }
 
-    pub fn room_tile_add_ext(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 12
-        unimplemented!("Called unimplemented kernel function room_tile_add_ext")
+    pub fn room_tile_add_ext(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (room_id, background_index, tile_x, tile_y, width, height, x, y, depth, xscale, yscale, alpha) =
+            expect_args!(args, [int, int, int, int, int, int, real, real, real, real, real, real])?;
+        if let Some(room) = self.assets.rooms.get_asset_mut(room_id) {
+            self.last_tile_id += 1;
+            room.tiles.push(Tile {
+                x: x.into(),
+                y: y.into(),
+                background_index: background_index.into(),
+                tile_x: tile_x.into(),
+                tile_y: tile_y.into(),
+                width: width.into(),
+                height: height.into(),
+                depth: depth.into(),
+                id: self.last_tile_id.into(),
+                alpha: alpha.into(),
+                blend: 0xffffff.into(),
+                xscale: xscale.into(),
+                yscale: yscale.into(),
+                visible: true.into(),
+            });
+            Ok(self.last_tile_id.into())
+        } else {
+            Ok((-1).into())
+        }
     }
 
-    pub fn room_tile_clear(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function room_tile_clear")
+    pub fn room_tile_clear(&mut self, args: &[Value]) -> gml::Result<Value> {
+        // A nonexistent room isn't an error, the same as in room_tile_add_ext
+        let room_id = expect_args!(args, [int])?;
+        if let Some(room) = self.assets.rooms.get_asset_mut(room_id) {
+            room.tiles.clear();
+        }
+        Ok(Default::default())
     }
 
     pub fn part_type_create(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -11189,7 +11399,7 @@ impl Game {
         let id = expect_args!(args, [int])?;
         match self.stacks.get(id) {
             Some(stack) => {
-                let mut output = "65000000".to_string();
+                let mut output = hex::encode_upper(ds::STACK_HEADER.to_le_bytes());
                 output.push_str(&hex::encode_upper((stack.len() as u32).to_le_bytes()));
                 output.extend(stack.iter().map(|v| hex::encode_upper(v.as_bytes())));
                 Ok(output.into())
@@ -11208,7 +11418,7 @@ impl Game {
                         // Read header and size
                         let mut buf = [0u8; 4];
                         if reader.read_exact(&mut buf).is_ok()
-                            && u32::from_le_bytes(buf) == 0x65
+                            && u32::from_le_bytes(buf) == ds::STACK_HEADER
                             && reader.read_exact(&mut buf).is_ok()
                         {
                             let size = u32::from_le_bytes(buf) as usize;
@@ -11332,14 +11542,30 @@ impl Game {
         }
     }
 
-    pub fn ds_queue_write(&self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function ds_queue_write")
+    pub fn ds_queue_write(&self, args: &[Value]) -> gml::Result<Value> {
+        let id = expect_args!(args, [int])?;
+        match self.queues.get(id) {
+            Some(queue) => Ok(ds::write_queue(queue).into()),
+            None => Err(gml::Error::FunctionError("ds_queue_write".into(), ds::Error::NonexistentStructure(id).into())),
+        }
     }
 
-    pub fn ds_queue_read(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function ds_queue_read")
+    pub fn ds_queue_read(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (id, hex_data) = expect_args!(args, [int, string])?;
+        match self.queues.get_mut(id) {
+            Some(old_queue) => {
+                match hex::decode(hex_data.as_ref()) {
+                    Ok(data) => {
+                        if let Some(queue) = ds::read_queue(data.as_slice()) {
+                            *old_queue = queue;
+                        }
+                    },
+                    Err(e) => eprintln!("Warning (ds_queue_read): {}", e),
+                }
+                Ok(Default::default())
+            },
+            None => Err(gml::Error::FunctionError("ds_queue_read".into(), ds::Error::NonexistentStructure(id).into())),
+        }
     }
 
     pub fn ds_list_create(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -11507,11 +11733,7 @@ impl Game {
         let id = expect_args!(args, [int])?;
         match self.lists.get_mut(id) {
             Some(list) => {
-                for _ in 1..list.len() {
-                    let id1 = self.rand.next_int(list.len() as u32 - 1);
-                    let id2 = self.rand.next_int(list.len() as u32 - 1);
-                    list.swap(id1 as usize, id2 as usize);
-                }
+                ds::shuffle(list.len(), &mut self.rand, |id1, id2| list.swap(id1, id2));
                 Ok(Default::default())
             },
             None => {
@@ -11524,7 +11746,7 @@ impl Game {
         let id = expect_args!(args, [int])?;
         match self.lists.get(id) {
             Some(list) => {
-                let mut output = "2D010000".to_string();
+                let mut output = hex::encode_upper(ds::LIST_HEADER.to_le_bytes());
                 output.push_str(&hex::encode_upper((list.len() as u32).to_le_bytes()));
                 output.extend(list.iter().map(|v| hex::encode_upper(v.as_bytes())));
                 Ok(output.into())
@@ -11538,7 +11760,7 @@ impl Game {
         fn read_list(mut reader: &[u8]) -> Option<ds::List> {
             let mut buf = [0u8; 4];
             reader.read_exact(&mut buf).ok()?;
-            if u32::from_le_bytes(buf) != 0x12d {
+            if u32::from_le_bytes(buf) != ds::LIST_HEADER {
                 return None
             }
             reader.read_exact(&mut buf).ok()?;
@@ -11736,7 +11958,7 @@ impl Game {
         let id = expect_args!(args, [int])?;
         match self.maps.get(id) {
             Some(map) => {
-                let mut output = "91010000".to_string();
+                let mut output = hex::encode_upper(ds::MAP_HEADER.to_le_bytes());
                 output.push_str(&hex::encode_upper((map.keys.len() as u32).to_le_bytes()));
                 output.extend(map.keys.iter().map(|v| hex::encode_upper(v.as_bytes())));
                 output.extend(map.values.iter().map(|v| hex::encode_upper(v.as_bytes())));
@@ -11751,7 +11973,7 @@ impl Game {
         fn read_map(mut reader: &[u8]) -> Option<ds::Map> {
             let mut buf = [0u8; 4];
             reader.read_exact(&mut buf).ok()?;
-            if u32::from_le_bytes(buf) != 0x191 {
+            if u32::from_le_bytes(buf) != ds::MAP_HEADER {
                 return None
             }
             reader.read_exact(&mut buf).ok()?;
@@ -12145,9 +12367,7 @@ impl Game {
         let (id, x, y, val) = expect_args!(args, [int, int, int, any])?;
         if let Some(grid) = self.grids.get_mut(id) {
             if let Some(cell) = grid.get_mut(x, y) {
-                if cell.add_assign(val.clone()).is_err() {
-                    *cell = val;
-                }
+                ds::add_cell(cell, val);
             }
             Ok(Default::default())
         } else {
@@ -12158,9 +12378,8 @@ impl Game {
     pub fn ds_grid_multiply(&mut self, args: &[Value]) -> gml::Result<Value> {
         let (id, x, y, val) = expect_args!(args, [int, int, int, any])?;
         if let Some(grid) = self.grids.get_mut(id) {
-            match (grid.get_mut(x, y), val) {
-                (Some(Value::Real(cell)), Value::Real(fac)) => *cell *= fac,
-                _ => (),
+            if let Some(cell) = grid.get_mut(x, y) {
+                ds::multiply_cell(cell, val);
             }
             Ok(Default::default())
         } else {
@@ -12180,14 +12399,28 @@ impl Game {
         }
     }
 
-    pub fn ds_grid_add_region(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 6
-        unimplemented!("Called unimplemented kernel function ds_grid_add_region")
+    pub fn ds_grid_add_region(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (id, x1, y1, x2, y2, val) = expect_args!(args, [int, int, int, int, int, any])?;
+        if let Some(grid) = self.grids.get_mut(id) {
+            for cell in grid.region_mut(x1, y1, x2, y2) {
+                ds::add_cell(cell, val.clone());
+            }
+            Ok(Default::default())
+        } else {
+            Err(gml::Error::FunctionError("ds_grid_add_region".into(), ds::Error::NonexistentStructure(id).into()))
+        }
     }
 
-    pub fn ds_grid_multiply_region(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 6
-        unimplemented!("Called unimplemented kernel function ds_grid_multiply_region")
+    pub fn ds_grid_multiply_region(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (id, x1, y1, x2, y2, val) = expect_args!(args, [int, int, int, int, int, any])?;
+        if let Some(grid) = self.grids.get_mut(id) {
+            for cell in grid.region_mut(x1, y1, x2, y2) {
+                ds::multiply_cell(cell, val.clone());
+            }
+            Ok(Default::default())
+        } else {
+            Err(gml::Error::FunctionError("ds_grid_multiply_region".into(), ds::Error::NonexistentStructure(id).into()))
+        }
     }
 
     pub fn ds_grid_set_disk(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -12202,29 +12435,65 @@ impl Game {
         }
     }
 
-    pub fn ds_grid_add_disk(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 5
-        unimplemented!("Called unimplemented kernel function ds_grid_add_disk")
+    pub fn ds_grid_add_disk(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (id, xm, ym, r, val) = expect_args!(args, [int, real, real, real, any])?;
+        if let Some(grid) = self.grids.get_mut(id) {
+            for cell in grid.disk_mut(xm, ym, r) {
+                ds::add_cell(cell, val.clone());
+            }
+            Ok(Default::default())
+        } else {
+            Err(gml::Error::FunctionError("ds_grid_add_disk".into(), ds::Error::NonexistentStructure(id).into()))
+        }
     }
 
-    pub fn ds_grid_multiply_disk(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 5
-        unimplemented!("Called unimplemented kernel function ds_grid_multiply_disk")
+    pub fn ds_grid_multiply_disk(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let (id, xm, ym, r, val) = expect_args!(args, [int, real, real, real, any])?;
+        if let Some(grid) = self.grids.get_mut(id) {
+            for cell in grid.disk_mut(xm, ym, r) {
+                ds::multiply_cell(cell, val.clone());
+            }
+            Ok(Default::default())
+        } else {
+            Err(gml::Error::FunctionError("ds_grid_multiply_disk".into(), ds::Error::NonexistentStructure(id).into()))
+        }
     }
 
-    pub fn ds_grid_set_grid_region(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 8
-        unimplemented!("Called unimplemented kernel function ds_grid_set_grid_region")
+    pub fn ds_grid_set_grid_region(&mut self, args: &[Value]) -> gml::Result<Value> {
+        self.ds_grid_combine_grid_region("ds_grid_set_grid_region", args, |cell, val| *cell = val)
     }
 
-    pub fn ds_grid_add_grid_region(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 8
-        unimplemented!("Called unimplemented kernel function ds_grid_add_grid_region")
+    pub fn ds_grid_add_grid_region(&mut self, args: &[Value]) -> gml::Result<Value> {
+        self.ds_grid_combine_grid_region("ds_grid_add_grid_region", args, ds::add_cell)
     }
 
-    pub fn ds_grid_multiply_grid_region(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 8
-        unimplemented!("Called unimplemented kernel function ds_grid_multiply_grid_region")
+    pub fn ds_grid_multiply_grid_region(&mut self, args: &[Value]) -> gml::Result<Value> {
+        self.ds_grid_combine_grid_region("ds_grid_multiply_grid_region", args, ds::multiply_cell)
+    }
+
+    /// Shared by the ds_grid_*_grid_region functions, which differ only in what they do to each cell.
+    fn ds_grid_combine_grid_region(
+        &mut self,
+        function: &str,
+        args: &[Value],
+        op: impl FnMut(&mut Value, Value),
+    ) -> gml::Result<Value> {
+        let (id, src_id, x1, y1, x2, y2, xpos, ypos) = expect_args!(args, [int, int, int, int, int, int, int, int])?;
+        let (region, pos) = ((x1, y1, x2, y2), (xpos, ypos));
+        if self.grids.get(src_id).is_none() {
+            return Err(gml::Error::FunctionError(function.into(), ds::Error::NonexistentStructure(src_id).into()))
+        }
+        if id == src_id {
+            if let Some(grid) = self.grids.get_mut(id) {
+                grid.combine_own_region(region, pos, op);
+            }
+            Ok(Default::default())
+        } else if let Some((grid, src)) = self.grids.get_mut_and_ref(id, src_id) {
+            grid.combine_region(src, region, pos, op);
+            Ok(Default::default())
+        } else {
+            Err(gml::Error::FunctionError(function.into(), ds::Error::NonexistentStructure(id).into()))
+        }
     }
 
     pub fn ds_grid_get(&self, args: &[Value]) -> gml::Result<Value> {
@@ -12239,7 +12508,7 @@ impl Game {
     pub fn ds_grid_get_sum(&self, args: &[Value]) -> gml::Result<Value> {
         let (id, x1, y1, x2, y2) = expect_args!(args, [int, int, int, int, int])?;
         if let Some(grid) = self.grids.get(id) {
-            Ok(grid.region(x1, y1, x2, y2).filter_map(Value::as_real).sum::<Real>().into())
+            Ok(ds::sum(grid.region(x1, y1, x2, y2)).into())
         } else {
             Err(gml::Error::FunctionError("ds_grid_get_sum".into(), ds::Error::NonexistentStructure(id).into()))
         }
@@ -12248,12 +12517,7 @@ impl Game {
     pub fn ds_grid_get_max(&self, args: &[Value]) -> gml::Result<Value> {
         let (id, x1, y1, x2, y2) = expect_args!(args, [int, int, int, int, int])?;
         if let Some(grid) = self.grids.get(id) {
-            // weird fold needed due to NaN nonsense
-            Ok(grid
-                .region(x1, y1, x2, y2)
-                .filter_map(Value::as_real)
-                .fold(Real::from(-100000000), |acc, val| if val >= acc { val } else { acc })
-                .into())
+            Ok(ds::max(grid.region(x1, y1, x2, y2)).into())
         } else {
             Err(gml::Error::FunctionError("ds_grid_get_max".into(), ds::Error::NonexistentStructure(id).into()))
         }
@@ -12262,12 +12526,7 @@ impl Game {
     pub fn ds_grid_get_min(&self, args: &[Value]) -> gml::Result<Value> {
         let (id, x1, y1, x2, y2) = expect_args!(args, [int, int, int, int, int])?;
         if let Some(grid) = self.grids.get(id) {
-            // weird fold needed due to NaN nonsense
-            Ok(grid
-                .region(x1, y1, x2, y2)
-                .filter_map(Value::as_real)
-                .fold(Real::from(100000000), |acc, val| if val <= acc { val } else { acc })
-                .into())
+            Ok(ds::min(grid.region(x1, y1, x2, y2)).into())
         } else {
             Err(gml::Error::FunctionError("ds_grid_get_min".into(), ds::Error::NonexistentStructure(id).into()))
         }
@@ -12292,24 +12551,40 @@ impl Game {
         }
     }
 
-    pub fn ds_grid_get_disk_sum(&self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 4
-        unimplemented!("Called unimplemented kernel function ds_grid_get_disk_sum")
+    pub fn ds_grid_get_disk_sum(&self, args: &[Value]) -> gml::Result<Value> {
+        let (id, xm, ym, r) = expect_args!(args, [int, real, real, real])?;
+        if let Some(grid) = self.grids.get(id) {
+            Ok(ds::sum(grid.disk(xm, ym, r)).into())
+        } else {
+            Err(gml::Error::FunctionError("ds_grid_get_disk_sum".into(), ds::Error::NonexistentStructure(id).into()))
+        }
     }
 
-    pub fn ds_grid_get_disk_max(&self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 4
-        unimplemented!("Called unimplemented kernel function ds_grid_get_disk_max")
+    pub fn ds_grid_get_disk_max(&self, args: &[Value]) -> gml::Result<Value> {
+        let (id, xm, ym, r) = expect_args!(args, [int, real, real, real])?;
+        if let Some(grid) = self.grids.get(id) {
+            Ok(ds::max(grid.disk(xm, ym, r)).into())
+        } else {
+            Err(gml::Error::FunctionError("ds_grid_get_disk_max".into(), ds::Error::NonexistentStructure(id).into()))
+        }
     }
 
-    pub fn ds_grid_get_disk_min(&self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 4
-        unimplemented!("Called unimplemented kernel function ds_grid_get_disk_min")
+    pub fn ds_grid_get_disk_min(&self, args: &[Value]) -> gml::Result<Value> {
+        let (id, xm, ym, r) = expect_args!(args, [int, real, real, real])?;
+        if let Some(grid) = self.grids.get(id) {
+            Ok(ds::min(grid.disk(xm, ym, r)).into())
+        } else {
+            Err(gml::Error::FunctionError("ds_grid_get_disk_min".into(), ds::Error::NonexistentStructure(id).into()))
+        }
     }
 
-    pub fn ds_grid_get_disk_mean(&self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 4
-        unimplemented!("Called unimplemented kernel function ds_grid_get_disk_mean")
+    pub fn ds_grid_get_disk_mean(&self, args: &[Value]) -> gml::Result<Value> {
+        let (id, xm, ym, r) = expect_args!(args, [int, real, real, real])?;
+        if let Some(grid) = self.grids.get(id) {
+            Ok(ds::mean(grid.disk(xm, ym, r)).into())
+        } else {
+            Err(gml::Error::FunctionError("ds_grid_get_disk_mean".into(), ds::Error::NonexistentStructure(id).into()))
+        }
     }
 
     pub fn ds_grid_value_exists(&self, args: &[Value]) -> gml::Result<Value> {
@@ -12389,9 +12664,14 @@ impl Game {
         }
     }
 
-    pub fn ds_grid_shuffle(&mut self, _args: &[Value]) -> gml::Result<Value> {
-        // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function ds_grid_shuffle")
+    pub fn ds_grid_shuffle(&mut self, args: &[Value]) -> gml::Result<Value> {
+        let id = expect_args!(args, [int])?;
+        if let Some(grid) = self.grids.get_mut(id) {
+            grid.shuffle(&mut self.rand);
+            Ok(Default::default())
+        } else {
+            Err(gml::Error::FunctionError("ds_grid_shuffle".into(), ds::Error::NonexistentStructure(id).into()))
+        }
     }
 
     pub fn ds_grid_write(&self, args: &[Value]) -> gml::Result<Value> {
@@ -12509,12 +12789,12 @@ impl Game {
 
     pub fn sound_fade(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function sound_fade")
+        unimplemented_function!(sound_fade)
     }
 
     pub fn sound_pan(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function sound_pan")
+        unimplemented_function!(sound_pan)
     }
 
     pub fn sound_background_tempo(&mut self, _args: &[Value]) -> gml::Result<Value> {
@@ -12531,162 +12811,162 @@ impl Game {
 
     pub fn sound_set_search_directory(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function sound_set_search_directory")
+        unimplemented_function!(sound_set_search_directory)
     }
 
     pub fn sound_effect_set(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function sound_effect_set")
+        unimplemented_function!(sound_effect_set)
     }
 
     pub fn sound_effect_chorus(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 8
-        unimplemented!("Called unimplemented kernel function sound_effect_chorus")
+        unimplemented_function!(sound_effect_chorus)
     }
 
     pub fn sound_effect_compressor(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 7
-        unimplemented!("Called unimplemented kernel function sound_effect_compressor")
+        unimplemented_function!(sound_effect_compressor)
     }
 
     pub fn sound_effect_echo(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 6
-        unimplemented!("Called unimplemented kernel function sound_effect_echo")
+        unimplemented_function!(sound_effect_echo)
     }
 
     pub fn sound_effect_flanger(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 8
-        unimplemented!("Called unimplemented kernel function sound_effect_flanger")
+        unimplemented_function!(sound_effect_flanger)
     }
 
     pub fn sound_effect_gargle(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function sound_effect_gargle")
+        unimplemented_function!(sound_effect_gargle)
     }
 
     pub fn sound_effect_equalizer(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 4
-        unimplemented!("Called unimplemented kernel function sound_effect_equalizer")
+        unimplemented_function!(sound_effect_equalizer)
     }
 
     pub fn sound_effect_reverb(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 5
-        unimplemented!("Called unimplemented kernel function sound_effect_reverb")
+        unimplemented_function!(sound_effect_reverb)
     }
 
     pub fn sound_3d_set_sound_position(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 4
-        unimplemented!("Called unimplemented kernel function sound_3d_set_sound_position")
+        unimplemented_function!(sound_3d_set_sound_position)
     }
 
     pub fn sound_3d_set_sound_velocity(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 4
-        unimplemented!("Called unimplemented kernel function sound_3d_set_sound_velocity")
+        unimplemented_function!(sound_3d_set_sound_velocity)
     }
 
     pub fn sound_3d_set_sound_distance(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 3
-        unimplemented!("Called unimplemented kernel function sound_3d_set_sound_distance")
+        unimplemented_function!(sound_3d_set_sound_distance)
     }
 
     pub fn sound_3d_set_sound_cone(&mut self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 7
-        unimplemented!("Called unimplemented kernel function sound_3d_set_sound_cone")
+        unimplemented_function!(sound_3d_set_sound_cone)
     }
 
     pub fn cd_init(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function cd_init")
+        unimplemented_function!(cd_init)
     }
 
     pub fn cd_present(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function cd_present")
+        unimplemented_function!(cd_present)
     }
 
     pub fn cd_number(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function cd_number")
+        unimplemented_function!(cd_number)
     }
 
     pub fn cd_playing(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function cd_playing")
+        unimplemented_function!(cd_playing)
     }
 
     pub fn cd_paused(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function cd_paused")
+        unimplemented_function!(cd_paused)
     }
 
     pub fn cd_track(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function cd_track")
+        unimplemented_function!(cd_track)
     }
 
     pub fn cd_length(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function cd_length")
+        unimplemented_function!(cd_length)
     }
 
     pub fn cd_track_length(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function cd_track_length")
+        unimplemented_function!(cd_track_length)
     }
 
     pub fn cd_position(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function cd_position")
+        unimplemented_function!(cd_position)
     }
 
     pub fn cd_track_position(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function cd_track_position")
+        unimplemented_function!(cd_track_position)
     }
 
     pub fn cd_play(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 2
-        unimplemented!("Called unimplemented kernel function cd_play")
+        unimplemented_function!(cd_play)
     }
 
     pub fn cd_stop(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function cd_stop")
+        unimplemented_function!(cd_stop)
     }
 
     pub fn cd_pause(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function cd_pause")
+        unimplemented_function!(cd_pause)
     }
 
     pub fn cd_resume(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function cd_resume")
+        unimplemented_function!(cd_resume)
     }
 
     pub fn cd_set_position(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function cd_set_position")
+        unimplemented_function!(cd_set_position)
     }
 
     pub fn cd_set_track_position(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function cd_set_track_position")
+        unimplemented_function!(cd_set_track_position)
     }
 
     pub fn cd_open_door(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function cd_open_door")
+        unimplemented_function!(cd_open_door)
     }
 
     pub fn cd_close_door(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 0
-        unimplemented!("Called unimplemented kernel function cd_close_door")
+        unimplemented_function!(cd_close_door)
     }
 
     pub fn mci_command(&self, _args: &[Value]) -> gml::Result<Value> {
         // Expected arg count: 1
-        unimplemented!("Called unimplemented kernel function MCI_command")
+        unimplemented_function!(MCI_command)
     }
 
     pub fn d3d_start(&mut self, args: &[Value]) -> gml::Result<Value> {
@@ -2136,3 +2136,74 @@ pub const FUNCTIONS: phf::OrderedMap<&'static str, Function> = phf_ordered_map!
     "d3d_model_wall" => Function::Engine(Game::d3d_model_wall),
     "d3d_model_floor" => Function::Engine(Game::d3d_model_floor),
 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Reads how many arguments a kernel function takes from its source, going by whichever comes first:
+    /// expect_args! gives a fixed count, looking at args.len() and the like means any count,
+    /// and a call which passes the arguments on means the same as that function.
+    /// Returns None if the function doesn't do any of those.
+    fn arg_count(kernel: &str, method: &str) -> Option<Option<usize>> {
+        let body = &kernel[kernel.find(&format!("fn {}(", method))?..];
+        let body = &body[..body.find("\n    }\n")?];
+
+        let fixed = body.find("expect_args!(args, [").map(|pos| {
+            let list = &body[pos + "expect_args!(args, [".len()..];
+            (pos, list[..list.find(']').unwrap()].split(',').filter(|arg| !arg.trim().is_empty()).count())
+        });
+        let any = ["args.len()", "args.get(", "args.iter()"].iter().filter_map(|s| body.find(s)).min();
+        let passed = body
+            .match_indices("self.")
+            .chain(body.match_indices("Self::"))
+            .filter_map(|(pos, prefix)| {
+                let call = &body[pos + prefix.len()..];
+                let (callee, rest) = call.split_at(call.find(|c: char| !(c.is_alphanumeric() || c == '_'))?);
+                let rest = rest.strip_prefix('(')?;
+                let rest = rest.strip_prefix("context, ").unwrap_or(rest);
+                let rest = match rest.strip_prefix('"') {
+                    Some(rest) => rest.split_once("\", ")?.1,
+                    None => rest,
+                };
+                (rest.starts_with("args)") || rest.starts_with("args,")).then_some((pos, callee))
+            })
+            .min();
+
+        let first = [fixed.map(|(pos, _)| pos), any, passed.map(|(pos, _)| pos)].into_iter().flatten().min()?;
+        match (fixed, passed) {
+            (Some((pos, count)), _) if pos == first => Some(Some(count)),
+            (_, Some((pos, callee))) if pos == first => arg_count(kernel, callee),
+            _ => Some(None),
+        }
+    }
+
+    #[test]
+    fn kernel_function_table() {
+        // The decompiler uses gml_parser's list of functions, so it has to have the same ones as here
+        let table = gml_parser::kernel::FUNCTIONS.iter().copied().collect::<HashMap<_, _>>();
+        assert_eq!(table.len(), gml_parser::kernel::FUNCTIONS.len(), "gml_parser::kernel has duplicate functions");
+        for name in FUNCTIONS.keys() {
+            assert!(table.contains_key(name), "{} is missing from gml_parser::kernel", name);
+        }
+        assert_eq!(table.len(), FUNCTIONS.len(), "gml_parser::kernel has functions which aren't here");
+
+        // and the same argument counts, wherever those can be read from the kernel
+        let kernel = include_str!("kernel.rs");
+        let mut checked = 0;
+        for line in include_str!("mappings.rs").lines().filter_map(|line| line.trim().strip_prefix('"')) {
+            let (name, function) = match line.split_once("\" => Function::") {
+                Some(parts) => parts,
+                None => continue,
+            };
+            let method = function.split_once("(Game::").and_then(|(_, rest)| rest.split_once(')')).unwrap().0;
+            if let Some(count) = arg_count(kernel, method) {
+                assert_eq!(table[name], count, "{} takes a different number of arguments in gml_parser::kernel", name);
+                checked += 1;
+            }
+        }
+        // Most functions should have been understood, or this isn't checking much
+        assert!(checked > 1000, "only read the argument counts of {} functions", checked);
+    }
+}
@@ -0,0 +1,331 @@
+//! Constant folding and dead code elimination for compiled GML.
+//!
+//! Optimized code must behave exactly like the code it came from, errors included. Anything which fails while
+//! being folded becomes a `RuntimeError` in the same place, so it's only raised if and when it's reached.
+
+use crate::gml::{
+    compiler,
+    mappings,
+    runtime::{ArrayAccessor, InstanceIdentifier, Instruction, Node},
+    unimplemented::UNIMPLEMENTED_FUNCTIONS,
+    Function, Value,
+};
+
+/// Optimizes a block of compiled code.
+pub fn block(code: Vec<Instruction>) -> Vec<Instruction> {
+    let mut output = Vec::with_capacity(code.len());
+    for instruction in code {
+        self::instruction(instruction, &mut output);
+        // Anything after an exit, break, continue or error can never run
+        if matches!(output.last(), Some(Instruction::Return { .. } | Instruction::RuntimeError { .. })) {
+            break
+        }
+    }
+    output
+}
+
+/// Optimizes a single instruction, adding whatever is left of it to the output.
+fn instruction(instruction: Instruction, output: &mut Vec<Instruction>) {
+    match instruction {
+        Instruction::SetField { mut accessor, value } => {
+            accessor.owner = owner(accessor.owner);
+            accessor.array = array(accessor.array);
+            output.push(Instruction::SetField { accessor, value: node(value) });
+        },
+        Instruction::SetVariable { mut accessor, value } => {
+            accessor.owner = owner(accessor.owner);
+            accessor.array = array(accessor.array);
+            output.push(Instruction::SetVariable { accessor, value: node(value) });
+        },
+        Instruction::EvalExpression { node: n } => output.push(Instruction::EvalExpression { node: node(n) }),
+        Instruction::IfElse { cond, if_body, else_body } => match node(cond) {
+            Node::Literal { value } => {
+                let body = if value.is_truthy() { if_body } else { else_body };
+                output.extend(block(body.into_vec()));
+            },
+            cond => output.push(Instruction::IfElse {
+                cond,
+                if_body: block(if_body.into_vec()).into_boxed_slice(),
+                else_body: block(else_body.into_vec()).into_boxed_slice(),
+            }),
+        },
+        Instruction::LoopUntil { cond, body } => {
+            output.push(Instruction::LoopUntil { cond: node(cond), body: block(body.into_vec()).into_boxed_slice() });
+        },
+        Instruction::LoopWhile { cond, body } => match node(cond) {
+            Node::Literal { value } if !value.is_truthy() => (),
+            cond => output.push(Instruction::LoopWhile { cond, body: block(body.into_vec()).into_boxed_slice() }),
+        },
+        Instruction::LoopFor { cond, body, step } => match node(cond) {
+            // The initial statement is compiled before the loop, so it's kept either way
+            Node::Literal { value } if !value.is_truthy() => (),
+            cond => output.push(Instruction::LoopFor {
+                cond,
+                body: block(body.into_vec()).into_boxed_slice(),
+                step: block(step.into_vec()).into_boxed_slice(),
+            }),
+        },
+        Instruction::Repeat { count, body } => {
+            output.push(Instruction::Repeat { count: node(count), body: block(body.into_vec()).into_boxed_slice() });
+        },
+        Instruction::SetReturnValue { value } => output.push(Instruction::SetReturnValue { value: node(value) }),
+        Instruction::Switch { input, cases, default, body } => {
+            // Cases jump into the middle of the body, so each stretch of code between them is optimized separately
+            let mut starts = cases.iter().map(|(_, start)| *start).chain(default).collect::<Vec<_>>();
+            starts.push(0);
+            starts.sort_unstable();
+            starts.dedup();
+            let mut body = body.into_vec();
+            let mut segments = Vec::with_capacity(starts.len());
+            for &start in starts.iter().rev() {
+                segments.push(body.split_off(start));
+            }
+            let mut new_body = Vec::with_capacity(body.len());
+            let mut new_starts = Vec::with_capacity(starts.len());
+            for segment in segments.into_iter().rev() {
+                new_starts.push(new_body.len());
+                new_body.extend(block(segment));
+            }
+            let new_start = |start: usize| new_starts[starts.binary_search(&start).unwrap()];
+            output.push(Instruction::Switch {
+                input: node(input),
+                cases: cases.into_vec().into_iter().map(|(case, start)| (node(case), new_start(start))).collect(),
+                default: default.map(new_start),
+                body: new_body.into_boxed_slice(),
+            });
+        },
+        Instruction::With { target, body } => {
+            output.push(Instruction::With { target: node(target), body: block(body.into_vec()).into_boxed_slice() });
+        },
+        Instruction::Return { .. }
+        | Instruction::GlobalVar { .. }
+        | Instruction::RuntimeError { .. }
+        | Instruction::Line { .. } => output.push(instruction),
+    }
+}
+
+/// Optimizes an expression.
+pub fn node(node: Node) -> Node {
+    match node {
+        Node::Function { args, function_id } => {
+            let args = nodes(args);
+            if let Some((name, Function::Pure(function))) = mappings::FUNCTIONS.index(function_id) {
+                // Unimplemented functions do whatever the policy says at runtime, and the runtime panics on more
+                // than 16 arguments, so neither of those can be called now
+                if args.len() <= 16 && UNIMPLEMENTED_FUNCTIONS.binary_search(name).is_err() {
+                    if let Some(values) = literals(&args) {
+                        return result(function(&values))
+                    }
+                }
+            }
+            Node::Function { args, function_id }
+        },
+        Node::Script { args, script_id } => Node::Script { args: nodes(args), script_id },
+        Node::ExtensionFunction { args, id } => Node::ExtensionFunction { args: nodes(args), id },
+        Node::Field { mut accessor } => {
+            accessor.owner = owner(accessor.owner);
+            accessor.array = array(accessor.array);
+            Node::Field { accessor }
+        },
+        Node::Variable { mut accessor } => {
+            accessor.owner = owner(accessor.owner);
+            accessor.array = array(accessor.array);
+            Node::Variable { accessor }
+        },
+        Node::Binary { left, right, operator, type_unsafe } => match (self::node(*left), self::node(*right)) {
+            (Node::Literal { value: lhs }, Node::Literal { value: rhs }) => match operator.call(lhs.clone(), rhs) {
+                Ok(value) => Node::Literal { value },
+                // This is the + in +=, which ignores errors
+                Err(_) if type_unsafe => Node::Literal { value: lhs },
+                Err(error) => Node::RuntimeError { error },
+            },
+            (left, right) => Node::Binary { left: Box::new(left), right: Box::new(right), operator, type_unsafe },
+        },
+        Node::Unary { child, operator } => match self::node(*child) {
+            Node::Literal { value } => result(operator.call(value)),
+            child => Node::Unary { child: Box::new(child), operator },
+        },
+        node @ (Node::Literal { .. } | Node::Constant { .. } | Node::RuntimeError { .. }) => node,
+    }
+}
+
+fn nodes(nodes: Box<[Node]>) -> Box<[Node]> {
+    nodes.into_vec().into_iter().map(node).collect()
+}
+
+fn owner(owner: InstanceIdentifier) -> InstanceIdentifier {
+    match owner {
+        InstanceIdentifier::Expression(expression) => compiler::instance_identifier(node(*expression)),
+        owner => owner,
+    }
+}
+
+fn array(array: ArrayAccessor) -> ArrayAccessor {
+    match array {
+        ArrayAccessor::None => ArrayAccessor::None,
+        ArrayAccessor::Single(index) => ArrayAccessor::Single(Box::new(node(*index))),
+        ArrayAccessor::Double(index1, index2) => {
+            ArrayAccessor::Double(Box::new(node(*index1)), Box::new(node(*index2)))
+        },
+    }
+}
+
+/// The values of some expressions, if they're all literals.
+fn literals(nodes: &[Node]) -> Option<Vec<Value>> {
+    nodes
+        .iter()
+        .map(|node| match node {
+            Node::Literal { value } => Some(value.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn result(result: crate::gml::Result<Value>) -> Node {
+    match result {
+        Ok(value) => Node::Literal { value },
+        Err(error) => Node::RuntimeError { error },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gml::{Code, Compiler};
+
+    fn compile(source: &str, optimize: bool) -> Code {
+        let mut compiler = Compiler::new();
+        compiler.set_optimize(optimize);
+        compiler.compile(source.as_bytes()).unwrap()
+    }
+
+    /// Evaluates an expression made only of literals, operators and pure functions, the same way the runtime does.
+    fn eval(node: &Node) -> crate::gml::Result<Value> {
+        match node {
+            Node::Literal { value } => Ok(value.clone()),
+            Node::Function { args, function_id } => match mappings::FUNCTIONS.index(*function_id) {
+                Some((_, Function::Pure(function))) => function(&args.iter().map(eval).collect::<Result<Vec<_>, _>>()?),
+                _ => panic!("only pure functions can be called without a game"),
+            },
+            Node::Binary { left, right, operator, type_unsafe } => {
+                // Both sides are always evaluated, so && and || don't short-circuit
+                let left = eval(left)?;
+                match operator.call(left.clone(), eval(right)?) {
+                    Err(_) if *type_unsafe => Ok(left),
+                    result => result,
+                }
+            },
+            Node::Unary { child, operator } => operator.call(eval(child)?),
+            Node::RuntimeError { error } => Err(error.clone()),
+            _ => panic!("only literals can be evaluated without a game"),
+        }
+    }
+
+    /// Runs code which only assigns to fields and branches, listing each assignment and any error.
+    /// Returns whether the code carried on to the end.
+    fn run(code: &[Instruction], out: &mut Vec<String>) -> bool {
+        for instruction in code {
+            let result = match instruction {
+                Instruction::SetField { accessor, value } => {
+                    eval(value).map(|value| out.push(format!("{} = {:?}", accessor.index, value)))
+                },
+                Instruction::IfElse { cond, if_body, else_body } => match eval(cond) {
+                    Ok(cond) if !run(if cond.is_truthy() { if_body } else { else_body }, out) => return false,
+                    result => result.map(|_| ()),
+                },
+                Instruction::Return { .. } => return false,
+                Instruction::RuntimeError { error } => Err(error.clone()),
+                Instruction::Line { .. } => Ok(()),
+                _ => panic!("only assignments and branches can be run without a game"),
+            };
+            if let Err(error) = result {
+                out.push(format!("error: {:?}", error));
+                return false
+            }
+        }
+        true
+    }
+
+    /// Runs some code both with and without optimizing it, checking the two do the same thing.
+    fn run_both(source: &str) -> Vec<String> {
+        let (mut unoptimized, mut optimized) = (Vec::new(), Vec::new());
+        run(&compile(source, false), &mut unoptimized);
+        run(&compile(source, true), &mut optimized);
+        assert_eq!(unoptimized, optimized, "optimizing changed what this does: {}", source);
+        optimized
+    }
+
+    fn assigned_real(instruction: &Instruction) -> Option<f64> {
+        match instruction {
+            Instruction::SetField { value: Node::Literal { value: Value::Real(r) }, .. } => Some(r.into_inner()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn folds_literals() {
+        let code = compile("a = 1 + 2 * 3; b = -(4 - 6); c = abs(-5) + max(1, 2, 3)", true);
+        let values = code.iter().map(assigned_real).collect::<Vec<_>>();
+        assert_eq!(values, [Some(7.0), Some(2.0), Some(8.0)]);
+
+        let code = compile("a = 1 + 2 * 3", false);
+        assert!(matches!(&code[..], [Instruction::SetField { value: Node::Binary { .. }, .. }]));
+    }
+
+    #[test]
+    fn keeps_errors() {
+        let code = compile("a = 1 + \"x\"; b = 2", true);
+        assert!(matches!(&code[..], [Instruction::SetField { value: Node::RuntimeError { .. }, .. }, _]));
+    }
+
+    #[test]
+    fn removes_dead_code() {
+        let code = compile("if 1 { a = 1 } else { a = 2 } while false { b = 1 } exit; c = 3", true);
+        assert!(matches!(&code[..], [Instruction::SetField { .. }, Instruction::Return { .. }]));
+        assert_eq!(assigned_real(&code[0]), Some(1.0));
+
+        let code = compile("if 1 { a = 1 } else { a = 2 } while false { b = 1 } exit; c = 3", false);
+        assert_eq!(code.len(), 4);
+    }
+
+    #[test]
+    fn keeps_switch_cases() {
+        let code = compile("switch a { case 1: if 0 { b = 1 } b = 2; case 2: exit; b = 3; default: b = 4 }", true);
+        match &code[..] {
+            [Instruction::Switch { cases, default, body, .. }] => {
+                assert_eq!(cases.iter().map(|(_, start)| *start).collect::<Vec<_>>(), [0, 1]);
+                assert_eq!(*default, Some(2));
+                assert_eq!(body.len(), 3);
+                assert_eq!(assigned_real(&body[2]), Some(4.0));
+            },
+            _ => panic!("expected a switch"),
+        }
+    }
+
+    #[test]
+    fn same_results() {
+        run_both("a = 1 + 2 * 3 - 4 / 8; b = \"x\" + string(5) + string(max(1, 2.5)); c = 3 * \"ab\"");
+        run_both("a = !(1 < 2) ^^ 2 >= 2; b = 1 == 1.0 != (\"a\" < \"b\")");
+        run_both("a = 0.1 + 0.2; b = 1 << 40 | 5 & ~2; c = -(-0); d = round(2.5) + floor(-1.5) + sqrt(-1)");
+        assert_eq!(run_both("a = 1; b = -\"x\"; c = 2").len(), 2);
+        assert_eq!(run_both("if 2 > 1 { a = 1 } else { a = \"x\" * \"y\" } exit; b = 3"), ["0 = Real(1.0)"]);
+    }
+
+    #[test]
+    fn same_division_results() {
+        run_both("a = 7 div 2; b = -7 div 2; c = 7 div -2; d = 7.5 div 0.5; e = 0 div 0");
+        run_both("a = 7 mod 3; b = -7 mod 3; c = 7 mod -3; d = 7.5 mod 2; e = 5 mod 0; f = 0 mod 0");
+        run_both("a = 1 / 0; b = -1 / 0; c = 0 / 0; d = 1 div 0; e = -1 div 0");
+        assert_eq!(run_both("a = 1; b = 5 div \"x\"; c = 2").len(), 2);
+        assert_eq!(run_both("a = 1; b = \"x\" mod 5; c = 2").len(), 2);
+    }
+
+    #[test]
+    fn same_results_without_short_circuits() {
+        // The right side of && and || is evaluated even when the left decides the result, errors included
+        assert_eq!(run_both("a = 0 && 1; b = 1 || 0; c = 0 && 1 div 0; d = 1 || 0 mod 0").len(), 4);
+        assert_eq!(run_both("a = 0 && -\"x\"; b = 1").len(), 1);
+        assert_eq!(run_both("a = 1 || \"x\" - 1; b = 1").len(), 1);
+        assert_eq!(run_both("if 0 && \"x\" / 2 { a = 1 } b = 1").len(), 1);
+    }
+}
@@ -1,10 +1,11 @@
 use crate::{
     asset,
-    game::{Game, GameClock, GetAsset, SceneChange, Version},
+    game::{profiler::Entry, Game, GameClock, GetAsset, SceneChange, Version},
     gml::{
         self,
         datetime::DateTime,
         mappings::{self, constants as gml_constants},
+        unimplemented::Policy,
         Context, InstanceVariable, Value,
     },
     instance::Field,
@@ -36,6 +37,7 @@ pub enum Instruction {
     With { target: Node, body: Box<[Instruction]> },
     GlobalVar { fields: Vec<usize> },
     RuntimeError { error: Error },
+    Line { line: usize },
 }
 
 /// Node representing one value in an expression.
@@ -163,6 +165,8 @@ pub enum Error {
     BadDirectoryError(String),
     ExternalFunction(String, String),
     InvalidExternal(i32),
+    // Added after savestates had compiled code in them, so it goes last to keep the others numbered the same
+    UnimplementedFunction(String),
 }
 
 impl std::error::Error for Error {}
@@ -210,12 +214,13 @@ impl Display for Error {
             Self::BadDirectoryError(s) => write!(f, "cannot encode working directory {} with current encoding", s),
             Self::ExternalFunction(s, e) => write!(f, "failed to call external function \"{}\": {}", s, e),
             Self::InvalidExternal(i) => write!(f, "tried to call nonexistent external function with id {}", i),
+            Self::UnimplementedFunction(fname) => write!(f, "function \"{}\" is not implemented", fname),
         }
     }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
-enum Target {
+pub(super) enum Target {
     Single(Option<usize>),
     Objects(i32),
     All,
@@ -244,6 +249,7 @@ impl fmt::Debug for Instruction {
             Instruction::With { target, body } => write!(f, "With({:?}, {:?})", target, body),
             Instruction::GlobalVar { fields } => write!(f, "GlobalVar({:?})", fields),
             Instruction::RuntimeError { error } => write!(f, "RuntimeError({:?})", error),
+            Instruction::Line { line } => write!(f, "Line({})", line),
         }
     }
 }
@@ -313,7 +319,17 @@ impl UnaryOperator {
 
 impl Game {
     pub fn invoke(&mut self, function_id: usize, context: &mut Context, args: &[Value]) -> gml::Result<Value> {
-        mappings::FUNCTIONS.index(function_id).unwrap().1.invoke(self, context, args)
+        let (&name, &function) = mappings::FUNCTIONS.index(function_id).unwrap();
+        match self.unimplemented_policy.get(function_id) {
+            None | Some(Policy::Panic) => {
+                self.profile(Entry::Function(function_id), |game| function.invoke(game, context, args))
+            },
+            Some(Policy::Error) => Err(Error::UnimplementedFunction(name.to_string())),
+            Some(Policy::Default) => {
+                self.unimplemented_policy.warn(function_id);
+                Ok(Default::default())
+            },
+        }
     }
 
     pub fn execute(&mut self, instructions: &[Instruction], context: &mut Context) -> gml::Result<ReturnType> {
@@ -332,77 +348,13 @@ impl Game {
                 let target = self.get_target(context, &accessor.owner, self.globalvars.contains(&accessor.index))?;
                 let array_index = self.get_array_index(&accessor.array, context)?;
                 let value = self.eval(value, context)?;
-                context.return_value = value.clone();
-                match target {
-                    Target::Single(None) => (),
-                    Target::Single(Some(instance)) => {
-                        self.set_instance_field(instance, accessor.index, array_index, value);
-                    },
-                    Target::Objects(index) => {
-                        let mut iter = self.room.instance_list.iter_by_identity(index);
-                        while let Some(instance) = iter.next(&self.room.instance_list) {
-                            self.set_instance_field(instance, accessor.index, array_index, value.clone());
-                        }
-                    },
-                    Target::All => {
-                        let mut iter = self.room.instance_list.iter_by_drawing();
-                        while let Some(instance) = iter.next(&self.room.instance_list) {
-                            self.set_instance_field(instance, accessor.index, array_index, value.clone());
-                        }
-                    },
-                    Target::Global => {
-                        if let Some(field) = self.globals.fields.get_mut(&accessor.index) {
-                            field.set(array_index, value)
-                        } else {
-                            self.globals.fields.insert(accessor.index, Field::new(array_index, value));
-                        }
-                    },
-                    Target::Local => {
-                        if let Some(field) = context.locals.fields.get_mut(&accessor.index) {
-                            field.set(array_index, value)
-                        } else {
-                            context.locals.fields.insert(accessor.index, Field::new(array_index, value));
-                        }
-                    },
-                }
+                self.write_field(context, target, accessor.index, array_index, value);
             },
             Instruction::SetVariable { accessor, value } => {
                 let target = self.get_target(context, &accessor.owner, false)?;
                 let array_index = self.get_array_index(&accessor.array, context)?;
                 let value = self.eval(value, context)?;
-                context.return_value = value.clone();
-                match target {
-                    Target::Single(None) => (),
-                    Target::Single(Some(instance)) => {
-                        self.set_instance_var(instance, &accessor.var, array_index, value, context)?;
-                    },
-                    Target::Objects(index) => {
-                        let mut iter = self.room.instance_list.iter_by_identity(index);
-                        while let Some(instance) = iter.next(&self.room.instance_list) {
-                            self.set_instance_var(instance, &accessor.var, array_index, value.clone(), context)?;
-                        }
-                    },
-                    Target::All => {
-                        let mut iter = self.room.instance_list.iter_by_drawing();
-                        while let Some(instance) = iter.next(&self.room.instance_list) {
-                            self.set_instance_var(instance, &accessor.var, array_index, value.clone(), context)?;
-                        }
-                    },
-                    Target::Global => {
-                        if let Some(field) = self.globals.vars.get_mut(&accessor.var) {
-                            field.set(array_index, value)
-                        } else {
-                            self.globals.vars.insert(accessor.var, Field::new(array_index, value));
-                        }
-                    },
-                    Target::Local => {
-                        if let Some(field) = context.locals.vars.get_mut(&accessor.var) {
-                            field.set(array_index, value)
-                        } else {
-                            context.locals.vars.insert(accessor.var, Field::new(array_index, value));
-                        }
-                    },
-                }
+                self.write_variable(context, target, &accessor.var, array_index, value)?;
             },
             Instruction::EvalExpression { node } => {
                 context.return_value = self.eval(node, context)?;
@@ -564,6 +516,11 @@ impl Game {
                 }
             },
             Instruction::RuntimeError { error } => return Err(error.clone()),
+            Instruction::Line { line } => {
+                if self.debugger.is_some() {
+                    self.debug_line(*line, context);
+                }
+            },
         }
 
         Ok(ReturnType::Normal)
@@ -596,7 +553,7 @@ impl Game {
                     }
 
                     let mut new_context = Context::copy_with_args(context, arg_values, args.len());
-                    self.execute(&instructions, &mut new_context)?;
+                    self.execute_code(&instructions, &mut new_context)?;
                     Ok(new_context.return_value)
                 } else {
                     Err(Error::NonexistentAsset(asset::Type::Script, *script_id as i32))
@@ -613,168 +570,12 @@ impl Game {
             Node::Field { accessor } => {
                 let target = self.get_target(context, &accessor.owner, self.globalvars.contains(&accessor.index))?;
                 let array_index = self.get_array_index(&accessor.array, context)?;
-                match target {
-                    Target::Single(None) if self.uninit_fields_are_zero => Ok(Default::default()),
-                    Target::Single(None) => Err(Error::UninitializedVariable(
-                        self.compiler.get_field_name(accessor.index).unwrap(),
-                        array_index,
-                    )),
-                    Target::Single(Some(instance)) => self.get_instance_field(instance, accessor.index, array_index),
-                    Target::Objects(index) => {
-                        if let Some(instance) =
-                            self.room.instance_list.iter_by_identity(index).next(&self.room.instance_list)
-                        {
-                            self.get_instance_field(instance, accessor.index, array_index)
-                        } else {
-                            if self.uninit_fields_are_zero {
-                                Ok(Default::default())
-                            } else {
-                                Err(Error::UninitializedVariable(
-                                    self.compiler.get_field_name(accessor.index).unwrap(),
-                                    array_index,
-                                ))
-                            }
-                        }
-                    },
-                    Target::All => {
-                        if let Some(instance) = self.room.instance_list.iter_by_drawing().next(&self.room.instance_list)
-                        {
-                            self.get_instance_field(instance, accessor.index, array_index)
-                        } else {
-                            if self.uninit_fields_are_zero {
-                                Ok(Default::default())
-                            } else {
-                                Err(Error::UninitializedVariable(
-                                    self.compiler.get_field_name(accessor.index).unwrap(),
-                                    array_index,
-                                ))
-                            }
-                        }
-                    },
-                    Target::Global => match self.globals.fields.get(&accessor.index).and_then(|x| x.get(array_index)) {
-                        Some(i) => Ok(i),
-                        None => {
-                            if self.uninit_fields_are_zero {
-                                Ok(Default::default())
-                            } else {
-                                return Err(Error::UninitializedVariable(
-                                    self.compiler.get_field_name(accessor.index).unwrap(),
-                                    array_index,
-                                ))
-                            }
-                        },
-                    },
-                    Target::Local => {
-                        match context.locals.fields.get(&accessor.index).and_then(|x| x.get(array_index)) {
-                            Some(i) => Ok(i),
-                            None => {
-                                if self.uninit_fields_are_zero {
-                                    Ok(Default::default())
-                                } else {
-                                    return Err(Error::UninitializedVariable(
-                                        self.compiler.get_field_name(accessor.index).unwrap(),
-                                        array_index,
-                                    ))
-                                }
-                            },
-                        }
-                    },
-                }
+                self.read_field(context, target, accessor.index, array_index)
             },
             Node::Variable { accessor } => {
                 let target = self.get_target(context, &accessor.owner, false)?;
                 let array_index = self.get_array_index(&accessor.array, context)?;
-                match target {
-                    Target::Single(None) if self.uninit_fields_are_zero => Ok(Default::default()),
-                    Target::Single(None) => Err(Error::UninitializedVariable(
-                        String::from(mappings::INSTANCE_VARIABLES.iter().find(|(_, x)| x == &accessor.var).unwrap().0),
-                        array_index,
-                    )),
-                    Target::Single(Some(instance)) => {
-                        self.get_instance_var(instance, &accessor.var, array_index, context)
-                    },
-                    Target::Objects(index) => {
-                        if let Some(instance) =
-                            self.room.instance_list.iter_by_identity(index).next(&self.room.instance_list)
-                        {
-                            self.get_instance_var(instance, &accessor.var, array_index, context)
-                        } else {
-                            if self.uninit_fields_are_zero {
-                                Ok(Default::default())
-                            } else {
-                                Err(Error::UninitializedVariable(
-                                    String::from(
-                                        mappings::INSTANCE_VARIABLES
-                                            .iter()
-                                            .find(|(_, x)| x == &accessor.var)
-                                            .unwrap()
-                                            .0,
-                                    ),
-                                    array_index,
-                                ))
-                            }
-                        }
-                    },
-                    Target::All => {
-                        if let Some(instance) = self.room.instance_list.iter_by_drawing().next(&self.room.instance_list)
-                        {
-                            self.get_instance_var(instance, &accessor.var, array_index, context)
-                        } else {
-                            if self.uninit_fields_are_zero {
-                                Ok(Default::default())
-                            } else {
-                                Err(Error::UninitializedVariable(
-                                    String::from(
-                                        mappings::INSTANCE_VARIABLES
-                                            .iter()
-                                            .find(|(_, x)| x == &accessor.var)
-                                            .unwrap()
-                                            .0,
-                                    ),
-                                    array_index,
-                                ))
-                            }
-                        }
-                    },
-                    Target::Global => match self.globals.vars.get(&accessor.var).and_then(|x| x.get(array_index)) {
-                        Some(i) => Ok(i),
-                        None => {
-                            if self.uninit_fields_are_zero {
-                                Ok(Default::default())
-                            } else {
-                                return Err(Error::UninitializedVariable(
-                                    String::from(
-                                        mappings::INSTANCE_VARIABLES
-                                            .iter()
-                                            .find(|(_, x)| x == &accessor.var)
-                                            .unwrap()
-                                            .0,
-                                    ),
-                                    array_index,
-                                ))
-                            }
-                        },
-                    },
-                    Target::Local => match context.locals.vars.get(&accessor.var).and_then(|x| x.get(array_index)) {
-                        Some(i) => Ok(i),
-                        None => {
-                            if self.uninit_fields_are_zero {
-                                Ok(Default::default())
-                            } else {
-                                return Err(Error::UninitializedVariable(
-                                    String::from(
-                                        mappings::INSTANCE_VARIABLES
-                                            .iter()
-                                            .find(|(_, x)| x == &accessor.var)
-                                            .unwrap()
-                                            .0,
-                                    ),
-                                    array_index,
-                                ))
-                            }
-                        },
-                    },
-                }
+                self.read_variable(context, target, &accessor.var, array_index)
             },
             Node::Binary { left, right, operator, type_unsafe } => {
                 // the + in += can happen here, and += ignores errors in the + portion
@@ -790,24 +591,246 @@ impl Game {
         }
     }
 
+    // Read a field from a target whose owner and array index have already been evaluated
+    pub(super) fn read_field(
+        &self,
+        context: &Context,
+        target: Target,
+        index: usize,
+        array_index: u32,
+    ) -> gml::Result<Value> {
+        match target {
+            Target::Single(None) if self.uninit_fields_are_zero => Ok(Default::default()),
+            Target::Single(None) => {
+                Err(Error::UninitializedVariable(self.compiler.get_field_name(index).unwrap(), array_index))
+            },
+            Target::Single(Some(instance)) => self.get_instance_field(instance, index, array_index),
+            Target::Objects(object) => {
+                if let Some(instance) = self.room.instance_list.iter_by_identity(object).next(&self.room.instance_list)
+                {
+                    self.get_instance_field(instance, index, array_index)
+                } else {
+                    if self.uninit_fields_are_zero {
+                        Ok(Default::default())
+                    } else {
+                        Err(Error::UninitializedVariable(self.compiler.get_field_name(index).unwrap(), array_index))
+                    }
+                }
+            },
+            Target::All => {
+                if let Some(instance) = self.room.instance_list.iter_by_drawing().next(&self.room.instance_list) {
+                    self.get_instance_field(instance, index, array_index)
+                } else {
+                    if self.uninit_fields_are_zero {
+                        Ok(Default::default())
+                    } else {
+                        Err(Error::UninitializedVariable(self.compiler.get_field_name(index).unwrap(), array_index))
+                    }
+                }
+            },
+            Target::Global => match self.globals.fields.get(&index).and_then(|x| x.get(array_index)) {
+                Some(i) => Ok(i),
+                None => {
+                    if self.uninit_fields_are_zero {
+                        Ok(Default::default())
+                    } else {
+                        return Err(Error::UninitializedVariable(
+                            self.compiler.get_field_name(index).unwrap(),
+                            array_index,
+                        ))
+                    }
+                },
+            },
+            Target::Local => match context.locals.fields.get(&index).and_then(|x| x.get(array_index)) {
+                Some(i) => Ok(i),
+                None => {
+                    if self.uninit_fields_are_zero {
+                        Ok(Default::default())
+                    } else {
+                        return Err(Error::UninitializedVariable(
+                            self.compiler.get_field_name(index).unwrap(),
+                            array_index,
+                        ))
+                    }
+                },
+            },
+        }
+    }
+
+    // Read an instance variable from a target whose owner and array index have already been evaluated
+    pub(super) fn read_variable(
+        &self,
+        context: &Context,
+        target: Target,
+        var: &InstanceVariable,
+        array_index: u32,
+    ) -> gml::Result<Value> {
+        match target {
+            Target::Single(None) if self.uninit_fields_are_zero => Ok(Default::default()),
+            Target::Single(None) => Err(Error::UninitializedVariable(
+                String::from(mappings::INSTANCE_VARIABLES.iter().find(|(_, x)| x == var).unwrap().0),
+                array_index,
+            )),
+            Target::Single(Some(instance)) => self.get_instance_var(instance, var, array_index, context),
+            Target::Objects(object) => {
+                if let Some(instance) = self.room.instance_list.iter_by_identity(object).next(&self.room.instance_list)
+                {
+                    self.get_instance_var(instance, var, array_index, context)
+                } else {
+                    if self.uninit_fields_are_zero {
+                        Ok(Default::default())
+                    } else {
+                        Err(Error::UninitializedVariable(
+                            String::from(mappings::INSTANCE_VARIABLES.iter().find(|(_, x)| x == var).unwrap().0),
+                            array_index,
+                        ))
+                    }
+                }
+            },
+            Target::All => {
+                if let Some(instance) = self.room.instance_list.iter_by_drawing().next(&self.room.instance_list) {
+                    self.get_instance_var(instance, var, array_index, context)
+                } else {
+                    if self.uninit_fields_are_zero {
+                        Ok(Default::default())
+                    } else {
+                        Err(Error::UninitializedVariable(
+                            String::from(mappings::INSTANCE_VARIABLES.iter().find(|(_, x)| x == var).unwrap().0),
+                            array_index,
+                        ))
+                    }
+                }
+            },
+            Target::Global => match self.globals.vars.get(var).and_then(|x| x.get(array_index)) {
+                Some(i) => Ok(i),
+                None => {
+                    if self.uninit_fields_are_zero {
+                        Ok(Default::default())
+                    } else {
+                        return Err(Error::UninitializedVariable(
+                            String::from(mappings::INSTANCE_VARIABLES.iter().find(|(_, x)| x == var).unwrap().0),
+                            array_index,
+                        ))
+                    }
+                },
+            },
+            Target::Local => match context.locals.vars.get(var).and_then(|x| x.get(array_index)) {
+                Some(i) => Ok(i),
+                None => {
+                    if self.uninit_fields_are_zero {
+                        Ok(Default::default())
+                    } else {
+                        return Err(Error::UninitializedVariable(
+                            String::from(mappings::INSTANCE_VARIABLES.iter().find(|(_, x)| x == var).unwrap().0),
+                            array_index,
+                        ))
+                    }
+                },
+            },
+        }
+    }
+
+    // Assign a value to a field on a target, also setting it as the context's return value
+    pub(super) fn write_field(
+        &mut self,
+        context: &mut Context,
+        target: Target,
+        index: usize,
+        array_index: u32,
+        value: Value,
+    ) {
+        context.return_value = value.clone();
+        match target {
+            Target::Single(None) => (),
+            Target::Single(Some(instance)) => {
+                self.set_instance_field(instance, index, array_index, value);
+            },
+            Target::Objects(object) => {
+                let mut iter = self.room.instance_list.iter_by_identity(object);
+                while let Some(instance) = iter.next(&self.room.instance_list) {
+                    self.set_instance_field(instance, index, array_index, value.clone())
+                }
+            },
+            Target::All => {
+                let mut iter = self.room.instance_list.iter_by_drawing();
+                while let Some(instance) = iter.next(&self.room.instance_list) {
+                    self.set_instance_field(instance, index, array_index, value.clone())
+                }
+            },
+            Target::Global => {
+                if let Some(field) = self.globals.fields.get_mut(&index) {
+                    field.set(array_index, value)
+                } else {
+                    self.globals.fields.insert(index, Field::new(array_index, value))
+                }
+            },
+            Target::Local => {
+                if let Some(field) = context.locals.fields.get_mut(&index) {
+                    field.set(array_index, value)
+                } else {
+                    context.locals.fields.insert(index, Field::new(array_index, value))
+                }
+            },
+        }
+    }
+
+    // Assign a value to an instance variable on a target, also setting it as the context's return value
+    pub(super) fn write_variable(
+        &mut self,
+        context: &mut Context,
+        target: Target,
+        var: &InstanceVariable,
+        array_index: u32,
+        value: Value,
+    ) -> gml::Result<()> {
+        context.return_value = value.clone();
+        match target {
+            Target::Single(None) => (),
+            Target::Single(Some(instance)) => {
+                self.set_instance_var(instance, var, array_index, value, context)?;
+            },
+            Target::Objects(object) => {
+                let mut iter = self.room.instance_list.iter_by_identity(object);
+                while let Some(instance) = iter.next(&self.room.instance_list) {
+                    self.set_instance_var(instance, var, array_index, value.clone(), context)?;
+                }
+            },
+            Target::All => {
+                let mut iter = self.room.instance_list.iter_by_drawing();
+                while let Some(instance) = iter.next(&self.room.instance_list) {
+                    self.set_instance_var(instance, var, array_index, value.clone(), context)?;
+                }
+            },
+            Target::Global => {
+                if let Some(field) = self.globals.vars.get_mut(var) {
+                    field.set(array_index, value)
+                } else {
+                    self.globals.vars.insert(*var, Field::new(array_index, value))
+                }
+            },
+            Target::Local => {
+                if let Some(field) = context.locals.vars.get_mut(var) {
+                    field.set(array_index, value)
+                } else {
+                    context.locals.vars.insert(*var, Field::new(array_index, value))
+                }
+            },
+        }
+        Ok(())
+    }
+
     // Resolves an ArrayAccessor to an index (u32)
     fn get_array_index(&mut self, accessor: &ArrayAccessor, context: &mut Context) -> gml::Result<u32> {
         match accessor {
             ArrayAccessor::None => Ok(0),
             ArrayAccessor::Single(node) => {
                 let index = self.eval(node, context)?.round();
-                if index < 0 || index >= 32000 { Err(Error::InvalidArrayIndex(index)) } else { Ok(index as u32) }
+                array_index(index, None)
             },
             ArrayAccessor::Double(node1, node2) => {
                 let index1 = self.eval(node1, context)?.round();
                 let index2 = self.eval(node2, context)?.round();
-                if index1 < 0 || index1 >= 32000 {
-                    Err(Error::InvalidArrayIndex(index1))
-                } else if index2 < 0 || index2 >= 32000 {
-                    Err(Error::InvalidArrayIndex(index2))
-                } else {
-                    Ok(((index1 * 32000) + index2) as u32)
-                }
+                array_index(index1, Some(index2))
             },
         }
     }
@@ -1136,8 +1159,11 @@ impl Game {
             InstanceVariable::MouseLastbutton => Ok(f64::from(self.input.mouse_lastbutton()).into()),
             InstanceVariable::KeyboardKey => Ok(f64::from(self.input.keyboard_key()).into()),
             InstanceVariable::KeyboardLastkey => Ok(f64::from(self.input.keyboard_lastkey()).into()),
-            InstanceVariable::KeyboardLastchar => todo!("keyboard_lastchar getter"),
-            InstanceVariable::KeyboardString => todo!("keyboard_string getter"),
+            InstanceVariable::KeyboardLastchar => match self.input.keyboard_lastchar() {
+                0 => Ok("".into()),
+                ch => Ok(vec![ch].into()),
+            },
+            InstanceVariable::KeyboardString => Ok(self.input.keyboard_string().to_vec().into()),
             InstanceVariable::CursorSprite => Ok(self.cursor_sprite.into()),
             InstanceVariable::ShowScore => Ok(self.score_capt_d.into()),
             InstanceVariable::ShowLives => Ok(self.lives_capt_d.into()),
@@ -1483,8 +1509,14 @@ impl Game {
                     self.input.set_keyboard_lastkey(vk);
                 }
             },
-            InstanceVariable::KeyboardLastchar => todo!("keyboard_lastchar setter"),
-            InstanceVariable::KeyboardString => todo!("keyboard_string setter"),
+            InstanceVariable::KeyboardLastchar => {
+                let string: gml::String = value.into();
+                self.input.set_keyboard_lastchar(string.as_ref().first().copied().unwrap_or(0));
+            },
+            InstanceVariable::KeyboardString => {
+                let string: gml::String = value.into();
+                self.input.set_keyboard_string(string.as_ref());
+            },
             InstanceVariable::CursorSprite => self.cursor_sprite = value.round(),
             InstanceVariable::ShowScore => {
                 self.has_set_show_score = true;
@@ -1553,7 +1585,7 @@ impl Game {
     }
 
     // Resolves an InstanceIdentifier to a Target
-    fn get_target(
+    pub(super) fn get_target(
         &mut self,
         context: &mut Context,
         identifier: &InstanceIdentifier,
@@ -1573,17 +1605,37 @@ impl Game {
             },
             InstanceIdentifier::Expression(node) => {
                 let value = self.eval(node, context).map(i32::from)?;
-                match value {
-                    gml::SELF | gml::UNSPECIFIED => Ok(Target::Single(Some(context.this))),
-                    gml::OTHER => Ok(Target::Single(Some(context.other))),
-                    gml::ALL => Ok(Target::All),
-                    gml::NOONE => Ok(Target::Single(None)),
-                    gml::GLOBAL => Ok(Target::Global),
-                    gml::LOCAL => Ok(Target::Local),
-                    i if i >= 100_000 => Ok(Target::Single(self.room.instance_list.get_by_instid(i))),
-                    i => Ok(Target::Objects(i)),
-                }
+                Ok(self.target_of(context, value))
             },
         }
     }
+
+    // Resolves the value of an instance expression, such as the left side of a dot, to a Target
+    pub(super) fn target_of(&self, context: &Context, value: i32) -> Target {
+        match value {
+            gml::SELF | gml::UNSPECIFIED => Target::Single(Some(context.this)),
+            gml::OTHER => Target::Single(Some(context.other)),
+            gml::ALL => Target::All,
+            gml::NOONE => Target::Single(None),
+            gml::GLOBAL => Target::Global,
+            gml::LOCAL => Target::Local,
+            i if i >= 100_000 => Target::Single(self.room.instance_list.get_by_instid(i)),
+            i => Target::Objects(i),
+        }
+    }
+}
+
+// Checks the evaluated indices of a 1D or 2D array access, and combines them into one index (u32)
+pub(super) fn array_index(index1: i32, index2: Option<i32>) -> gml::Result<u32> {
+    if index1 < 0 || index1 >= 32000 {
+        Err(Error::InvalidArrayIndex(index1))
+    } else if let Some(index2) = index2 {
+        if index2 < 0 || index2 >= 32000 {
+            Err(Error::InvalidArrayIndex(index2))
+        } else {
+            Ok(((index1 * 32000) + index2) as u32)
+        }
+    } else {
+        Ok(index1 as u32)
+    }
 }
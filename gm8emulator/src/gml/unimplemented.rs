@@ -0,0 +1,397 @@
+use crate::{
+    action::{self, Tree},
+    game::{ExtensionFunction, Game},
+    gml::{
+        mappings,
+        runtime::{ArrayAccessor, InstanceIdentifier, Instruction, Node},
+    },
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+// Generated by build.rs from every kernel function which still calls unimplemented_function!()
+include!(concat!(env!("OUT_DIR"), "/_unimplemented.rs"));
+
+/// What to do when game code calls a kernel function which hasn't been implemented yet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Policy {
+    /// Panic, aborting the emulator.
+    Panic,
+
+    /// Raise a GML runtime error, which the game or TAS UI can deal with.
+    Error,
+
+    /// Log a warning the first time it's called and return 0.
+    Default,
+}
+
+impl FromStr for Policy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "panic" => Ok(Self::Panic),
+            "error" => Ok(Self::Error),
+            "default" => Ok(Self::Default),
+            _ => Err(format!("unknown unimplemented function policy \"{}\" (expected panic, error or default)", s)),
+        }
+    }
+}
+
+/// Per-function table of policies for unimplemented kernel functions.
+pub struct PolicyTable {
+    /// Policy for any function which doesn't have its own, if one was given
+    default: Option<Policy>,
+
+    /// Policies for specific functions, by function ID
+    overrides: HashMap<usize, Policy>,
+
+    /// Whether each function ID refers to an unimplemented function
+    unimplemented: Box<[bool]>,
+
+    /// Functions which have already logged a warning under the Default policy
+    warned: HashSet<usize>,
+
+    /// Warnings which haven't been shown to the user yet
+    warnings: Vec<String>,
+
+    /// Every rule which has been applied, in order
+    rules: Vec<String>,
+}
+
+impl PolicyTable {
+    /// The policy used when none has been given.
+    pub const FALLBACK: Policy = Policy::Panic;
+
+    pub fn new() -> Self {
+        let mut unimplemented = vec![false; mappings::FUNCTIONS.len()].into_boxed_slice();
+        for id in UNIMPLEMENTED_FUNCTIONS.iter().filter_map(|name| mappings::FUNCTIONS.get_index(*name)) {
+            unimplemented[id] = true;
+        }
+        Self {
+            default: None,
+            overrides: HashMap::new(),
+            unimplemented,
+            warned: HashSet::new(),
+            warnings: Vec::new(),
+            rules: Vec::new(),
+        }
+    }
+
+    /// Applies a rule, which is either a policy name to use for all functions,
+    /// or `function=policy` to set the policy for one function.
+    pub fn apply(&mut self, rule: &str) -> Result<(), String> {
+        self.apply_rule(rule)?;
+        self.rules.push(rule.into());
+        Ok(())
+    }
+
+    fn apply_rule(&mut self, rule: &str) -> Result<(), String> {
+        match rule.split_once('=') {
+            Some((name, policy)) => {
+                let name = name.trim();
+                let policy = policy.trim().parse()?;
+                match mappings::FUNCTIONS.get_index(name) {
+                    Some(id) if self.unimplemented[id] => {
+                        self.overrides.insert(id, policy);
+                        Ok(())
+                    },
+                    Some(_) => Err(format!("function {} is already implemented", name)),
+                    None => Err(format!("unknown function {}", name)),
+                }
+            },
+            None => {
+                self.default = Some(rule.trim().parse()?);
+                Ok(())
+            },
+        }
+    }
+
+    /// Applies every rule set in another table on top of this one.
+    pub fn extend(&mut self, other: Self) {
+        if other.default.is_some() {
+            self.default = other.default;
+        }
+        self.overrides.extend(other.overrides);
+        self.rules.extend(other.rules);
+    }
+
+    /// Every rule which has been applied to this table.
+    pub fn rules(&self) -> &[String] {
+        &self.rules
+    }
+
+    /// Gets the policy for a function, or None if it's implemented.
+    pub fn get(&self, function_id: usize) -> Option<Policy> {
+        if self.is_unimplemented(function_id) {
+            Some(self.overrides.get(&function_id).copied().or(self.default).unwrap_or(Self::FALLBACK))
+        } else {
+            None
+        }
+    }
+
+    /// Queues a warning that a function was called and returned 0, the first time it's called for that function.
+    pub fn warn(&mut self, function_id: usize) {
+        if self.warned.insert(function_id) {
+            let name = mappings::FUNCTIONS.index(function_id).unwrap().0;
+            self.warnings.push(format!("called unimplemented function {}, returning 0", name));
+        }
+    }
+
+    /// Takes every warning queued since this was last called, so whoever's running the game can show them.
+    pub fn take_warnings(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Whether a given function ID refers to an unimplemented function.
+    pub fn is_unimplemented(&self, function_id: usize) -> bool {
+        self.unimplemented.get(function_id).copied().unwrap_or(false)
+    }
+}
+
+/// Every unimplemented function referenced by a game's compiled code, along with where it's referenced from.
+pub struct Report {
+    pub functions: BTreeMap<&'static str, Vec<String>>,
+}
+
+impl Report {
+    /// Walks all of a game's compiled code looking for calls to unimplemented functions.
+    pub fn new(game: &Game) -> Self {
+        let mut report = Self { functions: BTreeMap::new() };
+        let table = &game.unimplemented_policy;
+
+        for script in game.assets.scripts.iter().flatten() {
+            report.check_code(table, &script.compiled, || format!("script {}", script.name));
+        }
+        for trigger in game.assets.triggers.iter().flatten() {
+            report.check_code(table, &trigger.condition, || format!("trigger {}", trigger.name));
+        }
+        for timeline in game.assets.timelines.iter().flatten() {
+            for (moment, tree) in timeline.moments.borrow().iter() {
+                report.check_tree(table, &tree.borrow(), || format!("timeline {} moment {}", timeline.name, moment));
+            }
+        }
+        for object in game.assets.objects.iter().flatten() {
+            for (ev_type, map) in object.events.iter().enumerate() {
+                for (ev_number, tree) in map.iter() {
+                    report.check_tree(table, &tree.borrow(), || {
+                        format!("object {} event {},{}", object.name, ev_type, ev_number)
+                    });
+                }
+            }
+        }
+        for room in game.assets.rooms.iter().flatten() {
+            if let Ok(code) = &room.creation_code {
+                report.check_code(table, code, || format!("room {} creation code", room.name));
+            }
+            for instance in room.instances.iter() {
+                if let Ok(code) = &instance.creation {
+                    report.check_code(table, code, || format!("creation code of instance {}", instance.id));
+                }
+            }
+        }
+        for (i, function) in game.extension_functions.iter().enumerate() {
+            if let Some(ExtensionFunction::Gml(code)) = function {
+                report.check_code(table, code, || format!("extension function {}", i));
+            }
+        }
+
+        report
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+    }
+
+    fn add(&mut self, function_id: usize, location: String) {
+        let name = mappings::FUNCTIONS.index(function_id).unwrap().0;
+        let locations = self.functions.entry(name).or_insert_with(Vec::new);
+        if !locations.contains(&location) {
+            locations.push(location);
+        }
+    }
+
+    fn check_code(&mut self, table: &PolicyTable, code: &[Instruction], location: impl Fn() -> String) {
+        let mut found = Vec::new();
+        visit_instructions(code, &mut |id| {
+            if table.is_unimplemented(id) {
+                found.push(id);
+            }
+        });
+        for id in found {
+            self.add(id, location());
+        }
+    }
+
+    fn check_tree(&mut self, table: &PolicyTable, tree: &Tree, location: impl Fn() -> String) {
+        for action in tree.iter() {
+            match &action.body {
+                action::Body::Normal { args, body, .. } => {
+                    args.iter().for_each(|arg| self.check_node(table, arg, &location));
+                    match body {
+                        action::GmlBody::Function(id) => {
+                            if table.is_unimplemented(*id) {
+                                self.add(*id, location());
+                            }
+                        },
+                        action::GmlBody::Code(code) => self.check_code(table, code, &location),
+                    }
+                },
+                action::Body::Repeat { count } => self.check_node(table, count, &location),
+                _ => (),
+            }
+        }
+    }
+
+    fn check_node(&mut self, table: &PolicyTable, node: &Node, location: impl Fn() -> String) {
+        let mut found = Vec::new();
+        visit_node(node, &mut |id| {
+            if table.is_unimplemented(id) {
+                found.push(id);
+            }
+        });
+        for id in found {
+            self.add(id, location());
+        }
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "game references {} unimplemented function(s):", self.functions.len())?;
+        for (name, locations) in self.functions.iter() {
+            writeln!(f, "  > {} ({})", name, locations.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Calls the given closure with the ID of every kernel function called in some compiled code.
+fn visit_instructions(code: &[Instruction], f: &mut impl FnMut(usize)) {
+    for instruction in code.iter() {
+        match instruction {
+            Instruction::SetField { accessor, value } => {
+                visit_array(&accessor.array, f);
+                visit_owner(&accessor.owner, f);
+                visit_node(value, f);
+            },
+            Instruction::SetVariable { accessor, value } => {
+                visit_array(&accessor.array, f);
+                visit_owner(&accessor.owner, f);
+                visit_node(value, f);
+            },
+            Instruction::EvalExpression { node } => visit_node(node, f),
+            Instruction::IfElse { cond, if_body, else_body } => {
+                visit_node(cond, f);
+                visit_instructions(if_body, f);
+                visit_instructions(else_body, f);
+            },
+            Instruction::LoopUntil { cond, body } | Instruction::LoopWhile { cond, body } => {
+                visit_node(cond, f);
+                visit_instructions(body, f);
+            },
+            Instruction::LoopFor { cond, body, step } => {
+                visit_node(cond, f);
+                visit_instructions(body, f);
+                visit_instructions(step, f);
+            },
+            Instruction::Repeat { count, body } => {
+                visit_node(count, f);
+                visit_instructions(body, f);
+            },
+            Instruction::SetReturnValue { value } => visit_node(value, f),
+            Instruction::Switch { input, cases, body, .. } => {
+                visit_node(input, f);
+                cases.iter().for_each(|(case, _)| visit_node(case, f));
+                visit_instructions(body, f);
+            },
+            Instruction::With { target, body } => {
+                visit_node(target, f);
+                visit_instructions(body, f);
+            },
+            Instruction::Return { .. }
+            | Instruction::GlobalVar { .. }
+            | Instruction::RuntimeError { .. }
+            | Instruction::Line { .. } => (),
+        }
+    }
+}
+
+fn visit_node(node: &Node, f: &mut impl FnMut(usize)) {
+    match node {
+        Node::Function { args, function_id } => {
+            f(*function_id);
+            args.iter().for_each(|arg| visit_node(arg, f));
+        },
+        Node::Script { args, .. } | Node::ExtensionFunction { args, .. } => {
+            args.iter().for_each(|arg| visit_node(arg, f));
+        },
+        Node::Field { accessor } => {
+            visit_array(&accessor.array, f);
+            visit_owner(&accessor.owner, f);
+        },
+        Node::Variable { accessor } => {
+            visit_array(&accessor.array, f);
+            visit_owner(&accessor.owner, f);
+        },
+        Node::Binary { left, right, .. } => {
+            visit_node(left, f);
+            visit_node(right, f);
+        },
+        Node::Unary { child, .. } => visit_node(child, f),
+        Node::Literal { .. } | Node::Constant { .. } | Node::RuntimeError { .. } => (),
+    }
+}
+
+fn visit_array(array: &ArrayAccessor, f: &mut impl FnMut(usize)) {
+    match array {
+        ArrayAccessor::None => (),
+        ArrayAccessor::Single(index) => visit_node(index, f),
+        ArrayAccessor::Double(index1, index2) => {
+            visit_node(index1, f);
+            visit_node(index2, f);
+        },
+    }
+}
+
+fn visit_owner(owner: &InstanceIdentifier, f: &mut impl FnMut(usize)) {
+    if let InstanceIdentifier::Expression(node) = owner {
+        visit_node(node, f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policy_rules() {
+        let mut table = PolicyTable::new();
+        let implemented = mappings::FUNCTIONS.get_index("instance_create").unwrap();
+        assert_eq!(table.get(implemented), None);
+        if let Some(name) = UNIMPLEMENTED_FUNCTIONS.first() {
+            let id = mappings::FUNCTIONS.get_index(*name).unwrap();
+            assert_eq!(table.get(id), Some(Policy::Panic));
+        }
+
+        assert!(table.apply("default").is_ok());
+        assert!(table.apply("ignore").is_err());
+        assert!(table.apply("instance_create=panic").is_err());
+        assert!(table.apply("not_a_function=panic").is_err());
+        assert_eq!(table.rules(), ["default"]);
+
+        if let Some(name) = UNIMPLEMENTED_FUNCTIONS.first() {
+            let id = mappings::FUNCTIONS.get_index(*name).unwrap();
+            assert_eq!(table.get(id), Some(Policy::Default));
+            table.warn(id);
+            table.warn(id);
+            assert_eq!(table.take_warnings().len(), 1);
+            assert!(table.take_warnings().is_empty());
+            assert!(table.apply(&format!("{}=panic", name)).is_ok());
+            assert_eq!(table.get(id), Some(Policy::Panic));
+        }
+    }
+}
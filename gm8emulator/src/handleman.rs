@@ -56,6 +56,25 @@ impl<T> HandleList<T> {
     pub fn put(&mut self, handle: T) -> i32 {
         self.add(handle).unwrap()
     }
+
+    // Borrows two different handles at once, one of them mutably.
+    pub fn get_mut_and_ref(&mut self, index: i32, other: i32) -> Option<(&mut T, &T)> {
+        let index = usize::try_from(index).ok().filter(|&i| i < self.0.len())?;
+        let other = usize::try_from(other).ok().filter(|&i| i < self.0.len())?;
+        if index < other {
+            let (left, right) = self.0.split_at_mut(other);
+            Some((left[index].as_mut()?, right[0].as_ref()?))
+        } else if index > other {
+            let (left, right) = self.0.split_at_mut(index);
+            Some((right[0].as_mut()?, left[other].as_ref()?))
+        } else {
+            None
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (i32, &T)> {
+        self.0.iter().enumerate().filter_map(|(i, x)| Some((i.try_into().ok()?, x.as_ref()?)))
+    }
 }
 
 impl<T, const LEN: usize> HandleArray<T, LEN> {
@@ -69,6 +69,9 @@ pub trait UiCustomFunction {
     fn mouse_pos(&self) -> Vec2<f32>;
     fn rect(&self, min: Vec2<f32>, max: Vec2<f32>, colour: Colour, alpha: u8);
     fn rect_outline(&self, min: Vec2<f32>, max: Vec2<f32>, colour: Colour, alpha: u8);
+    fn line(&self, from: Vec2<f32>, to: Vec2<f32>, colour: Colour, alpha: u8);
+    fn push_clip_rect(&self, min: Vec2<f32>, max: Vec2<f32>);
+    fn pop_clip_rect(&self);
     fn begin_screen_cover(&mut self);
     fn popup(&mut self, message: &str) -> bool;
 }
@@ -176,6 +179,30 @@ impl UiCustomFunction for imgui::Ui {
         self.get_window_draw_list().add_rect(min, max, colour).build();
     }
 
+    fn line(&self, from: Vec2<f32>, to: Vec2<f32>, colour: Colour, alpha: u8) {
+        let colour: ImColor32 = imgui::ImColor32::from_rgba_f32s(
+            colour.r as f32,
+            colour.g as f32,
+            colour.b as f32,
+            (alpha as f32) / 255.
+        );
+        let from: [f32; 2] = [from.0, from.1];
+        let to: [f32; 2] = [to.0, to.1];
+        self.get_window_draw_list().add_line(from, to, colour).build();
+    }
+
+    fn push_clip_rect(&self, min: Vec2<f32>, max: Vec2<f32>) {
+        unsafe {
+            c::ImDrawList_PushClipRect(c::igGetWindowDrawList(), min.into(), max.into(), true);
+        }
+    }
+
+    fn pop_clip_rect(&self) {
+        unsafe {
+            c::ImDrawList_PopClipRect(c::igGetWindowDrawList());
+        }
+    }
+
     fn begin_screen_cover(&mut self) {
         unsafe {
             c::igSetNextWindowFocus();
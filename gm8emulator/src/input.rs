@@ -1,4 +1,5 @@
 use crate::types::ArraySerde;
+use encoding_rs::Encoding;
 use serde::{Deserialize, Serialize};
 
 const KEY_MAX: usize = u8::max_value() as usize + 1;
@@ -6,6 +7,8 @@ const MB_ANY: i8 = -1;
 const MB_NONE: i8 = 0;
 const VK_NOKEY: u8 = 0; // TODO: dont redefine
 const VK_ANYKEY: u8 = 1; // TODO: dont redefine
+const CHAR_BACKSPACE: u8 = 0x08;
+const KEYBOARD_STRING_MAX: usize = 1024;
 
 #[derive(Copy, Clone, Debug, Deserialize, Serialize)]
 #[repr(u8)]
@@ -475,6 +478,13 @@ pub fn ramen2mb(x: ramen::input::MouseButton) -> i8 {
     MouseButton::try_from(x).map(|e| e as i8).unwrap_or(0)
 }
 
+/// Encodes a typed character in the game's codepage, which is how GM8 receives text.
+/// Returns None if the codepage has no such character.
+pub fn encode_char(ch: char, encoding: &'static Encoding) -> Option<Vec<u8>> {
+    let (bytes, _, unmappable) = encoding.encode(ch.encode_utf8(&mut [0; 4]));
+    if unmappable { None } else { Some(bytes.into_owned()) }
+}
+
 fn mouse2button(code: i8) -> Option<Button> {
     match code {
         x if x == MouseButton::Left as i8 => Some(Button::MouseLeft),
@@ -508,6 +518,10 @@ pub struct Input {
     // gamemaker weirdness
     key_current: u8,
     key_previous: u8,
+    #[serde(deserialize_with = "crate::game::savestate::since_v1")]
+    key_lastchar: u8,
+    #[serde(deserialize_with = "crate::game::savestate::since_v1")]
+    key_string: Vec<u8>, // last KEYBOARD_STRING_MAX printable characters typed
     mouse_current: i8,
     mouse_previous: i8,
     mouse_position_previous: (i32, i32),
@@ -525,6 +539,8 @@ impl Input {
             mouse_wheel: (false, false),
             key_current: 0,
             key_previous: 0,
+            key_lastchar: 0,
+            key_string: Vec::new(),
             mouse_current: 0,
             mouse_previous: 0,
             mouse_position_previous: (0, 0),
@@ -679,7 +695,7 @@ impl Input {
     pub fn keyboard_clear_all(&mut self) {
         self.key_current = 0;
         self.key_previous = 0;
-        // TODO: self.key_lastchar = 0;
+        self.key_lastchar = 0;
         self.button_state.iter_mut().for_each(|x| *x = false);
         self.button_state_press.iter_mut().for_each(|x| *x = false);
         self.button_state_release.iter_mut().for_each(|x| *x = false);
@@ -729,6 +745,44 @@ impl Input {
         self.key_previous = vk;
     }
 
+    /// Handles a typed character. This comes from the OS after keyboard layout and shift are applied,
+    /// so it isn't derived from key presses. Backspace erases the last character of keyboard_string.
+    pub fn char_input(&mut self, ch: u8) {
+        self.key_lastchar = ch;
+        match ch {
+            CHAR_BACKSPACE => {
+                self.key_string.pop();
+            },
+            0x20..=0xFF => {
+                if self.key_string.len() >= KEYBOARD_STRING_MAX {
+                    self.key_string.remove(0);
+                }
+                self.key_string.push(ch);
+            },
+            _ => (),
+        }
+    }
+
+    #[inline]
+    pub fn keyboard_lastchar(&self) -> u8 {
+        self.key_lastchar
+    }
+
+    #[inline]
+    pub fn set_keyboard_lastchar(&mut self, ch: u8) {
+        self.key_lastchar = ch;
+    }
+
+    #[inline]
+    pub fn keyboard_string(&self) -> &[u8] {
+        &self.key_string
+    }
+
+    pub fn set_keyboard_string(&mut self, string: &[u8]) {
+        let start = string.len().saturating_sub(KEYBOARD_STRING_MAX);
+        self.key_string = string[start..].to_vec();
+    }
+
     fn mouse_check_button_internal_indirect(&self, state: &[bool; KEY_MAX], mb: i8) -> bool {
         match mb {
             MB_ANY => {
@@ -866,3 +920,25 @@ impl Input {
         *self = Self::new();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_text() {
+        let mut input = Input::new();
+        for ch in "aЖあ😀".chars() {
+            for byte in encode_char(ch, encoding_rs::SHIFT_JIS).into_iter().flatten() {
+                input.char_input(byte);
+            }
+        }
+        // Ж and あ are two bytes each in Shift-JIS, and there's no 😀
+        assert_eq!(input.keyboard_string(), b"a\x84\x47\x82\xA0");
+        assert_eq!(input.keyboard_lastchar(), 0xA0);
+
+        assert_eq!(encode_char('Ж', encoding_rs::WINDOWS_1251), Some(vec![0xC6]));
+        assert_eq!(encode_char('Ж', encoding_rs::WINDOWS_1252), None);
+        assert_eq!(encode_char('€', encoding_rs::WINDOWS_1252), Some(vec![0x80]));
+    }
+}
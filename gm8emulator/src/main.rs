@@ -18,7 +18,7 @@ use game::{
     Game, GameClock, PlayType, Replay,
 };
 use std::{
-    env, fs,
+    env, fs, io,
     path::{Path, PathBuf},
     process,
 };
@@ -48,13 +48,28 @@ fn xmain() -> i32 {
     opts.optflag("h", "help", "prints this help message");
     opts.optflag("s", "strict", "enable various data integrity checks");
     opts.optflag("t", "singlethread", "parse gamedata synchronously");
+    opts.optflag("", "experimental-legacy", "also reads GameMaker 5, 6 and 7 games (experimental)");
     opts.optflag("v", "verbose", "enables verbose logging");
     opts.optflag("r", "realtime", "disables clock spoofing");
+    opts.optflag("O", "no-optimize", "disables constant folding and dead code elimination in compiled GML");
+    opts.optopt("", "interpreter", "which interpreter runs GML (tree or bytecode, default tree)", "NAME");
+    opts.optflag("", "compare-interpreters", "replays -f with each interpreter, timing them and checking they agree");
+    opts.optflag("", "debug", "runs the GML debugger from stdin, or with -n, lets the recorder step lines");
+    opts.optopt("", "debug-port", "runs the game under the GML debugger, controlled over TCP", "PORT");
+    opts.optflag("", "console", "runs GML typed into stdin between frames");
+    opts.optopt("", "profile", "times GML and draw calls, writing folded stacks to FILE when the game ends", "FILE");
     opts.optopt("l", "no-framelimit-until", "disables the frame-limiter until specified frame", "FRAME");
     opts.optopt("n", "project-name", "name of TAS project to create or load", "NAME");
     opts.optopt("f", "replay-file", "path to savestate file to replay", "FILE");
     opts.optopt("o", "output-file", "output savestate name in replay mode", "FILE.bin");
     opts.optmulti("a", "game-arg", "argument to pass to the game", "ARG");
+    opts.optmulti("", "extensions", "folder to find extension packages in when running a project file", "DIR");
+    opts.optmulti(
+        "u",
+        "unimplemented",
+        "what to do when an unimplemented function is called (panic, error or default), or FUNCTION=POLICY",
+        "POLICY",
+    );
 
     let matches = match opts.parse(&args[1..]) {
         Ok(matches) => matches,
@@ -78,6 +93,7 @@ fn xmain() -> i32 {
 
     let strict = matches.opt_present("s");
     let multithread = !matches.opt_present("t");
+    let legacy = matches.opt_present("experimental-legacy");
     let spoof_time = !matches.opt_present("r");
     let frame_limit_at = matches
         .opt_str("l")
@@ -90,7 +106,9 @@ fn xmain() -> i32 {
         .unwrap_or(0);
     let frame_limiter = !matches.opt_present("l");
     let verbose = matches.opt_present("v");
+    let optimize = !matches.opt_present("O");
     let output_bin = matches.opt_str("o").map(PathBuf::from);
+    let profile_path = matches.opt_str("profile").map(PathBuf::from);
     let project_path = matches.opt_str("n").map(|name| {
         let mut p = env::current_dir().expect("std::env::current_dir() failed");
         p.push("projects");
@@ -171,13 +189,68 @@ fn xmain() -> i32 {
         }
     };
 
+    let mut unimplemented_policy = gml::unimplemented::PolicyTable::new();
+    for rule in matches.opt_strs("u") {
+        if let Err(e) = unimplemented_policy.apply(&rule) {
+            eprintln!("invalid argument for -u: {}", e);
+            return EXIT_FAILURE
+        }
+    }
+
+    let interpreter = match matches.opt_str("interpreter").map(|s| s.parse::<gml::Interpreter>()) {
+        Some(Ok(interpreter)) => interpreter,
+        Some(Err(e)) => {
+            eprintln!("invalid argument for --interpreter: {}", e);
+            return EXIT_FAILURE
+        },
+        None => gml::Interpreter::Tree,
+    };
+
+    let debug_port = match matches.opt_str("debug-port").map(|s| s.parse::<u16>()) {
+        Some(Ok(port)) => Some(port),
+        Some(Err(e)) => {
+            eprintln!("invalid argument for --debug-port: {}", e);
+            return EXIT_FAILURE
+        },
+        None => None,
+    };
+    let debug = matches.opt_present("debug") || debug_port.is_some();
+    if debug_port.is_some() && project_path.is_some() {
+        eprintln!("--debug-port can't be used with -n, use the debugger window in the recorder instead");
+        return EXIT_FAILURE
+    }
+
+    let console = matches.opt_present("console");
+    if console && project_path.is_some() {
+        eprintln!("--console can't be used with -n, use the console window in the recorder instead");
+        return EXIT_FAILURE
+    }
+    if console && replay.is_some() {
+        eprintln!("--console can't be used with -f, since it would make the replay go out of sync");
+        return EXIT_FAILURE
+    }
+    if console && debug && debug_port.is_none() {
+        eprintln!("--console can't be used with --debug, since they both read from stdin");
+        return EXIT_FAILURE
+    }
+
+    let compare = matches.opt_present("compare-interpreters");
+    if compare && (replay.is_none() || project_path.is_some()) {
+        eprintln!("--compare-interpreters needs a recording to replay, given with -f");
+        return EXIT_FAILURE
+    }
+    if compare && (debug || profile_path.is_some() || output_bin.is_some() || matches.opt_present("interpreter")) {
+        eprintln!("--compare-interpreters can't be used with --debug, --profile, -o or --interpreter");
+        return EXIT_FAILURE
+    }
+
     let mut game_args = matches.opt_strs("game-arg");
     game_args.insert(0, input.to_string());
     let game_args = game_args;
 
     let file_path = Path::new(&input);
 
-    let mut file = match fs::read(file_path) {
+    let file = match fs::read(file_path) {
         Ok(data) => data,
         Err(err) => {
             eprintln!("failed to open '{}': {}", input, err);
@@ -189,18 +262,19 @@ fn xmain() -> i32 {
         println!("loading '{}'...", input);
     }
 
-    #[rustfmt::skip]
-    let assets = gm8exe::reader::from_exe(
-        &mut file,                              // mut exe: AsRef<[u8]>
-        if verbose {                            // logger: Option<Fn(&str)>
-            Some(|s: &str| println!("{}", s))
+    let logger = if verbose { Some(|s: &str| println!("{}", s)) } else { None };
+    // packages are installed to the IDE's extensions folder, so a copy of that next to the project works too
+    let mut extension_dirs = matches.opt_strs("extensions").into_iter().map(PathBuf::from).collect::<Vec<_>>();
+    extension_dirs.extend(file_path.parent().map(|dir| dir.join("extensions")));
+    // Project files (.gmk/.gm81) can be run directly, which is handy during development
+    let load_assets = |mut file: Vec<u8>| {
+        if gm8exe::gmk::is_gmk(&file) {
+            gm8exe::gmk::from_gmk(&file, &extension_dirs, logger, strict, multithread)
         } else {
-            None
-        },
-        strict,                                 // strict: bool
-        multithread,                            // multithread: bool
-    );
-    let assets = match assets {
+            gm8exe::reader::from_exe(&mut file, logger, strict, multithread, legacy)
+        }
+    };
+    let assets = match load_assets(file) {
         Ok(assets) => assets,
         Err(err) => {
             eprintln!("failed to load '{}' - {}", input, err);
@@ -226,6 +300,36 @@ fn xmain() -> i32 {
         PlayType::Normal
     };
 
+    if compare {
+        // Launching takes the assets, so the second run has to load them again
+        let mut assets = Some(assets);
+        let launch = || -> Result<Game, Box<dyn std::error::Error>> {
+            let assets = match assets.take() {
+                Some(assets) => assets,
+                None => load_assets(fs::read(&absolute_path)?).map_err(|e| e.to_string())?,
+            };
+            let mut game = Game::launch(
+                assets,
+                absolute_path.clone(),
+                game_args.clone(),
+                None,
+                encoding,
+                false,
+                0,
+                play_type,
+                optimize,
+                false,
+            )?;
+            let mut policy = gml::unimplemented::PolicyTable::new();
+            for rule in matches.opt_strs("u") {
+                policy.apply(&rule)?;
+            }
+            game.unimplemented_policy.extend(policy);
+            Ok(game)
+        };
+        return compare_interpreters(replay.unwrap(), launch)
+    }
+
     let mut components = match Game::launch(
         assets,
         absolute_path,
@@ -235,6 +339,8 @@ fn xmain() -> i32 {
         frame_limiter,
         frame_limit_at,
         play_type,
+        optimize,
+        debug,
     ) {
         Ok(g) => g,
         Err(e) => {
@@ -243,9 +349,45 @@ fn xmain() -> i32 {
         },
     };
 
+    components.unimplemented_policy.extend(unimplemented_policy);
+    components.interpreter = interpreter;
+
+    // Most games call something unimplemented somewhere, so this is only worth showing when it's been asked for
+    if verbose || !components.unimplemented_policy.rules().is_empty() {
+        let report = gml::unimplemented::Report::new(&components);
+        if !report.is_empty() {
+            eprint!("{}", report);
+        }
+    }
+
+    // The recorder attaches its own debugger when it needs one
+    if debug && project_path.is_none() {
+        let frontend = match debug_port {
+            Some(port) => match game::debugger::tcp_frontend(port) {
+                Ok(frontend) => frontend,
+                Err(e) => {
+                    eprintln!("Failed to wait for a debugger on port {}: {}", port, e);
+                    return EXIT_FAILURE
+                },
+            },
+            None => game::debugger::stdio_frontend(),
+        };
+        let mut debugger = game::debugger::Debugger::new(frontend);
+        debugger.resume(game::debugger::Resume::StepInto);
+        components.debugger = Some(Box::new(debugger));
+    }
+
+    if console {
+        components.console = Some(Box::new(game::console::StdinConsole::spawn()));
+    }
+
+    if profile_path.is_some() {
+        components.profiler = Some(Box::new(game::profiler::Profiler::new()));
+    }
+
     let time_now = GameClock::SpoofedNanos(gml::datetime::now_as_nanos());
 
-    if let Err(err) = if let Some(path) = project_path {
+    let result = if let Some(path) = project_path {
         components.clock = time_now;
         components.record(path);
         Ok(())
@@ -275,10 +417,75 @@ fn xmain() -> i32 {
             std::fs::remove_dir_all(temp_dir).ok();
         }
         result
-    } {
+    };
+
+    if let (Some(path), Some(profiler)) = (profile_path, &components.profiler) {
+        match fs::File::create(&path).and_then(|f| profiler.write_folded(&components, io::BufWriter::new(f))) {
+            Ok(()) => println!("Wrote profile to {:?}", path),
+            Err(e) => eprintln!("Failed to write profile to {:?}: {}", path, e),
+        }
+    }
+
+    if let Err(err) = result {
         println!("Runtime error: {}", err);
         EXIT_FAILURE
     } else {
         EXIT_SUCCESS
     }
 }
+
+/// Replays a recording with each interpreter in turn, without the frame limiter, so they can be timed against each
+/// other. The game's state is fingerprinted after every frame, and the first frame where they disagree is reported.
+fn compare_interpreters(
+    replay: Replay,
+    mut launch: impl FnMut() -> Result<Game, Box<dyn std::error::Error>>,
+) -> i32 {
+    let mut traces = Vec::new();
+    for interpreter in [gml::Interpreter::Tree, gml::Interpreter::Bytecode] {
+        let mut game = match launch() {
+            Ok(game) => game,
+            Err(e) => {
+                eprintln!("Failed to launch game: {}", e);
+                return EXIT_FAILURE
+            },
+        };
+        game.interpreter = interpreter;
+        game.replay_trace = Some(Vec::new());
+
+        // The replay only stops at the end if it has somewhere to save to
+        let output = env::temp_dir().join(format!("gm8emulator-compare-{}.bin", process::id()));
+        let temp_dir = PathBuf::from(game.decode_str(game.temp_directory.as_ref()).into_owned());
+        let files_to_delete = game
+            .included_files
+            .iter()
+            .filter(|i| i.remove_at_end)
+            .map(|i| PathBuf::from(game.decode_str(i.name.as_ref()).into_owned()))
+            .collect::<Vec<_>>();
+        let result = game.replay(replay.clone(), Some(output.clone()));
+        for file in files_to_delete.into_iter().chain(Some(output)) {
+            fs::remove_file(file).ok();
+        }
+        fs::remove_dir_all(temp_dir).ok();
+        if let Err(e) = result {
+            println!("Runtime error using the {} interpreter: {}", interpreter, e);
+            return EXIT_FAILURE
+        }
+        traces.push(game.replay_trace.take().unwrap_or_default());
+    }
+
+    let (tree, bytecode) = (&traces[0], &traces[1]);
+    match tree.iter().zip(bytecode.iter()).position(|(a, b)| a != b) {
+        Some(frame) => {
+            println!("The interpreters disagree from frame {} onwards", frame);
+            EXIT_FAILURE
+        },
+        None if tree.len() != bytecode.len() => {
+            println!("The interpreters ran for {} and {} frames", tree.len(), bytecode.len());
+            EXIT_FAILURE
+        },
+        None => {
+            println!("The interpreters agree on all {} frames", tree.len());
+            EXIT_SUCCESS
+        },
+    }
+}
@@ -7,7 +7,7 @@ use crate::types::Colour;
 use atlas::{AtlasRect, AtlasRef};
 use ramen::{connection::Connection, window::Window};
 use serde::{Deserialize, Serialize};
-use std::any::Any;
+use std::{any::Any, time::Duration};
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Scaling {
@@ -177,6 +177,13 @@ pub struct VertexBuffer {
 
 pub struct Renderer(Box<dyn RendererTrait>);
 
+/// How many draw calls a renderer has made, and how long it spent making them.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DrawStats {
+    pub calls: u64,
+    pub time: Duration,
+}
+
 pub trait RendererTrait {
     fn as_any(&self) -> &dyn Any;
     fn max_texture_size(&self) -> u32;
@@ -201,6 +208,9 @@ pub trait RendererTrait {
     fn get_vsync(&self) -> bool;
     fn wait_vsync(&self);
 
+    /// Totals for every draw call made since the renderer was created, for profiling.
+    fn draw_stats(&self) -> DrawStats;
+
     fn get_rect(&self, id: AtlasRef) -> Option<&AtlasRect>;
 
     fn draw_sprite(&mut self, tex: AtlasRef, x: f64, y: f64, xs: f64, ys: f64, ang: f64, col: i32, alpha: f64) {
@@ -601,6 +611,10 @@ impl Renderer {
         self.0.wait_vsync()
     }
 
+    pub fn draw_stats(&self) -> DrawStats {
+        self.0.draw_stats()
+    }
+
     pub fn draw_sprite(
         &mut self,
         texture: AtlasRef,
@@ -1,7 +1,7 @@
 use crate::{
     render::{
         atlas::{AtlasBuilder, AtlasRect, AtlasRef},
-        mat4mult, BlendType, Fog, Light, PrimitiveBuilder, PrimitiveShape, PrimitiveType, RendererOptions,
+        mat4mult, BlendType, DrawStats, Fog, Light, PrimitiveBuilder, PrimitiveShape, PrimitiveType, RendererOptions,
         RendererTrait, SavedTexture, Scaling, Vertex, VertexBuffer,
     },
     types::Colour,
@@ -9,7 +9,7 @@ use crate::{
 use memoffset::offset_of;
 use ramen::{connection::Connection, window::Window};
 use rect_packer::DensePacker;
-use std::{any::Any, f64::consts::PI, ffi::CStr, mem::size_of, ptr};
+use std::{any::Any, f64::consts::PI, ffi::CStr, mem::size_of, ptr, time::Instant};
 
 /// Auto-generated OpenGL bindings from gl_generator
 pub mod gl {
@@ -197,6 +197,7 @@ pub struct RendererImpl {
     depth: f32,
     primitive_2d: PrimitiveBuilder,
     primitive_3d: PrimitiveBuilder,
+    draw_stats: DrawStats,
 
     loc_gm81_normalize: GLint, // uniform bool gm81_normalize
     loc_tex: GLint,            // uniform sampler2D tex
@@ -637,6 +638,7 @@ impl RendererImpl {
                 depth: 0.0,
                 primitive_2d: PrimitiveBuilder::new(Default::default(), PrimitiveType::PointList, false),
                 primitive_3d: PrimitiveBuilder::new(Default::default(), PrimitiveType::PointList, false),
+                draw_stats: Default::default(),
 
                 loc_gm81_normalize: gl.GetUniformLocation(program, b"gm81_normalize\0".as_ptr().cast()),
                 loc_tex: gl.GetUniformLocation(program, b"tex\0".as_ptr().cast()),
@@ -771,6 +773,7 @@ impl RendererImpl {
             return
         }
 
+        let start = Instant::now();
         unsafe {
             // if something else broke check here just in case
             match self.gl.GetError() {
@@ -802,6 +805,8 @@ impl RendererImpl {
                 gl::STREAM_DRAW,
             );
         }
+        self.draw_stats.calls += 1;
+        self.draw_stats.time += start.elapsed();
     }
 }
 
@@ -1050,6 +1055,10 @@ impl RendererTrait for RendererImpl {
         unsafe { self.imp.wait_vsync() }
     }
 
+    fn draw_stats(&self) -> DrawStats {
+        self.draw_stats
+    }
+
     fn create_sprite_colour(&mut self, width: i32, height: i32, col: Colour) -> Result<AtlasRef, String> {
         let atlas_ref = self.create_surface(width, height, false)?;
         if let Some(rect) = self.get_rect(atlas_ref) {
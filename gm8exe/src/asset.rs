@@ -1,4 +1,5 @@
 pub mod background;
+mod bitmap;
 pub mod code_action;
 pub mod constant;
 pub mod extension;
@@ -1,5 +1,5 @@
 use crate::{
-    asset::{assert_ver, Asset, Error, PascalString, ReadChunk, ReadPascalString, WritePascalString},
+    asset::{assert_ver, bitmap, Asset, Error, PascalString, ReadChunk, ReadPascalString, WritePascalString},
     GameVersion,
 };
 use byteorder::{ReadBytesExt, WriteBytesExt, LE};
@@ -8,6 +8,9 @@ use std::io::{self, Read};
 pub const VERSION1: u32 = 710;
 pub const VERSION2: u32 = 800;
 
+/// The version of backgrounds from before GM8, which have a bitmap image.
+pub const VERSION_LEGACY: u32 = 543;
+
 pub struct Background {
     /// The asset name present in GML and the editor.
     pub name: PascalString,
@@ -25,8 +28,11 @@ pub struct Background {
 }
 
 impl Asset for Background {
-    fn deserialize_exe(mut reader: impl Read, _version: GameVersion, strict: bool) -> Result<Self, Error> {
+    fn deserialize_exe(mut reader: impl Read, version: GameVersion, strict: bool) -> Result<Self, Error> {
         let name = reader.read_pas_string()?;
+        if version.is_legacy() {
+            return Self::deserialize_legacy(reader, name, strict)
+        }
 
         let version1 = reader.read_u32::<LE>()?;
         let version2 = reader.read_u32::<LE>()?;
@@ -52,8 +58,11 @@ impl Asset for Background {
         }
     }
 
-    fn serialize_exe(&self, mut writer: impl io::Write, _version: GameVersion) -> io::Result<()> {
+    fn serialize_exe(&self, mut writer: impl io::Write, version: GameVersion) -> io::Result<()> {
         writer.write_pas_string(&self.name)?;
+        if version.is_legacy() {
+            return self.serialize_legacy(writer)
+        }
         writer.write_u32::<LE>(VERSION1)?;
         writer.write_u32::<LE>(VERSION2)?;
         writer.write_u32::<LE>(self.width)?;
@@ -65,3 +74,88 @@ impl Asset for Background {
         Ok(())
     }
 }
+
+impl Background {
+    // Reads the rest of a pre-GM8 background, where the image is a bitmap after the tileset settings
+    fn deserialize_legacy(mut reader: impl Read, name: PascalString, strict: bool) -> Result<Self, Error> {
+        let version = reader.read_u32::<LE>()?;
+        if strict {
+            assert_ver(version, VERSION_LEGACY)?;
+        }
+        reader.read_u32::<LE>()?; // width
+        reader.read_u32::<LE>()?; // height
+        let transparent = reader.read_u32::<LE>()? != 0;
+        reader.read_u32::<LE>()?; // smooth edges
+        reader.read_u32::<LE>()?; // preload texture
+
+        // Tileset settings (is tileset, tile size, offset and separation) are only used by the room editor
+        for _ in 0..7 {
+            reader.read_u32::<LE>()?;
+        }
+
+        let image = if reader.read_u32::<LE>()? != 0 { bitmap::read(&mut reader, transparent)? } else { None };
+        match image {
+            Some(image) if image.width > 0 && image.height > 0 => {
+                Ok(Background { name, width: image.width, height: image.height, data: Some(image.data) })
+            },
+            _ => Ok(Background { name, width: 0, height: 0, data: None }),
+        }
+    }
+
+    fn serialize_legacy(&self, mut writer: impl io::Write) -> io::Result<()> {
+        writer.write_u32::<LE>(VERSION_LEGACY)?;
+        writer.write_u32::<LE>(self.width)?;
+        writer.write_u32::<LE>(self.height)?;
+        let transparent =
+            self.data.as_ref().is_some_and(|data| bitmap::bottom_left_transparent(self.width, self.height, data));
+        writer.write_u32::<LE>(transparent.into())?;
+        writer.write_u32::<LE>(0)?; // smooth edges
+        writer.write_u32::<LE>(1)?; // preload texture
+        writer.write_u32::<LE>(0)?; // is tileset
+        writer.write_u32::<LE>(16)?; // tile width
+        writer.write_u32::<LE>(16)?; // tile height
+        for _ in 0..4 {
+            writer.write_u32::<LE>(0)?; // offset and separation
+        }
+        match &self.data {
+            Some(data) if self.width * self.height != 0 => {
+                writer.write_u32::<LE>(1)?;
+                bitmap::write(&mut writer, self.width, self.height, data)?;
+            },
+            _ => writer.write_u32::<LE>(0)?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_round_trip() {
+        let data = (0..3 * 2).flat_map(|i| [i, i * 2, i * 3, 255]).collect::<Vec<u8>>().into_boxed_slice();
+        let background = Background {
+            name: PascalString(b"bg_test".to_vec().into_boxed_slice()),
+            width: 3,
+            height: 2,
+            data: Some(data),
+        };
+        let mut buf = Vec::new();
+        background.serialize_exe(&mut buf, GameVersion::GameMaker7_0).unwrap();
+        let read = Background::deserialize_exe(buf.as_slice(), GameVersion::GameMaker7_0, true).unwrap();
+        assert_eq!((read.width, read.height), (3, 2));
+        assert_eq!(read.data, background.data);
+    }
+
+    #[test]
+    fn legacy_without_image() {
+        let background =
+            Background { name: PascalString(b"bg_empty".to_vec().into_boxed_slice()), width: 0, height: 0, data: None };
+        let mut buf = Vec::new();
+        background.serialize_exe(&mut buf, GameVersion::GameMaker6_0).unwrap();
+        let read = Background::deserialize_exe(buf.as_slice(), GameVersion::GameMaker6_0, true).unwrap();
+        assert_eq!((read.width, read.height), (0, 0));
+        assert!(read.data.is_none());
+    }
+}
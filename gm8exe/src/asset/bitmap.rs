@@ -0,0 +1,110 @@
+//! Images as sprites and backgrounds stored them before GM8: a zlib-compressed .bmp file, with a -1 in front
+//! instead if there's no image. Transparency came from the colour of the bottom-left pixel rather than alpha.
+
+use crate::asset::{Error, ReadChunk};
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use flate2::{bufread::ZlibDecoder, write::ZlibEncoder, Compression};
+use std::io::{self, Read, Write};
+
+pub struct Bitmap {
+    pub width: u32,
+    pub height: u32,
+
+    /// BGRA pixeldata, top row first
+    pub data: Box<[u8]>,
+}
+
+/// Reads an image which may be missing. Every pixel comes out opaque unless `transparent` is set,
+/// in which case any pixel the same colour as the bottom-left one is made fully transparent.
+pub fn read(reader: &mut impl Read, transparent: bool) -> Result<Option<Bitmap>, Error> {
+    if reader.read_i32::<LE>()? == -1 {
+        return Ok(None)
+    }
+    let len = reader.read_u32::<LE>()? as usize;
+    let mut bmp = Vec::new();
+    ZlibDecoder::new(reader.read_chunk(len)?.as_slice()).read_to_end(&mut bmp)?;
+    let mut bitmap = decode(&bmp)?;
+    if transparent && bitmap.width != 0 && bitmap.height != 0 {
+        let bottom_left = (bitmap.height - 1) as usize * bitmap.width as usize * 4;
+        let key = [bitmap.data[bottom_left], bitmap.data[bottom_left + 1], bitmap.data[bottom_left + 2]];
+        for pixel in bitmap.data.chunks_exact_mut(4) {
+            if pixel[..3] == key {
+                pixel[3] = 0;
+            }
+        }
+    }
+    Ok(Some(bitmap))
+}
+
+/// Writes an image as a 32-bit bitmap.
+pub fn write(writer: &mut impl Write, width: u32, height: u32, bgra: &[u8]) -> io::Result<()> {
+    let pixel_len = width * height * 4;
+    let mut enc = ZlibEncoder::new(Vec::new(), Compression::default());
+    enc.write_all(b"BM")?;
+    enc.write_u32::<LE>(54 + pixel_len)?; // file size
+    enc.write_u32::<LE>(0)?; // reserved
+    enc.write_u32::<LE>(54)?; // pixel data offset
+    enc.write_u32::<LE>(40)?; // info header size
+    enc.write_u32::<LE>(width)?;
+    enc.write_u32::<LE>(height)?; // positive height - rows are stored bottom-up
+    enc.write_u16::<LE>(1)?; // planes
+    enc.write_u16::<LE>(32)?; // bits per pixel
+    enc.write_u32::<LE>(0)?; // compression - none
+    enc.write_u32::<LE>(pixel_len)?;
+    enc.write_u32::<LE>(0)?; // horizontal resolution
+    enc.write_u32::<LE>(0)?; // vertical resolution
+    enc.write_u32::<LE>(0)?; // palette size
+    enc.write_u32::<LE>(0)?; // important colours
+    if width != 0 {
+        for row in bgra.chunks_exact(width as usize * 4).rev() {
+            enc.write_all(row)?;
+        }
+    }
+    let encoded = enc.finish()?;
+    writer.write_i32::<LE>(10)?; // anything but -1
+    writer.write_u32::<LE>(encoded.len() as u32)?;
+    writer.write_all(&encoded)
+}
+
+/// Whether an image would be written with the transparent flag, which is when its bottom-left pixel is.
+pub fn bottom_left_transparent(width: u32, height: u32, bgra: &[u8]) -> bool {
+    let alpha = ((height as usize).saturating_sub(1) * width as usize) * 4 + 3;
+    bgra.get(alpha).map(|a| *a == 0).unwrap_or(false)
+}
+
+// Decodes an uncompressed 24 or 32-bit .bmp file
+fn decode(bmp: &[u8]) -> Result<Bitmap, Error> {
+    let mut header = bmp;
+    if header.read_u16::<LE>()? != u16::from_le_bytes(*b"BM") {
+        return Err(Error::MalformedData)
+    }
+    header.read_u32::<LE>()?; // file size
+    header.read_u32::<LE>()?; // reserved
+    let offset = header.read_u32::<LE>()? as usize;
+    header.read_u32::<LE>()?; // info header size
+    let width = header.read_i32::<LE>()?;
+    let height = header.read_i32::<LE>()?;
+    header.read_u16::<LE>()?; // planes
+    let bpp = header.read_u16::<LE>()?;
+    let compression = header.read_u32::<LE>()?;
+    if width < 0 || !matches!(bpp, 24 | 32) || compression != 0 {
+        return Err(Error::MalformedData)
+    }
+
+    // a negative height means the rows are stored top-down
+    let (width, height, bottom_up) = (width as u32, height.unsigned_abs(), height > 0);
+    let bytes_per_pixel = usize::from(bpp / 8);
+    let stride = (width as usize * bytes_per_pixel + 3) & !3;
+    let pixels = bmp.get(offset..).ok_or(Error::MalformedData)?;
+    if pixels.len() < stride * height as usize {
+        return Err(Error::MalformedData)
+    }
+    let mut data = Vec::with_capacity(width as usize * height as usize * 4);
+    for y in 0..height as usize {
+        let row = if bottom_up { height as usize - 1 - y } else { y };
+        for pixel in pixels[row * stride..].chunks_exact(bytes_per_pixel).take(width as usize) {
+            data.extend_from_slice(&[pixel[0], pixel[1], pixel[2], 255]);
+        }
+    }
+    Ok(Bitmap { width, height, data: data.into_boxed_slice() })
+}
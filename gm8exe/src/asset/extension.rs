@@ -1,13 +1,14 @@
 use crate::{
-    asset::{assert_ver, Error, PascalString, ReadPascalString},
+    asset::{assert_ver, Error, PascalString, ReadPascalString, WritePascalString},
     reader::inflate,
 };
-use byteorder::{ReadBytesExt, LE};
-use std::io::{self, Read, Seek, SeekFrom};
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use flate2::{write::ZlibEncoder, Compression};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 
 pub const VERSION: u32 = 700;
 
-const ARG_MAX: usize = 17;
+pub const ARG_MAX: usize = 17;
 
 pub struct Extension {
     /// The name of the extension.
@@ -187,68 +188,143 @@ impl Extension {
             })
             .collect::<Result<_, Error>>()?;
 
-        let contents_len = reader.read_u32::<LE>()? as usize - 4;
-        let seed1_raw = reader.read_u32::<LE>()?;
-        let data_pos = reader.position() as usize;
-        reader.seek(SeekFrom::Current(contents_len as _))?;
-
-        // Don't do decryption if there are no contents
-        if contents_len != 0 {
-            // decryption setup
-            let mut char_table = [0u8; 0x200];
-            let mut seed1: i32 = seed1_raw as _;
-            let mut seed2: i32 = (seed1 % 0xFA) + 6;
-            seed1 /= 0xFA;
-            if seed1 < 0 {
-                seed1 += 100;
-            }
-            if seed2 < 0 {
-                seed2 += 100;
-            }
-            for (i, val) in char_table.iter_mut().enumerate() {
-                *val = (i % 256) as u8; // 0-255 repeating (twice)
-            }
+        read_contents(reader, &mut files)?;
+        Ok(Extension { name, folder_name, files })
+    }
 
-            // calculating char table - pass 1: pseudorandom byteswap
-            for i in 1..0x2711 {
-                let idx: usize = ((((i * seed2 as u32) + seed1 as u32) % 0xFE) + 1) as _;
-                let b1 = char_table[idx];
-                let b2 = char_table[idx + 1];
-                char_table[idx] = b2;
-                char_table[idx + 1] = b1;
+    /// Writes the extension in the format `read` expects, encrypting file contents with the given seed.
+    pub fn write(&self, writer: &mut impl io::Write, seed: u32) -> io::Result<()> {
+        writer.write_u32::<LE>(VERSION)?;
+        writer.write_pas_string(&self.name)?;
+        writer.write_pas_string(&self.folder_name)?;
+
+        writer.write_u32::<LE>(self.files.len() as u32)?;
+        for file in &self.files {
+            writer.write_u32::<LE>(VERSION)?;
+            writer.write_pas_string(&file.name)?;
+            writer.write_u32::<LE>(file.kind as u32)?;
+            writer.write_pas_string(&file.initializer)?;
+            writer.write_pas_string(&file.finalizer)?;
+
+            writer.write_u32::<LE>(file.functions.len() as u32)?;
+            for function in &file.functions {
+                writer.write_u32::<LE>(VERSION)?;
+                writer.write_pas_string(&function.name)?;
+                writer.write_pas_string(&function.external_name)?;
+                writer.write_u32::<LE>(function.convention as u32)?;
+                writer.write_u32::<LE>(function.id)?;
+                writer.write_i32::<LE>(function.arg_count)?;
+                for kind in function.arg_types.iter() {
+                    writer.write_u32::<LE>(*kind as u32)?;
+                }
+                writer.write_u32::<LE>(function.return_type as u32)?;
             }
 
-            // .. pass 2: use low half to scramble top half
-            for i in 0..0x100 {
-                let lo: u8 = char_table[i + 1];
-                char_table[lo as usize + 0x100] = (i as u8).wrapping_add(1);
+            writer.write_u32::<LE>(file.consts.len() as u32)?;
+            for constant in &file.consts {
+                writer.write_u32::<LE>(VERSION)?;
+                writer.write_pas_string(&constant.name)?;
+                writer.write_pas_string(&constant.value)?;
             }
+        }
 
-            // decrypt data chunk
-            for byte in &mut reader.get_mut()[data_pos + 1..data_pos + contents_len] {
-                *byte = char_table[*byte as usize + 0x100];
-            }
+        write_contents(writer, &self.files, seed)
+    }
+}
 
-            let end_pos = reader.position() as usize;
-            reader.set_position(data_pos as u64);
+/// Reads the encrypted contents of an extension's files, which come after the list of files.
+/// This is also how extension packages installed with the IDE store them.
+pub(crate) fn read_contents(reader: &mut io::Cursor<&mut [u8]>, files: &mut [File]) -> Result<(), Error> {
+    let contents_len = reader.read_u32::<LE>()? as usize - 4;
+    let seed1_raw = reader.read_u32::<LE>()?;
+    let data_pos = reader.position() as usize;
+    reader.seek(SeekFrom::Current(contents_len as _))?;
+
+    // Don't do decryption if there are no contents
+    if contents_len != 0 {
+        let char_table = char_table(seed1_raw);
+
+        // decrypt data chunk
+        for byte in &mut reader.get_mut()[data_pos + 1..data_pos + contents_len] {
+            *byte = char_table[*byte as usize + 0x100];
+        }
 
-            // write file chunks
-            for file in &mut files {
-                if file.kind != FileKind::ActionLibrary {
-                    let len = reader.read_u32::<LE>()? as usize;
-                    let pos = reader.position() as usize;
+        let end_pos = reader.position() as usize;
+        reader.set_position(data_pos as u64);
 
-                    reader.seek(SeekFrom::Current(len as i64))?; // pre-check for next get
-                    let mut file_bytes = Vec::new();
-                    inflate(reader.get_ref().get(pos..pos + len).unwrap_or_else(|| unreachable!()))
-                        .read_to_end(&mut file_bytes)?;
-                    file.contents = file_bytes.into_boxed_slice();
-                }
-            }
+        // write file chunks
+        for file in files.iter_mut() {
+            if file.kind != FileKind::ActionLibrary {
+                let len = reader.read_u32::<LE>()? as usize;
+                let pos = reader.position() as usize;
 
-            reader.set_position(end_pos as u64);
+                reader.seek(SeekFrom::Current(len as i64))?; // pre-check for next get
+                let mut file_bytes = Vec::new();
+                inflate(reader.get_ref().get(pos..pos + len).unwrap_or_else(|| unreachable!()))
+                    .read_to_end(&mut file_bytes)?;
+                file.contents = file_bytes.into_boxed_slice();
+            }
         }
 
-        Ok(Extension { name, folder_name, files })
+        reader.set_position(end_pos as u64);
     }
+    Ok(())
+}
+
+/// Writes the contents of an extension's files in the format `read_contents` expects.
+pub(crate) fn write_contents(writer: &mut impl io::Write, files: &[File], seed: u32) -> io::Result<()> {
+    // action libraries have no contents in the exe
+    let mut contents = Vec::new();
+    for file in files.iter().filter(|f| f.kind != FileKind::ActionLibrary) {
+        let mut enc = ZlibEncoder::new(Vec::new(), Compression::default());
+        enc.write_all(&file.contents)?;
+        let data = enc.finish()?;
+        contents.write_u32::<LE>(data.len() as u32)?;
+        contents.extend_from_slice(&data);
+    }
+
+    // the first byte is left unencrypted, the rest are substituted using the low half of the table
+    let char_table = char_table(seed);
+    for byte in contents.iter_mut().skip(1) {
+        *byte = char_table[usize::from(*byte)];
+    }
+
+    writer.write_u32::<LE>(contents.len() as u32 + 4)?;
+    writer.write_u32::<LE>(seed)?;
+    writer.write_all(&contents)?;
+    Ok(())
+}
+
+/// Calculates the table used to encrypt extension contents. The low half encrypts, the high half decrypts.
+fn char_table(seed: u32) -> [u8; 0x200] {
+    let mut char_table = [0u8; 0x200];
+    let mut seed1: i32 = seed as _;
+    let mut seed2: i32 = (seed1 % 0xFA) + 6;
+    seed1 /= 0xFA;
+    if seed1 < 0 {
+        seed1 += 100;
+    }
+    if seed2 < 0 {
+        seed2 += 100;
+    }
+    for (i, val) in char_table.iter_mut().enumerate() {
+        *val = (i % 256) as u8; // 0-255 repeating (twice)
+    }
+
+    // calculating char table - pass 1: pseudorandom byteswap
+    for i in 1..0x2711 {
+        let idx: usize = ((((i * seed2 as u32) + seed1 as u32) % 0xFE) + 1) as _;
+        let b1 = char_table[idx];
+        let b2 = char_table[idx + 1];
+        char_table[idx] = b2;
+        char_table[idx + 1] = b1;
+    }
+
+    // .. pass 2: use low half to scramble top half
+    for i in 0..0x100 {
+        let lo: u8 = char_table[i + 1];
+        char_table[lo as usize + 0x100] = (i as u8).wrapping_add(1);
+    }
+
+    char_table
 }
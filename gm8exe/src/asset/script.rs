@@ -7,6 +7,9 @@ use std::io::{self, Read};
 
 pub const VERSION: u32 = 800;
 
+/// The version of scripts from before GM8, which are laid out the same.
+pub const VERSION_LEGACY: u32 = 400;
+
 pub struct Script {
     /// The asset name present in GML and the editor.
     pub name: PascalString,
@@ -16,21 +19,21 @@ pub struct Script {
 }
 
 impl Asset for Script {
-    fn deserialize_exe(mut reader: impl Read, _version: GameVersion, strict: bool) -> Result<Self, Error> {
+    fn deserialize_exe(mut reader: impl Read, game_version: GameVersion, strict: bool) -> Result<Self, Error> {
         let name = reader.read_pas_string()?;
 
         let version = reader.read_u32::<LE>()?;
         if strict {
-            assert_ver(version, VERSION)?;
+            assert_ver(version, if game_version.is_legacy() { VERSION_LEGACY } else { VERSION })?;
         }
 
         let source = reader.read_pas_string()?;
         Ok(Script { name, source })
     }
 
-    fn serialize_exe(&self, mut writer: impl io::Write, _version: GameVersion) -> io::Result<()> {
+    fn serialize_exe(&self, mut writer: impl io::Write, version: GameVersion) -> io::Result<()> {
         writer.write_pas_string(&self.name)?;
-        writer.write_u32::<LE>(VERSION)?;
+        writer.write_u32::<LE>(if version.is_legacy() { VERSION_LEGACY } else { VERSION })?;
         writer.write_pas_string(&self.source)?;
         Ok(())
     }
@@ -1,8 +1,9 @@
 use crate::{
     asset::{
-        assert_ver, assert_ver_multiple, Asset, Error, PascalString, ReadChunk, ReadPascalString, WritePascalString,
+        assert_ver, assert_ver_multiple, bitmap, Asset, Error, PascalString, ReadChunk, ReadPascalString,
+        WritePascalString,
     },
-    GameVersion,
+    gmk, GameVersion,
 };
 use byteorder::{ReadBytesExt, WriteBytesExt, LE};
 use std::io::{self, Read};
@@ -11,6 +12,9 @@ pub const VERSION: u32 = 800;
 pub const VERSION_COLLISION: u32 = 800;
 pub const VERSION_FRAME: u32 = 800;
 
+/// The version of sprites from before GM8, which have bitmap frames and no collision maps.
+pub const VERSION_LEGACY: u32 = 542;
+
 pub struct Sprite {
     /// The asset name present in GML and the editor.
     pub name: PascalString,
@@ -62,10 +66,16 @@ pub struct CollisionMap {
 }
 
 impl Asset for Sprite {
-    fn deserialize_exe(mut reader: impl Read, _version: GameVersion, strict: bool) -> Result<Self, Error> {
+    fn deserialize_exe(mut reader: impl Read, game_version: GameVersion, strict: bool) -> Result<Self, Error> {
         let name = reader.read_pas_string()?;
 
         let version = reader.read_u32::<LE>()?;
+        if game_version.is_legacy() {
+            if strict {
+                assert_ver(version, VERSION_LEGACY)?;
+            }
+            return Self::deserialize_legacy(reader, name)
+        }
         if strict {
             assert_ver_multiple(version, &[VERSION, 810])?;
         }
@@ -130,8 +140,11 @@ impl Asset for Sprite {
         Ok(Sprite { name, origin_x, origin_y, frames, colliders, per_frame_colliders })
     }
 
-    fn serialize_exe(&self, mut writer: impl io::Write, _version: GameVersion) -> io::Result<()> {
+    fn serialize_exe(&self, mut writer: impl io::Write, version: GameVersion) -> io::Result<()> {
         writer.write_pas_string(&self.name)?;
+        if version.is_legacy() {
+            return self.serialize_legacy(writer)
+        }
         writer.write_u32::<LE>(VERSION)?;
         writer.write_i32::<LE>(self.origin_x)?;
         writer.write_i32::<LE>(self.origin_y)?;
@@ -164,3 +177,127 @@ impl Asset for Sprite {
         Ok(())
     }
 }
+
+impl Sprite {
+    // Reads the rest of a pre-GM8 sprite. Its collision maps are built the way the runner would build them,
+    // from the bounding box settings and, for precise collisions, the frames' transparency.
+    fn deserialize_legacy(mut reader: impl Read, name: PascalString) -> Result<Self, Error> {
+        reader.read_u32::<LE>()?; // width
+        reader.read_u32::<LE>()?; // height
+        let bbox_left = reader.read_u32::<LE>()?;
+        let bbox_right = reader.read_u32::<LE>()?;
+        let bbox_bottom = reader.read_u32::<LE>()?;
+        let bbox_top = reader.read_u32::<LE>()?;
+        let transparent = reader.read_u32::<LE>()? != 0;
+        reader.read_u32::<LE>()?; // smooth edges
+        reader.read_u32::<LE>()?; // preload texture
+        let bbox_kind = reader.read_u32::<LE>()?;
+        let precise = reader.read_u32::<LE>()? != 0;
+        let origin_x = reader.read_i32::<LE>()?;
+        let origin_y = reader.read_i32::<LE>()?;
+        let frame_count = reader.read_u32::<LE>()?;
+        let frames = (0..frame_count)
+            .map(|_| {
+                Ok(match bitmap::read(&mut reader, transparent)? {
+                    Some(image) => Frame { width: image.width, height: image.height, data: image.data },
+                    None => Frame { width: 0, height: 0, data: Box::new([]) },
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let bbox = match bbox_kind {
+            0 => None,
+            1 => Some((0, u32::MAX, 0, u32::MAX)),
+            _ => Some((bbox_left, bbox_right, bbox_top, bbox_bottom)),
+        };
+        // Precise collisions check against each frame's own pixels, so only they get a map per frame
+        let colliders = gmk::make_colliders(&frames, precise, if precise { 0 } else { 1 }, 0, bbox);
+
+        Ok(Sprite { name, origin_x, origin_y, frames, colliders, per_frame_colliders: precise })
+    }
+
+    fn serialize_legacy(&self, mut writer: impl io::Write) -> io::Result<()> {
+        let (width, height) = self.frames.first().map(|f| (f.width, f.height)).unwrap_or((0, 0));
+        writer.write_u32::<LE>(VERSION_LEGACY)?;
+        writer.write_u32::<LE>(width)?;
+        writer.write_u32::<LE>(height)?;
+        match self.colliders.first() {
+            Some(map) => {
+                writer.write_u32::<LE>(map.bbox_left)?;
+                writer.write_u32::<LE>(map.bbox_right)?;
+                writer.write_u32::<LE>(map.bbox_bottom)?;
+                writer.write_u32::<LE>(map.bbox_top)?;
+            },
+            None => {
+                writer.write_u32::<LE>(0)?;
+                writer.write_u32::<LE>(width.saturating_sub(1))?;
+                writer.write_u32::<LE>(height.saturating_sub(1))?;
+                writer.write_u32::<LE>(0)?;
+            },
+        }
+        let transparent =
+            self.frames.first().is_some_and(|f| bitmap::bottom_left_transparent(f.width, f.height, &f.data));
+        writer.write_u32::<LE>(transparent.into())?;
+        writer.write_u32::<LE>(0)?; // smooth edges
+        writer.write_u32::<LE>(1)?; // preload texture
+        writer.write_u32::<LE>(2)?; // bounding box type - 2 = manual
+        writer.write_u32::<LE>(self.per_frame_colliders.into())?; // precise collision, see above
+        writer.write_i32::<LE>(self.origin_x)?;
+        writer.write_i32::<LE>(self.origin_y)?;
+        writer.write_u32::<LE>(self.frames.len() as u32)?;
+        for frame in self.frames.iter() {
+            bitmap::write(&mut writer, frame.width, frame.height, &frame.data)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A 2x2 sprite whose bottom-left pixel is the transparent colour, along with the top-right one
+    fn legacy_sprite() -> Sprite {
+        let clear = [0, 0, 255, 0];
+        let solid = [255, 0, 0, 255];
+        let data = [solid, clear, clear, solid].concat().into_boxed_slice();
+        Sprite {
+            name: PascalString(b"spr_test".to_vec().into_boxed_slice()),
+            origin_x: 1,
+            origin_y: -2,
+            frames: vec![Frame { width: 2, height: 2, data }],
+            colliders: Vec::new(),
+            per_frame_colliders: true,
+        }
+    }
+
+    #[test]
+    fn legacy_round_trip() {
+        let sprite = legacy_sprite();
+        let mut data = Vec::new();
+        sprite.serialize_exe(&mut data, GameVersion::GameMaker6_0).unwrap();
+        let read = Sprite::deserialize_exe(data.as_slice(), GameVersion::GameMaker6_0, true).unwrap();
+        assert_eq!(read.name.0, sprite.name.0);
+        assert_eq!((read.origin_x, read.origin_y), (1, -2));
+        assert_eq!(read.frames.len(), 1);
+        assert_eq!((read.frames[0].width, read.frames[0].height), (2, 2));
+        assert_eq!(read.frames[0].data, sprite.frames[0].data);
+
+        // No bounding box was stored, so the precise map covers only the opaque pixels
+        assert!(read.per_frame_colliders);
+        assert_eq!(read.colliders.len(), 1);
+        assert_eq!(&*read.colliders[0].data, &[true, false, false, true]);
+    }
+
+    #[test]
+    fn legacy_version_checked() {
+        let mut data = Vec::new();
+        legacy_sprite().serialize_exe(&mut data, GameVersion::GameMaker8_0).unwrap();
+        assert!(Sprite::deserialize_exe(data.as_slice(), GameVersion::GameMaker7_0, true).is_err());
+
+        let mut data = Vec::new();
+        legacy_sprite().serialize_exe(&mut data, GameVersion::GameMaker7_0).unwrap();
+        assert!(Sprite::deserialize_exe(data.as_slice(), GameVersion::GameMaker7_0, true).is_ok());
+        assert!(Sprite::deserialize_exe(data.as_slice(), GameVersion::GameMaker8_0, true).is_err());
+    }
+}
@@ -1,16 +1,29 @@
 pub mod antidec;
+pub mod gm50;
+pub mod gm60;
+pub mod gm70;
 pub mod gm80;
 pub mod gm81;
 
 use crate::{reader::ReaderError, upx, GameVersion};
-use std::io::{self, Seek, SeekFrom};
+use byteorder::{ReadBytesExt, LE};
+use std::{
+    io::{self, Seek, SeekFrom},
+    ops::RangeInclusive,
+};
+
+/// Magic number at the start of every GameMaker gamedata header.
+const HEADER_MAGIC: u32 = 1234321;
 
 /// Identifies the game version and start of gamedata header, given a data cursor.
 /// Also removes any version-specific encryptions.
+///
+/// The pre-GM8 formats haven't been checked against many real games yet, so they're only looked for if `legacy` is set.
 pub fn find<F>(
     exe: &mut io::Cursor<&mut [u8]>,
     logger: Option<F>,
     upx_data: Option<(u32, u32)>,
+    legacy: bool,
 ) -> Result<GameVersion, ReaderError>
 where
     F: Copy + Fn(&str),
@@ -115,6 +128,15 @@ where
                     Ok(GameVersion::GameMaker8_0)
                 } else if gm81::check(exe, logger)? || gm81::check_lazy(exe, logger)? {
                     Ok(GameVersion::GameMaker8_1)
+                } else if !legacy {
+                    log!(logger, "Not a GM8 game, and pre-GM8 formats are only checked for when asked to");
+                    Err(ReaderError::UnknownFormat)
+                } else if gm70::check(exe, logger)? {
+                    Ok(GameVersion::GameMaker7_0)
+                } else if gm60::check(exe, logger)? {
+                    Ok(GameVersion::GameMaker6_0)
+                } else if gm50::check(exe, logger)? {
+                    Ok(GameVersion::GameMaker5_0)
                 } else {
                     Err(ReaderError::UnknownFormat)
                 }
@@ -122,3 +144,96 @@ where
         },
     }
 }
+
+/// Searches for a pre-GM8 gamedata header, which is the magic number followed by a version in the given range.
+/// Like the runners, this only looks at every 10000th byte. If found, the cursor is left just past the header.
+fn seek_legacy_header<F>(
+    exe: &mut io::Cursor<&mut [u8]>,
+    versions: RangeInclusive<u32>,
+    logger: Option<F>,
+) -> io::Result<bool>
+where
+    F: Copy + Fn(&str),
+{
+    let len = exe.get_ref().len() as u64;
+    let mut pos = 0;
+    while pos + 16 <= len {
+        exe.set_position(pos);
+        if exe.read_u32::<LE>()? == HEADER_MAGIC {
+            let version = exe.read_u32::<LE>()?;
+            if versions.contains(&version) {
+                log!(logger, "Found header with version {} at 0x{:X}", version, pos);
+                // Skip the rest of the header, which is laid out the same as GM8.0's
+                exe.seek(SeekFrom::Current(8))?;
+                return Ok(true)
+            }
+        }
+        pos += 10000;
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use byteorder::WriteBytesExt;
+
+    // Some data with a legacy header at the given offset, followed by its 8 remaining header bytes and a marker
+    fn with_header(offset: usize, version: u32) -> Vec<u8> {
+        let mut data = vec![0u8; offset];
+        data.write_u32::<LE>(HEADER_MAGIC).unwrap();
+        data.write_u32::<LE>(version).unwrap();
+        data.extend_from_slice(&[0; 8]);
+        data.write_u32::<LE>(0xDEADBEEF).unwrap();
+        data
+    }
+
+    type Check = fn(&mut io::Cursor<&mut [u8]>, Option<fn(&str)>) -> io::Result<bool>;
+
+    // Whether a check finds a header at the given offset, leaving the cursor just past it
+    fn finds(check: Check, offset: usize, version: u32) -> bool {
+        let mut data = with_header(offset, version);
+        let mut exe = io::Cursor::new(data.as_mut_slice());
+        check(&mut exe, None).unwrap() && exe.read_u32::<LE>().unwrap() == 0xDEADBEEF
+    }
+
+    #[test]
+    fn legacy_versions() {
+        assert!(finds(gm50::check, 20000, 530));
+        assert!(finds(gm60::check, 20000, 620));
+        assert!(finds(gm70::check, 20000, 702));
+
+        // Each version only takes its own headers
+        assert!(!finds(gm50::check, 10000, 620));
+        assert!(!finds(gm70::check, 10000, 620));
+    }
+
+    #[test]
+    fn legacy_header_alignment() {
+        // The runners only look every 10000 bytes, so a header anywhere else isn't one
+        assert!(finds(gm60::check, 0, 620));
+        assert!(!finds(gm60::check, 10004, 620));
+    }
+
+    #[test]
+    fn gm70_decrypt() {
+        let plain = b"GameMaker 7 asset data, long enough to wrap the position offset".repeat(5);
+        let seed = 123456;
+        let (table, _) = gm70::seed_tables(seed);
+
+        let mut data = Vec::new();
+        data.write_u32::<LE>(1).unwrap(); // garbage before the seed, in dwords
+        data.write_u32::<LE>(2).unwrap(); // garbage after the seed
+        data.extend_from_slice(&[0xAA; 4]);
+        data.write_u32::<LE>(seed).unwrap();
+        data.extend_from_slice(&[0xBB; 8]);
+        data.write_u32::<LE>(plain.len() as u32).unwrap();
+        let start = data.len();
+        data.extend(plain.iter().enumerate().map(|(i, b)| table[usize::from(b.wrapping_add(i as u8))]));
+
+        let mut exe = io::Cursor::new(data.as_mut_slice());
+        gm70::decrypt::<fn(&str)>(&mut exe, None).unwrap();
+        assert_eq!(exe.position() as usize, start);
+        assert_eq!(&data[start..], plain.as_slice());
+    }
+}
@@ -0,0 +1,12 @@
+use super::seek_legacy_header;
+use std::io;
+
+/// Check if this is a GameMaker 5.x game by searching for its data header.
+/// If so, sets the cursor to the start of the gamedata. Like GM6, GM5 gamedata isn't encrypted.
+pub fn check<F>(exe: &mut io::Cursor<&mut [u8]>, logger: Option<F>) -> io::Result<bool>
+where
+    F: Copy + Fn(&str),
+{
+    log!(logger, "Checking for GM5.x format...");
+    seek_legacy_header(exe, 500..=599, logger)
+}
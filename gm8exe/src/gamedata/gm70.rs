@@ -0,0 +1,71 @@
+use super::seek_legacy_header;
+use byteorder::{ReadBytesExt, LE};
+use std::io::{self, Seek, SeekFrom};
+
+/// Check if this is a GameMaker 7 game by searching for its data header.
+/// If so, sets the cursor to the start of the gamedata. The encrypted part comes later, so call `decrypt` for that.
+pub fn check<F>(exe: &mut io::Cursor<&mut [u8]>, logger: Option<F>) -> io::Result<bool>
+where
+    F: Copy + Fn(&str),
+{
+    log!(logger, "Checking for GM7 format...");
+    seek_legacy_header(exe, 700..=799, logger)
+}
+
+/// Generates GM7's substitution table for a given seed, along with its inverse.
+/// GM7 uses the same tables to encrypt its .gmk project files.
+pub fn seed_tables(seed: u32) -> ([u8; 256], [u8; 256]) {
+    let mut table = [0u8; 256];
+    let mut reverse_table = [0u8; 256];
+
+    let a = 6 + (seed % 250);
+    let b = seed / 250;
+    for (i, val) in table.iter_mut().enumerate() {
+        *val = i as u8;
+    }
+    for i in 1..=10000u32 {
+        let j = 1 + (i.wrapping_mul(a).wrapping_add(b) % 254) as usize;
+        table.swap(j, j + 1);
+    }
+    for (i, val) in table.iter().enumerate() {
+        reverse_table[usize::from(*val)] = i as u8;
+    }
+
+    (table, reverse_table)
+}
+
+/// Removes GameMaker 7 protection in-place.
+pub fn decrypt<F>(data: &mut io::Cursor<&mut [u8]>, logger: Option<F>) -> io::Result<()>
+where
+    F: Copy + Fn(&str),
+{
+    // like in GM8.0, the key is squished inbetween 2 chunks of useless garbage, but here it's a seed for the table
+    let garbage1_size = data.read_u32::<LE>()? as i64 * 4;
+    let garbage2_size = data.read_u32::<LE>()? as i64 * 4;
+    data.seek(SeekFrom::Current(garbage1_size))?;
+    let seed = data.read_u32::<LE>()?;
+    data.seek(SeekFrom::Current(garbage2_size))?;
+    let (_, reverse_table) = seed_tables(seed);
+
+    // asset data length
+    let len = data.read_u32::<LE>()? as usize;
+
+    let pos = data.position() as usize;
+    let data = data.get_mut();
+    let end = data.len().min(pos + len);
+    log!(
+        logger,
+        "Decrypting asset data... (size: {}, seed: {}, garbage1: {}, garbage2: {})",
+        len,
+        seed,
+        garbage1_size,
+        garbage2_size
+    );
+
+    // each byte is substituted, then offset by its position in the encrypted block
+    for (i, byte) in data[pos..end].iter_mut().enumerate() {
+        *byte = reverse_table[usize::from(*byte)].wrapping_sub(i as u8);
+    }
+
+    Ok(())
+}
@@ -184,3 +184,18 @@ where
 
     Ok(())
 }
+
+/// Applies GameMaker 8.0 protection in-place to a block of asset data, undoing what `decrypt` does.
+/// The swap table must be a permutation of every byte value.
+pub fn encrypt(data: &mut [u8], swap_table: &[u8; 256]) {
+    // first pass: the swaps from decrypt's second pass, in the opposite order
+    for i in 0..data.len() {
+        let b = i.saturating_sub(usize::from(swap_table[i & 0xFF]));
+        data.swap(i, b);
+    }
+
+    // second pass: forwards this time, so each byte is mixed with the already-encrypted byte before it
+    for i in 1..data.len() {
+        data[i] = swap_table[usize::from(data[i].wrapping_add(data[i - 1]).wrapping_add(i as u8))];
+    }
+}
@@ -0,0 +1,1061 @@
+//! Reader for GameMaker 8 project files (.gmk and .gm81).
+//!
+//! Project files hold the same assets as a compiled game, but some of the data the runner needs isn't stored in them:
+//! - Sprite collision masks are rebuilt here from the shape, alpha tolerance and bounding box settings.
+//! - Font glyphs are rendered by the IDE from system fonts at compile time, so they're rendered here the same way.
+//! - Extensions are only referenced by name, so they're loaded from the packages installed with the IDE,
+//!   each of which is a description file (.ged) and a file with the contents of its files (.dat).
+
+use crate::{
+    asset::{
+        self, extension, object::Object, path, room, sprite, timeline::Timeline, Asset, CodeAction, Error,
+        ReadChunk, ReadPascalString, WritePascalString,
+    },
+    reader::{inflate, ReaderError},
+    settings::{GameHelpDialog, Settings},
+    sysfont, AssetList, GameAssets, GameVersion,
+};
+use byteorder::{ReadBytesExt, LE};
+use flate2::bufread::ZlibDecoder;
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use std::{
+    fs,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+/// The magic number at the start of every project file.
+pub const MAGIC: u32 = 1234321;
+
+/// Whether this data looks like a GameMaker project file.
+pub fn is_gmk(data: &[u8]) -> bool {
+    data.get(0..4) == Some(&MAGIC.to_le_bytes()[..])
+}
+
+/// A node of the resource tree, which arranges the assets into folders in the IDE.
+pub struct TreeNode {
+    /// 1 for a top-level folder, 2 for a user folder, 3 for an asset.
+    pub status: u32,
+    /// Which asset kind this node belongs to, using the same numbering as the resource tree.
+    pub kind: u32,
+    /// The asset index, for nodes which are assets.
+    pub index: u32,
+    pub name: asset::PascalString,
+    pub children: Vec<TreeNode>,
+}
+
+// Resource tree kind of the "Rooms" folder and the rooms in it
+const TREE_ROOM: u32 = 4;
+
+/// Reads a project file. Extension packages are looked for in `extension_dirs`, which would usually be
+/// the IDE's "extensions" folder.
+pub fn from_gmk<F>(
+    gmk: &[u8],
+    extension_dirs: &[PathBuf],
+    logger: Option<F>,
+    strict: bool,
+    multithread: bool,
+) -> Result<GameAssets, ReaderError>
+where
+    F: Copy + Fn(&str),
+{
+    let mut gmk = io::Cursor::new(gmk);
+
+    // little helper thing
+    macro_rules! assert_ver {
+        ($name: literal, $expect: expr, $ver: expr) => {{
+            let expected = $expect;
+            let got = $ver;
+            if strict && got != expected {
+                log!(logger, "Unexpected {} version {} (expected {})", $name, got, expected);
+                Err(ReaderError::AssetError(Error::VersionError { expected, got }))
+            } else {
+                Ok(())
+            }
+        }};
+    }
+
+    if gmk.read_u32::<LE>()? != MAGIC {
+        return Err(ReaderError::UnknownFormat)
+    }
+    let version = match gmk.read_u32::<LE>()? {
+        800 => GameVersion::GameMaker8_0,
+        810 => GameVersion::GameMaker8_1,
+        v => {
+            log!(logger, "Unsupported project file version {}", v);
+            return Err(ReaderError::UnknownFormat)
+        },
+    };
+    log!(logger, "Reading {:?} project file", version);
+
+    let game_id = gmk.read_u32::<LE>()?;
+    let guid = [gmk.read_u32::<LE>()?, gmk.read_u32::<LE>()?, gmk.read_u32::<LE>()?, gmk.read_u32::<LE>()?];
+    log!(logger, "Game ID: {}", game_id);
+
+    // Game Settings
+    assert_ver!("settings header", 800, gmk.read_u32::<LE>()?)?;
+    let (settings, ico_file_raw) = read_settings(&mut zlib_block(&mut gmk)?, version)?;
+    log!(logger, " + Loaded settings structure");
+
+    // Triggers
+    assert_ver!("triggers header", 800, gmk.read_u32::<LE>()?)?;
+    let triggers = read_assets(&mut gmk, |data| asset::Trigger::deserialize_exe(data, version, strict), multithread)?;
+    skip_timestamp(&mut gmk)?;
+    log!(logger, " + Read {} triggers", triggers.len());
+
+    // Constants
+    assert_ver!("constants header", 800, gmk.read_u32::<LE>()?)?;
+    let constant_count = gmk.read_u32::<LE>()? as usize;
+    let mut constants = Vec::with_capacity(constant_count);
+    for _ in 0..constant_count {
+        let name = gmk.read_pas_string()?;
+        let expression = gmk.read_pas_string()?;
+        constants.push(asset::Constant { name, expression });
+    }
+    skip_timestamp(&mut gmk)?;
+    log!(logger, " + Read {} constants", constants.len());
+
+    // Sounds
+    assert_ver!("sounds header", 800, gmk.read_u32::<LE>()?)?;
+    let sounds = read_assets(
+        &mut gmk,
+        |data| asset::Sound::deserialize_exe(without_timestamp(data)?, version, strict),
+        multithread,
+    )?;
+    log!(logger, " + Read {} sounds", sounds.len());
+
+    // Sprites
+    assert_ver!("sprites header", 800, gmk.read_u32::<LE>()?)?;
+    let sprites = read_assets(&mut gmk, |data| read_sprite(data, strict), multithread)?;
+    log!(logger, " + Read {} sprites", sprites.len());
+
+    // Backgrounds
+    assert_ver!("backgrounds header", 800, gmk.read_u32::<LE>()?)?;
+    let backgrounds = read_assets(&mut gmk, |data| read_background(data, strict), multithread)?;
+    log!(logger, " + Read {} backgrounds", backgrounds.len());
+
+    // Paths
+    assert_ver!("paths header", 800, gmk.read_u32::<LE>()?)?;
+    let paths = read_assets(&mut gmk, |data| read_path(data, strict), multithread)?;
+    log!(logger, " + Read {} paths", paths.len());
+
+    // Scripts
+    assert_ver!("scripts header", 800, gmk.read_u32::<LE>()?)?;
+    let scripts = read_assets(
+        &mut gmk,
+        |data| asset::Script::deserialize_exe(without_timestamp(data)?, version, strict),
+        multithread,
+    )?;
+    log!(logger, " + Read {} scripts", scripts.len());
+
+    // Fonts
+    assert_ver!("fonts header", 800, gmk.read_u32::<LE>()?)?;
+    let mut fonts = read_assets(&mut gmk, |data| read_font(data, version, strict), multithread)?;
+    log!(logger, " + Read {} fonts", fonts.len());
+    render_fonts(&mut fonts, version, logger);
+
+    // Timelines
+    assert_ver!("timelines header", 800, gmk.read_u32::<LE>()?)?;
+    let timelines = read_assets(&mut gmk, |data| read_timeline(data, version, strict), multithread)?;
+    log!(logger, " + Read {} timelines", timelines.len());
+
+    // Objects
+    assert_ver!("objects header", 800, gmk.read_u32::<LE>()?)?;
+    let objects = read_assets(&mut gmk, |data| read_object(data, version, strict), multithread)?;
+    log!(logger, " + Read {} objects", objects.len());
+
+    // Rooms
+    assert_ver!("rooms header", 800, gmk.read_u32::<LE>()?)?;
+    let rooms = read_assets(&mut gmk, |data| read_room(data, version, strict), multithread)?;
+    log!(logger, " + Read {} rooms", rooms.len());
+
+    let last_instance_id = gmk.read_i32::<LE>()?;
+    let last_tile_id = gmk.read_i32::<LE>()?;
+
+    // Included Files
+    assert_ver!("included files header", 800, gmk.read_u32::<LE>()?)?;
+    let included_file_count = gmk.read_u32::<LE>()? as usize;
+    let mut included_files = Vec::with_capacity(included_file_count);
+    for _ in 0..included_file_count {
+        let mut data = zlib_block(&mut gmk)?;
+        skip_timestamp(&mut data)?;
+        included_files.push(asset::IncludedFile::deserialize_exe(data, version, strict)?);
+    }
+    log!(logger, " + Read {} included files", included_files.len());
+
+    // Extensions
+    assert_ver!("extensions header", 700, gmk.read_u32::<LE>()?)?;
+    let extension_count = gmk.read_u32::<LE>()? as usize;
+    let mut extensions = Vec::with_capacity(extension_count);
+    let mut next_function_id = 0;
+    for _ in 0..extension_count {
+        let name = gmk.read_pas_string()?;
+        match find_extension_package(extension_dirs, &name) {
+            Some(path) => {
+                let extension = read_extension_package(&path, &mut next_function_id, strict)?;
+                log!(logger, " + Loaded extension package '{}' from {}", name, path.display());
+                extensions.push(extension);
+            },
+            None => {
+                log!(logger, "WARNING: extension package '{}' isn't installed, so it won't be loaded", name);
+                extensions.push(asset::Extension { name, folder_name: Default::default(), files: Vec::new() });
+            },
+        }
+    }
+
+    // Help Dialog
+    assert_ver!("game information header", 800, gmk.read_u32::<LE>()?)?;
+    let help_dialog = {
+        let mut data = zlib_block(&mut gmk)?;
+        let bg_colour = data.read_u32::<LE>()?.into();
+        let new_window = data.read_u32::<LE>()? != 0;
+        let caption = data.read_pas_string()?;
+        let left = data.read_i32::<LE>()?;
+        let top = data.read_i32::<LE>()?;
+        let width = data.read_u32::<LE>()?;
+        let height = data.read_u32::<LE>()?;
+        let border = data.read_u32::<LE>()? != 0;
+        let resizable = data.read_u32::<LE>()? != 0;
+        let window_on_top = data.read_u32::<LE>()? != 0;
+        let freeze_game = data.read_u32::<LE>()? != 0;
+        skip_timestamp(&mut data)?;
+        let info = data.read_pas_string()?;
+        GameHelpDialog {
+            bg_colour,
+            new_window,
+            caption,
+            left,
+            top,
+            width,
+            height,
+            border,
+            resizable,
+            window_on_top,
+            freeze_game,
+            info,
+        }
+    };
+
+    // Action library initialization code
+    assert_ver!("action library initialization code header", 500, gmk.read_u32::<LE>()?)?;
+    let str_count = gmk.read_u32::<LE>()? as usize;
+    let mut library_init_strings = Vec::with_capacity(str_count);
+    for _ in 0..str_count {
+        library_init_strings.push(gmk.read_pas_string()?);
+    }
+
+    // Room Order - the IDE ignores this and uses the order of the rooms in the resource tree instead
+    assert_ver!("room order header", 700, gmk.read_u32::<LE>()?)?;
+    let ro_count = gmk.read_u32::<LE>()? as usize;
+    let mut room_order = Vec::with_capacity(ro_count);
+    for _ in 0..ro_count {
+        room_order.push(gmk.read_i32::<LE>()?);
+    }
+
+    let tree = (0..12).map(|_| read_tree_node(&mut gmk)).collect::<io::Result<Vec<_>>>()?;
+    let mut tree_rooms = Vec::new();
+    for node in tree.iter().filter(|n| n.kind == TREE_ROOM) {
+        collect_tree_assets(node, &mut tree_rooms);
+    }
+    if !tree_rooms.is_empty() {
+        room_order = tree_rooms;
+    }
+    log!(logger, " + Room order from resource tree: {:?}", room_order);
+
+    Ok(GameAssets {
+        triggers,
+        constants,
+        extensions,
+        sprites,
+        sounds,
+        backgrounds,
+        paths,
+        scripts,
+        fonts,
+        timelines,
+        objects,
+        rooms,
+        included_files,
+        version,
+        dx_dll: Vec::new(),
+        ico_file_raw,
+        help_dialog,
+        last_instance_id,
+        last_tile_id,
+        library_init_strings,
+        room_order,
+        settings,
+        game_id,
+        guid,
+    })
+}
+
+/// Reads one node of the resource tree, along with everything under it.
+pub fn read_tree_node(reader: &mut impl Read) -> io::Result<TreeNode> {
+    let status = reader.read_u32::<LE>()?;
+    let kind = reader.read_u32::<LE>()?;
+    let index = reader.read_u32::<LE>()?;
+    let name = reader.read_pas_string()?;
+    let child_count = reader.read_u32::<LE>()? as usize;
+    let children = (0..child_count).map(|_| read_tree_node(reader)).collect::<io::Result<_>>()?;
+    Ok(TreeNode { status, kind, index, name, children })
+}
+
+// Appends the asset indices under a resource tree node, in the order they're shown in the IDE
+fn collect_tree_assets(node: &TreeNode, out: &mut Vec<i32>) {
+    if node.status == 3 {
+        out.push(node.index as i32);
+    }
+    for child in &node.children {
+        collect_tree_assets(child, out);
+    }
+}
+
+// Reads a length-prefixed zlib block, advancing past it
+fn zlib_block<'a>(src: &mut io::Cursor<&'a [u8]>) -> io::Result<ZlibDecoder<&'a [u8]>> {
+    let len = src.read_u32::<LE>()? as usize;
+    let pos = src.position() as usize;
+    src.seek(SeekFrom::Current(len as i64))?;
+    let data: &'a [u8] = src.get_ref();
+    data.get(pos..pos + len).map(inflate).ok_or_else(|| io::ErrorKind::UnexpectedEof.into())
+}
+
+// Last-changed timestamps (as Delphi TDateTime doubles) are only useful to the IDE
+fn skip_timestamp(reader: &mut impl Read) -> io::Result<()> {
+    reader.read_f64::<LE>().map(|_| ())
+}
+
+// Most asset blocks are laid out like their exe counterparts, except with a timestamp after the name
+fn without_timestamp(mut reader: impl Read) -> io::Result<impl Read> {
+    let name = reader.read_pas_string()?;
+    skip_timestamp(&mut reader)?;
+    let mut prefix = Vec::with_capacity(name.0.len() + 4);
+    prefix.write_pas_string(&name)?;
+    Ok(io::Cursor::new(prefix).chain(reader))
+}
+
+// Reads a list of assets, each of which is in its own zlib block
+fn read_assets<T, F>(
+    src: &mut io::Cursor<&[u8]>,
+    deserializer: F,
+    multithread: bool,
+) -> Result<AssetList<T>, ReaderError>
+where
+    T: Send,
+    F: Fn(ZlibDecoder<&[u8]>) -> Result<T, Error> + Sync,
+{
+    let count = src.read_u32::<LE>()? as usize;
+    let mut blocks = Vec::with_capacity(count);
+    for _ in 0..count {
+        let len = src.read_u32::<LE>()? as usize;
+        let pos = src.position() as usize;
+        src.seek(SeekFrom::Current(len as i64))?;
+        blocks.push(src.get_ref().get(pos..pos + len).ok_or(Error::MalformedData)?);
+    }
+
+    let to_asset = |data: &&[u8]| {
+        let mut data = inflate(*data);
+        match data.read_u32::<LE>()? {
+            0 => Ok(None),
+            _ => Ok(Some(Box::new(deserializer(data)?))),
+        }
+    };
+    if multithread { blocks.par_iter().map(to_asset).collect() } else { blocks.iter().map(to_asset).collect() }
+}
+
+fn read_settings(cfg: &mut impl Read, version: GameVersion) -> Result<(Settings, Option<Vec<u8>>), ReaderError> {
+    fn read_zlib_maybe(cfg: &mut impl Read) -> Result<Option<Box<[u8]>>, ReaderError> {
+        if cfg.read_u32::<LE>()? != 0 {
+            let len = cfg.read_u32::<LE>()? as usize;
+            let compressed = cfg.read_chunk(len)?;
+            let mut data = Vec::new();
+            inflate(&compressed).read_to_end(&mut data)?;
+            Ok(Some(data.into_boxed_slice()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    let fullscreen = cfg.read_u32::<LE>()? != 0;
+    let interpolate_pixels = cfg.read_u32::<LE>()? != 0;
+    let dont_draw_border = cfg.read_u32::<LE>()? != 0;
+    let display_cursor = cfg.read_u32::<LE>()? != 0;
+    let scaling = cfg.read_i32::<LE>()?;
+    let allow_resize = cfg.read_u32::<LE>()? != 0;
+    let window_on_top = cfg.read_u32::<LE>()? != 0;
+    let clear_colour = cfg.read_u32::<LE>()?;
+    let set_resolution = cfg.read_u32::<LE>()? != 0;
+    let colour_depth = cfg.read_u32::<LE>()?;
+    let resolution = cfg.read_u32::<LE>()?;
+    let frequency = cfg.read_u32::<LE>()?;
+    let dont_show_buttons = cfg.read_u32::<LE>()? != 0;
+    let (vsync, force_cpu_render) = match (version, cfg.read_u32::<LE>()?) {
+        (GameVersion::GameMaker8_1, x) => ((x & 1) != 0, (x & (1 << 7)) != 0),
+        (_, x) => (x != 0, true),
+    };
+    let disable_screensaver = cfg.read_u32::<LE>()? != 0;
+    let f4_fullscreen_toggle = cfg.read_u32::<LE>()? != 0;
+    let f1_help_menu = cfg.read_u32::<LE>()? != 0;
+    let esc_close_game = cfg.read_u32::<LE>()? != 0;
+    let f5_save_f6_load = cfg.read_u32::<LE>()? != 0;
+    let f9_screenshot = cfg.read_u32::<LE>()? != 0;
+    let treat_close_as_esc = cfg.read_u32::<LE>()? != 0;
+    let priority = cfg.read_u32::<LE>()?;
+    let freeze_on_lose_focus = cfg.read_u32::<LE>()? != 0;
+    let loading_bar = cfg.read_u32::<LE>()?;
+    let (backdata, frontdata) =
+        if loading_bar == 2 { (read_zlib_maybe(cfg)?, read_zlib_maybe(cfg)?) } else { (None, None) };
+    // Unlike in exes, there's a flag for showing the image as well as one for whether there is one
+    let custom_load_image = if cfg.read_u32::<LE>()? != 0 { read_zlib_maybe(cfg)? } else { None };
+    let transparent = cfg.read_u32::<LE>()? != 0;
+    let translucency = cfg.read_u32::<LE>()?;
+    let scale_progress_bar = cfg.read_u32::<LE>()? != 0;
+    let ico_len = cfg.read_u32::<LE>()? as usize;
+    let ico_file_raw = if ico_len != 0 { Some(cfg.read_chunk(ico_len)?) } else { None };
+    let show_error_messages = cfg.read_u32::<LE>()? != 0;
+    let log_errors = cfg.read_u32::<LE>()? != 0;
+    let always_abort = cfg.read_u32::<LE>()? != 0;
+    let (zero_uninitialized_vars, error_on_uninitialized_args) = match (version, cfg.read_u32::<LE>()?) {
+        (GameVersion::GameMaker8_1, x) => ((x & 1) != 0, (x & 2) != 0),
+        (_, x) => (x != 0, false),
+    };
+    // The rest is author, version and copyright info for the IDE, which GameAssets doesn't keep
+
+    let settings = Settings {
+        fullscreen,
+        scaling,
+        interpolate_pixels,
+        clear_colour,
+        allow_resize,
+        window_on_top,
+        dont_draw_border,
+        dont_show_buttons,
+        display_cursor,
+        freeze_on_lose_focus,
+        disable_screensaver,
+        force_cpu_render,
+        set_resolution,
+        colour_depth,
+        resolution,
+        frequency,
+        vsync,
+        esc_close_game,
+        treat_close_as_esc,
+        f1_help_menu,
+        f4_fullscreen_toggle,
+        f5_save_f6_load,
+        f9_screenshot,
+        priority,
+        custom_load_image,
+        transparent,
+        translucency,
+        loading_bar,
+        backdata,
+        frontdata,
+        scale_progress_bar,
+        show_error_messages,
+        log_errors,
+        always_abort,
+        zero_uninitialized_vars,
+        error_on_uninitialized_args,
+        swap_creation_events: false,
+    };
+    Ok((settings, ico_file_raw))
+}
+
+// Checks an asset's version field if reading strictly
+fn check_ver(reader: &mut impl Read, expected: u32, strict: bool) -> Result<(), Error> {
+    let got = reader.read_u32::<LE>()?;
+    if strict && got != expected { Err(Error::VersionError { expected, got }) } else { Ok(()) }
+}
+
+fn read_sprite(mut reader: impl Read, strict: bool) -> Result<sprite::Sprite, Error> {
+    let name = reader.read_pas_string()?;
+    skip_timestamp(&mut reader)?;
+    check_ver(&mut reader, 800, strict)?;
+    let origin_x = reader.read_i32::<LE>()?;
+    let origin_y = reader.read_i32::<LE>()?;
+    let frame_count = reader.read_u32::<LE>()? as usize;
+    let frames = (0..frame_count)
+        .map(|_| {
+            check_ver(&mut reader, 800, strict)?;
+            let width = reader.read_u32::<LE>()?;
+            let height = reader.read_u32::<LE>()?;
+            let data = if width * height != 0 {
+                let len = reader.read_u32::<LE>()? as usize;
+                reader.read_chunk(len)?.into_boxed_slice()
+            } else {
+                Box::new([])
+            };
+            Ok(sprite::Frame { width, height, data })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let shape = reader.read_u32::<LE>()?;
+    let alpha_tolerance = reader.read_u32::<LE>()?;
+    let per_frame_colliders = reader.read_u32::<LE>()? != 0;
+    let bbox_kind = reader.read_u32::<LE>()?;
+    let bbox_left = reader.read_u32::<LE>()?;
+    let bbox_right = reader.read_u32::<LE>()?;
+    let bbox_bottom = reader.read_u32::<LE>()?;
+    let bbox_top = reader.read_u32::<LE>()?;
+
+    let bbox = match bbox_kind {
+        0 => None,
+        1 => Some((0, u32::MAX, 0, u32::MAX)),
+        _ => Some((bbox_left, bbox_right, bbox_top, bbox_bottom)),
+    };
+    let colliders = make_colliders(&frames, per_frame_colliders, shape, alpha_tolerance, bbox);
+
+    Ok(sprite::Sprite { name, origin_x, origin_y, frames, colliders, per_frame_colliders })
+}
+
+/// Builds a sprite's collision maps the way the IDE does when compiling.
+///
+/// `shape` is 0 for precise, 1 for rectangle, 2 for disk and 3 for diamond.
+/// `bbox` is (left, right, top, bottom), clamped to the sprite's size, or None to fit it around the opaque pixels.
+pub fn make_colliders(
+    frames: &[sprite::Frame],
+    per_frame: bool,
+    shape: u32,
+    alpha_tolerance: u32,
+    bbox: Option<(u32, u32, u32, u32)>,
+) -> Vec<sprite::CollisionMap> {
+    let (width, height) = match frames.first() {
+        Some(frame) => (frame.width, frame.height),
+        None => return Vec::new(),
+    };
+    let opaque = |frame: &sprite::Frame, x: u32, y: u32| {
+        x < frame.width
+            && y < frame.height
+            && frame.data.get(((y * frame.width + x) * 4 + 3) as usize).is_some_and(|a| u32::from(*a) > alpha_tolerance)
+    };
+    let mask = |frames: &[sprite::Frame]| -> Vec<bool> {
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| frames.iter().any(|f| opaque(f, x, y)))
+            .collect()
+    };
+    let masks =
+        if per_frame { frames.iter().map(|f| mask(std::slice::from_ref(f))).collect() } else { vec![mask(frames)] };
+
+    masks
+        .into_iter()
+        .map(|mask| {
+            let (left, right, top, bottom) = match bbox {
+                Some((left, right, top, bottom)) => (
+                    left.min(width.saturating_sub(1)),
+                    right.min(width.saturating_sub(1)),
+                    top.min(height.saturating_sub(1)),
+                    bottom.min(height.saturating_sub(1)),
+                ),
+                None => {
+                    let solid = |x: u32, y: u32| mask[(y * width + x) as usize];
+                    let mut columns = (0..width).filter(|&x| (0..height).any(|y| solid(x, y)));
+                    let mut rows = (0..height).filter(|&y| (0..width).any(|x| solid(x, y)));
+                    match (columns.clone().next(), rows.clone().next()) {
+                        (Some(left), Some(top)) => (left, columns.next_back().unwrap(), top, rows.next_back().unwrap()),
+                        _ => (width.saturating_sub(1), 0, height.saturating_sub(1), 0),
+                    }
+                },
+            };
+
+            // Disks and diamonds are fitted to the bounding box
+            let xcentre = f64::from(left + right) / 2.0;
+            let ycentre = f64::from(top + bottom) / 2.0;
+            let xrad = f64::from(right.saturating_sub(left)) / 2.0 + 0.5;
+            let yrad = f64::from(bottom.saturating_sub(top)) / 2.0 + 0.5;
+            let mut data = vec![false; (width * height) as usize].into_boxed_slice();
+            // Zero-sized sprites have no pixels to fill in
+            if !data.is_empty() {
+                for y in top..=bottom {
+                    for x in left..=right {
+                        let dx = (f64::from(x) - xcentre) / xrad;
+                        let dy = (f64::from(y) - ycentre) / yrad;
+                        let i = (y * width + x) as usize;
+                        data[i] = match shape {
+                            1 => true,
+                            2 => dx * dx + dy * dy < 1.0,
+                            3 => dx.abs() + dy.abs() <= 1.0,
+                            _ => mask[i],
+                        };
+                    }
+                }
+            }
+            sprite::CollisionMap {
+                width,
+                height,
+                bbox_left: left,
+                bbox_right: right,
+                bbox_top: top,
+                bbox_bottom: bottom,
+                data,
+            }
+        })
+        .collect()
+}
+
+fn read_background(mut reader: impl Read, strict: bool) -> Result<asset::Background, Error> {
+    let name = reader.read_pas_string()?;
+    skip_timestamp(&mut reader)?;
+    check_ver(&mut reader, 710, strict)?;
+    // Tileset settings (is tileset, tile size, offset and separation) are only used by the room editor
+    for _ in 0..7 {
+        reader.read_u32::<LE>()?;
+    }
+    check_ver(&mut reader, 800, strict)?;
+    let width = reader.read_u32::<LE>()?;
+    let height = reader.read_u32::<LE>()?;
+    let data = if width * height != 0 {
+        let len = reader.read_u32::<LE>()? as usize;
+        Some(reader.read_chunk(len)?.into_boxed_slice())
+    } else {
+        None
+    };
+    Ok(asset::Background { name, width, height, data })
+}
+
+fn read_path(mut reader: impl Read, strict: bool) -> Result<path::Path, Error> {
+    let name = reader.read_pas_string()?;
+    skip_timestamp(&mut reader)?;
+    check_ver(&mut reader, path::VERSION, strict)?;
+    let connection = path::ConnectionKind::from(reader.read_u32::<LE>()?);
+    let closed = reader.read_u32::<LE>()? != 0;
+    let precision = reader.read_u32::<LE>()?;
+    reader.read_i32::<LE>()?; // room to show as background in the path editor
+    reader.read_u32::<LE>()?; // snap x
+    reader.read_u32::<LE>()?; // snap y
+    let point_count = reader.read_u32::<LE>()? as usize;
+    let points = (0..point_count)
+        .map(|_| {
+            Ok(path::Point {
+                x: reader.read_f64::<LE>()?,
+                y: reader.read_f64::<LE>()?,
+                speed: reader.read_f64::<LE>()?,
+            })
+        })
+        .collect::<io::Result<_>>()?;
+    Ok(path::Path { name, connection, precision, closed, points })
+}
+
+fn read_font(mut reader: impl Read, version: GameVersion, strict: bool) -> Result<asset::Font, Error> {
+    let name = reader.read_pas_string()?;
+    skip_timestamp(&mut reader)?;
+    check_ver(&mut reader, 800, strict)?;
+    let sys_name = reader.read_pas_string()?;
+    let size = reader.read_u32::<LE>()?;
+    let bold = reader.read_u32::<LE>()? != 0;
+    let italic = reader.read_u32::<LE>()? != 0;
+    let mut range_start = reader.read_u32::<LE>()?;
+    let (aa_level, charset) = match version {
+        GameVersion::GameMaker8_1 => {
+            let packed = range_start;
+            range_start &= 0xFFFF;
+            (packed >> 24, (packed >> 16) & 0xFF)
+        },
+        _ => (0, 0),
+    };
+    let range_end = reader.read_u32::<LE>()?;
+    Ok(asset::Font {
+        name,
+        sys_name,
+        size,
+        bold,
+        italic,
+        range_start,
+        range_end,
+        charset,
+        aa_level,
+        dmap: Box::new([0; 0x600]),
+        map_width: 0,
+        map_height: 0,
+        pixel_map: Box::new([]),
+    })
+}
+
+// Renders glyphs for fonts from the fonts installed on the system, like the IDE does when compiling
+fn render_fonts<F>(fonts: &mut AssetList<asset::Font>, version: GameVersion, logger: Option<F>)
+where
+    F: Copy + Fn(&str),
+{
+    if fonts.iter().all(Option::is_none) {
+        return
+    }
+    let families = fonts.iter().flatten().map(|f| f.sys_name.to_string()).collect::<Vec<_>>();
+    let requests = fonts
+        .iter()
+        .flatten()
+        .zip(&families)
+        .map(|(font, family)| sysfont::Request { family, bold: font.bold, italic: font.italic })
+        .collect::<Vec<_>>();
+    let files = sysfont::find(&sysfont::font_dirs(), &requests);
+    for (font, file) in fonts.iter_mut().flatten().zip(files) {
+        // GM8.0 always antialiases, GM8.1 has a setting for it
+        let antialias = version != GameVersion::GameMaker8_1 || font.aa_level != 0;
+        match file.map(|file| (sysfont::render(font, &file, antialias), file)) {
+            Some((Ok(()), file)) => log!(logger, " + Rendered font '{}' from {}", font.name, file.path.display()),
+            Some((Err(_), file)) => {
+                log!(logger, "WARNING: couldn't render font '{}' from {}", font.name, file.path.display())
+            },
+            None => log!(
+                logger,
+                "WARNING: no installed font called '{}', so text drawn with font '{}' will be invisible",
+                font.sys_name,
+                font.name
+            ),
+        }
+    }
+}
+
+/// Finds the description file of an installed extension package, by the name it's referenced by.
+pub fn find_extension_package(dirs: &[PathBuf], name: &asset::PascalString) -> Option<PathBuf> {
+    dirs.iter().map(|dir| dir.join(format!("{}.ged", name))).find(|path| path.is_file())
+}
+
+/// Reads an installed extension package from its description file and the contents file next to it.
+/// Function IDs are given out in order, starting from `next_function_id`, as the IDE does when compiling.
+pub fn read_extension_package(
+    path: &Path,
+    next_function_id: &mut u32,
+    strict: bool,
+) -> Result<asset::Extension, Error> {
+    let mut ged = io::Cursor::new(fs::read(path)?);
+    check_ver(&mut ged, extension::VERSION, strict)?;
+    ged.read_u32::<LE>()?; // editable
+    let name = ged.read_pas_string()?;
+    let folder_name = ged.read_pas_string()?;
+    for _ in 0..6 {
+        ged.read_pas_string()?; // version, author, date, license, description and help file
+    }
+    ged.read_u32::<LE>()?; // hidden
+    let uses_count = ged.read_u32::<LE>()?;
+    for _ in 0..uses_count {
+        ged.read_pas_string()?;
+    }
+
+    let file_count = ged.read_u32::<LE>()? as usize;
+    let mut files = (0..file_count)
+        .map(|_| {
+            check_ver(&mut ged, extension::VERSION, strict)?;
+            let name = ged.read_pas_string()?;
+            ged.read_pas_string()?; // path it was added from
+            let kind = extension::FileKind::from(ged.read_u32::<LE>()?);
+            let initializer = ged.read_pas_string()?;
+            let finalizer = ged.read_pas_string()?;
+            let function_count = ged.read_u32::<LE>()? as usize;
+            let functions = (0..function_count)
+                .map(|_| {
+                    check_ver(&mut ged, extension::VERSION, strict)?;
+                    let name = ged.read_pas_string()?;
+                    let external_name = ged.read_pas_string()?;
+                    let convention = ged.read_u32::<LE>()?.into();
+                    ged.read_pas_string()?; // help line
+                    ged.read_u32::<LE>()?; // hidden
+                    let arg_count = ged.read_i32::<LE>()?;
+                    let mut arg_types = [extension::FunctionValueKind::GMReal; extension::ARG_MAX];
+                    for kind in arg_types.iter_mut() {
+                        *kind = ged.read_u32::<LE>()?.into();
+                    }
+                    let return_type = ged.read_u32::<LE>()?.into();
+                    let id = *next_function_id;
+                    *next_function_id += 1;
+                    Ok(extension::FileFunction {
+                        name,
+                        external_name,
+                        convention,
+                        id,
+                        arg_count,
+                        arg_types,
+                        return_type,
+                    })
+                })
+                .collect::<Result<_, Error>>()?;
+            let const_count = ged.read_u32::<LE>()? as usize;
+            let consts = (0..const_count)
+                .map(|_| {
+                    check_ver(&mut ged, extension::VERSION, strict)?;
+                    let constant = extension::FileConst { name: ged.read_pas_string()?, value: ged.read_pas_string()? };
+                    ged.read_u32::<LE>()?; // hidden
+                    Ok(constant)
+                })
+                .collect::<Result<_, Error>>()?;
+            Ok(extension::File { name, kind, initializer, finalizer, functions, consts, contents: Box::new([]) })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let mut dat = fs::read(path.with_extension("dat"))?;
+    extension::read_contents(&mut io::Cursor::new(dat.as_mut_slice()), &mut files)?;
+    Ok(asset::Extension { name, folder_name, files })
+}
+
+// Reads a list of actions, as found in object events and timeline moments
+fn read_actions(reader: &mut impl Read, version: GameVersion, strict: bool) -> Result<Vec<CodeAction>, Error> {
+    check_ver(reader, 400, strict)?;
+    let count = reader.read_u32::<LE>()? as usize;
+    (0..count).map(|_| CodeAction::deserialize_exe(reader, version, strict)).collect()
+}
+
+fn read_timeline(mut reader: impl Read, version: GameVersion, strict: bool) -> Result<Timeline, Error> {
+    let name = reader.read_pas_string()?;
+    skip_timestamp(&mut reader)?;
+    check_ver(&mut reader, 500, strict)?;
+    let moment_count = reader.read_u32::<LE>()? as usize;
+    let moments = (0..moment_count)
+        .map(|_| Ok((reader.read_u32::<LE>()?, read_actions(&mut reader, version, strict)?)))
+        .collect::<Result<_, Error>>()?;
+    Ok(Timeline { name, moments })
+}
+
+fn read_object(mut reader: impl Read, version: GameVersion, strict: bool) -> Result<Object, Error> {
+    let name = reader.read_pas_string()?;
+    skip_timestamp(&mut reader)?;
+    check_ver(&mut reader, 430, strict)?;
+    let sprite_index = reader.read_i32::<LE>()?;
+    let solid = reader.read_u32::<LE>()? != 0;
+    let visible = reader.read_u32::<LE>()? != 0;
+    let depth = reader.read_i32::<LE>()?;
+    let persistent = reader.read_u32::<LE>()? != 0;
+    let parent_index = reader.read_i32::<LE>()?;
+    let mask_index = reader.read_i32::<LE>()?;
+
+    // This is the index of the last event type rather than a count
+    let event_list_count = reader.read_u32::<LE>()? as usize + 1;
+    let mut events = Vec::with_capacity(event_list_count.max(12));
+    for _ in 0..event_list_count {
+        let mut list = Vec::new();
+        loop {
+            let sub = reader.read_i32::<LE>()?;
+            if sub == -1 {
+                break
+            }
+            list.push((sub as u32, read_actions(&mut reader, version, strict)?));
+        }
+        events.push(list);
+    }
+    // The runner expects every event type to be present
+    events.resize_with(events.len().max(12), Vec::new);
+
+    Ok(Object { name, sprite_index, solid, visible, depth, persistent, parent_index, mask_index, events })
+}
+
+fn read_room(mut reader: impl Read, version: GameVersion, strict: bool) -> Result<room::Room, Error> {
+    let name = reader.read_pas_string()?;
+    skip_timestamp(&mut reader)?;
+    check_ver(&mut reader, 541, strict)?;
+    let caption = reader.read_pas_string()?;
+    let width = reader.read_u32::<LE>()?;
+    let height = reader.read_u32::<LE>()?;
+    reader.read_u32::<LE>()?; // snap x
+    reader.read_u32::<LE>()?; // snap y
+    reader.read_u32::<LE>()?; // isometric grid
+    let speed = reader.read_u32::<LE>()?;
+    let persistent = reader.read_u32::<LE>()? != 0;
+    let bg_colour = reader.read_u32::<LE>()?.into();
+    let (clear_screen, clear_region) = match (version, reader.read_u32::<LE>()?) {
+        (GameVersion::GameMaker8_1, x) => ((x & 0b01) != 0, (x & 0b10) == 0),
+        (_, x) => (x != 0, true),
+    };
+    let creation_code = reader.read_pas_string()?;
+
+    let background_count = reader.read_u32::<LE>()? as usize;
+    let backgrounds = (0..background_count)
+        .map(|_| {
+            Ok(room::Background {
+                visible_on_start: reader.read_u32::<LE>()? != 0,
+                is_foreground: reader.read_u32::<LE>()? != 0,
+                source_bg: reader.read_i32::<LE>()?,
+                xoffset: reader.read_i32::<LE>()?,
+                yoffset: reader.read_i32::<LE>()?,
+                tile_horz: reader.read_u32::<LE>()? != 0,
+                tile_vert: reader.read_u32::<LE>()? != 0,
+                hspeed: reader.read_i32::<LE>()?,
+                vspeed: reader.read_i32::<LE>()?,
+                stretch: reader.read_u32::<LE>()? != 0,
+            })
+        })
+        .collect::<io::Result<_>>()?;
+
+    let views_enabled = reader.read_u32::<LE>()? != 0;
+    let view_count = reader.read_u32::<LE>()? as usize;
+    let views = (0..view_count)
+        .map(|_| {
+            Ok(room::View {
+                visible: reader.read_u32::<LE>()? != 0,
+                source_x: reader.read_i32::<LE>()?,
+                source_y: reader.read_i32::<LE>()?,
+                source_w: reader.read_u32::<LE>()?,
+                source_h: reader.read_u32::<LE>()?,
+                port_x: reader.read_i32::<LE>()?,
+                port_y: reader.read_i32::<LE>()?,
+                port_w: reader.read_u32::<LE>()?,
+                port_h: reader.read_u32::<LE>()?,
+                following: room::ViewFollowData {
+                    hborder: reader.read_i32::<LE>()?,
+                    vborder: reader.read_i32::<LE>()?,
+                    hspeed: reader.read_i32::<LE>()?,
+                    vspeed: reader.read_i32::<LE>()?,
+                    target: reader.read_i32::<LE>()?,
+                },
+            })
+        })
+        .collect::<io::Result<_>>()?;
+
+    // Project files can't store GM8.1's instance and tile scaling, blending or rotation
+    let instance_count = reader.read_u32::<LE>()? as usize;
+    let instances = (0..instance_count)
+        .map(|_| {
+            let instance = room::Instance {
+                x: reader.read_i32::<LE>()?,
+                y: reader.read_i32::<LE>()?,
+                object: reader.read_i32::<LE>()?,
+                id: reader.read_i32::<LE>()?,
+                creation_code: reader.read_pas_string()?,
+                xscale: 1.0,
+                yscale: 1.0,
+                blend: u32::MAX,
+                angle: 0.0,
+            };
+            reader.read_u32::<LE>()?; // locked in editor
+            Ok(instance)
+        })
+        .collect::<io::Result<_>>()?;
+
+    let tile_count = reader.read_u32::<LE>()? as usize;
+    let tiles = (0..tile_count)
+        .map(|_| {
+            let tile = room::Tile {
+                x: reader.read_i32::<LE>()?,
+                y: reader.read_i32::<LE>()?,
+                source_bg: reader.read_i32::<LE>()?,
+                tile_x: reader.read_u32::<LE>()?,
+                tile_y: reader.read_u32::<LE>()?,
+                width: reader.read_u32::<LE>()?,
+                height: reader.read_u32::<LE>()?,
+                depth: reader.read_i32::<LE>()?,
+                id: reader.read_i32::<LE>()?,
+                xscale: 1.0,
+                yscale: 1.0,
+                blend: u32::MAX,
+            };
+            reader.read_u32::<LE>()?; // locked in editor
+            Ok(tile)
+        })
+        .collect::<io::Result<_>>()?;
+
+    // The rest is room editor state, which the runner doesn't need
+
+    Ok(room::Room {
+        name,
+        caption,
+        width,
+        height,
+        speed,
+        persistent,
+        bg_colour,
+        clear_screen,
+        clear_region,
+        creation_code,
+        backgrounds,
+        views_enabled,
+        views,
+        instances,
+        tiles,
+        uses_810_features: false,
+        uses_811_features: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use byteorder::WriteBytesExt;
+    use extension::{CallingConvention, FileKind, FunctionValueKind};
+
+    // A package with one DLL that has one function and one constant, in the format the IDE installs it in
+    fn write_package(dir: &Path, name: &str, function_count: u32) {
+        let mut ged = Vec::new();
+        ged.write_u32::<LE>(700).unwrap();
+        ged.write_u32::<LE>(0).unwrap(); // editable
+        ged.write_pas_string(&name.into()).unwrap();
+        ged.write_pas_string(&"pkg_folder".into()).unwrap();
+        for info in ["1.0", "someone", "2010", "free", "does things", "help.chm"] {
+            ged.write_pas_string(&info.into()).unwrap();
+        }
+        ged.write_u32::<LE>(0).unwrap(); // hidden
+        ged.write_u32::<LE>(1).unwrap();
+        ged.write_pas_string(&"Other Package".into()).unwrap();
+
+        ged.write_u32::<LE>(1).unwrap();
+        ged.write_u32::<LE>(700).unwrap();
+        ged.write_pas_string(&"thing.dll".into()).unwrap();
+        ged.write_pas_string(&"C:\\src\\thing.dll".into()).unwrap();
+        ged.write_u32::<LE>(FileKind::DynamicLibrary as u32).unwrap();
+        ged.write_pas_string(&"thing_init".into()).unwrap();
+        ged.write_pas_string(&"".into()).unwrap();
+        ged.write_u32::<LE>(function_count).unwrap();
+        for i in 0..function_count {
+            ged.write_u32::<LE>(700).unwrap();
+            ged.write_pas_string(&format!("thing_{}", i).as_str().into()).unwrap();
+            ged.write_pas_string(&"Thing".into()).unwrap();
+            ged.write_u32::<LE>(CallingConvention::Stdcall as u32).unwrap();
+            ged.write_pas_string(&"thing(x)".into()).unwrap();
+            ged.write_u32::<LE>(0).unwrap(); // hidden
+            ged.write_i32::<LE>(1).unwrap();
+            for _ in 0..extension::ARG_MAX {
+                ged.write_u32::<LE>(FunctionValueKind::GMString as u32).unwrap();
+            }
+            ged.write_u32::<LE>(FunctionValueKind::GMReal as u32).unwrap();
+        }
+        ged.write_u32::<LE>(1).unwrap();
+        ged.write_u32::<LE>(700).unwrap();
+        ged.write_pas_string(&"THING_MAX".into()).unwrap();
+        ged.write_pas_string(&"10".into()).unwrap();
+        ged.write_u32::<LE>(0).unwrap(); // hidden
+        fs::write(dir.join(format!("{}.ged", name)), ged).unwrap();
+
+        let files = [extension::File {
+            name: "thing.dll".into(),
+            kind: FileKind::DynamicLibrary,
+            initializer: "".into(),
+            finalizer: "".into(),
+            functions: Vec::new(),
+            consts: Vec::new(),
+            contents: b"MZ not really a dll".to_vec().into(),
+        }];
+        let mut dat = Vec::new();
+        extension::write_contents(&mut dat, &files, 777).unwrap();
+        fs::write(dir.join(format!("{}.dat", name)), dat).unwrap();
+    }
+
+    #[test]
+    fn extension_package() {
+        let dir = std::env::temp_dir().join(format!("gm8exe-extensions-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write_package(&dir, "First", 2);
+        write_package(&dir, "Second", 1);
+
+        let dirs = [dir.join("missing"), dir.clone()];
+        assert!(find_extension_package(&dirs, &"Third".into()).is_none());
+        let mut next_id = 0;
+        let first =
+            read_extension_package(&find_extension_package(&dirs, &"First".into()).unwrap(), &mut next_id, true);
+        let second = read_extension_package(&dir.join("Second.ged"), &mut next_id, true);
+        fs::remove_dir_all(&dir).unwrap();
+        let (first, second) = (first.unwrap(), second.unwrap());
+
+        assert_eq!((&*first.name.0, &*first.folder_name.0), (&b"First"[..], &b"pkg_folder"[..]));
+        let file = &first.files[0];
+        assert_eq!((&*file.name.0, &*file.initializer.0), (&b"thing.dll"[..], &b"thing_init"[..]));
+        assert!(file.kind == FileKind::DynamicLibrary);
+        assert_eq!(&*file.contents, b"MZ not really a dll");
+        assert_eq!((&*file.consts[0].name.0, &*file.consts[0].value.0), (&b"THING_MAX"[..], &b"10"[..]));
+        let function = &file.functions[1];
+        assert_eq!((&*function.name.0, &*function.external_name.0), (&b"thing_1"[..], &b"Thing"[..]));
+        assert!(function.convention == CallingConvention::Stdcall && function.return_type == FunctionValueKind::GMReal);
+        assert!(function.arg_count == 1 && function.arg_types[0] == FunctionValueKind::GMString);
+
+        // function IDs carry on from one package to the next
+        assert_eq!(first.files[0].functions.iter().map(|f| f.id).collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(second.files[0].functions[0].id, 2);
+    }
+}
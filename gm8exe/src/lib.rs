@@ -21,10 +21,13 @@ macro_rules! log {
 pub mod asset;
 pub mod def;
 pub mod gamedata;
+pub mod gmk;
 pub mod reader;
 pub mod rsrc;
 pub mod settings;
+pub mod sysfont;
 pub mod upx;
+pub mod writer;
 
 mod colour;
 
@@ -62,10 +65,21 @@ pub struct GameAssets {
     pub guid: [u32; 4],
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum GameVersion {
+    /// Any GameMaker 5.x version
+    GameMaker5_0,
+    GameMaker6_0,
+    GameMaker7_0,
     GameMaker8_0,
     GameMaker8_1,
 }
 
+impl GameVersion {
+    /// Whether this is a version from before GameMaker 8, which stores some chunks differently.
+    pub fn is_legacy(self) -> bool {
+        matches!(self, GameVersion::GameMaker5_0 | GameVersion::GameMaker6_0 | GameVersion::GameMaker7_0)
+    }
+}
+
 pub use colour::Colour;
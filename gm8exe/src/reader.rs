@@ -1,6 +1,6 @@
 use crate::{
     asset::*,
-    gamedata::{self, gm80},
+    gamedata::{self, gm70, gm80},
     rsrc,
     settings::{GameHelpDialog, Settings},
     AssetList, GameAssets, GameVersion,
@@ -66,7 +66,15 @@ pub struct PESection {
     pub disk_address: u32,
 }
 
-pub fn from_exe<I, F>(mut exe: I, logger: Option<F>, strict: bool, multithread: bool) -> Result<GameAssets, ReaderError>
+/// Reads a game's assets from its executable. GameMaker 5, 6 and 7 games are only recognized if `legacy` is set,
+/// as reading them is still experimental.
+pub fn from_exe<I, F>(
+    mut exe: I,
+    logger: Option<F>,
+    strict: bool,
+    multithread: bool,
+    legacy: bool,
+) -> Result<GameAssets, ReaderError>
 where
     F: Copy + Fn(&str),
     I: AsRef<[u8]> + AsMut<[u8]>,
@@ -156,7 +164,7 @@ where
     };
 
     // Identify the game version in use and locate the gamedata header
-    let game_ver = gamedata::find(&mut exe, logger, upx_data)?;
+    let game_ver = gamedata::find(&mut exe, logger, upx_data, legacy)?;
 
     // little helper thing
     macro_rules! assert_ver {
@@ -212,8 +220,8 @@ where
         let frequency = cfg.read_u32::<LE>()?;
         let dont_show_buttons = cfg.read_u32::<LE>()? != 0;
         let (vsync, force_cpu_render) = match (game_ver, cfg.read_u32::<LE>()?) {
-            (GameVersion::GameMaker8_0, x) => (x != 0, true), // see 8.1.141 changelog
             (GameVersion::GameMaker8_1, x) => ((x & 1) != 0, (x & (1 << 7)) != 0),
+            (_, x) => (x != 0, true), // see 8.1.141 changelog
         };
         let disable_screensaver = cfg.read_u32::<LE>()? != 0;
         let f4_fullscreen_toggle = cfg.read_u32::<LE>()? != 0;
@@ -235,8 +243,8 @@ where
         let log_errors = cfg.read_u32::<LE>()? != 0;
         let always_abort = cfg.read_u32::<LE>()? != 0;
         let (zero_uninitialized_vars, error_on_uninitialized_args) = match (game_ver, cfg.read_u32::<LE>()?) {
-            (GameVersion::GameMaker8_0, x) => (x != 0, false),
             (GameVersion::GameMaker8_1, x) => ((x & 1) != 0, (x & 2) != 0),
+            (_, x) => (x != 0, false),
         };
         let swap_creation_events = match cfg.read_u32::<LE>() {
             Ok(_webgl) => cfg.read_u32::<LE>()? != 0,
@@ -400,12 +408,18 @@ where
     exe.read_exact(&mut dx_dll)?;
 
     // yeah
-    gm80::decrypt(&mut exe, logger)?;
+    match game_ver {
+        GameVersion::GameMaker5_0 | GameVersion::GameMaker6_0 => (),
+        GameVersion::GameMaker7_0 => gm70::decrypt(&mut exe, logger)?,
+        GameVersion::GameMaker8_0 | GameVersion::GameMaker8_1 => gm80::decrypt(&mut exe, logger)?,
+    }
 
-    // Garbage field - random bytes
-    let garbage_dwords = exe.read_u32::<LE>()?;
-    exe.seek(SeekFrom::Current((garbage_dwords * 4) as i64))?;
-    log!(logger, "Skipped {} garbage DWORDs", garbage_dwords);
+    // Garbage field - random bytes, which came in with the encryption
+    if !matches!(game_ver, GameVersion::GameMaker5_0 | GameVersion::GameMaker6_0) {
+        let garbage_dwords = exe.read_u32::<LE>()?;
+        exe.seek(SeekFrom::Current((garbage_dwords * 4) as i64))?;
+        log!(logger, "Skipped {} garbage DWORDs", garbage_dwords);
+    }
 
     // GM8 Pro flag, game ID
     let pro_flag: bool = exe.read_u32::<LE>()? != 0;
@@ -474,13 +488,27 @@ where
         get_assets(src, |data| <T as Asset>::deserialize_exe(data, version, strict), multithread)
     }
 
-    assert_ver!("extensions header", 700, exe.read_u32::<LE>()?)?;
-    let extension_count = exe.read_u32::<LE>()? as usize;
-    let mut extensions = Vec::with_capacity(extension_count);
-    for _ in 0..extension_count {
-        let ext = Extension::read(&mut exe, strict)?;
-        log!(logger, "+ Added extension '{}' (files: {})", ext.name, ext.files.len());
-        extensions.push(ext);
+    // Before GM8, asset chunk headers give the version of the chunk's layout, the same as in project files,
+    // and the other chunks carry the major version instead of 800
+    let chunk_ver = |legacy: u32| if game_ver.is_legacy() { legacy } else { 800 };
+    let major_ver = match game_ver {
+        GameVersion::GameMaker5_0 => 500,
+        GameVersion::GameMaker6_0 => 600,
+        GameVersion::GameMaker7_0 => 700,
+        GameVersion::GameMaker8_0 | GameVersion::GameMaker8_1 => 800,
+    };
+
+    // Extensions were added in GM7
+    let mut extensions = Vec::new();
+    if !matches!(game_ver, GameVersion::GameMaker5_0 | GameVersion::GameMaker6_0) {
+        assert_ver!("extensions header", 700, exe.read_u32::<LE>()?)?;
+        let extension_count = exe.read_u32::<LE>()? as usize;
+        extensions.reserve(extension_count);
+        for _ in 0..extension_count {
+            let ext = Extension::read(&mut exe, strict)?;
+            log!(logger, "+ Added extension '{}' (files: {})", ext.name, ext.files.len());
+            extensions.push(ext);
+        }
     }
 
     // Rewrap data immutable.
@@ -488,9 +516,13 @@ where
     let mut exe = io::Cursor::new(exe.into_inner() as &[u8]);
     exe.set_position(prev_pos);
 
-    // Triggers
-    assert_ver!("triggers header", 800, exe.read_u32::<LE>()?)?;
-    let triggers: AssetList<Trigger> = get_assets_ex(&mut exe, game_ver, strict, multithread)?;
+    // Triggers were added in GM8
+    let triggers: AssetList<Trigger> = if game_ver.is_legacy() {
+        Vec::new()
+    } else {
+        assert_ver!("triggers header", 800, exe.read_u32::<LE>()?)?;
+        get_assets_ex(&mut exe, game_ver, strict, multithread)?
+    };
     if logger.is_some() {
         triggers.iter().flatten().for_each(|trigger| {
             log!(
@@ -503,19 +535,22 @@ where
         });
     }
 
-    // Constants
-    assert_ver!("constants header", 800, exe.read_u32::<LE>()?)?;
-    let constant_count = exe.read_u32::<LE>()? as usize;
-    let mut constants = Vec::with_capacity(constant_count);
-    for _ in 0..constant_count {
-        let name = exe.read_pas_string()?;
-        let expression = exe.read_pas_string()?;
-        log!(logger, " + Added constant '{}' (expression: {})", name, expression);
-        constants.push(Constant { name, expression });
+    // Constants were added in GM6
+    let mut constants = Vec::new();
+    if game_ver != GameVersion::GameMaker5_0 {
+        assert_ver!("constants header", major_ver, exe.read_u32::<LE>()?)?;
+        let constant_count = exe.read_u32::<LE>()? as usize;
+        constants.reserve(constant_count);
+        for _ in 0..constant_count {
+            let name = exe.read_pas_string()?;
+            let expression = exe.read_pas_string()?;
+            log!(logger, " + Added constant '{}' (expression: {})", name, expression);
+            constants.push(Constant { name, expression });
+        }
     }
 
     // Sounds
-    assert_ver!("sounds header", 800, exe.read_u32::<LE>()?)?;
+    assert_ver!("sounds header", chunk_ver(400), exe.read_u32::<LE>()?)?;
     let sounds: AssetList<Sound> = get_assets_ex(&mut exe, game_ver, strict, multithread)?;
     if logger.is_some() {
         sounds.iter().flatten().for_each(|sound| {
@@ -524,7 +559,7 @@ where
     }
 
     // Sprites
-    assert_ver!("sprites header", 800, exe.read_u32::<LE>()?)?;
+    assert_ver!("sprites header", chunk_ver(400), exe.read_u32::<LE>()?)?;
     let sprites: AssetList<Sprite> = get_assets_ex(&mut exe, game_ver, strict, multithread)?;
     if logger.is_some() {
         sprites.iter().flatten().for_each(|sprite| {
@@ -546,7 +581,7 @@ where
     }
 
     // Backgrounds
-    assert_ver!("backgrounds header", 800, exe.read_u32::<LE>()?)?;
+    assert_ver!("backgrounds header", chunk_ver(400), exe.read_u32::<LE>()?)?;
     let backgrounds: AssetList<Background> = get_assets_ex(&mut exe, game_ver, strict, multithread)?;
     if logger.is_some() {
         backgrounds.iter().flatten().for_each(|background| {
@@ -555,7 +590,7 @@ where
     }
 
     // Paths
-    assert_ver!("paths header", 800, exe.read_u32::<LE>()?)?;
+    assert_ver!("paths header", chunk_ver(420), exe.read_u32::<LE>()?)?;
     let paths: AssetList<Path> = get_assets_ex(&mut exe, game_ver, strict, multithread)?;
     if logger.is_some() {
         use crate::asset::path::ConnectionKind;
@@ -578,7 +613,7 @@ where
     }
 
     // Scripts
-    assert_ver!("scripts header", 800, exe.read_u32::<LE>()?)?;
+    assert_ver!("scripts header", chunk_ver(400), exe.read_u32::<LE>()?)?;
     let scripts: AssetList<Script> = get_assets_ex(&mut exe, game_ver, strict, multithread)?;
     if logger.is_some() {
         scripts.iter().flatten().for_each(|script| {
@@ -586,9 +621,13 @@ where
         });
     }
 
-    // Fonts
-    assert_ver!("fonts header", 800, exe.read_u32::<LE>()?)?;
-    let fonts: AssetList<Font> = get_assets_ex(&mut exe, game_ver, strict, multithread)?;
+    // Fonts were added in GM6
+    let fonts: AssetList<Font> = if game_ver == GameVersion::GameMaker5_0 {
+        Vec::new()
+    } else {
+        assert_ver!("fonts header", chunk_ver(540), exe.read_u32::<LE>()?)?;
+        get_assets_ex(&mut exe, game_ver, strict, multithread)?
+    };
     if logger.is_some() {
         fonts.iter().flatten().for_each(|font| {
             log!(
@@ -604,7 +643,7 @@ where
     }
 
     // Timelines
-    assert_ver!("timelines header", 800, exe.read_u32::<LE>()?)?;
+    assert_ver!("timelines header", chunk_ver(500), exe.read_u32::<LE>()?)?;
     let timelines: AssetList<Timeline> = get_assets_ex(&mut exe, game_ver, strict, multithread)?;
     if logger.is_some() {
         timelines.iter().flatten().for_each(|timeline| {
@@ -613,7 +652,7 @@ where
     }
 
     // Objects
-    assert_ver!("objects header", 800, exe.read_u32::<LE>()?)?;
+    assert_ver!("objects header", chunk_ver(400), exe.read_u32::<LE>()?)?;
     let objects: AssetList<Object> = get_assets_ex(&mut exe, game_ver, strict, multithread)?;
     if logger.is_some() {
         objects.iter().flatten().for_each(|object| {
@@ -630,7 +669,7 @@ where
     }
 
     // Rooms
-    assert_ver!("rooms header", 800, exe.read_u32::<LE>()?)?;
+    assert_ver!("rooms header", chunk_ver(420), exe.read_u32::<LE>()?)?;
     let rooms: AssetList<Room> = get_assets_ex(&mut exe, game_ver, strict, multithread)?;
     if logger.is_some() {
         rooms.iter().flatten().for_each(|room| {
@@ -650,7 +689,7 @@ where
     let last_tile_id = exe.read_i32::<LE>()?;
 
     // Included Files
-    assert_ver!("included files header", 800, exe.read_u32::<LE>()?)?;
+    assert_ver!("included files header", major_ver, exe.read_u32::<LE>()?)?;
     // TODO: how was this different from the others? why is it not using get_assets?
     let included_files = get_asset_refs(&mut exe)?
         .iter()
@@ -679,7 +718,7 @@ where
     }
 
     // Help Dialog
-    assert_ver!("help dialog", 800, exe.read_u32::<LE>()?)?;
+    assert_ver!("help dialog", major_ver, exe.read_u32::<LE>()?)?;
     let help_dialog = {
         let len = exe.read_u32::<LE>()? as usize;
         let pos = exe.position() as usize;
@@ -0,0 +1,286 @@
+//! Rendering font assets from the fonts installed on the system, the way the IDE does when it compiles a game.
+//!
+//! Project files only store the name, size and style of each font, so this is where fonts loaded from them
+//! get their glyphs. Only the first 256 character codes are rendered, as Windows-1252.
+
+use crate::asset::Font;
+use ab_glyph::{Font as _, FontRef, InvalidFont, PxScale, ScaleFont};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// The width of the pixel map glyphs get laid out in, unless a glyph is wider than this.
+const MAP_WIDTH: u32 = 512;
+
+/// A font file picked for a font asset.
+pub struct FontFile {
+    pub path: PathBuf,
+    pub data: Vec<u8>,
+
+    /// Which font this is, if the file is a collection of them.
+    pub index: u32,
+}
+
+/// A font to look for: its family name, and whether it should be bold and italic.
+pub struct Request<'a> {
+    pub family: &'a str,
+    pub bold: bool,
+    pub italic: bool,
+}
+
+/// The folders fonts get installed to on this system.
+pub fn font_dirs() -> Vec<PathBuf> {
+    let home = env::var_os("HOME").map(PathBuf::from);
+    let mut dirs = Vec::new();
+    if cfg!(windows) {
+        let windir = env::var_os("WINDIR").map(PathBuf::from).unwrap_or_else(|| PathBuf::from("C:\\Windows"));
+        dirs.push(windir.join("Fonts"));
+        if let Some(local) = env::var_os("LOCALAPPDATA") {
+            dirs.push(PathBuf::from(local).join("Microsoft").join("Windows").join("Fonts"));
+        }
+    } else if cfg!(target_os = "macos") {
+        dirs.push(PathBuf::from("/System/Library/Fonts"));
+        dirs.push(PathBuf::from("/Library/Fonts"));
+        dirs.extend(home.map(|h| h.join("Library").join("Fonts")));
+    } else {
+        dirs.push(PathBuf::from("/usr/share/fonts"));
+        dirs.push(PathBuf::from("/usr/local/share/fonts"));
+        if let Some(home) = home {
+            dirs.push(home.join(".fonts"));
+            dirs.push(home.join(".local").join("share").join("fonts"));
+        }
+    }
+    dirs
+}
+
+/// Searches the given folders, and the folders in them, for the best font file for each request.
+/// Family names are matched case-insensitively, and a file with the wrong style is only used if there's no better one.
+pub fn find(dirs: &[PathBuf], requests: &[Request]) -> Vec<Option<FontFile>> {
+    let mut found: Vec<Option<(u32, FontFile)>> = requests.iter().map(|_| None).collect();
+    let mut pending = dirs.to_vec();
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            if path.is_dir() {
+                pending.push(path);
+            } else if is_font_file(&path) {
+                if let Ok(data) = fs::read(&path) {
+                    check_file(&path, data, requests, &mut found);
+                }
+            }
+        }
+    }
+    found.into_iter().map(|f| f.map(|(_, file)| file)).collect()
+}
+
+fn is_font_file(path: &Path) -> bool {
+    let ext = path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+    matches!(ext.as_deref(), Some("ttf" | "otf" | "ttc" | "otc"))
+}
+
+// Checks every font in a file against the requests, keeping it for any it matches better than what's been found
+fn check_file(path: &Path, data: Vec<u8>, requests: &[Request], found: &mut [Option<(u32, FontFile)>]) {
+    let mut best = Vec::new();
+    for index in 0..ttf_parser::fonts_in_collection(&data).unwrap_or(1) {
+        let face = match ttf_parser::Face::parse(&data, index) {
+            Ok(face) => face,
+            Err(_) => continue,
+        };
+        let families = face
+            .names()
+            .into_iter()
+            .filter(|n| matches!(n.name_id, ttf_parser::name_id::FAMILY | ttf_parser::name_id::TYPOGRAPHIC_FAMILY))
+            .filter_map(|n| n.to_string())
+            .collect::<Vec<_>>();
+        for (i, request) in requests.iter().enumerate() {
+            if families.iter().any(|f| f.eq_ignore_ascii_case(request.family)) {
+                let mismatches =
+                    u32::from(face.is_bold() != request.bold) + u32::from(face.is_italic() != request.italic);
+                if found[i].as_ref().map_or(true, |(m, _)| mismatches < *m) {
+                    best.push((i, mismatches, index));
+                }
+            }
+        }
+    }
+    for (i, mismatches, index) in best {
+        if found[i].as_ref().map_or(true, |(m, _)| mismatches < *m) {
+            found[i] = Some((mismatches, FontFile { path: path.to_path_buf(), data: data.clone(), index }));
+        }
+    }
+}
+
+/// Renders a font asset's glyphs from a font file, filling in its glyph table and pixel map.
+/// The size is in points at 96 DPI, like GameMaker uses. Without antialiasing, glyphs only have opaque pixels.
+pub fn render(font: &mut Font, file: &FontFile, antialias: bool) -> Result<(), InvalidFont> {
+    let face = FontRef::try_from_slice_and_index(&file.data, file.index)?;
+    let scale = face.pt_to_px_scale(font.size as f32).unwrap_or_else(|| PxScale::from(font.size as f32));
+    let scaled = face.as_scaled(scale);
+    let ascent = scaled.ascent();
+    let height = (ascent - scaled.descent()).ceil().max(1.0) as u32;
+
+    // every glyph is as tall as a line, with the baseline in the same place, so they can be drawn top-aligned
+    struct Glyph {
+        code: usize,
+        width: u32,
+        offset: i32,
+        distance: u32,
+        pixels: Vec<u8>,
+    }
+    let first = font.range_start.min(255);
+    let last = font.range_end.min(255);
+    let glyphs = (first..=last)
+        .map(|code| {
+            let mut glyph = scaled.scaled_glyph(windows_1252(code as u8));
+            let distance = scaled.h_advance(glyph.id).round().max(0.0) as u32;
+            glyph.position = ab_glyph::point(0.0, ascent);
+            match scaled.outline_glyph(glyph) {
+                Some(outline) => {
+                    let bounds = outline.px_bounds();
+                    let width = (bounds.width() as u32).max(1);
+                    let top = bounds.min.y as i32;
+                    let mut pixels = vec![0; (width * height) as usize];
+                    outline.draw(|x, y, coverage| {
+                        let y = top + y as i32;
+                        if x < width && (0..height as i32).contains(&y) {
+                            pixels[(y as u32 * width + x) as usize] = match antialias {
+                                true => (coverage.clamp(0.0, 1.0) * 255.0).round() as u8,
+                                false if coverage >= 0.5 => 255,
+                                false => 0,
+                            };
+                        }
+                    });
+                    Glyph { code: code as usize, width, offset: bounds.min.x as i32, distance, pixels }
+                },
+                None => Glyph { code: code as usize, width: 1, offset: 0, distance, pixels: vec![0; height as usize] },
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // lay the glyphs out in rows
+    let map_width = glyphs.iter().map(|g| g.width).max().unwrap_or(1).max(MAP_WIDTH);
+    let mut positions = Vec::with_capacity(glyphs.len());
+    let (mut x, mut y) = (0, 0);
+    for glyph in &glyphs {
+        if x + glyph.width > map_width {
+            x = 0;
+            y += height;
+        }
+        positions.push((x, y));
+        x += glyph.width;
+    }
+    let map_height = y + height;
+
+    let mut pixel_map = vec![0u8; (map_width * map_height) as usize];
+    let mut dmap = Box::new([0u32; 0x600]);
+    for (glyph, (x, y)) in glyphs.iter().zip(positions) {
+        for (row, line) in glyph.pixels.chunks_exact(glyph.width as usize).enumerate() {
+            let start = ((y + row as u32) * map_width + x) as usize;
+            pixel_map[start..start + line.len()].copy_from_slice(line);
+        }
+        dmap[glyph.code * 6..glyph.code * 6 + 6].copy_from_slice(&[
+            x,
+            y,
+            glyph.width,
+            height,
+            glyph.offset as u32,
+            glyph.distance,
+        ]);
+    }
+
+    font.dmap = dmap;
+    font.map_width = map_width;
+    font.map_height = map_height;
+    font.pixel_map = pixel_map.into_boxed_slice();
+    Ok(())
+}
+
+// Windows-1252 only differs from Latin-1 in 0x80-0x9F
+fn windows_1252(byte: u8) -> char {
+    const HIGH: [char; 32] = [
+        '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}', '\u{90}', '‘',
+        '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+    ];
+    match byte {
+        0x80..=0x9F => HIGH[usize::from(byte - 0x80)],
+        _ => char::from(byte),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::PascalString;
+
+    fn font(size: u32) -> Font {
+        Font {
+            name: "fnt_test".into(),
+            sys_name: PascalString::default(),
+            size,
+            bold: false,
+            italic: false,
+            range_start: 32,
+            range_end: 127,
+            charset: 0,
+            aa_level: 0,
+            dmap: Box::new([0; 0x600]),
+            map_width: 0,
+            map_height: 0,
+            pixel_map: Box::new([]),
+        }
+    }
+
+    #[test]
+    fn code_page() {
+        assert_eq!(windows_1252(b'A'), 'A');
+        assert_eq!(windows_1252(0x80), '€');
+        assert_eq!(windows_1252(0x9F), 'Ÿ');
+        assert_eq!(windows_1252(0xE9), 'é');
+    }
+
+    #[test]
+    fn no_match() {
+        let requests = [Request { family: "Not A Real Font Family", bold: false, italic: false }];
+        assert!(find(&[env::temp_dir().join("gm8exe-no-fonts-here")], &requests)[0].is_none());
+        assert!(render(&mut font(12), &FontFile { path: PathBuf::new(), data: vec![0; 16], index: 0 }, true).is_err());
+    }
+
+    // This needs a font installed to do anything, so it takes the first one it can find
+    #[test]
+    fn render_system_font() {
+        let file = match font_dirs().iter().flat_map(|d| walk(d)).find(|p| is_font_file(p)) {
+            Some(path) => FontFile { data: fs::read(&path).unwrap(), path, index: 0 },
+            None => return,
+        };
+        let mut font = font(12);
+        if render(&mut font, &file, true).is_err() {
+            return
+        }
+        let glyph = |c: u8| &font.dmap[usize::from(c) * 6..usize::from(c) * 6 + 6];
+        assert!(font.map_width >= MAP_WIDTH && font.map_height >= glyph(b'A')[3]);
+        assert_eq!(font.pixel_map.len(), (font.map_width * font.map_height) as usize);
+        assert!(glyph(b'A')[5] > 0 && glyph(b'W')[2] > glyph(b'.')[2]);
+        assert_eq!(glyph(31), [0; 6]);
+        assert_eq!(glyph(128), [0; 6]);
+
+        // 'A' has some ink in it, while a space doesn't
+        let ink = |c: u8| {
+            let [x, y, w, h, ..] = glyph(c) else { unreachable!() };
+            (*y..y + h)
+                .flat_map(|row| (*x..x + w).map(move |col| (row, col)))
+                .any(|(row, col)| font.pixel_map[(row * font.map_width + col) as usize] != 0)
+        };
+        assert!(ink(b'A') && !ink(b' '));
+    }
+
+    fn walk(dir: &Path) -> Vec<PathBuf> {
+        let mut out = Vec::new();
+        for path in fs::read_dir(dir).into_iter().flatten().filter_map(|e| e.ok()).map(|e| e.path()) {
+            if path.is_dir() { out.extend(walk(&path)) } else { out.push(path) }
+        }
+        out
+    }
+}
@@ -0,0 +1,625 @@
+use crate::{
+    asset::{Asset, WritePascalString},
+    gamedata::{gm80, gm81},
+    settings::{GameHelpDialog, Settings},
+    AssetList, GameAssets, GameVersion,
+};
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use flate2::{write::ZlibEncoder, Compression};
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use std::{
+    fmt::{self, Display},
+    io::{self, Read, Write},
+};
+
+#[derive(Debug)]
+pub enum WriterError {
+    IO(io::Error),
+    UnknownRunner,
+    VersionMismatch { runner: GameVersion, assets: GameVersion },
+}
+impl std::error::Error for WriterError {}
+impl Display for WriterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WriterError::IO(err) => write!(f, "io error: {}", err),
+            WriterError::UnknownRunner => write!(f, "unknown runner, expected a standard GM8.0 or GM8.1 runner"),
+            WriterError::VersionMismatch { runner, assets } => {
+                write!(f, "runner is for {:?}, but the assets are for {:?}", runner, assets)
+            },
+        }
+    }
+}
+
+impl From<io::Error> for WriterError {
+    fn from(err: io::Error) -> Self {
+        WriterError::IO(err)
+    }
+}
+
+/// Where a runner looks for its gamedata, as read from its loading sequence.
+struct Runner {
+    version: GameVersion,
+    header_start: usize,
+    magic: u32,
+    header_version: u32,
+    xor_method: gm81::XorMethod,
+}
+
+/// Reads the loading sequence of a standard GM8.0 or GM8.1 runner. Returns None if it isn't one.
+/// If a magic or version check has been patched out, the value GameMaker itself would use is assumed.
+fn identify_runner(runner: &[u8]) -> io::Result<Option<Runner>> {
+    let mut exe = io::Cursor::new(runner);
+
+    // see gm80::check
+    if runner.len() >= 0x144AC0 + 4
+        && runner.get(0xA49BE..0xA49C6) == Some(&[0x8B, 0x45, 0xF4, 0xE8, 0x2A, 0xBD, 0xFD, 0xFF])
+    {
+        exe.set_position(0xA49C6);
+        let magic = if exe.read_u8()? == 0x3D { exe.read_u32::<LE>()? } else { 1234321 };
+        exe.set_position(0xA49E9);
+        let version_check = runner.get(0xA49E2..0xA49E9) == Some(&[0x8B, 0xC6, 0xE8, 0x07, 0xBD, 0xFD, 0xFF]);
+        let header_version = if version_check && exe.read_u8()? == 0x3D { exe.read_u32::<LE>()? } else { 800 };
+        exe.set_position(0x144AC0);
+        let header_start = exe.read_u32::<LE>()? as usize;
+        return Ok(Some(Runner {
+            version: GameVersion::GameMaker8_0,
+            header_start,
+            magic,
+            header_version,
+            xor_method: gm81::XorMethod::Normal,
+        }))
+    }
+
+    // see gm81::check
+    if runner.get(0x226CF3..0x226CFB) == Some(&[0xE8, 0x80, 0xF2, 0xDD, 0xFF, 0xC7, 0x45, 0xF0]) {
+        exe.set_position(0x226CFB);
+        let header_start = exe.read_u32::<LE>()? as usize;
+        exe.set_position(0x226CFB + 4 + 125);
+        let mut buf = [0u8; 3];
+        exe.read_exact(&mut buf)?;
+        let magic = if buf == [0x81, 0x7D, 0xEC] { exe.read_u32::<LE>()? } else { 0xF7140067 };
+        let xor_method = match runner.get(0x10BB83..0x10BB8B) {
+            Some([0x8B, 0x02, 0xC1, 0xE0, 0x10, 0x8B, 0x11, 0x81]) => gm81::XorMethod::Sudalv,
+            _ => gm81::XorMethod::Normal,
+        };
+        return Ok(Some(Runner {
+            version: GameVersion::GameMaker8_1,
+            header_start,
+            magic,
+            header_version: 810,
+            xor_method,
+        }))
+    }
+
+    Ok(None)
+}
+
+/// Builds a game executable from a runner and a set of assets.
+///
+/// The runner should be a standard GM8.0 or GM8.1 runner matching `assets.version`. Anything in it from where the
+/// runner starts looking for gamedata is dropped, so the executable of an existing game can be used as well.
+/// The runner's own resources, such as its icon, are kept as they are.
+pub fn to_exe<F>(
+    runner: &[u8],
+    assets: &GameAssets,
+    logger: Option<F>,
+    multithread: bool,
+) -> Result<Vec<u8>, WriterError>
+where
+    F: Copy + Fn(&str),
+{
+    let info = identify_runner(runner)?.ok_or(WriterError::UnknownRunner)?;
+    if info.version != assets.version {
+        return Err(WriterError::VersionMismatch { runner: info.version, assets: assets.version })
+    }
+    log!(logger, "Writing gamedata for {:?} at 0x{:X}", info.version, info.header_start);
+
+    let mut exe = runner[..runner.len().min(info.header_start)].to_vec();
+    exe.resize(info.header_start, 0);
+
+    // Header
+    let header_pos = exe.len();
+    match info.version {
+        GameVersion::GameMaker8_1 => {
+            // The magic value is split across two dwords, with the rest of their bits free for a xor value.
+            exe.write_u32::<LE>(info.magic & 0xFF00FF00)?;
+            exe.write_u32::<LE>(info.magic & 0x00FF00FF)?;
+            exe.write_u32::<LE>(assets.game_id)?; // hash key number
+            exe.write_u32::<LE>(assets.guid[0])?; // xor seed
+            exe.write_all(&[0; 20])?;
+        },
+        _ => {
+            exe.write_u32::<LE>(info.magic)?;
+            exe.write_u32::<LE>(info.header_version)?;
+            exe.write_all(&[0; 8])?;
+        },
+    }
+
+    // Game Settings
+    let mut enc = ZlibEncoder::new(Vec::new(), Compression::default());
+    write_settings(&mut enc, &assets.settings, assets.version)?;
+    let settings = enc.finish()?;
+    exe.write_u32::<LE>(settings.len() as u32)?;
+    exe.write_all(&settings)?;
+    log!(logger, " + Wrote settings chunk ({} bytes)", settings.len());
+
+    // Embedded DirectX DLL - its name isn't kept when reading, but it's always this
+    exe.write_pas_string(&"D3DX8.dll".into())?;
+    exe.write_u32::<LE>(assets.dx_dll.len() as u32)?;
+    exe.write_all(&assets.dx_dll)?;
+
+    // Everything else goes through GM8.0's protection, with no garbage either side of the swap table
+    let mut data = Vec::new();
+    write_gamedata(&mut data, assets, logger, multithread)?;
+    let swap_table = swap_table(assets.game_id);
+    gm80::encrypt(&mut data, &swap_table);
+    exe.write_u32::<LE>(0)?;
+    exe.write_u32::<LE>(0)?;
+    exe.write_all(&swap_table)?;
+    exe.write_u32::<LE>(data.len() as u32)?;
+    exe.write_all(&data)?;
+    log!(logger, " + Encrypted asset data ({} bytes)", data.len());
+
+    // GM8.1's protection is a xor stream over the rest of the file, so applying it is the same as removing it
+    if info.version == GameVersion::GameMaker8_1 {
+        let mut cursor = io::Cursor::new(exe.as_mut_slice());
+        cursor.set_position(header_pos as u64 + 8);
+        gm81::decrypt(&mut cursor, logger, info.xor_method)?;
+    }
+
+    Ok(exe)
+}
+
+/// Writes the settings chunk, before compression.
+fn write_settings(cfg: &mut impl Write, settings: &Settings, version: GameVersion) -> io::Result<()> {
+    fn write_data_maybe(cfg: &mut impl Write, data: &Option<Box<[u8]>>) -> io::Result<()> {
+        match data {
+            Some(data) => {
+                cfg.write_u32::<LE>(1)?;
+                cfg.write_u32::<LE>(data.len() as u32)?;
+                cfg.write_all(data)
+            },
+            None => cfg.write_u32::<LE>(0),
+        }
+    }
+
+    cfg.write_u32::<LE>(settings.fullscreen.into())?;
+    cfg.write_u32::<LE>(settings.interpolate_pixels.into())?;
+    cfg.write_u32::<LE>(settings.dont_draw_border.into())?;
+    cfg.write_u32::<LE>(settings.display_cursor.into())?;
+    cfg.write_i32::<LE>(settings.scaling)?;
+    cfg.write_u32::<LE>(settings.allow_resize.into())?;
+    cfg.write_u32::<LE>(settings.window_on_top.into())?;
+    cfg.write_u32::<LE>(settings.clear_colour)?;
+    cfg.write_u32::<LE>(settings.set_resolution.into())?;
+    cfg.write_u32::<LE>(settings.colour_depth)?;
+    cfg.write_u32::<LE>(settings.resolution)?;
+    cfg.write_u32::<LE>(settings.frequency)?;
+    cfg.write_u32::<LE>(settings.dont_show_buttons.into())?;
+    match version {
+        GameVersion::GameMaker8_1 => {
+            cfg.write_u32::<LE>(u32::from(settings.vsync) | (u32::from(settings.force_cpu_render) << 7))?
+        },
+        _ => cfg.write_u32::<LE>(settings.vsync.into())?,
+    }
+    cfg.write_u32::<LE>(settings.disable_screensaver.into())?;
+    cfg.write_u32::<LE>(settings.f4_fullscreen_toggle.into())?;
+    cfg.write_u32::<LE>(settings.f1_help_menu.into())?;
+    cfg.write_u32::<LE>(settings.esc_close_game.into())?;
+    cfg.write_u32::<LE>(settings.f5_save_f6_load.into())?;
+    cfg.write_u32::<LE>(settings.f9_screenshot.into())?;
+    cfg.write_u32::<LE>(settings.treat_close_as_esc.into())?;
+    cfg.write_u32::<LE>(settings.priority)?;
+    cfg.write_u32::<LE>(settings.freeze_on_lose_focus.into())?;
+    cfg.write_u32::<LE>(settings.loading_bar)?;
+    if settings.loading_bar != 0 {
+        write_data_maybe(cfg, &settings.backdata)?;
+        write_data_maybe(cfg, &settings.frontdata)?;
+    }
+    write_data_maybe(cfg, &settings.custom_load_image)?;
+    cfg.write_u32::<LE>(settings.transparent.into())?;
+    cfg.write_u32::<LE>(settings.translucency)?;
+    cfg.write_u32::<LE>(settings.scale_progress_bar.into())?;
+    cfg.write_u32::<LE>(settings.show_error_messages.into())?;
+    cfg.write_u32::<LE>(settings.log_errors.into())?;
+    cfg.write_u32::<LE>(settings.always_abort.into())?;
+    match version {
+        GameVersion::GameMaker8_1 => cfg.write_u32::<LE>(
+            u32::from(settings.zero_uninitialized_vars) | (u32::from(settings.error_on_uninitialized_args) << 1),
+        )?,
+        _ => cfg.write_u32::<LE>(settings.zero_uninitialized_vars.into())?,
+    }
+    cfg.write_u32::<LE>(0)?; // webgl
+    cfg.write_u32::<LE>(settings.swap_creation_events.into())?;
+    Ok(())
+}
+
+/// Writes everything which comes after the DLL, before encryption.
+fn write_gamedata<F>(data: &mut Vec<u8>, assets: &GameAssets, logger: Option<F>, multithread: bool) -> io::Result<()>
+where
+    F: Copy + Fn(&str),
+{
+    let version = assets.version;
+
+    data.write_u32::<LE>(0)?; // garbage DWORDs
+    data.write_u32::<LE>(1)?; // pro flag
+    data.write_u32::<LE>(assets.game_id)?;
+    for n in assets.guid.iter() {
+        data.write_u32::<LE>(*n)?;
+    }
+
+    data.write_u32::<LE>(700)?;
+    data.write_u32::<LE>(assets.extensions.len() as u32)?;
+    for extension in &assets.extensions {
+        extension.write(data, assets.game_id)?;
+    }
+
+    data.write_u32::<LE>(800)?;
+    write_assets(data, &assets.triggers, version, multithread)?;
+
+    data.write_u32::<LE>(800)?;
+    data.write_u32::<LE>(assets.constants.len() as u32)?;
+    for constant in &assets.constants {
+        data.write_pas_string(&constant.name)?;
+        data.write_pas_string(&constant.expression)?;
+    }
+
+    data.write_u32::<LE>(800)?;
+    write_assets(data, &assets.sounds, version, multithread)?;
+    data.write_u32::<LE>(800)?;
+    write_assets(data, &assets.sprites, version, multithread)?;
+    data.write_u32::<LE>(800)?;
+    write_assets(data, &assets.backgrounds, version, multithread)?;
+    data.write_u32::<LE>(800)?;
+    write_assets(data, &assets.paths, version, multithread)?;
+    data.write_u32::<LE>(800)?;
+    write_assets(data, &assets.scripts, version, multithread)?;
+    data.write_u32::<LE>(800)?;
+    write_assets(data, &assets.fonts, version, multithread)?;
+    data.write_u32::<LE>(800)?;
+    write_assets(data, &assets.timelines, version, multithread)?;
+    data.write_u32::<LE>(800)?;
+    write_assets(data, &assets.objects, version, multithread)?;
+    data.write_u32::<LE>(800)?;
+    write_assets(data, &assets.rooms, version, multithread)?;
+    log!(logger, " + Wrote assets");
+
+    data.write_i32::<LE>(assets.last_instance_id)?;
+    data.write_i32::<LE>(assets.last_tile_id)?;
+
+    // Included files always exist, so they don't have the flag other assets do
+    data.write_u32::<LE>(800)?;
+    data.write_u32::<LE>(assets.included_files.len() as u32)?;
+    for file in &assets.included_files {
+        let mut enc = ZlibEncoder::new(Vec::new(), Compression::default());
+        file.serialize_exe(&mut enc, version)?;
+        write_chunk(data, &enc.finish()?)?;
+    }
+
+    data.write_u32::<LE>(800)?;
+    let mut enc = ZlibEncoder::new(Vec::new(), Compression::default());
+    write_help_dialog(&mut enc, &assets.help_dialog)?;
+    write_chunk(data, &enc.finish()?)?;
+
+    data.write_u32::<LE>(500)?;
+    data.write_u32::<LE>(assets.library_init_strings.len() as u32)?;
+    for string in &assets.library_init_strings {
+        data.write_pas_string(string)?;
+    }
+
+    data.write_u32::<LE>(700)?;
+    data.write_u32::<LE>(assets.room_order.len() as u32)?;
+    for room in &assets.room_order {
+        data.write_i32::<LE>(*room)?;
+    }
+
+    Ok(())
+}
+
+fn write_help_dialog(writer: &mut impl Write, help_dialog: &GameHelpDialog) -> io::Result<()> {
+    writer.write_u32::<LE>(help_dialog.bg_colour.into())?;
+    writer.write_u32::<LE>(help_dialog.new_window.into())?;
+    writer.write_pas_string(&help_dialog.caption)?;
+    writer.write_i32::<LE>(help_dialog.left)?;
+    writer.write_i32::<LE>(help_dialog.top)?;
+    writer.write_u32::<LE>(help_dialog.width)?;
+    writer.write_u32::<LE>(help_dialog.height)?;
+    writer.write_u32::<LE>(help_dialog.border.into())?;
+    writer.write_u32::<LE>(help_dialog.resizable.into())?;
+    writer.write_u32::<LE>(help_dialog.window_on_top.into())?;
+    writer.write_u32::<LE>(help_dialog.freeze_game.into())?;
+    writer.write_pas_string(&help_dialog.info)?;
+    Ok(())
+}
+
+/// Writes a length-prefixed chunk of data.
+fn write_chunk(writer: &mut impl Write, chunk: &[u8]) -> io::Result<()> {
+    writer.write_u32::<LE>(chunk.len() as u32)?;
+    writer.write_all(chunk)
+}
+
+/// Writes a list of assets, each compressed separately with a flag for whether it exists.
+fn write_assets<T>(data: &mut Vec<u8>, list: &AssetList<T>, version: GameVersion, multithread: bool) -> io::Result<()>
+where
+    T: Asset + Sync,
+{
+    let compress = |asset: &Option<Box<T>>| {
+        let mut enc = ZlibEncoder::new(Vec::new(), Compression::default());
+        match asset {
+            Some(asset) => {
+                enc.write_u32::<LE>(1)?;
+                asset.serialize_exe(&mut enc, version)?;
+            },
+            None => enc.write_u32::<LE>(0)?,
+        }
+        enc.finish()
+    };
+
+    let chunks = if multithread {
+        list.par_iter().map(compress).collect::<io::Result<Vec<_>>>()?
+    } else {
+        list.iter().map(compress).collect::<io::Result<Vec<_>>>()?
+    };
+    data.write_u32::<LE>(chunks.len() as u32)?;
+    for chunk in chunks {
+        write_chunk(data, &chunk)?;
+    }
+    Ok(())
+}
+
+/// Makes a swap table for GM8.0's protection. Any permutation works, so it's shuffled with a simple xorshift.
+fn swap_table(seed: u32) -> [u8; 256] {
+    let mut table = [0u8; 256];
+    for (i, val) in table.iter_mut().enumerate() {
+        *val = i as u8;
+    }
+    let mut state = seed | 1;
+    for i in (1..table.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        table.swap(i, state as usize % (i + 1));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asset::{
+            path::{ConnectionKind, Point},
+            sprite::{CollisionMap, Frame},
+            sound::SoundFX,
+            Background, Constant, Path, Script, Sound, SoundKind, Sprite,
+        },
+        reader,
+    };
+
+    // The smallest file that passes for a standard GM8.0 runner: a PE header with no sections,
+    // the loading sequence gm80::check looks for, and the header position after it.
+    fn gm80_runner() -> Vec<u8> {
+        let mut runner = vec![0u8; 0x144AC4];
+        runner[..2].copy_from_slice(b"MZ");
+        runner[0x3C..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+        runner[0x40..0x46].copy_from_slice(b"PE\0\0\x4C\x01");
+        runner[0xA49BE..0xA49C6].copy_from_slice(&[0x8B, 0x45, 0xF4, 0xE8, 0x2A, 0xBD, 0xFD, 0xFF]);
+        runner[0xA49C6] = 0x3D;
+        runner[0xA49C7..0xA49CB].copy_from_slice(&1234321u32.to_le_bytes());
+        runner[0xA49CB..0xA49D1].copy_from_slice(&[0x0F, 0x85, 0x18, 0x01, 0x00, 0x00]);
+        runner[0xA49E2..0xA49E9].copy_from_slice(&[0x8B, 0xC6, 0xE8, 0x07, 0xBD, 0xFD, 0xFF]);
+        runner[0xA49E9] = 0x3D;
+        runner[0xA49EA..0xA49EE].copy_from_slice(&800u32.to_le_bytes());
+        runner[0xA49EE..0xA49F4].copy_from_slice(&[0x0F, 0x85, 0xF5, 0x00, 0x00, 0x00]);
+        runner[0x144AC0..].copy_from_slice(&0x144AC4u32.to_le_bytes());
+        runner
+    }
+
+    fn settings() -> Settings {
+        Settings {
+            fullscreen: true,
+            scaling: -1,
+            interpolate_pixels: false,
+            clear_colour: 0x123456,
+            allow_resize: true,
+            window_on_top: false,
+            dont_draw_border: false,
+            dont_show_buttons: false,
+            display_cursor: true,
+            freeze_on_lose_focus: false,
+            disable_screensaver: true,
+            force_cpu_render: true,
+            set_resolution: false,
+            colour_depth: 0,
+            resolution: 0,
+            frequency: 0,
+            vsync: true,
+            esc_close_game: true,
+            treat_close_as_esc: true,
+            f1_help_menu: true,
+            f4_fullscreen_toggle: true,
+            f5_save_f6_load: true,
+            f9_screenshot: true,
+            priority: 0,
+            custom_load_image: None,
+            transparent: false,
+            translucency: 255,
+            loading_bar: 2,
+            backdata: Some(vec![1, 2, 3].into_boxed_slice()),
+            frontdata: None,
+            scale_progress_bar: true,
+            show_error_messages: true,
+            log_errors: false,
+            always_abort: false,
+            zero_uninitialized_vars: true,
+            error_on_uninitialized_args: false,
+            swap_creation_events: false,
+        }
+    }
+
+    fn assets() -> GameAssets {
+        let frame = Frame { width: 2, height: 1, data: vec![1, 2, 3, 255, 4, 5, 6, 0].into_boxed_slice() };
+        let collider = CollisionMap {
+            width: 2,
+            height: 1,
+            bbox_left: 0,
+            bbox_right: 0,
+            bbox_top: 0,
+            bbox_bottom: 0,
+            data: vec![true, false].into_boxed_slice(),
+        };
+        GameAssets {
+            triggers: Vec::new(),
+            constants: vec![Constant { name: "SPEED".into(), expression: "4".into() }],
+            extensions: Vec::new(),
+            sprites: vec![Some(Box::new(Sprite {
+                name: "spr_player".into(),
+                origin_x: 1,
+                origin_y: 0,
+                frames: vec![frame],
+                colliders: vec![collider],
+                per_frame_colliders: false,
+            }))],
+            sounds: vec![Some(Box::new(Sound {
+                name: "snd_jump".into(),
+                source: "jump.wav".into(),
+                extension: ".wav".into(),
+                data: Some(b"RIFF".to_vec().into_boxed_slice()),
+                kind: SoundKind::Normal,
+                volume: 0.5,
+                pan: -1.0,
+                preload: true,
+                fx: SoundFX { chorus: false, echo: true, flanger: false, gargle: false, reverb: true },
+            }))],
+            backgrounds: vec![
+                None,
+                Some(Box::new(Background {
+                    name: "bg_sky".into(),
+                    width: 1,
+                    height: 1,
+                    data: Some(vec![9, 8, 7, 255].into_boxed_slice()),
+                })),
+            ],
+            paths: vec![Some(Box::new(Path {
+                name: "path_loop".into(),
+                connection: ConnectionKind::SmoothCurve,
+                precision: 4,
+                closed: true,
+                points: vec![Point { x: 0.0, y: 1.0, speed: 100.0 }, Point { x: 32.0, y: 16.0, speed: 50.0 }],
+            }))],
+            scripts: vec![
+                Some(Box::new(Script { name: "scr_a".into(), source: "return 1".into() })),
+                None,
+                Some(Box::new(Script { name: "scr_b".into(), source: "return argument0 * 2".into() })),
+            ],
+            fonts: Vec::new(),
+            timelines: Vec::new(),
+            objects: Vec::new(),
+            rooms: Vec::new(),
+            included_files: Vec::new(),
+            version: GameVersion::GameMaker8_0,
+            dx_dll: b"not really a dll".to_vec(),
+            ico_file_raw: None,
+            help_dialog: GameHelpDialog {
+                bg_colour: 0xFFFFFF.into(),
+                new_window: true,
+                caption: "Help".into(),
+                left: -1,
+                top: -1,
+                width: 600,
+                height: 400,
+                border: true,
+                resizable: true,
+                window_on_top: false,
+                freeze_game: true,
+                info: "Press the arrow keys".into(),
+            },
+            last_instance_id: 100123,
+            last_tile_id: 10000456,
+            library_init_strings: vec!["lib_init()".into()],
+            room_order: vec![2, 0, 1],
+            settings: settings(),
+            game_id: 0xC0FFEE,
+            guid: [1, 2, 3, 4],
+        }
+    }
+
+    #[test]
+    fn gm80_round_trip() {
+        let assets = assets();
+        let exe = to_exe::<fn(&str)>(&gm80_runner(), &assets, None, false).unwrap();
+        let read = reader::from_exe(exe, None::<fn(&str)>, true, false, false).unwrap();
+
+        assert_eq!(read.version, GameVersion::GameMaker8_0);
+        assert_eq!((read.game_id, read.guid), (assets.game_id, assets.guid));
+        assert_eq!(read.dx_dll, assets.dx_dll);
+        assert_eq!((read.last_instance_id, read.last_tile_id), (100123, 10000456));
+        assert_eq!(read.room_order, assets.room_order);
+        assert_eq!(read.library_init_strings[0].0, assets.library_init_strings[0].0);
+
+        let settings = &read.settings;
+        assert!(settings.fullscreen && settings.vsync && !settings.interpolate_pixels);
+        assert_eq!((settings.scaling, settings.clear_colour, settings.loading_bar), (-1, 0x123456, 2));
+        assert_eq!(settings.backdata.as_deref(), Some(&[1, 2, 3][..]));
+        assert!(settings.frontdata.is_none());
+        assert_eq!(read.help_dialog.info.0, assets.help_dialog.info.0);
+        assert_eq!((read.help_dialog.width, read.help_dialog.height), (600, 400));
+
+        assert_eq!(read.constants.len(), 1);
+        assert_eq!((&*read.constants[0].name.0, &*read.constants[0].expression.0), (&b"SPEED"[..], &b"4"[..]));
+
+        let sprite = read.sprites[0].as_ref().unwrap();
+        assert_eq!(sprite.name.0, assets.sprites[0].as_ref().unwrap().name.0);
+        assert_eq!((sprite.origin_x, sprite.frames.len(), sprite.colliders.len()), (1, 1, 1));
+        assert_eq!(sprite.frames[0].data, assets.sprites[0].as_ref().unwrap().frames[0].data);
+        assert_eq!(&*sprite.colliders[0].data, &[true, false]);
+
+        let sound = read.sounds[0].as_ref().unwrap();
+        assert_eq!(sound.data.as_deref(), Some(&b"RIFF"[..]));
+        assert_eq!((sound.volume, sound.pan, sound.preload), (0.5, -1.0, true));
+        assert!(sound.fx.echo && sound.fx.reverb && !sound.fx.chorus);
+
+        assert!(read.backgrounds[0].is_none());
+        let background = read.backgrounds[1].as_ref().unwrap();
+        assert_eq!((background.width, background.height), (1, 1));
+        assert_eq!(background.data.as_deref(), Some(&[9, 8, 7, 255][..]));
+
+        let path = read.paths[0].as_ref().unwrap();
+        assert!(path.closed && path.connection == ConnectionKind::SmoothCurve);
+        let points = path.points.iter().map(|p| (p.x, p.y, p.speed)).collect::<Vec<_>>();
+        assert_eq!(points, vec![(0.0, 1.0, 100.0), (32.0, 16.0, 50.0)]);
+
+        let scripts = read.scripts.iter().map(|s| s.as_ref().map(|s| s.source.0.to_vec())).collect::<Vec<_>>();
+        assert_eq!(scripts, vec![Some(b"return 1".to_vec()), None, Some(b"return argument0 * 2".to_vec())]);
+    }
+
+    #[test]
+    fn wrong_runner() {
+        let mut assets = assets();
+        assets.version = GameVersion::GameMaker8_1;
+        assert!(matches!(
+            to_exe::<fn(&str)>(&gm80_runner(), &assets, None, false),
+            Err(WriterError::VersionMismatch { .. })
+        ));
+        assert!(matches!(to_exe::<fn(&str)>(&[0; 1024], &assets, None, false), Err(WriterError::UnknownRunner)));
+    }
+
+    #[test]
+    fn gm80_encrypt() {
+        let plain = (0..1000u32).map(|i| (i * 7 + i / 3) as u8).collect::<Vec<_>>();
+        let table = swap_table(12345);
+        let mut data = plain.clone();
+        gm80::encrypt(&mut data, &table);
+        assert_ne!(data, plain);
+
+        // laid out the way the reader expects: no garbage either side of the table, then the length
+        let mut block = vec![0; 8];
+        block.extend_from_slice(&table);
+        block.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        let start = block.len();
+        block.extend_from_slice(&data);
+        gm80::decrypt::<fn(&str)>(&mut io::Cursor::new(block.as_mut_slice()), None).unwrap();
+        assert_eq!(&block[start..], plain.as_slice());
+    }
+}
@@ -1,19 +1,29 @@
 use crate::{
     lexer::Lexer,
+    span::Span,
     token::{Keyword, Operator, Separator, Token},
 };
 
 use std::{
     error, fmt,
-    iter::{IntoIterator, Peekable},
+    iter::IntoIterator,
     ops::{Deref, DerefMut},
 };
 
 #[derive(Debug, PartialEq)]
 pub struct AST<'a>(Vec<Expr<'a>>);
 
+/// An expression along with where it came from in the source code.
+///
+/// Spans are ignored when comparing expressions, so the same code formatted differently gives equal trees.
+#[derive(Debug)]
+pub struct Expr<'a> {
+    pub kind: ExprKind<'a>,
+    pub span: Span,
+}
+
 #[derive(Debug, PartialEq)]
-pub enum Expr<'a> {
+pub enum ExprKind<'a> {
     LiteralIdentifier(&'a [u8]),
     LiteralReal(f64),
     LiteralString(&'a [u8]),
@@ -119,36 +129,123 @@ pub struct WhileExpr<'a> {
 
 #[derive(Debug)]
 pub struct Error {
+    pub kind: ErrorKind,
     pub message: String,
+    pub span: Span,
+
+    /// What the parser would have accepted in place of whatever caused the error, if it knows.
+    pub expected: Vec<Expected>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// A token which isn't valid where it appears.
+    UnexpectedToken,
+
+    /// The code ended partway through something.
+    UnexpectedEof,
+
+    /// An operator which isn't valid where it appears, such as `a * 1` as a statement.
+    InvalidOperator,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Expected {
+    Keyword(Keyword),
+    Operator(Operator),
+    Separator(Separator),
+
+    /// Any identifier.
+    Identifier,
+
+    /// An assignment operator such as `=` or `+=`.
+    Assignment,
+
+    /// Anything which evaluates to a value.
+    Expression,
+
+    /// Any statement, such as an assignment, function call or `if`.
+    Statement,
+}
+
+impl<'a> Expr<'a> {
+    pub fn new(kind: ExprKind<'a>, span: Span) -> Self {
+        Expr { kind, span }
+    }
+}
+
+/// Makes an expression with a default span, for building trees which weren't parsed from anything.
+impl<'a> From<ExprKind<'a>> for Expr<'a> {
+    fn from(kind: ExprKind<'a>) -> Self {
+        Expr { kind, span: Span::default() }
+    }
+}
+
+impl<'a> PartialEq for Expr<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
 }
 
 impl Error {
-    pub fn new(message: String) -> Self {
-        Error { message }
+    pub fn new(kind: ErrorKind, message: String, span: Span) -> Self {
+        Error { kind, message, span, expected: Vec::new() }
+    }
+
+    fn expecting(mut self, expected: &[Expected]) -> Self {
+        self.expected.extend_from_slice(expected);
+        self
+    }
+
+    /// Makes an error for finding the wrong token, or no token at all.
+    fn unexpected(token: Option<Token>, span: Span, context: &str, expected: &[Expected]) -> Self {
+        match token {
+            Some(t) => Error::new(ErrorKind::UnexpectedToken, format!("Unexpected token {:?} {}", t, context), span),
+            None => Error::new(ErrorKind::UnexpectedEof, format!("Found EOF unexpectedly {}", context), span),
+        }
+        .expecting(expected)
+    }
+}
+
+impl<'a> From<Token<'a>> for Expected {
+    fn from(token: Token<'a>) -> Self {
+        match token {
+            Token::Keyword(k) => Expected::Keyword(k),
+            Token::Operator(o) => Expected::Operator(o),
+            Token::Separator(s) => Expected::Separator(s),
+            Token::Identifier(_) => Expected::Identifier,
+            Token::Real(_) | Token::String(_) | Token::InvalidChar(..) => Expected::Expression,
+        }
     }
 }
 
 impl<'a> fmt::Display for Expr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.kind.fmt(f)
+    }
+}
+
+impl<'a> fmt::Display for ExprKind<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Expr::LiteralIdentifier(id) => write!(f, "{}", String::from_utf8_lossy(id)),
-            Expr::LiteralReal(r) => write!(f, "{}", r),
-            Expr::LiteralString(s) => write!(f, "\"{}\"", String::from_utf8_lossy(s)),
+            ExprKind::LiteralIdentifier(id) => write!(f, "{}", String::from_utf8_lossy(id)),
+            ExprKind::LiteralReal(r) => write!(f, "{}", r),
+            ExprKind::LiteralString(s) => write!(f, "\"{}\"", String::from_utf8_lossy(s)),
 
-            Expr::Unary(unary) => write!(f, "({} {})", unary.op, unary.child),
-            Expr::Binary(binary) => write!(f, "({} {} {})", binary.op, binary.left, binary.right),
+            ExprKind::Unary(unary) => write!(f, "({} {})", unary.op, unary.child),
+            ExprKind::Binary(binary) => write!(f, "({} {} {})", binary.op, binary.left, binary.right),
 
-            Expr::DoUntil(dountil) => write!(f, "(do {} until {})", dountil.body, dountil.cond),
-            Expr::For(for_ex) => {
+            ExprKind::DoUntil(dountil) => write!(f, "(do {} until {})", dountil.body, dountil.cond),
+            ExprKind::For(for_ex) => {
                 write!(f, "(for ({}, {}, {}) {})", for_ex.start, for_ex.cond, for_ex.step, for_ex.body)
             },
-            Expr::Function(call) => write!(
+            ExprKind::Function(call) => write!(
                 f,
                 "(@{} {})",
                 String::from_utf8_lossy(call.name),
                 call.params.iter().fold(String::new(), |acc, fnname| acc + &format!("{} ", fnname)).trim_end()
             ),
-            Expr::Group(group) => write!(
+            ExprKind::Group(group) => write!(
                 f,
                 "<{}>",
                 group
@@ -156,13 +253,13 @@ impl<'a> fmt::Display for Expr<'a> {
                     .fold(String::new(), |acc, expr| acc + &format!("{}, ", expr))
                     .trim_end_matches(|ch| ch == ' ' || ch == ',')
             ),
-            Expr::If(if_ex) => match if_ex.else_body {
+            ExprKind::If(if_ex) => match if_ex.else_body {
                 Some(ref els) => write!(f, "(if {} {} {})", if_ex.cond, if_ex.body, els),
                 None => write!(f, "(if {} {})", if_ex.cond, if_ex.body),
             },
-            Expr::Repeat(repeat) => write!(f, "(repeat {} {})", repeat.count, repeat.body),
-            Expr::Switch(switch) => write!(f, "(switch {} {})", switch.input, switch.body),
-            Expr::Var(var) => write!(
+            ExprKind::Repeat(repeat) => write!(f, "(repeat {} {})", repeat.count, repeat.body),
+            ExprKind::Switch(switch) => write!(f, "(switch {} {})", switch.input, switch.body),
+            ExprKind::Var(var) => write!(
                 f,
                 "(var {})",
                 var.vars
@@ -170,7 +267,7 @@ impl<'a> fmt::Display for Expr<'a> {
                     .fold(String::new(), |acc, varname| acc + &format!("{} ", String::from_utf8_lossy(varname)))
                     .trim_end()
             ),
-            Expr::GlobalVar(var) => write!(
+            ExprKind::GlobalVar(var) => write!(
                 f,
                 "(globalvar {})",
                 var.vars
@@ -178,16 +275,16 @@ impl<'a> fmt::Display for Expr<'a> {
                     .fold(String::new(), |acc, varname| acc + &format!("{} ", String::from_utf8_lossy(varname)))
                     .trim_end()
             ),
-            Expr::With(with) => write!(f, "(with {} {})", with.target, with.body),
-            Expr::While(while_ex) => write!(f, "(while {} {})", while_ex.cond, while_ex.body),
+            ExprKind::With(with) => write!(f, "(with {} {})", with.target, with.body),
+            ExprKind::While(while_ex) => write!(f, "(while {} {})", while_ex.cond, while_ex.body),
 
-            Expr::Case(e) => write!(f, "(case {})", e),
-            Expr::Default => write!(f, "(default)"),
+            ExprKind::Case(e) => write!(f, "(case {})", e),
+            ExprKind::Default => write!(f, "(default)"),
 
-            Expr::Continue => write!(f, "(continue)"),
-            Expr::Break => write!(f, "(break)"),
-            Expr::Exit => write!(f, "(exit)"),
-            Expr::Return(e) => write!(f, "(return {})", e),
+            ExprKind::Continue => write!(f, "(continue)"),
+            ExprKind::Break => write!(f, "(break)"),
+            ExprKind::Exit => write!(f, "(exit)"),
+            ExprKind::Return(e) => write!(f, "(return {})", e),
         }
     }
 }
@@ -195,37 +292,105 @@ impl<'a> fmt::Display for Expr<'a> {
 impl error::Error for Error {}
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.message)
+        write!(f, "{} ({})", self.message, self.span)
+    }
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expected::Keyword(k) => write!(f, "`{}`", k),
+            Expected::Operator(o) => write!(f, "`{}`", o),
+            Expected::Separator(s) => write!(f, "`{}`", s),
+            Expected::Identifier => write!(f, "identifier"),
+            Expected::Assignment => write!(f, "assignment operator"),
+            Expected::Expression => write!(f, "expression"),
+            Expected::Statement => write!(f, "statement"),
+        }
     }
 }
 
 // TODO? This is not the prettiest.
 macro_rules! expect_token {
-    ( $token: expr, $($content: tt)* ) => ({
-        match $token {
+    ( $lex: expr, $($content: tt)* ) => ({
+        match $lex.next() {
             Some(Token::$($content)*) => {},
             Some(t) => {
-                return Err(Error::new(format!(
-                    "Unexpected token {:?}; `{}` expected",
-                    t, Token::$($content)*,
-                )));
+                return Err(Error::new(
+                    ErrorKind::UnexpectedToken,
+                    format!("Unexpected token {:?}; `{}` expected", t, Token::$($content)*),
+                    $lex.span(),
+                ).expecting(&[Token::$($content)*.into()]));
             }
             None => {
-                return Err(Error::new(format!(
-                    "Unexpected EOF; `{}` expected",
-                    Token::$($content)*,
-                )));
+                return Err(Error::new(
+                    ErrorKind::UnexpectedEof,
+                    format!("Unexpected EOF; `{}` expected", Token::$($content)*),
+                    $lex.span(),
+                ).expecting(&[Token::$($content)*.into()]));
             }
         }
     });
 }
 
+/// A lexer with one token of lookahead, which keeps track of where each token came from.
+#[derive(Clone)]
+struct Tokens<'a> {
+    lex: Lexer<'a>,
+    peeked: Option<(Option<Token<'a>>, Span)>,
+    span: Span,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(source: &'a [u8]) -> Self {
+        Tokens { lex: Lexer::new(source), peeked: None, span: Span::default() }
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let (token, span) = match self.peeked.take() {
+            Some(peeked) => peeked,
+            None => (self.lex.next(), self.lex.span()),
+        };
+        self.span = span;
+        token
+    }
+
+    fn peek(&mut self) -> Option<&Token<'a>> {
+        if self.peeked.is_none() {
+            self.peeked = Some((self.lex.next(), self.lex.span()));
+        }
+        self.peeked.as_ref().and_then(|(token, _)| token.as_ref())
+    }
+
+    /// The span of the next token, without consuming it.
+    fn peek_span(&mut self) -> Span {
+        self.peek();
+        self.peeked.map(|(_, span)| span).unwrap_or(self.span)
+    }
+
+    /// The span of the last token consumed, or the end of the code if everything has been consumed.
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    /// A span from `start` up to and including the last token consumed.
+    fn since(&self, start: Span) -> Span {
+        start.to(self.span)
+    }
+}
+
 impl<'a> Default for AST<'a> {
     fn default() -> Self {
         AST(Vec::new())
     }
 }
 
+impl<'a> From<Vec<Expr<'a>>> for AST<'a> {
+    fn from(expressions: Vec<Expr<'a>>) -> Self {
+        AST(expressions)
+    }
+}
+
 impl<'a> Deref for AST<'a> {
     type Target = Vec<Expr<'a>>;
 
@@ -251,7 +416,7 @@ impl<'a> IntoIterator for AST<'a> {
 
 impl<'a> AST<'a> {
     pub fn new(source: &'a [u8]) -> Result<Self, Error> {
-        let mut lex = Lexer::new(source).peekable();
+        let mut lex = Tokens::new(source);
         let mut expressions = Vec::new();
 
         loop {
@@ -267,11 +432,15 @@ impl<'a> AST<'a> {
     }
 
     pub fn expression(source: &'a [u8]) -> Result<Expr<'a>, Error> {
-        let mut lex = Lexer::new(source).peekable();
-        if lex.peek().is_some() { AST::read_binary_tree(&mut lex, None, false) } else { Ok(Expr::LiteralReal(0.0)) }
+        let mut lex = Tokens::new(source);
+        if lex.peek().is_some() {
+            AST::read_binary_tree(&mut lex, None, false)
+        } else {
+            Ok(Expr::new(ExprKind::LiteralReal(0.0), lex.peek_span()))
+        }
     }
 
-    fn read_line(lex: &mut Peekable<Lexer<'a>>) -> Result<Option<Expr<'a>>, Error> {
+    fn read_line(lex: &mut Tokens<'a>) -> Result<Option<Expr<'a>>, Error> {
         let token = loop {
             match lex.next() {
                 Some(Token::Separator(Separator::Semicolon)) => continue,
@@ -279,11 +448,12 @@ impl<'a> AST<'a> {
                 None => return Ok(None), // EOF
             }
         };
+        let start = lex.span();
 
         // Use token type to determine what logic we should apply here
         let ret = match token {
             Token::Keyword(key) => {
-                match key {
+                let kind = match key {
                     Keyword::Var | Keyword::GlobalVar => {
                         // Read var identifiers
                         if let Some(&Token::Identifier(id)) = lex.peek() {
@@ -328,28 +498,25 @@ impl<'a> AST<'a> {
                             }
 
                             match key {
-                                Keyword::Var => Ok(Some(Expr::Var(Box::new(VarExpr { vars })))),
-                                Keyword::GlobalVar => Ok(Some(Expr::GlobalVar(Box::new(GlobalVarExpr { vars })))),
+                                Keyword::Var => ExprKind::Var(Box::new(VarExpr { vars })),
+                                Keyword::GlobalVar => ExprKind::GlobalVar(Box::new(GlobalVarExpr { vars })),
                                 _ => unreachable!(),
                             }
                         } else {
                             // This doesn't do anything in GML. We could probably make it a NOP.
                             match key {
-                                Keyword::Var => Ok(Some(Expr::Var(Box::new(VarExpr { vars: vec![] })))),
-                                Keyword::GlobalVar => {
-                                    Ok(Some(Expr::GlobalVar(Box::new(GlobalVarExpr { vars: vec![] }))))
-                                },
+                                Keyword::Var => ExprKind::Var(Box::new(VarExpr { vars: vec![] })),
+                                Keyword::GlobalVar => ExprKind::GlobalVar(Box::new(GlobalVarExpr { vars: vec![] })),
                                 _ => unreachable!(),
                             }
                         }
                     },
 
                     Keyword::Do => {
-                        let body = AST::read_group(lex)?
-                            .ok_or_else(|| Error::new("Unexpected EOF after 'do' keyword".to_string()))?;
-                        expect_token!(lex.next(), Keyword(Keyword::Until));
+                        let body = AST::read_group(lex)?.ok_or_else(|| AST::eof_error(lex, "after 'do' keyword"))?;
+                        expect_token!(lex, Keyword(Keyword::Until));
                         let cond = AST::read_binary_tree(lex, None, false)?;
-                        Ok(Some(Expr::DoUntil(Box::new(DoUntilExpr { cond, body }))))
+                        ExprKind::DoUntil(Box::new(DoUntilExpr { cond, body }))
                     },
 
                     Keyword::If => {
@@ -357,24 +524,19 @@ impl<'a> AST<'a> {
                         if lex.peek() == Some(&Token::Separator(Separator::Then)) {
                             lex.next();
                         }
-                        let body = AST::read_group(lex)?
-                            .ok_or_else(|| Error::new("Unexpected EOF after 'if' condition".to_string()))?;
+                        let body = AST::read_group(lex)?.ok_or_else(|| AST::eof_error(lex, "after 'if' condition"))?;
                         let else_body = if lex.peek() == Some(&Token::Keyword(Keyword::Else)) {
                             lex.next(); // consume 'else'
-                            Some(
-                                AST::read_group(lex)?
-                                    .ok_or_else(|| Error::new("Unexpected EOF after 'else' keyword".to_string()))?,
-                            )
+                            Some(AST::read_group(lex)?.ok_or_else(|| AST::eof_error(lex, "after 'else' keyword"))?)
                         } else {
                             None
                         };
-                        Ok(Some(Expr::If(Box::new(IfExpr { cond, body, else_body }))))
+                        ExprKind::If(Box::new(IfExpr { cond, body, else_body }))
                     },
 
                     Keyword::For => {
-                        expect_token!(lex.next(), Separator(Separator::ParenLeft));
-                        let start = AST::read_line(lex)?
-                            .ok_or_else(|| Error::new("Unexpected EOF during 'for' params".to_string()))?;
+                        expect_token!(lex, Separator(Separator::ParenLeft));
+                        let start = AST::read_line(lex)?.ok_or_else(|| AST::eof_error(lex, "during 'for' params"))?;
                         if lex.peek() == Some(&Token::Separator(Separator::Semicolon)) {
                             lex.next();
                         }
@@ -382,29 +544,27 @@ impl<'a> AST<'a> {
                         if lex.peek() == Some(&Token::Separator(Separator::Semicolon)) {
                             lex.next();
                         }
-                        let step = AST::read_line(lex)?
-                            .ok_or_else(|| Error::new("Unexpected EOF during 'for' params".to_string()))?;
+                        let step = AST::read_line(lex)?.ok_or_else(|| AST::eof_error(lex, "during 'for' params"))?;
                         while lex.peek() == Some(&Token::Separator(Separator::Semicolon)) {
                             lex.next();
                         }
-                        expect_token!(lex.next(), Separator(Separator::ParenRight));
-                        let body = AST::read_group(lex)?
-                            .ok_or_else(|| Error::new("Unexpected EOF after 'for' params".to_string()))?;
-                        Ok(Some(Expr::For(Box::new(ForExpr { start, cond, step, body }))))
+                        expect_token!(lex, Separator(Separator::ParenRight));
+                        let body = AST::read_group(lex)?.ok_or_else(|| AST::eof_error(lex, "after 'for' params"))?;
+                        ExprKind::For(Box::new(ForExpr { start, cond, step, body }))
                     },
 
                     Keyword::Repeat => {
                         let count = AST::read_binary_tree(lex, None, false)?;
-                        let body = AST::read_group(lex)?
-                            .ok_or_else(|| Error::new("Unexpected EOF after 'repeat' condition".to_string()))?;
-                        Ok(Some(Expr::Repeat(Box::new(RepeatExpr { count, body }))))
+                        let body =
+                            AST::read_group(lex)?.ok_or_else(|| AST::eof_error(lex, "after 'repeat' condition"))?;
+                        ExprKind::Repeat(Box::new(RepeatExpr { count, body }))
                     },
 
                     Keyword::Switch => {
                         let input = AST::read_binary_tree(lex, None, false)?;
-                        let body = AST::read_line(lex)?
-                            .ok_or_else(|| Error::new("Unexpected EOF after 'switch' condition".to_string()))?;
-                        Ok(Some(Expr::Switch(Box::new(SwitchExpr { input, body }))))
+                        let body =
+                            AST::read_line(lex)?.ok_or_else(|| AST::eof_error(lex, "after 'switch' condition"))?;
+                        ExprKind::Switch(Box::new(SwitchExpr { input, body }))
                     },
 
                     Keyword::With => {
@@ -412,9 +572,9 @@ impl<'a> AST<'a> {
                         if lex.peek() == Some(&Token::Keyword(Keyword::Do)) {
                             lex.next();
                         }
-                        let body = AST::read_group(lex)?
-                            .ok_or_else(|| Error::new("Unexpected EOF after 'with' condition".to_string()))?;
-                        Ok(Some(Expr::With(Box::new(WithExpr { target, body }))))
+                        let body =
+                            AST::read_group(lex)?.ok_or_else(|| AST::eof_error(lex, "after 'with' condition"))?;
+                        ExprKind::With(Box::new(WithExpr { target, body }))
                     },
 
                     Keyword::While => {
@@ -422,35 +582,43 @@ impl<'a> AST<'a> {
                         if lex.peek() == Some(&Token::Keyword(Keyword::Do)) {
                             lex.next();
                         }
-                        let body = AST::read_group(lex)?
-                            .ok_or_else(|| Error::new("Unexpected EOF after 'while' condition".to_string()))?;
-                        Ok(Some(Expr::While(Box::new(WhileExpr { cond, body }))))
+                        let body =
+                            AST::read_group(lex)?.ok_or_else(|| AST::eof_error(lex, "after 'while' condition"))?;
+                        ExprKind::While(Box::new(WhileExpr { cond, body }))
                     },
 
                     Keyword::Case => {
                         let expr = AST::read_binary_tree(lex, None, false)?;
-                        expect_token!(lex.next(), Separator(Separator::Colon));
-                        Ok(Some(Expr::Case(Box::new(expr))))
+                        expect_token!(lex, Separator(Separator::Colon));
+                        ExprKind::Case(Box::new(expr))
                     },
 
                     Keyword::Default => {
-                        expect_token!(lex.next(), Separator(Separator::Colon));
-                        Ok(Some(Expr::Default))
+                        expect_token!(lex, Separator(Separator::Colon));
+                        ExprKind::Default
                     },
 
-                    Keyword::Break => Ok(Some(Expr::Break)),
+                    Keyword::Break => ExprKind::Break,
 
-                    Keyword::Continue => Ok(Some(Expr::Continue)),
+                    Keyword::Continue => ExprKind::Continue,
 
-                    Keyword::Exit => Ok(Some(Expr::Exit)),
+                    Keyword::Exit => ExprKind::Exit,
 
                     Keyword::Return => {
                         let val = AST::read_binary_tree(lex, None, false)?;
-                        Ok(Some(Expr::Return(Box::new(val))))
+                        ExprKind::Return(Box::new(val))
                     },
 
-                    _ => return Err(Error::new(format!("Invalid Keyword at beginning of expression: {:?}", key))),
-                }
+                    _ => {
+                        return Err(Error::new(
+                            ErrorKind::UnexpectedToken,
+                            format!("Invalid Keyword at beginning of expression: {:?}", key),
+                            start,
+                        )
+                        .expecting(&[Expected::Statement]))
+                    },
+                };
+                Ok(Some(Expr::new(kind, lex.since(start))))
             },
 
             Token::Identifier(id) => {
@@ -459,7 +627,12 @@ impl<'a> AST<'a> {
                 let next_token = match lex.peek() {
                     Some(t) => t,
                     None => {
-                        return Err(Error::new(format!("Stray identifier at EOF: {:?}", String::from_utf8_lossy(id))))
+                        return Err(Error::new(
+                            ErrorKind::UnexpectedEof,
+                            format!("Stray identifier at EOF: {:?}", String::from_utf8_lossy(id)),
+                            start,
+                        )
+                        .expecting(&[Expected::Assignment, Expected::Separator(Separator::ParenLeft)]))
                     },
                 };
                 match next_token {
@@ -479,11 +652,18 @@ impl<'a> AST<'a> {
                             match lex.peek() {
                                 Some(Token::Separator(Separator::BraceRight)) => {
                                     lex.next();
-                                    break Ok(Some(Expr::Group(inner_expressions)))
+                                    break Ok(Some(Expr::new(ExprKind::Group(inner_expressions), lex.since(start))))
                                 },
                                 _ => match AST::read_line(lex) {
                                     Ok(Some(e)) => inner_expressions.push(e),
-                                    Ok(None) => break Err(Error::new("Unclosed brace at EOF".to_string())),
+                                    Ok(None) => {
+                                        break Err(Error::new(
+                                            ErrorKind::UnexpectedEof,
+                                            "Unclosed brace at EOF".to_string(),
+                                            start,
+                                        )
+                                        .expecting(&[Expected::Separator(Separator::BraceRight)]))
+                                    },
                                     Err(e) => break Err(e),
                                 },
                             }
@@ -498,11 +678,25 @@ impl<'a> AST<'a> {
                     },
 
                     // Default
-                    _ => return Err(Error::new(format!("Invalid Separator at beginning of expression: {:?}", sep))),
+                    _ => {
+                        return Err(Error::new(
+                            ErrorKind::UnexpectedToken,
+                            format!("Invalid Separator at beginning of expression: {:?}", sep),
+                            start,
+                        )
+                        .expecting(&[Expected::Statement]))
+                    },
                 }
             },
 
-            _ => return Err(Error::new(format!("Invalid token at beginning of expression: {:?}", token))),
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::UnexpectedToken,
+                    format!("Invalid token at beginning of expression: {:?}", token),
+                    start,
+                )
+                .expecting(&[Expected::Statement]))
+            },
         };
 
         // skip over trailing semicolons
@@ -513,13 +707,20 @@ impl<'a> AST<'a> {
         ret
     }
 
-    fn read_group(lex: &mut Peekable<Lexer<'a>>) -> Result<Option<Expr<'a>>, Error> {
+    /// Makes an error for reaching EOF when a statement was expected.
+    fn eof_error(lex: &Tokens<'a>, context: &str) -> Error {
+        Error::new(ErrorKind::UnexpectedEof, format!("Unexpected EOF {}", context), lex.span())
+            .expecting(&[Expected::Statement])
+    }
+
+    fn read_group(lex: &mut Tokens<'a>) -> Result<Option<Expr<'a>>, Error> {
         match lex.peek() {
             Some(Token::Separator(Separator::Semicolon)) => {
+                let start = lex.peek_span();
                 while lex.peek() == Some(&Token::Separator(Separator::Semicolon)) {
                     lex.next();
                 }
-                Ok(Some(Expr::Group(vec![])))
+                Ok(Some(Expr::new(ExprKind::Group(vec![]), lex.since(start))))
             },
             Some(_) => Self::read_line(lex),
             None => Ok(None),
@@ -527,25 +728,34 @@ impl<'a> AST<'a> {
     }
 
     fn read_binary_tree(
-        lex: &mut Peekable<Lexer<'a>>,
+        lex: &mut Tokens<'a>,
         first_token: Option<Token<'a>>, // Sometimes we've already parsed the first token, so it should be put here.
         expect_assignment: bool,        // Do we expect the first op to be an assignment?
     ) -> Result<Expr<'a>, Error> {
         let (val, op) = AST::read_binary_tree_recursive(lex, first_token, expect_assignment, 0)?;
-        if let Some(stray_op) = op {
-            Err(Error::new(format!("read_binary_tree has stray operator: {:?}", stray_op)))
+        if let Some((stray_op, span)) = op {
+            Err(Error::new(
+                ErrorKind::InvalidOperator,
+                format!("read_binary_tree has stray operator: {:?}", stray_op),
+                span,
+            ))
         } else {
             Ok(val)
         }
     }
 
     fn read_binary_tree_recursive(
-        lex: &mut Peekable<Lexer<'a>>,
+        lex: &mut Tokens<'a>,
         first_token: Option<Token<'a>>, // Sometimes we've already parsed the first token, so it should be put here.
         expect_assignment: bool,        // Do we expect the first op to be an assignment?
         lowest_prec: u8,                // We are not allowed to go below this operator precedence in this tree.
                                         // If we do, we'll return the next op.
-    ) -> Result<(Expr<'a>, Option<Operator>), Error> {
+    ) -> Result<(Expr<'a>, Option<(Operator, Span)>), Error> {
+        fn binary<'a>(op: Operator, left: Expr<'a>, right: Expr<'a>) -> Expr<'a> {
+            let span = left.span.to(right.span);
+            Expr::new(ExprKind::Binary(Box::new(BinaryExpr { op, left, right })), span)
+        }
+
         // Get the first expression before any operators
         let mut lhs = AST::read_btree_expression(lex, first_token)?;
 
@@ -559,6 +769,7 @@ impl<'a> AST<'a> {
 
                 // Consume operator
                 lex.next();
+                let mut op_span = lex.span();
 
                 // Now, loop until there are no more buffered operators.
                 loop {
@@ -568,54 +779,66 @@ impl<'a> AST<'a> {
                     if let Some(precedence) = AST::get_op_precedence(&op) {
                         // this op is invalid if an assignment is expected
                         if expect_assignment {
-                            break Err(Error::new(format!("Invalid operator {:?} found, expected assignment", op)))
+                            break Err(Error::new(
+                                ErrorKind::InvalidOperator,
+                                format!("Invalid operator {:?} found, expected assignment", op),
+                                op_span,
+                            )
+                            .expecting(&[Expected::Assignment]))
                         }
                         // If this op has lower prec than we're allowed to read, we have to return it here.
                         if precedence < lowest_prec {
-                            break Ok((lhs, Some(op)))
+                            break Ok((lhs, Some((op, op_span))))
                         }
                         // We're allowed to use the next operator. Let's read an RHS to put on after it.
                         // We limit this tree to current precedence + 1 to prevent it using operators of our
                         // current precedence.  This way, 1/2/3 is correctly built as (1/2)/3 rather than 1/(2/3).
                         let (rhs, next_op) = AST::read_binary_tree_recursive(lex, None, false, precedence + 1)?;
-                        if let Some(next_op) = next_op {
+                        if let Some((next_op, next_span)) = next_op {
                             // There's another operator even after the RHS.
                             if let Some(next_prec) = AST::get_op_precedence(&next_op) {
                                 if next_prec < lowest_prec {
                                     // This next op is lower than we're allowed to go, so we must return it
-                                    break Ok((
-                                        Expr::Binary(Box::new(BinaryExpr { op, left: lhs, right: rhs })),
-                                        Some(next_op),
-                                    ))
+                                    break Ok((binary(op, lhs, rhs), Some((next_op, next_span))))
                                 } else {
                                     // Update LHS by sticking RHS onto it,
                                     // set op to the new operator, and go round again.
-                                    lhs = Expr::Binary(Box::new(BinaryExpr { op, left: lhs, right: rhs }));
+                                    lhs = binary(op, lhs, rhs);
                                     op = next_op;
+                                    op_span = next_span;
                                 }
                             } else {
                                 // Precedence would already have been checked by the returning function.
-                                break Err(Error::new(format!(
-                                    "read_binary_tree_recursive returned invalid operator: {}",
-                                    next_op
-                                )))
+                                break Err(Error::new(
+                                    ErrorKind::InvalidOperator,
+                                    format!("read_binary_tree_recursive returned invalid operator: {}", next_op),
+                                    next_span,
+                                ))
                             }
                         } else {
                             // No more operators so let's put our lhs and rhs together.
-                            break Ok((Expr::Binary(Box::new(BinaryExpr { op, left: lhs, right: rhs })), None))
+                            break Ok((binary(op, lhs, rhs), None))
                         }
                     } else {
                         // this op is invalid if assignment not expected, OR if it's a unary operator
                         // (those have no precedence so they pass the previous test.)
                         if !expect_assignment || op == Operator::Not || op == Operator::Complement {
-                            break Err(Error::new(format!("Invalid operator {:?} found, expected evaluable", op)))
+                            break Err(Error::new(
+                                ErrorKind::InvalidOperator,
+                                format!("Invalid operator {:?} found, expected evaluable", op),
+                                op_span,
+                            ))
                         } else {
                             // No need to do precedence on an assignment, so just grab RHS and return
                             let (rhs, stray_op) = AST::read_binary_tree_recursive(lex, None, false, lowest_prec)?;
-                            break if let Some(op) = stray_op {
-                                Err(Error::new(format!("Stray operator {:?} in expression", op)))
+                            break if let Some((op, span)) = stray_op {
+                                Err(Error::new(
+                                    ErrorKind::InvalidOperator,
+                                    format!("Stray operator {:?} in expression", op),
+                                    span,
+                                ))
                             } else {
-                                Ok((Expr::Binary(Box::new(BinaryExpr { op, left: lhs, right: rhs })), None))
+                                Ok((binary(op, lhs, rhs), None))
                             }
                         }
                     }
@@ -623,7 +846,9 @@ impl<'a> AST<'a> {
             },
             _ => {
                 if expect_assignment {
-                    Err(Error::new(format!("Invalid token {:?} when expecting assignment operator", next_token)))
+                    let message = format!("Invalid token {:?} when expecting assignment operator", next_token);
+                    let kind = if next_token.is_some() { ErrorKind::UnexpectedToken } else { ErrorKind::UnexpectedEof };
+                    Err(Error::new(kind, message, lex.peek_span()).expecting(&[Expected::Assignment]))
                 } else {
                     Ok((lhs, None))
                 }
@@ -631,37 +856,55 @@ impl<'a> AST<'a> {
         }
     }
 
-    fn read_btree_expression(lex: &mut Peekable<Lexer<'a>>, first_token: Option<Token<'a>>) -> Result<Expr<'a>, Error> {
+    fn read_btree_expression(lex: &mut Tokens<'a>, first_token: Option<Token<'a>>) -> Result<Expr<'a>, Error> {
         // Get first token and match it
-        let mut lhs = match if first_token.is_some() { first_token } else { lex.next() } {
+        let token = if first_token.is_some() { first_token } else { lex.next() };
+        let start = lex.span();
+        let mut lhs = match token {
             Some(Token::Separator(ref sep)) if *sep == Separator::ParenLeft => {
-                let binary_tree = AST::read_binary_tree(lex, None, false)?;
-                if lex.next() != Some(Token::Separator(Separator::ParenRight)) {
-                    return Err(Error::new("Unclosed parenthesis in binary tree".to_string()))
-                } else {
-                    binary_tree
+                let mut binary_tree = AST::read_binary_tree(lex, None, false)?;
+                match lex.next() {
+                    Some(Token::Separator(Separator::ParenRight)) => {
+                        // The parentheses are part of the expression as far as anyone reading the code is concerned
+                        binary_tree.span = lex.since(start);
+                        binary_tree
+                    },
+                    t => {
+                        return Err(Error::unexpected(
+                            t,
+                            lex.span(),
+                            "in parenthesised expression",
+                            &[Expected::Separator(Separator::ParenRight)],
+                        ))
+                    },
                 }
             },
             Some(Token::Operator(op)) => {
                 if op == Operator::Add || op == Operator::Subtract || op == Operator::Not || op == Operator::Complement
                 {
-                    Expr::Unary(Box::new(UnaryExpr { op, child: AST::read_btree_expression(lex, None)? }))
+                    let child = AST::read_btree_expression(lex, None)?;
+                    let span = start.to(child.span);
+                    Expr::new(ExprKind::Unary(Box::new(UnaryExpr { op, child })), span)
                 } else {
-                    return Err(Error::new(format!("Invalid unary operator {:?} in expression", op)))
+                    return Err(Error::new(
+                        ErrorKind::InvalidOperator,
+                        format!("Invalid unary operator {:?} in expression", op),
+                        start,
+                    )
+                    .expecting(&[Expected::Expression]))
                 }
             },
             Some(Token::Identifier(t)) => {
                 if lex.peek() == Some(&Token::Separator(Separator::ParenLeft)) {
                     AST::read_function_call(lex, t)?
                 } else {
-                    Expr::LiteralIdentifier(t)
+                    Expr::new(ExprKind::LiteralIdentifier(t), start)
                 }
             },
 
-            Some(Token::Real(t)) => Expr::LiteralReal(t),
-            Some(Token::String(t)) => Expr::LiteralString(t),
-            Some(t) => return Err(Error::new(format!("Invalid token while scanning binary tree: {:?}", t))),
-            None => return Err(Error::new("Found EOF unexpectedly while reading binary tree".to_string())),
+            Some(Token::Real(t)) => Expr::new(ExprKind::LiteralReal(t), start),
+            Some(Token::String(t)) => Expr::new(ExprKind::LiteralString(t), start),
+            t => return Err(Error::unexpected(t, start, "while scanning binary tree", &[Expected::Expression])),
         };
 
         // Do we need to amend this LHS at all?
@@ -669,6 +912,7 @@ impl<'a> AST<'a> {
             match lex.peek() {
                 Some(Token::Separator(ref sep)) if *sep == Separator::BracketLeft => {
                     lex.next();
+                    let bracket = lex.span();
                     let mut dimensions = Vec::new();
                     if lex.peek() == Some(&Token::Separator(Separator::BracketRight)) {
                         lex.next();
@@ -684,34 +928,35 @@ impl<'a> AST<'a> {
                                         break
                                     }
                                 },
-                                Some(t) => {
-                                    return Err(Error::new(format!("Invalid token {:?}, expected expression", t)))
-                                },
-                                None => {
-                                    return Err(Error::new(
-                                        "Found EOF unexpectedly while reading array accessor".to_string(),
-                                    ))
+                                t => {
+                                    return Err(Error::unexpected(t, lex.span(), "while reading array accessor", &[
+                                        Expected::Separator(Separator::Comma),
+                                        Expected::Separator(Separator::BracketRight),
+                                    ]))
                                 },
                             }
                         }
                     }
-                    lhs = Expr::Binary(Box::new(BinaryExpr {
-                        op: Operator::Index,
-                        left: lhs,
-                        right: Expr::Group(dimensions),
-                    }));
+                    let span = lhs.span.to(lex.span());
+                    let right = Expr::new(ExprKind::Group(dimensions), lex.since(bracket));
+                    lhs = Expr::new(
+                        ExprKind::Binary(Box::new(BinaryExpr { op: Operator::Index, left: lhs, right })),
+                        span,
+                    );
                 },
 
                 Some(Token::Separator(ref sep)) if *sep == Separator::Period => {
                     lex.next();
                     lhs = match lex.next() {
-                        Some(Token::Identifier(id)) => Expr::Binary(Box::new(BinaryExpr {
-                            op: Operator::Deref,
-                            left: lhs,
-                            right: Expr::LiteralIdentifier(id),
-                        })),
-                        Some(t) => return Err(Error::new(format!("Unexpected token {:?} following deref", t))),
-                        None => return Err(Error::new("Found EOF unexpectedly while reading binary tree".to_string())),
+                        Some(Token::Identifier(id)) => {
+                            let span = lhs.span.to(lex.span());
+                            let right = Expr::new(ExprKind::LiteralIdentifier(id), lex.span());
+                            Expr::new(
+                                ExprKind::Binary(Box::new(BinaryExpr { op: Operator::Deref, left: lhs, right })),
+                                span,
+                            )
+                        },
+                        t => return Err(Error::unexpected(t, lex.span(), "following deref", &[Expected::Identifier])),
                     }
                 },
                 _ => break,
@@ -721,8 +966,9 @@ impl<'a> AST<'a> {
         Ok(lhs)
     }
 
-    fn read_function_call(lex: &mut Peekable<Lexer<'a>>, function_name: &'a [u8]) -> Result<Expr<'a>, Error> {
-        expect_token!(lex.next(), Separator(Separator::ParenLeft));
+    fn read_function_call(lex: &mut Tokens<'a>, function_name: &'a [u8]) -> Result<Expr<'a>, Error> {
+        let start = lex.span();
+        expect_token!(lex, Separator(Separator::ParenLeft));
 
         let mut params = Vec::new();
         if lex.peek() == Some(&Token::Separator(Separator::ParenRight)) {
@@ -739,15 +985,19 @@ impl<'a> AST<'a> {
                             break
                         }
                     },
-                    Some(t) => return Err(Error::new(format!("Invalid token {:?}, expected expression", t))),
-                    None => return Err(Error::new("Found EOF unexpectedly while reading function call".to_string())),
+                    t => {
+                        return Err(Error::unexpected(t, lex.span(), "while reading function call", &[
+                            Expected::Separator(Separator::Comma),
+                            Expected::Separator(Separator::ParenRight),
+                        ]))
+                    },
                 }
             }
         }
-        Ok(Expr::Function(Box::new(FunctionExpr { name: function_name, params })))
+        Ok(Expr::new(ExprKind::Function(Box::new(FunctionExpr { name: function_name, params })), lex.since(start)))
     }
 
-    fn get_op_precedence(op: &Operator) -> Option<u8> {
+    pub(crate) fn get_op_precedence(op: &Operator) -> Option<u8> {
         match op {
             Operator::Add => Some(4),
             Operator::Subtract => Some(4),
@@ -788,6 +1038,7 @@ impl<'a> AST<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::span::Span;
 
     /// Helper function for all the AST testcases.
     fn assert_ast(input: &str, expected_output: Option<Vec<Expr>>) {
@@ -812,11 +1063,11 @@ mod tests {
         assert_ast(
             // Simple assignment - Assign
             "a = 1",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::Assign,
-                left: Expr::LiteralIdentifier(b"a"),
-                right: Expr::LiteralReal(1.0),
-            }))]),
+                left: ExprKind::LiteralIdentifier(b"a").into(),
+                right: ExprKind::LiteralReal(1.0).into(),
+            })).into()]),
         )
     }
 
@@ -825,11 +1076,11 @@ mod tests {
         assert_ast(
             // Simple assignment - AssignAdd
             "b += 2",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::AssignAdd,
-                left: Expr::LiteralIdentifier(b"b"),
-                right: Expr::LiteralReal(2.0),
-            }))]),
+                left: ExprKind::LiteralIdentifier(b"b").into(),
+                right: ExprKind::LiteralReal(2.0).into(),
+            })).into()]),
         )
     }
 
@@ -838,11 +1089,11 @@ mod tests {
         assert_ast(
             // Simple assignment - AssignSubtract
             "c -= 3",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::AssignSubtract,
-                left: Expr::LiteralIdentifier(b"c"),
-                right: Expr::LiteralReal(3.0),
-            }))]),
+                left: ExprKind::LiteralIdentifier(b"c").into(),
+                right: ExprKind::LiteralReal(3.0).into(),
+            })).into()]),
         )
     }
 
@@ -851,11 +1102,11 @@ mod tests {
         assert_ast(
             // Simple assignment - AssignMultiply
             "d *= 4",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::AssignMultiply,
-                left: Expr::LiteralIdentifier(b"d"),
-                right: Expr::LiteralReal(4.0),
-            }))]),
+                left: ExprKind::LiteralIdentifier(b"d").into(),
+                right: ExprKind::LiteralReal(4.0).into(),
+            })).into()]),
         )
     }
 
@@ -864,11 +1115,11 @@ mod tests {
         assert_ast(
             // Simple assignment - AssignDivide
             "e /= 5",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::AssignDivide,
-                left: Expr::LiteralIdentifier(b"e"),
-                right: Expr::LiteralReal(5.0),
-            }))]),
+                left: ExprKind::LiteralIdentifier(b"e").into(),
+                right: ExprKind::LiteralReal(5.0).into(),
+            })).into()]),
         )
     }
 
@@ -877,11 +1128,11 @@ mod tests {
         assert_ast(
             // Simple assignment - AssignBinaryAnd
             "f &= 6",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::AssignBitwiseAnd,
-                left: Expr::LiteralIdentifier(b"f"),
-                right: Expr::LiteralReal(6.0),
-            }))]),
+                left: ExprKind::LiteralIdentifier(b"f").into(),
+                right: ExprKind::LiteralReal(6.0).into(),
+            })).into()]),
         )
     }
 
@@ -890,11 +1141,11 @@ mod tests {
         assert_ast(
             // Simple assignment - AssignBinaryOr
             "g |= 7",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::AssignBitwiseOr,
-                left: Expr::LiteralIdentifier(b"g"),
-                right: Expr::LiteralReal(7.0),
-            }))]),
+                left: ExprKind::LiteralIdentifier(b"g").into(),
+                right: ExprKind::LiteralReal(7.0).into(),
+            })).into()]),
         )
     }
 
@@ -903,11 +1154,11 @@ mod tests {
         assert_ast(
             // Simple assignment - AssignBinaryXor
             "h ^= 8",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::AssignBitwiseXor,
-                left: Expr::LiteralIdentifier(b"h"),
-                right: Expr::LiteralReal(8.0),
-            }))]),
+                left: ExprKind::LiteralIdentifier(b"h").into(),
+                right: ExprKind::LiteralReal(8.0).into(),
+            })).into()]),
         )
     }
 
@@ -938,19 +1189,19 @@ mod tests {
         assert_ast(
             // Assignment with deref and index on lhs
             "a.b[c] += d;",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::AssignAdd,
-                left: Expr::Binary(Box::new(BinaryExpr {
+                left: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::Index,
-                    left: Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::Binary(Box::new(BinaryExpr {
                         op: Operator::Deref,
-                        left: Expr::LiteralIdentifier(b"a"),
-                        right: Expr::LiteralIdentifier(b"b"),
-                    })),
-                    right: Expr::Group(vec![Expr::LiteralIdentifier(b"c")]),
-                })),
-                right: Expr::LiteralIdentifier(b"d"),
-            }))]),
+                        left: ExprKind::LiteralIdentifier(b"a").into(),
+                        right: ExprKind::LiteralIdentifier(b"b").into(),
+                    })).into(),
+                    right: ExprKind::Group(vec![ExprKind::LiteralIdentifier(b"c").into()]).into(),
+                })).into(),
+                right: ExprKind::LiteralIdentifier(b"d").into(),
+            })).into()]),
         );
     }
 
@@ -959,39 +1210,45 @@ mod tests {
         assert_ast(
             // Arbitrary chains of deref, 1- and 2-dimension index ops on both lhs and rhs
             "a.b[c].d.e[f,g]=h[i,j].k",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::Assign,
-                left: Expr::Binary(Box::new(BinaryExpr {
+                left: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::Index,
-                    left: Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::Binary(Box::new(BinaryExpr {
                         op: Operator::Deref,
-                        left: Expr::Binary(Box::new(BinaryExpr {
+                        left: ExprKind::Binary(Box::new(BinaryExpr {
                             op: Operator::Deref,
-                            left: Expr::Binary(Box::new(BinaryExpr {
+                            left: ExprKind::Binary(Box::new(BinaryExpr {
                                 op: Operator::Index,
-                                left: Expr::Binary(Box::new(BinaryExpr {
+                                left: ExprKind::Binary(Box::new(BinaryExpr {
                                     op: Operator::Deref,
-                                    left: Expr::LiteralIdentifier(b"a"),
-                                    right: Expr::LiteralIdentifier(b"b"),
-                                })),
-                                right: Expr::Group(vec![Expr::LiteralIdentifier(b"c")]),
-                            })),
-                            right: Expr::LiteralIdentifier(b"d"),
-                        })),
-                        right: Expr::LiteralIdentifier(b"e"),
-                    })),
-                    right: Expr::Group(vec![Expr::LiteralIdentifier(b"f"), Expr::LiteralIdentifier(b"g")]),
-                })),
-                right: Expr::Binary(Box::new(BinaryExpr {
+                                    left: ExprKind::LiteralIdentifier(b"a").into(),
+                                    right: ExprKind::LiteralIdentifier(b"b").into(),
+                                })).into(),
+                                right: ExprKind::Group(vec![ExprKind::LiteralIdentifier(b"c").into()]).into(),
+                            })).into(),
+                            right: ExprKind::LiteralIdentifier(b"d").into(),
+                        })).into(),
+                        right: ExprKind::LiteralIdentifier(b"e").into(),
+                    })).into(),
+                    right: ExprKind::Group(vec![
+                        ExprKind::LiteralIdentifier(b"f").into(),
+                        ExprKind::LiteralIdentifier(b"g").into(),
+                    ]).into(),
+                })).into(),
+                right: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::Deref,
-                    left: Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::Binary(Box::new(BinaryExpr {
                         op: Operator::Index,
-                        left: Expr::LiteralIdentifier(b"h"),
-                        right: Expr::Group(vec![Expr::LiteralIdentifier(b"i"), Expr::LiteralIdentifier(b"j")]),
-                    })),
-                    right: Expr::LiteralIdentifier(b"k"),
-                })),
-            }))]),
+                        left: ExprKind::LiteralIdentifier(b"h").into(),
+                        right: ExprKind::Group(vec![
+                            ExprKind::LiteralIdentifier(b"i").into(),
+                            ExprKind::LiteralIdentifier(b"j").into(),
+                        ]).into(),
+                    })).into(),
+                    right: ExprKind::LiteralIdentifier(b"k").into(),
+                })).into(),
+            })).into()]),
         );
     }
 
@@ -1000,19 +1257,19 @@ mod tests {
         assert_ast(
             // Assignment whose LHS is an expression-deref
             "(a + 1).x = 400;",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::Assign,
-                left: Expr::Binary(Box::new(BinaryExpr {
+                left: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::Deref,
-                    left: Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::Binary(Box::new(BinaryExpr {
                         op: Operator::Add,
-                        left: Expr::LiteralIdentifier(b"a"),
-                        right: Expr::LiteralReal(1.0),
-                    })),
-                    right: Expr::LiteralIdentifier(b"x"),
-                })),
-                right: Expr::LiteralReal(400.0),
-            }))]),
+                        left: ExprKind::LiteralIdentifier(b"a").into(),
+                        right: ExprKind::LiteralReal(1.0).into(),
+                    })).into(),
+                    right: ExprKind::LiteralIdentifier(b"x").into(),
+                })).into(),
+                right: ExprKind::LiteralReal(400.0).into(),
+            })).into()]),
         );
     }
 
@@ -1021,15 +1278,15 @@ mod tests {
         assert_ast(
             // Differentiation between usages of '=' - simple
             "a=b=c",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::Assign,
-                left: Expr::LiteralIdentifier(b"a"),
-                right: Expr::Binary(Box::new(BinaryExpr {
+                left: ExprKind::LiteralIdentifier(b"a").into(),
+                right: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::Equal,
-                    left: Expr::LiteralIdentifier(b"b"),
-                    right: Expr::LiteralIdentifier(b"c"),
-                })),
-            }))]),
+                    left: ExprKind::LiteralIdentifier(b"b").into(),
+                    right: ExprKind::LiteralIdentifier(b"c").into(),
+                })).into(),
+            })).into()]),
         );
     }
 
@@ -1038,39 +1295,39 @@ mod tests {
         assert_ast(
             // Differentiation between usages of '=' - complex
             "(a=b).c[d=e]=f[g=h]=i",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::Assign,
-                left: Expr::Binary(Box::new(BinaryExpr {
+                left: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::Index,
-                    left: Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::Binary(Box::new(BinaryExpr {
                         op: Operator::Deref,
-                        left: Expr::Binary(Box::new(BinaryExpr {
+                        left: ExprKind::Binary(Box::new(BinaryExpr {
                             op: Operator::Equal,
-                            left: Expr::LiteralIdentifier(b"a"),
-                            right: Expr::LiteralIdentifier(b"b"),
-                        })),
-                        right: Expr::LiteralIdentifier(b"c"),
-                    })),
-                    right: Expr::Group(vec![Expr::Binary(Box::new(BinaryExpr {
+                            left: ExprKind::LiteralIdentifier(b"a").into(),
+                            right: ExprKind::LiteralIdentifier(b"b").into(),
+                        })).into(),
+                        right: ExprKind::LiteralIdentifier(b"c").into(),
+                    })).into(),
+                    right: ExprKind::Group(vec![ExprKind::Binary(Box::new(BinaryExpr {
                         op: Operator::Equal,
-                        left: Expr::LiteralIdentifier(b"d"),
-                        right: Expr::LiteralIdentifier(b"e"),
-                    }))]),
-                })),
-                right: Expr::Binary(Box::new(BinaryExpr {
+                        left: ExprKind::LiteralIdentifier(b"d").into(),
+                        right: ExprKind::LiteralIdentifier(b"e").into(),
+                    })).into()]).into(),
+                })).into(),
+                right: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::Equal,
-                    left: Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::Binary(Box::new(BinaryExpr {
                         op: Operator::Index,
-                        left: Expr::LiteralIdentifier(b"f"),
-                        right: Expr::Group(vec![Expr::Binary(Box::new(BinaryExpr {
+                        left: ExprKind::LiteralIdentifier(b"f").into(),
+                        right: ExprKind::Group(vec![ExprKind::Binary(Box::new(BinaryExpr {
                             op: Operator::Equal,
-                            left: Expr::LiteralIdentifier(b"g"),
-                            right: Expr::LiteralIdentifier(b"h"),
-                        }))]),
-                    })),
-                    right: Expr::LiteralIdentifier(b"i"),
-                })),
-            }))]),
+                            left: ExprKind::LiteralIdentifier(b"g").into(),
+                            right: ExprKind::LiteralIdentifier(b"h").into(),
+                        })).into()]).into(),
+                    })).into(),
+                    right: ExprKind::LiteralIdentifier(b"i").into(),
+                })).into(),
+            })).into()]),
         );
     }
 
@@ -1086,11 +1343,14 @@ mod tests {
         assert_ast(
             // Binary tree format - unary operator - positive
             "a=+1",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::Assign,
-                left: Expr::LiteralIdentifier(b"a"),
-                right: Expr::Unary(Box::new(UnaryExpr { op: Operator::Add, child: Expr::LiteralReal(1.0) })),
-            }))]),
+                left: ExprKind::LiteralIdentifier(b"a").into(),
+                right: ExprKind::Unary(Box::new(UnaryExpr {
+                    op: Operator::Add,
+                    child: ExprKind::LiteralReal(1.0).into(),
+                })).into(),
+            })).into()]),
         )
     }
 
@@ -1099,11 +1359,14 @@ mod tests {
         assert_ast(
             // Binary tree format - unary operator - negative
             "a=-1",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::Assign,
-                left: Expr::LiteralIdentifier(b"a"),
-                right: Expr::Unary(Box::new(UnaryExpr { op: Operator::Subtract, child: Expr::LiteralReal(1.0) })),
-            }))]),
+                left: ExprKind::LiteralIdentifier(b"a").into(),
+                right: ExprKind::Unary(Box::new(UnaryExpr {
+                    op: Operator::Subtract,
+                    child: ExprKind::LiteralReal(1.0).into(),
+                })).into(),
+            })).into()]),
         )
     }
 
@@ -1112,11 +1375,14 @@ mod tests {
         assert_ast(
             // Binary tree format - unary operator - complement
             "a=~1",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::Assign,
-                left: Expr::LiteralIdentifier(b"a"),
-                right: Expr::Unary(Box::new(UnaryExpr { op: Operator::Complement, child: Expr::LiteralReal(1.0) })),
-            }))]),
+                left: ExprKind::LiteralIdentifier(b"a").into(),
+                right: ExprKind::Unary(Box::new(UnaryExpr {
+                    op: Operator::Complement,
+                    child: ExprKind::LiteralReal(1.0).into(),
+                })).into(),
+            })).into()]),
         )
     }
 
@@ -1125,11 +1391,14 @@ mod tests {
         assert_ast(
             // Binary tree format - unary operator - negative
             "a=!1",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::Assign,
-                left: Expr::LiteralIdentifier(b"a"),
-                right: Expr::Unary(Box::new(UnaryExpr { op: Operator::Not, child: Expr::LiteralReal(1.0) })),
-            }))]),
+                left: ExprKind::LiteralIdentifier(b"a").into(),
+                right: ExprKind::Unary(Box::new(UnaryExpr {
+                    op: Operator::Not,
+                    child: ExprKind::LiteralReal(1.0).into(),
+                })).into(),
+            })).into()]),
         )
     }
 
@@ -1138,43 +1407,46 @@ mod tests {
         assert_ast(
             // Binary tree format - unary operators - syntax parse test
             "a = 1+!~-b.c[+d]-2--3", // (- (- (+ 1 2) 3) 4)
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::Assign,
-                left: Expr::LiteralIdentifier(b"a"),
-                right: Expr::Binary(Box::new(BinaryExpr {
+                left: ExprKind::LiteralIdentifier(b"a").into(),
+                right: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::Subtract,
-                    left: Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::Binary(Box::new(BinaryExpr {
                         op: Operator::Subtract,
-                        left: Expr::Binary(Box::new(BinaryExpr {
+                        left: ExprKind::Binary(Box::new(BinaryExpr {
                             op: Operator::Add,
-                            left: Expr::LiteralReal(1.0),
-                            right: Expr::Unary(Box::new(UnaryExpr {
+                            left: ExprKind::LiteralReal(1.0).into(),
+                            right: ExprKind::Unary(Box::new(UnaryExpr {
                                 op: Operator::Not,
-                                child: Expr::Unary(Box::new(UnaryExpr {
+                                child: ExprKind::Unary(Box::new(UnaryExpr {
                                     op: Operator::Complement,
-                                    child: Expr::Unary(Box::new(UnaryExpr {
+                                    child: ExprKind::Unary(Box::new(UnaryExpr {
                                         op: Operator::Subtract,
-                                        child: Expr::Binary(Box::new(BinaryExpr {
+                                        child: ExprKind::Binary(Box::new(BinaryExpr {
                                             op: Operator::Index,
-                                            left: Expr::Binary(Box::new(BinaryExpr {
+                                            left: ExprKind::Binary(Box::new(BinaryExpr {
                                                 op: Operator::Deref,
-                                                left: Expr::LiteralIdentifier(b"b"),
-                                                right: Expr::LiteralIdentifier(b"c"),
-                                            })),
-                                            right: Expr::Group(vec![Expr::Unary(Box::new(UnaryExpr {
+                                                left: ExprKind::LiteralIdentifier(b"b").into(),
+                                                right: ExprKind::LiteralIdentifier(b"c").into(),
+                                            })).into(),
+                                            right: ExprKind::Group(vec![ExprKind::Unary(Box::new(UnaryExpr {
                                                 op: Operator::Add,
-                                                child: Expr::LiteralIdentifier(b"d"),
-                                            }))]),
-                                        })),
-                                    })),
-                                })),
-                            })),
-                        })),
-                        right: Expr::LiteralReal(2.0),
-                    })),
-                    right: Expr::Unary(Box::new(UnaryExpr { op: Operator::Subtract, child: Expr::LiteralReal(3.0) })),
-                })),
-            }))]),
+                                                child: ExprKind::LiteralIdentifier(b"d").into(),
+                                            })).into()]).into(),
+                                        })).into(),
+                                    })).into(),
+                                })).into(),
+                            })).into(),
+                        })).into(),
+                        right: ExprKind::LiteralReal(2.0).into(),
+                    })).into(),
+                    right: ExprKind::Unary(Box::new(UnaryExpr {
+                        op: Operator::Subtract,
+                        child: ExprKind::LiteralReal(3.0).into(),
+                    })).into(),
+                })).into(),
+            })).into()]),
         )
     }
 
@@ -1183,18 +1455,18 @@ mod tests {
         assert_ast(
             // Unary operator applied to sub-tree
             "a = ~(b + 1)",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::Assign,
-                left: Expr::LiteralIdentifier(b"a"),
-                right: Expr::Unary(Box::new(UnaryExpr {
+                left: ExprKind::LiteralIdentifier(b"a").into(),
+                right: ExprKind::Unary(Box::new(UnaryExpr {
                     op: Operator::Complement,
-                    child: Expr::Binary(Box::new(BinaryExpr {
+                    child: ExprKind::Binary(Box::new(BinaryExpr {
                         op: Operator::Add,
-                        left: Expr::LiteralIdentifier(b"b"),
-                        right: Expr::LiteralReal(1.0),
-                    })),
-                })),
-            }))]),
+                        left: ExprKind::LiteralIdentifier(b"b").into(),
+                        right: ExprKind::LiteralReal(1.0).into(),
+                    })).into(),
+                })).into(),
+            })).into()]),
         )
     }
 
@@ -1203,14 +1475,20 @@ mod tests {
         assert_ast(
             // Function call syntax
             "instance_create(random(800), random(608,), apple);",
-            Some(vec![Expr::Function(Box::new(FunctionExpr {
+            Some(vec![ExprKind::Function(Box::new(FunctionExpr {
                 name: b"instance_create",
                 params: vec![
-                    Expr::Function(Box::new(FunctionExpr { name: b"random", params: vec![Expr::LiteralReal(800.0)] })),
-                    Expr::Function(Box::new(FunctionExpr { name: b"random", params: vec![Expr::LiteralReal(608.0)] })),
-                    Expr::LiteralIdentifier(b"apple"),
+                    ExprKind::Function(Box::new(FunctionExpr {
+                        name: b"random",
+                        params: vec![ExprKind::LiteralReal(800.0).into()],
+                    })).into(),
+                    ExprKind::Function(Box::new(FunctionExpr {
+                        name: b"random",
+                        params: vec![ExprKind::LiteralReal(608.0).into()],
+                    })).into(),
+                    ExprKind::LiteralIdentifier(b"apple").into(),
                 ],
-            }))]),
+            })).into()]),
         )
     }
 
@@ -1219,35 +1497,35 @@ mod tests {
         assert_ast(
             // For-loop syntax - standard
             "for(i = 0; i < 10; i += 1) { a = 1; b = c;}",
-            Some(vec![Expr::For(Box::new(ForExpr {
-                start: Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::For(Box::new(ForExpr {
+                start: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::Assign,
-                    left: Expr::LiteralIdentifier(b"i"),
-                    right: Expr::LiteralReal(0.0),
-                })),
-                cond: Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::LiteralIdentifier(b"i").into(),
+                    right: ExprKind::LiteralReal(0.0).into(),
+                })).into(),
+                cond: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::LessThan,
-                    left: Expr::LiteralIdentifier(b"i"),
-                    right: Expr::LiteralReal(10.0),
-                })),
-                step: Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::LiteralIdentifier(b"i").into(),
+                    right: ExprKind::LiteralReal(10.0).into(),
+                })).into(),
+                step: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::AssignAdd,
-                    left: Expr::LiteralIdentifier(b"i"),
-                    right: Expr::LiteralReal(1.0),
-                })),
-                body: Expr::Group(vec![
-                    Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::LiteralIdentifier(b"i").into(),
+                    right: ExprKind::LiteralReal(1.0).into(),
+                })).into(),
+                body: ExprKind::Group(vec![
+                    ExprKind::Binary(Box::new(BinaryExpr {
                         op: Operator::Assign,
-                        left: Expr::LiteralIdentifier(b"a"),
-                        right: Expr::LiteralReal(1.0),
-                    })),
-                    Expr::Binary(Box::new(BinaryExpr {
+                        left: ExprKind::LiteralIdentifier(b"a").into(),
+                        right: ExprKind::LiteralReal(1.0).into(),
+                    })).into(),
+                    ExprKind::Binary(Box::new(BinaryExpr {
                         op: Operator::Assign,
-                        left: Expr::LiteralIdentifier(b"b"),
-                        right: Expr::LiteralIdentifier(b"c"),
-                    })),
-                ]),
-            }))]),
+                        left: ExprKind::LiteralIdentifier(b"b").into(),
+                        right: ExprKind::LiteralIdentifier(b"c").into(),
+                    })).into(),
+                ]).into(),
+            })).into()]),
         )
     }
 
@@ -1256,28 +1534,28 @@ mod tests {
         assert_ast(
             // For-loop syntax - no separators
             "for(i=0 i<10 i+=1) c=3",
-            Some(vec![Expr::For(Box::new(ForExpr {
-                start: Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::For(Box::new(ForExpr {
+                start: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::Assign,
-                    left: Expr::LiteralIdentifier(b"i"),
-                    right: Expr::LiteralReal(0.0),
-                })),
-                cond: Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::LiteralIdentifier(b"i").into(),
+                    right: ExprKind::LiteralReal(0.0).into(),
+                })).into(),
+                cond: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::LessThan,
-                    left: Expr::LiteralIdentifier(b"i"),
-                    right: Expr::LiteralReal(10.0),
-                })),
-                step: Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::LiteralIdentifier(b"i").into(),
+                    right: ExprKind::LiteralReal(10.0).into(),
+                })).into(),
+                step: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::AssignAdd,
-                    left: Expr::LiteralIdentifier(b"i"),
-                    right: Expr::LiteralReal(1.0),
-                })),
-                body: Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::LiteralIdentifier(b"i").into(),
+                    right: ExprKind::LiteralReal(1.0).into(),
+                })).into(),
+                body: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::Assign,
-                    left: Expr::LiteralIdentifier(b"c"),
-                    right: Expr::LiteralReal(3.0),
-                })),
-            }))]),
+                    left: ExprKind::LiteralIdentifier(b"c").into(),
+                    right: ExprKind::LiteralReal(3.0).into(),
+                })).into(),
+            })).into()]),
         )
     }
 
@@ -1286,28 +1564,28 @@ mod tests {
         assert_ast(
             // For-loop syntax - arbitrary semicolons
             "for(i=0; i<10 i+=1; ;) {d=4}",
-            Some(vec![Expr::For(Box::new(ForExpr {
-                start: Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::For(Box::new(ForExpr {
+                start: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::Assign,
-                    left: Expr::LiteralIdentifier(b"i"),
-                    right: Expr::LiteralReal(0.0),
-                })),
-                cond: Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::LiteralIdentifier(b"i").into(),
+                    right: ExprKind::LiteralReal(0.0).into(),
+                })).into(),
+                cond: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::LessThan,
-                    left: Expr::LiteralIdentifier(b"i"),
-                    right: Expr::LiteralReal(10.0),
-                })),
-                step: Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::LiteralIdentifier(b"i").into(),
+                    right: ExprKind::LiteralReal(10.0).into(),
+                })).into(),
+                step: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::AssignAdd,
-                    left: Expr::LiteralIdentifier(b"i"),
-                    right: Expr::LiteralReal(1.0),
-                })),
-                body: Expr::Group(vec![Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::LiteralIdentifier(b"i").into(),
+                    right: ExprKind::LiteralReal(1.0).into(),
+                })).into(),
+                body: ExprKind::Group(vec![ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::Assign,
-                    left: Expr::LiteralIdentifier(b"d"),
-                    right: Expr::LiteralReal(4.0),
-                }))]),
-            }))]),
+                    left: ExprKind::LiteralIdentifier(b"d").into(),
+                    right: ExprKind::LiteralReal(4.0).into(),
+                })).into()]).into(),
+            })).into()]),
         )
     }
 
@@ -1315,11 +1593,11 @@ mod tests {
     fn pascal_init_assign() {
         assert_ast(
             "a := 1",
-            Some(vec![Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::Assign,
-                left: Expr::LiteralIdentifier(b"a"),
-                right: Expr::LiteralReal(1.0),
-            }))]),
+                left: ExprKind::LiteralIdentifier(b"a").into(),
+                right: ExprKind::LiteralReal(1.0).into(),
+            })).into()]),
         );
     }
 
@@ -1336,31 +1614,31 @@ mod tests {
                 a = 4;
             end
             ",
-            Some(vec![Expr::If(Box::new(IfExpr {
-                cond: Expr::Binary(Box::new(BinaryExpr {
+            Some(vec![ExprKind::If(Box::new(IfExpr {
+                cond: ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::Equal,
-                    left: Expr::LiteralIdentifier(b"a"),
-                    right: Expr::LiteralReal(1.0),
-                })),
-                body: Expr::Group(vec![Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::LiteralIdentifier(b"a").into(),
+                    right: ExprKind::LiteralReal(1.0).into(),
+                })).into(),
+                body: ExprKind::Group(vec![ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::Assign,
-                    left: Expr::LiteralIdentifier(b"a"),
-                    right: Expr::LiteralReal(2.0),
-                }))]),
-                else_body: Some(Expr::If(Box::new(IfExpr {
-                    cond: Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::LiteralIdentifier(b"a").into(),
+                    right: ExprKind::LiteralReal(2.0).into(),
+                })).into()]).into(),
+                else_body: Some(ExprKind::If(Box::new(IfExpr {
+                    cond: ExprKind::Binary(Box::new(BinaryExpr {
                         op: Operator::Equal,
-                        left: Expr::LiteralIdentifier(b"a"),
-                        right: Expr::LiteralReal(2.0),
-                    })),
-                    body: Expr::Group(vec![Expr::Binary(Box::new(BinaryExpr {
+                        left: ExprKind::LiteralIdentifier(b"a").into(),
+                        right: ExprKind::LiteralReal(2.0).into(),
+                    })).into(),
+                    body: ExprKind::Group(vec![ExprKind::Binary(Box::new(BinaryExpr {
                         op: Operator::Assign,
-                        left: Expr::LiteralIdentifier(b"a"),
-                        right: Expr::LiteralReal(4.0),
-                    }))]),
+                        left: ExprKind::LiteralIdentifier(b"a").into(),
+                        right: ExprKind::LiteralReal(4.0).into(),
+                    })).into()]).into(),
                     else_body: None,
-                }))),
-            }))]),
+                })).into()),
+            })).into()]),
         );
     }
 
@@ -1370,8 +1648,8 @@ mod tests {
             // var syntax - basic constructions
             "var a; var b, c",
             Some(vec![
-                Expr::Var(Box::new(VarExpr { vars: vec![b"a"] })),
-                Expr::Var(Box::new(VarExpr { vars: vec![b"b", b"c"] })),
+                ExprKind::Var(Box::new(VarExpr { vars: vec![b"a"] })).into(),
+                ExprKind::Var(Box::new(VarExpr { vars: vec![b"b", b"c"] })).into(),
             ]),
         )
     }
@@ -1382,10 +1660,10 @@ mod tests {
             // var syntax - unusual valid constructions
             "var; var a,b,; var c,var",
             Some(vec![
-                Expr::Var(Box::new(VarExpr { vars: vec![] })),
-                Expr::Var(Box::new(VarExpr { vars: vec![b"a", b"b"] })),
-                Expr::Var(Box::new(VarExpr { vars: vec![b"c"] })),
-                Expr::Var(Box::new(VarExpr { vars: vec![] })),
+                ExprKind::Var(Box::new(VarExpr { vars: vec![] })).into(),
+                ExprKind::Var(Box::new(VarExpr { vars: vec![b"a", b"b"] })).into(),
+                ExprKind::Var(Box::new(VarExpr { vars: vec![b"c"] })).into(),
+                ExprKind::Var(Box::new(VarExpr { vars: vec![] })).into(),
             ]),
         )
     }
@@ -1403,8 +1681,8 @@ mod tests {
         assert_ast(
             "var a instance_create instance_destroy ()",
             Some(vec![
-                Expr::Var(Box::new(VarExpr { vars: vec![b"a", b"instance_create"] })),
-                Expr::Function(Box::new(FunctionExpr { name: b"instance_destroy", params: vec![] })),
+                ExprKind::Var(Box::new(VarExpr { vars: vec![b"a", b"instance_create"] })).into(),
+                ExprKind::Function(Box::new(FunctionExpr { name: b"instance_destroy", params: vec![] })).into(),
             ]),
         )
     }
@@ -1415,16 +1693,16 @@ mod tests {
         assert_ast(
             "var a b global.g = 0",
             Some(vec![
-                Expr::Var(Box::new(VarExpr { vars: vec![b"a", b"b"] })),
-                Expr::Binary(Box::new(BinaryExpr {
+                ExprKind::Var(Box::new(VarExpr { vars: vec![b"a", b"b"] })).into(),
+                ExprKind::Binary(Box::new(BinaryExpr {
                     op: Operator::Assign,
-                    left: Expr::Binary(Box::new(BinaryExpr {
+                    left: ExprKind::Binary(Box::new(BinaryExpr {
                         op: Operator::Deref,
-                        left: Expr::LiteralIdentifier(b"global"),
-                        right: Expr::LiteralIdentifier(b"g"),
-                    })),
-                    right: Expr::LiteralReal(0.0),
-                })),
+                        left: ExprKind::LiteralIdentifier(b"global").into(),
+                        right: ExprKind::LiteralIdentifier(b"g").into(),
+                    })).into(),
+                    right: ExprKind::LiteralReal(0.0).into(),
+                })).into(),
             ]),
         )
     }
@@ -1432,13 +1710,13 @@ mod tests {
     #[test]
     fn expression_literal_real() {
         // expression - single literal real
-        assert_eq!(AST::expression(b"1").unwrap(), Expr::LiteralReal(1.0));
+        assert_eq!(AST::expression(b"1").unwrap(), ExprKind::LiteralReal(1.0).into());
     }
 
     #[test]
     fn expression_literal_identifier() {
         // expression - literal identifier
-        assert_eq!(AST::expression(b"a").unwrap(), Expr::LiteralIdentifier(b"a"));
+        assert_eq!(AST::expression(b"a").unwrap(), ExprKind::LiteralIdentifier(b"a").into());
     }
 
     #[test]
@@ -1446,17 +1724,77 @@ mod tests {
         // expression - unary and binary operators
         assert_eq!(
             AST::expression(b"1 * -2").unwrap(),
-            Expr::Binary(Box::new(BinaryExpr {
+            ExprKind::Binary(Box::new(BinaryExpr {
                 op: Operator::Multiply,
-                left: Expr::LiteralReal(1.0),
-                right: Expr::Unary(Box::new(UnaryExpr { op: Operator::Subtract, child: Expr::LiteralReal(2.0) })),
-            }))
+                left: ExprKind::LiteralReal(1.0).into(),
+                right: ExprKind::Unary(Box::new(UnaryExpr {
+                    op: Operator::Subtract,
+                    child: ExprKind::LiteralReal(2.0).into(),
+                })).into(),
+            })).into()
         );
     }
 
     #[test]
     fn expression_with_overrun() {
         // expression with extra code after it - extra code should be dropped
-        assert_eq!(AST::expression(b"0; a=1; game_end()").unwrap(), Expr::LiteralReal(0.0));
+        assert_eq!(AST::expression(b"0; a=1; game_end()").unwrap(), ExprKind::LiteralReal(0.0).into());
+    }
+
+    #[test]
+    fn spans() {
+        // spans of statements and of the expressions inside them
+        let ast = AST::new(b"a = 1;\r\nif (b)\n    c[2].d = f(3, \"x\")").unwrap();
+        assert_eq!(ast[0].span, Span { start: 0, end: 5, line: 1, column: 1 });
+        assert_eq!(ast[1].span, Span { start: 8, end: 37, line: 2, column: 1 });
+        match &ast[1].kind {
+            ExprKind::If(if_ex) => {
+                assert_eq!(if_ex.cond.span, Span { start: 11, end: 14, line: 2, column: 4 });
+                assert_eq!(if_ex.body.span, Span { start: 19, end: 37, line: 3, column: 5 });
+                match &if_ex.body.kind {
+                    ExprKind::Binary(assign) => {
+                        assert_eq!(assign.left.span, Span { start: 19, end: 25, line: 3, column: 5 });
+                        assert_eq!(assign.right.span, Span { start: 28, end: 37, line: 3, column: 14 });
+                    },
+                    _ => panic!("expected assignment"),
+                }
+            },
+            _ => panic!("expected if statement"),
+        }
+    }
+
+    #[test]
+    fn spans_skip_comments() {
+        // comments shouldn't count as part of the next token
+        let ast = AST::new(b"// one\n/* two\n */ a = 1").unwrap();
+        assert_eq!(ast[0].span, Span { start: 18, end: 23, line: 3, column: 5 });
+    }
+
+    #[test]
+    fn error_unexpected_token() {
+        // a missing `until` should point at whatever was there instead
+        let err = AST::new(b"do {\n  a = 1\n} while b").unwrap_err();
+        assert_eq!(err.kind, ErrorKind::UnexpectedToken);
+        assert_eq!(err.span, Span { start: 15, end: 20, line: 3, column: 3 });
+        assert_eq!(err.expected, vec![Expected::Keyword(Keyword::Until)]);
+    }
+
+    #[test]
+    fn error_unexpected_eof() {
+        // running out of code should point at the end of it
+        let err = AST::new(b"a = f(1,").unwrap_err();
+        assert_eq!(err.kind, ErrorKind::UnexpectedEof);
+        assert_eq!(err.span, Span { start: 8, end: 8, line: 1, column: 9 });
+        assert_eq!(err.expected, vec![Expected::Expression]);
+    }
+
+    #[test]
+    fn error_invalid_operator() {
+        // a statement which isn't an assignment should point at its operator
+        let err = AST::new(b"a = 1\nb * 2").unwrap_err();
+        assert_eq!(err.kind, ErrorKind::InvalidOperator);
+        assert_eq!(err.span, Span { start: 8, end: 9, line: 2, column: 3 });
+        assert_eq!(err.expected, vec![Expected::Assignment]);
+        assert_eq!(err.to_string(), "Invalid operator Multiply found, expected assignment (line 2, column 3)");
     }
 }
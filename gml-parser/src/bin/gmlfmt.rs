@@ -0,0 +1,138 @@
+//! Formats GML files. Built with the `bin` feature: `cargo build -p gml-parser --features bin`.
+
+use gml_parser::{
+    cst::SyntaxTree,
+    printer::{BlockStyle, BraceStyle, Config, Printer},
+};
+use std::{
+    env, fs,
+    io::{self, Read, Write},
+    process,
+};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    assert!(!args.is_empty());
+    let process_path = args[0].as_str();
+
+    let mut opts = getopts::Options::new();
+    opts.optflag("h", "help", "print this help message")
+        .optflag("w", "write", "overwrite the files with their formatted code instead of printing it")
+        .optflag("c", "check", "only check whether the files are formatted, failing if any aren't")
+        .optopt("i", "indent", "set the number of spaces per indent, or 0 for tabs (default=4)", "N")
+        .optopt("b", "blocks", "set block style braces/begin/preserve (default=preserve)", "")
+        .optflag("n", "next-line", "put opening braces on their own line")
+        .optflag("C", "compact", "don't put spaces around operators or after commas")
+        .optflag("a", "always-braces", "wrap every if/loop body in a block");
+
+    let matches = match opts.parse(&args[1..]) {
+        Ok(matches) => matches,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        },
+    };
+
+    if matches.opt_present("h") {
+        let brief = format!(
+            "Usage: {} [options] [FILE...]\n\n\
+             Formats GML code, reading from stdin if no files are given.\n\
+             Line endings are kept as they were.",
+            process_path
+        );
+        print!("{}", opts.usage(&brief));
+        return
+    }
+
+    let mut config = Config::default();
+    match matches.opt_str("i").map(|s| s.parse::<usize>()) {
+        Some(Ok(0)) => config.indent = "\t".into(),
+        Some(Ok(n)) => config.indent = " ".repeat(n),
+        Some(Err(_)) => {
+            eprintln!("Invalid indent size");
+            process::exit(1);
+        },
+        None => (),
+    }
+    config.block_style = match matches.opt_str("b").as_deref() {
+        Some("braces") => BlockStyle::Braces,
+        Some("begin") => BlockStyle::BeginEnd,
+        Some("preserve") | None => BlockStyle::Preserve,
+        Some(s) => {
+            eprintln!("Invalid block style '{}' (valid styles are braces/begin/preserve)", s);
+            process::exit(1);
+        },
+    };
+    if matches.opt_present("n") {
+        config.brace_style = BraceStyle::NextLine;
+    }
+    config.operator_spacing = !matches.opt_present("C");
+    config.always_braces = matches.opt_present("a");
+
+    let write = matches.opt_present("w");
+    let check = matches.opt_present("c");
+    if matches.free.is_empty() {
+        let mut source = Vec::new();
+        if let Err(e) = io::stdin().read_to_end(&mut source) {
+            eprintln!("Failed to read stdin: {}", e);
+            process::exit(1);
+        }
+        match format(&source, config) {
+            Ok(formatted) if check => process::exit(if formatted == source { 0 } else { 1 }),
+            Ok(formatted) => {
+                let _ = io::stdout().write_all(&formatted);
+            },
+            Err(e) => {
+                eprintln!("<stdin>: {}", e);
+                process::exit(1);
+            },
+        }
+        return
+    }
+
+    let mut failed = false;
+    for path in &matches.free {
+        let source = match fs::read(path) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("{}: {}", path, e);
+                failed = true;
+                continue
+            },
+        };
+        match format(&source, config.clone()) {
+            Ok(formatted) if check => {
+                if formatted != source {
+                    println!("{}", path);
+                    failed = true;
+                }
+            },
+            Ok(formatted) if write => {
+                if formatted != source {
+                    if let Err(e) = fs::write(path, formatted) {
+                        eprintln!("{}: {}", path, e);
+                        failed = true;
+                    }
+                }
+            },
+            Ok(formatted) => {
+                let _ = io::stdout().write_all(&formatted);
+            },
+            Err(e) => {
+                eprintln!("{}: {}", path, e);
+                failed = true;
+            },
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+fn format(source: &[u8], mut config: Config) -> Result<Vec<u8>, gml_parser::ast::Error> {
+    if !source.contains(&b'\r') {
+        config.newline = "\n".into();
+    }
+    let tree = SyntaxTree::parse(source)?;
+    Ok(Printer::new(config).source(source).comments(tree.comments()).print(&tree.to_ast()))
+}
@@ -0,0 +1,437 @@
+//! A lossless syntax tree, which keeps every byte of the source code including whitespace and comments.
+//!
+//! Each node covers the same code as the `ast::Expr` it was built from, and holds that code's tokens and child nodes
+//! in order. Tokens keep their exact spelling, so `begin` stays `begin` and `<>` stays `<>`, along with the trivia
+//! (whitespace and comments) in front of them. Writing every token and its trivia back out in order gives exactly the
+//! code that was parsed.
+
+use crate::{
+    ast::{self, Expr, ExprKind, AST},
+    lexer::Lexer,
+    span::Span,
+    token::{Operator, Separator, Token},
+};
+use std::iter::Peekable;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TriviaKind {
+    Whitespace,
+
+    /// A comment starting with `//`, not including the newline that ends it.
+    LineComment,
+
+    /// A comment between `/*` and `*/`. The `*/` may be missing if the comment runs to the end of the code.
+    BlockComment,
+}
+
+/// Whitespace or a comment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Trivia<'a> {
+    pub kind: TriviaKind,
+    pub text: &'a [u8],
+
+    /// Byte offset of the start of the text in the source code.
+    pub start: usize,
+}
+
+/// A comment, along with where it sits relative to the code around it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Comment<'a> {
+    pub text: &'a [u8],
+
+    /// Byte offset of the start of the comment in the source code.
+    pub start: usize,
+
+    /// Whether the comment is on the same line as the token before it, as in `a = 1; // comment`.
+    pub trailing: bool,
+
+    /// Byte offset of the end of the last token before the comment, not counting semicolons.
+    /// For a comment after a statement, this is where the statement ends.
+    pub after: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntaxToken<'a> {
+    pub token: Token<'a>,
+
+    /// The token exactly as it was written.
+    pub text: &'a [u8],
+    pub span: Span,
+
+    /// Whitespace and comments between the previous token and this one.
+    pub leading: Vec<Trivia<'a>>,
+}
+
+/// The kind of expression a node was built from. Operators are kept here since `=` can mean either `Assign` or `Equal`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NodeKind {
+    /// The top of the tree, holding every statement.
+    Root,
+
+    Identifier,
+    Real,
+    String,
+
+    Unary(Operator),
+    Binary(Operator),
+
+    DoUntil,
+    For,
+    Function,
+    Group,
+    If,
+    Repeat,
+    Switch,
+    Var,
+    GlobalVar,
+    With,
+    While,
+
+    Case,
+    Default,
+
+    Continue,
+    Break,
+    Exit,
+    Return,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SyntaxElement<'a> {
+    Node(SyntaxNode<'a>),
+    Token(SyntaxToken<'a>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntaxNode<'a> {
+    pub kind: NodeKind,
+    pub span: Span,
+    pub children: Vec<SyntaxElement<'a>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntaxTree<'a> {
+    pub root: SyntaxNode<'a>,
+
+    /// Whitespace and comments after the last token.
+    pub trailing: Vec<Trivia<'a>>,
+}
+
+impl<'a> SyntaxTree<'a> {
+    /// Parses GML code into a lossless tree. Errors are the same as `AST::new` gives.
+    pub fn parse(source: &'a [u8]) -> Result<Self, ast::Error> {
+        let ast = AST::new(source)?;
+
+        let mut lex = Lexer::new(source);
+        let mut tokens = Vec::new();
+        let mut last_end = 0;
+        while let Some(token) = lex.next() {
+            let span = lex.span();
+            let leading = split_trivia(&source[last_end..span.start], last_end);
+            tokens.push(SyntaxToken { token, text: &source[span.range()], span, leading });
+            last_end = span.end;
+        }
+        let trailing = split_trivia(&source[last_end..], last_end);
+
+        let mut tokens = tokens.into_iter().peekable();
+        let mut root = build_node(NodeKind::Root, ast.iter().collect(), usize::MAX, &mut tokens);
+        root.span = Span { start: 0, end: source.len(), line: 1, column: 1 };
+        Ok(SyntaxTree { root, trailing })
+    }
+
+    /// Writes the tree back out as source code. This is exactly the code it was parsed from, unless it's been edited.
+    pub fn to_source(&self) -> Vec<u8> {
+        let mut output = Vec::new();
+        self.root.write_source(&mut output);
+        for trivia in &self.trailing {
+            output.extend_from_slice(trivia.text);
+        }
+        output
+    }
+
+    /// Converts the tree into the usual syntax tree, dropping all the trivia.
+    pub fn to_ast(&self) -> AST<'a> {
+        self.root.nodes().map(SyntaxNode::to_expr).collect::<Vec<_>>().into()
+    }
+
+    /// Returns every comment in the code, in order.
+    pub fn comments(&self) -> Vec<Comment<'a>> {
+        fn collect<'a>(leading: &[Trivia<'a>], after: Option<usize>, comments: &mut Vec<Comment<'a>>) {
+            // Anything before the first newline is on the same line as the previous token
+            // Semicolons at the very start of the code don't count as a token here
+            let mut trailing = after.is_some();
+            for trivia in leading {
+                match trivia.kind {
+                    TriviaKind::Whitespace => trailing &= !trivia.text.contains(&b'\n'),
+                    TriviaKind::LineComment | TriviaKind::BlockComment => comments.push(Comment {
+                        text: trivia.text,
+                        start: trivia.start,
+                        trailing,
+                        after: after.unwrap_or(0),
+                    }),
+                }
+            }
+        }
+
+        let mut comments = Vec::new();
+        let mut after = None;
+        self.root.for_each_token(&mut |token| {
+            collect(&token.leading, after, &mut comments);
+            if token.token != Token::Separator(Separator::Semicolon) {
+                after = Some(token.span.end);
+            }
+        });
+        collect(&self.trailing, after, &mut comments);
+        comments
+    }
+}
+
+impl<'a> SyntaxNode<'a> {
+    /// Iterates over the child nodes, skipping tokens.
+    pub fn nodes(&self) -> impl Iterator<Item = &SyntaxNode<'a>> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// Iterates over the tokens directly in this node, skipping child nodes.
+    pub fn tokens(&self) -> impl Iterator<Item = &SyntaxToken<'a>> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(_) => None,
+            SyntaxElement::Token(token) => Some(token),
+        })
+    }
+
+    /// The first token anywhere in the node, whose leading trivia are the comments just before it.
+    pub fn first_token(&self) -> Option<&SyntaxToken<'a>> {
+        self.children.iter().find_map(|child| match child {
+            SyntaxElement::Node(node) => node.first_token(),
+            SyntaxElement::Token(token) => Some(token),
+        })
+    }
+
+    /// Calls a function on every token in the node, including those in child nodes, in order.
+    pub fn for_each_token(&self, f: &mut impl FnMut(&SyntaxToken<'a>)) {
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => node.for_each_token(f),
+                SyntaxElement::Token(token) => f(token),
+            }
+        }
+    }
+
+    /// Writes the node's code, including the trivia before each of its tokens.
+    pub fn write_source(&self, output: &mut Vec<u8>) {
+        self.for_each_token(&mut |token| {
+            for trivia in &token.leading {
+                output.extend_from_slice(trivia.text);
+            }
+            output.extend_from_slice(token.text);
+        });
+    }
+
+    /// Converts the node into an expression. The root node becomes a group of all the statements.
+    ///
+    /// Panics if the node doesn't have the children its kind needs, which can only happen if it's been edited.
+    pub fn to_expr(&self) -> Expr<'a> {
+        let mut nodes = self.nodes().map(SyntaxNode::to_expr);
+        macro_rules! next {
+            () => {
+                nodes.next().expect("syntax node is missing a child node")
+            };
+        }
+        // Parenthesised literals have the parentheses as tokens too
+        let literal = || {
+            self.tokens()
+                .map(|t| t.token)
+                .find(|t| matches!(t, Token::Identifier(_) | Token::Real(_) | Token::String(_)))
+                .expect("literal node has no literal token")
+        };
+        let names = || {
+            self.tokens()
+                .filter_map(|t| match t.token {
+                    Token::Identifier(name) => Some(name),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let kind = match self.kind {
+            NodeKind::Root | NodeKind::Group => ExprKind::Group(self.nodes().map(SyntaxNode::to_expr).collect()),
+            NodeKind::Identifier | NodeKind::Real | NodeKind::String => match literal() {
+                Token::Identifier(name) => ExprKind::LiteralIdentifier(name),
+                Token::Real(real) => ExprKind::LiteralReal(real),
+                Token::String(string) => ExprKind::LiteralString(string),
+                _ => unreachable!(),
+            },
+            NodeKind::Unary(op) => ExprKind::Unary(Box::new(ast::UnaryExpr { op, child: next!() })),
+            NodeKind::Binary(op) => ExprKind::Binary(Box::new(ast::BinaryExpr { op, left: next!(), right: next!() })),
+            NodeKind::DoUntil => {
+                let body = next!();
+                ExprKind::DoUntil(Box::new(ast::DoUntilExpr { cond: next!(), body }))
+            },
+            NodeKind::For => {
+                ExprKind::For(Box::new(ast::ForExpr { start: next!(), cond: next!(), step: next!(), body: next!() }))
+            },
+            NodeKind::Function => {
+                let name = names().first().copied().expect("function node has no name");
+                ExprKind::Function(Box::new(ast::FunctionExpr { name, params: nodes.collect() }))
+            },
+            NodeKind::If => {
+                ExprKind::If(Box::new(ast::IfExpr { cond: next!(), body: next!(), else_body: nodes.next() }))
+            },
+            NodeKind::Repeat => ExprKind::Repeat(Box::new(ast::RepeatExpr { count: next!(), body: next!() })),
+            NodeKind::Switch => ExprKind::Switch(Box::new(ast::SwitchExpr { input: next!(), body: next!() })),
+            NodeKind::Var => ExprKind::Var(Box::new(ast::VarExpr { vars: names() })),
+            NodeKind::GlobalVar => ExprKind::GlobalVar(Box::new(ast::GlobalVarExpr { vars: names() })),
+            NodeKind::With => ExprKind::With(Box::new(ast::WithExpr { target: next!(), body: next!() })),
+            NodeKind::While => ExprKind::While(Box::new(ast::WhileExpr { cond: next!(), body: next!() })),
+            NodeKind::Case => ExprKind::Case(Box::new(next!())),
+            NodeKind::Default => ExprKind::Default,
+            NodeKind::Continue => ExprKind::Continue,
+            NodeKind::Break => ExprKind::Break,
+            NodeKind::Exit => ExprKind::Exit,
+            NodeKind::Return => ExprKind::Return(Box::new(next!())),
+        };
+        Expr::new(kind, self.span)
+    }
+}
+
+/// Builds a node out of an expression, taking the tokens it covers from the front of `tokens`.
+fn build_expr<'a, I>(expr: &Expr<'a>, tokens: &mut Peekable<I>) -> SyntaxNode<'a>
+where
+    I: Iterator<Item = SyntaxToken<'a>>,
+{
+    let (kind, children): (_, Vec<&Expr>) = match &expr.kind {
+        ExprKind::LiteralIdentifier(_) => (NodeKind::Identifier, vec![]),
+        ExprKind::LiteralReal(_) => (NodeKind::Real, vec![]),
+        ExprKind::LiteralString(_) => (NodeKind::String, vec![]),
+        ExprKind::Unary(unary) => (NodeKind::Unary(unary.op), vec![&unary.child]),
+        ExprKind::Binary(binary) => (NodeKind::Binary(binary.op), vec![&binary.left, &binary.right]),
+        ExprKind::DoUntil(do_until) => (NodeKind::DoUntil, vec![&do_until.body, &do_until.cond]),
+        ExprKind::For(for_ex) => (NodeKind::For, vec![&for_ex.start, &for_ex.cond, &for_ex.step, &for_ex.body]),
+        ExprKind::Function(call) => (NodeKind::Function, call.params.iter().collect()),
+        ExprKind::Group(group) => (NodeKind::Group, group.iter().collect()),
+        ExprKind::If(if_ex) => (NodeKind::If, [&if_ex.cond, &if_ex.body].into_iter().chain(&if_ex.else_body).collect()),
+        ExprKind::Repeat(repeat) => (NodeKind::Repeat, vec![&repeat.count, &repeat.body]),
+        ExprKind::Switch(switch) => (NodeKind::Switch, vec![&switch.input, &switch.body]),
+        ExprKind::Var(_) => (NodeKind::Var, vec![]),
+        ExprKind::GlobalVar(_) => (NodeKind::GlobalVar, vec![]),
+        ExprKind::With(with) => (NodeKind::With, vec![&with.target, &with.body]),
+        ExprKind::While(while_ex) => (NodeKind::While, vec![&while_ex.cond, &while_ex.body]),
+        ExprKind::Case(value) => (NodeKind::Case, vec![value.as_ref()]),
+        ExprKind::Default => (NodeKind::Default, vec![]),
+        ExprKind::Continue => (NodeKind::Continue, vec![]),
+        ExprKind::Break => (NodeKind::Break, vec![]),
+        ExprKind::Exit => (NodeKind::Exit, vec![]),
+        ExprKind::Return(value) => (NodeKind::Return, vec![value.as_ref()]),
+    };
+    let mut node = build_node(kind, children, expr.span.end, tokens);
+    node.span = expr.span;
+    node
+}
+
+/// Builds a node with the given children, taking every token before `end` which isn't in one of them.
+fn build_node<'a, I>(kind: NodeKind, children: Vec<&Expr<'a>>, end: usize, tokens: &mut Peekable<I>) -> SyntaxNode<'a>
+where
+    I: Iterator<Item = SyntaxToken<'a>>,
+{
+    fn take_tokens<'a>(
+        until: usize,
+        elements: &mut Vec<SyntaxElement<'a>>,
+        tokens: &mut Peekable<impl Iterator<Item = SyntaxToken<'a>>>,
+    ) {
+        while let Some(token) = tokens.next_if(|t| t.span.start < until) {
+            elements.push(SyntaxElement::Token(token));
+        }
+    }
+
+    let mut elements = Vec::new();
+    for child in children {
+        take_tokens(child.span.start, &mut elements, tokens);
+        elements.push(SyntaxElement::Node(build_expr(child, tokens)));
+    }
+    take_tokens(end, &mut elements, tokens);
+    SyntaxNode { kind, span: Span::default(), children: elements }
+}
+
+/// Splits the gap between two tokens into whitespace and comments.
+/// The lexer skips exactly these, so anything else can't be in a gap.
+fn split_trivia(text: &[u8], start: usize) -> Vec<Trivia<'_>> {
+    let mut trivia = Vec::new();
+    let mut pos = 0;
+    while pos < text.len() {
+        let rest = &text[pos..];
+        let (kind, len) = if rest.starts_with(b"//") {
+            (TriviaKind::LineComment, rest.iter().position(|&ch| ch == b'\n' || ch == b'\r').unwrap_or(rest.len()))
+        } else if rest.starts_with(b"/*") {
+            let end = rest[2..].windows(2).position(|w| w == b"*/").map_or(rest.len(), |i| i + 4);
+            (TriviaKind::BlockComment, end)
+        } else {
+            let end = rest.iter().position(|&ch| ch > b' ').unwrap_or(rest.len());
+            (TriviaKind::Whitespace, end.max(1))
+        };
+        trivia.push(Trivia { kind, text: &rest[..len], start: start + pos });
+        pos += len;
+    }
+    trivia
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that the tree gives back the same code and the same AST as parsing normally.
+    fn assert_lossless(input: &str) -> SyntaxTree<'_> {
+        let tree = SyntaxTree::parse(input.as_bytes()).expect("input should parse");
+        assert_eq!(String::from_utf8_lossy(&tree.to_source()), input);
+        assert_eq!(tree.to_ast(), AST::new(input.as_bytes()).unwrap());
+        tree
+    }
+
+    #[test]
+    fn lossless() {
+        assert_lossless("");
+        assert_lossless("  // nothing but a comment\r\n");
+        assert_lossless("a = 1; b := 2 /* three */ c=(4 <> 5)and not 6;;");
+        assert_lossless("if a then begin b[1, 2] = c.d end else\n\t{ e = -(f) + g(h, 'i') }");
+        assert_lossless("var a, b; globalvar c with other do x = 1 while (y) z += 2");
+        assert_lossless("do { a = 1 } until a > 2 repeat 3 exit for (i = 0; i < 10; i += 1) {break continue}");
+        assert_lossless("switch (a) { case 1: /* one */ b = 2; break; default: return 3 } // end\n/* unclosed");
+        assert_lossless("(1).x = 2 if a; else ; s = \"unclosed");
+    }
+
+    #[test]
+    fn structure() {
+        let tree = assert_lossless("if a /* why */ b = 1\r\nelse begin c() end");
+        let statement = tree.root.nodes().next().unwrap();
+        assert_eq!(statement.kind, NodeKind::If);
+        assert_eq!(statement.nodes().map(|n| n.kind).collect::<Vec<_>>(), [
+            NodeKind::Identifier,
+            NodeKind::Binary(Operator::Assign),
+            NodeKind::Group,
+        ]);
+        assert_eq!(statement.tokens().map(|t| t.text).collect::<Vec<_>>(), [&b"if"[..], b"else"]);
+
+        let group = statement.nodes().nth(2).unwrap();
+        assert_eq!(group.tokens().map(|t| t.text).collect::<Vec<_>>(), [&b"begin"[..], b"end"]);
+        let assignment = statement.nodes().nth(1).unwrap();
+        assert_eq!(assignment.first_token().unwrap().leading, [
+            Trivia { kind: TriviaKind::Whitespace, text: b" ", start: 4 },
+            Trivia { kind: TriviaKind::BlockComment, text: b"/* why */", start: 5 },
+            Trivia { kind: TriviaKind::Whitespace, text: b" ", start: 14 },
+        ]);
+    }
+
+    #[test]
+    fn comments() {
+        let tree = assert_lossless("// first\na = 1; // same line\n/* next */ b = 2 /* end */");
+        assert_eq!(tree.comments(), [
+            Comment { text: b"// first", start: 0, trailing: false, after: 0 },
+            Comment { text: b"// same line", start: 16, trailing: true, after: 14 },
+            Comment { text: b"/* next */", start: 29, trailing: false, after: 14 },
+            Comment { text: b"/* end */", start: 46, trailing: true, after: 45 },
+        ]);
+    }
+}
@@ -0,0 +1,1287 @@
+/// Every kernel function with how many arguments it takes, or None if it takes a variable number.
+///
+/// This has the same functions as the emulator's function table (gm8emulator/src/gml/mappings.rs),
+/// which has a test checking that the two agree, so new functions need adding to both.
+pub static FUNCTIONS: &[(&str, Option<usize>)] = &[
+    ("display_get_width", Some(0)),
+    ("display_get_height", Some(0)),
+    ("display_get_colordepth", Some(0)),
+    ("display_get_frequency", Some(0)),
+    ("display_set_size", Some(2)),
+    ("display_set_colordepth", Some(1)),
+    ("display_set_frequency", Some(1)),
+    ("display_set_all", Some(4)),
+    ("display_test_all", Some(4)),
+    ("display_reset", Some(0)),
+    ("display_mouse_get_x", Some(0)),
+    ("display_mouse_get_y", Some(0)),
+    ("display_mouse_set", Some(2)),
+    ("window_set_visible", Some(1)),
+    ("window_get_visible", Some(0)),
+    ("window_set_fullscreen", Some(1)),
+    ("window_get_fullscreen", Some(0)),
+    ("window_set_showborder", Some(1)),
+    ("window_get_showborder", Some(0)),
+    ("window_set_showicons", Some(1)),
+    ("window_get_showicons", Some(0)),
+    ("window_set_stayontop", Some(1)),
+    ("window_get_stayontop", Some(0)),
+    ("window_set_sizeable", Some(1)),
+    ("window_get_sizeable", Some(0)),
+    ("window_set_caption", Some(1)),
+    ("window_get_caption", Some(0)),
+    ("window_set_cursor", Some(1)),
+    ("window_get_cursor", Some(0)),
+    ("window_set_color", Some(1)),
+    ("window_get_color", Some(0)),
+    ("window_set_position", Some(2)),
+    ("window_set_size", Some(2)),
+    ("window_set_rectangle", Some(4)),
+    ("window_center", Some(0)),
+    ("window_default", Some(0)),
+    ("window_get_x", Some(0)),
+    ("window_get_y", Some(0)),
+    ("window_get_width", Some(0)),
+    ("window_get_height", Some(0)),
+    ("window_set_region_size", Some(3)),
+    ("window_get_region_width", Some(0)),
+    ("window_get_region_height", Some(0)),
+    ("window_set_region_scale", Some(2)),
+    ("window_get_region_scale", Some(0)),
+    ("window_mouse_get_x", Some(0)),
+    ("window_mouse_get_y", Some(0)),
+    ("window_mouse_set", Some(2)),
+    ("window_view_mouse_get_x", Some(1)),
+    ("window_view_mouse_get_y", Some(1)),
+    ("window_view_mouse_set", Some(3)),
+    ("window_views_mouse_get_x", Some(0)),
+    ("window_views_mouse_get_y", Some(0)),
+    ("window_views_mouse_set", Some(2)),
+    ("set_synchronization", Some(1)),
+    ("set_automatic_draw", Some(1)),
+    ("screen_redraw", Some(0)),
+    ("screen_refresh", Some(0)),
+    ("screen_wait_vsync", Some(0)),
+    ("screen_save", Some(1)),
+    ("screen_save_part", Some(5)),
+    ("draw_getpixel", Some(2)),
+    ("draw_set_color", Some(1)),
+    ("draw_set_alpha", Some(1)),
+    ("draw_get_color", Some(0)),
+    ("draw_get_alpha", Some(0)),
+    ("make_color", Some(3)),
+    ("make_color_rgb", Some(3)),
+    ("make_color_hsv", Some(3)),
+    ("color_get_red", Some(1)),
+    ("color_get_green", Some(1)),
+    ("color_get_blue", Some(1)),
+    ("color_get_hue", Some(1)),
+    ("color_get_saturation", Some(1)),
+    ("color_get_value", Some(1)),
+    ("merge_color", Some(3)),
+    ("draw_set_blend_mode", Some(1)),
+    ("draw_set_blend_mode_ext", Some(2)),
+    ("draw_clear", Some(1)),
+    ("draw_clear_alpha", Some(2)),
+    ("draw_point", Some(2)),
+    ("draw_line", Some(4)),
+    ("draw_line_width", Some(5)),
+    ("draw_rectangle", Some(5)),
+    ("draw_roundrect", Some(5)),
+    ("draw_triangle", Some(7)),
+    ("draw_circle", Some(4)),
+    ("draw_ellipse", Some(5)),
+    ("draw_arrow", Some(5)),
+    ("draw_button", Some(5)),
+    ("draw_healthbar", Some(11)),
+    ("draw_path", Some(4)),
+    ("draw_point_color", Some(3)),
+    ("draw_line_color", Some(6)),
+    ("draw_line_width_color", Some(7)),
+    ("draw_rectangle_color", Some(9)),
+    ("draw_roundrect_color", Some(7)),
+    ("draw_triangle_color", Some(10)),
+    ("draw_circle_color", Some(6)),
+    ("draw_ellipse_color", Some(7)),
+    ("draw_set_circle_precision", Some(1)),
+    ("draw_primitive_begin", Some(1)),
+    ("draw_primitive_begin_texture", Some(2)),
+    ("draw_primitive_end", Some(0)),
+    ("draw_vertex", Some(2)),
+    ("draw_vertex_color", Some(4)),
+    ("draw_vertex_texture", Some(4)),
+    ("draw_vertex_texture_color", Some(6)),
+    ("sprite_get_texture", Some(2)),
+    ("background_get_texture", Some(1)),
+    ("texture_exists", Some(1)),
+    ("texture_set_interpolation", Some(1)),
+    ("texture_set_blending", Some(1)),
+    ("texture_set_repeat", Some(1)),
+    ("texture_get_width", Some(1)),
+    ("texture_get_height", Some(1)),
+    ("texture_preload", Some(1)),
+    ("texture_set_priority", Some(2)),
+    ("draw_set_font", Some(1)),
+    ("draw_set_halign", Some(1)),
+    ("draw_set_valign", Some(1)),
+    ("string_width", Some(1)),
+    ("string_height", Some(1)),
+    ("string_width_ext", Some(3)),
+    ("string_height_ext", Some(3)),
+    ("draw_text", Some(3)),
+    ("draw_text_ext", Some(5)),
+    ("draw_text_transformed", Some(6)),
+    ("draw_text_ext_transformed", Some(8)),
+    ("draw_text_color", Some(8)),
+    ("draw_text_transformed_color", Some(11)),
+    ("draw_text_ext_color", Some(10)),
+    ("draw_text_ext_transformed_color", Some(13)),
+    ("draw_self", Some(0)),
+    ("draw_sprite", Some(4)),
+    ("draw_sprite_pos", Some(11)),
+    ("draw_sprite_ext", Some(9)),
+    ("draw_sprite_stretched", Some(6)),
+    ("draw_sprite_stretched_ext", Some(8)),
+    ("draw_sprite_part", Some(8)),
+    ("draw_sprite_part_ext", Some(12)),
+    ("draw_sprite_general", Some(16)),
+    ("draw_sprite_tiled", Some(4)),
+    ("draw_sprite_tiled_ext", Some(8)),
+    ("draw_background", Some(3)),
+    ("draw_background_ext", Some(8)),
+    ("draw_background_stretched", Some(5)),
+    ("draw_background_stretched_ext", Some(7)),
+    ("draw_background_part", Some(7)),
+    ("draw_background_part_ext", Some(11)),
+    ("draw_background_general", Some(15)),
+    ("draw_background_tiled", Some(3)),
+    ("draw_background_tiled_ext", Some(7)),
+    ("tile_get_x", Some(1)),
+    ("tile_get_y", Some(1)),
+    ("tile_get_left", Some(1)),
+    ("tile_get_top", Some(1)),
+    ("tile_get_width", Some(1)),
+    ("tile_get_height", Some(1)),
+    ("tile_get_depth", Some(1)),
+    ("tile_get_visible", Some(1)),
+    ("tile_get_xscale", Some(1)),
+    ("tile_get_yscale", Some(1)),
+    ("tile_get_blend", Some(1)),
+    ("tile_get_alpha", Some(1)),
+    ("tile_get_background", Some(1)),
+    ("tile_set_visible", Some(2)),
+    ("tile_set_background", Some(2)),
+    ("tile_set_region", Some(5)),
+    ("tile_set_position", Some(3)),
+    ("tile_set_depth", Some(2)),
+    ("tile_set_scale", Some(3)),
+    ("tile_set_blend", Some(2)),
+    ("tile_set_alpha", Some(2)),
+    ("tile_add", Some(8)),
+    ("tile_find", Some(3)),
+    ("tile_exists", Some(1)),
+    ("tile_delete", Some(1)),
+    ("tile_delete_at", Some(3)),
+    ("tile_layer_hide", Some(1)),
+    ("tile_layer_show", Some(1)),
+    ("tile_layer_delete", Some(1)),
+    ("tile_layer_shift", Some(3)),
+    ("tile_layer_find", Some(3)),
+    ("tile_layer_delete_at", Some(3)),
+    ("tile_layer_depth", Some(2)),
+    ("surface_create", Some(2)),
+    ("surface_create_ext", Some(2)),
+    ("surface_free", Some(1)),
+    ("surface_exists", Some(1)),
+    ("surface_get_width", Some(1)),
+    ("surface_get_height", Some(1)),
+    ("surface_get_texture", Some(1)),
+    ("surface_set_target", Some(1)),
+    ("surface_reset_target", Some(0)),
+    ("draw_surface", Some(3)),
+    ("draw_surface_ext", Some(8)),
+    ("draw_surface_stretched", Some(5)),
+    ("draw_surface_stretched_ext", Some(7)),
+    ("draw_surface_part", Some(7)),
+    ("draw_surface_part_ext", Some(11)),
+    ("draw_surface_general", Some(15)),
+    ("draw_surface_tiled", Some(3)),
+    ("draw_surface_tiled_ext", Some(7)),
+    ("surface_save", Some(2)),
+    ("surface_save_part", Some(6)),
+    ("surface_getpixel", Some(3)),
+    ("surface_copy", Some(4)),
+    ("surface_copy_part", Some(8)),
+    ("action_path_old", Some(3)),
+    ("action_set_sprite", Some(2)),
+    ("action_draw_font", Some(1)),
+    ("action_draw_font_old", Some(6)),
+    ("action_fill_color", Some(1)),
+    ("action_line_color", Some(1)),
+    ("action_highscore", Some(0)),
+    ("action_move", Some(2)),
+    ("action_set_motion", Some(2)),
+    ("action_set_hspeed", Some(1)),
+    ("action_set_vspeed", Some(1)),
+    ("action_set_gravity", Some(2)),
+    ("action_set_friction", Some(1)),
+    ("action_move_point", Some(3)),
+    ("action_move_to", Some(2)),
+    ("action_move_start", Some(0)),
+    ("action_move_random", Some(2)),
+    ("action_snap", Some(2)),
+    ("action_wrap", Some(1)),
+    ("action_reverse_xdir", Some(0)),
+    ("action_reverse_ydir", Some(0)),
+    ("action_move_contact", Some(3)),
+    ("action_bounce", Some(2)),
+    ("action_path", Some(4)),
+    ("action_path_end", Some(0)),
+    ("action_path_position", Some(1)),
+    ("action_path_speed", Some(1)),
+    ("action_linear_step", Some(4)),
+    ("action_potential_step", Some(4)),
+    ("action_kill_object", Some(0)),
+    ("action_create_object", Some(3)),
+    ("action_create_object_motion", Some(5)),
+    ("action_create_object_random", Some(6)),
+    ("action_change_object", Some(2)),
+    ("action_kill_position", Some(2)),
+    ("action_sprite_set", Some(3)),
+    ("action_sprite_transform", Some(4)),
+    ("action_sprite_color", Some(2)),
+    ("action_sound", Some(2)),
+    ("action_end_sound", Some(1)),
+    ("action_if_sound", Some(1)),
+    ("action_another_room", Some(2)),
+    ("action_current_room", Some(1)),
+    ("action_previous_room", Some(1)),
+    ("action_next_room", Some(1)),
+    ("action_if_previous_room", Some(0)),
+    ("action_if_next_room", Some(0)),
+    ("action_set_alarm", Some(2)),
+    ("action_sleep", Some(2)),
+    ("action_set_timeline", Some(2)),
+    ("action_timeline_set", Some(4)),
+    ("action_timeline_start", Some(0)),
+    ("action_timeline_pause", Some(0)),
+    ("action_timeline_stop", Some(0)),
+    ("action_set_timeline_position", Some(1)),
+    ("action_set_timeline_speed", Some(1)),
+    ("action_message", Some(1)),
+    ("action_show_info", Some(0)),
+    ("action_show_video", Some(3)),
+    ("action_splash_video", Some(2)),
+    ("action_splash_text", Some(1)),
+    ("action_splash_image", Some(1)),
+    ("action_splash_web", Some(2)),
+    ("action_splash_settings", Some(5)),
+    ("action_end_game", Some(0)),
+    ("action_restart_game", Some(0)),
+    ("action_save_game", Some(1)),
+    ("action_load_game", Some(1)),
+    ("action_replace_sprite", Some(3)),
+    ("action_replace_sound", Some(2)),
+    ("action_replace_background", Some(2)),
+    ("action_if_empty", Some(3)),
+    ("action_if_collision", Some(3)),
+    ("action_if", Some(1)),
+    ("action_if_number", Some(3)),
+    ("action_if_object", Some(3)),
+    ("action_if_question", Some(1)),
+    ("action_if_dice", Some(1)),
+    ("action_if_mouse", Some(1)),
+    ("action_if_aligned", Some(2)),
+    ("action_execute_script", Some(6)),
+    ("action_inherited", Some(0)),
+    ("action_if_variable", Some(3)),
+    ("action_draw_variable", Some(3)),
+    ("action_set_score", Some(1)),
+    ("action_if_score", Some(2)),
+    ("action_draw_score", Some(3)),
+    ("action_highscore_show", Some(5)),
+    ("action_highscore_clear", Some(0)),
+    ("action_set_life", Some(1)),
+    ("action_if_life", Some(2)),
+    ("action_draw_life", Some(3)),
+    ("action_draw_life_images", Some(3)),
+    ("action_set_health", Some(1)),
+    ("action_if_health", Some(2)),
+    ("action_draw_health", Some(6)),
+    ("action_set_caption", Some(6)),
+    ("action_partsyst_create", Some(1)),
+    ("action_partsyst_destroy", Some(0)),
+    ("action_partsyst_clear", Some(0)),
+    ("action_parttype_create_old", Some(6)),
+    ("action_parttype_create", Some(6)),
+    ("action_parttype_color", Some(6)),
+    ("action_parttype_life", Some(3)),
+    ("action_parttype_speed", Some(6)),
+    ("action_parttype_gravity", Some(3)),
+    ("action_parttype_secondary", Some(5)),
+    ("action_partemit_create", Some(6)),
+    ("action_partemit_destroy", Some(1)),
+    ("action_partemit_burst", Some(3)),
+    ("action_partemit_stream", Some(3)),
+    ("action_cd_play", Some(2)),
+    ("action_cd_stop", Some(0)),
+    ("action_cd_pause", Some(0)),
+    ("action_cd_resume", Some(0)),
+    ("action_cd_present", Some(0)),
+    ("action_cd_playing", Some(0)),
+    ("action_set_cursor", Some(2)),
+    ("action_webpage", Some(1)),
+    ("action_draw_sprite", Some(4)),
+    ("action_draw_background", Some(4)),
+    ("action_draw_text", Some(3)),
+    ("action_draw_text_transformed", Some(6)),
+    ("action_draw_rectangle", Some(5)),
+    ("action_draw_gradient_hor", Some(6)),
+    ("action_draw_gradient_vert", Some(6)),
+    ("action_draw_ellipse", Some(5)),
+    ("action_draw_ellipse_gradient", Some(6)),
+    ("action_draw_line", Some(4)),
+    ("action_draw_arrow", Some(5)),
+    ("action_color", Some(1)),
+    ("action_font", Some(2)),
+    ("action_fullscreen", Some(1)),
+    ("action_snapshot", Some(1)),
+    ("action_effect", Some(6)),
+    ("is_real", Some(1)),
+    ("is_string", Some(1)),
+    ("random", Some(1)),
+    ("random_range", Some(2)),
+    ("irandom", Some(1)),
+    ("irandom_range", Some(2)),
+    ("random_set_seed", Some(1)),
+    ("random_get_seed", Some(0)),
+    ("randomize", Some(0)),
+    ("abs", Some(1)),
+    ("round", Some(1)),
+    ("floor", Some(1)),
+    ("ceil", Some(1)),
+    ("sign", Some(1)),
+    ("frac", Some(1)),
+    ("sqrt", Some(1)),
+    ("sqr", Some(1)),
+    ("exp", Some(1)),
+    ("ln", Some(1)),
+    ("log2", Some(1)),
+    ("log10", Some(1)),
+    ("sin", Some(1)),
+    ("cos", Some(1)),
+    ("tan", Some(1)),
+    ("arcsin", Some(1)),
+    ("arccos", Some(1)),
+    ("arctan", Some(1)),
+    ("arctan2", Some(2)),
+    ("degtorad", Some(1)),
+    ("radtodeg", Some(1)),
+    ("power", Some(2)),
+    ("logn", Some(2)),
+    ("min", None),
+    ("max", None),
+    ("min3", Some(3)),
+    ("max3", Some(3)),
+    ("mean", None),
+    ("median", None),
+    ("choose", None),
+    ("clamp", Some(3)),
+    ("lerp", Some(3)),
+    ("real", Some(1)),
+    ("string", Some(1)),
+    ("string_format", Some(3)),
+    ("chr", Some(1)),
+    ("ansi_char", Some(1)),
+    ("ord", Some(1)),
+    ("string_length", Some(1)),
+    ("string_byte_length", Some(1)),
+    ("string_byte_at", Some(2)),
+    ("string_pos", Some(2)),
+    ("string_copy", Some(3)),
+    ("string_char_at", Some(2)),
+    ("string_delete", Some(3)),
+    ("string_insert", Some(3)),
+    ("string_lower", Some(1)),
+    ("string_upper", Some(1)),
+    ("string_repeat", Some(2)),
+    ("string_letters", Some(1)),
+    ("string_digits", Some(1)),
+    ("string_lettersdigits", Some(1)),
+    ("string_replace", Some(3)),
+    ("string_replace_all", Some(3)),
+    ("string_count", Some(2)),
+    ("dot_product", Some(4)),
+    ("dot_product_3d", Some(6)),
+    ("point_distance_3d", Some(6)),
+    ("point_distance", Some(4)),
+    ("point_direction", Some(4)),
+    ("lengthdir_x", Some(2)),
+    ("lengthdir_y", Some(2)),
+    ("move_random", Some(2)),
+    ("place_free", Some(2)),
+    ("place_empty", Some(2)),
+    ("place_meeting", Some(3)),
+    ("place_snapped", Some(2)),
+    ("move_snap", Some(2)),
+    ("move_towards_point", Some(3)),
+    ("move_contact", Some(1)),
+    ("move_contact_solid", Some(2)),
+    ("move_contact_all", Some(2)),
+    ("move_outside_solid", Some(2)),
+    ("move_outside_all", Some(2)),
+    ("move_bounce", Some(1)),
+    ("move_bounce_solid", Some(1)),
+    ("move_bounce_all", Some(1)),
+    ("move_wrap", Some(3)),
+    ("motion_set", Some(2)),
+    ("motion_add", Some(2)),
+    ("distance_to_point", Some(2)),
+    ("distance_to_object", Some(1)),
+    ("path_start", Some(4)),
+    ("path_end", Some(0)),
+    ("mp_linear_step", Some(4)),
+    ("mp_linear_path", Some(5)),
+    ("mp_linear_step_object", Some(4)),
+    ("mp_linear_path_object", Some(5)),
+    ("mp_potential_settings", Some(4)),
+    ("mp_potential_step", Some(4)),
+    ("mp_potential_path", Some(6)),
+    ("mp_potential_step_object", Some(4)),
+    ("mp_potential_path_object", Some(6)),
+    ("mp_grid_create", Some(6)),
+    ("mp_grid_destroy", Some(1)),
+    ("mp_grid_clear_all", Some(1)),
+    ("mp_grid_clear_cell", Some(3)),
+    ("mp_grid_clear_rectangle", Some(5)),
+    ("mp_grid_add_cell", Some(3)),
+    ("mp_grid_add_rectangle", Some(5)),
+    ("mp_grid_add_instances", Some(3)),
+    ("mp_grid_path", Some(7)),
+    ("mp_grid_draw", Some(1)),
+    ("collision_point", Some(5)),
+    ("collision_rectangle", Some(7)),
+    ("collision_circle", Some(6)),
+    ("collision_ellipse", Some(7)),
+    ("collision_line", Some(7)),
+    ("instance_find", Some(2)),
+    ("instance_exists", Some(1)),
+    ("instance_number", Some(1)),
+    ("instance_position", Some(3)),
+    ("instance_nearest", Some(3)),
+    ("instance_furthest", Some(3)),
+    ("instance_place", Some(3)),
+    ("instance_create", Some(3)),
+    ("instance_copy", Some(1)),
+    ("instance_change", Some(2)),
+    ("instance_destroy", Some(0)),
+    ("instance_sprite", Some(1)),
+    ("position_empty", Some(2)),
+    ("position_meeting", Some(3)),
+    ("position_destroy", Some(2)),
+    ("position_change", Some(4)),
+    ("instance_deactivate_all", Some(1)),
+    ("instance_deactivate_object", Some(1)),
+    ("instance_deactivate_region", Some(6)),
+    ("instance_activate_all", Some(0)),
+    ("instance_activate_object", Some(1)),
+    ("instance_activate_region", Some(5)),
+    ("room_goto", Some(1)),
+    ("room_goto_previous", Some(0)),
+    ("room_goto_next", Some(0)),
+    ("room_previous", Some(1)),
+    ("room_next", Some(1)),
+    ("room_restart", Some(0)),
+    ("game_end", Some(0)),
+    ("game_restart", Some(0)),
+    ("game_load", Some(1)),
+    ("game_save", Some(1)),
+    ("transition_define", Some(2)),
+    ("transition_exists", Some(1)),
+    ("sleep", Some(1)),
+    ("YoYo_GetPlatform", Some(0)),
+    ("YoYo_GetDevice", Some(0)),
+    ("YoYo_OpenURL", Some(1)),
+    ("YoYo_OpenURL_ext", Some(2)),
+    ("YoYo_OpenURL_full", Some(3)),
+    ("YoYo_GetDomain", Some(0)),
+    ("YoYo_GetTimer", Some(0)),
+    ("YoYo_AddVirtualKey", Some(5)),
+    ("YoYo_DeleteVirtualKey", Some(1)),
+    ("YoYo_ShowVirtualKey", Some(1)),
+    ("YoYo_HideVirtualKey", Some(1)),
+    ("YoYo_EnableAlphaBlend", Some(1)),
+    ("file_bin_open", Some(2)),
+    ("file_bin_rewrite", Some(1)),
+    ("file_bin_close", Some(1)),
+    ("file_bin_position", Some(1)),
+    ("file_bin_size", Some(1)),
+    ("file_bin_seek", Some(2)),
+    ("file_bin_read_byte", Some(1)),
+    ("file_bin_write_byte", Some(2)),
+    ("file_text_open_read", Some(1)),
+    ("file_text_open_write", Some(1)),
+    ("file_text_open_append", Some(1)),
+    ("file_text_close", Some(1)),
+    ("file_text_read_string", Some(1)),
+    ("file_text_read_real", Some(1)),
+    ("file_text_readln", Some(1)),
+    ("file_text_eof", Some(1)),
+    ("file_text_eoln", Some(1)),
+    ("file_text_write_string", Some(2)),
+    ("file_text_write_real", Some(2)),
+    ("file_text_writeln", Some(1)),
+    ("file_open_read", Some(1)),
+    ("file_open_write", Some(1)),
+    ("file_open_append", Some(1)),
+    ("file_close", Some(0)),
+    ("file_read_string", Some(0)),
+    ("file_read_real", Some(0)),
+    ("file_readln", Some(0)),
+    ("file_eof", Some(0)),
+    ("file_eoln", Some(0)),
+    ("file_write_string", Some(1)),
+    ("file_write_real", Some(1)),
+    ("file_writeln", Some(0)),
+    ("file_exists", Some(1)),
+    ("file_delete", Some(1)),
+    ("file_rename", Some(2)),
+    ("file_copy", Some(2)),
+    ("directory_exists", Some(1)),
+    ("directory_create", Some(1)),
+    ("file_find_first", Some(2)),
+    ("file_find_next", Some(0)),
+    ("file_find_close", Some(0)),
+    ("file_attributes", Some(2)),
+    ("filename_name", Some(1)),
+    ("filename_path", Some(1)),
+    ("filename_dir", Some(1)),
+    ("filename_drive", Some(1)),
+    ("filename_ext", Some(1)),
+    ("filename_change_ext", Some(2)),
+    ("export_include_file", Some(1)),
+    ("export_include_file_location", Some(2)),
+    ("discard_include_file", Some(1)),
+    ("execute_program", Some(3)),
+    ("execute_shell", Some(2)),
+    ("parameter_count", Some(0)),
+    ("parameter_string", Some(1)),
+    ("environment_get_variable", Some(1)),
+    ("registry_write_string", Some(2)),
+    ("registry_write_real", Some(2)),
+    ("registry_read_string", Some(1)),
+    ("registry_read_real", Some(1)),
+    ("registry_exists", Some(1)),
+    ("registry_write_string_ext", Some(3)),
+    ("registry_write_real_ext", Some(3)),
+    ("registry_read_string_ext", Some(2)),
+    ("registry_read_real_ext", Some(2)),
+    ("registry_exists_ext", Some(2)),
+    ("registry_set_root", Some(1)),
+    ("ini_open", Some(1)),
+    ("ini_close", Some(0)),
+    ("ini_read_string", Some(3)),
+    ("ini_read_real", Some(3)),
+    ("ini_write_string", Some(3)),
+    ("ini_write_real", Some(3)),
+    ("ini_key_exists", Some(2)),
+    ("ini_section_exists", Some(1)),
+    ("ini_key_delete", Some(2)),
+    ("ini_section_delete", Some(1)),
+    ("disk_free", None),
+    ("disk_size", None),
+    ("splash_set_caption", Some(1)),
+    ("splash_set_fullscreen", Some(1)),
+    ("splash_set_border", Some(1)),
+    ("splash_set_size", Some(2)),
+    ("splash_set_position", Some(2)),
+    ("splash_set_adapt", Some(1)),
+    ("splash_set_top", Some(1)),
+    ("splash_set_color", Some(1)),
+    ("splash_set_main", Some(1)),
+    ("splash_set_scale", Some(1)),
+    ("splash_set_cursor", Some(1)),
+    ("splash_set_interrupt", Some(1)),
+    ("splash_set_stop_key", Some(1)),
+    ("splash_set_close_button", Some(1)),
+    ("splash_set_stop_mouse", Some(1)),
+    ("splash_show_video", Some(2)),
+    ("splash_show_image", Some(2)),
+    ("splash_show_text", Some(2)),
+    ("splash_show_web", Some(2)),
+    ("show_image", Some(3)),
+    ("show_video", Some(3)),
+    ("show_text", Some(4)),
+    ("show_message", Some(1)),
+    ("show_question", Some(1)),
+    ("show_error", Some(2)),
+    ("show_info", Some(0)),
+    ("load_info", Some(1)),
+    ("highscore_show", Some(1)),
+    ("highscore_set_background", Some(1)),
+    ("highscore_set_border", Some(1)),
+    ("highscore_set_font", Some(3)),
+    ("highscore_set_strings", Some(3)),
+    ("highscore_set_colors", Some(3)),
+    ("highscore_show_ext", Some(7)),
+    ("highscore_clear", Some(0)),
+    ("highscore_add", Some(2)),
+    ("highscore_add_current", Some(0)),
+    ("highscore_value", Some(1)),
+    ("highscore_name", Some(1)),
+    ("draw_highscore", Some(4)),
+    ("show_message_ext", Some(4)),
+    ("message_background", Some(1)),
+    ("message_button", Some(1)),
+    ("message_alpha", Some(1)),
+    ("message_text_font", Some(4)),
+    ("message_button_font", Some(4)),
+    ("message_input_font", Some(4)),
+    ("message_text_charset", Some(2)),
+    ("message_mouse_color", Some(1)),
+    ("message_input_color", Some(1)),
+    ("message_position", Some(2)),
+    ("message_size", Some(2)),
+    ("message_caption", Some(2)),
+    ("show_menu", Some(2)),
+    ("show_menu_pos", Some(4)),
+    ("get_integer", Some(2)),
+    ("get_string", Some(2)),
+    ("get_color", Some(1)),
+    ("get_open_filename", Some(2)),
+    ("get_save_filename", Some(2)),
+    ("get_directory", Some(1)),
+    ("get_directory_alt", Some(2)),
+    ("keyboard_get_numlock", Some(0)),
+    ("keyboard_set_numlock", Some(1)),
+    ("keyboard_key_press", Some(1)),
+    ("keyboard_key_release", Some(1)),
+    ("keyboard_set_map", Some(2)),
+    ("keyboard_get_map", Some(1)),
+    ("keyboard_unset_map", Some(0)),
+    ("keyboard_check", Some(1)),
+    ("keyboard_check_pressed", Some(1)),
+    ("keyboard_check_released", Some(1)),
+    ("keyboard_check_direct", Some(1)),
+    ("mouse_check_button", Some(1)),
+    ("mouse_check_button_pressed", Some(1)),
+    ("mouse_check_button_released", Some(1)),
+    ("mouse_wheel_up", Some(0)),
+    ("mouse_wheel_down", Some(0)),
+    ("joystick_exists", Some(1)),
+    ("joystick_direction", Some(1)),
+    ("joystick_name", Some(1)),
+    ("joystick_axes", Some(1)),
+    ("joystick_buttons", Some(1)),
+    ("joystick_has_pov", Some(1)),
+    ("joystick_check_button", Some(2)),
+    ("joystick_xpos", Some(1)),
+    ("joystick_ypos", Some(1)),
+    ("joystick_zpos", Some(1)),
+    ("joystick_rpos", Some(1)),
+    ("joystick_upos", Some(1)),
+    ("joystick_vpos", Some(1)),
+    ("joystick_pov", Some(1)),
+    ("keyboard_clear", Some(1)),
+    ("mouse_clear", Some(1)),
+    ("io_clear", Some(0)),
+    ("io_handle", Some(0)),
+    ("keyboard_wait", Some(0)),
+    ("mouse_wait", Some(0)),
+    ("mplay_init_ipx", Some(0)),
+    ("mplay_init_tcpip", Some(1)),
+    ("mplay_init_modem", Some(2)),
+    ("mplay_init_serial", Some(5)),
+    ("mplay_connect_status", Some(0)),
+    ("mplay_end", Some(0)),
+    ("mplay_session_mode", Some(1)),
+    ("mplay_session_create", Some(3)),
+    ("mplay_session_find", Some(0)),
+    ("mplay_session_name", Some(1)),
+    ("mplay_session_join", Some(2)),
+    ("mplay_session_status", Some(0)),
+    ("mplay_session_end", Some(0)),
+    ("mplay_player_find", Some(0)),
+    ("mplay_player_name", Some(1)),
+    ("mplay_player_id", Some(1)),
+    ("mplay_data_write", Some(2)),
+    ("mplay_data_read", Some(1)),
+    ("mplay_data_mode", Some(1)),
+    ("mplay_message_send", Some(3)),
+    ("mplay_message_send_guaranteed", Some(3)),
+    ("mplay_message_receive", Some(1)),
+    ("mplay_message_id", Some(0)),
+    ("mplay_message_value", Some(0)),
+    ("mplay_message_player", Some(0)),
+    ("mplay_message_name", Some(0)),
+    ("mplay_message_count", Some(1)),
+    ("mplay_message_clear", Some(1)),
+    ("mplay_ipaddress", Some(0)),
+    ("event_inherited", Some(0)),
+    ("event_perform", Some(2)),
+    ("event_user", Some(1)),
+    ("event_perform_object", Some(3)),
+    ("external_define", None),
+    ("external_call", None),
+    ("external_free", Some(1)),
+    ("get_function_address", Some(1)),
+    ("external_define0", Some(3)),
+    ("external_call0", Some(1)),
+    ("external_define1", Some(4)),
+    ("external_call1", Some(2)),
+    ("external_define2", Some(5)),
+    ("external_call2", Some(3)),
+    ("external_define3", Some(6)),
+    ("external_call3", Some(4)),
+    ("external_define4", Some(7)),
+    ("external_call4", Some(5)),
+    ("external_define5", Some(3)),
+    ("external_call5", Some(6)),
+    ("external_define6", Some(3)),
+    ("external_call6", Some(7)),
+    ("external_define7", Some(3)),
+    ("external_call7", Some(8)),
+    ("external_define8", Some(3)),
+    ("external_call8", Some(9)),
+    ("execute_string", None),
+    ("execute_file", None),
+    ("window_handle", Some(0)),
+    ("show_debug_message", Some(1)),
+    ("set_program_priority", Some(1)),
+    ("set_application_title", Some(1)),
+    ("variable_global_exists", Some(1)),
+    ("variable_global_get", Some(1)),
+    ("variable_global_array_get", Some(2)),
+    ("variable_global_array2_get", Some(3)),
+    ("variable_global_set", Some(2)),
+    ("variable_global_array_set", Some(3)),
+    ("variable_global_array2_set", Some(4)),
+    ("variable_local_exists", Some(1)),
+    ("variable_local_get", Some(1)),
+    ("variable_local_array_get", Some(2)),
+    ("variable_local_array2_get", Some(3)),
+    ("variable_local_set", Some(2)),
+    ("variable_local_array_set", Some(3)),
+    ("variable_local_array2_set", Some(4)),
+    ("clipboard_has_text", Some(0)),
+    ("clipboard_set_text", Some(1)),
+    ("clipboard_get_text", Some(0)),
+    ("date_current_datetime", Some(0)),
+    ("date_current_date", Some(0)),
+    ("date_current_time", Some(0)),
+    ("date_create_datetime", Some(6)),
+    ("date_create_date", Some(3)),
+    ("date_create_time", Some(3)),
+    ("date_valid_datetime", Some(6)),
+    ("date_valid_date", Some(3)),
+    ("date_valid_time", Some(3)),
+    ("date_inc_year", Some(2)),
+    ("date_inc_month", Some(2)),
+    ("date_inc_week", Some(2)),
+    ("date_inc_day", Some(2)),
+    ("date_inc_hour", Some(2)),
+    ("date_inc_minute", Some(2)),
+    ("date_inc_second", Some(2)),
+    ("date_get_year", Some(1)),
+    ("date_get_month", Some(1)),
+    ("date_get_week", Some(1)),
+    ("date_get_day", Some(1)),
+    ("date_get_hour", Some(1)),
+    ("date_get_minute", Some(1)),
+    ("date_get_second", Some(1)),
+    ("date_get_weekday", Some(1)),
+    ("date_get_day_of_year", Some(1)),
+    ("date_get_hour_of_year", Some(1)),
+    ("date_get_minute_of_year", Some(1)),
+    ("date_get_second_of_year", Some(1)),
+    ("date_year_span", Some(2)),
+    ("date_month_span", Some(2)),
+    ("date_week_span", Some(2)),
+    ("date_day_span", Some(2)),
+    ("date_hour_span", Some(2)),
+    ("date_minute_span", Some(2)),
+    ("date_second_span", Some(2)),
+    ("date_compare_datetime", Some(2)),
+    ("date_compare_date", Some(2)),
+    ("date_compare_time", Some(2)),
+    ("date_date_of", Some(1)),
+    ("date_time_of", Some(1)),
+    ("date_datetime_string", Some(1)),
+    ("date_date_string", Some(1)),
+    ("date_time_string", Some(1)),
+    ("date_days_in_month", Some(1)),
+    ("date_days_in_year", Some(1)),
+    ("date_leap_year", Some(1)),
+    ("date_is_today", Some(1)),
+    ("sprite_name", Some(1)),
+    ("sprite_exists", Some(1)),
+    ("sprite_get_name", Some(1)),
+    ("sprite_get_number", Some(1)),
+    ("sprite_get_width", Some(1)),
+    ("sprite_get_height", Some(1)),
+    ("sprite_get_xoffset", Some(1)),
+    ("sprite_get_yoffset", Some(1)),
+    ("sprite_get_bbox_left", Some(1)),
+    ("sprite_get_bbox_right", Some(1)),
+    ("sprite_get_bbox_top", Some(1)),
+    ("sprite_get_bbox_bottom", Some(1)),
+    ("sprite_set_offset", Some(3)),
+    ("sprite_set_alpha_from_sprite", Some(2)),
+    ("sprite_create_from_screen", Some(8)),
+    ("sprite_add_from_screen", Some(7)),
+    ("sprite_create_from_surface", Some(9)),
+    ("sprite_add_from_surface", Some(8)),
+    ("sprite_add", Some(6)),
+    ("sprite_replace", Some(7)),
+    ("sprite_add_sprite", Some(1)),
+    ("sprite_replace_sprite", Some(2)),
+    ("sprite_delete", Some(1)),
+    ("sprite_duplicate", Some(1)),
+    ("sprite_assign", Some(2)),
+    ("sprite_merge", Some(2)),
+    ("sprite_save", Some(3)),
+    ("sprite_save_strip", Some(2)),
+    ("sprite_collision_mask", Some(9)),
+    ("sprite_set_cache_size", Some(2)),
+    ("sprite_set_cache_size_ext", Some(3)),
+    ("background_name", Some(1)),
+    ("background_exists", Some(1)),
+    ("background_get_name", Some(1)),
+    ("background_get_width", Some(1)),
+    ("background_get_height", Some(1)),
+    ("background_set_alpha_from_background", Some(2)),
+    ("background_create_from_screen", Some(6)),
+    ("background_create_from_surface", Some(7)),
+    ("background_create_color", Some(3)),
+    ("background_create_gradient", Some(5)),
+    ("background_add", Some(3)),
+    ("background_replace", Some(4)),
+    ("background_add_background", Some(1)),
+    ("background_replace_background", Some(2)),
+    ("background_delete", Some(1)),
+    ("background_duplicate", Some(1)),
+    ("background_assign", Some(2)),
+    ("background_save", Some(2)),
+    ("sound_name", Some(1)),
+    ("sound_exists", Some(1)),
+    ("sound_get_name", Some(1)),
+    ("sound_get_kind", Some(1)),
+    ("sound_get_preload", Some(1)),
+    ("sound_discard", Some(1)),
+    ("sound_restore", Some(1)),
+    ("sound_add", Some(3)),
+    ("sound_replace", Some(4)),
+    ("sound_delete", Some(1)),
+    ("font_name", Some(1)),
+    ("font_exists", Some(1)),
+    ("font_get_name", Some(1)),
+    ("font_get_fontname", Some(1)),
+    ("font_get_size", Some(1)),
+    ("font_get_bold", Some(1)),
+    ("font_get_italic", Some(1)),
+    ("font_get_first", Some(1)),
+    ("font_get_last", Some(1)),
+    ("font_add", Some(6)),
+    ("font_replace", Some(7)),
+    ("font_add_sprite", Some(4)),
+    ("font_replace_sprite", Some(5)),
+    ("font_delete", Some(1)),
+    ("script_name", Some(1)),
+    ("script_exists", Some(1)),
+    ("script_get_name", Some(1)),
+    ("script_get_text", Some(1)),
+    ("script_execute", None),
+    ("path_name", Some(1)),
+    ("path_exists", Some(1)),
+    ("path_get_name", Some(1)),
+    ("path_get_length", Some(1)),
+    ("path_get_kind", Some(1)),
+    ("path_get_closed", Some(1)),
+    ("path_get_precision", Some(1)),
+    ("path_get_number", Some(1)),
+    ("path_get_point_x", Some(2)),
+    ("path_get_point_y", Some(2)),
+    ("path_get_point_speed", Some(2)),
+    ("path_get_x", Some(2)),
+    ("path_get_y", Some(2)),
+    ("path_get_speed", Some(2)),
+    ("path_set_kind", Some(2)),
+    ("path_set_closed", Some(2)),
+    ("path_set_precision", Some(2)),
+    ("path_add", Some(0)),
+    ("path_duplicate", Some(1)),
+    ("path_assign", Some(2)),
+    ("path_append", Some(2)),
+    ("path_delete", Some(1)),
+    ("path_add_point", Some(4)),
+    ("path_insert_point", Some(5)),
+    ("path_change_point", Some(5)),
+    ("path_delete_point", Some(2)),
+    ("path_clear_points", Some(1)),
+    ("path_reverse", Some(1)),
+    ("path_mirror", Some(1)),
+    ("path_flip", Some(1)),
+    ("path_rotate", Some(2)),
+    ("path_scale", Some(3)),
+    ("path_shift", Some(3)),
+    ("timeline_name", Some(1)),
+    ("timeline_exists", Some(1)),
+    ("timeline_get_name", Some(1)),
+    ("timeline_add", Some(0)),
+    ("timeline_delete", Some(1)),
+    ("timeline_clear", Some(1)),
+    ("timeline_moment_clear", Some(2)),
+    ("timeline_moment_add", Some(3)),
+    ("object_name", Some(1)),
+    ("object_exists", Some(1)),
+    ("object_get_name", Some(1)),
+    ("object_get_sprite", Some(1)),
+    ("object_get_solid", Some(1)),
+    ("object_get_visible", Some(1)),
+    ("object_get_depth", Some(1)),
+    ("object_get_persistent", Some(1)),
+    ("object_get_mask", Some(1)),
+    ("object_get_parent", Some(1)),
+    ("object_is_ancestor", Some(2)),
+    ("object_set_sprite", Some(2)),
+    ("object_set_solid", Some(2)),
+    ("object_set_visible", Some(2)),
+    ("object_set_depth", Some(2)),
+    ("object_set_persistent", Some(2)),
+    ("object_set_mask", Some(2)),
+    ("object_set_parent", Some(2)),
+    ("object_add", Some(0)),
+    ("object_delete", Some(1)),
+    ("object_event_clear", Some(3)),
+    ("object_event_add", Some(4)),
+    ("room_name", Some(1)),
+    ("room_exists", Some(1)),
+    ("room_get_name", Some(1)),
+    ("room_set_width", Some(2)),
+    ("room_set_height", Some(2)),
+    ("room_set_caption", Some(2)),
+    ("room_set_persistent", Some(2)),
+    ("room_set_code", Some(2)),
+    ("room_set_background_color", Some(3)),
+    ("room_set_background", Some(12)),
+    ("room_set_view", Some(16)),
+    ("room_set_view_enabled", Some(2)),
+    ("room_add", Some(0)),
+    ("room_duplicate", Some(1)),
+    ("room_assign", Some(2)),
+    ("room_instance_add", Some(4)),
+    ("room_instance_clear", Some(1)),
+    ("room_tile_add", Some(9)),
+    ("room_tile_add_ext", Some(12)),
+    ("room_tile_clear", Some(1)),
+    ("part_type_create", Some(0)),
+    ("part_type_destroy", Some(1)),
+    ("part_type_exists", Some(1)),
+    ("part_type_clear", Some(1)),
+    ("part_type_shape", Some(2)),
+    ("part_type_sprite", Some(5)),
+    ("part_type_size", Some(5)),
+    ("part_type_scale", Some(3)),
+    ("part_type_life", Some(3)),
+    ("part_type_step", Some(3)),
+    ("part_type_death", Some(3)),
+    ("part_type_speed", Some(5)),
+    ("part_type_direction", Some(5)),
+    ("part_type_orientation", Some(6)),
+    ("part_type_gravity", Some(3)),
+    ("part_type_color_mix", Some(3)),
+    ("part_type_color_rgb", Some(7)),
+    ("part_type_color_hsv", Some(7)),
+    ("part_type_color1", Some(2)),
+    ("part_type_color2", Some(3)),
+    ("part_type_color3", Some(4)),
+    ("part_type_color", Some(4)),
+    ("part_type_alpha1", Some(2)),
+    ("part_type_alpha2", Some(3)),
+    ("part_type_alpha3", Some(4)),
+    ("part_type_alpha", Some(4)),
+    ("part_type_blend", Some(2)),
+    ("part_system_create", Some(0)),
+    ("part_system_destroy", Some(1)),
+    ("part_system_exists", Some(1)),
+    ("part_system_clear", Some(1)),
+    ("part_system_draw_order", Some(2)),
+    ("part_system_depth", Some(2)),
+    ("part_system_position", Some(3)),
+    ("part_system_automatic_update", Some(2)),
+    ("part_system_automatic_draw", Some(2)),
+    ("part_system_update", Some(1)),
+    ("part_system_drawit", Some(1)),
+    ("part_particles_create", Some(5)),
+    ("part_particles_create_color", Some(6)),
+    ("part_particles_clear", Some(1)),
+    ("part_particles_count", Some(1)),
+    ("part_emitter_create", Some(1)),
+    ("part_emitter_destroy", Some(2)),
+    ("part_emitter_destroy_all", Some(1)),
+    ("part_emitter_exists", Some(2)),
+    ("part_emitter_clear", Some(2)),
+    ("part_emitter_region", Some(8)),
+    ("part_emitter_burst", Some(4)),
+    ("part_emitter_stream", Some(4)),
+    ("part_attractor_create", Some(1)),
+    ("part_attractor_destroy", Some(2)),
+    ("part_attractor_destroy_all", Some(1)),
+    ("part_attractor_exists", Some(2)),
+    ("part_attractor_clear", Some(2)),
+    ("part_attractor_position", Some(4)),
+    ("part_attractor_force", Some(6)),
+    ("part_destroyer_create", Some(1)),
+    ("part_destroyer_destroy", Some(2)),
+    ("part_destroyer_destroy_all", Some(1)),
+    ("part_destroyer_exists", Some(2)),
+    ("part_destroyer_clear", Some(2)),
+    ("part_destroyer_region", Some(7)),
+    ("part_deflector_create", Some(1)),
+    ("part_deflector_destroy", Some(2)),
+    ("part_deflector_destroy_all", Some(1)),
+    ("part_deflector_exists", Some(2)),
+    ("part_deflector_clear", Some(2)),
+    ("part_deflector_region", Some(6)),
+    ("part_deflector_kind", Some(3)),
+    ("part_deflector_friction", Some(3)),
+    ("part_changer_create", Some(1)),
+    ("part_changer_destroy", Some(2)),
+    ("part_changer_destroy_all", Some(1)),
+    ("part_changer_exists", Some(2)),
+    ("part_changer_clear", Some(2)),
+    ("part_changer_region", Some(7)),
+    ("part_changer_kind", Some(3)),
+    ("part_changer_types", Some(4)),
+    ("effect_create_below", Some(5)),
+    ("effect_create_above", Some(5)),
+    ("effect_clear", Some(0)),
+    ("ds_set_precision", Some(1)),
+    ("ds_stack_create", Some(0)),
+    ("ds_stack_destroy", Some(1)),
+    ("ds_stack_clear", Some(1)),
+    ("ds_stack_copy", Some(2)),
+    ("ds_stack_size", Some(1)),
+    ("ds_stack_empty", Some(1)),
+    ("ds_stack_push", Some(2)),
+    ("ds_stack_pop", Some(1)),
+    ("ds_stack_top", Some(1)),
+    ("ds_stack_write", Some(1)),
+    ("ds_stack_read", Some(2)),
+    ("ds_queue_create", Some(0)),
+    ("ds_queue_destroy", Some(1)),
+    ("ds_queue_clear", Some(1)),
+    ("ds_queue_copy", Some(2)),
+    ("ds_queue_size", Some(1)),
+    ("ds_queue_empty", Some(1)),
+    ("ds_queue_enqueue", Some(2)),
+    ("ds_queue_dequeue", Some(1)),
+    ("ds_queue_head", Some(1)),
+    ("ds_queue_tail", Some(1)),
+    ("ds_queue_write", Some(1)),
+    ("ds_queue_read", Some(2)),
+    ("ds_list_create", Some(0)),
+    ("ds_list_destroy", Some(1)),
+    ("ds_list_clear", Some(1)),
+    ("ds_list_copy", Some(2)),
+    ("ds_list_size", Some(1)),
+    ("ds_list_empty", Some(1)),
+    ("ds_list_add", Some(2)),
+    ("ds_list_insert", Some(3)),
+    ("ds_list_replace", Some(3)),
+    ("ds_list_delete", Some(2)),
+    ("ds_list_find_index", Some(2)),
+    ("ds_list_find_value", Some(2)),
+    ("ds_list_sort", Some(2)),
+    ("ds_list_shuffle", Some(1)),
+    ("ds_list_write", Some(1)),
+    ("ds_list_read", Some(2)),
+    ("ds_map_create", Some(0)),
+    ("ds_map_destroy", Some(1)),
+    ("ds_map_clear", Some(1)),
+    ("ds_map_copy", Some(2)),
+    ("ds_map_size", Some(1)),
+    ("ds_map_empty", Some(1)),
+    ("ds_map_add", Some(3)),
+    ("ds_map_replace", Some(3)),
+    ("ds_map_delete", Some(2)),
+    ("ds_map_exists", Some(2)),
+    ("ds_map_find_value", Some(2)),
+    ("ds_map_find_previous", Some(2)),
+    ("ds_map_find_next", Some(2)),
+    ("ds_map_find_first", Some(1)),
+    ("ds_map_find_last", Some(1)),
+    ("ds_map_write", Some(1)),
+    ("ds_map_read", Some(2)),
+    ("ds_priority_create", Some(0)),
+    ("ds_priority_destroy", Some(1)),
+    ("ds_priority_clear", Some(1)),
+    ("ds_priority_copy", Some(2)),
+    ("ds_priority_size", Some(1)),
+    ("ds_priority_empty", Some(1)),
+    ("ds_priority_add", Some(3)),
+    ("ds_priority_change_priority", Some(3)),
+    ("ds_priority_find_priority", Some(2)),
+    ("ds_priority_delete_value", Some(2)),
+    ("ds_priority_delete_min", Some(1)),
+    ("ds_priority_find_min", Some(1)),
+    ("ds_priority_delete_max", Some(1)),
+    ("ds_priority_find_max", Some(1)),
+    ("ds_priority_write", Some(1)),
+    ("ds_priority_read", Some(2)),
+    ("ds_grid_create", Some(2)),
+    ("ds_grid_destroy", Some(1)),
+    ("ds_grid_copy", Some(2)),
+    ("ds_grid_resize", Some(3)),
+    ("ds_grid_width", Some(1)),
+    ("ds_grid_height", Some(1)),
+    ("ds_grid_clear", Some(2)),
+    ("ds_grid_set", Some(4)),
+    ("ds_grid_add", Some(4)),
+    ("ds_grid_multiply", Some(4)),
+    ("ds_grid_set_region", Some(6)),
+    ("ds_grid_add_region", Some(6)),
+    ("ds_grid_multiply_region", Some(6)),
+    ("ds_grid_set_disk", Some(5)),
+    ("ds_grid_add_disk", Some(5)),
+    ("ds_grid_multiply_disk", Some(5)),
+    ("ds_grid_set_grid_region", Some(8)),
+    ("ds_grid_add_grid_region", Some(8)),
+    ("ds_grid_multiply_grid_region", Some(8)),
+    ("ds_grid_get", Some(3)),
+    ("ds_grid_get_sum", Some(5)),
+    ("ds_grid_get_max", Some(5)),
+    ("ds_grid_get_min", Some(5)),
+    ("ds_grid_get_mean", Some(5)),
+    ("ds_grid_get_disk_sum", Some(4)),
+    ("ds_grid_get_disk_max", Some(4)),
+    ("ds_grid_get_disk_min", Some(4)),
+    ("ds_grid_get_disk_mean", Some(4)),
+    ("ds_grid_value_exists", Some(6)),
+    ("ds_grid_value_x", Some(6)),
+    ("ds_grid_value_y", Some(6)),
+    ("ds_grid_value_disk_exists", Some(5)),
+    ("ds_grid_value_disk_x", Some(5)),
+    ("ds_grid_value_disk_y", Some(5)),
+    ("ds_grid_shuffle", Some(1)),
+    ("ds_grid_write", Some(1)),
+    ("ds_grid_read", Some(2)),
+    ("sound_play", Some(1)),
+    ("sound_loop", Some(1)),
+    ("sound_stop", Some(1)),
+    ("sound_stop_all", None),
+    ("sound_isplaying", Some(1)),
+    ("sound_volume", Some(2)),
+    ("sound_fade", Some(3)),
+    ("sound_pan", Some(2)),
+    ("sound_background_tempo", Some(1)),
+    ("sound_global_volume", Some(1)),
+    ("sound_set_search_directory", Some(1)),
+    ("sound_effect_set", Some(2)),
+    ("sound_effect_chorus", Some(8)),
+    ("sound_effect_compressor", Some(7)),
+    ("sound_effect_echo", Some(6)),
+    ("sound_effect_flanger", Some(8)),
+    ("sound_effect_gargle", Some(3)),
+    ("sound_effect_equalizer", Some(4)),
+    ("sound_effect_reverb", Some(5)),
+    ("sound_3d_set_sound_position", Some(4)),
+    ("sound_3d_set_sound_velocity", Some(4)),
+    ("sound_3d_set_sound_distance", Some(3)),
+    ("sound_3d_set_sound_cone", Some(7)),
+    ("cd_init", Some(0)),
+    ("cd_present", Some(0)),
+    ("cd_number", Some(0)),
+    ("cd_playing", Some(0)),
+    ("cd_paused", Some(0)),
+    ("cd_track", Some(0)),
+    ("cd_length", Some(0)),
+    ("cd_track_length", Some(1)),
+    ("cd_position", Some(0)),
+    ("cd_track_position", Some(0)),
+    ("cd_play", Some(2)),
+    ("cd_stop", Some(0)),
+    ("cd_pause", Some(0)),
+    ("cd_resume", Some(0)),
+    ("cd_set_position", Some(1)),
+    ("cd_set_track_position", Some(1)),
+    ("cd_open_door", Some(0)),
+    ("cd_close_door", Some(0)),
+    ("MCI_command", Some(1)),
+    ("d3d_start", Some(0)),
+    ("d3d_end", Some(0)),
+    ("d3d_set_perspective", Some(1)),
+    ("d3d_set_hidden", Some(1)),
+    ("d3d_set_depth", Some(1)),
+    ("d3d_set_zwriteenable", Some(1)),
+    ("d3d_set_lighting", Some(1)),
+    ("d3d_set_shading", Some(1)),
+    ("d3d_set_fog", Some(4)),
+    ("d3d_set_culling", Some(1)),
+    ("d3d_primitive_begin", Some(1)),
+    ("d3d_primitive_begin_texture", Some(2)),
+    ("d3d_primitive_end", Some(0)),
+    ("d3d_vertex", Some(3)),
+    ("d3d_vertex_color", Some(5)),
+    ("d3d_vertex_texture", Some(5)),
+    ("d3d_vertex_texture_color", Some(7)),
+    ("d3d_vertex_normal", Some(6)),
+    ("d3d_vertex_normal_color", Some(8)),
+    ("d3d_vertex_normal_texture", Some(8)),
+    ("d3d_vertex_normal_texture_color", Some(10)),
+    ("d3d_draw_block", Some(9)),
+    ("d3d_draw_cylinder", Some(11)),
+    ("d3d_draw_cone", Some(11)),
+    ("d3d_draw_ellipsoid", Some(10)),
+    ("d3d_draw_wall", Some(9)),
+    ("d3d_draw_floor", Some(9)),
+    ("d3d_set_projection", Some(9)),
+    ("d3d_set_projection_ext", Some(13)),
+    ("d3d_set_projection_ortho", Some(5)),
+    ("d3d_set_projection_perspective", Some(5)),
+    ("d3d_transform_set_identity", Some(0)),
+    ("d3d_transform_set_translation", Some(3)),
+    ("d3d_transform_set_scaling", Some(3)),
+    ("d3d_transform_set_rotation_x", Some(1)),
+    ("d3d_transform_set_rotation_y", Some(1)),
+    ("d3d_transform_set_rotation_z", Some(1)),
+    ("d3d_transform_set_rotation_axis", Some(4)),
+    ("d3d_transform_add_translation", Some(3)),
+    ("d3d_transform_add_scaling", Some(3)),
+    ("d3d_transform_add_rotation_x", Some(1)),
+    ("d3d_transform_add_rotation_y", Some(1)),
+    ("d3d_transform_add_rotation_z", Some(1)),
+    ("d3d_transform_add_rotation_axis", Some(4)),
+    ("d3d_transform_stack_clear", Some(0)),
+    ("d3d_transform_stack_empty", Some(0)),
+    ("d3d_transform_stack_push", Some(0)),
+    ("d3d_transform_stack_pop", Some(0)),
+    ("d3d_transform_stack_top", Some(0)),
+    ("d3d_transform_stack_discard", Some(0)),
+    ("d3d_light_define_ambient", Some(1)),
+    ("d3d_light_define_direction", Some(5)),
+    ("d3d_light_define_point", Some(6)),
+    ("d3d_light_enable", Some(2)),
+    ("d3d_model_create", Some(0)),
+    ("d3d_model_destroy", Some(1)),
+    ("d3d_model_clear", Some(1)),
+    ("d3d_model_load", Some(2)),
+    ("d3d_model_save", Some(2)),
+    ("d3d_model_draw", Some(5)),
+    ("d3d_model_primitive_begin", Some(2)),
+    ("d3d_model_primitive_end", Some(1)),
+    ("d3d_model_vertex", Some(4)),
+    ("d3d_model_vertex_color", Some(6)),
+    ("d3d_model_vertex_texture", Some(6)),
+    ("d3d_model_vertex_texture_color", Some(8)),
+    ("d3d_model_vertex_normal", Some(7)),
+    ("d3d_model_vertex_normal_color", Some(9)),
+    ("d3d_model_vertex_normal_texture", Some(9)),
+    ("d3d_model_vertex_normal_texture_color", Some(11)),
+    ("d3d_model_block", Some(9)),
+    ("d3d_model_cylinder", Some(11)),
+    ("d3d_model_cone", Some(11)),
+    ("d3d_model_ellipsoid", Some(10)),
+    ("d3d_model_wall", Some(9)),
+    ("d3d_model_floor", Some(9)),
+];
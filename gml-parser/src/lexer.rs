@@ -1,4 +1,7 @@
-use crate::token::{Keyword, Operator, Separator, Token};
+use crate::{
+    span::Span,
+    token::{Keyword, Operator, Separator, Token},
+};
 
 use std::{
     iter::{Copied, Enumerate, Peekable},
@@ -12,37 +15,71 @@ pub struct Lexer<'a> {
     /// GML source code to return references to.
     src: &'a [u8],
 
-    line_hint: usize,
-
     /// Iterator over the source code as raw bytes.
     iter: Peekable<Enumerate<Copied<slice::Iter<'a, u8>>>>,
+
+    /// Where the last token returned starts and ends.
+    span: Span,
+
+    /// Byte offset of the start of the last token returned.
+    token_start: usize,
+
+    /// How far into the source lines have been counted, and the line number and start offset at that point.
+    scanned: usize,
+    line: usize,
+    line_start: usize,
 }
 
 impl<'a> Lexer<'a> {
     /// Creates a new Lexer over GML source code.
     pub fn new(src: &'a [u8]) -> Self {
-        Lexer { src, line_hint: 1, iter: src.iter().copied().enumerate().peekable() }
+        Lexer {
+            src,
+            iter: src.iter().copied().enumerate().peekable(),
+            span: Span::default(),
+            token_start: 0,
+            scanned: 0,
+            line: 1,
+            line_start: 0,
+        }
     }
 
-    /// Returns the current line number in the source code.
+    /// Returns the line number of the last token returned.
     pub fn line(&self) -> usize {
-        self.line_hint
+        self.span.line
+    }
+
+    /// Returns where the last token returned is in the source code.
+    /// After the end of the code has been reached, this is an empty span at the very end.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the byte offset of the next unread character.
+    fn position(&mut self) -> usize {
+        self.iter.peek().map_or(self.src.len(), |&(i, _)| i)
+    }
+
+    /// Makes a span between two byte offsets, working out which line it starts on.
+    /// Offsets must not go backwards between calls, so lines only ever need counting once.
+    fn make_span(&mut self, start: usize, end: usize) -> Span {
+        for (i, &ch) in self.src[self.scanned..start].iter().enumerate() {
+            if ch == b'\n' {
+                self.line += 1;
+                self.line_start = self.scanned + i + 1;
+            }
+        }
+        self.scanned = start;
+        Span { start, end, line: self.line, column: start - self.line_start + 1 }
     }
 
     /// Fast-forwards the internal iterator to the next token, skipping over whitespace.
-    /// Returns how many lines (LF) were skipped in the process.
-    fn fast_forward(&mut self) -> usize {
-        let mut lines_skipped: usize = 0;
-        loop {
-            match self.iter.peek() {
-                Some(&(_, ch)) if ch <= b' ' => {
-                    if ch == b'\n' {
-                        lines_skipped += 1;
-                    }
-                    self.iter.next();
-                },
-                _ => break lines_skipped,
+    fn fast_forward(&mut self) {
+        while let Some(&(_, ch)) = self.iter.peek() {
+            if ch > b' ' {
+                break
             }
+            self.iter.next();
         }
     }
 }
@@ -51,8 +88,21 @@ impl<'a> Iterator for Lexer<'a> {
     type Item = Token<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        let token = self.read_token();
+        let (start, end) = match token {
+            Some(_) => (self.token_start, self.position()),
+            None => (self.src.len(), self.src.len()),
+        };
+        self.span = self.make_span(start, end);
+        token
+    }
+}
+
+impl<'a> Lexer<'a> {
+    /// Reads the next token, skipping over any whitespace and comments before it.
+    fn read_token(&mut self) -> Option<Token<'a>> {
         // locate next token
-        self.line_hint = self.fast_forward();
+        self.fast_forward();
 
         /// Helper function to reconstruct our byte slices to a string easily.
         /// This is fine since we operate on something that is a &str in a first place,
@@ -63,6 +113,7 @@ impl<'a> Iterator for Lexer<'a> {
         }
 
         let head = *self.iter.peek()?;
+        self.token_start = head.0;
 
         #[allow(clippy::match_overlapping_arm)] // quotes overlap with the catch-all ASCII
         Some(match head.1 {
@@ -288,7 +339,7 @@ impl<'a> Iterator for Lexer<'a> {
                                         },
                                     }
                                 }
-                                return self.next()
+                                return self.read_token()
                             },
 
                             _ => return Some(Token::Operator(op)),
@@ -338,7 +389,7 @@ impl<'a> Iterator for Lexer<'a> {
                                 },
                             }
                         }
-                        return self.next()
+                        return self.read_token()
                     } else if op == Operator::LessThan && ch2 == b'>' {
                         // <> is the same as != (let's call it a diamond)
 
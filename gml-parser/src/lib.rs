@@ -1,3 +1,7 @@
 pub mod ast;
+pub mod cst;
+pub mod kernel;
 pub mod lexer;
+pub mod printer;
+pub mod span;
 pub mod token;
@@ -0,0 +1,707 @@
+//! Turns syntax trees back into GML source code.
+//!
+//! The printer only adds parentheses where they're needed to keep the tree the same, so parsing printed code gives
+//! back an equal tree (spans aside) unless `Config::always_braces` is on. Comments aren't part of the tree, but they
+//! can be given to the printer separately from a `cst::SyntaxTree`.
+
+use crate::{
+    ast::{Expr, ExprKind, AST},
+    cst::Comment,
+    span::Span,
+    token::Operator,
+};
+use std::io::Write;
+
+/// Where opening braces go.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BraceStyle {
+    /// At the end of the line that starts the statement, as in `if (a) {`.
+    SameLine,
+
+    /// On a line of their own, lined up with the closing brace.
+    NextLine,
+}
+
+/// How blocks are delimited.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockStyle {
+    /// `{` and `}`.
+    Braces,
+
+    /// `begin` and `end`.
+    BeginEnd,
+
+    /// Whichever of the two each block used in the source code.
+    /// Falls back to braces if the printer wasn't given the source code, or for blocks that weren't in it.
+    Preserve,
+}
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Written once per level of indentation.
+    pub indent: String,
+
+    /// Written at the end of each line. GameMaker itself uses `\r\n`.
+    pub newline: String,
+
+    pub brace_style: BraceStyle,
+    pub block_style: BlockStyle,
+
+    /// Whether to put spaces around binary operators and after commas.
+    /// Operators which are words, such as `mod`, always have spaces around them.
+    pub operator_spacing: bool,
+
+    /// Whether to put the body of every `if`, loop and so on in a block, even if it's a single statement.
+    /// This adds groups to the tree, so printed code won't parse back to exactly the same tree.
+    pub always_braces: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            indent: "    ".into(),
+            newline: "\r\n".into(),
+            brace_style: BraceStyle::SameLine,
+            block_style: BlockStyle::Preserve,
+            operator_spacing: true,
+            always_braces: false,
+        }
+    }
+}
+
+/// The position an expression is printed in, for deciding what a replacement should look like.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Place {
+    /// Anywhere a value is expected.
+    Value,
+
+    /// The instance on the left of a `.`, as in `owner.field`.
+    Owner,
+
+    /// The target of a `with` statement.
+    WithTarget,
+}
+
+/// Something to print instead of an expression.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Replacement {
+    /// A number, which is wrapped in parentheses if it wouldn't parse where it's going.
+    Real(f64),
+
+    /// Code which is written out exactly as it is. It must be a single operand, such as an identifier.
+    Raw(Vec<u8>),
+}
+
+/// Lets code be changed on its way out without building a new syntax tree, such as to rename things.
+///
+/// The default implementations print everything as it is.
+pub trait Hooks {
+    /// Called before any expression which isn't a statement is printed.
+    /// Returning a replacement prints that instead of the expression and everything in it.
+    fn replace(&mut self, _expr: &Expr, _place: Place) -> Option<Replacement> {
+        None
+    }
+
+    /// Writes a variable or field name, including names in `var` and `globalvar` declarations.
+    fn identifier(&mut self, name: &[u8], out: &mut Vec<u8>) {
+        out.extend_from_slice(name);
+    }
+
+    /// Writes the name of a function or script being called.
+    fn function(&mut self, name: &[u8], out: &mut Vec<u8>) {
+        out.extend_from_slice(name);
+    }
+}
+
+/// Hooks which don't change anything.
+pub struct NoHooks;
+
+impl Hooks for NoHooks {}
+
+pub struct Printer<'s, H = NoHooks> {
+    config: Config,
+    hooks: H,
+    source: Option<&'s [u8]>,
+    comments: Vec<Comment<'s>>,
+    next_comment: usize,
+    output: Vec<u8>,
+    indent: usize,
+}
+
+impl Printer<'_> {
+    pub fn new(config: Config) -> Self {
+        Printer::with_hooks(config, NoHooks)
+    }
+}
+
+impl<'s, H: Hooks> Printer<'s, H> {
+    pub fn with_hooks(config: Config, hooks: H) -> Self {
+        Printer { config, hooks, source: None, comments: Vec::new(), next_comment: 0, output: Vec::new(), indent: 0 }
+    }
+
+    /// Gives the printer the code the trees were parsed from, which `BlockStyle::Preserve` needs.
+    pub fn source(mut self, source: &'s [u8]) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Gives the printer comments to put back in, as returned by `SyntaxTree::comments`.
+    ///
+    /// Comments between statements stay where they were, and comments at the end of a statement's line stay there.
+    /// Comments inside a statement are moved to the lines after it, since there's nowhere to put them otherwise,
+    /// along with any comments at the end of its line so that they stay in order.
+    pub fn comments(mut self, comments: Vec<Comment<'s>>) -> Self {
+        self.comments = comments;
+        self.next_comment = 0;
+        self
+    }
+
+    pub fn hooks(&self) -> &H {
+        &self.hooks
+    }
+
+    pub fn hooks_mut(&mut self) -> &mut H {
+        &mut self.hooks
+    }
+
+    /// Prints a list of statements, each ending with a newline.
+    pub fn print(&mut self, code: &[Expr]) -> Vec<u8> {
+        for statement in code {
+            self.statement_line(statement);
+            self.newline();
+        }
+        while let Some(comment) = self.take_comment(|_| true) {
+            self.write(comment);
+            self.newline();
+        }
+        std::mem::take(&mut self.output)
+    }
+
+    /// Prints a single expression, such as one parsed by `AST::expression`.
+    pub fn print_expression(&mut self, expr: &Expr) -> Vec<u8> {
+        self.expr(expr, Place::Value, false);
+        std::mem::take(&mut self.output)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.output.extend_from_slice(bytes);
+    }
+
+    fn newline(&mut self) {
+        self.output.extend_from_slice(self.config.newline.as_bytes());
+        for _ in 0..self.indent {
+            self.output.extend_from_slice(self.config.indent.as_bytes());
+        }
+    }
+
+    /// Writes a separator such as a comma, followed by a space if operators are spaced.
+    fn separator(&mut self, sep: &[u8]) {
+        self.write(sep);
+        if self.config.operator_spacing {
+            self.write(b" ");
+        }
+    }
+
+    /// Takes the next comment if it matches the predicate.
+    fn take_comment(&mut self, predicate: impl FnOnce(&Comment) -> bool) -> Option<&'s [u8]> {
+        let comment = self.comments.get(self.next_comment).filter(|c| predicate(c))?;
+        self.next_comment += 1;
+        Some(comment.text)
+    }
+
+    /// Writes a statement, followed by a semicolon if it needs one, with any comments before it or on its line.
+    fn statement_line(&mut self, stmt: &Expr) {
+        while let Some(comment) = self.take_comment(|c| c.start < stmt.span.start) {
+            self.write(comment);
+            self.newline();
+        }
+        self.statement(stmt);
+        if matches!(
+            stmt.kind,
+            ExprKind::Binary(_)
+                | ExprKind::Function(_)
+                | ExprKind::Var(_)
+                | ExprKind::GlobalVar(_)
+                | ExprKind::DoUntil(_)
+                | ExprKind::Continue
+                | ExprKind::Break
+                | ExprKind::Exit
+                | ExprKind::Return(_)
+        ) {
+            self.write(b";");
+        }
+        let mut inside = Vec::new();
+        while let Some(comment) = self.take_comment(|c| c.start < stmt.span.end) {
+            inside.push(comment);
+        }
+        // Comments inside the statement come before the ones at the end of its line in the source,
+        // so if there are any, those go on lines of their own after them to stay in order
+        let on_line = inside.is_empty();
+        while let Some(comment) = self.take_comment(|c| c.trailing && c.after == stmt.span.end) {
+            if on_line {
+                self.write(b" ");
+                self.write(comment);
+            } else {
+                inside.push(comment);
+            }
+        }
+        for comment in inside {
+            self.newline();
+            self.write(comment);
+        }
+    }
+
+    fn statement(&mut self, stmt: &Expr) {
+        match &stmt.kind {
+            ExprKind::Binary(binary) if !matches!(binary.op, Operator::Deref | Operator::Index) => {
+                // Assignment
+                self.expr(&binary.left, Place::Value, false);
+                self.operator(binary.op);
+                self.expr(&binary.right, Place::Value, false);
+            },
+            ExprKind::Function(_) => self.expr_kind(stmt),
+            ExprKind::Group(statements) => self.block(statements, Some(stmt.span)),
+            ExprKind::DoUntil(do_until) => {
+                self.write(b"do");
+                let block = self.body(&do_until.body);
+                self.after_body(block);
+                self.write(b"until ");
+                self.condition(&do_until.cond);
+            },
+            ExprKind::For(for_ex) => {
+                self.write(b"for (");
+                self.statement(&for_ex.start);
+                self.separator(b";");
+                self.expr(&for_ex.cond, Place::Value, false);
+                self.separator(b";");
+                self.statement(&for_ex.step);
+                self.write(b")");
+                self.body(&for_ex.body);
+            },
+            ExprKind::If(if_ex) => {
+                self.write(b"if ");
+                self.condition(&if_ex.cond);
+                let block = self.body(&if_ex.body);
+                if let Some(else_body) = &if_ex.else_body {
+                    self.after_body(block);
+                    self.write(b"else");
+                    if matches!(else_body.kind, ExprKind::If(_)) {
+                        self.write(b" ");
+                        self.statement(else_body);
+                    } else {
+                        self.body(else_body);
+                    }
+                }
+            },
+            ExprKind::Repeat(repeat) => {
+                self.write(b"repeat ");
+                self.condition(&repeat.count);
+                self.body(&repeat.body);
+            },
+            ExprKind::Switch(switch) => {
+                self.write(b"switch ");
+                self.condition(&switch.input);
+                self.body(&switch.body);
+            },
+            ExprKind::Var(var) => self.declaration(b"var", &var.vars),
+            ExprKind::GlobalVar(var) => self.declaration(b"globalvar", &var.vars),
+            ExprKind::With(with) => {
+                self.write(b"with (");
+                self.expr(&with.target, Place::WithTarget, false);
+                self.write(b")");
+                self.body(&with.body);
+            },
+            ExprKind::While(while_ex) => {
+                self.write(b"while ");
+                self.condition(&while_ex.cond);
+                self.body(&while_ex.body);
+            },
+            ExprKind::Case(value) => {
+                self.write(b"case ");
+                self.expr(value, Place::Value, false);
+                self.write(b":");
+            },
+            ExprKind::Default => self.write(b"default:"),
+            ExprKind::Continue => self.write(b"continue"),
+            ExprKind::Break => self.write(b"break"),
+            ExprKind::Exit => self.write(b"exit"),
+            ExprKind::Return(value) => {
+                self.write(b"return ");
+                self.expr(value, Place::Value, false);
+            },
+
+            // These can't be statements, but print them anyway rather than losing them
+            _ => self.expr(stmt, Place::Value, false),
+        }
+    }
+
+    /// Writes an expression in parentheses, as after `if` or `while`.
+    fn condition(&mut self, cond: &Expr) {
+        self.write(b"(");
+        self.expr(cond, Place::Value, false);
+        self.write(b")");
+    }
+
+    fn declaration(&mut self, keyword: &[u8], names: &[&[u8]]) {
+        self.write(keyword);
+        for (i, name) in names.iter().enumerate() {
+            if i == 0 {
+                self.write(b" ");
+            } else {
+                self.separator(b",");
+            }
+            self.hooks.identifier(name, &mut self.output);
+        }
+    }
+
+    /// Writes the body of an `if`, loop and so on, returning whether it was written as a block.
+    fn body(&mut self, body: &Expr) -> bool {
+        match &body.kind {
+            ExprKind::Group(statements) => {
+                self.before_block();
+                self.block(statements, Some(body.span));
+                true
+            },
+            _ if self.config.always_braces => {
+                self.before_block();
+                self.block(std::slice::from_ref(body), None);
+                true
+            },
+            _ => {
+                self.indent += 1;
+                self.newline();
+                self.statement_line(body);
+                self.indent -= 1;
+                false
+            },
+        }
+    }
+
+    fn before_block(&mut self) {
+        match self.config.brace_style {
+            BraceStyle::SameLine => self.write(b" "),
+            BraceStyle::NextLine => self.newline(),
+        }
+    }
+
+    /// Writes whatever goes between a body and a following `else` or `until`.
+    fn after_body(&mut self, was_block: bool) {
+        if was_block && self.config.brace_style == BraceStyle::SameLine {
+            self.write(b" ");
+        } else {
+            self.newline();
+        }
+    }
+
+    fn block(&mut self, statements: &[Expr], span: Option<Span>) {
+        let begin_end = match self.config.block_style {
+            BlockStyle::Braces => false,
+            BlockStyle::BeginEnd => true,
+            BlockStyle::Preserve => match (self.source, span) {
+                (Some(source), Some(span)) => source.get(span.start..).is_some_and(|s| s.starts_with(b"begin")),
+                _ => false,
+            },
+        };
+        self.write(if begin_end { b"begin" } else { b"{" });
+
+        // Statements after a case label are indented one level further than the label
+        self.indent += 1;
+        let mut in_case = false;
+        for statement in statements {
+            let is_label = matches!(statement.kind, ExprKind::Case(_) | ExprKind::Default);
+            if is_label && in_case {
+                self.indent -= 1;
+            }
+            self.newline();
+            self.statement_line(statement);
+            if is_label {
+                self.indent += 1;
+                in_case = true;
+            }
+        }
+        if in_case {
+            self.indent -= 1;
+        }
+        let end = span.map_or(0, |s| s.end);
+        let mut empty = statements.is_empty();
+        while let Some(comment) = self.take_comment(|c| c.start < end) {
+            self.newline();
+            self.write(comment);
+            empty = false;
+        }
+        self.indent -= 1;
+
+        if !empty {
+            self.newline();
+        } else if begin_end {
+            self.write(b" ");
+        }
+        self.write(if begin_end { b"end" } else { b"}" });
+    }
+
+    fn operator(&mut self, op: Operator) {
+        let word = matches!(op, Operator::Modulo | Operator::IntDivide);
+        let spaced = word || self.config.operator_spacing;
+        if spaced {
+            self.write(b" ");
+        }
+        let _ = write!(self.output, "{}", op);
+        if spaced {
+            self.write(b" ");
+        }
+    }
+
+    /// Writes an expression, in parentheses if `wrap` is set, unless the hooks replace it.
+    fn expr(&mut self, expr: &Expr, place: Place, wrap: bool) {
+        match self.hooks.replace(expr, place) {
+            Some(Replacement::Real(real)) => {
+                if place == Place::Owner {
+                    // `1.x` would be read as the number `1.` followed by `x`
+                    let _ = write!(self.output, "({})", real);
+                } else {
+                    let _ = write!(self.output, "{}", real);
+                }
+            },
+            Some(Replacement::Raw(code)) => self.write(&code),
+            None if wrap => {
+                self.write(b"(");
+                self.expr_kind(expr);
+                self.write(b")");
+            },
+            None => self.expr_kind(expr),
+        }
+    }
+
+    fn expr_kind(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::LiteralIdentifier(name) => self.hooks.identifier(name, &mut self.output),
+            ExprKind::LiteralReal(real) => {
+                let _ = write!(self.output, "{}", real);
+            },
+            ExprKind::LiteralString(string) => {
+                // There's no escaping in GML, so strings with double quotes in them must have been single-quoted
+                let quote: &[u8] = if string.contains(&b'"') { b"'" } else { b"\"" };
+                self.write(quote);
+                self.write(string);
+                self.write(quote);
+            },
+            ExprKind::Unary(unary) => {
+                let _ = write!(self.output, "{}", unary.op);
+                self.expr(&unary.child, Place::Value, is_operation(&unary.child));
+            },
+            ExprKind::Binary(binary) => match binary.op {
+                Operator::Index => {
+                    self.expr(&binary.left, Place::Value, !is_operand(&binary.left));
+                    self.write(b"[");
+                    if let ExprKind::Group(dimensions) = &binary.right.kind {
+                        for (i, dimension) in dimensions.iter().enumerate() {
+                            if i != 0 {
+                                self.separator(b",");
+                            }
+                            self.expr(dimension, Place::Value, false);
+                        }
+                    }
+                    self.write(b"]");
+                },
+                Operator::Deref => {
+                    self.expr(&binary.left, Place::Owner, !is_operand(&binary.left));
+                    self.write(b".");
+                    match &binary.right.kind {
+                        ExprKind::LiteralIdentifier(name) => self.hooks.identifier(name, &mut self.output),
+                        _ => self.expr_kind(&binary.right),
+                    }
+                },
+                op => {
+                    // Operators of the same precedence are read left to right,
+                    // so the right side needs wrapping if it's another of them
+                    let precedence = AST::get_op_precedence(&op);
+                    let wrap = |side: &Expr, right: bool| match &side.kind {
+                        ExprKind::Binary(b) => match AST::get_op_precedence(&b.op) {
+                            Some(p) => p < precedence.unwrap_or(0) || (right && Some(p) == precedence),
+                            None => false,
+                        },
+                        _ => false,
+                    };
+                    self.expr(&binary.left, Place::Value, wrap(&binary.left, false));
+                    self.operator(op);
+                    self.expr(&binary.right, Place::Value, wrap(&binary.right, true));
+                },
+            },
+            ExprKind::Function(call) => {
+                self.hooks.function(call.name, &mut self.output);
+                self.write(b"(");
+                for (i, param) in call.params.iter().enumerate() {
+                    if i != 0 {
+                        self.separator(b",");
+                    }
+                    self.expr(param, Place::Value, false);
+                }
+                self.write(b")");
+            },
+            _ => self.statement(expr),
+        }
+    }
+}
+
+/// Prints a list of statements with no hooks.
+pub fn print(code: &[Expr], config: Config) -> Vec<u8> {
+    Printer::new(config).print(code)
+}
+
+/// Whether an expression is a binary operation other than indexing or a dereference.
+fn is_operation(expr: &Expr) -> bool {
+    matches!(&expr.kind, ExprKind::Binary(b) if !matches!(b.op, Operator::Deref | Operator::Index))
+}
+
+/// Whether an expression can go before `.` or `[` without parentheses.
+fn is_operand(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::LiteralIdentifier(_) | ExprKind::Function(_) => true,
+        ExprKind::Binary(b) => matches!(b.op, Operator::Deref | Operator::Index),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cst::SyntaxTree;
+
+    /// Parses, prints and parses again, checking the tree didn't change. Returns the printed code.
+    fn round_trip(input: &str, config: Config) -> String {
+        let ast = AST::new(input.as_bytes()).expect("input should parse");
+        let printed = Printer::new(config).source(input.as_bytes()).print(&ast);
+        let printed = String::from_utf8(printed).unwrap();
+        match AST::new(printed.as_bytes()) {
+            Ok(reparsed) => assert_eq!(ast, reparsed, "tree changed after printing:\n{}", printed),
+            Err(e) => panic!("printed code didn't parse: {}\n{}", e, printed),
+        }
+        printed
+    }
+
+    fn lf() -> Config {
+        Config { newline: "\n".into(), ..Config::default() }
+    }
+
+    #[test]
+    fn statements() {
+        assert_eq!(
+            round_trip("a=1 b+=2;;c[1,2]=d.e f(1,2,3) var x,y globalvar z exit", lf()),
+            "a = 1;\nb += 2;\nc[1, 2] = d.e;\nf(1, 2, 3);\nvar x, y;\nglobalvar z;\nexit;\n",
+        );
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(
+            round_trip("a = (1 + 2) * 3 - (4 - 5) + 6 / (7 div 8) mod 9", lf()),
+            "a = (1 + 2) * 3 - (4 - 5) + 6 / (7 div 8) mod 9;\n",
+        );
+        assert_eq!(round_trip("a = ((1 + 2) + 3) && !(b || c) == -(d)", lf()), "a = 1 + 2 + 3 && !(b || c) == -d;\n");
+        assert_eq!(round_trip("a = (b + c).d + (1).e + (-f)[0]", lf()), "a = (b + c).d + (1).e + (-f)[0];\n");
+        round_trip("a = 1 < 2 == 3 > 4 ^^ 5 << 6 >> 7 & 8 | 9 ^ ~10", lf());
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(round_trip("a = 'say \"hi\"' + \"it's\"", lf()), "a = 'say \"hi\"' + \"it's\";\n");
+    }
+
+    #[test]
+    fn control_flow() {
+        let input = "if a b = 1 else if c {d = 2} else begin e = 3 end \
+                     while x {y()} do z += 1 until z > 10; repeat 3 {} \
+                     for (i = 0; i < 10; i += 1) with other {instance_destroy() break continue} \
+                     switch a { case 1: case 2: b = 3 break default: return 4 }";
+        assert_eq!(
+            round_trip(input, lf()),
+            "if (a)\n    b = 1;\nelse if (c) {\n    d = 2;\n} else begin\n    e = 3;\nend\n\
+             while (x) {\n    y();\n}\n\
+             do\n    z += 1;\nuntil (z > 10);\n\
+             repeat (3) {}\n\
+             for (i = 0; i < 10; i += 1)\n    with (other) {\n        instance_destroy();\n        break;\n        \
+             continue;\n    }\n\
+             switch (a) {\n    case 1:\n    case 2:\n        b = 3;\n        break;\n    default:\n        \
+             return 4;\n}\n",
+        );
+    }
+
+    #[test]
+    fn configs() {
+        let input = "if a {b = 1 c = 2} else begin d = 3 end do {e = f * 2} until e";
+        let configs = [
+            Config { brace_style: BraceStyle::NextLine, ..lf() },
+            Config { block_style: BlockStyle::Braces, ..lf() },
+            Config { block_style: BlockStyle::BeginEnd, ..lf() },
+            Config { operator_spacing: false, indent: "\t".into(), ..lf() },
+            Config::default(),
+        ];
+        for config in configs {
+            round_trip(input, config);
+        }
+
+        assert_eq!(
+            round_trip(input, Config { brace_style: BraceStyle::NextLine, block_style: BlockStyle::BeginEnd, ..lf() }),
+            "if (a)\nbegin\n    b = 1;\n    c = 2;\nend\nelse\nbegin\n    d = 3;\nend\n\
+             do\nbegin\n    e = f * 2;\nend\nuntil (e);\n",
+        );
+        assert_eq!(
+            round_trip(input, Config { operator_spacing: false, indent: "\t".into(), ..lf() }),
+            "if (a) {\n\tb=1;\n\tc=2;\n} else begin\n\td=3;\nend\ndo {\n\te=f*2;\n} until (e);\n",
+        );
+    }
+
+    #[test]
+    fn always_braces() {
+        let ast = AST::new(b"if a b = 1 else c()").unwrap();
+        let printed = print(&ast, Config { always_braces: true, ..lf() });
+        assert_eq!(printed, b"if (a) {\n    b = 1;\n} else {\n    c();\n}\n");
+    }
+
+    #[test]
+    fn hooks() {
+        struct Renamer;
+        impl Hooks for Renamer {
+            fn replace(&mut self, expr: &Expr, place: Place) -> Option<Replacement> {
+                match (&expr.kind, place) {
+                    (ExprKind::LiteralIdentifier(b"two"), _) => Some(Replacement::Real(2.0)),
+                    (ExprKind::LiteralIdentifier(b"me"), Place::Owner) => Some(Replacement::Raw(b"self".to_vec())),
+                    _ => None,
+                }
+            }
+
+            fn identifier(&mut self, name: &[u8], out: &mut Vec<u8>) {
+                out.extend_from_slice(b"v_");
+                out.extend_from_slice(name);
+            }
+
+            fn function(&mut self, name: &[u8], out: &mut Vec<u8>) {
+                out.extend_from_slice(b"f_");
+                out.extend_from_slice(name);
+            }
+        }
+
+        let ast = AST::new(b"var a; a = me.b + two.c * two f(a)").unwrap();
+        let printed = Printer::with_hooks(lf(), Renamer).print(&ast);
+        assert_eq!(String::from_utf8(printed).unwrap(), "var v_a;\nv_a = self.v_b + (2).v_c * 2;\nf_f(v_a);\n");
+    }
+
+    #[test]
+    fn comments() {
+        let input = "// header\na=1 // one\nif a {/* inside */ b = 2 /* two */ c = (3 + // three\n4) // four\n\
+                     // last\n}\nswitch a {case 1: // case\nexit}/* end */";
+        let tree = SyntaxTree::parse(input.as_bytes()).unwrap();
+        let printed = Printer::new(lf()).source(input.as_bytes()).comments(tree.comments()).print(&tree.to_ast());
+        assert_eq!(
+            String::from_utf8(printed).unwrap(),
+            "// header\na = 1; // one\nif (a) {\n    /* inside */\n    b = 2; /* two */\n    c = 3 + 4;\n    \
+             // three\n    // four\n    // last\n}\nswitch (a) {\n    case 1: // case\n        exit;\n} /* end */\n",
+        );
+    }
+
+    #[test]
+    fn expression() {
+        let expr = AST::expression(b"a+b*(c-d)").unwrap();
+        assert_eq!(Printer::new(Config::default()).print_expression(&expr), b"a + b * (c - d)");
+    }
+}
@@ -0,0 +1,43 @@
+use std::{fmt, ops::Range};
+
+/// A range of bytes in GML source code, along with the line and column it starts on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Span {
+    /// Byte offset of the first byte in the span.
+    pub start: usize,
+
+    /// Byte offset one past the last byte in the span.
+    pub end: usize,
+
+    /// The line `start` is on, counting from 1.
+    pub line: usize,
+
+    /// The column `start` is at, counting from 1.
+    /// This counts bytes rather than characters, since GML isn't necessarily UTF-8.
+    pub column: usize,
+}
+
+impl Span {
+    /// Returns a span covering both this span and `other`, and everything in between.
+    pub fn to(self, other: Span) -> Span {
+        let (first, last) = if other.start < self.start { (other, self) } else { (self, other) };
+        Span { end: first.end.max(last.end), ..first }
+    }
+
+    /// Returns the byte range of the span, for indexing into the source code.
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+impl Default for Span {
+    fn default() -> Self {
+        Span { start: 0, end: 0, line: 1, column: 1 }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}